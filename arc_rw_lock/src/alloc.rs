@@ -1 +1,40 @@
+//! The allocator API, or a minimal stand-in for it on stable toolchains.
 
+#[cfg(not(feature = "stable"))]
+pub use std::alloc::{Allocator, Global};
+
+#[cfg(feature = "stable")]
+pub use stable::{Allocator, Global};
+
+#[cfg(feature = "stable")]
+mod stable {
+    use std::{alloc::Layout, ptr::NonNull};
+
+    /// A stand-in for `std::alloc::Allocator` covering the operations used by this crate.
+    ///
+    /// # Safety
+    ///
+    /// See `std::alloc::Allocator`.
+    pub unsafe trait Allocator {
+        /// Deallocates the memory referenced by `ptr`.
+        ///
+        /// # Safety
+        ///
+        /// `ptr` must denote a block of memory currently allocated via this allocator
+        /// and `layout` must fit that block of memory.
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+    }
+
+    /// The global memory allocator.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Global;
+
+    unsafe impl Allocator for Global {
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if layout.size() != 0 {
+                // SAFETY: User-upheld invariant.
+                unsafe { std::alloc::dealloc(ptr.as_ptr(), layout) }
+            }
+        }
+    }
+}
@@ -3,9 +3,11 @@ pub(crate) use inner::InnerArc;
 
 mod mapped {
     use super::InnerArc;
-    use crate::lock::MappedRwLock;
-    use std::{
+    use crate::{
         alloc::{Allocator, Global},
+        lock::MappedRwLock,
+    };
+    use std::{
         borrow::{Borrow, BorrowMut},
         convert::{AsMut, AsRef},
         mem::needs_drop,
@@ -175,9 +177,11 @@ pub use mapped::{ArcMappedRwLock, UniqueArcMappedRwLock};
 
 mod reader {
     use super::InnerArc;
-    use crate::lock::ReaderLock;
-    use std::{
+    use crate::{
         alloc::{Allocator, Global},
+        lock::ReaderLock,
+    };
+    use std::{
         borrow::Borrow,
         convert::AsRef,
         mem::needs_drop,
@@ -6,6 +6,8 @@ use std::{
 };
 
 use crate::lock::InnerRwLock;
+#[cfg(test)]
+use crate::lock::PoisonLock;
 
 #[repr(C)]
 pub(crate) struct InnerArc<T: ?Sized> {
@@ -27,23 +29,36 @@ impl<T: ?Sized> InnerArc<T> {
     };
     const UNIQUE_COUNTER_MAX: usize = Self::SHARED_COUNTER_MAX << (usize::BITS / 2);
 
+    /// Returns the allocation which holds `lock` and its layout.
+    ///
+    /// # Safety
+    ///
+    /// `lock` must point to the `lock` field of an `InnerArc<T>` which has been
+    /// allocated and not yet deallocated, with the metadata it was allocated with.
+    /// The pointee is never read, so another thread may hold a guard on the data.
+    /// With the `stable` feature, where `Layout::for_value_raw` is not available,
+    /// the layout is read through a shared reference to the lock instead,
+    /// so no mutable reference to the data may be live during the call.
     pub(crate) const unsafe fn from_lock(lock: NonNull<InnerRwLock<T>>) -> (NonNull<Self>, Layout) {
-        let (layout, offset) = match Layout::new::<AtomicUsize>()
-            // SAFETY: User-upheld invariant.
-            .extend(unsafe { Layout::for_value_raw(lock.as_ptr()) })
-        {
+        // SAFETY: The caller guarantees that `lock` carries the metadata of a live
+        //         allocation, which is all `for_value_raw` reads.
+        #[cfg(not(feature = "stable"))]
+        let lock_layout = unsafe { Layout::for_value_raw(lock.as_ptr()) };
+        // SAFETY: The caller guarantees that `lock` points to a live lock
+        //         which is not mutably borrowed.
+        #[cfg(feature = "stable")]
+        let lock_layout = Layout::for_value(unsafe { lock.as_ref() });
+        let (layout, offset) = match Layout::new::<AtomicUsize>().extend(lock_layout) {
             Ok(res) => res,
             // SAFETY: User-upheld invariant.
             Err(_) => unsafe { hint::unreachable_unchecked() },
         };
-        let (ptr, metadata) = lock.to_raw_parts();
-        // SAFETY: By construction, `ptr.byte_sub(offset)` calculates the
+        // SAFETY: By construction, `lock.byte_sub(offset)` calculates the
         //         address of the underlying `InnerArc`, which has already
         //         been successfully allocated.
-        (
-            NonNull::from_raw_parts(unsafe { ptr.byte_sub(offset) }, metadata),
-            layout,
-        )
+        let ptr = unsafe { lock.byte_sub(offset) }.as_ptr() as *mut Self;
+        // SAFETY: `ptr` is derived from a non-null pointer to a live allocation.
+        (unsafe { NonNull::new_unchecked(ptr) }, layout)
     }
 
     pub(crate) unsafe fn decrement_shared_counter(this: NonNull<Self>, order: Ordering) -> bool {
@@ -66,3 +81,20 @@ impl<T: ?Sized> InnerArc<T> {
             == Self::UNIQUE_COUNTER_MAX
     }
 }
+
+#[cfg(test)]
+impl<T> InnerArc<[T]> {
+    /// Allocates `data` with a single unique handle registered and
+    /// returns its lock, to be wrapped into that handle.
+    pub(crate) fn new_unique<const N: usize>(data: [T; N]) -> NonNull<InnerRwLock<[T]>> {
+        let inner: Box<InnerArc<[T]>> = Box::new(InnerArc {
+            counter: AtomicUsize::new(Self::UNIQUE_COUNTER_ONE),
+            lock: InnerRwLock {
+                poison_lock: PoisonLock::new(),
+                data,
+            },
+        });
+        // SAFETY: `Box::into_raw` returns a non-null pointer to a live `InnerArc`.
+        unsafe { NonNull::new_unchecked(&raw mut (*Box::into_raw(inner)).lock) }
+    }
+}
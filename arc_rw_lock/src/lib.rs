@@ -1,15 +1,20 @@
 #![allow(dead_code)]
-#![feature(allocator_api, ptr_metadata, layout_for_ptr, sync_nonpoison)]
+#![cfg_attr(
+    not(feature = "stable"),
+    feature(allocator_api, layout_for_ptr, sync_nonpoison)
+)]
 
 mod alloc;
 mod arc;
 pub use arc::{ArcMappedRwLock, ArcReaderLock, UniqueArcMappedRwLock};
 mod lock;
-pub use lock::{MappedRwLock, MappedRwLockGuard, ReaderLock, ReaderLockGuard};
+pub use lock::{MappedRwLock, MappedRwLockGuard, ReaderLock, ReaderLockGuard, WouldBlock};
 mod slice;
+#[cfg(feature = "rayon")]
+pub use slice::ParIterMut;
 pub use slice::{
-    ArcElementRwLock, ArcSliceReaderLock, ArcSliceRwLock, ElementRwLock, ElementRwLockGuard,
-    SliceReaderLock, SliceReaderLockGuard, SliceRwLock, UniqueArcElementRwLock,
+    ArcElementRwLock, ArcSliceReaderLock, ArcSliceRwLock, ElementRwLock, ElementRwLockGuard, Iter,
+    IterMut, SliceReaderLock, SliceReaderLockGuard, SliceRwLock, UniqueArcElementRwLock,
     UniqueArcSliceRwLock,
 };
 mod unique_arc;
@@ -1,15 +1,25 @@
 mod inner;
 pub(crate) use inner::InnerRwLock;
+#[cfg(test)]
+pub(crate) use inner::PoisonLock;
+
+#[cfg(not(feature = "stable"))]
+pub use std::sync::nonpoison::WouldBlock;
+
+/// The lock could not be acquired at this time because the operation would otherwise block.
+#[cfg(feature = "stable")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WouldBlock;
 
 mod mapped {
     use crate::lock::InnerRwLock;
 
-    use super::inner::PoisonLock;
+    use super::{WouldBlock, inner::PoisonLock};
     use std::{
         marker::PhantomData,
+        mem::ManuallyDrop,
         ops::{Deref, DerefMut},
         ptr::NonNull,
-        sync::nonpoison::WouldBlock,
         thread::panicking,
     };
 
@@ -26,6 +36,15 @@ mod mapped {
             unsafe { self.subfield.as_ref() }
         }
 
+        /// Returns the version of the whole data, which changes every time a write guard
+        /// to any of its subfields is dropped after having been dereferenced mutably.
+        ///
+        /// Equal versions guarantee that the data has not been written to in between.
+        pub fn version(&self) -> usize {
+            // SAFETY: By construction, `self.inner` points to live and valid data.
+            unsafe { (*self.inner.as_ptr()).poison_lock.version() }
+        }
+
         pub fn write(&mut self) -> MappedRwLockGuard<'_, T> {
             // SAFETY: By construction, `self.inner` points to live and valid data.
             let poison_lock = unsafe { &(*self.inner.as_ptr()).poison_lock };
@@ -35,6 +54,7 @@ mod mapped {
                 // SAFETY: - By construction, `self.subfield` points to live and valid data.
                 //         - Aliasing rules are enforced via synchronization.
                 data: unsafe { self.subfield.as_mut() },
+                written: false,
                 phantom: PhantomData,
             }
         }
@@ -48,6 +68,7 @@ mod mapped {
                     // SAFETY: - By construction, `self.subfield` points to live and valid data.
                     //         - Aliasing rules are enforced via synchronization.
                     data: unsafe { self.subfield.as_mut() },
+                    written: false,
                     phantom: PhantomData,
                 })
             } else {
@@ -61,12 +82,38 @@ mod mapped {
     pub struct MappedRwLockGuard<'a, T: ?Sized> {
         lock: &'a PoisonLock,
         data: &'a mut T,
+        /// Whether the data has been dereferenced mutably, so that guards
+        /// which only read leave the version alone.
+        written: bool,
         /// For opting-out of `Send`
         phantom: PhantomData<*const T>,
     }
 
+    impl<'a, T: ?Sized> MappedRwLockGuard<'a, T> {
+        /// Narrows the guard to a part of its data, keeping the lock held.
+        ///
+        /// `f` should not panic, or the lock is never released.
+        pub(crate) fn map<V: ?Sized>(
+            self,
+            f: impl FnOnce(&'a mut T) -> &'a mut V,
+        ) -> MappedRwLockGuard<'a, V> {
+            let this = ManuallyDrop::new(self);
+            // SAFETY: `this` is never dropped, so `data` is moved out of it only once.
+            let data = unsafe { (&raw const this.data).read() };
+            MappedRwLockGuard {
+                lock: this.lock,
+                data: f(data),
+                written: this.written,
+                phantom: PhantomData,
+            }
+        }
+    }
+
     impl<'a, T: ?Sized> Drop for MappedRwLockGuard<'a, T> {
         fn drop(&mut self) {
+            if self.written {
+                self.lock.bump_version();
+            }
             // SAFETY: The existance of this guard guarantees that the counter is non-zero.
             unsafe {
                 self.lock.lock.drop_writer_unchecked();
@@ -87,6 +134,7 @@ mod mapped {
 
     impl<'a, T: ?Sized> DerefMut for MappedRwLockGuard<'a, T> {
         fn deref_mut(&mut self) -> &mut Self::Target {
+            self.written = true;
             self.data
         }
     }
@@ -107,6 +155,13 @@ mod read {
     pub struct ReaderLock<T: ?Sized>(pub(crate) NonNull<InnerRwLock<T>>);
 
     impl<T: ?Sized> ReaderLock<T> {
+        /// Returns the version of the data, which changes every time a write guard
+        /// to any of its subfields is dropped after having been dereferenced mutably.
+        pub fn version(&self) -> usize {
+            // SAFETY: By construction, `self.0` points to live and valid data.
+            unsafe { (*self.0.as_ptr()).poison_lock.version() }
+        }
+
         pub fn read(&self) -> LockResult<ReaderLockGuard<'_, T>> {
             // SAFETY: By construction, `self.0` points to live and valid data.
             let lock = unsafe { &(*self.0.as_ptr()).poison_lock };
@@ -116,9 +171,9 @@ mod read {
                 phantom: PhantomData,
             };
             if lock.is_poisoned() {
-                Ok(guard)
-            } else {
                 Err(PoisonError::new(guard))
+            } else {
+                Ok(guard)
             }
         }
 
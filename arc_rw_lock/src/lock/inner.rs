@@ -1,6 +1,6 @@
 use std::{
     hint, process,
-    sync::atomic::{self, AtomicBool, AtomicU32, Ordering},
+    sync::atomic::{self, AtomicBool, AtomicU32, AtomicUsize, Ordering},
 };
 
 use crate::unlikely;
@@ -255,6 +255,7 @@ impl Lock {
 pub(crate) struct PoisonLock {
     pub(crate) lock: Lock,
     poison: AtomicBool,
+    version: AtomicUsize,
 }
 
 impl PoisonLock {
@@ -263,6 +264,7 @@ impl PoisonLock {
         Self {
             lock: Lock::new(),
             poison: AtomicBool::new(false),
+            version: AtomicUsize::new(0),
         }
     }
 
@@ -276,6 +278,17 @@ impl PoisonLock {
         self.poison.store(true, Ordering::Release);
     }
 
+    /// Returns the number of times write access has been released after the data
+    /// was borrowed mutably, modulo `usize::MAX + 1`.
+    pub(crate) fn version(&self) -> usize {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Records that the data may have been modified.
+    pub(crate) fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
     /// Clears poison from the lock.
     pub(crate) fn remove_poison(&self) {
         self.poison.store(false, Ordering::Release);
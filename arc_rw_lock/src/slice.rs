@@ -1,20 +1,21 @@
 use crate::{
     ArcMappedRwLock, ArcReaderLock, MappedRwLock, MappedRwLockGuard, ReaderLock, ReaderLockGuard,
     UniqueArcMappedRwLock,
-    arc::InnerArc,
-    slice::{iter::IterMut, iter_mut::Iter},
-};
-use std::{
     alloc::{Allocator, Global},
-    mem,
-    ops::Range,
-    process,
-    ptr::NonNull,
-    sync::atomic::Ordering,
+    arc::InnerArc,
+    lock::InnerRwLock,
+    unlikely,
 };
+use std::{mem, ops::Range, process, ptr::NonNull, sync::atomic::Ordering};
 
 mod iter;
+pub use iter::Iter;
 mod iter_mut;
+pub use iter_mut::IterMut;
+#[cfg(feature = "rayon")]
+mod par_iter;
+#[cfg(feature = "rayon")]
+pub use par_iter::ParIterMut;
 
 pub type ElementRwLock<T> = MappedRwLock<T, [T]>;
 
@@ -39,12 +40,17 @@ pub type ArcSliceReaderLock<T, A = Global> = ArcReaderLock<[T], A>;
 pub type UniqueArcSliceRwLock<T, A = Global> = UniqueArcMappedRwLock<[T], [T], A>;
 
 impl<T> ElementRwLock<T> {
+    /// Overwrites the element with `value` under a single write lock.
+    pub fn set(&mut self, value: T) {
+        *self.write() = value;
+    }
+
     pub const fn element_offset(&self) -> usize {
         // SAFETY: By construction, `inner` points to live and valid data.
-        let (ptr_whole, _) = unsafe { &raw mut (*self.inner.as_ptr()).data }.to_raw_parts();
+        let ptr_whole = unsafe { &raw mut (*self.inner.as_ptr()).data }.cast::<()>();
         // SAFETY: The offset of `data` guarantees `ptr` is non-null.
         let ptr_whole = unsafe { NonNull::new_unchecked(ptr_whole) }.cast::<T>();
-        let (ptr, _) = self.subfield.to_raw_parts();
+        let ptr = self.subfield.cast::<()>();
         let ptr = ptr.cast::<T>();
         // SAFETY: By construction, `ptr` points to a subslice of `ptr_whole`.
         unsafe { ptr.offset_from_unsigned(ptr_whole) }
@@ -52,12 +58,26 @@ impl<T> ElementRwLock<T> {
 }
 
 impl<T> SliceRwLock<T> {
+    /// Overwrites every element with a clone of `value` under a single write lock,
+    /// e.g. to zero a buffer of forces before they are accumulated.
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        self.write().fill(value);
+    }
+
+    /// Overwrites every element with the values returned by `f` under a single write lock.
+    pub fn fill_with<F: FnMut() -> T>(&mut self, f: F) {
+        self.write().fill_with(f);
+    }
+
     pub const fn subslice_range(&self) -> Range<usize> {
         // SAFETY: By construction, `inner` points to live and valid data.
-        let (ptr_whole, _) = unsafe { &raw mut (*self.inner.as_ptr()).data }.to_raw_parts();
+        let ptr_whole = unsafe { &raw mut (*self.inner.as_ptr()).data }.cast::<()>();
         // SAFETY: The offset of `data` guarantees `ptr` is non-null.
         let ptr_whole = unsafe { NonNull::new_unchecked(ptr_whole) }.cast::<T>();
-        let (ptr, len) = self.subfield.to_raw_parts();
+        let (ptr, len) = (self.subfield.cast::<()>(), self.subfield.len());
         let ptr = ptr.cast::<T>();
         // SAFETY: By construction, `ptr` points to a subslice of `ptr_whole`.
         let start = unsafe { ptr.offset_from_unsigned(ptr_whole) };
@@ -95,4 +115,316 @@ impl<T, A: Allocator> UniqueArcSliceRwLock<T, A> {
         mem::forget(self);
         IterMut { lock, allocator }
     }
+
+    /// Returns the element at `index`, or `None` if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.lock.read().get(index)
+    }
+
+    /// Locks the slice for writing and returns a guard over the element at `index`,
+    /// or `None` if `index` is out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<ElementRwLockGuard<'_, T>> {
+        if index >= self.lock.subfield.len() {
+            return None;
+        }
+        // SAFETY: Checked above that `index` is within the subslice.
+        Some(
+            self.lock
+                .write()
+                .map(|slice| unsafe { slice.get_unchecked_mut(index) }),
+        )
+    }
+
+    /// Converts the lock into a lock over the element at `index`,
+    /// or `None` if `index` is out of bounds.
+    pub fn into_element(self, index: usize) -> Option<UniqueArcElementRwLock<T, A>> {
+        let (ptr, len) = (self.lock.subfield.cast::<()>(), self.lock.subfield.len());
+        if index >= len {
+            return None;
+        }
+        // SAFETY: All fields of `self` are forgotten immediately after
+        //         reading them out of the pointers.
+        let lock = unsafe { (&raw const self.lock).read() };
+        let allocator = unsafe { (&raw const self.allocator).read() };
+        mem::forget(self);
+        Some(UniqueArcElementRwLock {
+            lock: MappedRwLock {
+                inner: lock.inner,
+                // SAFETY: Checked above that `index` is within the subslice.
+                subfield: unsafe { ptr.cast::<T>().add(index) },
+            },
+            allocator,
+        })
+    }
+
+    /// Splits the lock into a lock over the first element and
+    /// a lock over the rest of the slice, or returns `None` if
+    /// the slice is empty.
+    pub fn split_first(self) -> Option<(UniqueArcElementRwLock<T, A>, Self)>
+    where
+        A: Clone,
+    {
+        let (ptr, len) = (self.lock.subfield.cast::<()>(), self.lock.subfield.len());
+        if len == 0 {
+            return None;
+        }
+        let ptr = ptr.cast::<T>();
+        let (inner, allocator) = self.increment_for_split();
+        Some((
+            UniqueArcElementRwLock {
+                lock: MappedRwLock {
+                    inner,
+                    subfield: ptr,
+                },
+                allocator: allocator.clone(),
+            },
+            Self {
+                lock: MappedRwLock {
+                    inner,
+                    subfield: NonNull::slice_from_raw_parts(
+                        // SAFETY: `ptr` points to a slice which contains at least one element.
+                        unsafe { ptr.add(1) },
+                        // SAFETY: Checked above that `len > 0`.
+                        unsafe { len.unchecked_sub(1) },
+                    ),
+                },
+                allocator,
+            },
+        ))
+    }
+
+    /// Splits the lock into a lock over the last element and
+    /// a lock over the rest of the slice, or returns `None` if
+    /// the slice is empty.
+    pub fn split_last(self) -> Option<(UniqueArcElementRwLock<T, A>, Self)>
+    where
+        A: Clone,
+    {
+        let (ptr, len) = (self.lock.subfield.cast::<()>(), self.lock.subfield.len());
+        if len == 0 {
+            return None;
+        }
+        let ptr = ptr.cast::<T>();
+        // SAFETY: Checked above that `len > 0`.
+        let len = unsafe { len.unchecked_sub(1) };
+        let (inner, allocator) = self.increment_for_split();
+        Some((
+            UniqueArcElementRwLock {
+                lock: MappedRwLock {
+                    inner,
+                    // SAFETY: `ptr` points to a slice which contains at least `len + 1` elements.
+                    subfield: unsafe { ptr.add(len) },
+                },
+                allocator: allocator.clone(),
+            },
+            Self {
+                lock: MappedRwLock {
+                    inner,
+                    subfield: NonNull::slice_from_raw_parts(ptr, len),
+                },
+                allocator,
+            },
+        ))
+    }
+
+    /// Splits the lock into locks over `[0, mid)` and `[mid, len)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    pub fn split_at(self, mid: usize) -> (Self, Self)
+    where
+        A: Clone,
+    {
+        let (ptr, len) = (self.lock.subfield.cast::<()>(), self.lock.subfield.len());
+        assert!(mid <= len, "mid > len");
+        let ptr = ptr.cast::<T>();
+        let (inner, allocator) = self.increment_for_split();
+        (
+            Self {
+                lock: MappedRwLock {
+                    inner,
+                    subfield: NonNull::slice_from_raw_parts(ptr, mid),
+                },
+                allocator: allocator.clone(),
+            },
+            Self {
+                lock: MappedRwLock {
+                    inner,
+                    subfield: NonNull::slice_from_raw_parts(
+                        // SAFETY: Checked above that `mid` is within the subslice.
+                        unsafe { ptr.add(mid) },
+                        // SAFETY: Checked above that `mid <= len`.
+                        unsafe { len.unchecked_sub(mid) },
+                    ),
+                },
+                allocator,
+            },
+        )
+    }
+
+    /// Takes the fields out of `self` and registers one
+    /// more unique handle to the allocation.
+    fn increment_for_split(self) -> (NonNull<InnerRwLock<[T]>>, A) {
+        // SAFETY: All fields of `self` are forgotten immediately after
+        //         reading them out of the pointers.
+        let lock = unsafe { (&raw const self.lock).read() };
+        let allocator = unsafe { (&raw const self.allocator).read() };
+        mem::forget(self);
+        if unlikely(unsafe {
+            // SAFETY: By construction, the calculated pointer points to a valid and live instance of `InnerArc`.
+            InnerArc::increment_unique_counter(
+                // SAFETY: `lock.inner` has been allocated as a part of an `InnerArc`.
+                InnerArc::from_lock(lock.inner).0,
+                Ordering::Release,
+            )
+        }) {
+            process::abort();
+        }
+        (lock.inner, allocator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn slice_lock<const N: usize>(data: [usize; N]) -> UniqueArcSliceRwLock<usize> {
+        let inner = InnerArc::new_unique(data);
+        UniqueArcSliceRwLock {
+            lock: MappedRwLock {
+                inner,
+                // SAFETY: `inner` points to the live lock which has just been allocated.
+                subfield: unsafe { NonNull::new_unchecked(&raw mut (*inner.as_ptr()).data) },
+            },
+            allocator: Global,
+        }
+    }
+
+    #[test]
+    fn get_is_bounds_checked() {
+        let mut lock = slice_lock([0, 1, 2]);
+        assert_eq!(lock.get(2), Some(&2));
+        assert_eq!(lock.get(3), None);
+        assert!(lock.get_mut(3).is_none());
+        assert!(lock.into_element(3).is_none());
+    }
+
+    #[test]
+    fn writing_an_element_bumps_the_version_once() {
+        let mut lock = slice_lock([0, 1, 2]);
+        let version = lock.version();
+        *lock.get_mut(1).unwrap() = 7;
+        assert_eq!(lock.read(), [0, 7, 2]);
+        assert_eq!(lock.version(), version.wrapping_add(1));
+    }
+
+    #[test]
+    fn guards_which_only_read_leave_the_version_alone() {
+        let mut lock = slice_lock([0, 1, 2]);
+        let version = lock.version();
+        assert_eq!(*lock.get_mut(1).unwrap(), 1);
+        assert_eq!(lock.write().len(), 3);
+        assert_eq!(lock.version(), version);
+    }
+
+    #[test]
+    fn splits_cover_disjoint_elements() {
+        let lock = slice_lock([0, 1, 2, 3, 4]);
+        let (first, rest) = lock.split_first().unwrap();
+        let (last, middle) = rest.split_last().unwrap();
+        assert_eq!((first.element_offset(), *first.read()), (0, 0));
+        assert_eq!((last.element_offset(), *last.read()), (4, 4));
+        assert_eq!(middle.subslice_range(), 1..4);
+        assert_eq!(middle.read(), [1, 2, 3]);
+
+        let (left, right) = middle.split_at(1);
+        assert_eq!(
+            (left.subslice_range(), right.subslice_range()),
+            (1..2, 2..4)
+        );
+        let (empty, left) = left.split_at(0);
+        assert!(empty.split_first().is_none());
+        let (_, empty) = left.split_at(1);
+        assert!(empty.split_last().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "mid > len")]
+    fn split_at_rejects_a_point_past_the_end() {
+        slice_lock([0, 1, 2]).split_at(4);
+    }
+
+    #[test]
+    fn disjoint_locks_write_concurrently() {
+        let lock = slice_lock([0; 6]);
+        let version = lock.version();
+        let (mut left, right) = lock.split_at(2);
+        let (mut element, mut right) = right.split_last().unwrap();
+
+        // Every guard is held at once: writers to subfields do not exclude each other.
+        let mut left_guard = left.write();
+        let mut right_guard = right.write();
+        let mut element_guard = element.write();
+        left_guard.fill(1);
+        right_guard.fill(2);
+        *element_guard = 3;
+        drop((left_guard, right_guard, element_guard));
+
+        assert_eq!(left.read(), [1, 1]);
+        assert_eq!(right.read(), [2, 2, 2]);
+        assert_eq!(*element.read(), 3);
+        assert_eq!(left.version(), version.wrapping_add(3));
+        assert_eq!(right.version(), left.version());
+    }
+
+    #[test]
+    fn disjoint_locks_write_from_several_threads() {
+        let mut lock = slice_lock([0; 64]);
+        let mut locks = Vec::new();
+        for _ in 0..7 {
+            let (head, tail) = lock.split_at(8);
+            locks.push(head);
+            lock = tail;
+        }
+        locks.push(lock);
+
+        let locks = thread::scope(|scope| {
+            let handles: Vec<_> = locks
+                .into_iter()
+                .enumerate()
+                .map(|(i, mut lock)| {
+                    scope.spawn(move || {
+                        for _ in 0..100 {
+                            for value in lock.write().iter_mut() {
+                                *value += i;
+                            }
+                        }
+                        lock
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for (i, lock) in locks.iter().enumerate() {
+            assert!(lock.read().iter().all(|&value| value == 100 * i));
+            assert_eq!(lock.version(), 800);
+        }
+    }
+
+    #[test]
+    fn element_locks_outlive_the_slice_lock() {
+        let mut lock = slice_lock([0, 1, 2]);
+        *lock.get_mut(0).unwrap() = 5;
+        let mut element = lock.into_element(2).unwrap();
+        assert_eq!(element.element_offset(), 2);
+        element.set(9);
+        assert_eq!(*element.read(), 9);
+        assert_eq!(element.version(), 2);
+    }
 }
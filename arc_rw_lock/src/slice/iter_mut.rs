@@ -1,19 +1,24 @@
 use std::{
-    alloc::{Allocator, Global},
+    iter::FusedIterator,
     mem::needs_drop,
     process,
     ptr::NonNull,
     sync::atomic::{self, Ordering},
 };
 
-use crate::{MappedRwLock, UniqueArcElementRwLock, arc::InnerArc, unlikely};
+use crate::{
+    MappedRwLock, UniqueArcElementRwLock,
+    alloc::{Allocator, Global},
+    arc::InnerArc,
+    unlikely,
+};
 
-pub struct Iter<T, A: Allocator = Global> {
+pub struct IterMut<T, A: Allocator = Global> {
     pub(crate) lock: MappedRwLock<[T], [T]>,
     pub(crate) allocator: A,
 }
 
-impl<T, A: Allocator> Drop for Iter<T, A> {
+impl<T, A: Allocator> Drop for IterMut<T, A> {
     fn drop(&mut self) {
         // SAFETY: `self.lock.inner` has been allocated as a part of an `InnerArc`.
         let (allocation, layout) = unsafe { InnerArc::from_lock(self.lock.inner) };
@@ -34,15 +39,15 @@ impl<T, A: Allocator> Drop for Iter<T, A> {
     }
 }
 
-impl<T, A: Allocator + Clone> Iterator for Iter<T, A> {
+impl<T, A: Allocator + Clone> Iterator for IterMut<T, A> {
     type Item = UniqueArcElementRwLock<T, A>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (ptr, len) = self.lock.subfield.to_raw_parts();
+        let (ptr, len) = (self.lock.subfield.cast::<()>(), self.lock.subfield.len());
         if len > 0 {
             let ptr = ptr.cast::<T>();
             unsafe {
-                self.lock.subfield = NonNull::from_raw_parts(
+                self.lock.subfield = NonNull::slice_from_raw_parts(
                     // SAFETY: `ptr` points to a slice which contains at least one element.
                     ptr.add(1),
                     len.unchecked_sub(1),
@@ -50,7 +55,7 @@ impl<T, A: Allocator + Clone> Iterator for Iter<T, A> {
             }
             if unlikely(unsafe {
                 // SAFETY: By construction, the calculated pointer points to a valid and live instance of `InnerArc`.
-                InnerArc::increment_shared_counter(
+                InnerArc::increment_unique_counter(
                     // SAFETY: `self.lock.inner` has been allocated as a part of an `InnerArc`.
                     InnerArc::from_lock(self.lock.inner).0,
                     Ordering::Release,
@@ -69,19 +74,24 @@ impl<T, A: Allocator + Clone> Iterator for Iter<T, A> {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.lock.subfield.len();
+        (len, Some(len))
+    }
 }
 
-impl<T, A: Allocator + Clone> DoubleEndedIterator for Iter<T, A> {
+impl<T, A: Allocator + Clone> DoubleEndedIterator for IterMut<T, A> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        let (ptr, mut len) = self.lock.subfield.to_raw_parts();
+        let (ptr, mut len) = (self.lock.subfield.cast::<()>(), self.lock.subfield.len());
         if len > 0 {
             // SAFETY: Checked above that `len > 0`.
             len = unsafe { len.unchecked_sub(1) };
             let ptr = ptr.cast::<T>();
-            self.lock.subfield = NonNull::from_raw_parts(ptr, len);
+            self.lock.subfield = NonNull::slice_from_raw_parts(ptr, len);
             if unlikely(unsafe {
                 // SAFETY: By construction, the calculated pointer points to a valid and live instance of `InnerArc`.
-                InnerArc::increment_shared_counter(
+                InnerArc::increment_unique_counter(
                     // SAFETY: `self.lock.inner` has been allocated as a part of an `InnerArc`.
                     InnerArc::from_lock(self.lock.inner).0,
                     Ordering::Release,
@@ -102,3 +112,21 @@ impl<T, A: Allocator + Clone> DoubleEndedIterator for Iter<T, A> {
         }
     }
 }
+
+impl<T, A: Allocator + Clone> ExactSizeIterator for IterMut<T, A> {}
+
+impl<T, A: Allocator + Clone> FusedIterator for IterMut<T, A> {}
+
+unsafe impl<T, A> Send for IterMut<T, A>
+where
+    T: Send + Sync,
+    A: Allocator + Send,
+{
+}
+
+unsafe impl<T, A> Sync for IterMut<T, A>
+where
+    T: Send + Sync,
+    A: Allocator + Sync,
+{
+}
@@ -0,0 +1,89 @@
+use crate::alloc::{Allocator, Global};
+
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, ParallelIterator,
+    plumbing::{Consumer, Producer, ProducerCallback, UnindexedConsumer, bridge},
+};
+
+use crate::{UniqueArcElementRwLock, UniqueArcSliceRwLock, slice::iter_mut::IterMut};
+
+/// A parallel iterator over the element locks of a [`UniqueArcSliceRwLock`].
+pub struct ParIterMut<T, A: Allocator = Global> {
+    lock: UniqueArcSliceRwLock<T, A>,
+}
+
+impl<T, A> IntoParallelIterator for UniqueArcSliceRwLock<T, A>
+where
+    T: Send + Sync,
+    A: Allocator + Clone + Send + Sync,
+{
+    type Iter = ParIterMut<T, A>;
+    type Item = UniqueArcElementRwLock<T, A>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIterMut { lock: self }
+    }
+}
+
+impl<T, A> ParallelIterator for ParIterMut<T, A>
+where
+    T: Send + Sync,
+    A: Allocator + Clone + Send + Sync,
+{
+    type Item = UniqueArcElementRwLock<T, A>;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T, A> IndexedParallelIterator for ParIterMut<T, A>
+where
+    T: Send + Sync,
+    A: Allocator + Clone + Send + Sync,
+{
+    fn len(&self) -> usize {
+        self.lock.subfield.len()
+    }
+
+    fn drive<C>(self, consumer: C) -> C::Result
+    where
+        C: Consumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB>(self, callback: CB) -> CB::Output
+    where
+        CB: ProducerCallback<Self::Item>,
+    {
+        callback.callback(SliceProducer(self.lock))
+    }
+}
+
+struct SliceProducer<T, A: Allocator>(UniqueArcSliceRwLock<T, A>);
+
+impl<T, A> Producer for SliceProducer<T, A>
+where
+    T: Send + Sync,
+    A: Allocator + Clone + Send + Sync,
+{
+    type Item = UniqueArcElementRwLock<T, A>;
+    type IntoIter = IterMut<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let (left, right) = self.0.split_at(index);
+        (Self(left), Self(right))
+    }
+}
@@ -0,0 +1,518 @@
+mod correlation {
+    /// Returns the time autocorrelation `⟨x(0) · x(t)⟩` of a series of vectors for lags
+    /// from zero to `max_lag` samples, averaged over every time origin.
+    ///
+    /// Lags beyond the length of the series are dropped.
+    pub fn autocorrelation<S: AsRef<[f64]>>(series: &[S], max_lag: usize) -> Vec<f64> {
+        let lags = (max_lag + 1).min(series.len());
+        (0..lags)
+            .map(|lag| {
+                let origins = series.len() - lag;
+                series[..origins]
+                    .iter()
+                    .zip(&series[lag..])
+                    .map(|(first, second)| {
+                        first
+                            .as_ref()
+                            .iter()
+                            .zip(second.as_ref())
+                            .map(|(first, second)| first * second)
+                            .sum::<f64>()
+                    })
+                    .sum::<f64>()
+                    / origins as f64
+            })
+            .collect()
+    }
+
+    /// Returns the running integral of `values` sampled every `time_step` with the trapezoidal rule,
+    /// starting at zero.
+    pub fn running_integral(values: &[f64], time_step: f64) -> Vec<f64> {
+        let mut integral = 0.0;
+        let mut running = Vec::with_capacity(values.len());
+        if !values.is_empty() {
+            running.push(integral);
+        }
+        for pair in values.windows(2) {
+            integral += 0.5 * (pair[0] + pair[1]) * time_step;
+            running.push(integral);
+        }
+        running
+    }
+}
+
+pub use correlation::{autocorrelation, running_integral};
+
+mod green_kubo {
+    use super::{autocorrelation, running_integral};
+    use crate::core::constants::BOLTZMANN_CONSTANT;
+
+    /// A transport coefficient estimated from the integral of a time autocorrelation function.
+    #[derive(Clone, Debug)]
+    pub struct GreenKubo {
+        /// The autocorrelation function, averaged over the components, at every lag.
+        pub correlation: Vec<f64>,
+        /// The transport coefficient obtained by integrating up to every lag.
+        pub running_integral: Vec<f64>,
+        pub time_step: f64,
+    }
+
+    impl GreenKubo {
+        fn new(correlation: Vec<f64>, prefactor: f64, time_step: f64) -> Self {
+            let running_integral = running_integral(&correlation, time_step)
+                .into_iter()
+                .map(|integral| prefactor * integral)
+                .collect();
+            Self {
+                correlation,
+                running_integral,
+                time_step,
+            }
+        }
+
+        /// Returns the transport coefficient averaged over the running integral
+        /// between the times `start` and `end`, where it should have reached a plateau,
+        /// or `None` if no lag falls in between.
+        pub fn plateau(&self, start: f64, end: f64) -> Option<f64> {
+            let values: Vec<f64> = self
+                .running_integral
+                .iter()
+                .enumerate()
+                .filter(|(lag, _)| (start..=end).contains(&(*lag as f64 * self.time_step)))
+                .map(|(_, &value)| value)
+                .collect();
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        }
+    }
+
+    /// Returns the shear viscosity `η = V / (k_B T) ∫ ⟨P_ab(0) P_ab(t)⟩ dt`
+    /// of a system in a box of volume `volume` from the pressure tensors sampled
+    /// every `time_step`, averaging over the off-diagonal components `a < b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system has fewer than two dimensions.
+    pub fn viscosity<const N: usize>(
+        pressure_tensors: &[[[f64; N]; N]],
+        volume: f64,
+        thermal_energy: f64,
+        time_step: f64,
+        max_lag: usize,
+    ) -> GreenKubo {
+        assert!(N > 1, "shear requires at least two dimensions");
+        let components = N * (N - 1) / 2;
+        let series: Vec<Vec<f64>> = pressure_tensors
+            .iter()
+            .map(|tensor| {
+                (0..N)
+                    .flat_map(|row| (row + 1..N).map(move |column| tensor[row][column]))
+                    .collect()
+            })
+            .collect();
+        let correlation = autocorrelation(&series, max_lag)
+            .into_iter()
+            .map(|correlation| correlation / components as f64)
+            .collect();
+        GreenKubo::new(correlation, volume / thermal_energy, time_step)
+    }
+
+    /// Returns the thermal conductivity `κ = V / (k_B T²) ∫ ⟨J(0) · J(t)⟩ / N dt`
+    /// of a system in a box of volume `volume` from the heat fluxes sampled every `time_step`.
+    pub fn thermal_conductivity<const N: usize>(
+        heat_fluxes: &[[f64; N]],
+        volume: f64,
+        thermal_energy: f64,
+        time_step: f64,
+        max_lag: usize,
+    ) -> GreenKubo {
+        let correlation = autocorrelation(heat_fluxes, max_lag)
+            .into_iter()
+            .map(|correlation| correlation / N as f64)
+            .collect();
+        let prefactor = volume * f64::from(BOLTZMANN_CONSTANT) / (thermal_energy * thermal_energy);
+        GreenKubo::new(correlation, prefactor, time_step)
+    }
+}
+
+pub use green_kubo::{GreenKubo, thermal_conductivity, viscosity};
+
+mod trace {
+    use lib::output::{TraceError, TraceReader};
+    use std::io::{Read, Write};
+
+    fn format_quantity(value: f64) -> String {
+        if value.is_finite() {
+            format!("{:>16.8e}", value)
+        } else {
+            // Flagged so that the first broken step stands out when scanning the table.
+            format!("{:>15}!", value)
+        }
+    }
+
+    /// Prints the records of the binary trace in `reader` to `writer` as a table,
+    /// one line per group and replica with a blank line between steps,
+    /// and returns the number of records.
+    ///
+    /// Non-finite quantities are marked with '!'.
+    pub fn print_trace<R: Read, W: Write>(reader: R, writer: &mut W) -> Result<usize, TraceError> {
+        let trace = TraceReader::new(reader)?;
+        let settings = trace.settings().clone();
+        let steps = settings.steps();
+        writeln!(
+            writer,
+            "# level {:?}, steps {} to {}",
+            settings.level(),
+            steps.start,
+            steps.end.saturating_sub(1)
+        )?;
+        let columns = ["potential", "kinetic", "max force", "thermostat"];
+        write!(writer, "# {:>8}\t{:>7}\t{:>5}", "step", "replica", "group")?;
+        for column in &columns[..settings.level().quantities()] {
+            write!(writer, "\t{:>16}", column)?;
+        }
+        writeln!(writer)?;
+        let mut records = 0;
+        let mut last_step = None;
+        for record in trace {
+            let record = record?;
+            if last_step.is_some_and(|step| step != record.step) {
+                writeln!(writer)?;
+            }
+            last_step = Some(record.step);
+            write!(
+                writer,
+                "{:>10}\t{:>7}\t{:>5}\t{}\t{}",
+                record.step,
+                record.replica,
+                record.group,
+                format_quantity(record.potential_energy),
+                format_quantity(record.kinetic_energy)
+            )?;
+            for quantity in [record.max_force, record.thermostat_increment]
+                .into_iter()
+                .flatten()
+            {
+                write!(writer, "\t{}", format_quantity(quantity))?;
+            }
+            writeln!(writer)?;
+            records += 1;
+        }
+        Ok(records)
+    }
+}
+
+pub use trace::print_trace;
+
+mod multi_temperature {
+    use crate::{
+        core::constants::BOLTZMANN_CONSTANT,
+        estimator::{
+            free_energy::{FreeEnergyError, log_sum_exp},
+            reweight::WeightedAverage,
+        },
+    };
+    use lib::{
+        core::tolerance::TolerancePolicy,
+        numeric::iterate::FixedPoint,
+        output::{Dimension, ObservableRegistry, ValuesOutput},
+    };
+
+    #[derive(Clone, Debug)]
+    struct Run {
+        beta: f64,
+        energies: Vec<f64>,
+        /// The values of every observable, sample-major.
+        values: Vec<f64>,
+    }
+
+    /// A weighted histogram analysis of the energies sampled at several temperatures,
+    /// by independent runs or by the replicas of a replica exchange run,
+    /// which combines all samples into estimates of the observables at any temperature
+    /// within the range covered by the runs.
+    ///
+    /// The reduced free energies `f_r` of the runs are solved self-consistently from
+    /// the energy histograms, after which every sample is weighted in the canonical
+    /// ensemble at `β` by `exp(-β E) / Σ_r N_r exp(f_r - β_r E)`.
+    #[derive(Clone, Debug)]
+    pub struct MultiTemperatureWham {
+        observables: Vec<(String, Dimension)>,
+        bin_width: f64,
+        runs: Vec<Run>,
+    }
+
+    impl MultiTemperatureWham {
+        /// Constructs an analysis of the observables named and dimensioned by `observables`,
+        /// histogramming the energies in bins of `bin_width`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `bin_width` is not positive.
+        pub fn new(observables: &[(&str, Dimension)], bin_width: f64) -> Self {
+            assert!(bin_width > 0.0, "the bin width must be positive");
+            Self {
+                observables: observables
+                    .iter()
+                    .map(|&(name, dimension)| (name.to_owned(), dimension))
+                    .collect(),
+                bin_width,
+                runs: Vec::new(),
+            }
+        }
+
+        /// Adds a run at `temperature` without samples and returns its index.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `temperature` is not positive.
+        pub fn add_run(&mut self, temperature: f64) -> usize {
+            assert!(temperature > 0.0, "the temperature must be positive");
+            self.runs.push(Run {
+                beta: 1.0 / (f64::from(BOLTZMANN_CONSTANT) * temperature),
+                energies: Vec::new(),
+                values: Vec::new(),
+            });
+            self.runs.len() - 1
+        }
+
+        /// Adds a sample of the potential energy `energy` and of the observables `values`
+        /// to the run at index `run`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `run` is out of bounds or there is not a value for every observable.
+        pub fn add_sample(&mut self, run: usize, energy: f64, values: &[f64]) {
+            assert_eq!(
+                values.len(),
+                self.observables.len(),
+                "there must be a value for every observable"
+            );
+            let run = &mut self.runs[run];
+            run.energies.push(energy);
+            run.values.extend_from_slice(values);
+        }
+
+        /// Returns the number of runs.
+        pub fn runs(&self) -> usize {
+            self.runs.len()
+        }
+
+        /// Returns the temperature of the run at index `run`.
+        pub fn temperature(&self, run: usize) -> f64 {
+            1.0 / (f64::from(BOLTZMANN_CONSTANT) * self.runs[run].beta)
+        }
+
+        /// Solves the WHAM equations for the reduced free energies of the runs,
+        /// relative to the first run, until the largest change of a free energy
+        /// in an iteration is tolerated.
+        pub fn solve(&self, tolerance: &TolerancePolicy<f64>) -> Result<Vec<f64>, FreeEnergyError> {
+            if self.runs.is_empty() || self.runs.iter().any(|run| run.energies.is_empty()) {
+                return Err(FreeEnergyError::NoSamples);
+            }
+            let lowest = self
+                .runs
+                .iter()
+                .flat_map(|run| &run.energies)
+                .fold(f64::INFINITY, |lowest, &energy| lowest.min(energy));
+            let bin = |energy: f64| ((energy - lowest) / self.bin_width) as usize;
+            let bins = self
+                .runs
+                .iter()
+                .flat_map(|run| &run.energies)
+                .map(|&energy| bin(energy) + 1)
+                .max()
+                .unwrap_or(0);
+            // Only the total histogram enters the density of states.
+            let mut histogram = vec![0usize; bins];
+            for &energy in self.runs.iter().flat_map(|run| &run.energies) {
+                histogram[bin(energy)] += 1;
+            }
+            let occupied: Vec<(f64, f64)> = histogram
+                .iter()
+                .enumerate()
+                .filter(|&(_, &count)| count > 0)
+                .map(|(index, &count)| {
+                    let center = lowest + (index as f64 + 0.5) * self.bin_width;
+                    (center, (count as f64).ln())
+                })
+                .collect();
+            let log_samples: Vec<f64> = self
+                .runs
+                .iter()
+                .map(|run| (run.energies.len() as f64).ln())
+                .collect();
+            let mut free_energies = vec![0.0; self.runs.len()];
+            FixedPoint::new(*tolerance)
+                .solve(&mut free_energies, |free_energies, updated| {
+                    let log_density: Vec<f64> = occupied
+                        .iter()
+                        .map(|&(energy, log_count)| {
+                            log_count
+                                - log_sum_exp(
+                                    self.runs.iter().zip(free_energies).zip(&log_samples).map(
+                                        |((run, free_energy), log_samples)| {
+                                            log_samples + free_energy - run.beta * energy
+                                        },
+                                    ),
+                                )
+                        })
+                        .collect();
+                    for (run, updated) in self.runs.iter().zip(updated.iter_mut()) {
+                        *updated =
+                            -log_sum_exp(occupied.iter().zip(&log_density).map(
+                                |(&(energy, _), log_density)| log_density - run.beta * energy,
+                            ));
+                    }
+                    let reference = updated[0];
+                    updated
+                        .iter_mut()
+                        .for_each(|free_energy| *free_energy -= reference);
+                    if updated.iter().all(|free_energy| free_energy.is_finite()) {
+                        Ok(())
+                    } else {
+                        Err(FreeEnergyError::NoOverlap)
+                    }
+                })
+                .map_err(FreeEnergyError::from)?;
+            Ok(free_energies)
+        }
+
+        /// Estimates the energy, the heat capacity and every observable at each of
+        /// `temperatures` from the reduced free energies of the runs returned by
+        /// [`MultiTemperatureWham::solve`].
+        ///
+        /// # Panics
+        ///
+        /// Panics if there is not a free energy for every run.
+        pub fn curves(&self, free_energies: &[f64], temperatures: &[f64]) -> TemperatureCurves {
+            assert_eq!(
+                free_energies.len(),
+                self.runs.len(),
+                "there must be a free energy for every run"
+            );
+            let observables = self.observables.len();
+            let log_samples: Vec<f64> = self
+                .runs
+                .iter()
+                .map(|run| (run.energies.len() as f64).ln())
+                .collect();
+            // The logarithm of the denominator of the weight of every sample, run by run.
+            let log_denominators: Vec<Vec<f64>> = self
+                .runs
+                .iter()
+                .map(|run| {
+                    run.energies
+                        .iter()
+                        .map(|&energy| {
+                            log_sum_exp(self.runs.iter().zip(free_energies).zip(&log_samples).map(
+                                |((other, free_energy), log_samples)| {
+                                    log_samples + free_energy - other.beta * energy
+                                },
+                            ))
+                        })
+                        .collect()
+                })
+                .collect();
+            let mut points = Vec::with_capacity(temperatures.len());
+            for &temperature in temperatures {
+                let beta = 1.0 / (f64::from(BOLTZMANN_CONSTANT) * temperature);
+                let mut energy = WeightedAverage::new();
+                let mut squared_energy = WeightedAverage::new();
+                let mut values = vec![WeightedAverage::new(); observables];
+                for (run, log_denominators) in self.runs.iter().zip(&log_denominators) {
+                    for (sample, (&sample_energy, log_denominator)) in
+                        run.energies.iter().zip(log_denominators).enumerate()
+                    {
+                        let log_weight = -beta * sample_energy - log_denominator;
+                        energy.add(sample_energy, log_weight);
+                        squared_energy.add(sample_energy * sample_energy, log_weight);
+                        let sample_values =
+                            &run.values[sample * observables..(sample + 1) * observables];
+                        for (average, &value) in values.iter_mut().zip(sample_values) {
+                            average.add(value, log_weight);
+                        }
+                    }
+                }
+                let mean_energy = energy.mean().unwrap_or(f64::NAN);
+                let fluctuation =
+                    squared_energy.mean().unwrap_or(f64::NAN) - mean_energy * mean_energy;
+                points.push(TemperaturePoint {
+                    temperature,
+                    energy: mean_energy,
+                    heat_capacity: fluctuation * f64::from(BOLTZMANN_CONSTANT) * beta * beta,
+                    effective_sample_size: energy.effective_sample_size(),
+                    values: values
+                        .iter()
+                        .map(|average| average.mean().unwrap_or(f64::NAN))
+                        .collect(),
+                });
+            }
+            TemperatureCurves {
+                observables: self.observables.clone(),
+                points,
+            }
+        }
+    }
+
+    /// The estimates of [`MultiTemperatureWham`] at a temperature.
+    #[derive(Clone, Debug)]
+    pub struct TemperaturePoint {
+        pub temperature: f64,
+        pub energy: f64,
+        /// The heat capacity `(⟨E²⟩ - ⟨E⟩²) / (k_B T²)` from the fluctuations of the energy.
+        pub heat_capacity: f64,
+        /// Kish's effective sample size of the weights, which is small where
+        /// the temperature lies outside the range covered by the runs.
+        pub effective_sample_size: f64,
+        /// The values of the observables, in the order they were given.
+        pub values: Vec<f64>,
+    }
+
+    /// The estimates of [`MultiTemperatureWham`] over a range of temperatures.
+    #[derive(Clone, Debug)]
+    pub struct TemperatureCurves {
+        observables: Vec<(String, Dimension)>,
+        points: Vec<TemperaturePoint>,
+    }
+
+    impl TemperatureCurves {
+        pub fn points(&self) -> &[TemperaturePoint] {
+            &self.points
+        }
+
+        /// Registers the columns written by [`TemperatureCurves::write_values`].
+        pub fn register_values(&self, registry: &mut ObservableRegistry) {
+            registry.register("temperature", Dimension::TEMPERATURE);
+            registry.register("energy", Dimension::ENERGY);
+            registry.register("heat_capacity", Dimension::ENERGY / Dimension::TEMPERATURE);
+            registry.register("effective_sample_size", Dimension::DIMENSIONLESS);
+            for (name, dimension) in &self.observables {
+                registry.register(name, *dimension);
+            }
+        }
+
+        /// Writes one line per temperature, prefixed by its index.
+        pub fn write_values<O>(&self, stream: &mut O) -> Result<(), O::Error>
+        where
+            O: ValuesOutput<f64> + ?Sized,
+        {
+            for (index, point) in self.points.iter().enumerate() {
+                stream.write_step(index)?;
+                for value in [
+                    point.temperature,
+                    point.energy,
+                    point.heat_capacity,
+                    point.effective_sample_size,
+                ]
+                .into_iter()
+                .chain(point.values.iter().copied())
+                {
+                    stream.write_value(value)?;
+                }
+                stream.new_line()?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use multi_temperature::{MultiTemperatureWham, TemperatureCurves, TemperaturePoint};
@@ -0,0 +1,646 @@
+use lib::core::Vector;
+use num::Float;
+
+use crate::potential::electrostatics::PeriodicBox;
+
+/// Returns the displacement from `from` to `to`, after the minimum image convention if a box is given.
+fn displacement<const N: usize, T, V>(
+    periodic_box: Option<&PeriodicBox<T, N>>,
+    from: &V,
+    to: &V,
+) -> V
+where
+    T: Float,
+    V: Vector<N, Element = T> + Clone,
+{
+    let displacement = to.clone() - from.clone();
+    match periodic_box {
+        Some(periodic_box) => periodic_box.minimum_image(displacement),
+        None => displacement,
+    }
+}
+
+mod switching {
+    use num::Float;
+
+    /// A function going smoothly from 1 at short distances to 0 at long ones,
+    /// counting the neighbors of an atom in a differentiable way.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum SwitchingFunction<T> {
+        /// `(1 - (r / r_0)^n) / (1 - (r / r_0)^m)`, truncated to zero beyond `cutoff`.
+        Rational { r_0: T, n: i32, m: i32, cutoff: T },
+        /// 1 up to `inner`, `(1 + cos(π (r - inner) / (outer - inner))) / 2` up to `outer`
+        /// and 0 beyond, which vanishes smoothly without truncation.
+        Cosine { inner: T, outer: T },
+    }
+
+    impl<T: Float + From<f32>> SwitchingFunction<T> {
+        /// Constructs the rational switching function with the usual exponents `n = 6` and `m = 12`,
+        /// truncated where it falls below 10⁻³.
+        pub fn rational(r_0: T) -> Self {
+            assert!(r_0 > T::zero(), "the switching distance must be positive");
+            Self::Rational {
+                r_0,
+                n: 6,
+                m: 12,
+                cutoff: r_0 * <T as From<f32>>::from(10f32.powf(0.5)),
+            }
+        }
+
+        /// Constructs the cosine switching function between `inner` and `outer`.
+        pub fn cosine(inner: T, outer: T) -> Self {
+            assert!(
+                inner >= T::zero(),
+                "the inner distance must be non-negative"
+            );
+            assert!(
+                outer > inner,
+                "the outer distance must exceed the inner distance"
+            );
+            Self::Cosine { inner, outer }
+        }
+
+        /// Returns the distance beyond which the function vanishes.
+        pub fn cutoff(&self) -> T {
+            match *self {
+                Self::Rational { cutoff, .. } => cutoff,
+                Self::Cosine { outer, .. } => outer,
+            }
+        }
+
+        /// Returns the value of the function and its derivative at the distance `distance`.
+        pub fn evaluate(&self, distance: T) -> (T, T) {
+            match *self {
+                Self::Rational { r_0, n, m, cutoff } => {
+                    if distance >= cutoff {
+                        return (T::zero(), T::zero());
+                    }
+                    let x = distance / r_0;
+                    let (n_float, m_float) = (
+                        <T as From<f32>>::from(n as f32),
+                        <T as From<f32>>::from(m as f32),
+                    );
+                    let epsilon = <T as From<f32>>::from(1e-4);
+                    if (x - T::one()).abs() < epsilon {
+                        // The limit of the ratio and of its slope at r = r_0.
+                        let value = n_float / m_float;
+                        let slope =
+                            n_float * (n_float - m_float) / (<T as From<f32>>::from(2.0) * m_float);
+                        return (value, slope / r_0);
+                    }
+                    let x_n = x.powi(n);
+                    let x_m = x.powi(m);
+                    let numerator = T::one() - x_n;
+                    let denominator = T::one() - x_m;
+                    let value = numerator / denominator;
+                    let slope = (m_float * x.powi(m - 1) * numerator
+                        - n_float * x.powi(n - 1) * denominator)
+                        / (denominator * denominator);
+                    (value, slope / r_0)
+                }
+                Self::Cosine { inner, outer } => {
+                    if distance <= inner {
+                        (T::one(), T::zero())
+                    } else if distance >= outer {
+                        (T::zero(), T::zero())
+                    } else {
+                        let half = <T as From<f32>>::from(0.5);
+                        let pi = <T as From<f32>>::from(std::f32::consts::PI);
+                        let width = outer - inner;
+                        let phase = pi * (distance - inner) / width;
+                        (
+                            half * (T::one() + phase.cos()),
+                            -half * pi / width * phase.sin(),
+                        )
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub use switching::SwitchingFunction;
+
+mod variable {
+    /// A function of the positions of a group used to bias the sampling or to follow
+    /// the progress of a process, e.g. in metadynamics or nucleation studies.
+    pub trait CollectiveVariable<T, V> {
+        /// Returns the value of the variable for the atoms at `positions`.
+        fn value(&self, positions: &[V]) -> T;
+
+        /// Returns the value of the variable for the atoms at `positions`
+        /// and sets `gradient` to its gradient with respect to each position.
+        fn value_and_gradient(&self, positions: &[V], gradient: &mut [V]) -> T;
+    }
+}
+
+pub use variable::CollectiveVariable;
+
+mod coordination {
+    use lib::core::Vector;
+    use num::Float;
+
+    use super::{CollectiveVariable, SwitchingFunction, displacement};
+    use crate::potential::electrostatics::PeriodicBox;
+
+    /// The mean number of neighbors `(1 / |A|) Σ_{i ∈ A} Σ_{j ∈ B, j ≠ i} s(r_ij)`
+    /// of the central atoms `A` among the neighboring atoms `B`, counted with a switching function `s`.
+    ///
+    /// The atoms are indexed in the positions the variable is evaluated on and the pairs
+    /// are enumerated directly, which suits the small sets of atoms typical of biased variables.
+    ///
+    /// # Panics
+    ///
+    /// Evaluating the variable panics if an index is out of range.
+    #[derive(Clone, Debug)]
+    pub struct CoordinationNumber<const N: usize, T> {
+        centers: Box<[usize]>,
+        neighbors: Box<[usize]>,
+        switching: SwitchingFunction<T>,
+        periodic_box: Option<PeriodicBox<T, N>>,
+    }
+
+    impl<const N: usize, T: Float + From<f32>> CoordinationNumber<N, T> {
+        pub fn new(
+            centers: Vec<usize>,
+            neighbors: Vec<usize>,
+            switching: SwitchingFunction<T>,
+        ) -> Self {
+            assert!(!centers.is_empty(), "the variable needs central atoms");
+            Self {
+                centers: centers.into_boxed_slice(),
+                neighbors: neighbors.into_boxed_slice(),
+                switching,
+                periodic_box: None,
+            }
+        }
+
+        /// Applies the minimum image convention of `periodic_box` to the distances.
+        pub fn with_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            self.periodic_box = Some(periodic_box);
+            self
+        }
+
+        /// Returns the coordination number of every central atom.
+        pub fn per_atom<V>(&self, positions: &[V]) -> Vec<T>
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            self.centers
+                .iter()
+                .map(|&center| {
+                    self.neighbors
+                        .iter()
+                        .filter(|&&neighbor| neighbor != center)
+                        .fold(T::zero(), |count, &neighbor| {
+                            let distance = displacement(
+                                self.periodic_box.as_ref(),
+                                &positions[center],
+                                &positions[neighbor],
+                            )
+                            .magnitude_squared()
+                            .sqrt();
+                            count + self.switching.evaluate(distance).0
+                        })
+                })
+                .collect()
+        }
+    }
+
+    impl<const N: usize, T, V> CollectiveVariable<T, V> for CoordinationNumber<N, T>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        fn value(&self, positions: &[V]) -> T {
+            let centers = <T as From<f32>>::from(self.centers.len() as f32);
+            self.per_atom(positions)
+                .into_iter()
+                .fold(T::zero(), |sum, count| sum + count)
+                / centers
+        }
+
+        fn value_and_gradient(&self, positions: &[V], gradient: &mut [V]) -> T {
+            gradient.fill_with(V::zero);
+            let centers = <T as From<f32>>::from(self.centers.len() as f32);
+            let mut sum = T::zero();
+            for &center in self.centers.iter() {
+                for &neighbor in self
+                    .neighbors
+                    .iter()
+                    .filter(|&&neighbor| neighbor != center)
+                {
+                    let separation = displacement(
+                        self.periodic_box.as_ref(),
+                        &positions[center],
+                        &positions[neighbor],
+                    );
+                    let distance = separation.clone().magnitude_squared().sqrt();
+                    let (value, slope) = self.switching.evaluate(distance);
+                    sum = sum + value;
+                    if slope != T::zero() && distance > T::zero() {
+                        let direction = separation * (slope / (distance * centers));
+                        gradient[neighbor] += direction.clone();
+                        gradient[center] -= direction;
+                    }
+                }
+            }
+            sum / centers
+        }
+    }
+}
+
+pub use coordination::CoordinationNumber;
+
+mod steinhardt {
+    use lib::core::Vector;
+    use num::Float;
+
+    use super::{CollectiveVariable, SwitchingFunction, displacement};
+    use crate::potential::electrostatics::PeriodicBox;
+
+    /// Returns the Legendre polynomial of degree `degree` and its derivative at `x`.
+    fn legendre<T: Float + From<f32>>(degree: usize, x: T) -> (T, T) {
+        let (mut previous, mut current) = (T::one(), x);
+        let (mut previous_slope, mut current_slope) = (T::zero(), T::one());
+        if degree == 0 {
+            return (previous, previous_slope);
+        }
+        for order in 1..degree {
+            let order_float = <T as From<f32>>::from(order as f32);
+            let next = ((order_float + order_float + T::one()) * x * current
+                - order_float * previous)
+                / (order_float + T::one());
+            let next_slope = previous_slope + (order_float + order_float + T::one()) * current;
+            (previous, current) = (current, next);
+            (previous_slope, current_slope) = (current_slope, next_slope);
+        }
+        (current, current_slope)
+    }
+
+    /// A neighbor of a central atom.
+    struct Neighbor<T, V> {
+        index: usize,
+        distance: T,
+        direction: V,
+        weight: T,
+        slope: T,
+    }
+
+    /// The Steinhardt bond-order parameter `Q_l`, the mean over the central atoms of
+    ///
+    /// `q_l(i) = (4π / (2l + 1) Σ_m |Σ_j s(r_ij) Y_lm(r̂_ij)|²)^(1/2) / Σ_j s(r_ij)`,
+    ///
+    /// where `j` runs over the neighboring atoms weighted by a switching function `s`.
+    /// The addition theorem of the spherical harmonics turns `q_l(i)` into a double sum
+    /// over the neighbors of the Legendre polynomial `P_l` of the cosines between the bonds,
+    /// so the degree `l` is arbitrary; `Q_6` separates crystalline from liquid environments
+    /// and `Q_4` tells the crystal structures apart.
+    ///
+    /// # Panics
+    ///
+    /// Evaluating the variable panics if an index is out of range.
+    #[derive(Clone, Debug)]
+    pub struct Steinhardt<T> {
+        degree: usize,
+        centers: Box<[usize]>,
+        neighbors: Box<[usize]>,
+        switching: SwitchingFunction<T>,
+        periodic_box: Option<PeriodicBox<T, 3>>,
+    }
+
+    impl<T: Float + From<f32>> Steinhardt<T> {
+        /// Constructs the parameter of degree `degree`.
+        pub fn new(
+            degree: usize,
+            centers: Vec<usize>,
+            neighbors: Vec<usize>,
+            switching: SwitchingFunction<T>,
+        ) -> Self {
+            assert!(!centers.is_empty(), "the variable needs central atoms");
+            Self {
+                degree,
+                centers: centers.into_boxed_slice(),
+                neighbors: neighbors.into_boxed_slice(),
+                switching,
+                periodic_box: None,
+            }
+        }
+
+        /// Constructs `Q_4`.
+        pub fn q4(
+            centers: Vec<usize>,
+            neighbors: Vec<usize>,
+            switching: SwitchingFunction<T>,
+        ) -> Self {
+            Self::new(4, centers, neighbors, switching)
+        }
+
+        /// Constructs `Q_6`.
+        pub fn q6(
+            centers: Vec<usize>,
+            neighbors: Vec<usize>,
+            switching: SwitchingFunction<T>,
+        ) -> Self {
+            Self::new(6, centers, neighbors, switching)
+        }
+
+        /// Applies the minimum image convention of `periodic_box` to the distances.
+        pub fn with_box(mut self, periodic_box: PeriodicBox<T, 3>) -> Self {
+            self.periodic_box = Some(periodic_box);
+            self
+        }
+
+        pub fn degree(&self) -> usize {
+            self.degree
+        }
+
+        /// Returns the neighbors of the atom at `center` within the range of the switching function.
+        fn neighbors_of<V>(&self, positions: &[V], center: usize) -> Vec<Neighbor<T, V>>
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            self.neighbors
+                .iter()
+                .filter(|&&neighbor| neighbor != center)
+                .filter_map(|&neighbor| {
+                    let separation = displacement(
+                        self.periodic_box.as_ref(),
+                        &positions[center],
+                        &positions[neighbor],
+                    );
+                    let distance = separation.clone().magnitude_squared().sqrt();
+                    let (weight, slope) = self.switching.evaluate(distance);
+                    (weight > T::zero() && distance > T::zero()).then(|| Neighbor {
+                        index: neighbor,
+                        distance,
+                        direction: separation / distance,
+                        weight,
+                        slope,
+                    })
+                })
+                .collect()
+        }
+
+        /// Returns `Σ_jk w_j w_k P_l(r̂_j · r̂_k)` and `Σ_j w_j` over `neighbors`.
+        fn sums<V>(&self, neighbors: &[Neighbor<T, V>]) -> (T, T)
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            let mut squared = T::zero();
+            for first in neighbors {
+                for second in neighbors {
+                    let cosine = first.direction.clone().dot(second.direction.clone());
+                    squared =
+                        squared + first.weight * second.weight * legendre(self.degree, cosine).0;
+                }
+            }
+            let total = neighbors
+                .iter()
+                .fold(T::zero(), |total, neighbor| total + neighbor.weight);
+            (squared, total)
+        }
+
+        /// Returns `q_l` of every central atom, which is zero for atoms without neighbors.
+        pub fn per_atom<V>(&self, positions: &[V]) -> Vec<T>
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            self.centers
+                .iter()
+                .map(|&center| {
+                    let (squared, total) = self.sums(&self.neighbors_of(positions, center));
+                    if squared > T::zero() && total > T::zero() {
+                        squared.sqrt() / total
+                    } else {
+                        T::zero()
+                    }
+                })
+                .collect()
+        }
+    }
+
+    impl<T, V> CollectiveVariable<T, V> for Steinhardt<T>
+    where
+        T: Float + From<f32>,
+        V: Vector<3, Element = T> + Clone,
+    {
+        fn value(&self, positions: &[V]) -> T {
+            let centers = <T as From<f32>>::from(self.centers.len() as f32);
+            self.per_atom(positions)
+                .into_iter()
+                .fold(T::zero(), |sum, q| sum + q)
+                / centers
+        }
+
+        fn value_and_gradient(&self, positions: &[V], gradient: &mut [V]) -> T {
+            gradient.fill_with(V::zero);
+            let centers = <T as From<f32>>::from(self.centers.len() as f32);
+            let two = <T as From<f32>>::from(2.0);
+            let mut sum = T::zero();
+            for &center in self.centers.iter() {
+                let neighbors = self.neighbors_of(positions, center);
+                let (squared, total) = self.sums(&neighbors);
+                if squared <= T::zero() || total <= T::zero() {
+                    continue;
+                }
+                let root = squared.sqrt();
+                sum = sum + root / total;
+                // q = √S / W, so dq = dS / (2 √S W) - √S dW / W².
+                let squared_factor = T::one() / (two * root * total * centers);
+                let total_factor = root / (total * total * centers);
+                for first in &neighbors {
+                    let mut weight_sum = T::zero();
+                    let mut angular = V::zero();
+                    for second in &neighbors {
+                        let cosine = first.direction.clone().dot(second.direction.clone());
+                        let (polynomial, polynomial_slope) = legendre(self.degree, cosine);
+                        weight_sum = weight_sum + second.weight * polynomial;
+                        // The derivative of the cosine with respect to the first neighbor.
+                        let tangent = (second.direction.clone() - first.direction.clone() * cosine)
+                            / first.distance;
+                        angular.mul_add_assign(&tangent, second.weight * polynomial_slope);
+                    }
+                    let radial = first.direction.clone()
+                        * (first.slope * (squared_factor * two * weight_sum - total_factor));
+                    let force = radial + angular * (squared_factor * two * first.weight);
+                    gradient[first.index] += force.clone();
+                    gradient[center] -= force;
+                }
+            }
+            sum / centers
+        }
+    }
+}
+
+pub use steinhardt::Steinhardt;
+
+mod bias {
+    use std::convert::Infallible;
+
+    use lib::{
+        core::Vector,
+        potential::{GroupInTypeInImage, physical::PhysicalPotential},
+    };
+    use num::Float;
+
+    use super::CollectiveVariable;
+
+    /// A bias potential acting on the value of a collective variable,
+    /// e.g. an umbrella or the sum of the Gaussians deposited by metadynamics.
+    pub trait Bias<T> {
+        /// Returns the bias and its derivative at the value `value` of the variable.
+        fn evaluate(&self, value: T) -> (T, T);
+    }
+
+    /// The harmonic umbrella `k (s - s_0)² / 2`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct HarmonicBias<T> {
+        pub center: T,
+        pub force_constant: T,
+    }
+
+    impl<T: Float + From<f32>> Bias<T> for HarmonicBias<T> {
+        fn evaluate(&self, value: T) -> (T, T) {
+            let deviation = value - self.center;
+            (
+                self.force_constant * deviation * deviation / <T as From<f32>>::from(2.0),
+                self.force_constant * deviation,
+            )
+        }
+    }
+
+    /// A physical potential biasing a collective variable of a group.
+    ///
+    /// Like other biases, its energy is excluded from the reported physical potential energy
+    /// unless requested otherwise with [`Biased::reporting_energy`]. The value of the variable
+    /// in the latest evaluation is kept, so the same variable serves as an observable.
+    pub struct Biased<const N: usize, T, V, C, B> {
+        variable: C,
+        bias: B,
+        report_energy: bool,
+        value: Option<T>,
+        gradient: Vec<V>,
+    }
+
+    impl<const N: usize, T, V, C, B> Biased<N, T, V, C, B>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+        C: CollectiveVariable<T, V>,
+        B: Bias<T>,
+    {
+        pub fn new(variable: C, bias: B) -> Self {
+            Self {
+                variable,
+                bias,
+                report_energy: false,
+                value: None,
+                gradient: Vec::new(),
+            }
+        }
+
+        /// Includes the energy of the bias in the reported physical potential energy.
+        pub fn reporting_energy(mut self) -> Self {
+            self.report_energy = true;
+            self
+        }
+
+        pub fn variable(&self) -> &C {
+            &self.variable
+        }
+
+        pub fn bias(&self) -> &B {
+            &self.bias
+        }
+
+        /// Returns the bias for modification, e.g. to deposit a Gaussian.
+        pub fn bias_mut(&mut self) -> &mut B {
+            &mut self.bias
+        }
+
+        /// Returns the value of the variable in the latest evaluation, if any.
+        pub fn value(&self) -> Option<T> {
+            self.value
+        }
+
+        /// Evaluates the bias and adds its forces to `group_forces`, if given.
+        fn apply(&mut self, positions: &[V], group_forces: Option<&mut [V]>) -> T {
+            let value = match group_forces {
+                Some(group_forces) => {
+                    self.gradient.resize(positions.len(), V::zero());
+                    let value = self
+                        .variable
+                        .value_and_gradient(positions, &mut self.gradient);
+                    let (_, slope) = self.bias.evaluate(value);
+                    for (force, gradient) in group_forces.iter_mut().zip(&self.gradient) {
+                        force.mul_add_assign(gradient, -slope);
+                    }
+                    value
+                }
+                None => self.variable.value(positions),
+            };
+            self.value = Some(value);
+            if self.report_energy {
+                self.bias.evaluate(value).0
+            } else {
+                T::zero()
+            }
+        }
+    }
+
+    impl<const N: usize, T, V, C, B> PhysicalPotential<T, V> for Biased<N, T, V, C, B>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+        C: CollectiveVariable<T, V>,
+        B: Bias<T>,
+    {
+        type Error = Infallible;
+
+        fn calculate_potential_set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            group_forces.fill_with(V::zero);
+            Ok(self.apply(positions.read(), Some(group_forces)))
+        }
+
+        fn calculate_potential_add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            Ok(self.apply(positions.read(), Some(group_forces)))
+        }
+
+        fn calculate_potential(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+        ) -> Result<T, Self::Error> {
+            Ok(self.apply(positions.read(), None))
+        }
+
+        fn set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            group_forces.fill_with(V::zero);
+            self.apply(positions.read(), Some(group_forces));
+            Ok(())
+        }
+
+        fn add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            self.apply(positions.read(), Some(group_forces));
+            Ok(())
+        }
+    }
+}
+
+pub use bias::{Bias, Biased, HarmonicBias};
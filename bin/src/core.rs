@@ -1,6 +1,7 @@
 pub mod constants {
     pub const REDUCED_PLANK_CONSTANT: f32 = 1.0;
     pub const BOLTZMANN_CONSTANT: f32 = 1.0;
+    pub const COULOMB_CONSTANT: f32 = 1.0;
 }
 
 mod unimplemented {
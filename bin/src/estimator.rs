@@ -1,2 +1,13 @@
 pub mod classical;
+pub mod composite;
+pub mod export;
+pub mod free_energy;
+pub mod permutation;
+pub mod pipeline;
 pub mod quantum;
+pub mod reweight;
+pub mod structure;
+pub mod superfluid;
+pub mod transport;
+pub mod trotter;
+pub mod widom;
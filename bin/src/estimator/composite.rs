@@ -0,0 +1,253 @@
+mod jackknife {
+    use num::Float;
+
+    /// Block averages of two observables sampled at the same steps, kept in pairs
+    /// so that functions of both averages can be given jackknife error bars
+    /// which account for the covariance of the observables.
+    #[derive(Clone, Debug)]
+    pub struct PairedBlocks<T> {
+        block_size: usize,
+        sums: (T, T),
+        count: usize,
+        block_means: Vec<(T, T)>,
+    }
+
+    impl<T: Float + From<f32>> PairedBlocks<T> {
+        pub fn new(block_size: usize) -> Self {
+            assert!(block_size > 0, "the block size must be positive");
+            Self {
+                block_size,
+                sums: (T::zero(), T::zero()),
+                count: 0,
+                block_means: Vec::new(),
+            }
+        }
+
+        pub fn add(&mut self, first: T, second: T) {
+            self.sums = (self.sums.0 + first, self.sums.1 + second);
+            self.count += 1;
+            if self.count == self.block_size {
+                let size = <T as From<f32>>::from(self.block_size as f32);
+                self.block_means
+                    .push((self.sums.0 / size, self.sums.1 / size));
+                self.sums = (T::zero(), T::zero());
+                self.count = 0;
+            }
+        }
+
+        /// Returns the number of completed blocks.
+        pub fn blocks(&self) -> usize {
+            self.block_means.len()
+        }
+
+        /// Returns the jackknife estimate of `function` of the means of both observables,
+        /// corrected for the bias of a nonlinear function, and its standard error,
+        /// or `None` if fewer than two blocks are completed.
+        ///
+        /// Every block is left out in turn, and the spread of `function` of the means
+        /// of the remaining blocks gives the error bar.
+        pub fn jackknife<F: Fn(T, T) -> T>(&self, function: F) -> Option<(T, T)> {
+            let blocks = self.block_means.len();
+            if blocks < 2 {
+                return None;
+            }
+            let cast = |value: usize| <T as From<f32>>::from(value as f32);
+            let sums = self
+                .block_means
+                .iter()
+                .fold((T::zero(), T::zero()), |sums, &(first, second)| {
+                    (sums.0 + first, sums.1 + second)
+                });
+            let full = function(sums.0 / cast(blocks), sums.1 / cast(blocks));
+            let left_out: Vec<T> = self
+                .block_means
+                .iter()
+                .map(|&(first, second)| {
+                    function(
+                        (sums.0 - first) / cast(blocks - 1),
+                        (sums.1 - second) / cast(blocks - 1),
+                    )
+                })
+                .collect();
+            let mean = left_out.iter().fold(T::zero(), |sum, &value| sum + value) / cast(blocks);
+            let squared_deviations = left_out.iter().fold(T::zero(), |sum, &value| {
+                sum + (value - mean) * (value - mean)
+            });
+            let estimate = cast(blocks) * full - cast(blocks - 1) * mean;
+            let error = (squared_deviations * cast(blocks - 1) / cast(blocks)).sqrt();
+            Some((estimate, error))
+        }
+
+        pub fn reset(&mut self) {
+            *self = Self::new(self.block_size);
+        }
+    }
+}
+
+pub use jackknife::PairedBlocks;
+
+mod combination {
+    use lib::output::Dimension;
+    use num::Float;
+
+    /// A function combining the averages of two observables into a derived quantity.
+    pub trait Combination<T> {
+        fn combine(first: T, second: T) -> T;
+
+        fn dimension(first: Dimension, second: Dimension) -> Dimension;
+    }
+
+    /// The ratio of the average of the first observable to that of the second.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Ratio;
+
+    impl<T: Float> Combination<T> for Ratio {
+        fn combine(first: T, second: T) -> T {
+            first / second
+        }
+
+        fn dimension(first: Dimension, second: Dimension) -> Dimension {
+            first / second
+        }
+    }
+
+    /// The product of the averages of both observables.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Product;
+
+    impl<T: Float> Combination<T> for Product {
+        fn combine(first: T, second: T) -> T {
+            first * second
+        }
+
+        fn dimension(first: Dimension, second: Dimension) -> Dimension {
+            first * second
+        }
+    }
+}
+
+pub use combination::{Combination, Product, Ratio};
+
+mod estimator {
+    use super::{Combination, PairedBlocks, Product, Ratio};
+    use lib::output::{Dimension, ObservableRegistry, ValuesOutput};
+    use num::Float;
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        marker::PhantomData,
+    };
+
+    /// An observable named in a composite estimator is not in the registry.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct UnknownObservable(pub String);
+
+    impl Display for UnknownObservable {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            write!(f, "no observable {:?} is registered", self.0)
+        }
+    }
+
+    impl Error for UnknownObservable {}
+
+    /// An estimator of a combination of the averages of two registered observables,
+    /// e.g. `⟨A⟩ / ⟨B⟩`, with an error bar from a jackknife over blocks of samples.
+    ///
+    /// Both observables are sampled at the same steps, so the error bar accounts
+    /// for their covariance, which propagating the errors of the two averages
+    /// separately would miss.
+    #[derive(Clone, Debug)]
+    pub struct CompositeEstimator<T, C> {
+        columns: (usize, usize),
+        dimension: Dimension,
+        blocks: PairedBlocks<T>,
+        combination: PhantomData<C>,
+    }
+
+    /// The ratio `⟨A⟩ / ⟨B⟩` of the averages of two observables.
+    pub type RatioEstimator<T> = CompositeEstimator<T, Ratio>;
+
+    /// The product `⟨A⟩ ⟨B⟩` of the averages of two observables.
+    pub type ProductEstimator<T> = CompositeEstimator<T, Product>;
+
+    impl<T, C> CompositeEstimator<T, C>
+    where
+        T: Float + From<f32>,
+        C: Combination<T>,
+    {
+        /// Constructs an estimator combining the observables named `first` and `second`
+        /// in `registry`, whose samples are averaged in blocks of `block_size`.
+        pub fn new(
+            registry: &ObservableRegistry,
+            first: &str,
+            second: &str,
+            block_size: usize,
+        ) -> Result<Self, UnknownObservable> {
+            let find = |name: &str| {
+                registry
+                    .entries()
+                    .iter()
+                    .position(|(registered, _)| registered == name)
+                    .ok_or_else(|| UnknownObservable(name.to_owned()))
+            };
+            let columns = (find(first)?, find(second)?);
+            let entries = registry.entries();
+            Ok(Self {
+                columns,
+                dimension: C::dimension(entries[columns.0].1, entries[columns.1].1),
+                blocks: PairedBlocks::new(block_size),
+                combination: PhantomData,
+            })
+        }
+
+        /// Adds the samples of both observables from `values`, the values of every
+        /// registered observable at a step in the order of their columns.
+        pub fn add(&mut self, values: &[T]) {
+            self.blocks
+                .add(values[self.columns.0], values[self.columns.1]);
+        }
+
+        /// Returns the bias-corrected estimate, or `None` before two blocks are completed.
+        pub fn estimate(&self) -> Option<T> {
+            self.blocks
+                .jackknife(C::combine)
+                .map(|(estimate, _)| estimate)
+        }
+
+        /// Returns the jackknife standard error of the estimate,
+        /// or `None` before two blocks are completed.
+        pub fn standard_error(&self) -> Option<T> {
+            self.blocks.jackknife(C::combine).map(|(_, error)| error)
+        }
+
+        pub fn blocks(&self) -> &PairedBlocks<T> {
+            &self.blocks
+        }
+
+        /// Registers the values written by [`CompositeEstimator::write_values`] under `name`.
+        pub fn register_values(&self, registry: &mut ObservableRegistry, name: &str) {
+            registry.register(name, self.dimension);
+            registry.register(&format!("{}_error", name), self.dimension);
+        }
+
+        /// Writes the estimate and its standard error,
+        /// either of which is NaN until enough blocks are completed.
+        pub fn write_values<O>(&self, stream: &mut O) -> Result<(), O::Error>
+        where
+            O: ValuesOutput<T> + ?Sized,
+        {
+            let (estimate, error) = self
+                .blocks
+                .jackknife(C::combine)
+                .unwrap_or((T::nan(), T::nan()));
+            stream.write_value(estimate)?;
+            stream.write_value(error)
+        }
+
+        pub fn reset(&mut self) {
+            self.blocks.reset();
+        }
+    }
+}
+
+pub use estimator::{CompositeEstimator, ProductEstimator, RatioEstimator, UnknownObservable};
@@ -0,0 +1,265 @@
+mod recorder {
+    use num::Float;
+    use std::ops::{Bound, RangeBounds};
+
+    /// The statistics of an observable over the recorded samples of a range of steps.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct ObservableSummary<T> {
+        pub name: String,
+        pub samples: usize,
+        pub first_step: Option<usize>,
+        pub last_step: Option<usize>,
+        /// The value at the last step.
+        pub last: Option<T>,
+        pub mean: Option<T>,
+        /// The unbiased variance of the samples, given at least two.
+        pub variance: Option<T>,
+        /// The standard error of the mean for uncorrelated samples, given at least two.
+        pub standard_error: Option<T>,
+        pub min: Option<T>,
+        pub max: Option<T>,
+    }
+
+    /// The statistics of every observable at some point of a run.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct ObservablesExport<T> {
+        /// The last step recorded when the statistics were exported.
+        pub step: Option<usize>,
+        pub observables: Vec<ObservableSummary<T>>,
+    }
+
+    impl<T> ObservablesExport<T> {
+        /// Returns the statistics of the observable named `name`.
+        pub fn get(&self, name: &str) -> Option<&ObservableSummary<T>> {
+            self.observables
+                .iter()
+                .find(|observable| observable.name == name)
+        }
+    }
+
+    /// The values of the observables recorded by the main thread at the end of every step
+    /// and kept until they have been written to the output, so that analysis scripts can
+    /// inspect their statistics while the run goes on, e.g. to decide on early termination.
+    #[derive(Clone, Debug)]
+    pub struct ObservableRecorder<T> {
+        names: Vec<String>,
+        steps: Vec<usize>,
+        values: Vec<Vec<T>>,
+        flushed: usize,
+        last_step: Option<usize>,
+    }
+
+    impl<T: Float + From<f32>> ObservableRecorder<T> {
+        /// Constructs a recorder of the observables named `names`, in the order of their values.
+        ///
+        /// # Panics
+        ///
+        /// Panics if two observables share a name.
+        pub fn new(names: &[&str]) -> Self {
+            for (index, name) in names.iter().enumerate() {
+                assert!(
+                    !names[..index].contains(name),
+                    "every observable must have a distinct name"
+                );
+            }
+            Self {
+                names: names.iter().map(|&name| name.to_owned()).collect(),
+                steps: Vec::new(),
+                values: vec![Vec::new(); names.len()],
+                flushed: 0,
+                last_step: None,
+            }
+        }
+
+        /// Records the values of every observable at `step`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if there is not a value for every observable
+        /// or `step` does not follow the last recorded step.
+        pub fn record(&mut self, step: usize, values: &[T]) {
+            assert_eq!(
+                values.len(),
+                self.names.len(),
+                "there must be a value for every observable"
+            );
+            assert!(
+                self.last_step.is_none_or(|last| step > last),
+                "the steps must be recorded in order"
+            );
+            self.last_step = Some(step);
+            self.steps.push(step);
+            for (samples, &value) in self.values.iter_mut().zip(values) {
+                samples.push(value);
+            }
+        }
+
+        /// Returns the number of steps recorded since the last flush.
+        pub fn pending(&self) -> usize {
+            self.steps.len() - self.flushed
+        }
+
+        /// Returns the steps recorded since the last flush and the values of every observable
+        /// at those steps, to be written to the output before calling [`ObservableRecorder::flush`].
+        pub fn unflushed(&self) -> (&[usize], Vec<&[T]>) {
+            (
+                &self.steps[self.flushed..],
+                self.values
+                    .iter()
+                    .map(|samples| &samples[self.flushed..])
+                    .collect(),
+            )
+        }
+
+        /// Marks every recorded step as written to the output.
+        ///
+        /// The samples are kept, so that statistics can still be exported over them,
+        /// unless `discard` is `true`, in which case they are dropped to bound the memory.
+        pub fn flush(&mut self, discard: bool) {
+            if discard {
+                self.steps.clear();
+                for samples in &mut self.values {
+                    samples.clear();
+                }
+            }
+            self.flushed = self.steps.len();
+        }
+
+        /// Returns the statistics of every observable over the kept samples
+        /// whose steps lie within `range`, without disturbing the recording.
+        pub fn export_observables<R: RangeBounds<usize>>(&self, range: R) -> ObservablesExport<T> {
+            let start = match range.start_bound() {
+                Bound::Included(&start) => self.steps.partition_point(|&step| step < start),
+                Bound::Excluded(&start) => self.steps.partition_point(|&step| step <= start),
+                Bound::Unbounded => 0,
+            };
+            let end = match range.end_bound() {
+                Bound::Included(&end) => self.steps.partition_point(|&step| step <= end),
+                Bound::Excluded(&end) => self.steps.partition_point(|&step| step < end),
+                Bound::Unbounded => self.steps.len(),
+            };
+            let end = end.max(start);
+            let steps = &self.steps[start..end];
+            ObservablesExport {
+                step: self.last_step,
+                observables: self
+                    .names
+                    .iter()
+                    .zip(&self.values)
+                    .map(|(name, samples)| summarize(name, steps, &samples[start..end]))
+                    .collect(),
+            }
+        }
+    }
+
+    fn summarize<T: Float + From<f32>>(
+        name: &str,
+        steps: &[usize],
+        samples: &[T],
+    ) -> ObservableSummary<T> {
+        let cast = |value: usize| <T as From<f32>>::from(value as f32);
+        let count = samples.len();
+        let mean = (count > 0)
+            .then(|| samples.iter().fold(T::zero(), |sum, &value| sum + value) / cast(count));
+        let variance = mean.filter(|_| count > 1).map(|mean| {
+            samples.iter().fold(T::zero(), |sum, &value| {
+                sum + (value - mean) * (value - mean)
+            }) / cast(count - 1)
+        });
+        ObservableSummary {
+            name: name.to_owned(),
+            samples: count,
+            first_step: steps.first().copied(),
+            last_step: steps.last().copied(),
+            last: samples.last().copied(),
+            mean,
+            variance,
+            standard_error: variance.map(|variance| (variance / cast(count)).sqrt()),
+            min: samples.iter().copied().reduce(T::min),
+            max: samples.iter().copied().reduce(T::max),
+        }
+    }
+}
+
+pub use recorder::{ObservableRecorder, ObservableSummary, ObservablesExport};
+
+mod json {
+    use std::fmt::{Display, Write};
+
+    use num::Float;
+
+    use super::{ObservableSummary, ObservablesExport};
+
+    fn write_string(json: &mut String, string: &str) {
+        json.push('"');
+        for character in string.chars() {
+            match character {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                character if character.is_control() => {
+                    let _ = write!(json, "\\u{:04x}", character as u32);
+                }
+                character => json.push(character),
+            }
+        }
+        json.push('"');
+    }
+
+    // JSON has no representation of infinities and NaN, which become `null`.
+    fn write_number<T: Float + Display>(json: &mut String, value: Option<T>) {
+        match value.filter(|value| value.is_finite()) {
+            Some(value) => {
+                let _ = write!(json, "{}", value);
+            }
+            None => json.push_str("null"),
+        }
+    }
+
+    fn write_step(json: &mut String, step: Option<usize>) {
+        match step {
+            Some(step) => {
+                let _ = write!(json, "{}", step);
+            }
+            None => json.push_str("null"),
+        }
+    }
+
+    fn write_summary<T: Float + Display>(json: &mut String, summary: &ObservableSummary<T>) {
+        json.push_str("{\"name\":");
+        write_string(json, &summary.name);
+        let _ = write!(json, ",\"samples\":{}", summary.samples);
+        json.push_str(",\"first_step\":");
+        write_step(json, summary.first_step);
+        json.push_str(",\"last_step\":");
+        write_step(json, summary.last_step);
+        for (key, value) in [
+            ("last", summary.last),
+            ("mean", summary.mean),
+            ("variance", summary.variance),
+            ("standard_error", summary.standard_error),
+            ("min", summary.min),
+            ("max", summary.max),
+        ] {
+            let _ = write!(json, ",\"{}\":", key);
+            write_number(json, value);
+        }
+        json.push('}');
+    }
+
+    impl<T: Float + Display> ObservablesExport<T> {
+        /// Serializes the statistics as a single JSON object.
+        pub fn to_json(&self) -> String {
+            let mut json = String::from("{\"step\":");
+            write_step(&mut json, self.step);
+            json.push_str(",\"observables\":[");
+            for (index, summary) in self.observables.iter().enumerate() {
+                if index > 0 {
+                    json.push(',');
+                }
+                write_summary(&mut json, summary);
+            }
+            json.push_str("]}");
+            json
+        }
+    }
+}
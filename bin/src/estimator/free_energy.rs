@@ -0,0 +1,391 @@
+mod error {
+    use lib::{core::error::NotConvergedError, numeric::iterate::IterationError};
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+    };
+
+    /// An error arising when estimating free energies.
+    #[derive(Clone, Copy, Debug)]
+    pub enum FreeEnergyError {
+        /// A state has no samples, or no state has been defined.
+        NoSamples,
+        /// The energy distributions of the states do not overlap.
+        NoOverlap,
+        /// The self-consistent iteration has not converged.
+        NotConverged(NotConvergedError<f64>),
+    }
+
+    impl Display for FreeEnergyError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::NoSamples => write!(f, "a state has no samples"),
+                Self::NoOverlap => write!(f, "the states do not overlap"),
+                Self::NotConverged(err) => write!(f, "free energies have {}", err),
+            }
+        }
+    }
+
+    impl From<IterationError<f64, FreeEnergyError>> for FreeEnergyError {
+        fn from(value: IterationError<f64, FreeEnergyError>) -> Self {
+            match value {
+                IterationError::Map(err) => err,
+                IterationError::Diverged(_) => Self::NoOverlap,
+                IterationError::NotConverged(err, _) => Self::NotConverged(err),
+            }
+        }
+    }
+
+    impl Error for FreeEnergyError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::NotConverged(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+}
+
+pub use error::FreeEnergyError;
+
+/// Returns `ln Σ exp(x)` without overflowing.
+pub(crate) fn log_sum_exp<I: IntoIterator<Item = f64>>(values: I) -> f64 {
+    let values: Vec<f64> = values.into_iter().collect();
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values
+        .iter()
+        .map(|value| (value - max).exp())
+        .sum::<f64>()
+        .ln()
+}
+
+mod bar {
+    use super::FreeEnergyError;
+
+    /// Returns the Fermi function `1 / (1 + exp(x))`.
+    fn fermi(x: f64) -> f64 {
+        if x > 0.0 {
+            let e = (-x).exp();
+            e / (1.0 + e)
+        } else {
+            1.0 / (1.0 + x.exp())
+        }
+    }
+
+    /// Estimates the reduced free energy difference `f1 - f0` between two states
+    /// with the Bennett acceptance ratio and returns it with its standard deviation.
+    ///
+    /// `forward` holds `u1 - u0` of samples drawn from state 0
+    /// and `reverse` holds `u0 - u1` of samples drawn from state 1,
+    /// both in units of `kT`.
+    pub fn bar(forward: &[f64], reverse: &[f64]) -> Result<(f64, f64), FreeEnergyError> {
+        if forward.is_empty() || reverse.is_empty() {
+            return Err(FreeEnergyError::NoSamples);
+        }
+        let (n_forward, n_reverse) = (forward.len() as f64, reverse.len() as f64);
+        let shift = (n_forward / n_reverse).ln();
+        // Negative below the solution and positive above it.
+        let imbalance = |difference: f64| {
+            forward
+                .iter()
+                .map(|work| fermi(shift + work - difference))
+                .sum::<f64>()
+                - reverse
+                    .iter()
+                    .map(|work| fermi(-shift + work + difference))
+                    .sum::<f64>()
+        };
+        let (mut lower, mut upper) = forward.iter().chain(reverse).fold(
+            (f64::INFINITY, f64::NEG_INFINITY),
+            |(lower, upper), work| (lower.min(-work.abs()), upper.max(work.abs())),
+        );
+        lower -= 1.0 + shift.abs();
+        upper += 1.0 + shift.abs();
+        if imbalance(lower) > 0.0 || imbalance(upper) < 0.0 {
+            return Err(FreeEnergyError::NoOverlap);
+        }
+        for _ in 0..200 {
+            let middle = (lower + upper) / 2.0;
+            if imbalance(middle) < 0.0 {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+        let difference = (lower + upper) / 2.0;
+        let relative_variance = |values: &mut dyn Iterator<Item = f64>, n: f64| {
+            let (sum, sum_squared) = values.fold((0.0, 0.0), |(sum, sum_squared), value| {
+                (sum + value, sum_squared + value * value)
+            });
+            let mean = sum / n;
+            (sum_squared / n / (mean * mean) - 1.0) / n
+        };
+        let variance = relative_variance(
+            &mut forward.iter().map(|work| fermi(shift + work - difference)),
+            n_forward,
+        ) + relative_variance(
+            &mut reverse.iter().map(|work| fermi(-shift + work + difference)),
+            n_reverse,
+        );
+        if !variance.is_finite() {
+            return Err(FreeEnergyError::NoOverlap);
+        }
+        Ok((difference, variance.max(0.0).sqrt()))
+    }
+}
+
+pub use bar::bar;
+
+mod mbar {
+    use super::{FreeEnergyError, log_sum_exp};
+    use lib::{
+        core::tolerance::TolerancePolicy,
+        numeric::{iterate::FixedPoint, linalg::SymmetricEigen},
+    };
+
+    /// Free energies of a set of states relative to the first one, with their covariance.
+    #[derive(Clone, Debug)]
+    pub struct FreeEnergies {
+        free_energies: Vec<f64>,
+        covariance: Vec<f64>,
+    }
+
+    impl FreeEnergies {
+        pub fn states(&self) -> usize {
+            self.free_energies.len()
+        }
+
+        /// Returns the reduced free energies of all states, with that of the first state being 0.
+        pub fn free_energies(&self) -> &[f64] {
+            &self.free_energies
+        }
+
+        /// Returns the reduced free energy difference `f_to - f_from` and its standard deviation.
+        ///
+        /// # Panics
+        ///
+        /// Panics if either state is out of bounds.
+        pub fn difference(&self, from: usize, to: usize) -> (f64, f64) {
+            let n = self.states();
+            let variance = self.covariance[from * n + from] + self.covariance[to * n + to]
+                - self.covariance[from * n + to]
+                - self.covariance[to * n + from];
+            (
+                self.free_energies[to] - self.free_energies[from],
+                variance.max(0.0).sqrt(),
+            )
+        }
+    }
+
+    /// The multistate Bennett acceptance ratio estimator.
+    ///
+    /// Every frame is recorded with the state it was sampled from and
+    /// its reduced energies `u_k = β_k U_k` evaluated in all states.
+    #[derive(Clone, Debug)]
+    pub struct Mbar {
+        states: usize,
+        samples: Vec<usize>,
+        /// The reduced energies of every frame in all states, frame-major.
+        reduced_energies: Vec<f64>,
+    }
+
+    impl Mbar {
+        pub fn new(states: usize) -> Self {
+            Self {
+                states,
+                samples: vec![0; states],
+                reduced_energies: Vec::new(),
+            }
+        }
+
+        pub fn states(&self) -> usize {
+            self.states
+        }
+
+        pub fn frames(&self) -> usize {
+            self.reduced_energies.len() / self.states.max(1)
+        }
+
+        /// Records a frame sampled from `state` with the reduced energies `reduced_energies`,
+        /// one per state.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `state` is out of bounds or the number of energies
+        /// differs from the number of states.
+        pub fn record(&mut self, state: usize, reduced_energies: &[f64]) {
+            assert!(state < self.states, "state out of bounds");
+            assert_eq!(
+                reduced_energies.len(),
+                self.states,
+                "every frame must be evaluated in all states"
+            );
+            self.samples[state] += 1;
+            self.reduced_energies.extend_from_slice(reduced_energies);
+        }
+
+        fn frame(&self, frame: usize) -> &[f64] {
+            &self.reduced_energies[frame * self.states..(frame + 1) * self.states]
+        }
+
+        /// Returns `ln Σ_k N_k exp(f_k - u_k)` of `frame`.
+        fn log_denominator(&self, frame: usize, free_energies: &[f64]) -> f64 {
+            log_sum_exp(
+                self.frame(frame)
+                    .iter()
+                    .zip(free_energies)
+                    .zip(&self.samples)
+                    .filter(|&(_, &samples)| samples > 0)
+                    .map(|((&energy, &free_energy), &samples)| {
+                        (samples as f64).ln() + free_energy - energy
+                    }),
+            )
+        }
+
+        /// Solves the self-consistent MBAR equations until the largest change of a free energy
+        /// in an iteration is tolerated, and estimates their covariance.
+        ///
+        /// The scale of the solution is the largest free energy.
+        pub fn solve(
+            &self,
+            tolerance: &TolerancePolicy<f64>,
+        ) -> Result<FreeEnergies, FreeEnergyError> {
+            let (k, frames) = (self.states, self.frames());
+            if k == 0 || self.samples.contains(&0) {
+                return Err(FreeEnergyError::NoSamples);
+            }
+            let mut free_energies = vec![0.0; k];
+            let mut log_denominators = vec![0.0; frames];
+            FixedPoint::new(*tolerance)
+                .solve(&mut free_energies, |free_energies, updated| {
+                    for (frame, log_denominator) in log_denominators.iter_mut().enumerate() {
+                        *log_denominator = self.log_denominator(frame, free_energies);
+                    }
+                    for (state, updated) in updated.iter_mut().enumerate() {
+                        *updated = -log_sum_exp(log_denominators.iter().enumerate().map(
+                            |(frame, &log_denominator)| -self.frame(frame)[state] - log_denominator,
+                        ));
+                    }
+                    let reference = updated[0];
+                    updated
+                        .iter_mut()
+                        .for_each(|free_energy| *free_energy -= reference);
+                    if updated.iter().all(|free_energy| free_energy.is_finite()) {
+                        Ok(())
+                    } else {
+                        Err(FreeEnergyError::NoOverlap)
+                    }
+                })
+                .map_err(FreeEnergyError::from)?;
+            let covariance = self.covariance(&free_energies);
+            Ok(FreeEnergies {
+                free_energies,
+                covariance,
+            })
+        }
+
+        /// Estimates the asymptotic covariance `Θ = (I - WᵀWN)⁺ WᵀW` of the free energies.
+        fn covariance(&self, free_energies: &[f64]) -> Vec<f64> {
+            let k = self.states;
+            // WᵀW, where W_nk = exp(f_k - u_kn) / Σ_j N_j exp(f_j - u_jn).
+            let mut gram = vec![0.0; k * k];
+            for frame in 0..self.frames() {
+                let log_denominator = self.log_denominator(frame, free_energies);
+                let weights: Vec<f64> = self
+                    .frame(frame)
+                    .iter()
+                    .zip(free_energies)
+                    .map(|(&energy, &free_energy)| (free_energy - energy - log_denominator).exp())
+                    .collect();
+                for i in 0..k {
+                    for j in 0..k {
+                        gram[i * k + j] += weights[i] * weights[j];
+                    }
+                }
+            }
+            // I - WᵀWN is similar to the symmetric I - N^½ WᵀW N^½,
+            // whose pseudo-inverse yields a generalized inverse of the former.
+            let sqrt_samples: Vec<f64> = self.samples.iter().map(|&n| (n as f64).sqrt()).collect();
+            let symmetric: Vec<f64> = (0..k * k)
+                .map(|index| {
+                    let (i, j) = (index / k, index % k);
+                    let identity = if i == j { 1.0 } else { 0.0 };
+                    identity - sqrt_samples[i] * gram[index] * sqrt_samples[j]
+                })
+                .collect();
+            let pseudo_inverse = SymmetricEigen::new(&symmetric, k).pseudo_inverse(1e-10);
+            // Θ = N^-½ B⁺ N^½ WᵀW.
+            let mut covariance = vec![0.0; k * k];
+            for i in 0..k {
+                for j in 0..k {
+                    covariance[i * k + j] = (0..k)
+                        .map(|m| pseudo_inverse[i * k + m] * sqrt_samples[m] * gram[m * k + j])
+                        .sum::<f64>()
+                        / sqrt_samples[i];
+                }
+            }
+            covariance
+        }
+    }
+}
+
+pub use mbar::{FreeEnergies, Mbar};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib::core::tolerance::TolerancePolicy;
+    use std::f64::consts::TAU;
+
+    /// Draws `count` positions of each of two harmonic wells of stiffness `stiffnesses`
+    /// at `kT = 1` and returns them with their reduced energies in both wells.
+    fn sample_wells(stiffnesses: [f64; 2], count: usize) -> [Vec<[f64; 2]>; 2] {
+        let mut state = 3u64;
+        let mut uniform = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((state >> 11) + 1) as f64 / 2f64.powi(53)
+        };
+        stiffnesses.map(|stiffness| {
+            (0..count)
+                .map(|_| {
+                    let normal = (-2.0 * uniform().ln()).sqrt() * (TAU * uniform()).cos();
+                    let position = normal / stiffness.sqrt();
+                    stiffnesses.map(|stiffness| 0.5 * stiffness * position * position)
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn two_state_mbar_reproduces_bar_on_harmonic_wells() {
+        let stiffnesses: [f64; 2] = [1.0, 3.0];
+        // The partition functions are `√(2π / k)`.
+        let exact = 0.5 * (stiffnesses[1] / stiffnesses[0]).ln();
+        let [first, second] = sample_wells(stiffnesses, 4000);
+
+        let forward: Vec<f64> = first.iter().map(|u| u[1] - u[0]).collect();
+        let reverse: Vec<f64> = second.iter().map(|u| u[0] - u[1]).collect();
+        let (difference, error) = bar(&forward, &reverse).unwrap();
+        assert!(error > 0.0 && error < 0.05);
+        assert!((difference - exact).abs() < 4.0 * error);
+
+        let mut mbar = Mbar::new(2);
+        for energies in &first {
+            mbar.record(0, energies);
+        }
+        for energies in &second {
+            mbar.record(1, energies);
+        }
+        let free_energies = mbar
+            .solve(&TolerancePolicy::absolute(1e-12, 10_000))
+            .unwrap();
+        let (mbar_difference, mbar_error) = free_energies.difference(0, 1);
+        assert!((mbar_difference - difference).abs() < 1e-8);
+        assert!((mbar_error - error).abs() < 1e-3 * error);
+    }
+}
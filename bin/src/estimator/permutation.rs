@@ -0,0 +1,116 @@
+mod cycles {
+    /// Returns the lengths of the cycles of `permutation`, in the order of their smallest atoms.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permutation` is not a permutation of `0..permutation.len()`.
+    pub fn cycle_lengths(permutation: &[usize]) -> Vec<usize> {
+        let mut visited = vec![false; permutation.len()];
+        let mut lengths = Vec::new();
+        for start in 0..permutation.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut length = 0;
+            let mut atom = start;
+            while !visited[atom] {
+                visited[atom] = true;
+                length += 1;
+                atom = permutation[atom];
+            }
+            assert_eq!(atom, start, "not a permutation");
+            lengths.push(length);
+        }
+        lengths
+    }
+}
+
+pub use cycles::cycle_lengths;
+
+mod histogram {
+    use super::cycle_lengths;
+    use lib::output::HistogramOutput;
+    use num::Float;
+
+    /// The distribution of the lengths of the permutation cycles of a bosonic group over time.
+    ///
+    /// Bin `k - 1` holds the fraction of atoms found in cycles of length `k`,
+    /// so the bins sum to one and a non-zero tail signals exchange.
+    #[derive(Clone, Debug)]
+    pub struct CycleLengthHistogram {
+        atoms_in_cycles: Vec<u64>,
+        samples: u64,
+    }
+
+    impl CycleLengthHistogram {
+        pub fn new(atoms: usize) -> Self {
+            assert!(atoms > 0, "the group must hold atoms");
+            Self {
+                atoms_in_cycles: vec![0; atoms],
+                samples: 0,
+            }
+        }
+
+        pub fn atoms(&self) -> usize {
+            self.atoms_in_cycles.len()
+        }
+
+        pub fn samples(&self) -> u64 {
+            self.samples
+        }
+
+        /// Adds the cycles of a sampled permutation of the group.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `permutation` does not permute the atoms of the group.
+        pub fn record(&mut self, permutation: &[usize]) {
+            assert_eq!(
+                permutation.len(),
+                self.atoms(),
+                "the permutation must map every atom of the group"
+            );
+            for length in cycle_lengths(permutation) {
+                self.atoms_in_cycles[length - 1] += length as u64;
+            }
+            self.samples += 1;
+        }
+
+        /// Returns the fraction of atoms in cycles of every length, or `None` without samples.
+        pub fn distribution<T: Float + From<f32>>(&self) -> Option<Vec<T>> {
+            (self.samples > 0).then(|| {
+                let total = <T as From<f32>>::from((self.samples * self.atoms() as u64) as f32);
+                self.atoms_in_cycles
+                    .iter()
+                    .map(|&count| <T as From<f32>>::from(count as f32) / total)
+                    .collect()
+            })
+        }
+
+        /// Returns the probability that an atom takes part in exchange,
+        /// i.e. belongs to a cycle longer than one.
+        pub fn exchange_probability<T: Float + From<f32>>(&self) -> Option<T> {
+            self.distribution::<T>()
+                .map(|distribution| T::one() - distribution[0])
+        }
+
+        /// Writes the distribution to a histogram stream. Nothing is written without samples.
+        pub fn write<T, O>(&self, step: usize, stream: &mut O) -> Result<(), O::Error>
+        where
+            T: Float + From<f32>,
+            O: HistogramOutput<T> + ?Sized,
+        {
+            match self.distribution() {
+                Some(distribution) => stream.write_histogram(step, &distribution),
+                None => Ok(()),
+            }
+        }
+
+        pub fn reset(&mut self) {
+            self.atoms_in_cycles.fill(0);
+            self.samples = 0;
+        }
+    }
+}
+
+pub use histogram::CycleLengthHistogram;
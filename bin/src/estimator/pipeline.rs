@@ -0,0 +1,383 @@
+mod adaptors {
+    use num::Float;
+
+    /// A transformation of the values of an observable, applied by the main thread
+    /// once per step after the value has been reduced over the groups.
+    pub trait ObservableAdaptor<T> {
+        /// Transforms the value of the observable at `step`,
+        /// returning `None` if there is no derived value yet.
+        fn apply(&mut self, step: usize, value: T) -> Option<T>;
+
+        /// Transforms a value received from a reduction, skipping steps
+        /// in which the observable has not been calculated.
+        fn apply_received(&mut self, step: usize, value: Option<T>) -> Option<T> {
+            value.and_then(|value| self.apply(step, value))
+        }
+
+        /// Feeds the values derived by this adaptor into `next`.
+        fn then<A: ObservableAdaptor<T>>(self, next: A) -> Chained<Self, A>
+        where
+            Self: Sized,
+        {
+            Chained(self, next)
+        }
+    }
+
+    /// Two adaptors applied one after the other.
+    #[derive(Clone, Debug)]
+    pub struct Chained<A, B>(pub A, pub B);
+
+    impl<T, A, B> ObservableAdaptor<T> for Chained<A, B>
+    where
+        A: ObservableAdaptor<T>,
+        B: ObservableAdaptor<T>,
+    {
+        fn apply(&mut self, step: usize, value: T) -> Option<T> {
+            let value = self.0.apply(step, value)?;
+            self.1.apply(step, value)
+        }
+    }
+
+    /// The mean of all values so far.
+    #[derive(Clone, Copy, Debug)]
+    pub struct RunningMean<T> {
+        mean: T,
+        samples: usize,
+    }
+
+    impl<T: Float> RunningMean<T> {
+        pub fn new() -> Self {
+            Self {
+                mean: T::zero(),
+                samples: 0,
+            }
+        }
+
+        pub fn samples(&self) -> usize {
+            self.samples
+        }
+    }
+
+    impl<T: Float> Default for RunningMean<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T: Float + From<f32>> ObservableAdaptor<T> for RunningMean<T> {
+        fn apply(&mut self, _step: usize, value: T) -> Option<T> {
+            self.samples += 1;
+            self.mean =
+                self.mean + (value - self.mean) / <T as From<f32>>::from(self.samples as f32);
+            Some(self.mean)
+        }
+    }
+
+    /// An average which forgets old values at the rate `smoothing` per sample.
+    #[derive(Clone, Copy, Debug)]
+    pub struct ExponentialMovingAverage<T> {
+        smoothing: T,
+        average: Option<T>,
+    }
+
+    impl<T: Float> ExponentialMovingAverage<T> {
+        /// Constructs an average in which every new value has the weight `smoothing`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `smoothing` is not within `(0, 1]`.
+        pub fn new(smoothing: T) -> Self {
+            assert!(
+                smoothing > T::zero() && smoothing <= T::one(),
+                "the smoothing factor must be within (0, 1]"
+            );
+            Self {
+                smoothing,
+                average: None,
+            }
+        }
+    }
+
+    impl<T: Float> ObservableAdaptor<T> for ExponentialMovingAverage<T> {
+        fn apply(&mut self, _step: usize, value: T) -> Option<T> {
+            let average = match self.average {
+                Some(average) => average + self.smoothing * (value - average),
+                None => value,
+            };
+            self.average = Some(average);
+            Some(average)
+        }
+    }
+
+    /// The rate of change of the values per unit of time, estimated by a backward difference
+    /// between consecutive samples, which need not be a single step apart.
+    #[derive(Clone, Copy, Debug)]
+    pub struct FiniteDifferenceRate<T> {
+        time_step: T,
+        previous: Option<(usize, T)>,
+    }
+
+    impl<T: Float> FiniteDifferenceRate<T> {
+        /// Constructs the rate of observables sampled every `time_step` units of time per step.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `time_step` is not positive.
+        pub fn new(time_step: T) -> Self {
+            assert!(time_step > T::zero(), "the time step must be positive");
+            Self {
+                time_step,
+                previous: None,
+            }
+        }
+    }
+
+    impl<T: Float + From<f32>> ObservableAdaptor<T> for FiniteDifferenceRate<T> {
+        fn apply(&mut self, step: usize, value: T) -> Option<T> {
+            let previous = self.previous.replace((step, value));
+            let (previous_step, previous_value) =
+                previous.filter(|&(previous, _)| previous < step)?;
+            let elapsed = <T as From<f32>>::from((step - previous_step) as f32) * self.time_step;
+            Some((value - previous_value) / elapsed)
+        }
+    }
+
+    /// The bound of a [`Threshold`] a value has crossed.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Bound {
+        Lower,
+        Upper,
+    }
+
+    /// A value which has left the range of a [`Threshold`].
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Alarm<T> {
+        pub step: usize,
+        pub value: T,
+        pub bound: Bound,
+    }
+
+    /// An alarm raised whenever the values leave the range between two bounds.
+    ///
+    /// The values are passed on unchanged. An alarm is raised once when the values leave the range
+    /// and again only after they have returned to it, so a drift does not raise one every step.
+    #[derive(Clone, Debug)]
+    pub struct Threshold<T> {
+        lower: Option<T>,
+        upper: Option<T>,
+        outside: bool,
+        alarms: Vec<Alarm<T>>,
+    }
+
+    impl<T: Float> Threshold<T> {
+        /// Constructs a threshold with optional bounds.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `lower` exceeds `upper`.
+        pub fn new(lower: Option<T>, upper: Option<T>) -> Self {
+            if let (Some(lower), Some(upper)) = (lower, upper) {
+                assert!(
+                    lower <= upper,
+                    "the lower bound must not exceed the upper one"
+                );
+            }
+            Self {
+                lower,
+                upper,
+                outside: false,
+                alarms: Vec::new(),
+            }
+        }
+
+        /// Removes and returns the alarms raised since the last call.
+        pub fn take_alarms(&mut self) -> Vec<Alarm<T>> {
+            std::mem::take(&mut self.alarms)
+        }
+    }
+
+    impl<T: Float> ObservableAdaptor<T> for Threshold<T> {
+        fn apply(&mut self, step: usize, value: T) -> Option<T> {
+            let bound = if self.lower.is_some_and(|lower| value < lower) {
+                Some(Bound::Lower)
+            } else if self.upper.is_some_and(|upper| value > upper) {
+                Some(Bound::Upper)
+            } else {
+                None
+            };
+            match bound {
+                Some(bound) if !self.outside => {
+                    self.outside = true;
+                    self.alarms.push(Alarm { step, value, bound });
+                }
+                Some(_) => {}
+                None => self.outside = false,
+            }
+            Some(value)
+        }
+    }
+}
+
+pub use adaptors::{
+    Alarm, Bound, Chained, ExponentialMovingAverage, FiniteDifferenceRate, ObservableAdaptor,
+    RunningMean, Threshold,
+};
+
+mod config {
+    use super::{
+        Alarm, ExponentialMovingAverage, FiniteDifferenceRate, ObservableAdaptor, RunningMean,
+        Threshold,
+    };
+    use crate::steering::ParseError;
+    use num::{Float, NumCast};
+
+    /// A single stage of a [`Pipeline`].
+    #[derive(Clone, Debug)]
+    pub enum Stage<T> {
+        RunningMean(RunningMean<T>),
+        ExponentialMovingAverage(ExponentialMovingAverage<T>),
+        FiniteDifferenceRate(FiniteDifferenceRate<T>),
+        Threshold(Threshold<T>),
+    }
+
+    impl<T: Float + From<f32>> ObservableAdaptor<T> for Stage<T> {
+        fn apply(&mut self, step: usize, value: T) -> Option<T> {
+            match self {
+                Self::RunningMean(adaptor) => adaptor.apply(step, value),
+                Self::ExponentialMovingAverage(adaptor) => adaptor.apply(step, value),
+                Self::FiniteDifferenceRate(adaptor) => adaptor.apply(step, value),
+                Self::Threshold(adaptor) => adaptor.apply(step, value),
+            }
+        }
+    }
+
+    /// A chain of adaptors read from a configuration file, applied to a named observable.
+    #[derive(Clone, Debug)]
+    pub struct Pipeline<T> {
+        observable: String,
+        stages: Vec<Stage<T>>,
+    }
+
+    impl<T> Pipeline<T> {
+        /// Returns the name of the observable the pipeline transforms.
+        pub fn observable(&self) -> &str {
+            &self.observable
+        }
+
+        pub fn stages(&self) -> &[Stage<T>] {
+            &self.stages
+        }
+    }
+
+    impl<T: Float> Pipeline<T> {
+        /// Removes and returns the alarms raised by every threshold since the last call.
+        pub fn take_alarms(&mut self) -> Vec<Alarm<T>> {
+            let mut alarms: Vec<_> = self
+                .stages
+                .iter_mut()
+                .flat_map(|stage| match stage {
+                    Stage::Threshold(threshold) => threshold.take_alarms(),
+                    _ => Vec::new(),
+                })
+                .collect();
+            alarms.sort_by_key(|alarm| alarm.step);
+            alarms
+        }
+    }
+
+    impl<T: Float + From<f32>> ObservableAdaptor<T> for Pipeline<T> {
+        fn apply(&mut self, step: usize, value: T) -> Option<T> {
+            self.stages
+                .iter_mut()
+                .try_fold(value, |value, stage| stage.apply(step, value))
+        }
+    }
+
+    /// Parses lines of `observable = stage | stage | ...` into pipelines,
+    /// skipping blank lines and `#` comments.
+    ///
+    /// The stages are `running_mean`, `ema <smoothing>`, `rate <time step>`
+    /// and `threshold <lower> <upper>`, where `-` stands for a missing bound, e.g.
+    /// `temperature = rate 0.5 | ema 0.01 | threshold - 0.1` raises an alarm
+    /// when the smoothed drift rate of the temperature exceeds 0.1.
+    pub fn parse_pipelines<T: Float + From<f32>>(
+        text: &str,
+    ) -> Result<Vec<Pipeline<T>>, ParseError> {
+        let mut pipelines = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| ParseError {
+                line: Some(index + 1),
+                message,
+            };
+            let (observable, stages) = line.split_once('=').ok_or_else(|| {
+                error(format!("expected `observable = stages`, found `{}`", line))
+            })?;
+            let stages = stages
+                .split('|')
+                .map(|stage| parse_stage(stage.trim()).map_err(error))
+                .collect::<Result<_, _>>()?;
+            pipelines.push(Pipeline {
+                observable: observable.trim().to_owned(),
+                stages,
+            });
+        }
+        Ok(pipelines)
+    }
+
+    fn parse_stage<T: Float + From<f32>>(stage: &str) -> Result<Stage<T>, String> {
+        let mut words = stage.split_whitespace();
+        let name = words.next().ok_or_else(|| "empty stage".to_owned())?;
+        let arguments: Vec<&str> = words.collect();
+        let number = |word: &str| {
+            word.parse::<f64>()
+                .ok()
+                .and_then(<T as NumCast>::from)
+                .ok_or_else(|| format!("invalid argument `{}` of `{}`", word, name))
+        };
+        let bound = |word: &str| match word {
+            "-" => Ok(None),
+            word => number(word).map(Some),
+        };
+        let invalid = |message: &str| Err(format!("`{}`: {}", name, message));
+        match (name, &arguments[..]) {
+            ("running_mean", []) => Ok(Stage::RunningMean(RunningMean::new())),
+            ("ema", [smoothing]) => {
+                let smoothing = number(smoothing)?;
+                if smoothing <= T::zero() || smoothing > T::one() {
+                    return invalid("the smoothing factor must be within (0, 1]");
+                }
+                Ok(Stage::ExponentialMovingAverage(
+                    ExponentialMovingAverage::new(smoothing),
+                ))
+            }
+            ("rate", [time_step]) => {
+                let time_step = number(time_step)?;
+                if time_step <= T::zero() {
+                    return invalid("the time step must be positive");
+                }
+                Ok(Stage::FiniteDifferenceRate(FiniteDifferenceRate::new(
+                    time_step,
+                )))
+            }
+            ("threshold", [lower, upper]) => {
+                let (lower, upper) = (bound(lower)?, bound(upper)?);
+                if let (Some(lower), Some(upper)) = (lower, upper)
+                    && lower > upper
+                {
+                    return invalid("the lower bound must not exceed the upper one");
+                }
+                Ok(Stage::Threshold(Threshold::new(lower, upper)))
+            }
+            ("running_mean" | "ema" | "rate" | "threshold", _) => {
+                invalid("wrong number of arguments")
+            }
+            _ => Err(format!("unknown stage `{}`", name)),
+        }
+    }
+}
+
+pub use config::{Pipeline, Stage, parse_pipelines};
@@ -0,0 +1,198 @@
+mod average {
+    use num::Float;
+
+    /// A running average of samples with weights given by their logarithms.
+    ///
+    /// The weights are kept relative to the largest log-weight seen so far,
+    /// so that strongly biased samples do not overflow.
+    #[derive(Clone, Copy, Debug)]
+    pub struct WeightedAverage<T> {
+        max_log_weight: T,
+        weights: T,
+        squared_weights: T,
+        weighted_values: T,
+        samples: usize,
+    }
+
+    impl<T: Float> WeightedAverage<T> {
+        pub fn new() -> Self {
+            Self {
+                max_log_weight: T::neg_infinity(),
+                weights: T::zero(),
+                squared_weights: T::zero(),
+                weighted_values: T::zero(),
+                samples: 0,
+            }
+        }
+
+        /// Adds `value` with weight `exp(log_weight)`.
+        ///
+        /// A sample of zero weight, i.e. of log-weight `-∞`, is counted but changes no sum.
+        pub fn add(&mut self, value: T, log_weight: T) {
+            self.samples += 1;
+            if log_weight == T::neg_infinity() {
+                return;
+            }
+            if log_weight > self.max_log_weight {
+                let scale = (self.max_log_weight - log_weight).exp();
+                self.weights = self.weights * scale;
+                self.squared_weights = self.squared_weights * scale * scale;
+                self.weighted_values = self.weighted_values * scale;
+                self.max_log_weight = log_weight;
+            }
+            let weight = (log_weight - self.max_log_weight).exp();
+            self.weights = self.weights + weight;
+            self.squared_weights = self.squared_weights + weight * weight;
+            self.weighted_values = self.weighted_values + weight * value;
+        }
+
+        pub fn samples(&self) -> usize {
+            self.samples
+        }
+
+        /// Returns the weighted average, or `None` if no sample of nonzero weight has been added.
+        pub fn mean(&self) -> Option<T> {
+            (self.weights > T::zero()).then(|| self.weighted_values / self.weights)
+        }
+
+        /// Returns Kish's effective sample size `(Σw)² / Σw²`,
+        /// which drops far below the number of samples when a few weights dominate.
+        pub fn effective_sample_size(&self) -> T {
+            if self.weights == T::zero() {
+                T::zero()
+            } else {
+                self.weights * self.weights / self.squared_weights
+            }
+        }
+
+        /// Returns the logarithm of the sum of the weights,
+        /// e.g. for free energy differences between the ensembles.
+        pub fn log_total_weight(&self) -> T {
+            self.max_log_weight + self.weights.ln()
+        }
+
+        pub fn reset(&mut self) {
+            *self = Self::new();
+        }
+    }
+
+    impl<T: Float> Default for WeightedAverage<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+pub use average::WeightedAverage;
+
+mod weight {
+    use num::Float;
+
+    /// A trait for functions assigning a sample of the simulated ensemble
+    /// its weight in a target ensemble.
+    pub trait WeightFunction<T> {
+        /// The quantity the weight is calculated from.
+        type Input: ?Sized;
+
+        /// Returns the logarithm of the unnormalized weight of a sample.
+        fn log_weight(&self, input: &Self::Input) -> T;
+    }
+
+    /// Removes a bias potential added to the sampled ensemble, e.g. by metadynamics.
+    ///
+    /// The input is the bias energy of the sample, which is weighted by `exp(βV)`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct BiasWeight<T> {
+        pub(crate) beta: T,
+    }
+
+    impl<T: Float> BiasWeight<T> {
+        pub fn new(beta: T) -> Self {
+            assert!(beta > T::zero(), "beta must be positive");
+            Self { beta }
+        }
+    }
+
+    impl<T: Float> WeightFunction<T> for BiasWeight<T> {
+        type Input = T;
+
+        fn log_weight(&self, bias: &T) -> T {
+            self.beta * *bias
+        }
+    }
+
+    /// Reweights between two actions, e.g. from a truncated bosonic action to the full one.
+    ///
+    /// The input is the dimensionless action of the target ensemble
+    /// minus the action of the sampled ensemble, which is weighted by `exp(-ΔS)`.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct ActionWeight;
+
+    impl<T: Float> WeightFunction<T> for ActionWeight {
+        type Input = T;
+
+        fn log_weight(&self, action_difference: &T) -> T {
+            -*action_difference
+        }
+    }
+}
+
+pub use weight::{ActionWeight, BiasWeight, WeightFunction};
+
+mod reweighted {
+    use super::{WeightFunction, WeightedAverage};
+    use num::Float;
+
+    /// An observable averaged in an ensemble other than the sampled one.
+    ///
+    /// Values of the observable are recorded by the main thread after
+    /// they have been reduced over the groups, together with the quantity
+    /// the weight function assigns their weight from.
+    #[derive(Clone, Debug)]
+    pub struct Reweighted<T, W> {
+        pub(crate) weight: W,
+        pub(crate) average: WeightedAverage<T>,
+    }
+
+    impl<T: Float, W: WeightFunction<T>> Reweighted<T, W> {
+        pub fn new(weight: W) -> Self {
+            Self {
+                weight,
+                average: WeightedAverage::new(),
+            }
+        }
+
+        /// Records a value of the observable in a sample described by `input`
+        /// and returns the updated reweighted average.
+        pub fn record(&mut self, value: T, input: &W::Input) -> T {
+            self.average.add(value, self.weight.log_weight(input));
+            self.average
+                .mean()
+                .expect("a sample has just been recorded")
+        }
+
+        /// Records a value received from a reduction, skipping steps in which
+        /// the observable has not been calculated.
+        pub fn record_received(&mut self, value: Option<T>, input: &W::Input) -> Option<T> {
+            value.map(|value| self.record(value, input))
+        }
+
+        pub fn weight(&self) -> &W {
+            &self.weight
+        }
+
+        pub fn average(&self) -> &WeightedAverage<T> {
+            &self.average
+        }
+
+        pub fn mean(&self) -> Option<T> {
+            self.average.mean()
+        }
+
+        pub fn effective_sample_size(&self) -> T {
+            self.average.effective_sample_size()
+        }
+    }
+}
+
+pub use reweighted::Reweighted;
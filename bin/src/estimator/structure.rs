@@ -0,0 +1,401 @@
+mod shells {
+    use num::Float;
+
+    /// Returns the volume of the unit ball in `dimensions` dimensions,
+    /// i.e. 2, π and 4π/3 in one, two and three dimensions.
+    pub fn unit_ball_volume<T: Float + From<f32>>(dimensions: usize) -> T {
+        let two_pi = <T as From<f32>>::from(2.0 * std::f32::consts::PI);
+        let mut volumes = [T::one(), <T as From<f32>>::from(2.0)];
+        for dimension in 2..=dimensions {
+            volumes[dimension % 2] =
+                volumes[dimension % 2] * two_pi / <T as From<f32>>::from(dimension as f32);
+        }
+        volumes[dimensions % 2]
+    }
+
+    /// Returns the measure of the shell between the radii `inner` and `outer`
+    /// in `dimensions` dimensions, i.e. the area of an annulus in two dimensions.
+    pub fn shell_measure<T: Float + From<f32>>(dimensions: usize, inner: T, outer: T) -> T {
+        let exponent = dimensions as i32;
+        unit_ball_volume::<T>(dimensions) * (outer.powi(exponent) - inner.powi(exponent))
+    }
+}
+
+pub use shells::{shell_measure, unit_ball_volume};
+
+mod radial {
+    use super::shell_measure;
+    use lib::{core::Vector, output::HistogramOutput};
+    use num::Float;
+
+    use crate::potential::electrostatics::PeriodicBox;
+
+    /// The radial distribution function `g(r)` of a group.
+    ///
+    /// Distances are measured along the periodic axes of the box and normalized by the shells
+    /// and the density in as many dimensions, so a two-dimensional box and a slab with an open
+    /// axis both yield the in-plane `g(r)`, which tends to one for an ideal gas.
+    #[derive(Clone, Debug)]
+    pub struct RadialDistribution<T> {
+        bin_width: T,
+        counts: Vec<u64>,
+        dimensions: Option<usize>,
+        /// The sum over samples of `N (N - 1) / (2 V)`, with `V` the periodic volume.
+        normalization: T,
+        samples: u64,
+    }
+
+    impl<T: Float + From<f32>> RadialDistribution<T> {
+        /// Constructs a histogram of `bins` bins up to `max_radius`.
+        pub fn new(max_radius: T, bins: usize) -> Self {
+            assert!(
+                max_radius > T::zero(),
+                "the maximal radius must be positive"
+            );
+            assert!(bins > 0, "the histogram must have bins");
+            Self {
+                bin_width: max_radius / <T as From<f32>>::from(bins as f32),
+                counts: vec![0; bins],
+                dimensions: None,
+                normalization: T::zero(),
+                samples: 0,
+            }
+        }
+
+        pub fn bin_width(&self) -> T {
+            self.bin_width
+        }
+
+        pub fn samples(&self) -> u64 {
+            self.samples
+        }
+
+        /// Returns the radius at the center of every bin.
+        pub fn radii(&self) -> Vec<T> {
+            (0..self.counts.len())
+                .map(|bin| <T as From<f32>>::from(bin as f32 + 0.5) * self.bin_width)
+                .collect()
+        }
+
+        /// Adds the pairs of a configuration of the group.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the maximal radius exceeds half of a periodic length of the box,
+        /// or the box does not have the periodic axes of the previous samples.
+        pub fn record<const N: usize, V>(
+            &mut self,
+            periodic_box: &PeriodicBox<T, N>,
+            positions: &[V],
+        ) where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let dimensions = periodic_box.periodic_dimensions();
+            assert!(dimensions > 0, "the box must be periodic along some axis");
+            assert_eq!(
+                *self.dimensions.get_or_insert(dimensions),
+                dimensions,
+                "the periodic axes of the box have changed"
+            );
+            let max_radius = self.bin_width * <T as From<f32>>::from(self.counts.len() as f32);
+            let lengths = periodic_box.lengths();
+            assert!(
+                (0..N).all(|axis| !periodic_box.is_periodic(axis)
+                    || max_radius <= <T as From<f32>>::from(0.5) * lengths[axis]),
+                "the maximal radius must not exceed half of a periodic length of the box"
+            );
+            for (i, first) in positions.iter().enumerate() {
+                for second in &positions[i + 1..] {
+                    let mut displacement =
+                        periodic_box.minimum_image(first.clone() - second.clone());
+                    for (axis, coordinate) in displacement.as_mut_array().iter_mut().enumerate() {
+                        if !periodic_box.is_periodic(axis) {
+                            *coordinate = T::zero();
+                        }
+                    }
+                    let bin = (displacement.magnitude_squared().sqrt() / self.bin_width)
+                        .to_usize()
+                        .unwrap_or(usize::MAX);
+                    if let Some(count) = self.counts.get_mut(bin) {
+                        *count += 1;
+                    }
+                }
+            }
+            let atoms = <T as From<f32>>::from(positions.len() as f32);
+            self.normalization = self.normalization
+                + atoms * (atoms - T::one())
+                    / (<T as From<f32>>::from(2.0) * periodic_box.periodic_volume());
+            self.samples += 1;
+        }
+
+        /// Returns `g(r)` at the radii of [`RadialDistribution::radii`], or `None` without samples.
+        pub fn distribution(&self) -> Option<Vec<T>> {
+            let dimensions = self.dimensions.filter(|_| self.samples > 0)?;
+            Some(
+                self.counts
+                    .iter()
+                    .enumerate()
+                    .map(|(bin, &count)| {
+                        let inner = <T as From<f32>>::from(bin as f32) * self.bin_width;
+                        let shell = shell_measure(dimensions, inner, inner + self.bin_width);
+                        <T as From<f32>>::from(count as f32) / (self.normalization * shell)
+                    })
+                    .collect(),
+            )
+        }
+
+        /// Writes `g(r)` to a histogram stream. Nothing is written without samples.
+        pub fn write<O>(&self, step: usize, stream: &mut O) -> Result<(), O::Error>
+        where
+            O: HistogramOutput<T> + ?Sized,
+        {
+            match self.distribution() {
+                Some(distribution) => stream.write_histogram(step, &distribution),
+                None => Ok(()),
+            }
+        }
+
+        pub fn reset(&mut self) {
+            self.counts.fill(0);
+            self.dimensions = None;
+            self.normalization = T::zero();
+            self.samples = 0;
+        }
+    }
+}
+
+pub use radial::RadialDistribution;
+
+mod structure_factor {
+    use lib::{core::Vector, output::HistogramOutput};
+    use num::Float;
+
+    use crate::potential::electrostatics::PeriodicBox;
+
+    /// The static structure factor `S(k) = ⟨|Σ_j exp(i k·r_j)|²⟩ / N` of a group
+    /// on the grid of wave vectors `k = 2π n / L` compatible with the box.
+    ///
+    /// Only the periodic axes carry wave numbers, so the grid of a two-dimensional box
+    /// or a slab is two-dimensional. Wave vectors of opposite signs give the same value,
+    /// so only one of each pair is kept.
+    #[derive(Clone, Debug)]
+    pub struct StructureFactor<const N: usize, T> {
+        wave_vectors: Vec<[T; N]>,
+        sums: Vec<T>,
+        samples: u64,
+    }
+
+    impl<const N: usize, T: Float + From<f32>> StructureFactor<N, T> {
+        /// Constructs the estimator on the wave numbers `-max_wave_number..=max_wave_number`
+        /// along every periodic axis of `periodic_box`.
+        pub fn new(periodic_box: &PeriodicBox<T, N>, max_wave_number: i64) -> Self {
+            assert!(
+                max_wave_number > 0,
+                "the maximal wave number must be positive"
+            );
+            let two_pi = <T as From<f32>>::from(2.0 * std::f32::consts::PI);
+            let lengths = periodic_box.lengths();
+            let axes: Vec<_> = (0..N)
+                .filter(|&axis| periodic_box.is_periodic(axis))
+                .collect();
+            let side = (2 * max_wave_number + 1) as usize;
+            let mut wave_vectors = Vec::new();
+            for index in 0..side.pow(axes.len() as u32) {
+                let mut numbers = [0i64; N];
+                let mut digits = index;
+                for &axis in &axes {
+                    numbers[axis] = (digits % side) as i64 - max_wave_number;
+                    digits /= side;
+                }
+                // Keeps the wave vectors whose first non-zero wave number is positive.
+                if numbers
+                    .iter()
+                    .find(|&&number| number != 0)
+                    .is_some_and(|&number| number > 0)
+                {
+                    wave_vectors.push(std::array::from_fn(|axis| {
+                        two_pi * <T as From<f32>>::from(numbers[axis] as f32) / lengths[axis]
+                    }));
+                }
+            }
+            Self {
+                sums: vec![T::zero(); wave_vectors.len()],
+                wave_vectors,
+                samples: 0,
+            }
+        }
+
+        pub fn wave_vectors(&self) -> &[[T; N]] {
+            &self.wave_vectors
+        }
+
+        pub fn samples(&self) -> u64 {
+            self.samples
+        }
+
+        /// Adds a configuration of the group.
+        pub fn record<V>(&mut self, positions: &[V])
+        where
+            V: Vector<N, Element = T>,
+        {
+            if positions.is_empty() {
+                return;
+            }
+            let atoms = <T as From<f32>>::from(positions.len() as f32);
+            for (wave_vector, sum) in self.wave_vectors.iter().zip(&mut self.sums) {
+                let (mut real, mut imaginary) = (T::zero(), T::zero());
+                for position in positions {
+                    let phase = wave_vector
+                        .iter()
+                        .zip(position.as_array())
+                        .fold(T::zero(), |phase, (&k, &r)| phase + k * r);
+                    real = real + phase.cos();
+                    imaginary = imaginary + phase.sin();
+                }
+                *sum = *sum + (real * real + imaginary * imaginary) / atoms;
+            }
+            self.samples += 1;
+        }
+
+        /// Returns `S(k)` at every wave vector, or `None` without samples.
+        pub fn values(&self) -> Option<Vec<T>> {
+            (self.samples > 0).then(|| {
+                let samples = <T as From<f32>>::from(self.samples as f32);
+                self.sums.iter().map(|&sum| sum / samples).collect()
+            })
+        }
+
+        /// Returns `S(k)` averaged over shells of `|k|` of width `bin_width`
+        /// as pairs of the mean `|k|` and the mean value, or `None` without samples.
+        pub fn radial_average(&self, bin_width: T) -> Option<Vec<(T, T)>> {
+            assert!(bin_width > T::zero(), "the bin width must be positive");
+            let values = self.values()?;
+            let mut bins: Vec<(T, T, usize)> = Vec::new();
+            for (wave_vector, value) in self.wave_vectors.iter().zip(values) {
+                let magnitude = wave_vector
+                    .iter()
+                    .fold(T::zero(), |sum, &k| sum + k * k)
+                    .sqrt();
+                let bin = (magnitude / bin_width).to_usize().unwrap_or(0);
+                if bins.len() <= bin {
+                    bins.resize(bin + 1, (T::zero(), T::zero(), 0));
+                }
+                let (k, s, count) = &mut bins[bin];
+                *k = *k + magnitude;
+                *s = *s + value;
+                *count += 1;
+            }
+            Some(
+                bins.into_iter()
+                    .filter(|&(_, _, count)| count > 0)
+                    .map(|(k, s, count)| {
+                        let count = <T as From<f32>>::from(count as f32);
+                        (k / count, s / count)
+                    })
+                    .collect(),
+            )
+        }
+
+        /// Writes `S(k)` at every wave vector to a histogram stream.
+        /// Nothing is written without samples.
+        pub fn write<O>(&self, step: usize, stream: &mut O) -> Result<(), O::Error>
+        where
+            O: HistogramOutput<T> + ?Sized,
+        {
+            match self.values() {
+                Some(values) => stream.write_histogram(step, &values),
+                None => Ok(()),
+            }
+        }
+
+        pub fn reset(&mut self) {
+            self.sums.fill(T::zero());
+            self.samples = 0;
+        }
+    }
+}
+
+pub use structure_factor::StructureFactor;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        potential::{electrostatics::PeriodicBox, pair::LennardJones, pair::PairParameters},
+        vector::ArrayVector,
+    };
+    use std::f64::consts::PI;
+
+    /// Returns the next number of a SplitMix64 sequence, uniform in [0, 1).
+    fn uniform(state: &mut u64) -> f64 {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)) as f64 / 2f64.powi(64)
+    }
+
+    #[test]
+    fn pair_in_two_dimensions_follows_its_boltzmann_factor() {
+        // Two Lennard-Jones atoms in a periodic square: their separation is distributed
+        // as `exp(-u(r) / T)`, so `g(r) = V exp(-u(r) / T) / Z` with `Z` the integral
+        // of the factor over the box.
+        let (length, cutoff, temperature) = (6.0, 2.5, 1.0);
+        let (bins, max_radius) = (30, 3.0);
+        let periodic_box = PeriodicBox::new([length; 2]);
+        let potential = LennardJones::<2, f64>::new(PairParameters::new(1.0, 1.0), cutoff)
+            .with_box(periodic_box);
+        let shift = 4.0 * (cutoff.powi(-12) - cutoff.powi(-6));
+        let boltzmann = |r: f64| {
+            if r >= cutoff {
+                1.0
+            } else {
+                (-(4.0 * (r.powi(-12) - r.powi(-6)) - shift) / temperature).exp()
+            }
+        };
+        // The integrals over radii by the midpoint rule.
+        let integral = |inner: f64, outer: f64, f: &dyn Fn(f64) -> f64| {
+            let steps = 2000;
+            let width = (outer - inner) / steps as f64;
+            (0..steps)
+                .map(|step| {
+                    let r = inner + (step as f64 + 0.5) * width;
+                    2.0 * PI * r * f(r) * width
+                })
+                .sum::<f64>()
+        };
+        let volume = length * length;
+        let partition = volume - integral(0.0, cutoff, &|r| 1.0 - boltzmann(r));
+
+        let mut distribution = RadialDistribution::new(max_radius, bins);
+        let mut state = 0x5eed;
+        let samples = 400_000;
+        while distribution.samples() < samples {
+            let positions = [
+                ArrayVector::from([0.0, 0.0]),
+                ArrayVector::from([length * uniform(&mut state), length * uniform(&mut state)]),
+            ];
+            // The energy is at least `-ε`, so the acceptance is at most one.
+            let energy = potential.accumulate(&positions, None);
+            if uniform(&mut state) < (-(energy + 1.0) / temperature).exp() {
+                distribution.record(&periodic_box, &positions);
+            }
+        }
+
+        let width = distribution.bin_width();
+        for (bin, &value) in distribution.distribution().unwrap().iter().enumerate() {
+            let inner = bin as f64 * width;
+            let shell = shell_measure(2, inner, inner + width);
+            let expected = volume / partition * integral(inner, inner + width, &boltzmann) / shell;
+            let count = samples as f64 * expected * shell / volume;
+            let tolerance = 5.0 * expected / count.max(1.0).sqrt() + 1e-3;
+            assert!(
+                (value - expected).abs() < tolerance,
+                "g({}) = {} instead of {}",
+                inner + 0.5 * width,
+                value,
+                expected
+            );
+        }
+    }
+}
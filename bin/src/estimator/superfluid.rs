@@ -0,0 +1,233 @@
+mod winding {
+    use lib::core::Vector;
+    use num::Float;
+
+    use crate::potential::electrostatics::PeriodicBox;
+
+    /// Returns the total winding vector `W = Σ (r_{j+1} - r_j)` of the paths of a group,
+    /// where `images[j][i]` is the position of atom `i` in image `j`
+    /// and the last image of atom `i` is linked to the first image of atom `permutation[i]`.
+    ///
+    /// Every link is wrapped to its minimum image, so `W` is a sum of lattice vectors
+    /// of the periodic box which is non-zero only for paths winding around it.
+    pub fn winding_vector<const N: usize, T, V>(
+        periodic_box: &PeriodicBox<T, N>,
+        images: &[&[V]],
+        permutation: &[usize],
+    ) -> V
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+    {
+        let (first, last) = match (images.first(), images.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return V::zero(),
+        };
+        assert_eq!(
+            permutation.len(),
+            first.len(),
+            "the permutation must map every atom of the group"
+        );
+        let mut winding = [T::zero(); N];
+        let mut add = |from: &V, to: &V| {
+            let mut link = to.clone();
+            for (coordinate, &start) in link.as_mut_array().iter_mut().zip(from.as_array()) {
+                *coordinate = *coordinate - start;
+            }
+            let link = periodic_box.minimum_image(link);
+            for (total, &coordinate) in winding.iter_mut().zip(link.as_array()) {
+                *total = *total + coordinate;
+            }
+        };
+        for pair in images.windows(2) {
+            assert_eq!(
+                pair[0].len(),
+                pair[1].len(),
+                "the images hold groups of different sizes"
+            );
+            for (from, to) in pair[0].iter().zip(pair[1].iter()) {
+                add(from, to);
+            }
+        }
+        for (from, &next) in last.iter().zip(permutation) {
+            add(from, &first[next]);
+        }
+        V::from(winding)
+    }
+
+    /// Returns the integer winding numbers `W / L` along every axis of the box,
+    /// which vanish along open axes.
+    pub fn winding_numbers<const N: usize, T, V>(
+        periodic_box: &PeriodicBox<T, N>,
+        winding: &V,
+    ) -> [i64; N]
+    where
+        T: Float,
+        V: Vector<N, Element = T>,
+    {
+        let lengths = periodic_box.lengths();
+        std::array::from_fn(|axis| {
+            if !periodic_box.is_periodic(axis) {
+                return 0;
+            }
+            (winding.as_array()[axis] / lengths[axis])
+                .round()
+                .to_i64()
+                .unwrap_or_default()
+        })
+    }
+}
+
+pub use winding::{winding_numbers, winding_vector};
+
+mod blocks {
+    use num::Float;
+
+    /// A running average split into blocks of consecutive samples,
+    /// whose means are treated as independent to estimate the error bar
+    /// of correlated samples.
+    #[derive(Clone, Debug)]
+    pub struct BlockAverage<T> {
+        block_size: usize,
+        sum: T,
+        count: usize,
+        block_means: Vec<T>,
+    }
+
+    impl<T: Float + From<f32>> BlockAverage<T> {
+        pub fn new(block_size: usize) -> Self {
+            assert!(block_size > 0, "the block size must be positive");
+            Self {
+                block_size,
+                sum: T::zero(),
+                count: 0,
+                block_means: Vec::new(),
+            }
+        }
+
+        pub fn add(&mut self, value: T) {
+            self.sum = self.sum + value;
+            self.count += 1;
+            if self.count == self.block_size {
+                self.block_means
+                    .push(self.sum / <T as From<f32>>::from(self.block_size as f32));
+                self.sum = T::zero();
+                self.count = 0;
+            }
+        }
+
+        /// Returns the number of completed blocks.
+        pub fn blocks(&self) -> usize {
+            self.block_means.len()
+        }
+
+        /// Returns the mean of the completed blocks, or `None` if there are none.
+        pub fn mean(&self) -> Option<T> {
+            (!self.block_means.is_empty()).then(|| {
+                self.block_means
+                    .iter()
+                    .fold(T::zero(), |sum, &mean| sum + mean)
+                    / <T as From<f32>>::from(self.block_means.len() as f32)
+            })
+        }
+
+        /// Returns the standard error of the mean of the completed blocks,
+        /// or `None` if there are fewer than two.
+        pub fn standard_error(&self) -> Option<T> {
+            let mean = self.mean()?;
+            let blocks = self.block_means.len();
+            (blocks > 1).then(|| {
+                let squared_deviations = self.block_means.iter().fold(T::zero(), |sum, &block| {
+                    sum + (block - mean) * (block - mean)
+                });
+                (squared_deviations / <T as From<f32>>::from(((blocks - 1) * blocks) as f32)).sqrt()
+            })
+        }
+
+        pub fn reset(&mut self) {
+            *self = Self::new(self.block_size);
+        }
+    }
+}
+
+pub use blocks::BlockAverage;
+
+mod fraction {
+    use super::BlockAverage;
+    use lib::core::Vector;
+    use num::Float;
+
+    use crate::core::constants::REDUCED_PLANK_CONSTANT;
+
+    /// The winding estimator `ρ_s / ρ = m ⟨W²⟩ / (d β ħ² N)` of the superfluid fraction
+    /// of a group of `N` bosons in a box periodic along `d` axes.
+    ///
+    /// Every group is estimated separately, with error bars from block averaging.
+    #[derive(Clone, Debug)]
+    pub struct SuperfluidFraction<T> {
+        mass: T,
+        thermal_energy: T,
+        atoms: usize,
+        dimensions: usize,
+        blocks: BlockAverage<T>,
+    }
+
+    impl<T: Float + From<f32>> SuperfluidFraction<T> {
+        pub fn new(mass: T, thermal_energy: T, atoms: usize, block_size: usize) -> Self {
+            assert!(mass > T::zero(), "the mass must be positive");
+            assert!(
+                thermal_energy > T::zero(),
+                "the thermal energy must be positive"
+            );
+            assert!(atoms > 0, "the group must hold atoms");
+            Self {
+                mass,
+                thermal_energy,
+                atoms,
+                dimensions: 3,
+                blocks: BlockAverage::new(block_size),
+            }
+        }
+
+        /// Sets the number of periodic axes, which is 3 unless the system
+        /// is two-dimensional or a slab.
+        pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+            assert!(dimensions > 0, "the box must be periodic along some axis");
+            self.dimensions = dimensions;
+            self
+        }
+
+        /// Adds the sample of the superfluid fraction given by the winding vector of the group.
+        pub fn record<const N: usize, V>(&mut self, winding: V)
+        where
+            V: Vector<N, Element = T>,
+        {
+            let hbar = <T as From<f32>>::from(REDUCED_PLANK_CONSTANT);
+            self.blocks.add(
+                self.mass * winding.magnitude_squared() * self.thermal_energy
+                    / (<T as From<f32>>::from(self.dimensions as f32)
+                        * hbar
+                        * hbar
+                        * <T as From<f32>>::from(self.atoms as f32)),
+            );
+        }
+
+        pub fn mean(&self) -> Option<T> {
+            self.blocks.mean()
+        }
+
+        pub fn standard_error(&self) -> Option<T> {
+            self.blocks.standard_error()
+        }
+
+        pub fn blocks(&self) -> &BlockAverage<T> {
+            &self.blocks
+        }
+
+        pub fn reset(&mut self) {
+            self.blocks.reset();
+        }
+    }
+}
+
+pub use fraction::SuperfluidFraction;
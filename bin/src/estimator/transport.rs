@@ -0,0 +1,232 @@
+mod fluxes {
+    use lib::{
+        core::Vector,
+        output::{Dimension, ObservableRegistry, ValuesOutput},
+    };
+    use num::Float;
+
+    use crate::potential::pair::{PairContribution, PairwiseAttribution};
+
+    /// Returns the name of the axis at `axis`.
+    fn axis_name(axis: usize) -> String {
+        match axis {
+            0 => "x".to_owned(),
+            1 => "y".to_owned(),
+            2 => "z".to_owned(),
+            axis => axis.to_string(),
+        }
+    }
+
+    /// The potential energy and the virial of every atom, accumulated pair by pair
+    /// from pairwise potentials, from which the stress of every atom, the pressure tensor
+    /// and the heat flux of the system follow.
+    ///
+    /// Every pair gives half of its energy and half of its virial `r_ij ⊗ F_ij` to each of its atoms.
+    /// The heat flux is `J V = Σ_i (e_i v_i + W_i v_i)`, where `e_i` is the total energy
+    /// and `W_i` the virial of atom `i`, which is the convective part and the pairwise part
+    /// of the Irving-Kirkwood heat flux. Both the pressure tensor and the heat flux
+    /// are the inputs of the Green-Kubo relations in [`crate::analysis`].
+    #[derive(Clone, Debug)]
+    pub struct AtomicFluxes<const N: usize, T> {
+        energies: Vec<T>,
+        virials: Vec<[[T; N]; N]>,
+    }
+
+    impl<const N: usize, T: Float + From<f32>> AtomicFluxes<N, T> {
+        /// Constructs empty accumulators for `atoms` atoms.
+        pub fn new(atoms: usize) -> Self {
+            Self {
+                energies: vec![T::zero(); atoms],
+                virials: vec![[[T::zero(); N]; N]; atoms],
+            }
+        }
+
+        pub fn atoms(&self) -> usize {
+            self.energies.len()
+        }
+
+        /// Zeroes the accumulators, to be called before the pairs of every new configuration.
+        pub fn clear(&mut self) {
+            self.energies.fill(T::zero());
+            self.virials.fill([[T::zero(); N]; N]);
+        }
+
+        /// Adds the contribution of a single pair.
+        ///
+        /// # Panics
+        ///
+        /// Panics if either atom of the pair is out of range.
+        pub fn add_pair<V>(&mut self, pair: &PairContribution<T, V>)
+        where
+            V: Vector<N, Element = T>,
+        {
+            let half = <T as From<f32>>::from(0.5);
+            let energy = half * pair.energy;
+            self.energies[pair.first] = self.energies[pair.first] + energy;
+            self.energies[pair.second] = self.energies[pair.second] + energy;
+            let displacement = pair.displacement.as_array();
+            let force = pair.force.as_array();
+            for atom in [pair.first, pair.second] {
+                for (row, &coordinate) in self.virials[atom].iter_mut().zip(displacement) {
+                    for (entry, &component) in row.iter_mut().zip(force) {
+                        *entry = *entry + half * coordinate * component;
+                    }
+                }
+            }
+        }
+
+        /// Adds the contributions of every pair of atoms at `positions` interacting through `potential`.
+        pub fn add_potential<V, P>(&mut self, potential: &P, positions: &[V])
+        where
+            V: Vector<N, Element = T>,
+            P: PairwiseAttribution<T, V> + ?Sized,
+        {
+            potential.for_each_pair(positions, |pair| self.add_pair(&pair));
+        }
+
+        /// Returns the potential energy of every atom.
+        pub fn energies(&self) -> &[T] {
+            &self.energies
+        }
+
+        /// Returns the virial `W_i = Σ_j r_ij ⊗ F_ij / 2` of every atom.
+        pub fn virials(&self) -> &[[[T; N]; N]] {
+            &self.virials
+        }
+
+        /// Returns the stress of every atom multiplied by the volume it occupies,
+        /// `S_i = -(m_i v_i ⊗ v_i + W_i)`, which adds up to minus the pressure tensor times the volume.
+        ///
+        /// # Panics
+        ///
+        /// Panics if a mass or a velocity is not given for every atom.
+        pub fn stresses<V>(&self, masses: &[T], velocities: &[V]) -> Vec<[[T; N]; N]>
+        where
+            V: Vector<N, Element = T>,
+        {
+            self.check_lengths(masses, velocities);
+            self.virials
+                .iter()
+                .zip(masses.iter().zip(velocities))
+                .map(|(virial, (&mass, velocity))| {
+                    let velocity = velocity.as_array();
+                    std::array::from_fn(|row| {
+                        std::array::from_fn(|column| {
+                            -(mass * velocity[row] * velocity[column] + virial[row][column])
+                        })
+                    })
+                })
+                .collect()
+        }
+
+        /// Returns the pressure tensor of the atoms in a box of volume `volume`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if a mass or a velocity is not given for every atom.
+        pub fn pressure_tensor<V>(&self, masses: &[T], velocities: &[V], volume: T) -> [[T; N]; N]
+        where
+            V: Vector<N, Element = T>,
+        {
+            let mut pressure = [[T::zero(); N]; N];
+            for stress in self.stresses(masses, velocities) {
+                for (row, stress) in pressure.iter_mut().zip(stress) {
+                    for (entry, stress) in row.iter_mut().zip(stress) {
+                        *entry = *entry - stress / volume;
+                    }
+                }
+            }
+            pressure
+        }
+
+        /// Returns the heat flux of the atoms in a box of volume `volume`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if a mass or a velocity is not given for every atom.
+        pub fn heat_flux<V>(&self, masses: &[T], velocities: &[V], volume: T) -> [T; N]
+        where
+            V: Vector<N, Element = T>,
+        {
+            self.check_lengths(masses, velocities);
+            let half = <T as From<f32>>::from(0.5);
+            let mut flux = [T::zero(); N];
+            for ((&energy, virial), (&mass, velocity)) in self
+                .energies
+                .iter()
+                .zip(&self.virials)
+                .zip(masses.iter().zip(velocities))
+            {
+                let velocity = velocity.as_array();
+                let kinetic_energy = half
+                    * mass
+                    * velocity
+                        .iter()
+                        .fold(T::zero(), |sum, &component| sum + component * component);
+                for ((entry, row), &component) in flux.iter_mut().zip(virial).zip(velocity) {
+                    let virial_term = row
+                        .iter()
+                        .zip(velocity)
+                        .fold(T::zero(), |sum, (&entry, &component)| {
+                            sum + entry * component
+                        });
+                    *entry = *entry + (energy + kinetic_energy) * component + virial_term;
+                }
+            }
+            flux.map(|component| component / volume)
+        }
+
+        /// Registers the values written by [`AtomicFluxes::write_values`].
+        pub fn register_values(registry: &mut ObservableRegistry) {
+            for row in 0..N {
+                for column in 0..N {
+                    registry.register(
+                        &format!("pressure_{}{}", axis_name(row), axis_name(column)),
+                        Dimension::PRESSURE,
+                    );
+                }
+            }
+            for axis in 0..N {
+                registry.register(
+                    &format!("heat_flux_{}", axis_name(axis)),
+                    Dimension::new(0, 1, -3, 0, 0),
+                );
+            }
+        }
+
+        /// Writes the pressure tensor, row by row, followed by the heat flux,
+        /// forming the time series the Green-Kubo relations are evaluated on.
+        pub fn write_values<V, O>(
+            &self,
+            masses: &[T],
+            velocities: &[V],
+            volume: T,
+            stream: &mut O,
+        ) -> Result<(), O::Error>
+        where
+            V: Vector<N, Element = T>,
+            O: ValuesOutput<T> + ?Sized,
+        {
+            for row in self.pressure_tensor(masses, velocities, volume) {
+                for entry in row {
+                    stream.write_value(entry)?;
+                }
+            }
+            for component in self.heat_flux(masses, velocities, volume) {
+                stream.write_value(component)?;
+            }
+            Ok(())
+        }
+
+        fn check_lengths<V>(&self, masses: &[T], velocities: &[V]) {
+            assert_eq!(masses.len(), self.atoms(), "every atom must have a mass");
+            assert_eq!(
+                velocities.len(),
+                self.atoms(),
+                "every atom must have a velocity"
+            );
+        }
+    }
+}
+
+pub use fluxes::AtomicFluxes;
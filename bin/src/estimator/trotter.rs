@@ -0,0 +1,243 @@
+mod thresholds {
+    use num::Float;
+
+    /// The thresholds above which [`TrotterDiagnostic`](super::TrotterDiagnostic) warns.
+    #[derive(Clone, Copy, Debug)]
+    pub struct TrotterThresholds<T> {
+        /// The largest tolerated discrepancy between the primitive and the virial
+        /// kinetic energies relative to the virial one.
+        pub relative_discrepancy: T,
+        /// The number of standard errors the discrepancy must exceed to be significant.
+        pub significance: T,
+        /// The smallest tolerated number of beads per unit of `βħω`,
+        /// where `ω` is the effective frequency of the system.
+        pub beads_per_quantumness: T,
+        /// The number of samples before any warning is issued.
+        pub min_samples: usize,
+    }
+
+    impl<T: Float + From<f32>> Default for TrotterThresholds<T> {
+        fn default() -> Self {
+            Self {
+                relative_discrepancy: <T as From<f32>>::from(0.02),
+                significance: <T as From<f32>>::from(3.0),
+                beads_per_quantumness: <T as From<f32>>::from(5.0),
+                min_samples: 100,
+            }
+        }
+    }
+}
+
+pub use thresholds::TrotterThresholds;
+
+mod diagnostic {
+    use super::TrotterThresholds;
+    use lib::output::{Dimension, ObservableRegistry, ValuesOutput};
+    use num::Float;
+
+    /// A warning that the discretization of the simulation is too coarse.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum TrotterWarning {
+        /// The ring polymer has too few beads to resolve the quantum fluctuations.
+        TooFewBeads {
+            /// The number of beads in use.
+            beads: usize,
+            /// The smallest number of beads the thresholds tolerate.
+            recommended: usize,
+        },
+        /// The primitive and the virial kinetic energies disagree, which does not
+        /// happen when the ring polymer distribution is sampled exactly,
+        /// so the time step is too large.
+        TimeStepTooLarge,
+    }
+
+    /// A running comparison of the primitive and the virial kinetic energy estimators.
+    ///
+    /// Both estimators have the same expectation in the distribution of the discretized
+    /// path integral, so a significant discrepancy between them reveals an integration error.
+    /// The virial kinetic energy relative to its classical value also yields the effective
+    /// frequency `ω` of a harmonic system with the same kinetic energy, and the bead count
+    /// is compared to `βħω`, the number of beads the quantum fluctuations require.
+    #[derive(Clone, Copy, Debug)]
+    pub struct TrotterDiagnostic<T> {
+        thresholds: TrotterThresholds<T>,
+        beads: usize,
+        classical_kinetic_energy: T,
+        samples: usize,
+        mean_primitive: T,
+        mean_virial: T,
+        mean_discrepancy: T,
+        squared_deviations: T,
+    }
+
+    impl<T: Float + From<f32>> TrotterDiagnostic<T> {
+        /// Constructs the diagnostic of a system of `degrees_of_freedom` degrees of freedom
+        /// sampled with `beads` beads at the thermal energy `thermal_energy`.
+        pub fn new(
+            beads: usize,
+            degrees_of_freedom: usize,
+            thermal_energy: T,
+            thresholds: TrotterThresholds<T>,
+        ) -> Self {
+            assert!(beads > 0, "the number of beads must be positive");
+            assert!(
+                degrees_of_freedom > 0,
+                "the number of degrees of freedom must be positive"
+            );
+            assert!(
+                thermal_energy > T::zero(),
+                "the thermal energy must be positive"
+            );
+            Self {
+                thresholds,
+                beads,
+                classical_kinetic_energy: <T as From<f32>>::from(0.5 * degrees_of_freedom as f32)
+                    * thermal_energy,
+                samples: 0,
+                mean_primitive: T::zero(),
+                mean_virial: T::zero(),
+                mean_discrepancy: T::zero(),
+                squared_deviations: T::zero(),
+            }
+        }
+
+        /// Adds the kinetic energies estimated in the same step.
+        pub fn record(&mut self, primitive: T, virial: T) {
+            self.samples += 1;
+            let samples = <T as From<f32>>::from(self.samples as f32);
+            self.mean_primitive = self.mean_primitive + (primitive - self.mean_primitive) / samples;
+            self.mean_virial = self.mean_virial + (virial - self.mean_virial) / samples;
+            let discrepancy = primitive - virial;
+            let deviation = discrepancy - self.mean_discrepancy;
+            self.mean_discrepancy = self.mean_discrepancy + deviation / samples;
+            self.squared_deviations =
+                self.squared_deviations + deviation * (discrepancy - self.mean_discrepancy);
+        }
+
+        /// Adds the kinetic energies received from the estimators,
+        /// skipping steps in which either of them was not calculated.
+        pub fn record_received(&mut self, primitive: Option<T>, virial: Option<T>) {
+            if let (Some(primitive), Some(virial)) = (primitive, virial) {
+                self.record(primitive, virial);
+            }
+        }
+
+        pub fn samples(&self) -> usize {
+            self.samples
+        }
+
+        pub fn mean_primitive(&self) -> T {
+            self.mean_primitive
+        }
+
+        pub fn mean_virial(&self) -> T {
+            self.mean_virial
+        }
+
+        /// Returns the mean primitive minus the mean virial kinetic energy.
+        pub fn discrepancy(&self) -> T {
+            self.mean_discrepancy
+        }
+
+        /// Returns the discrepancy relative to the mean virial kinetic energy.
+        pub fn relative_discrepancy(&self) -> T {
+            self.mean_discrepancy / self.mean_virial.abs()
+        }
+
+        /// Returns the standard error of the discrepancy, assuming uncorrelated samples.
+        pub fn standard_error(&self) -> T {
+            if self.samples < 2 {
+                return T::infinity();
+            }
+            let samples = <T as From<f32>>::from(self.samples as f32);
+            (self.squared_deviations / (samples - T::one()) / samples).sqrt()
+        }
+
+        /// Returns `βħω`, where `ω` is the frequency of a harmonic oscillator
+        /// whose virial kinetic energy exceeds its classical value by the same ratio,
+        /// which solves `(x / 2) coth(x / 2) = K / K_classical`.
+        pub fn quantumness(&self) -> T {
+            let ratio = self.mean_virial / self.classical_kinetic_energy;
+            if ratio <= T::one() {
+                return T::zero();
+            }
+            let two = <T as From<f32>>::from(2.0);
+            let kinetic_ratio = |x: T| {
+                let half = x / two;
+                half / half.tanh()
+            };
+            let (mut lower, mut upper) = (T::zero(), two * ratio + two);
+            for _ in 0..100 {
+                let middle = (lower + upper) / two;
+                if kinetic_ratio(middle) < ratio {
+                    lower = middle;
+                } else {
+                    upper = middle;
+                }
+            }
+            (lower + upper) / two
+        }
+
+        /// Returns the smallest number of beads the thresholds tolerate.
+        pub fn recommended_beads(&self) -> usize {
+            (self.thresholds.beads_per_quantumness * self.quantumness())
+                .ceil()
+                .to_usize()
+                .unwrap_or(usize::MAX)
+                .max(1)
+        }
+
+        /// Returns the warnings raised by the samples so far,
+        /// or none before the minimal number of samples.
+        pub fn warnings(&self) -> Vec<TrotterWarning> {
+            let mut warnings = Vec::new();
+            if self.samples < self.thresholds.min_samples {
+                return warnings;
+            }
+            let recommended = self.recommended_beads();
+            if self.beads < recommended {
+                warnings.push(TrotterWarning::TooFewBeads {
+                    beads: self.beads,
+                    recommended,
+                });
+            }
+            if self.relative_discrepancy().abs() > self.thresholds.relative_discrepancy
+                && self.discrepancy().abs() > self.thresholds.significance * self.standard_error()
+            {
+                warnings.push(TrotterWarning::TimeStepTooLarge);
+            }
+            warnings
+        }
+
+        /// Registers the values written by [`TrotterDiagnostic::write_values`].
+        pub fn register_values(registry: &mut ObservableRegistry) {
+            registry.register("trotter_discrepancy", Dimension::ENERGY);
+            registry.register("trotter_discrepancy_error", Dimension::ENERGY);
+            registry.register("recommended_beads", Dimension::DIMENSIONLESS);
+        }
+
+        /// Writes the discrepancy, its standard error and the recommended number of beads
+        /// to a stream of debug observables.
+        pub fn write_values<O>(&self, stream: &mut O) -> Result<(), O::Error>
+        where
+            O: ValuesOutput<T> + ?Sized,
+        {
+            stream.write_value(self.discrepancy())?;
+            stream.write_value(self.standard_error())?;
+            stream.write_value(<T as From<f32>>::from(self.recommended_beads() as f32))
+        }
+
+        pub fn reset(&mut self) {
+            *self = Self {
+                samples: 0,
+                mean_primitive: T::zero(),
+                mean_virial: T::zero(),
+                mean_discrepancy: T::zero(),
+                squared_deviations: T::zero(),
+                ..*self
+            };
+        }
+    }
+}
+
+pub use diagnostic::{TrotterDiagnostic, TrotterWarning};
@@ -0,0 +1,234 @@
+mod ghost {
+    use num::Float;
+    use rand::Rng;
+    use rand_distr::{Distribution, StandardNormal};
+
+    use crate::core::constants::REDUCED_PLANK_CONSTANT;
+
+    /// Samples the displacements of the `N`-dimensional beads of a free ring polymer
+    /// of `beads` beads from its centroid with the Lévy construction, where `lambda_squared`
+    /// is the variance `βħ² / (m P)` of a spring between two neighboring beads.
+    pub(super) fn free_ring<const N: usize, T, R>(
+        beads: usize,
+        lambda_squared: T,
+        rng: &mut R,
+    ) -> Vec<[T; N]>
+    where
+        T: Float + From<f32>,
+        R: Rng,
+    {
+        let mut ring = vec![[T::zero(); N]; beads];
+        for bead in 1..beads {
+            let remaining = <T as From<f32>>::from((beads - bead) as f32);
+            let weight = remaining / (remaining + T::one());
+            let deviation = (lambda_squared * weight).sqrt();
+            let previous = ring[bead - 1];
+            for (coordinate, previous) in ring[bead].iter_mut().zip(previous) {
+                // The path closes at the first bead, which sits at the origin.
+                let noise: f32 = StandardNormal.sample(rng);
+                *coordinate = previous * weight + deviation * <T as From<f32>>::from(noise);
+            }
+        }
+        let count = <T as From<f32>>::from(beads as f32);
+        let mut centroid = [T::zero(); N];
+        for bead in &ring {
+            for (sum, &coordinate) in centroid.iter_mut().zip(bead) {
+                *sum = *sum + coordinate / count;
+            }
+        }
+        for bead in &mut ring {
+            for (coordinate, &center) in bead.iter_mut().zip(&centroid) {
+                *coordinate = *coordinate - center;
+            }
+        }
+        ring
+    }
+
+    /// Returns `βħ² / (m P)` at the thermal energy `thermal_energy`.
+    pub(super) fn bead_spread<T: Float + From<f32>>(mass: T, thermal_energy: T, beads: usize) -> T {
+        let hbar = <T as From<f32>>::from(REDUCED_PLANK_CONSTANT);
+        hbar * hbar / (mass * thermal_energy * <T as From<f32>>::from(beads as f32))
+    }
+}
+
+mod insertion {
+    use lib::{
+        core::Vector,
+        output::{Dimension, ObservableRegistry, ValuesOutput},
+    };
+    use num::Float;
+    use rand::{
+        Rng,
+        distr::{Distribution, StandardUniform},
+    };
+
+    use super::ghost::{bead_spread, free_ring};
+    use crate::{
+        estimator::superfluid::BlockAverage,
+        potential::{electrostatics::PeriodicBox, pair::PairParameters},
+    };
+
+    /// The Widom estimator `μ_ex = -kT ln⟨exp(-β ΔU)⟩` of the excess chemical potential
+    /// of a particle, where `ΔU` is the energy of a ghost inserted at random into the system.
+    ///
+    /// Ghosts interact with the atoms through truncated Lennard-Jones potentials only, so no forces
+    /// are calculated and the system is left untouched. In a path integral simulation, the ghost
+    /// interacts with every image of the atoms through the bead of the same image and `ΔU` is averaged
+    /// over the images. The ghost is classical, with every bead at the insertion point, unless given
+    /// a mass with [`WidomInsertionObservable::with_quantum_ghost`], in which case its beads
+    /// are sampled as a free ring polymer around the insertion point.
+    pub struct WidomInsertionObservable<const N: usize, T, R> {
+        /// The parameters of the ghost with the atoms of every group.
+        parameters: Vec<PairParameters<T>>,
+        cutoff: T,
+        thermal_energy: T,
+        insertions: usize,
+        ghost_mass: Option<T>,
+        blocks: BlockAverage<T>,
+        rng: R,
+    }
+
+    impl<const N: usize, T, R> WidomInsertionObservable<N, T, R>
+    where
+        T: Float + From<f32>,
+        R: Rng,
+    {
+        /// Constructs an estimator inserting `insertions` ghosts in every sample,
+        /// interacting with the atoms of each group through the respective parameters
+        /// in `parameters` up to `cutoff`.
+        ///
+        /// Samples are averaged in blocks of `block_size` to estimate the error bar.
+        pub fn new(
+            parameters: Vec<PairParameters<T>>,
+            cutoff: T,
+            thermal_energy: T,
+            insertions: usize,
+            block_size: usize,
+            rng: R,
+        ) -> Self {
+            assert!(cutoff > T::zero(), "the cutoff must be positive");
+            assert!(
+                thermal_energy > T::zero(),
+                "the thermal energy must be positive"
+            );
+            assert!(insertions > 0, "every sample must insert ghosts");
+            Self {
+                parameters,
+                cutoff,
+                thermal_energy,
+                insertions,
+                ghost_mass: None,
+                blocks: BlockAverage::new(block_size),
+                rng,
+            }
+        }
+
+        /// Inserts ghosts of mass `mass` as free ring polymers.
+        pub fn with_quantum_ghost(mut self, mass: T) -> Self {
+            assert!(mass > T::zero(), "the mass must be positive");
+            self.ghost_mass = Some(mass);
+            self
+        }
+
+        /// Inserts ghosts into a configuration in which `groups[group][image]`
+        /// holds the positions of the atoms of a group in an image.
+        ///
+        /// # Panics
+        ///
+        /// Panics if there is not a set of parameters for every group
+        /// or the groups have different numbers of images.
+        pub fn record<V>(&mut self, periodic_box: &PeriodicBox<T, N>, groups: &[&[&[V]]])
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            assert_eq!(
+                groups.len(),
+                self.parameters.len(),
+                "every group must have parameters"
+            );
+            let images = groups.first().map_or(1, |group| group.len());
+            assert!(
+                groups.iter().all(|group| group.len() == images),
+                "every group must have the same number of images"
+            );
+            let lengths = periodic_box.lengths();
+            let cutoff_squared = self.cutoff * self.cutoff;
+            let beta = self.thermal_energy.recip();
+            let image_count = <T as From<f32>>::from(images.max(1) as f32);
+            let mut boltzmann_factors = T::zero();
+            for _ in 0..self.insertions {
+                let center: [T; N] = std::array::from_fn(|axis| {
+                    let uniform: f32 = StandardUniform.sample(&mut self.rng);
+                    lengths[axis] * <T as From<f32>>::from(uniform)
+                });
+                let ring = match self.ghost_mass {
+                    Some(mass) => free_ring::<N, T, R>(
+                        images,
+                        bead_spread(mass, self.thermal_energy, images),
+                        &mut self.rng,
+                    ),
+                    None => vec![[T::zero(); N]; images],
+                };
+                let mut energy = T::zero();
+                for (group, parameters) in groups.iter().zip(&self.parameters) {
+                    for (image, offset) in group.iter().zip(&ring) {
+                        let mut ghost = V::from(center);
+                        for (coordinate, &shift) in ghost.as_mut_array().iter_mut().zip(offset) {
+                            *coordinate = *coordinate + shift;
+                        }
+                        for position in image.iter() {
+                            let distance_squared = periodic_box
+                                .minimum_image(ghost.clone() - position.clone())
+                                .magnitude_squared();
+                            if distance_squared < cutoff_squared {
+                                energy = energy + parameters.lennard_jones(distance_squared).0;
+                            }
+                        }
+                    }
+                }
+                boltzmann_factors = boltzmann_factors + (-beta * energy / image_count).exp();
+            }
+            self.blocks
+                .add(boltzmann_factors / <T as From<f32>>::from(self.insertions as f32));
+        }
+
+        /// Returns the excess chemical potential, or `None` before the first completed block.
+        pub fn excess_chemical_potential(&self) -> Option<T> {
+            self.blocks
+                .mean()
+                .map(|mean| -self.thermal_energy * mean.ln())
+        }
+
+        /// Returns the standard error of the excess chemical potential propagated from the
+        /// block averages of the Boltzmann factor, or `None` before two blocks are completed.
+        pub fn standard_error(&self) -> Option<T> {
+            Some(self.thermal_energy * self.blocks.standard_error()? / self.blocks.mean()?)
+        }
+
+        pub fn blocks(&self) -> &BlockAverage<T> {
+            &self.blocks
+        }
+
+        /// Registers the values written by [`WidomInsertionObservable::write_values`].
+        pub fn register_values(registry: &mut ObservableRegistry) {
+            registry.register("excess_chemical_potential", Dimension::ENERGY);
+            registry.register("excess_chemical_potential_error", Dimension::ENERGY);
+        }
+
+        /// Writes the excess chemical potential and its standard error,
+        /// either of which is NaN until enough blocks are completed.
+        pub fn write_values<O>(&self, stream: &mut O) -> Result<(), O::Error>
+        where
+            O: ValuesOutput<T> + ?Sized,
+        {
+            stream.write_value(self.excess_chemical_potential().unwrap_or(T::nan()))?;
+            stream.write_value(self.standard_error().unwrap_or(T::nan()))
+        }
+
+        pub fn reset(&mut self) {
+            self.blocks.reset();
+        }
+    }
+}
+
+pub use insertion::WidomInsertionObservable;
@@ -1,11 +1,37 @@
-#![feature(portable_simd)]
+#![cfg_attr(not(feature = "stable"), feature(portable_simd))]
 
+pub mod analysis;
+pub mod colvar;
 pub mod core;
 pub mod estimator;
+pub mod manifold;
+pub mod memory;
+pub mod minimize;
+pub mod output;
+pub mod parallel;
 pub mod potential;
+pub mod profile;
+pub mod propagator;
+pub mod rare_event;
+pub mod replay;
+pub mod resolution;
+pub mod steering;
+pub mod sweep;
+pub mod systems;
 pub mod thermostat;
+pub mod util;
 pub mod vector;
 
+/// Counts the allocations so that memory usage can be reported
+/// and the hot loop checked for allocations.
+#[cfg(feature = "track_allocations")]
+#[global_allocator]
+static ALLOCATOR: memory::TrackingAllocator = memory::TrackingAllocator::new(std::alloc::System);
+
 fn main() {
     println!("Hello, world!");
+    let warnings = lib::core::warnings::WarningSink::global().summary();
+    if warnings.total() > 0 {
+        eprintln!("{}", warnings);
+    }
 }
@@ -0,0 +1,98 @@
+mod sphere {
+    use lib::core::{Vector, manifold::Manifold};
+    use num::Float;
+
+    /// The sphere of a given radius centered at the origin of `N`-dimensional space,
+    /// e.g. the surface of a ball for `N = 3` or a ring for `N = 2`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Sphere<T> {
+        radius: T,
+    }
+
+    impl<T: Float> Sphere<T> {
+        pub fn new(radius: T) -> Self {
+            assert!(radius > T::zero(), "the radius must be positive");
+            Self { radius }
+        }
+
+        pub fn radius(&self) -> T {
+            self.radius
+        }
+
+        /// Returns the point of the sphere closest to `point`.
+        pub fn project<const N: usize, V>(&self, point: V) -> V
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let norm = point.clone().magnitude_squared().sqrt();
+            point * (self.radius / norm)
+        }
+
+        /// Returns the angle between the directions of `from` and `to`, computed from the
+        /// chord between them, which stays accurate for nearby points unlike the arccosine.
+        fn angle<const N: usize, V>(&self, from: &V, to: &V) -> T
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let unit = |point: &V| point.clone() / point.clone().magnitude_squared().sqrt();
+            let chord = (unit(to) - unit(from)).magnitude_squared().sqrt();
+            let two = T::one() + T::one();
+            two * (chord / two).min(T::one()).asin()
+        }
+    }
+
+    impl<const N: usize, T, V> Manifold<N, V> for Sphere<T>
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+    {
+        fn distance_squared(&self, from: &V, to: &V) -> T {
+            let arc = self.radius * self.angle(from, to);
+            arc * arc
+        }
+
+        fn log_map(&self, from: &V, to: &V) -> V {
+            let direction = self.project_tangent(from, to.clone());
+            let norm = direction.clone().magnitude_squared().sqrt();
+            if norm == T::zero() {
+                return direction;
+            }
+            direction * (self.radius * self.angle(from, to) / norm)
+        }
+
+        fn exp_map(&self, point: &V, tangent: V) -> V {
+            let length = tangent.clone().magnitude_squared().sqrt();
+            if length == T::zero() {
+                return point.clone();
+            }
+            let angle = length / self.radius;
+            // The result is projected back to keep rounding errors from leaving the sphere.
+            self.project(
+                point.clone() * angle.cos() + tangent * (self.radius * angle.sin() / length),
+            )
+        }
+
+        fn project_tangent(&self, point: &V, vector: V) -> V {
+            let normal_component =
+                vector.clone().dot(point.clone()) / point.clone().magnitude_squared();
+            vector - point.clone() * normal_component
+        }
+
+        fn transport(&self, from: &V, to: &V, tangent: V) -> V {
+            let direction = self.log_map(from, to);
+            let length = direction.clone().magnitude_squared().sqrt();
+            if length == T::zero() {
+                return tangent;
+            }
+            let direction = direction / length;
+            let angle = length / self.radius;
+            // Only the component along the geodesic turns; the rest is parallel to the axis
+            // of the rotation carrying `from` to `to`.
+            let along = tangent.clone().dot(direction.clone());
+            tangent - direction.clone() * along
+                + (direction * angle.cos() - from.clone() * (angle.sin() / self.radius)) * along
+        }
+    }
+}
+
+pub use sphere::Sphere;
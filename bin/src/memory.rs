@@ -0,0 +1,505 @@
+mod allocator {
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    thread_local! {
+        /// The number of allocations made by this thread inside hot loops.
+        static HOT_ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+    }
+
+    /// The allocation counters at a point in time.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct AllocationStats {
+        /// The number of bytes currently allocated.
+        pub current: usize,
+        /// The largest number of bytes allocated at once since the last reset of the peak.
+        pub peak: usize,
+        /// The number of allocations made so far.
+        pub allocations: usize,
+    }
+
+    /// A global allocator which counts the allocated bytes and the allocations
+    /// made by threads inside a [`HotLoopGuard`].
+    pub struct TrackingAllocator<A = System> {
+        inner: A,
+        current: AtomicUsize,
+        peak: AtomicUsize,
+        allocations: AtomicUsize,
+    }
+
+    impl<A> TrackingAllocator<A> {
+        pub const fn new(inner: A) -> Self {
+            Self {
+                inner,
+                current: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+                allocations: AtomicUsize::new(0),
+            }
+        }
+
+        pub fn stats(&self) -> AllocationStats {
+            AllocationStats {
+                current: self.current.load(Ordering::Relaxed),
+                peak: self.peak.load(Ordering::Relaxed),
+                allocations: self.allocations.load(Ordering::Relaxed),
+            }
+        }
+
+        /// Lowers the peak to the current usage, so that the next peak
+        /// reflects the steady state rather than the setup.
+        pub fn reset_peak(&self) {
+            self.peak
+                .store(self.current.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+
+        fn grow(&self, size: usize) {
+            let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+            self.peak.fetch_max(current, Ordering::Relaxed);
+            self.allocations.fetch_add(1, Ordering::Relaxed);
+            // `try_with` fails only while the thread is being torn down.
+            let _ = HOT_ALLOCATIONS.try_with(|count| {
+                if let Some(allocations) = count.get() {
+                    count.set(Some(allocations + 1));
+                }
+            });
+        }
+
+        fn shrink(&self, size: usize) {
+            self.current.fetch_sub(size, Ordering::Relaxed);
+        }
+    }
+
+    // SAFETY: Every method forwards to the inner allocator unchanged.
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            // SAFETY: Upheld by the caller.
+            let ptr = unsafe { self.inner.alloc(layout) };
+            if !ptr.is_null() {
+                self.grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            // SAFETY: Upheld by the caller.
+            let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+            if !ptr.is_null() {
+                self.grow(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            // SAFETY: Upheld by the caller.
+            unsafe { self.inner.dealloc(ptr, layout) };
+            self.shrink(layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            // SAFETY: Upheld by the caller.
+            let new_ptr = unsafe { self.inner.realloc(ptr, layout, new_size) };
+            if !new_ptr.is_null() {
+                self.shrink(layout.size());
+                self.grow(new_size);
+            }
+            new_ptr
+        }
+    }
+
+    /// Marks the code running on this thread while the guard is alive as a hot loop,
+    /// in which nothing may be allocated.
+    ///
+    /// Allocations cannot fail loudly inside the allocator, so they are counted
+    /// and checked by a debug assertion when the guard is dropped.
+    /// Without a [`TrackingAllocator`] installed, nothing is counted.
+    pub struct HotLoopGuard {
+        outer: Option<usize>,
+    }
+
+    impl HotLoopGuard {
+        pub fn enter() -> Self {
+            Self {
+                outer: HOT_ALLOCATIONS.with(|count| count.replace(Some(0))),
+            }
+        }
+
+        /// Returns the number of allocations made inside the hot loop so far.
+        pub fn allocations(&self) -> usize {
+            HOT_ALLOCATIONS.with(|count| count.get().unwrap_or(0))
+        }
+    }
+
+    impl Drop for HotLoopGuard {
+        fn drop(&mut self) {
+            let allocations = HOT_ALLOCATIONS.with(|count| {
+                let allocations = count.get().unwrap_or(0);
+                count.set(self.outer.map(|outer| outer + allocations));
+                allocations
+            });
+            debug_assert!(
+                allocations == 0 || std::thread::panicking(),
+                "{} allocations inside a hot loop",
+                allocations
+            );
+        }
+    }
+}
+
+pub use allocator::{AllocationStats, HotLoopGuard, TrackingAllocator};
+
+mod report {
+    use std::{
+        fmt::{Display, Formatter, Result as FmtResult},
+        mem,
+    };
+
+    use super::{AllocationStats, ArenaStats};
+
+    /// Returns the number of bytes held by `len` values of type `V`.
+    pub const fn bytes_of<V>(len: usize) -> usize {
+        len * mem::size_of::<V>()
+    }
+
+    /// The memory held by every subsystem, e.g. the positions, momenta and forces
+    /// of every replica, the neighbor lists or the observables,
+    /// together with the totals seen by the allocator.
+    #[derive(Clone, Debug, Default)]
+    pub struct MemoryReport {
+        subsystems: Vec<(String, usize)>,
+        setup: Option<AllocationStats>,
+        steady: Option<AllocationStats>,
+        scratch: Option<ArenaStats>,
+    }
+
+    impl MemoryReport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Adds `bytes` to the memory held by `subsystem`.
+        pub fn record(&mut self, subsystem: &str, bytes: usize) {
+            match self
+                .subsystems
+                .iter_mut()
+                .find(|(name, _)| name == subsystem)
+            {
+                Some((_, total)) => *total += bytes,
+                None => self.subsystems.push((subsystem.to_owned(), bytes)),
+            }
+        }
+
+        /// Records the allocator counters at the end of the setup.
+        pub fn set_setup(&mut self, stats: AllocationStats) {
+            self.setup = Some(stats);
+        }
+
+        /// Records the allocator counters after the steady-state run.
+        pub fn set_steady(&mut self, stats: AllocationStats) {
+            self.steady = Some(stats);
+        }
+
+        /// Adds the usage of the scratch arena of a thread, keeping the largest peak
+        /// and the total capacity over the threads.
+        pub fn add_scratch(&mut self, stats: ArenaStats) {
+            let scratch = self.scratch.get_or_insert_default();
+            scratch.peak = scratch.peak.max(stats.peak);
+            scratch.capacity += stats.capacity;
+            scratch.growths += stats.growths;
+        }
+
+        pub fn subsystems(&self) -> &[(String, usize)] {
+            &self.subsystems
+        }
+
+        /// Returns the total memory recorded for the subsystems.
+        pub fn total(&self) -> usize {
+            self.subsystems.iter().map(|(_, bytes)| bytes).sum()
+        }
+    }
+
+    impl Display for MemoryReport {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            for (subsystem, bytes) in &self.subsystems {
+                writeln!(f, "{:<32}{:>16} B", subsystem, bytes)?;
+            }
+            writeln!(f, "{:<32}{:>16} B", "total", self.total())?;
+            for (stage, stats) in [("setup", self.setup), ("steady state", self.steady)] {
+                if let Some(stats) = stats {
+                    writeln!(
+                        f,
+                        "{:<32}{:>16} B current, {} B peak, {} allocations",
+                        stage, stats.current, stats.peak, stats.allocations
+                    )?;
+                }
+            }
+            if let Some(scratch) = self.scratch {
+                writeln!(
+                    f,
+                    "{:<32}{:>16} B held, {} B peak per step, {} growths",
+                    "scratch arenas", scratch.capacity, scratch.peak, scratch.growths
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use report::{MemoryReport, bytes_of};
+
+mod arena {
+    use std::{
+        alloc::{self, Layout},
+        cell::{Cell, UnsafeCell},
+        ptr::NonNull,
+        slice,
+    };
+
+    /// The alignment of every chunk, enough for SIMD vectors.
+    const CHUNK_ALIGN: usize = 64;
+
+    /// The size of the first chunk.
+    const INITIAL_CAPACITY: usize = 4096;
+
+    struct Chunk {
+        ptr: NonNull<u8>,
+        layout: Layout,
+    }
+
+    impl Chunk {
+        fn new(capacity: usize) -> Self {
+            let layout = Layout::from_size_align(capacity, CHUNK_ALIGN)
+                .expect("the capacity of a chunk must not overflow");
+            // SAFETY: The layout has a nonzero size, as the capacity is never zero.
+            let ptr = unsafe { alloc::alloc(layout) };
+            Self {
+                ptr: NonNull::new(ptr).unwrap_or_else(|| alloc::handle_alloc_error(layout)),
+                layout,
+            }
+        }
+
+        fn capacity(&self) -> usize {
+            self.layout.size()
+        }
+    }
+
+    impl Drop for Chunk {
+        fn drop(&mut self) {
+            // SAFETY: `ptr` has been allocated with `layout` in `Chunk::new`.
+            unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+        }
+    }
+
+    /// The usage of a [`ScratchArena`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct ArenaStats {
+        /// The number of bytes handed out since the last reset.
+        pub used: usize,
+        /// The largest number of bytes handed out within a single step.
+        pub peak: usize,
+        /// The number of bytes held by the arena.
+        pub capacity: usize,
+        /// The number of times the arena has had to allocate a new chunk.
+        pub growths: usize,
+    }
+
+    /// A bump allocator for the temporary buffers of a single thread within a step,
+    /// e.g. the candidates of a neighbor rebuild or the normal modes of a group.
+    ///
+    /// Buffers are handed out by shared reference and all of them are released at once
+    /// when the next step begins, so the borrow checker ensures none outlives its step.
+    /// Once the arena has grown to the peak usage of a step, which happens in the first few steps,
+    /// it no longer allocates.
+    pub struct ScratchArena {
+        chunks: UnsafeCell<Vec<Chunk>>,
+        /// The index of the chunk being filled.
+        current: Cell<usize>,
+        /// The number of bytes used in the chunk being filled.
+        offset: Cell<usize>,
+        used: Cell<usize>,
+        peak: Cell<usize>,
+        growths: Cell<usize>,
+    }
+
+    // SAFETY: The arena owns its chunks and hands out borrows of `self` only,
+    // so moving it to another thread moves every buffer with it.
+    unsafe impl Send for ScratchArena {}
+
+    impl ScratchArena {
+        pub fn new() -> Self {
+            Self::with_capacity(INITIAL_CAPACITY)
+        }
+
+        /// Constructs an arena which does not allocate until more than `capacity` bytes are used in a step.
+        pub fn with_capacity(capacity: usize) -> Self {
+            Self {
+                chunks: UnsafeCell::new(vec![Chunk::new(capacity.max(1))]),
+                current: Cell::new(0),
+                offset: Cell::new(0),
+                used: Cell::new(0),
+                peak: Cell::new(0),
+                growths: Cell::new(0),
+            }
+        }
+
+        pub fn stats(&self) -> ArenaStats {
+            ArenaStats {
+                used: self.used.get(),
+                peak: self.peak.get().max(self.used.get()),
+                capacity: self.chunks().iter().map(Chunk::capacity).sum(),
+                growths: self.growths.get(),
+            }
+        }
+
+        /// Returns a buffer of `len` copies of `value`, valid until the arena is reset.
+        #[allow(clippy::mut_from_ref)]
+        pub fn alloc_slice<T: Copy>(&self, len: usize, value: T) -> &mut [T] {
+            let ptr = self.alloc_bytes(Layout::array::<T>(len).expect("the buffer is too large"));
+            let ptr = ptr.cast::<T>().as_ptr();
+            for index in 0..len {
+                // SAFETY: `ptr` is aligned for `T` and valid for `len` values of `T`.
+                unsafe { ptr.add(index).write(value) };
+            }
+            // SAFETY: Every value has been initialized, and the memory is not handed out again
+            // before `reset`, which takes `self` mutably, so no other borrow aliases it.
+            unsafe { slice::from_raw_parts_mut(ptr, len) }
+        }
+
+        /// Returns a copy of `values` in the arena, valid until the arena is reset.
+        #[allow(clippy::mut_from_ref)]
+        pub fn alloc_copy<T: Copy>(&self, values: &[T]) -> &mut [T] {
+            let ptr = self.alloc_bytes(Layout::for_value(values));
+            let ptr = ptr.cast::<T>().as_ptr();
+            // SAFETY: `ptr` is aligned for `T`, valid for `values.len()` values
+            // and does not overlap `values`, which lie outside the unused part of the arena.
+            unsafe {
+                ptr.copy_from_nonoverlapping(values.as_ptr(), values.len());
+                slice::from_raw_parts_mut(ptr, values.len())
+            }
+        }
+
+        /// Releases every buffer, keeping the memory for the next step.
+        ///
+        /// If the last step needed more than one chunk, they are replaced by a single chunk
+        /// large enough for all of them, so that later steps of the same size do not allocate.
+        pub fn reset(&mut self) {
+            let used = self.used.replace(0);
+            self.peak.set(self.peak.get().max(used));
+            self.current.set(0);
+            self.offset.set(0);
+            let chunks = self.chunks.get_mut();
+            if chunks.len() > 1 {
+                let capacity = chunks.iter().map(Chunk::capacity).sum();
+                *chunks = vec![Chunk::new(capacity)];
+            }
+        }
+
+        /// Resets the arena and returns the context of `step` through which it is used.
+        pub fn begin_step(&mut self, step: usize) -> StepContext<'_> {
+            self.reset();
+            StepContext {
+                step,
+                scratch: self,
+            }
+        }
+
+        fn chunks(&self) -> &[Chunk] {
+            // SAFETY: The vector is only mutated by `alloc_bytes` and `reset`,
+            // neither of which runs while the returned borrow is alive on this thread,
+            // as the arena is not `Sync`.
+            unsafe { &*self.chunks.get() }
+        }
+
+        fn alloc_bytes(&self, layout: Layout) -> NonNull<u8> {
+            debug_assert!(layout.align() <= CHUNK_ALIGN, "the alignment is too large");
+            let size = layout.size();
+            self.used.set(self.used.get() + size);
+            {
+                let chunks = self.chunks();
+                let mut current = self.current.get();
+                while let Some(chunk) = chunks.get(current) {
+                    let start = self.offset.get().next_multiple_of(layout.align());
+                    if start + size <= chunk.capacity() {
+                        self.current.set(current);
+                        self.offset.set(start + size);
+                        // SAFETY: `start + size` is within the chunk.
+                        return unsafe { chunk.ptr.add(start) };
+                    }
+                    current += 1;
+                    self.offset.set(0);
+                }
+            }
+            // SAFETY: No borrow of the vector is alive, and the buffers already handed out
+            // point into the chunks themselves, which do not move when the vector grows.
+            let chunks = unsafe { &mut *self.chunks.get() };
+            let capacity = size.max(chunks.last().map_or(0, Chunk::capacity) * 2);
+            chunks.push(Chunk::new(capacity.max(1)));
+            self.growths.set(self.growths.get() + 1);
+            self.current.set(chunks.len() - 1);
+            self.offset.set(size);
+            chunks[chunks.len() - 1].ptr
+        }
+    }
+
+    impl Default for ScratchArena {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// The state shared by everything a thread does within a single step.
+    pub struct StepContext<'a> {
+        pub step: usize,
+        /// The arena for temporary buffers, reset at the beginning of the step.
+        pub scratch: &'a ScratchArena,
+    }
+
+    impl<'a> StepContext<'a> {
+        /// Returns a buffer of `len` copies of `value` which lives until the end of the step.
+        pub fn scratch_slice<T: Copy>(&self, len: usize, value: T) -> &'a mut [T] {
+            self.scratch.alloc_slice(len, value)
+        }
+    }
+}
+
+pub use arena::{ArenaStats, ScratchArena, StepContext};
+
+mod tracking {
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    use lib::events::{EventBus, StepFinished, StepStarted};
+
+    use super::{MemoryReport, TrackingAllocator};
+
+    /// Fills `report` with the counters of `allocator` over a run whose driver publishes
+    /// its events on `events`.
+    ///
+    /// The setup ends when the first step starts, at which point the peak is reset,
+    /// and the steady state is recorded whenever a step has been finalized.
+    pub fn track_run<A: Sync>(
+        events: &mut EventBus,
+        allocator: &'static TrackingAllocator<A>,
+        report: Arc<Mutex<MemoryReport>>,
+    ) {
+        let setup = Arc::clone(&report);
+        events.subscribe(move |event: &StepStarted| {
+            if event.step == 0 {
+                setup
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .set_setup(allocator.stats());
+                allocator.reset_peak();
+            }
+        });
+        events.subscribe(move |_: &StepFinished| {
+            report
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .set_steady(allocator.stats());
+        });
+    }
+}
+
+pub use tracking::track_run;
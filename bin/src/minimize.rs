@@ -0,0 +1,444 @@
+mod fire {
+    use lib::core::Vector;
+    use num::Float;
+
+    /// The parameters of the FIRE algorithm, with the defaults of Bitzek et al.,
+    /// Phys. Rev. Lett. 97, 170201 (2006).
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct FireParameters<T> {
+        /// The initial time step.
+        pub time_step: T,
+        /// The largest time step.
+        pub max_time_step: T,
+        /// The number of steps downhill before the time step may grow.
+        pub delay: usize,
+        /// The factor the time step grows by.
+        pub increase: T,
+        /// The factor the time step shrinks by when the system moves uphill.
+        pub decrease: T,
+        /// The initial fraction of the velocities turned along the forces.
+        pub mixing: T,
+        /// The factor the mixing decays by.
+        pub mixing_decrease: T,
+    }
+
+    impl<T: Float + From<f32>> FireParameters<T> {
+        /// Constructs the default parameters with the initial time step `time_step`.
+        pub fn new(time_step: T) -> Self {
+            assert!(time_step > T::zero(), "the time step must be positive");
+            Self {
+                time_step,
+                max_time_step: time_step * <T as From<f32>>::from(10.0),
+                delay: 5,
+                increase: <T as From<f32>>::from(1.1),
+                decrease: <T as From<f32>>::from(0.5),
+                mixing: <T as From<f32>>::from(0.1),
+                mixing_decrease: <T as From<f32>>::from(0.99),
+            }
+        }
+    }
+
+    /// The state of the fast inertial relaxation engine, which relaxes a configuration
+    /// by damped dynamics of unit masses whose velocities are turned along the forces
+    /// and stopped whenever the system moves uphill.
+    ///
+    /// A step consists of [`Fire::adapt`], which needs the power `F · v`, `|F|` and `|v|`
+    /// summed over all atoms, [`Fire::mix`] and a semi-implicit Euler step with [`Fire::integrate`].
+    #[derive(Clone, Copy, Debug)]
+    pub struct Fire<T> {
+        parameters: FireParameters<T>,
+        time_step: T,
+        mixing: T,
+        downhill_steps: usize,
+    }
+
+    /// The sums over the atoms a [`Fire`] step depends on.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct FireSums<T> {
+        /// The power `Σ F · v`.
+        pub power: T,
+        /// The squared norm of the velocities.
+        pub velocity_squared: T,
+        /// The squared norm of the forces.
+        pub force_squared: T,
+    }
+
+    impl<T: Float> FireSums<T> {
+        /// Returns the sums over the atoms with `velocities` under `forces`.
+        pub fn of<const N: usize, V>(velocities: &[V], forces: &[V]) -> Self
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            velocities
+                .iter()
+                .zip(forces)
+                .fold(Self::zero(), |sums, (velocity, force)| Self {
+                    power: sums.power + velocity.clone().dot(force.clone()),
+                    velocity_squared: sums.velocity_squared + velocity.clone().magnitude_squared(),
+                    force_squared: sums.force_squared + force.clone().magnitude_squared(),
+                })
+        }
+
+        pub fn zero() -> Self {
+            Self {
+                power: T::zero(),
+                velocity_squared: T::zero(),
+                force_squared: T::zero(),
+            }
+        }
+
+        /// Adds the sums over another set of atoms.
+        pub fn combine(self, other: Self) -> Self {
+            Self {
+                power: self.power + other.power,
+                velocity_squared: self.velocity_squared + other.velocity_squared,
+                force_squared: self.force_squared + other.force_squared,
+            }
+        }
+    }
+
+    impl<T: Float> Fire<T> {
+        pub fn new(parameters: FireParameters<T>) -> Self {
+            Self {
+                parameters,
+                time_step: parameters.time_step,
+                mixing: parameters.mixing,
+                downhill_steps: 0,
+            }
+        }
+
+        pub fn parameters(&self) -> &FireParameters<T> {
+            &self.parameters
+        }
+
+        /// Returns the current time step.
+        pub fn time_step(&self) -> T {
+            self.time_step
+        }
+
+        /// Adapts the time step and the mixing to the sums of the current configuration.
+        ///
+        /// Returns `false` if the system moves uphill, in which case the velocities must be zeroed
+        /// instead of mixed.
+        pub fn adapt(&mut self, sums: &FireSums<T>) -> bool {
+            if sums.power > T::zero() {
+                self.downhill_steps += 1;
+                if self.downhill_steps > self.parameters.delay {
+                    self.time_step = (self.time_step * self.parameters.increase)
+                        .min(self.parameters.max_time_step);
+                    self.mixing = self.mixing * self.parameters.mixing_decrease;
+                }
+                true
+            } else {
+                self.downhill_steps = 0;
+                self.time_step = self.time_step * self.parameters.decrease;
+                self.mixing = self.parameters.mixing;
+                false
+            }
+        }
+
+        /// Turns the velocities towards the forces, `v = (1 - α) v + α |v| F / |F|`,
+        /// or stops the atoms if `downhill` is `false`.
+        pub fn mix<const N: usize, V>(
+            &self,
+            downhill: bool,
+            sums: &FireSums<T>,
+            velocities: &mut [V],
+            forces: &[V],
+        ) where
+            V: Vector<N, Element = T> + Clone,
+        {
+            if !downhill {
+                velocities.fill_with(V::zero);
+                return;
+            }
+            let force_norm = sums.force_squared.sqrt();
+            if force_norm == T::zero() {
+                return;
+            }
+            let scale = self.mixing * sums.velocity_squared.sqrt() / force_norm;
+            for (velocity, force) in velocities.iter_mut().zip(forces) {
+                *velocity = velocity.clone() * (T::one() - self.mixing);
+                velocity.mul_add_assign(force, scale);
+            }
+        }
+
+        /// Accelerates the atoms of unit mass by `forces` and moves them for a time step.
+        pub fn integrate<const N: usize, V>(
+            &self,
+            positions: &mut [V],
+            velocities: &mut [V],
+            forces: &[V],
+        ) where
+            V: Vector<N, Element = T> + Clone,
+        {
+            for ((position, velocity), force) in positions.iter_mut().zip(velocities).zip(forces) {
+                velocity.mul_add_assign(force, self.time_step);
+                position.mul_add_assign(velocity, self.time_step);
+            }
+        }
+
+        /// Performs a whole step on a single set of atoms, recomputing `forces`
+        /// at the new positions with `calculate`, which returns the potential energy.
+        pub fn step<const N: usize, V, F, E>(
+            &mut self,
+            positions: &mut [V],
+            velocities: &mut [V],
+            forces: &mut [V],
+            mut calculate: F,
+        ) -> Result<T, E>
+        where
+            V: Vector<N, Element = T> + Clone,
+            F: FnMut(&[V], &mut [V]) -> Result<T, E>,
+        {
+            let sums = FireSums::of(velocities, forces);
+            let downhill = self.adapt(&sums);
+            self.mix(downhill, &sums, velocities, forces);
+            self.integrate(positions, velocities, forces);
+            calculate(positions, forces)
+        }
+    }
+}
+
+pub use fire::{Fire, FireParameters, FireSums};
+
+mod stage {
+    use super::{Fire, FireParameters};
+    use lib::core::Vector;
+    use num::Float;
+
+    /// The algorithm of a [`Minimization`].
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum MinimizationMethod<T> {
+        /// Steps along the forces, growing while the energy decreases
+        /// and shrinking otherwise, starting from a displacement of the atom
+        /// under the largest force by `max_displacement`.
+        SteepestDescent { max_displacement: T },
+        /// The FIRE algorithm with the given parameters.
+        Fire(FireParameters<T>),
+    }
+
+    /// The replicas of the atoms a [`Minimization`] moves.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum MinimizationTarget {
+        /// The centroids are moved, each replica keeping its displacement from its centroid,
+        /// under the force averaged over the images.
+        #[default]
+        Centroid,
+        /// Every replica is moved under its physical force.
+        AllBeads,
+    }
+
+    /// The outcome of a [`Minimization`].
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct MinimizationReport<T> {
+        pub iterations: usize,
+        /// The potential energy of the final configuration.
+        pub energy: T,
+        /// The largest force on an atom in the final configuration.
+        pub max_force: T,
+        /// Whether the largest force fell below the tolerance.
+        pub converged: bool,
+    }
+
+    /// A relaxation of the configuration performed before the simulation starts,
+    /// so that overlapping atoms in imported configurations do not blow up the first steps.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Minimization<T> {
+        pub method: MinimizationMethod<T>,
+        pub target: MinimizationTarget,
+        /// The largest force on an atom at which the configuration counts as relaxed.
+        pub force_tolerance: T,
+        pub max_iterations: usize,
+    }
+
+    impl<T: Float + From<f32>> Minimization<T> {
+        /// Constructs a minimization of the centroids with the FIRE algorithm.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `force_tolerance` or `time_step` is not positive.
+        pub fn new(force_tolerance: T, max_iterations: usize, time_step: T) -> Self {
+            assert!(
+                force_tolerance > T::zero(),
+                "the force tolerance must be positive"
+            );
+            Self {
+                method: MinimizationMethod::Fire(FireParameters::new(time_step)),
+                target: MinimizationTarget::Centroid,
+                force_tolerance,
+                max_iterations,
+            }
+        }
+
+        /// Relaxes `positions` under the forces set by `calculate`, which returns the potential energy.
+        pub fn minimize<const N: usize, V, F, E>(
+            &self,
+            positions: &mut [V],
+            mut calculate: F,
+        ) -> Result<MinimizationReport<T>, E>
+        where
+            V: Vector<N, Element = T> + Clone,
+            F: FnMut(&[V], &mut [V]) -> Result<T, E>,
+        {
+            let mut forces = vec![V::zero(); positions.len()];
+            let mut energy = calculate(positions, &mut forces)?;
+            let mut max_force = max_force(&forces);
+            let mut iterations = 0;
+            match self.method {
+                MinimizationMethod::SteepestDescent { max_displacement } => {
+                    let mut displacement = max_displacement;
+                    let mut trial = positions.to_vec();
+                    let mut trial_forces = forces.clone();
+                    while max_force > self.force_tolerance && iterations < self.max_iterations {
+                        iterations += 1;
+                        let scale = displacement / max_force;
+                        for ((trial, position), force) in
+                            trial.iter_mut().zip(&*positions).zip(&forces)
+                        {
+                            *trial = position.clone();
+                            trial.mul_add_assign(force, scale);
+                        }
+                        let trial_energy = calculate(&trial, &mut trial_forces)?;
+                        if trial_energy < energy {
+                            positions.clone_from_slice(&trial);
+                            std::mem::swap(&mut forces, &mut trial_forces);
+                            energy = trial_energy;
+                            max_force = self::max_force(&forces);
+                            displacement = displacement * <T as From<f32>>::from(1.2);
+                        } else {
+                            displacement = displacement * <T as From<f32>>::from(0.5);
+                        }
+                    }
+                }
+                MinimizationMethod::Fire(parameters) => {
+                    let mut fire = Fire::new(parameters);
+                    let mut velocities = vec![V::zero(); positions.len()];
+                    while max_force > self.force_tolerance && iterations < self.max_iterations {
+                        iterations += 1;
+                        energy =
+                            fire.step(positions, &mut velocities, &mut forces, &mut calculate)?;
+                        max_force = self::max_force(&forces);
+                    }
+                }
+            }
+            Ok(MinimizationReport {
+                iterations,
+                energy,
+                max_force,
+                converged: max_force <= self.force_tolerance,
+            })
+        }
+
+        /// Relaxes the ring polymers whose replicas in every image are given by `images`
+        /// according to the target, where `calculate` sets the physical forces
+        /// on the atoms of the image with the given index and returns their potential energy.
+        ///
+        /// The energy reported is averaged over the images.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the images hold different numbers of atoms.
+        pub fn minimize_images<const N: usize, V, F, E>(
+            &self,
+            images: &mut [Vec<V>],
+            mut calculate: F,
+        ) -> Result<MinimizationReport<T>, E>
+        where
+            V: Vector<N, Element = T> + Clone,
+            F: FnMut(usize, &[V], &mut [V]) -> Result<T, E>,
+        {
+            let Some(atoms) = images.first().map(Vec::len).filter(|&atoms| atoms > 0) else {
+                return self.minimize::<N, V, _, E>(&mut [], |_, _| Ok(T::zero()));
+            };
+            assert!(
+                images.iter().all(|image| image.len() == atoms),
+                "every image must hold the same number of atoms"
+            );
+            let image_count = <T as From<f32>>::from(images.len() as f32);
+            match self.target {
+                MinimizationTarget::Centroid => {
+                    let mut centroids = images[0].clone();
+                    for image in &images[1..] {
+                        for (centroid, position) in centroids.iter_mut().zip(image) {
+                            *centroid += position.clone();
+                        }
+                    }
+                    for centroid in &mut centroids {
+                        *centroid = centroid.clone() / image_count;
+                    }
+                    let displacements: Vec<Vec<V>> = images
+                        .iter()
+                        .map(|image| {
+                            image
+                                .iter()
+                                .zip(&centroids)
+                                .map(|(position, centroid)| position.clone() - centroid.clone())
+                                .collect()
+                        })
+                        .collect();
+                    let mut replica = vec![V::zero(); atoms];
+                    let mut replica_forces = vec![V::zero(); atoms];
+                    let report = self.minimize(&mut centroids, |centroids, forces| {
+                        forces.fill_with(V::zero);
+                        let mut energy = T::zero();
+                        for (image, displacements) in displacements.iter().enumerate() {
+                            for ((position, centroid), displacement) in
+                                replica.iter_mut().zip(centroids).zip(displacements)
+                            {
+                                *position = centroid.clone() + displacement.clone();
+                            }
+                            energy = energy + calculate(image, &replica, &mut replica_forces)?;
+                            for (force, replica_force) in forces.iter_mut().zip(&replica_forces) {
+                                *force += replica_force.clone();
+                            }
+                        }
+                        for force in forces.iter_mut() {
+                            *force = force.clone() / image_count;
+                        }
+                        Ok(energy / image_count)
+                    })?;
+                    for (image, displacements) in images.iter_mut().zip(&displacements) {
+                        for ((position, centroid), displacement) in
+                            image.iter_mut().zip(&centroids).zip(displacements)
+                        {
+                            *position = centroid.clone() + displacement.clone();
+                        }
+                    }
+                    Ok(report)
+                }
+                MinimizationTarget::AllBeads => {
+                    let mut beads: Vec<V> = images.iter().flatten().cloned().collect();
+                    let report = self.minimize(&mut beads, |beads, forces| {
+                        let mut energy = T::zero();
+                        for (image, (positions, forces)) in beads
+                            .chunks_exact(atoms)
+                            .zip(forces.chunks_exact_mut(atoms))
+                            .enumerate()
+                        {
+                            energy = energy + calculate(image, positions, forces)?;
+                        }
+                        Ok(energy / image_count)
+                    })?;
+                    for (image, positions) in images.iter_mut().zip(beads.chunks_exact(atoms)) {
+                        image.clone_from_slice(positions);
+                    }
+                    Ok(report)
+                }
+            }
+        }
+    }
+
+    fn max_force<const N: usize, T: Float, V>(forces: &[V]) -> T
+    where
+        V: Vector<N, Element = T> + Clone,
+    {
+        forces
+            .iter()
+            .map(|force| force.clone().magnitude_squared())
+            .fold(T::zero(), T::max)
+            .sqrt()
+    }
+}
+
+pub use stage::{Minimization, MinimizationMethod, MinimizationReport, MinimizationTarget};
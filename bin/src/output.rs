@@ -0,0 +1,566 @@
+mod xyz {
+    use std::{
+        fmt::Display,
+        io::{self, Write},
+    };
+
+    use lib::{
+        core::Vector,
+        output::{Provenance, TrajectoryFrame, TrajectoryLayout, TrajectoryOutput},
+    };
+
+    /// A writer of extended '.xyz' trajectories.
+    ///
+    /// Every frame starts with the number of entries and a comment line holding
+    /// the step and the layout. Frames with replicas carry a bead column
+    /// with the image of every entry. The provenance of the run, if written,
+    /// is appended to the comment line of the first frame as `key=value` pairs.
+    /// The dimensions of the box, if set, are written as the diagonal of the `Lattice`,
+    /// so that changes of the box by a barostat are reflected frame by frame.
+    pub struct XyzWriter<const N: usize, W> {
+        writer: W,
+        symbols: Vec<String>,
+        provenance: Option<String>,
+        lattice: Option<String>,
+    }
+
+    impl<const N: usize, W: Write> XyzWriter<N, W> {
+        /// Constructs a writer labelling the atom with index `i` by `symbols[i]`.
+        pub fn new(writer: W, symbols: Vec<String>) -> Self {
+            Self {
+                writer,
+                symbols,
+                provenance: None,
+                lattice: None,
+            }
+        }
+
+        /// Sets the lengths of the box written with the following frames.
+        pub fn set_box_lengths<T: Display>(&mut self, lengths: &[T; N]) {
+            let mut lattice = Vec::with_capacity(N * N);
+            for (row, length) in lengths.iter().enumerate() {
+                lattice.extend((0..N).map(|column| {
+                    if column == row {
+                        length.to_string()
+                    } else {
+                        "0".to_owned()
+                    }
+                }));
+            }
+            self.lattice = Some(lattice.join(" "));
+        }
+
+        pub fn get_ref(&self) -> &W {
+            &self.writer
+        }
+
+        pub fn get_mut(&mut self) -> &mut W {
+            &mut self.writer
+        }
+
+        pub fn into_inner(self) -> W {
+            self.writer
+        }
+    }
+
+    impl<const N: usize, T, V, W> TrajectoryOutput<V> for XyzWriter<N, W>
+    where
+        T: Display,
+        V: Vector<N, Element = T>,
+        W: Write,
+    {
+        type Error = io::Error;
+
+        fn write_provenance(&mut self, provenance: &Provenance) -> io::Result<()> {
+            self.provenance = Some(provenance.to_string());
+            Ok(())
+        }
+
+        fn write_frame(&mut self, step: usize, frame: &TrajectoryFrame<V>) -> io::Result<()> {
+            let (layout, with_beads) = match frame.layout() {
+                TrajectoryLayout::Centroid => ("centroid".to_owned(), false),
+                TrajectoryLayout::Replica(image) => (format!("replica:{}", image), true),
+                TrajectoryLayout::AllBeads => ("all_beads".to_owned(), true),
+            };
+            writeln!(self.writer, "{}", frame.vectors().len())?;
+            write!(self.writer, "step={} layout={} ", step, layout)?;
+            if let Some(lattice) = &self.lattice {
+                write!(self.writer, "Lattice=\"{}\" ", lattice)?;
+            }
+            write!(self.writer, "Properties=species:S:1:pos:R:{}", N)?;
+            if with_beads {
+                write!(self.writer, ":bead:I:1")?;
+            }
+            if let Some(provenance) = self.provenance.take() {
+                write!(self.writer, " {}", provenance)?;
+            }
+            writeln!(self.writer)?;
+            for (label, vector) in frame.iter() {
+                let symbol = self.symbols.get(label.atom).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("no symbol for atom #{}", label.atom),
+                    )
+                })?;
+                write!(self.writer, "{}", symbol)?;
+                for element in vector.as_array() {
+                    write!(self.writer, " {}", element)?;
+                }
+                if let (true, Some(image)) = (with_beads, label.image) {
+                    write!(self.writer, " {}", image)?;
+                }
+                writeln!(self.writer)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use xyz::XyzWriter;
+
+mod provenance {
+    use lib::output::Provenance;
+
+    /// Collects the metadata of a run of this binary configured by `config`.
+    ///
+    /// The commit is taken from the `GIT_HASH` environment variable at build time, if set,
+    /// e.g. with `GIT_HASH=$(git rev-parse HEAD) cargo build`.
+    pub fn run_provenance(config: &[u8], threads: usize, replicas: usize) -> Provenance {
+        let provenance = Provenance::new(env!("CARGO_PKG_VERSION"))
+            .with_config(config)
+            .with_parallelism(threads, replicas);
+        match option_env!("GIT_HASH") {
+            Some(git_hash) => provenance.with_git_hash(git_hash.trim()),
+            None => provenance,
+        }
+    }
+}
+
+pub use provenance::run_provenance;
+
+mod shard {
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        fs::File,
+        io::{self, BufRead, BufReader, BufWriter, Lines, Write},
+        path::{Path, PathBuf},
+    };
+
+    use lib::output::ResumeState;
+
+    use super::{CountingWriter, open_resumed};
+
+    /// How a simulation with several replicas writes a trajectory or a table of observables.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum OutputMode {
+        /// A single file written by one thread.
+        #[default]
+        Single,
+        /// One shard per replica, written by the thread of the replica,
+        /// and a manifest listing the shards, which [`merge_xyz_shards`]
+        /// and [`merge_table_shards`] read back into a single file.
+        Sharded,
+    }
+
+    impl OutputMode {
+        /// Creates the files of an output named `stem` with `extension` in `directory`
+        /// and returns a writer per file: a single one, or one per replica in the order of the replicas.
+        pub fn create(
+            self,
+            directory: &Path,
+            stem: &str,
+            extension: &str,
+            replicas: usize,
+        ) -> io::Result<Vec<BufWriter<File>>> {
+            match self {
+                Self::Single => {
+                    let file = File::create(directory.join(format!("{}.{}", stem, extension)))?;
+                    Ok(vec![BufWriter::new(file)])
+                }
+                Self::Sharded => {
+                    let manifest = ShardManifest::new(
+                        (0..replicas)
+                            .map(|replica| format!("{}.{}.{}", stem, replica, extension).into())
+                            .collect(),
+                    );
+                    let writers = manifest
+                        .shards()
+                        .iter()
+                        .map(|shard| File::create(directory.join(shard)).map(BufWriter::new))
+                        .collect::<io::Result<_>>()?;
+                    manifest.write(&mut File::create(
+                        directory.join(format!("{}.manifest", stem)),
+                    )?)?;
+                    Ok(writers)
+                }
+            }
+        }
+
+        /// Returns the names of the files of an output named `stem` with `extension`,
+        /// in the order of the writers returned by [`OutputMode::create`].
+        pub fn file_names(self, stem: &str, extension: &str, replicas: usize) -> Vec<String> {
+            match self {
+                Self::Single => vec![format!("{}.{}", stem, extension)],
+                Self::Sharded => (0..replicas)
+                    .map(|replica| format!("{}.{}.{}", stem, replica, extension))
+                    .collect(),
+            }
+        }
+
+        /// Opens the files of an output as [`OutputMode::create`] does, continuing every file
+        /// from the cursor recorded under its name in `state`, if any.
+        pub fn resume(
+            self,
+            directory: &Path,
+            stem: &str,
+            extension: &str,
+            replicas: usize,
+            state: &ResumeState,
+        ) -> io::Result<Vec<CountingWriter<BufWriter<File>>>> {
+            let names = self.file_names(stem, extension, replicas);
+            if self == Self::Sharded {
+                let manifest = ShardManifest::new(names.iter().map(PathBuf::from).collect());
+                manifest.write(&mut File::create(
+                    directory.join(format!("{}.manifest", stem)),
+                )?)?;
+            }
+            names
+                .iter()
+                .map(|name| open_resumed(&directory.join(name), state.get(name)))
+                .collect()
+        }
+    }
+
+    /// The list of the shards of an output, one per replica,
+    /// with paths relative to the directory of the manifest.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct ShardManifest {
+        shards: Vec<PathBuf>,
+    }
+
+    impl ShardManifest {
+        pub fn new(shards: Vec<PathBuf>) -> Self {
+            Self { shards }
+        }
+
+        /// Returns the paths of the shards in the order of the replicas.
+        pub fn shards(&self) -> &[PathBuf] {
+            &self.shards
+        }
+
+        /// Writes the manifest as a line per shard holding the replica and the path.
+        pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+            writeln!(writer, "# replica\tshard")?;
+            for (replica, shard) in self.shards.iter().enumerate() {
+                writeln!(writer, "{}\t{}", replica, shard.display())?;
+            }
+            Ok(())
+        }
+
+        /// Reads a manifest written by [`ShardManifest::write`].
+        pub fn read<R: BufRead>(reader: R) -> Result<Self, MergeError> {
+            let mut shards = Vec::new();
+            for (number, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let (_, shard) = line
+                    .split_once('\t')
+                    .filter(|(replica, _)| replica.parse() == Ok(shards.len()))
+                    .ok_or(MergeError::Manifest { line: number + 1 })?;
+                shards.push(PathBuf::from(shard));
+            }
+            Ok(Self { shards })
+        }
+
+        /// Opens the shards listed in the manifest at `path`.
+        fn open_shards(path: &Path) -> Result<Vec<Lines<BufReader<File>>>, MergeError> {
+            let manifest = Self::read(BufReader::new(File::open(path)?))?;
+            let directory = path.parent().unwrap_or(Path::new(""));
+            Ok(manifest
+                .shards
+                .iter()
+                .map(|shard| Ok(BufReader::new(File::open(directory.join(shard))?).lines()))
+                .collect::<io::Result<_>>()?)
+        }
+    }
+
+    /// Merges the '.xyz' shards listed in the manifest at `manifest` into global frames
+    /// holding the replicas of every atom in all images, as written with
+    /// [`TrajectoryLayout::AllBeads`](lib::output::TrajectoryLayout::AllBeads).
+    ///
+    /// Every shard must hold the frames of a single replica for the same steps.
+    pub fn merge_xyz_shards<W: Write>(manifest: &Path, writer: &mut W) -> Result<(), MergeError> {
+        let mut shards = ShardManifest::open_shards(manifest)?;
+        let mut lines = vec![0; shards.len()];
+        let mut entries = Vec::new();
+        loop {
+            let mut comment = None;
+            entries.clear();
+            for (replica, shard) in shards.iter_mut().enumerate() {
+                let line = &mut lines[replica];
+                let Some(count) = shard.next().transpose()? else {
+                    if replica == 0 {
+                        return Ok(());
+                    }
+                    return Err(MergeError::Misaligned {
+                        replica,
+                        line: *line + 1,
+                    });
+                };
+                *line += 1;
+                let count: usize = count.trim().parse().map_err(|_| MergeError::Malformed {
+                    replica,
+                    line: *line,
+                })?;
+                let header = shard.next().transpose()?.ok_or(MergeError::Malformed {
+                    replica,
+                    line: *line + 1,
+                })?;
+                *line += 1;
+                let step = header.split_whitespace().next().unwrap_or_default();
+                match &comment {
+                    None => comment = Some(header.clone()),
+                    Some(first) if first.split_whitespace().next() == Some(step) => {}
+                    Some(_) => {
+                        return Err(MergeError::Misaligned {
+                            replica,
+                            line: *line,
+                        });
+                    }
+                }
+                for _ in 0..count {
+                    entries.push(shard.next().transpose()?.ok_or(MergeError::Malformed {
+                        replica,
+                        line: *line + 1,
+                    })?);
+                    *line += 1;
+                }
+            }
+            let comment = comment.unwrap_or_default();
+            writeln!(writer, "{}", entries.len())?;
+            let fields: Vec<&str> = comment
+                .split_whitespace()
+                .map(|field| {
+                    if field.starts_with("layout=") {
+                        "layout=all_beads"
+                    } else {
+                        field
+                    }
+                })
+                .collect();
+            writeln!(writer, "{}", fields.join(" "))?;
+            for entry in &entries {
+                writeln!(writer, "{}", entry)?;
+            }
+        }
+    }
+
+    /// Merges the tables of observables listed in the manifest at `manifest`
+    /// into a single table whose rows hold the step followed by the values of every replica
+    /// in the order of the replicas.
+    ///
+    /// Lines starting with '#' are copied from the first shard only.
+    /// Every shard must hold a row for the same steps.
+    pub fn merge_table_shards<W: Write>(manifest: &Path, writer: &mut W) -> Result<(), MergeError> {
+        let mut shards = ShardManifest::open_shards(manifest)?;
+        let mut lines = vec![0; shards.len()];
+        let mut row = String::new();
+        loop {
+            row.clear();
+            let mut step = None;
+            for (replica, shard) in shards.iter_mut().enumerate() {
+                let line = &mut lines[replica];
+                let next = loop {
+                    let Some(next) = shard.next().transpose()? else {
+                        break None;
+                    };
+                    *line += 1;
+                    if next.starts_with('#') {
+                        if replica == 0 {
+                            writeln!(writer, "{}", next)?;
+                        }
+                    } else if !next.trim().is_empty() {
+                        break Some(next);
+                    }
+                };
+                let Some(next) = next else {
+                    if replica == 0 {
+                        return Ok(());
+                    }
+                    return Err(MergeError::Misaligned {
+                        replica,
+                        line: *line + 1,
+                    });
+                };
+                let mut fields = next.split_whitespace();
+                let this_step = fields.next().unwrap_or_default().to_owned();
+                match &step {
+                    None => {
+                        row.push_str(&this_step);
+                        step = Some(this_step);
+                    }
+                    Some(first) if *first == this_step => {}
+                    Some(_) => {
+                        return Err(MergeError::Misaligned {
+                            replica,
+                            line: *line,
+                        });
+                    }
+                }
+                for field in fields {
+                    row.push(' ');
+                    row.push_str(field);
+                }
+            }
+            writeln!(writer, "{}", row)?;
+        }
+    }
+
+    /// An error raised while merging shards.
+    #[derive(Debug)]
+    pub enum MergeError {
+        /// Reading a file or writing the merged output failed.
+        Io(io::Error),
+        /// The line at the given number of the manifest could not be parsed.
+        Manifest {
+            /// The number of the line, starting at 1.
+            line: usize,
+        },
+        /// The line at the given number of a shard could not be parsed.
+        Malformed {
+            /// The replica of the shard.
+            replica: usize,
+            /// The number of the line, starting at 1.
+            line: usize,
+        },
+        /// The frame or row at the given line of a shard belongs to a different step
+        /// than that of the first shard, or the shard ends earlier.
+        Misaligned {
+            /// The replica of the shard.
+            replica: usize,
+            /// The number of the line, starting at 1.
+            line: usize,
+        },
+    }
+
+    impl From<io::Error> for MergeError {
+        fn from(value: io::Error) -> Self {
+            Self::Io(value)
+        }
+    }
+
+    impl Display for MergeError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Io(err) => write!(f, "failed to merge the shards: {}", err),
+                Self::Manifest { line } => write!(f, "malformed manifest entry at line {}", line),
+                Self::Malformed { replica, line } => write!(
+                    f,
+                    "malformed line {} in the shard of replica #{}",
+                    line, replica
+                ),
+                Self::Misaligned { replica, line } => write!(
+                    f,
+                    "the shard of replica #{} is out of step with the first shard at line {}",
+                    replica, line
+                ),
+            }
+        }
+    }
+
+    impl Error for MergeError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::Io(err) => Some(err),
+                Self::Manifest { .. } | Self::Malformed { .. } | Self::Misaligned { .. } => None,
+            }
+        }
+    }
+}
+
+mod resume {
+    use std::{
+        fs::{File, OpenOptions},
+        io::{self, BufWriter, Seek, SeekFrom, Write},
+        path::Path,
+    };
+
+    use lib::output::OutputCursor;
+
+    /// A writer which counts the bytes written through it,
+    /// which is the length of the underlying file once it has been flushed.
+    pub struct CountingWriter<W> {
+        writer: W,
+        offset: u64,
+    }
+
+    impl<W: Write> CountingWriter<W> {
+        /// Wraps `writer`, which is already `offset` bytes into its file.
+        pub fn new(writer: W, offset: u64) -> Self {
+            Self { writer, offset }
+        }
+
+        /// Returns the number of bytes in the file, including those still buffered.
+        pub fn offset(&self) -> u64 {
+            self.offset
+        }
+
+        pub fn get_ref(&self) -> &W {
+            &self.writer
+        }
+
+        pub fn into_inner(self) -> W {
+            self.writer
+        }
+    }
+
+    impl<W: Write> Write for CountingWriter<W> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let written = self.writer.write(buf)?;
+            self.offset += written as u64;
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.writer.flush()
+        }
+    }
+
+    /// Opens the file of a stream at `path` to continue from `cursor`,
+    /// or creates it anew without a cursor.
+    ///
+    /// Whatever was written after the cursor, by a run which went on past its last checkpoint,
+    /// is cut off, so that the file holds every step exactly once.
+    /// Fails if the file is shorter than the offset of the cursor.
+    pub fn open_resumed(
+        path: &Path,
+        cursor: Option<OutputCursor>,
+    ) -> io::Result<CountingWriter<BufWriter<File>>> {
+        let Some(cursor) = cursor else {
+            return Ok(CountingWriter::new(BufWriter::new(File::create(path)?), 0));
+        };
+        let mut file = OpenOptions::new().write(true).open(path)?;
+        let length = file.metadata()?.len();
+        if length < cursor.offset {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} holds {} bytes, fewer than the {} at the checkpoint",
+                    path.display(),
+                    length,
+                    cursor.offset
+                ),
+            ));
+        }
+        file.set_len(cursor.offset)?;
+        file.seek(SeekFrom::End(0))?;
+        Ok(CountingWriter::new(BufWriter::new(file), cursor.offset))
+    }
+}
+
+pub use resume::{CountingWriter, open_resumed};
+
+pub use shard::{MergeError, OutputMode, ShardManifest, merge_table_shards, merge_xyz_shards};
@@ -0,0 +1,341 @@
+mod domain {
+    use lib::core::{
+        Vector,
+        error::InvalidIndexError,
+        sync_ops::{SyncAddReciever, SyncAddSender},
+        warnings::WarningSink,
+    };
+    use num::Float;
+
+    use crate::steering::ParseError;
+
+    /// The atoms a worker owns and the copies of nearby atoms it needs.
+    ///
+    /// Every pair of atoms within the halo width of each other is evaluated by exactly
+    /// one domain, the one owning the atom of the lower global index, as decided by
+    /// [`Domain::evaluates_pair`]. The forces a domain finds on its halo atoms are sent
+    /// back along with those on its owned atoms, so their sum over the domains
+    /// counts every pair once.
+    #[derive(Clone, Debug, Default)]
+    pub struct Domain {
+        /// Global indices of the atoms inside the domain.
+        owned: Vec<usize>,
+        /// Global indices of the atoms outside the domain within the halo width.
+        halo: Vec<usize>,
+        /// The local index of every atom of the system, if it is local,
+        /// kept between assignments so sending the forces does not allocate.
+        local_indices: Vec<Option<usize>>,
+    }
+
+    impl Domain {
+        pub fn owned(&self) -> &[usize] {
+            &self.owned
+        }
+
+        pub fn halo(&self) -> &[usize] {
+            &self.halo
+        }
+
+        /// Returns the global indices of the local atoms:
+        /// the owned atoms followed by the halo.
+        pub fn local_atoms(&self) -> impl Iterator<Item = usize> + '_ {
+            self.owned.iter().chain(&self.halo).copied()
+        }
+
+        /// Returns whether the domain evaluates the interaction between the local atoms
+        /// `first` and `second`.
+        ///
+        /// A pair of owned atoms is evaluated by the domain and a pair of halo atoms by
+        /// another one. A pair of an owned and a halo atom is evaluated by the domain
+        /// owning the one of the lower global index, provided the halo width is
+        /// at least the cutoff of the interaction.
+        pub fn evaluates_pair(&self, first: usize, second: usize) -> bool {
+            let owned = self.owned.len();
+            match (first < owned, second < owned) {
+                (true, true) => true,
+                (false, false) => false,
+                (true, false) => self.global_index(first) < self.global_index(second),
+                (false, true) => self.global_index(second) < self.global_index(first),
+            }
+        }
+
+        /// Returns the global index of the local atom `local`.
+        fn global_index(&self, local: usize) -> Option<usize> {
+            match local.checked_sub(self.owned.len()) {
+                None => Some(self.owned[local]),
+                Some(halo) => self.halo.get(halo).copied(),
+            }
+        }
+
+        fn index_local_atoms(&mut self, atoms: usize) {
+            self.local_indices.clear();
+            self.local_indices.resize(atoms, None);
+            for (local, atom) in self.owned.iter().chain(&self.halo).enumerate() {
+                self.local_indices[*atom] = Some(local);
+            }
+        }
+
+        /// Copies the positions of the local atoms into `local_positions`.
+        pub fn refresh_halo<V: Clone>(
+            &self,
+            positions: &[V],
+            local_positions: &mut Vec<V>,
+        ) -> Result<(), InvalidIndexError> {
+            local_positions.clear();
+            for atom in self.local_atoms() {
+                local_positions.push(
+                    positions
+                        .get(atom)
+                        .ok_or(InvalidIndexError::new(atom, positions.len()))?
+                        .clone(),
+                );
+            }
+            Ok(())
+        }
+
+        /// Sends the forces on the local atoms to be summed over all domains.
+        ///
+        /// Every domain sends a message for each of the `atoms` atoms of the system,
+        /// an empty one if the atom is not local, so that the reciever
+        /// obtains the total forces with [`receive_forces`]. The forces on the halo
+        /// atoms are sent too, so the pairs must have been split by [`Domain::evaluates_pair`].
+        pub fn send_forces<V, S>(
+            &self,
+            atoms: usize,
+            local_forces: &[V],
+            sender: &mut S,
+        ) -> Result<(), S::Error>
+        where
+            V: Clone,
+            S: SyncAddSender<V> + ?Sized,
+        {
+            for atom in 0..atoms {
+                let local = self.local_indices.get(atom).copied().flatten();
+                match local.and_then(|local| local_forces.get(local)) {
+                    Some(force) => sender.send(force.clone())?,
+                    None => sender.send_empty()?,
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Receives the forces sent by every domain with [`Domain::send_forces`]
+    /// and stores their sums in `forces`.
+    ///
+    /// Atoms that are not local to any domain are left untouched.
+    pub fn receive_forces<V, R>(forces: &mut [V], reciever: &mut R) -> Result<(), R::Error>
+    where
+        R: SyncAddReciever<V> + ?Sized,
+    {
+        let sums = reciever.receive_sums(forces.len())?;
+        for (force, sum) in forces.iter_mut().zip(sums) {
+            if let Some(sum) = sum {
+                *force = sum;
+            }
+        }
+        Ok(())
+    }
+
+    /// A spatial decomposition of the bounding box of a system into
+    /// a grid of domains, one per worker.
+    pub struct DomainDecomposition<const N: usize, T> {
+        divisions: [usize; N],
+        halo_width: T,
+        domains: Vec<Domain>,
+    }
+
+    impl<const N: usize, T> DomainDecomposition<N, T>
+    where
+        T: Float + From<f32>,
+    {
+        pub fn new(divisions: [usize; N], halo_width: T) -> Self {
+            assert!(
+                divisions.iter().all(|&division| division > 0),
+                "every dimension must be divided into at least one domain"
+            );
+            assert!(
+                halo_width >= T::zero(),
+                "the halo width must be non-negative"
+            );
+            let domains = divisions.iter().product();
+            WarningSink::global().check_threads(domains);
+            Self {
+                divisions,
+                halo_width,
+                domains: vec![Domain::default(); domains],
+            }
+        }
+
+        pub fn domains(&self) -> &[Domain] {
+            &self.domains
+        }
+
+        /// Reassigns the atoms to the domains according to `positions`.
+        pub fn assign<V>(&mut self, positions: &[V])
+        where
+            V: Vector<N, Element = T>,
+        {
+            for domain in &mut self.domains {
+                domain.owned.clear();
+                domain.halo.clear();
+            }
+            let Some(first) = positions.first() else {
+                return;
+            };
+            let (mut lower, mut upper) = (*first.as_array(), *first.as_array());
+            for position in positions {
+                for ((lower, upper), &coordinate) in
+                    lower.iter_mut().zip(&mut upper).zip(position.as_array())
+                {
+                    *lower = lower.min(coordinate);
+                    *upper = upper.max(coordinate);
+                }
+            }
+            let mut widths = [T::zero(); N];
+            for (dimension, width) in widths.iter_mut().enumerate() {
+                let division = <T as From<f32>>::from(self.divisions[dimension] as f32);
+                *width = ((upper[dimension] - lower[dimension]) / division).max(T::epsilon());
+            }
+            let cell = |dimension: usize, coordinate: T| {
+                ((coordinate - lower[dimension]) / widths[dimension])
+                    .floor()
+                    .to_usize()
+                    .unwrap_or(0)
+                    .min(self.divisions[dimension] - 1)
+            };
+
+            for (atom, position) in positions.iter().enumerate() {
+                let coordinates = position.as_array();
+                let mut owner = 0;
+                for dimension in (0..N).rev() {
+                    owner =
+                        owner * self.divisions[dimension] + cell(dimension, coordinates[dimension]);
+                }
+                self.domains[owner].owned.push(atom);
+
+                // Every domain whose box, extended by the halo width, contains the atom.
+                let mut ranges = [(0, 0); N];
+                for (dimension, range) in ranges.iter_mut().enumerate() {
+                    *range = (
+                        cell(dimension, coordinates[dimension] - self.halo_width),
+                        cell(dimension, coordinates[dimension] + self.halo_width),
+                    );
+                }
+                let mut current = ranges.map(|(start, _)| start);
+                loop {
+                    let mut domain = 0;
+                    for dimension in (0..N).rev() {
+                        domain = domain * self.divisions[dimension] + current[dimension];
+                    }
+                    if domain != owner {
+                        self.domains[domain].halo.push(atom);
+                    }
+                    let Some(dimension) =
+                        (0..N).find(|&dimension| current[dimension] < ranges[dimension].1)
+                    else {
+                        break;
+                    };
+                    current[dimension] += 1;
+                    for (lower_dimension, coordinate) in
+                        current.iter_mut().enumerate().take(dimension)
+                    {
+                        *coordinate = ranges[lower_dimension].0;
+                    }
+                }
+            }
+            for domain in &mut self.domains {
+                domain.index_local_atoms(positions.len());
+            }
+        }
+    }
+
+    /// The way work within an image is split between workers.
+    pub enum Parallelism<const N: usize, T> {
+        /// Every worker handles its own groups of atoms.
+        Groups,
+        /// Every worker handles a spatial domain.
+        Domains(DomainDecomposition<N, T>),
+    }
+
+    impl<const N: usize, T> Parallelism<N, T>
+    where
+        T: Float + From<f32>,
+    {
+        /// Parses `groups` or `domains` followed by the number of domains along every
+        /// dimension separated by `x`, as in `domains 2x2x1`, as the value of a switch
+        /// in a configuration file.
+        ///
+        /// The halo of the domains is `halo_width` wide, which should be
+        /// the longest cutoff of the potentials.
+        pub fn parse(value: &str, halo_width: T) -> Result<Self, ParseError> {
+            let error = |message: String| ParseError {
+                line: None,
+                message,
+            };
+            let mut words = value.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("groups"), None, None) => Ok(Self::Groups),
+                (Some("domains"), Some(divisions), None) => {
+                    let divisions: Vec<usize> = divisions
+                        .split('x')
+                        .map(|division| match division.parse() {
+                            Ok(division) if division > 0 => Ok(division),
+                            _ => Err(error(format!(
+                                "`{}` is not a positive number of domains",
+                                division
+                            ))),
+                        })
+                        .collect::<Result<_, _>>()?;
+                    let divisions = <[usize; N]>::try_from(divisions).map_err(|divisions| {
+                        error(format!(
+                            "expected the number of domains along {} dimensions, found {}",
+                            N,
+                            divisions.len()
+                        ))
+                    })?;
+                    if halo_width.is_nan() || halo_width < T::zero() {
+                        return Err(error("the halo width must be non-negative".to_string()));
+                    }
+                    Ok(Self::Domains(DomainDecomposition::new(
+                        divisions, halo_width,
+                    )))
+                }
+                _ => Err(error(format!(
+                    "unknown parallelism `{}`, expected `groups` or `domains` with their numbers",
+                    value.trim()
+                ))),
+            }
+        }
+
+        /// Returns the number of domains, or `None` if the workers handle groups.
+        pub fn domain_count(&self) -> Option<usize> {
+            match self {
+                Self::Groups => None,
+                Self::Domains(decomposition) => Some(decomposition.domains().len()),
+            }
+        }
+
+        /// Returns the domain of `worker`, or `None` if the workers handle groups
+        /// or there is no such domain.
+        pub fn domain(&self, worker: usize) -> Option<&Domain> {
+            match self {
+                Self::Groups => None,
+                Self::Domains(decomposition) => decomposition.domains().get(worker),
+            }
+        }
+
+        /// Reassigns the atoms to the domains according to `positions`,
+        /// which does nothing if the workers handle groups.
+        pub fn assign<V>(&mut self, positions: &[V])
+        where
+            V: Vector<N, Element = T>,
+        {
+            if let Self::Domains(decomposition) = self {
+                decomposition.assign(positions);
+            }
+        }
+    }
+}
+
+pub use domain::{Domain, DomainDecomposition, Parallelism, receive_forces};
@@ -1,2 +1,12 @@
+pub mod builder;
+pub mod eam;
+pub mod electrostatics;
 pub mod exchange;
+pub mod forcefield;
+pub mod ground_state;
+pub mod pair;
 pub mod physical;
+pub mod polarization;
+pub mod restraint;
+pub mod three_body;
+pub mod water;
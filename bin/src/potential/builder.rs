@@ -0,0 +1,118 @@
+mod error {
+    use std::{
+        error::Error,
+        fmt::{Debug, Display, Formatter, Result as FmtResult},
+    };
+
+    /// An invalid or missing parameter passed to the builder of a potential.
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum ParameterError<T> {
+        /// A required parameter has not been set.
+        Missing(&'static str),
+        /// A parameter which must be positive is zero, negative or NaN.
+        NotPositive { parameter: &'static str, value: T },
+        /// A parameter which must be non-negative is negative or NaN.
+        Negative { parameter: &'static str, value: T },
+        /// The cutoff exceeds half of a periodic length of the box,
+        /// so an atom would interact with more than one image of another.
+        CutoffExceedsBox { cutoff: T, axis: usize, length: T },
+        /// An atom is assigned an element which is not in the tables of the potential.
+        UnknownElement {
+            atom: usize,
+            element: usize,
+            elements: usize,
+        },
+    }
+
+    impl<T: Display> Display for ParameterError<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Missing(parameter) => write!(f, "the {} has not been set", parameter),
+                Self::NotPositive { parameter, value } => {
+                    write!(f, "the {} must be positive, found {}", parameter, value)
+                }
+                Self::Negative { parameter, value } => {
+                    write!(f, "the {} must be non-negative, found {}", parameter, value)
+                }
+                Self::CutoffExceedsBox {
+                    cutoff,
+                    axis,
+                    length,
+                } => write!(
+                    f,
+                    "the cutoff {} exceeds half of the periodic length {} of the box along axis {}",
+                    cutoff, length, axis
+                ),
+                Self::UnknownElement {
+                    atom,
+                    element,
+                    elements,
+                } => write!(
+                    f,
+                    "atom {} is of element {}, but the tables hold {} elements",
+                    atom, element, elements
+                ),
+            }
+        }
+    }
+
+    impl<T: Debug + Display> Error for ParameterError<T> {}
+}
+
+pub use error::ParameterError;
+
+mod checks {
+    use num::Float;
+
+    use super::ParameterError;
+    use crate::potential::electrostatics::PeriodicBox;
+
+    pub fn required<T, U>(
+        value: Option<U>,
+        parameter: &'static str,
+    ) -> Result<U, ParameterError<T>> {
+        value.ok_or(ParameterError::Missing(parameter))
+    }
+
+    pub fn positive<T: Float>(value: T, parameter: &'static str) -> Result<T, ParameterError<T>> {
+        if value > T::zero() {
+            Ok(value)
+        } else {
+            Err(ParameterError::NotPositive { parameter, value })
+        }
+    }
+
+    pub fn non_negative<T: Float>(
+        value: T,
+        parameter: &'static str,
+    ) -> Result<T, ParameterError<T>> {
+        if value >= T::zero() {
+            Ok(value)
+        } else {
+            Err(ParameterError::Negative { parameter, value })
+        }
+    }
+
+    /// Checks that `cutoff` does not exceed half of any periodic length of `periodic_box`.
+    pub fn fits_box<const N: usize, T: Float + From<f32>>(
+        cutoff: T,
+        periodic_box: &PeriodicBox<T, N>,
+    ) -> Result<(), ParameterError<T>> {
+        let half = <T as From<f32>>::from(0.5);
+        match periodic_box
+            .lengths()
+            .into_iter()
+            .enumerate()
+            .find(|&(axis, length)| periodic_box.is_periodic(axis) && cutoff > half * length)
+        {
+            Some((axis, length)) => Err(ParameterError::CutoffExceedsBox {
+                cutoff,
+                axis,
+                length,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+pub use checks::{fits_box, non_negative, positive, required};
@@ -0,0 +1,541 @@
+pub use lib::numeric::interp::UniformTable;
+
+mod setfl {
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        fs, io,
+        path::Path,
+    };
+
+    use lib::numeric::Real;
+    use num::{Float, NumCast};
+
+    use super::UniformTable;
+    use crate::steering::ParseError;
+
+    /// The tables of an element of an EAM potential.
+    #[derive(Clone, Debug)]
+    pub struct EamElement<T> {
+        pub name: String,
+        pub atomic_number: u32,
+        pub mass: T,
+        pub lattice_constant: T,
+        pub lattice: String,
+        /// The embedding energy as a function of the electron density.
+        pub embedding: UniformTable<T>,
+        /// The electron density contributed by an atom of this element as a function of distance.
+        pub density: UniformTable<T>,
+    }
+
+    /// The tables of an EAM potential for a set of elements, as stored in a `setfl` file.
+    #[derive(Clone, Debug)]
+    pub struct EamTables<T> {
+        elements: Vec<EamElement<T>>,
+        /// The pair potentials times the distance, for every pair of elements `i >= j`
+        /// in the order `(0, 0), (1, 0), (1, 1), (2, 0), ...`.
+        scaled_pairs: Vec<UniformTable<T>>,
+        cutoff: T,
+    }
+
+    impl<T: Float + From<f32> + Real> EamTables<T> {
+        pub fn elements(&self) -> &[EamElement<T>] {
+            &self.elements
+        }
+
+        /// Returns the index of the element named `name`.
+        pub fn element(&self, name: &str) -> Option<usize> {
+            self.elements
+                .iter()
+                .position(|element| element.name == name)
+        }
+
+        pub fn cutoff(&self) -> T {
+            self.cutoff
+        }
+
+        /// Returns the pair potential between atoms of `first` and `second` at `distance`
+        /// and its derivative.
+        pub fn pair(&self, first: usize, second: usize, distance: T) -> (T, T) {
+            let (high, low) = (first.max(second), first.min(second));
+            let (scaled, scaled_derivative) =
+                self.scaled_pairs[high * (high + 1) / 2 + low].evaluate(distance);
+            let potential = scaled / distance;
+            (potential, (scaled_derivative - potential) / distance)
+        }
+    }
+
+    /// Parses the tables of an EAM potential in the `setfl` format.
+    ///
+    /// The first three lines are comments, the fourth lists the elements and the fifth holds
+    /// the number of points and spacing of the density and distance grids and the cutoff.
+    /// Every element then has a line of its atomic number, mass, lattice constant and lattice,
+    /// followed by its embedding function and density, and the file ends with the pair potentials
+    /// times the distance for every pair of elements. The values may be split across lines freely.
+    pub fn parse_setfl<T: Float + From<f32> + Real>(
+        text: &str,
+    ) -> Result<EamTables<T>, ParseError> {
+        let mut lines = text.lines().enumerate().skip(3);
+        let mut header = |what: &str| {
+            lines
+                .next()
+                .map(|(index, line)| (index + 1, line))
+                .ok_or_else(|| ParseError {
+                    line: None,
+                    message: format!("the file ends before the {}", what),
+                })
+        };
+        let (line, elements_line) = header("list of elements")?;
+        let mut fields = elements_line.split_whitespace();
+        let count: usize = parse_field(fields.next(), line, "the number of elements")?;
+        let names: Vec<&str> = fields.collect();
+        if count == 0 || names.len() != count {
+            return Err(ParseError {
+                line: Some(line),
+                message: format!("expected {} element names, found {}", count, names.len()),
+            });
+        }
+        let (line, grid_line) = header("grid")?;
+        let mut fields = grid_line.split_whitespace();
+        let densities: usize = parse_field(fields.next(), line, "the number of densities")?;
+        let density_step: T = parse_number(fields.next(), line, "the density spacing")?;
+        let distances: usize = parse_field(fields.next(), line, "the number of distances")?;
+        let distance_step: T = parse_number(fields.next(), line, "the distance spacing")?;
+        let cutoff: T = parse_number(fields.next(), line, "the cutoff")?;
+        if densities < 2 || distances < 2 || density_step <= T::zero() || distance_step <= T::zero()
+        {
+            return Err(ParseError {
+                line: Some(line),
+                message: "the grids must have at least two positive steps".to_owned(),
+            });
+        }
+
+        let mut elements = Vec::with_capacity(count);
+        for name in names {
+            let (line, element_line) = lines
+                .next()
+                .map(|(index, line)| (index + 1, line))
+                .ok_or_else(|| ParseError {
+                    line: None,
+                    message: format!("the file ends before element {}", name),
+                })?;
+            let mut fields = element_line.split_whitespace();
+            let atomic_number = parse_field(fields.next(), line, "the atomic number")?;
+            let mass = parse_number(fields.next(), line, "the mass")?;
+            let lattice_constant = parse_number(fields.next(), line, "the lattice constant")?;
+            let lattice = fields.next().unwrap_or_default().to_owned();
+            let embedding = read_values(&mut lines, densities, name)?;
+            let density = read_values(&mut lines, distances, name)?;
+            elements.push(EamElement {
+                name: name.to_owned(),
+                atomic_number,
+                mass,
+                lattice_constant,
+                lattice,
+                embedding: UniformTable::new(T::zero(), density_step, embedding),
+                density: UniformTable::new(T::zero(), distance_step, density),
+            });
+        }
+        let scaled_pairs = (0..count * (count + 1) / 2)
+            .map(|_| {
+                read_values(&mut lines, distances, "pair potentials")
+                    .map(|values| UniformTable::new(T::zero(), distance_step, values))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(EamTables {
+            elements,
+            scaled_pairs,
+            cutoff,
+        })
+    }
+
+    fn parse_field<F: std::str::FromStr>(
+        field: Option<&str>,
+        line: usize,
+        what: &str,
+    ) -> Result<F, ParseError> {
+        field
+            .and_then(|field| field.parse().ok())
+            .ok_or_else(|| ParseError {
+                line: Some(line),
+                message: format!("missing or invalid {}", what),
+            })
+    }
+
+    fn parse_number<T: Float>(
+        field: Option<&str>,
+        line: usize,
+        what: &str,
+    ) -> Result<T, ParseError> {
+        parse_field::<f64>(field, line, what).and_then(|value| {
+            <T as NumCast>::from(value).ok_or_else(|| ParseError {
+                line: Some(line),
+                message: format!("{} is not representable", value),
+            })
+        })
+    }
+
+    /// Reads the next `count` values, which may span several lines.
+    fn read_values<'a, T: Float>(
+        lines: &mut impl Iterator<Item = (usize, &'a str)>,
+        count: usize,
+        what: &str,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut values = Vec::with_capacity(count);
+        while values.len() < count {
+            let (index, line) = lines.next().ok_or_else(|| ParseError {
+                line: None,
+                message: format!(
+                    "the file ends after {} of {} values of {}",
+                    values.len(),
+                    count,
+                    what
+                ),
+            })?;
+            for field in line.split_whitespace() {
+                if values.len() == count {
+                    return Err(ParseError {
+                        line: Some(index + 1),
+                        message: format!("a line of values of {} overruns the table", what),
+                    });
+                }
+                values.push(parse_number(Some(field), index + 1, "value")?);
+            }
+        }
+        Ok(values)
+    }
+
+    /// An error in loading the tables of an EAM potential from a file.
+    #[derive(Debug)]
+    pub enum SetflError {
+        Io(io::Error),
+        Parse(ParseError),
+    }
+
+    impl Display for SetflError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Io(err) => write!(f, "failed to read the setfl file: {}", err),
+                Self::Parse(err) => write!(f, "failed to parse the setfl file: {}", err),
+            }
+        }
+    }
+
+    impl Error for SetflError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::Io(err) => Some(err),
+                Self::Parse(err) => Some(err),
+            }
+        }
+    }
+
+    /// Reads the tables of an EAM potential from the `setfl` file at `path`;
+    /// see [`parse_setfl`].
+    pub fn load_setfl<T: Float + From<f32> + Real>(
+        path: impl AsRef<Path>,
+    ) -> Result<EamTables<T>, SetflError> {
+        let text = fs::read_to_string(path).map_err(SetflError::Io)?;
+        parse_setfl(&text).map_err(SetflError::Parse)
+    }
+}
+
+pub use setfl::{EamElement, EamTables, SetflError, load_setfl, parse_setfl};
+
+mod potential {
+    use std::marker::PhantomData;
+
+    use lib::{
+        core::{
+            Vector,
+            error::{ExpectationError, InvalidIndexError},
+        },
+        numeric::Real,
+        potential::{
+            GroupInTypeInImage,
+            physical::{BoxDependent, PhysicalPotential},
+        },
+    };
+    use num::Float;
+
+    use super::EamTables;
+    use crate::potential::{
+        builder::{ParameterError, fits_box, non_negative, required},
+        electrostatics::PeriodicBox,
+        pair::{NeighborListError, NeighborListStats, VerletList},
+    };
+
+    /// A builder of [`EamPotential`], obtained with [`EamPotential::builder`].
+    ///
+    /// The species of the atoms and the skin of the Verlet list are required
+    /// and the box is optional.
+    pub struct EamPotentialBuilder<const N: usize, T, V> {
+        tables: EamTables<T>,
+        species: Option<Vec<usize>>,
+        skin: Option<T>,
+        periodic_box: Option<PeriodicBox<T, N>>,
+        phantom: PhantomData<V>,
+    }
+
+    impl<const N: usize, T, V> EamPotentialBuilder<N, T, V>
+    where
+        T: Float + From<f32> + Real,
+        V: Vector<N, Element = T> + Clone,
+    {
+        /// Sets the indices of the elements of the tables the atoms of the group are of.
+        pub fn species(mut self, species: Vec<usize>) -> Self {
+            self.species = Some(species);
+            self
+        }
+
+        pub fn skin(mut self, skin: T) -> Self {
+            self.skin = Some(skin);
+            self
+        }
+
+        /// Applies the minimum image convention of `periodic_box`; see [`EamPotential::with_box`].
+        pub fn periodic_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            self.periodic_box = Some(periodic_box);
+            self
+        }
+
+        /// Constructs the potential, or returns the first parameter which is missing or invalid.
+        pub fn build(self) -> Result<EamPotential<N, T, V>, ParameterError<T>> {
+            let species = required(self.species, "species")?;
+            let elements = self.tables.elements().len();
+            if let Some((atom, &element)) = species
+                .iter()
+                .enumerate()
+                .find(|&(_, &element)| element >= elements)
+            {
+                return Err(ParameterError::UnknownElement {
+                    atom,
+                    element,
+                    elements,
+                });
+            }
+            let skin = non_negative(required(self.skin, "skin")?, "skin")?;
+            if let Some(periodic_box) = &self.periodic_box {
+                fits_box(self.tables.cutoff() + skin, periodic_box)?;
+            }
+            let potential = EamPotential::new(self.tables, species, skin);
+            Ok(match self.periodic_box {
+                Some(periodic_box) => potential.with_box(periodic_box),
+                None => potential,
+            })
+        }
+    }
+
+    /// The embedded-atom method potential between the atoms of a group in `N` dimensions.
+    ///
+    /// The forces are evaluated in two passes over the pairs of neighbors: the first accumulates
+    /// the electron density at every atom and the second adds the pair forces and those arising
+    /// from the derivative of the embedding energy. The densities and the derivatives are kept
+    /// in flat arrays indexed by atom between evaluations.
+    ///
+    /// The pairs are found with a Verlet list, under the minimum image convention
+    /// of the box if one is given.
+    pub struct EamPotential<const N: usize, T, V> {
+        tables: EamTables<T>,
+        species: Box<[usize]>,
+        neighbors: VerletList<N, T, V>,
+        pairs: Vec<(usize, usize, V, T)>,
+        densities: Vec<T>,
+        embedding_derivatives: Vec<T>,
+    }
+
+    impl<const N: usize, T, V> EamPotential<N, T, V>
+    where
+        T: Float + From<f32> + Real,
+        V: Vector<N, Element = T> + Clone,
+    {
+        /// Constructs the potential of a group whose atoms are of the elements of `tables`
+        /// at the indices `species`, with a Verlet list of skin `skin`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if an index in `species` is not that of an element of `tables`.
+        pub fn new(tables: EamTables<T>, species: Vec<usize>, skin: T) -> Self {
+            assert!(
+                species
+                    .iter()
+                    .all(|&element| element < tables.elements().len()),
+                "every atom must be of an element of the tables"
+            );
+            Self {
+                neighbors: VerletList::new(tables.cutoff(), skin),
+                tables,
+                species: species.into_boxed_slice(),
+                pairs: Vec::new(),
+                densities: Vec::new(),
+                embedding_derivatives: Vec::new(),
+            }
+        }
+
+        /// Applies the minimum image convention of `periodic_box` to the displacements.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the cutoff and the skin exceed half of a periodic length of the box.
+        pub fn with_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            self.neighbors.set_box(periodic_box);
+            self
+        }
+
+        pub fn tables(&self) -> &EamTables<T> {
+            &self.tables
+        }
+
+        /// Returns a builder of the potential with `tables`
+        /// which validates the parameters instead of panicking.
+        pub fn builder(tables: EamTables<T>) -> EamPotentialBuilder<N, T, V> {
+            EamPotentialBuilder {
+                tables,
+                species: None,
+                skin: None,
+                periodic_box: None,
+                phantom: PhantomData,
+            }
+        }
+
+        /// Returns the electron density at every atom at the last evaluation.
+        pub fn densities(&self) -> &[T] {
+            &self.densities
+        }
+
+        /// Returns the counters of the updates and the rebuilds of the Verlet list,
+        /// to be recorded in a [`ProfileReport`](crate::profile::ProfileReport).
+        pub fn neighbor_list_stats(&self) -> NeighborListStats {
+            self.neighbors.stats()
+        }
+
+        /// Collects the pairs within the cutoff with their displacements and distances.
+        fn collect_pairs(&mut self, positions: &[V]) -> Result<(), ExpectationError> {
+            let cutoff_squared = self.tables.cutoff() * self.tables.cutoff();
+            self.neighbors.update(positions, None)?;
+            let periodic_box = self.neighbors.periodic_box();
+            self.pairs.clear();
+            for (first, second) in self.neighbors.pairs() {
+                let mut displacement = positions[first].clone() - positions[second].clone();
+                if let Some(periodic_box) = periodic_box {
+                    displacement = periodic_box.minimum_image(displacement);
+                }
+                let distance_squared = displacement.clone().magnitude_squared();
+                if distance_squared < cutoff_squared {
+                    self.pairs
+                        .push((first, second, displacement, Float::sqrt(distance_squared)));
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns the energy of the group and adds the forces to `forces`, if given.
+        pub(crate) fn accumulate(
+            &mut self,
+            positions: &[V],
+            forces: Option<&mut [V]>,
+        ) -> Result<T, NeighborListError> {
+            if positions.len() != self.species.len() {
+                let atoms = positions.len().min(self.species.len());
+                return Err(InvalidIndexError::new(atoms, atoms).into());
+            }
+            self.collect_pairs(positions)?;
+            let elements = self.tables.elements();
+
+            self.densities.clear();
+            self.densities.resize(positions.len(), T::zero());
+            let mut energy = T::zero();
+            for &(first, second, _, distance) in &self.pairs {
+                let (first_element, second_element) = (self.species[first], self.species[second]);
+                self.densities[first] =
+                    self.densities[first] + elements[second_element].density.evaluate(distance).0;
+                self.densities[second] =
+                    self.densities[second] + elements[first_element].density.evaluate(distance).0;
+                energy = energy + self.tables.pair(first_element, second_element, distance).0;
+            }
+            self.embedding_derivatives.clear();
+            for (&density, &element) in self.densities.iter().zip(&self.species) {
+                let (embedding, derivative) = elements[element].embedding.evaluate(density);
+                energy = energy + embedding;
+                self.embedding_derivatives.push(derivative);
+            }
+
+            if let Some(forces) = forces {
+                for (first, second, displacement, distance) in &self.pairs {
+                    let (first, second, distance) = (*first, *second, *distance);
+                    let (first_element, second_element) =
+                        (self.species[first], self.species[second]);
+                    let derivative = self.tables.pair(first_element, second_element, distance).1
+                        + self.embedding_derivatives[first]
+                            * elements[second_element].density.evaluate(distance).1
+                        + self.embedding_derivatives[second]
+                            * elements[first_element].density.evaluate(distance).1;
+                    let force = displacement.clone() * (-derivative / distance);
+                    forces[first] += force.clone();
+                    forces[second] -= force;
+                }
+            }
+            Ok(energy)
+        }
+    }
+
+    impl<const N: usize, T: Float, V> BoxDependent<T> for EamPotential<N, T, V> {
+        /// Scales the box of the minimum image convention, if any.
+        fn scale_box(&mut self, factors: &[T]) {
+            self.neighbors.scale_box(factors);
+        }
+    }
+
+    impl<const N: usize, T, V> PhysicalPotential<T, V> for EamPotential<N, T, V>
+    where
+        T: Float + From<f32> + Real,
+        V: Vector<N, Element = T> + Clone,
+    {
+        type Error = NeighborListError;
+
+        fn calculate_potential_set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            group_forces.fill_with(V::zero);
+            self.accumulate(positions.read(), Some(group_forces))
+        }
+
+        fn calculate_potential_add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            self.accumulate(positions.read(), Some(group_forces))
+        }
+
+        fn calculate_potential(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+        ) -> Result<T, Self::Error> {
+            self.accumulate(positions.read(), None)
+        }
+
+        fn set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            group_forces.fill_with(V::zero);
+            self.accumulate(positions.read(), Some(group_forces))?;
+            Ok(())
+        }
+
+        fn add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            self.accumulate(positions.read(), Some(group_forces))?;
+            Ok(())
+        }
+    }
+}
+
+pub use potential::{EamPotential, EamPotentialBuilder};
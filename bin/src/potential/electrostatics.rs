@@ -0,0 +1,1142 @@
+mod periodic {
+    use lib::core::{
+        Vector,
+        error::{Expect, ExpectationError},
+    };
+    use num::{Float, NumCast};
+
+    /// An orthorhombic simulation box in `N` dimensions, periodic along every axis
+    /// except those opened with [`PeriodicBox::with_open_axes`].
+    ///
+    /// A box with `N = 2` describes a two-dimensional system, while a three-dimensional box
+    /// with an open axis describes a quasi-two-dimensional slab.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct PeriodicBox<T, const N: usize = 3> {
+        lengths: [T; N],
+        periodic: [bool; N],
+    }
+
+    impl<T: Float, const N: usize> PeriodicBox<T, N> {
+        pub fn new(lengths: [T; N]) -> Self {
+            assert!(
+                lengths.iter().all(|&length| length > T::zero()),
+                "box lengths must be positive"
+            );
+            Self {
+                lengths,
+                periodic: [true; N],
+            }
+        }
+
+        /// Makes the axes with `open[axis] == true` non-periodic.
+        ///
+        /// Along an open axis the length only sets the extent used for densities,
+        /// e.g. the thickness of a slab.
+        pub fn with_open_axes(mut self, open: [bool; N]) -> Self {
+            for (periodic, open) in self.periodic.iter_mut().zip(open) {
+                *periodic = !open;
+            }
+            self
+        }
+
+        pub fn lengths(&self) -> [T; N] {
+            self.lengths
+        }
+
+        /// Scales every length of the box by the respective factor in `factors`.
+        pub fn scale(&mut self, factors: &[T]) {
+            assert_eq!(factors.len(), N, "every axis must have a factor");
+            assert!(
+                factors.iter().all(|&factor| factor > T::zero()),
+                "scaling factors must be positive"
+            );
+            for (length, &factor) in self.lengths.iter_mut().zip(factors) {
+                *length = *length * factor;
+            }
+        }
+
+        pub fn is_periodic(&self, axis: usize) -> bool {
+            self.periodic[axis]
+        }
+
+        /// Returns whether the box is periodic along every axis.
+        pub fn is_fully_periodic(&self) -> bool {
+            self.periodic.iter().all(|&periodic| periodic)
+        }
+
+        /// Returns the number of periodic axes.
+        pub fn periodic_dimensions(&self) -> usize {
+            self.periodic.iter().filter(|&&periodic| periodic).count()
+        }
+
+        /// Returns the product of the lengths, i.e. the area of a two-dimensional box.
+        pub fn volume(&self) -> T {
+            self.lengths
+                .iter()
+                .fold(T::one(), |volume, &length| volume * length)
+        }
+
+        /// Returns the product of the lengths along the periodic axes,
+        /// i.e. the area of a slab.
+        pub fn periodic_volume(&self) -> T {
+            self.lengths
+                .iter()
+                .zip(&self.periodic)
+                .filter(|(_, periodic)| **periodic)
+                .fold(T::one(), |volume, (&length, _)| volume * length)
+        }
+
+        /// Returns the periodic image of `displacement` closest to the origin.
+        pub fn minimum_image<V>(&self, mut displacement: V) -> V
+        where
+            V: Vector<N, Element = T>,
+        {
+            for ((coordinate, &length), _) in displacement
+                .as_mut_array()
+                .iter_mut()
+                .zip(&self.lengths)
+                .zip(&self.periodic)
+                .filter(|(_, periodic)| **periodic)
+            {
+                *coordinate = *coordinate - length * (*coordinate / length).round();
+            }
+            displacement
+        }
+    }
+
+    /// Atoms sorted into the cells of a box, at least a given range wide along every axis
+    /// and wrapped around the periodic ones, so that the pairs of atoms closer than the range
+    /// are found among the atoms of neighboring cells instead of among all pairs.
+    ///
+    /// An open axis of the box is not divided into cells.
+    /// The storage of the cells is kept between builds.
+    #[derive(Clone, Debug)]
+    pub struct CellGrid<const N: usize> {
+        cells: [usize; N],
+        /// The first atom of every cell, or `usize::MAX` for an empty cell.
+        heads: Vec<usize>,
+        /// The next atom in the cell of every atom, or `usize::MAX` for the last one.
+        next: Vec<usize>,
+        /// The cell of every atom.
+        atom_cells: Vec<usize>,
+        /// The distinct neighboring cells of a cell, itself included, as offsets along every axis.
+        offsets: Vec<[usize; N]>,
+    }
+
+    impl<const N: usize> CellGrid<N> {
+        pub fn new() -> Self {
+            Self {
+                cells: [1; N],
+                heads: Vec::new(),
+                next: Vec::new(),
+                atom_cells: Vec::new(),
+                offsets: Vec::new(),
+            }
+        }
+
+        /// Returns the number of cells along every axis.
+        pub fn cells(&self) -> [usize; N] {
+            self.cells
+        }
+
+        /// Sorts `positions` into cells of `periodic_box` at least `range` wide.
+        ///
+        /// The number of cells is limited to about one per atom, so a short range
+        /// only makes the cells hold more atoms.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `range` is not positive.
+        pub fn build<T, V>(
+            &mut self,
+            periodic_box: &PeriodicBox<T, N>,
+            range: T,
+            positions: &[V],
+        ) -> Result<(), ExpectationError>
+        where
+            T: Float,
+            V: Vector<N, Element = T>,
+        {
+            assert!(range > T::zero(), "the range must be positive");
+            let periodic_axes = periodic_box.periodic_dimensions().max(1) as f64;
+            let max_cells = ((positions.len().max(1) as f64)
+                .powf(periodic_axes.recip())
+                .ceil() as usize)
+                .max(1);
+            let lengths = periodic_box.lengths();
+            for (axis, cells) in self.cells.iter_mut().enumerate() {
+                *cells = if periodic_box.is_periodic(axis) {
+                    (lengths[axis] / range)
+                        .floor()
+                        .to_usize()
+                        .unwrap_or(usize::MAX)
+                        .clamp(1, max_cells)
+                } else {
+                    1
+                };
+            }
+
+            self.heads.clear();
+            self.heads.resize(self.cells.iter().product(), usize::MAX);
+            self.next.clear();
+            self.next.resize(positions.len(), usize::MAX);
+            self.atom_cells.clear();
+            for (atom, position) in positions.iter().enumerate() {
+                let mut cell = 0;
+                for (axis, &coordinate) in position.as_array().iter().enumerate() {
+                    let cells = self.cells[axis];
+                    // Wrapped into the box before the conversion, so a far image of an atom
+                    // does not overflow the index.
+                    let fraction = coordinate / lengths[axis];
+                    let index = ((fraction - fraction.floor())
+                        * <T as NumCast>::from(cells)
+                            .expect_in_hot_path("the number of cells must be representable")?)
+                    .floor()
+                    .to_usize()
+                    .expect_in_hot_path("positions must be finite")?;
+                    cell = cell * cells + index.min(cells - 1);
+                }
+                self.next[atom] = self.heads[cell];
+                self.heads[cell] = atom;
+                self.atom_cells.push(cell);
+            }
+
+            // Along an axis of fewer than three cells the offsets of -1 and 1 wrap
+            // onto the same cells, which must be visited once.
+            let steps = self.cells.map(|cells| cells.min(3));
+            self.offsets.clear();
+            for index in 0..steps.iter().product() {
+                let mut digits = index;
+                self.offsets.push(std::array::from_fn(|axis| {
+                    let step = digits % steps[axis];
+                    digits /= steps[axis];
+                    if steps[axis] == 3 {
+                        (step + self.cells[axis] - 1) % self.cells[axis]
+                    } else {
+                        step
+                    }
+                }));
+            }
+            Ok(())
+        }
+
+        /// Calls `visit` with every pair of atoms in neighboring cells, the lower index first,
+        /// each pair once. The pairs closer than the range of the last build are among them.
+        pub fn for_each_candidate<F>(&self, mut visit: F)
+        where
+            F: FnMut(usize, usize),
+        {
+            for (atom, &cell) in self.atom_cells.iter().enumerate() {
+                let mut coordinates = [0; N];
+                let mut remainder = cell;
+                for (coordinate, &cells) in coordinates.iter_mut().zip(&self.cells).rev() {
+                    *coordinate = remainder % cells;
+                    remainder /= cells;
+                }
+                for offset in &self.offsets {
+                    let neighbor = coordinates.iter().zip(offset).zip(&self.cells).fold(
+                        0,
+                        |neighbor, ((&coordinate, &offset), &cells)| {
+                            neighbor * cells + (coordinate + offset) % cells
+                        },
+                    );
+                    let mut other = self.heads[neighbor];
+                    while other != usize::MAX {
+                        if other > atom {
+                            visit(atom, other);
+                        }
+                        other = self.next[other];
+                    }
+                }
+            }
+        }
+    }
+
+    impl<const N: usize> Default for CellGrid<N> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+pub use periodic::{CellGrid, PeriodicBox};
+
+mod ewald {
+    use super::{CellGrid, PeriodicBox};
+    use crate::core::constants::COULOMB_CONSTANT;
+    use lib::core::{Vector, error::ExpectationError, warnings::WarningSink};
+    use num::Float;
+
+    /// Returns the complementary error function of `x`.
+    ///
+    /// The relative error of the approximation is below 1.2e-7 for all `x`.
+    pub(crate) fn erfc<T: Float + From<f32>>(x: T) -> T {
+        const COEFFICIENTS: [f32; 10] = [
+            -1.265_512_2,
+            1.000_023_7,
+            0.374_091_96,
+            0.096_784_18,
+            -0.186_288_06,
+            0.278_868_07,
+            -1.135_204,
+            1.488_515_9,
+            -0.822_152_23,
+            0.170_872_77,
+        ];
+        let z = x.abs();
+        let t = T::one() / (T::one() + <T as From<f32>>::from(0.5) * z);
+        let polynomial = COEFFICIENTS
+            .iter()
+            .rev()
+            .fold(T::zero(), |accum, &coefficient| {
+                accum * t + <T as From<f32>>::from(coefficient)
+            });
+        let value = t * (polynomial - z * z).exp();
+        if x < T::zero() {
+            <T as From<f32>>::from(2.0) - value
+        } else {
+            value
+        }
+    }
+
+    /// Adds the real-space part of the Ewald sum to `forces` and returns its energy.
+    ///
+    /// Only pairs closer than `cutoff` under the minimum image convention interact,
+    /// and they are found among the neighbors in `cells`, rebuilt for the positions.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn real_space<T, V>(
+        periodic_box: &PeriodicBox<T>,
+        alpha: T,
+        cutoff: T,
+        charges: &[T],
+        positions: &[V],
+        forces: &mut [V],
+        cells: &mut CellGrid<3>,
+    ) -> Result<T, ExpectationError>
+    where
+        T: Float + From<f32>,
+        V: Vector<3, Element = T> + Clone,
+    {
+        let coulomb_constant = <T as From<f32>>::from(COULOMB_CONSTANT);
+        let gaussian_factor = <T as From<f32>>::from(2.0) * alpha
+            / <T as From<f32>>::from(std::f32::consts::PI).sqrt();
+        let cutoff_squared = cutoff * cutoff;
+        let mut energy = T::zero();
+        cells.build(periodic_box, cutoff, positions)?;
+        cells.for_each_candidate(|i, j| {
+            let displacement =
+                periodic_box.minimum_image(positions[i].clone() - positions[j].clone());
+            let distance_squared = displacement.clone().magnitude_squared();
+            if distance_squared >= cutoff_squared {
+                return;
+            }
+            let distance = distance_squared.sqrt();
+            let charge_product = coulomb_constant * charges[i] * charges[j];
+            let screened = erfc(alpha * distance) / distance;
+            energy = energy + charge_product * screened;
+            let factor = charge_product
+                * (screened + gaussian_factor * (-alpha * alpha * distance_squared).exp())
+                / distance_squared;
+            let force = displacement * factor;
+            forces[i] += force.clone();
+            forces[j] -= force;
+        });
+        Ok(energy)
+    }
+
+    /// Returns the energy of the interaction of every charge with its own screening cloud
+    /// and, for systems which are not neutral, with the neutralizing background.
+    pub(crate) fn self_energy<T: Float + From<f32>>(
+        periodic_box: &PeriodicBox<T>,
+        alpha: T,
+        charges: &[T],
+    ) -> T {
+        let pi = <T as From<f32>>::from(std::f32::consts::PI);
+        let coulomb_constant = <T as From<f32>>::from(COULOMB_CONSTANT);
+        let (total, total_squared) = charges
+            .iter()
+            .fold((T::zero(), T::zero()), |(total, total_squared), &charge| {
+                (total + charge, total_squared + charge * charge)
+            });
+        -coulomb_constant * alpha / pi.sqrt() * total_squared
+            - coulomb_constant * pi * total * total
+                / (<T as From<f32>>::from(2.0) * periodic_box.volume() * alpha * alpha)
+    }
+
+    /// Returns the splitting parameter for which the real-space sum,
+    /// truncated at `cutoff`, reaches a relative accuracy of `accuracy`.
+    pub(crate) fn tuned_alpha<T: Float>(cutoff: T, accuracy: T) -> T {
+        assert!(
+            accuracy > T::zero() && accuracy < T::one(),
+            "accuracy must be within (0, 1)"
+        );
+        assert!(cutoff > T::zero(), "cutoff must be positive");
+        (-accuracy.ln()).sqrt() / cutoff
+    }
+
+    /// A plain Ewald summation of periodic electrostatics.
+    ///
+    /// The reciprocal-space sum runs over every wave vector within the cutoffs,
+    /// so its cost grows as the number of atoms times the number of wave vectors.
+    #[derive(Clone, Debug)]
+    pub struct Ewald<T> {
+        periodic_box: PeriodicBox<T>,
+        alpha: T,
+        cutoff: T,
+        max_wave_numbers: [i64; 3],
+        cells: CellGrid<3>,
+    }
+
+    impl<T: Float + From<f32>> Ewald<T> {
+        pub fn new(
+            periodic_box: PeriodicBox<T>,
+            alpha: T,
+            cutoff: T,
+            max_wave_numbers: [i64; 3],
+        ) -> Self {
+            assert!(
+                periodic_box.is_fully_periodic(),
+                "the box must be periodic along every axis"
+            );
+            assert!(alpha > T::zero(), "alpha must be positive");
+            assert!(cutoff > T::zero(), "cutoff must be positive");
+            assert!(
+                max_wave_numbers.iter().all(|&max| max >= 0),
+                "maximal wave numbers must be non-negative"
+            );
+            let shortest = periodic_box
+                .lengths()
+                .iter()
+                .fold(T::infinity(), |shortest, &length| shortest.min(length));
+            WarningSink::global().check_cutoff(
+                "Ewald summation",
+                cutoff.to_f64().unwrap_or(f64::NAN),
+                shortest.to_f64().unwrap_or(f64::NAN),
+            );
+            Self {
+                periodic_box,
+                alpha,
+                cutoff,
+                max_wave_numbers,
+                cells: CellGrid::new(),
+            }
+        }
+
+        /// Constructs a summation which reaches a relative accuracy of `accuracy`
+        /// in both the real-space sum truncated at `cutoff` and the reciprocal-space sum.
+        pub fn tuned(periodic_box: PeriodicBox<T>, cutoff: T, accuracy: T) -> Self {
+            let alpha = tuned_alpha(cutoff, accuracy);
+            let max_wave_vector = <T as From<f32>>::from(2.0) * alpha * (-accuracy.ln()).sqrt();
+            let two_pi = <T as From<f32>>::from(std::f32::consts::TAU);
+            let lengths = periodic_box.lengths();
+            let max_wave_numbers = std::array::from_fn(|dimension| {
+                (max_wave_vector * lengths[dimension] / two_pi)
+                    .ceil()
+                    .to_i64()
+                    .expect("the number of wave vectors must fit in `i64`")
+            });
+            Self::new(periodic_box, alpha, cutoff, max_wave_numbers)
+        }
+
+        pub fn alpha(&self) -> T {
+            self.alpha
+        }
+
+        pub fn cutoff(&self) -> T {
+            self.cutoff
+        }
+
+        pub fn periodic_box(&self) -> &PeriodicBox<T> {
+            &self.periodic_box
+        }
+
+        /// Adds the reciprocal-space forces to `forces` and returns the reciprocal-space energy.
+        pub(crate) fn reciprocal_space<V>(
+            &self,
+            charges: &[T],
+            positions: &[V],
+            forces: &mut [V],
+        ) -> T
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            let two_pi = <T as From<f32>>::from(std::f32::consts::TAU);
+            let lengths = self.periodic_box.lengths();
+            let prefactor =
+                <T as From<f32>>::from(COULOMB_CONSTANT) * two_pi / self.periodic_box.volume();
+            let four_alpha_squared = <T as From<f32>>::from(4.0) * self.alpha * self.alpha;
+            let [max_x, max_y, max_z] = self.max_wave_numbers;
+            let mut phases = vec![T::zero(); positions.len()];
+            let mut energy = T::zero();
+            for nx in -max_x..=max_x {
+                for ny in -max_y..=max_y {
+                    for nz in -max_z..=max_z {
+                        if (nx, ny, nz) == (0, 0, 0) {
+                            continue;
+                        }
+                        let wave_numbers = [nx, ny, nz];
+                        let wave_vector: [T; 3] = std::array::from_fn(|dimension| {
+                            two_pi * <T as From<f32>>::from(wave_numbers[dimension] as f32)
+                                / lengths[dimension]
+                        });
+                        let wave_vector_squared =
+                            wave_vector.iter().fold(T::zero(), |sum, &k| sum + k * k);
+                        let weight =
+                            (-wave_vector_squared / four_alpha_squared).exp() / wave_vector_squared;
+                        let (mut structure_re, mut structure_im) = (T::zero(), T::zero());
+                        for ((phase, position), &charge) in
+                            phases.iter_mut().zip(positions).zip(charges)
+                        {
+                            *phase = wave_vector
+                                .iter()
+                                .zip(position.as_array())
+                                .fold(T::zero(), |sum, (&k, &x)| sum + k * x);
+                            structure_re = structure_re + charge * phase.cos();
+                            structure_im = structure_im + charge * phase.sin();
+                        }
+                        energy = energy
+                            + prefactor
+                                * weight
+                                * (structure_re * structure_re + structure_im * structure_im);
+                        let force_factor = <T as From<f32>>::from(2.0) * prefactor * weight;
+                        let direction = V::from_array(wave_vector);
+                        for ((force, &phase), &charge) in
+                            forces.iter_mut().zip(&phases).zip(charges)
+                        {
+                            let magnitude = force_factor
+                                * charge
+                                * (structure_re * phase.sin() - structure_im * phase.cos());
+                            force.mul_add_assign(&direction, magnitude);
+                        }
+                    }
+                }
+            }
+            energy
+        }
+
+        /// Sets `forces` to the electrostatic forces on the atoms and returns the energy.
+        pub fn calculate_potential_set_forces<V>(
+            &mut self,
+            charges: &[T],
+            positions: &[V],
+            forces: &mut [V],
+        ) -> Result<T, ExpectationError>
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            assert_eq!(charges.len(), positions.len());
+            assert_eq!(forces.len(), positions.len());
+            for force in forces.iter_mut() {
+                *force = V::zero();
+            }
+            Ok(real_space(
+                &self.periodic_box,
+                self.alpha,
+                self.cutoff,
+                charges,
+                positions,
+                forces,
+                &mut self.cells,
+            )? + self.reciprocal_space(charges, positions, forces)
+                + self_energy(&self.periodic_box, self.alpha, charges))
+        }
+    }
+}
+
+pub use ewald::Ewald;
+
+mod fft {
+    use num::{Complex, Float};
+
+    /// Transforms `data` in place with an unnormalized radix-2 discrete Fourier transform.
+    ///
+    /// The transform uses the kernel `exp(sign * 2πi jk / n)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `data` is not a power of two.
+    pub(crate) fn transform<T: Float + From<f32>>(data: &mut [Complex<T>], sign: T) {
+        let n = data.len();
+        assert!(n.is_power_of_two(), "the length must be a power of two");
+        let mut j = 0;
+        for i in 1..n {
+            let mut bit = n >> 1;
+            while j & bit != 0 {
+                j ^= bit;
+                bit >>= 1;
+            }
+            j |= bit;
+            if i < j {
+                data.swap(i, j);
+            }
+        }
+        let mut length = 2;
+        while length <= n {
+            let angle = sign * <T as From<f32>>::from(std::f32::consts::TAU)
+                / <T as From<f32>>::from(length as f32);
+            let root = Complex::new(angle.cos(), angle.sin());
+            for chunk in data.chunks_exact_mut(length) {
+                let (lower, upper) = chunk.split_at_mut(length / 2);
+                let mut twiddle = Complex::new(T::one(), T::zero());
+                for (a, b) in lower.iter_mut().zip(upper) {
+                    let product = *b * twiddle;
+                    *b = *a - product;
+                    *a = *a + product;
+                    twiddle = twiddle * root;
+                }
+            }
+            length <<= 1;
+        }
+    }
+
+    /// Transforms a row-major three-dimensional mesh of shape `shape` in place along every axis.
+    pub(crate) fn transform_3d<T: Float + From<f32>>(
+        data: &mut [Complex<T>],
+        shape: [usize; 3],
+        sign: T,
+        line: &mut Vec<Complex<T>>,
+    ) {
+        let strides = [shape[1] * shape[2], shape[2], 1];
+        for axis in 0..3 {
+            let (length, stride) = (shape[axis], strides[axis]);
+            for start in 0..data.len() {
+                // Visit every line along `axis` once, starting from its first element.
+                if (start / stride) % length != 0 {
+                    continue;
+                }
+                line.clear();
+                line.extend((0..length).map(|index| data[start + index * stride]));
+                transform(line, sign);
+                for (index, &value) in line.iter().enumerate() {
+                    data[start + index * stride] = value;
+                }
+            }
+        }
+    }
+}
+
+mod pppm {
+    use super::{
+        CellGrid, Ewald, PeriodicBox,
+        ewald::{real_space, self_energy, tuned_alpha},
+        fft::transform_3d,
+    };
+    use crate::core::constants::COULOMB_CONSTANT;
+    use lib::core::{
+        Vector,
+        error::{Expect, ExpectationError},
+    };
+    use num::{Complex, Float};
+
+    /// Fills `weights[j]` with the cardinal B-spline of order `weights.len()` at `offset + j`
+    /// and `derivatives[j]` with its derivative, for `offset` within `[0, 1)`.
+    fn bspline<T: Float + From<f32>>(offset: T, weights: &mut [T], derivatives: &mut [T]) {
+        let order = weights.len();
+        weights.fill(T::zero());
+        weights[0] = T::one();
+        for k in 2..=order {
+            if k == order {
+                derivatives[0] = weights[0];
+                for j in 1..order {
+                    derivatives[j] = weights[j] - weights[j - 1];
+                }
+            }
+            let divisor = <T as From<f32>>::from((k - 1) as f32);
+            for j in (0..k).rev() {
+                let shifted = offset + <T as From<f32>>::from(j as f32);
+                let lower = if j > 0 { weights[j - 1] } else { T::zero() };
+                weights[j] = (shifted * weights[j]
+                    + (<T as From<f32>>::from(k as f32) - shifted) * lower)
+                    / divisor;
+            }
+        }
+    }
+
+    /// A smooth particle-mesh Ewald solver of periodic electrostatics.
+    ///
+    /// Charges are spread onto a mesh with cardinal B-splines of order `order`
+    /// and the reciprocal-space sum is evaluated with fast Fourier transforms,
+    /// so its cost grows as the number of atoms plus the number of mesh points
+    /// times its logarithm.
+    #[derive(Clone, Debug)]
+    pub struct Pppm<T> {
+        periodic_box: PeriodicBox<T>,
+        alpha: T,
+        cutoff: T,
+        order: usize,
+        mesh: [usize; 3],
+        influence: Vec<T>,
+        grid: Vec<Complex<T>>,
+        /// The charges spread onto the mesh, kept for the energy after the transforms.
+        charge_density: Vec<T>,
+        line: Vec<Complex<T>>,
+        cells: CellGrid<3>,
+    }
+
+    impl<T: Float + From<f32>> Pppm<T> {
+        /// The largest number of mesh points along an axis [`Pppm::tuned`] will try.
+        pub const MAX_MESH: usize = 256;
+
+        /// # Panics
+        ///
+        /// Panics if the box has an open axis, a dimension of `mesh` is not a power of two
+        /// or `order` is outside `2..=8`.
+        pub fn new(
+            periodic_box: PeriodicBox<T>,
+            alpha: T,
+            cutoff: T,
+            mesh: [usize; 3],
+            order: usize,
+        ) -> Self {
+            assert!(
+                periodic_box.is_fully_periodic(),
+                "the box must be periodic along every axis"
+            );
+            assert!(alpha > T::zero(), "alpha must be positive");
+            assert!(cutoff > T::zero(), "cutoff must be positive");
+            assert!((2..=8).contains(&order), "order must be within 2..=8");
+            assert!(
+                mesh.iter()
+                    .all(|&points| points.is_power_of_two() && points >= order),
+                "mesh dimensions must be powers of two no smaller than the order"
+            );
+            let mut pppm = Self {
+                periodic_box,
+                alpha,
+                cutoff,
+                order,
+                mesh,
+                influence: Vec::new(),
+                grid: vec![Complex::new(T::zero(), T::zero()); mesh.iter().product()],
+                charge_density: Vec::new(),
+                line: Vec::new(),
+                cells: CellGrid::new(),
+            };
+            pppm.influence = pppm.influence_function();
+            pppm
+        }
+
+        /// Constructs a solver whose forces are within `accuracy` of a tuned [`Ewald`] summation
+        /// in the root mean square over the atoms of the configuration given by `charges` and `positions`.
+        ///
+        /// The splitting parameter is chosen from `cutoff` as for [`Ewald::tuned`],
+        /// after which the mesh is refined until the reciprocal-space forces reach the accuracy
+        /// or the mesh reaches [`Pppm::MAX_MESH`] points along an axis.
+        pub fn tuned<V>(
+            periodic_box: PeriodicBox<T>,
+            cutoff: T,
+            accuracy: T,
+            order: usize,
+            charges: &[T],
+            positions: &[V],
+        ) -> Self
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            let alpha = tuned_alpha(cutoff, accuracy);
+            let ewald = Ewald::tuned(periodic_box, cutoff, accuracy);
+            let zero = || V::zero();
+            let mut reference: Vec<V> = positions.iter().map(|_| zero()).collect();
+            ewald.reciprocal_space(charges, positions, &mut reference);
+            let lengths = periodic_box.lengths();
+            let mut mesh = std::array::from_fn(|dimension| {
+                // Start from about one mesh point per screening length.
+                (alpha * lengths[dimension])
+                    .ceil()
+                    .to_usize()
+                    .unwrap_or(1)
+                    .max(order)
+                    .next_power_of_two()
+                    .min(Self::MAX_MESH)
+            });
+            let mut forces: Vec<V> = positions.iter().map(|_| zero()).collect();
+            loop {
+                let mut pppm = Self::new(periodic_box, alpha, cutoff, mesh, order);
+                forces.iter_mut().for_each(|force| *force = zero());
+                pppm.reciprocal_space(charges, positions, &mut forces)
+                    .expect("positions must be finite");
+                let error_squared =
+                    forces
+                        .iter()
+                        .zip(&reference)
+                        .fold(T::zero(), |sum, (force, reference)| {
+                            sum + (force.clone() - reference.clone()).magnitude_squared()
+                        })
+                        / <T as From<f32>>::from(positions.len().max(1) as f32);
+                if error_squared.sqrt() <= accuracy
+                    || mesh.iter().all(|&points| points >= Self::MAX_MESH)
+                {
+                    return pppm;
+                }
+                mesh = mesh.map(|points| (points * 2).min(Self::MAX_MESH));
+            }
+        }
+
+        pub fn alpha(&self) -> T {
+            self.alpha
+        }
+
+        pub fn cutoff(&self) -> T {
+            self.cutoff
+        }
+
+        pub fn mesh(&self) -> [usize; 3] {
+            self.mesh
+        }
+
+        pub fn order(&self) -> usize {
+            self.order
+        }
+
+        pub fn periodic_box(&self) -> &PeriodicBox<T> {
+            &self.periodic_box
+        }
+
+        /// Returns the signed wave number of mesh index `index` along an axis of `points` points.
+        fn wave_number(index: usize, points: usize) -> T {
+            let signed = if index < points / 2 {
+                index as f32
+            } else {
+                index as f32 - points as f32
+            };
+            <T as From<f32>>::from(signed)
+        }
+
+        /// Returns the Ewald kernel divided by the squared B-spline structure factors
+        /// at every point of the reciprocal mesh.
+        fn influence_function(&self) -> Vec<T> {
+            let pi = <T as From<f32>>::from(std::f32::consts::PI);
+            let tau = <T as From<f32>>::from(std::f32::consts::TAU);
+            let lengths = self.periodic_box.lengths();
+            let mut weights = vec![T::zero(); self.order];
+            let mut derivatives = vec![T::zero(); self.order];
+            bspline(T::zero(), &mut weights, &mut derivatives);
+            let moduli: [Vec<T>; 3] = std::array::from_fn(|dimension| {
+                let points = self.mesh[dimension];
+                (0..points)
+                    .map(|index| {
+                        let (re, im) =
+                            (1..self.order).fold((T::zero(), T::zero()), |(re, im), k| {
+                                let angle = tau * <T as From<f32>>::from((index * (k - 1)) as f32)
+                                    / <T as From<f32>>::from(points as f32);
+                                (re + weights[k] * angle.cos(), im + weights[k] * angle.sin())
+                            });
+                        re * re + im * im
+                    })
+                    .collect()
+            });
+            let prefactor =
+                <T as From<f32>>::from(COULOMB_CONSTANT) / (pi * self.periodic_box.volume());
+            let alpha_squared = self.alpha * self.alpha;
+            let [nx, ny, nz] = self.mesh;
+            let mut influence = Vec::with_capacity(nx * ny * nz);
+            for i in 0..nx {
+                for j in 0..ny {
+                    for k in 0..nz {
+                        let modulus = moduli[0][i] * moduli[1][j] * moduli[2][k];
+                        if (i, j, k) == (0, 0, 0) || modulus <= T::epsilon() {
+                            // Modes which the splines cannot represent are dropped.
+                            influence.push(T::zero());
+                            continue;
+                        }
+                        let wave_squared = [i, j, k].iter().zip(&self.mesh).zip(&lengths).fold(
+                            T::zero(),
+                            |sum, ((&index, &points), &length)| {
+                                let m = Self::wave_number(index, points) / length;
+                                sum + m * m
+                            },
+                        );
+                        influence.push(
+                            prefactor * (-pi * pi * wave_squared / alpha_squared).exp()
+                                / (wave_squared * modulus),
+                        );
+                    }
+                }
+            }
+            influence
+        }
+
+        /// Calls `f` with the flat mesh index, the weight and the gradient of the weight
+        /// of every mesh point the charge at `position` is spread onto.
+        fn for_each_point<V, F>(
+            &self,
+            position: &V,
+            weights: &mut [[T; 8]; 3],
+            derivatives: &mut [[T; 8]; 3],
+            mut f: F,
+        ) -> Result<(), ExpectationError>
+        where
+            V: Vector<3, Element = T>,
+            F: FnMut(usize, T, [T; 3]),
+        {
+            let lengths = self.periodic_box.lengths();
+            let mut base = [0i64; 3];
+            for dimension in 0..3 {
+                let points = self.mesh[dimension];
+                let scaled = position.as_array()[dimension] / lengths[dimension]
+                    * <T as From<f32>>::from(points as f32);
+                let floor = scaled.floor();
+                base[dimension] = floor
+                    .to_i64()
+                    .expect_in_hot_path("positions must be finite")?;
+                bspline(
+                    scaled - floor,
+                    &mut weights[dimension][..self.order],
+                    &mut derivatives[dimension][..self.order],
+                );
+                for derivative in &mut derivatives[dimension][..self.order] {
+                    *derivative =
+                        *derivative * <T as From<f32>>::from(points as f32) / lengths[dimension];
+                }
+            }
+            let wrap = |dimension: usize, offset: usize| {
+                (base[dimension] - offset as i64).rem_euclid(self.mesh[dimension] as i64) as usize
+            };
+            for a in 0..self.order {
+                let i = wrap(0, a);
+                for b in 0..self.order {
+                    let j = wrap(1, b);
+                    for c in 0..self.order {
+                        let k = wrap(2, c);
+                        let (wx, wy, wz) = (weights[0][a], weights[1][b], weights[2][c]);
+                        f(
+                            (i * self.mesh[1] + j) * self.mesh[2] + k,
+                            wx * wy * wz,
+                            [
+                                derivatives[0][a] * wy * wz,
+                                wx * derivatives[1][b] * wz,
+                                wx * wy * derivatives[2][c],
+                            ],
+                        );
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Adds the reciprocal-space forces to `forces` and returns the reciprocal-space energy.
+        pub(crate) fn reciprocal_space<V>(
+            &mut self,
+            charges: &[T],
+            positions: &[V],
+            forces: &mut [V],
+        ) -> Result<T, ExpectationError>
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            let mut weights = [[T::zero(); 8]; 3];
+            let mut derivatives = [[T::zero(); 8]; 3];
+            let mut grid = std::mem::take(&mut self.grid);
+            grid.fill(Complex::new(T::zero(), T::zero()));
+            for (position, &charge) in positions.iter().zip(charges) {
+                self.for_each_point(
+                    position,
+                    &mut weights,
+                    &mut derivatives,
+                    |index, weight, _| {
+                        grid[index].re = grid[index].re + charge * weight;
+                    },
+                )?;
+            }
+            self.charge_density.clear();
+            self.charge_density
+                .extend(grid.iter().map(|value| value.re));
+            transform_3d(&mut grid, self.mesh, T::one(), &mut self.line);
+            for (value, &influence) in grid.iter_mut().zip(&self.influence) {
+                *value = *value * influence;
+            }
+            transform_3d(&mut grid, self.mesh, -T::one(), &mut self.line);
+            let energy = self
+                .charge_density
+                .iter()
+                .zip(&grid)
+                .fold(T::zero(), |sum, (&density, potential)| {
+                    sum + density * potential.re
+                })
+                / <T as From<f32>>::from(2.0);
+            for ((position, &charge), force) in positions.iter().zip(charges).zip(forces.iter_mut())
+            {
+                let mut gradient = [T::zero(); 3];
+                self.for_each_point(
+                    position,
+                    &mut weights,
+                    &mut derivatives,
+                    |index, _, weight_gradient| {
+                        for (component, derivative) in gradient.iter_mut().zip(weight_gradient) {
+                            *component = *component + derivative * grid[index].re;
+                        }
+                    },
+                )?;
+                force.mul_add_assign(&V::from_array(gradient), -charge);
+            }
+            self.grid = grid;
+            Ok(energy)
+        }
+
+        /// Sets `forces` to the electrostatic forces on the atoms and returns the energy.
+        pub fn calculate_potential_set_forces<V>(
+            &mut self,
+            charges: &[T],
+            positions: &[V],
+            forces: &mut [V],
+        ) -> Result<T, ExpectationError>
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            assert_eq!(charges.len(), positions.len());
+            assert_eq!(forces.len(), positions.len());
+            for force in forces.iter_mut() {
+                *force = V::zero();
+            }
+            Ok(real_space(
+                &self.periodic_box,
+                self.alpha,
+                self.cutoff,
+                charges,
+                positions,
+                forces,
+                &mut self.cells,
+            )? + self.reciprocal_space(charges, positions, forces)?
+                + self_energy(&self.periodic_box, self.alpha, charges))
+        }
+    }
+}
+
+pub use pppm::Pppm;
+
+mod selection {
+    use super::{Ewald, PeriodicBox, Pppm};
+    use lib::core::{Vector, error::ExpectationError};
+    use num::Float;
+
+    /// A solver of periodic electrostatics chosen by the size of the system.
+    #[derive(Clone, Debug)]
+    pub enum Electrostatics<T> {
+        Ewald(Ewald<T>),
+        Pppm(Pppm<T>),
+    }
+
+    impl<T: Float + From<f32>> Electrostatics<T> {
+        /// The default number of atoms from which [`Electrostatics::tuned`] chooses the mesh solver.
+        pub const DEFAULT_PPPM_THRESHOLD: usize = 1000;
+        /// The default order of the charge assignment splines.
+        pub const DEFAULT_ORDER: usize = 5;
+
+        /// Constructs a solver tuned to `accuracy` for the configuration given by `charges` and `positions`.
+        ///
+        /// A plain Ewald summation is used for fewer than `pppm_threshold` atoms
+        /// and a particle-mesh solver with splines of order [`Electrostatics::DEFAULT_ORDER`] otherwise.
+        pub fn tuned<V>(
+            periodic_box: PeriodicBox<T>,
+            cutoff: T,
+            accuracy: T,
+            pppm_threshold: usize,
+            charges: &[T],
+            positions: &[V],
+        ) -> Self
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            if positions.len() < pppm_threshold {
+                Self::Ewald(Ewald::tuned(periodic_box, cutoff, accuracy))
+            } else {
+                Self::Pppm(Pppm::tuned(
+                    periodic_box,
+                    cutoff,
+                    accuracy,
+                    Self::DEFAULT_ORDER,
+                    charges,
+                    positions,
+                ))
+            }
+        }
+
+        pub fn periodic_box(&self) -> &PeriodicBox<T> {
+            match self {
+                Self::Ewald(ewald) => ewald.periodic_box(),
+                Self::Pppm(pppm) => pppm.periodic_box(),
+            }
+        }
+
+        /// Sets `forces` to the electrostatic forces on the atoms and returns the energy.
+        pub fn calculate_potential_set_forces<V>(
+            &mut self,
+            charges: &[T],
+            positions: &[V],
+            forces: &mut [V],
+        ) -> Result<T, ExpectationError>
+        where
+            V: Vector<3, Element = T> + Clone,
+        {
+            match self {
+                Self::Ewald(ewald) => {
+                    ewald.calculate_potential_set_forces(charges, positions, forces)
+                }
+                Self::Pppm(pppm) => pppm.calculate_potential_set_forces(charges, positions, forces),
+            }
+        }
+    }
+}
+
+pub use selection::Electrostatics;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::ArrayVector;
+    use lib::core::Vector;
+
+    /// Asserts that the cells find the pairs closer than `range` found by visiting every pair.
+    fn assert_finds_pairs_within<const N: usize>(
+        periodic_box: &PeriodicBox<f64, N>,
+        range: f64,
+        positions: &[ArrayVector<N, f64>],
+    ) {
+        let within = |i: usize, j: usize| {
+            periodic_box
+                .minimum_image(positions[i] - positions[j])
+                .magnitude_squared()
+                < range * range
+        };
+        let mut cells = CellGrid::new();
+        cells.build(periodic_box, range, positions).unwrap();
+        let mut found = Vec::new();
+        cells.for_each_candidate(|i, j| {
+            assert!(i < j);
+            if within(i, j) {
+                found.push((i, j));
+            }
+        });
+        found.sort_unstable();
+        let expected: Vec<_> = (0..positions.len())
+            .flat_map(|i| (i + 1..positions.len()).map(move |j| (i, j)))
+            .filter(|&(i, j)| within(i, j))
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn cell_grid_finds_every_pair_once() {
+        let mut state = 1u64;
+        let mut uniform = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / 2f64.powi(53)
+        };
+        // Wide boxes, boxes of one or two cells along an axis and positions outside the box.
+        for (lengths, range) in [([10.0, 12.0, 9.0], 2.0), ([5.0, 3.0, 8.0], 1.4)] {
+            let periodic_box = PeriodicBox::new(lengths);
+            let positions: Vec<_> = (0..300)
+                .map(|_| ArrayVector::from(lengths.map(|length| length * (3.0 * uniform() - 1.0))))
+                .collect();
+            assert_finds_pairs_within(&periodic_box, range, &positions);
+        }
+        let slab = PeriodicBox::new([6.0, 6.0]).with_open_axes([false, true]);
+        let positions: Vec<_> = (0..200)
+            .map(|_| ArrayVector::from([6.0 * uniform(), 20.0 * uniform()]))
+            .collect();
+        assert_finds_pairs_within(&slab, 1.0, &positions);
+    }
+}
@@ -1,48 +1,96 @@
+pub use lib::potential::exchange::RingPolymerParameters;
+
 mod distinguishable {
-    use std::ops::{Add, Mul, Range};
+    use std::ops::{Add, Div, Mul, Range};
 
     use lib::{
         core::{
             Vector,
             error::{AccessError, EmptyError, InvalidRangeError},
             marker::{InnerIsLeading, InnerIsTrailing},
+            resolution::BeadCountDependent,
             stat::Distinguishable,
             zip_items, zip_iterators,
         },
         potential::exchange::InnerExchangePotential,
+        thermostat::TemperatureDependent,
     };
 
-    use crate::core::constants::{BOLTZMANN_CONSTANT, REDUCED_PLANK_CONSTANT};
+    use super::RingPolymerParameters;
 
     pub struct DistinguishableExchangePotential<const N: usize, T> {
+        /// The prefactor divided by the square of the temperature.
+        mass_prefactor: T,
         potential_prefactor: T,
+        beads: usize,
         group_range: Range<usize>,
     }
 
     impl<const N: usize, T> DistinguishableExchangePotential<N, T>
     where
-        T: Clone + From<f32> + PartialOrd + Mul<Output = T>,
+        T: Clone + From<f32> + PartialOrd + Mul<Output = T> + Div<Output = T>,
     {
         pub fn new(
             mass: T,
             temperature: T,
             inner_images: usize,
             group_range: Range<usize>,
+        ) -> Self {
+            Self::from_parameters(
+                mass,
+                &RingPolymerParameters::new(temperature, inner_images + 2),
+                group_range,
+            )
+        }
+
+        /// Constructs the springs of the atoms of mass `mass` in `group_range`
+        /// of a ring polymer with `parameters`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `mass` is not positive.
+        pub fn from_parameters(
+            mass: T,
+            parameters: &RingPolymerParameters<T>,
+            group_range: Range<usize>,
         ) -> Self {
             assert!(mass.clone() > 0.0.into(), "the mass must be positive");
+            let mass_prefactor = T::from(0.5) * parameters.spring_prefactor(mass);
+            let temperature = parameters.temperature();
+            Self {
+                potential_prefactor: mass_prefactor.clone() * temperature.clone() * temperature,
+                mass_prefactor,
+                beads: parameters.beads(),
+                group_range,
+            }
+        }
+    }
+
+    impl<const N: usize, T> TemperatureDependent<T> for DistinguishableExchangePotential<N, T>
+    where
+        T: Clone + From<f32> + PartialOrd + Mul<Output = T>,
+    {
+        fn set_temperature(&mut self, temperature: &T) {
             assert!(
                 temperature.clone() > 0.0.into(),
                 "the temperature must be positive"
             );
-            Self {
-                potential_prefactor: T::from(
-                    0.5 * ((inner_images + 2) as f32) * BOLTZMANN_CONSTANT * BOLTZMANN_CONSTANT
-                        / (REDUCED_PLANK_CONSTANT * REDUCED_PLANK_CONSTANT),
-                ) * mass
-                    * temperature.clone()
-                    * temperature,
-                group_range,
-            }
+            self.potential_prefactor =
+                self.mass_prefactor.clone() * temperature.clone() * temperature.clone();
+        }
+    }
+
+    /// The springs stiffen in proportion to the number of images.
+    impl<const N: usize, T> BeadCountDependent for DistinguishableExchangePotential<N, T>
+    where
+        T: Clone + From<f32> + Mul<Output = T> + Div<Output = T>,
+    {
+        fn set_beads(&mut self, beads: usize) {
+            assert!(beads > 0, "a ring polymer must have beads");
+            let factor = T::from(beads as f32) / T::from(self.beads as f32);
+            self.mass_prefactor = self.mass_prefactor.clone() * factor.clone();
+            self.potential_prefactor = self.potential_prefactor.clone() * factor;
+            self.beads = beads;
         }
     }
 
@@ -259,3 +307,371 @@ mod distinguishable {
 }
 
 pub use distinguishable::DistinguishableExchangePotential;
+
+mod bosonic {
+    use lib::{
+        core::{Vector, error::InvalidIndexError, resolution::BeadCountDependent},
+        thermostat::TemperatureDependent,
+    };
+    use num::Float;
+    use rand::{
+        Rng,
+        distr::{Distribution, StandardUniform},
+    };
+
+    use super::RingPolymerParameters;
+
+    /// The images whose springs are affected by the permutations of bosons.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum BoundaryImage {
+        /// The first image.
+        First,
+        /// The last image.
+        Last,
+    }
+
+    /// The permutation-weighted part of the exchange potential of bosons.
+    ///
+    /// Every permutation covers each atom exactly once, so the springs between
+    /// consecutive images contribute the same energy regardless of the permutation.
+    /// Only the springs connecting the last image of an atom to the first image
+    /// of another are weighted, which makes the recursion depend on these two images alone.
+    ///
+    /// The spring energies between the boundary images are cached along with the recursion,
+    /// so that moving a single atom in either of them costs O(N) to update the springs and
+    /// only re-evaluates the recursion for the cycles which may contain the moved atom.
+    ///
+    /// The same recursion, with every cycle of even length counted with a negative sign,
+    /// gives the weight of fermions. It is kept relative to that of the bosons,
+    /// which sample the configurations, as the [`sign`](Self::sign) of the exchange.
+    pub struct BosonicExchangePotential<const N: usize, T, V> {
+        /// The prefactor divided by the square of the temperature.
+        mass_prefactor: T,
+        potential_prefactor: T,
+        beads: usize,
+        boltzmann_constant: T,
+        beta: T,
+        first_image: Vec<V>,
+        last_image: Vec<V>,
+        /// `boundary_energies[l * atoms + m]` is the energy of the spring
+        /// between the last image of atom `l` and the first image of atom `m`.
+        boundary_energies: Vec<T>,
+        /// `chain_energies[l]` is the sum of the energies of the springs between
+        /// the last image of atom `j` and the first image of atom `j + 1` for all `j < l`.
+        chain_energies: Vec<T>,
+        /// `potentials[n]` is the potential of the first `n` atoms.
+        potentials: Vec<T>,
+        /// `signs[n]` is the ratio of the weight of the first `n` atoms as fermions
+        /// to their weight as bosons.
+        signs: Vec<T>,
+    }
+
+    impl<const N: usize, T, V> BosonicExchangePotential<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        pub fn new(
+            mass: T,
+            temperature: T,
+            inner_images: usize,
+            first_image: Vec<V>,
+            last_image: Vec<V>,
+        ) -> Self {
+            Self::from_parameters(
+                mass,
+                &RingPolymerParameters::new(temperature, inner_images + 2),
+                first_image,
+                last_image,
+            )
+        }
+
+        /// Constructs the exchange potential of bosons of mass `mass` with the given
+        /// boundary images in a ring polymer with `parameters`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `mass` is not positive or the boundary images
+        /// contain different numbers of atoms.
+        pub fn from_parameters(
+            mass: T,
+            parameters: &RingPolymerParameters<T>,
+            first_image: Vec<V>,
+            last_image: Vec<V>,
+        ) -> Self {
+            assert!(mass > T::zero(), "the mass must be positive");
+            assert_eq!(
+                first_image.len(),
+                last_image.len(),
+                "the boundary images must contain the same number of atoms"
+            );
+            let atoms = first_image.len();
+            let mass_prefactor = <T as From<f32>>::from(0.5) * parameters.spring_prefactor(mass);
+            let temperature = parameters.temperature();
+            let mut this = Self {
+                mass_prefactor,
+                potential_prefactor: mass_prefactor * temperature * temperature,
+                beads: parameters.beads(),
+                boltzmann_constant: parameters.boltzmann_constant(),
+                beta: parameters.beta(),
+                first_image,
+                last_image,
+                boundary_energies: vec![T::zero(); atoms * atoms],
+                chain_energies: vec![T::zero(); atoms],
+                potentials: vec![T::zero(); atoms + 1],
+                signs: vec![T::one(); atoms + 1],
+            };
+            this.update_all();
+            this
+        }
+
+        /// Returns the number of atoms.
+        pub fn atoms(&self) -> usize {
+            self.first_image.len()
+        }
+
+        /// Returns the positions of the atoms in `image`.
+        pub fn positions(&self, image: BoundaryImage) -> &[V] {
+            match image {
+                BoundaryImage::First => &self.first_image,
+                BoundaryImage::Last => &self.last_image,
+            }
+        }
+
+        /// Returns the permutation-weighted potential of all atoms.
+        pub fn potential(&self) -> T {
+            self.potentials.last().copied().unwrap_or(T::zero())
+        }
+
+        /// Returns the ratio of the weight of all atoms as fermions to their weight as bosons,
+        /// `W_F / W_B`, which lies within [-1, 1].
+        ///
+        /// Observables of fermions are the averages over the configurations of the bosons
+        /// weighted by this sign, divided by the average sign.
+        pub fn sign(&self) -> T {
+            self.signs.last().copied().unwrap_or(T::one())
+        }
+
+        /// Returns the probabilities that the last of the first `atoms` atoms belongs to a cycle
+        /// of each length from 1 to `atoms`, given the positions of the boundary images.
+        ///
+        /// The cycle of length `k` contains the atoms `atoms - k..atoms`.
+        pub fn cycle_length_probabilities(
+            &self,
+            atoms: usize,
+        ) -> Result<Vec<T>, InvalidIndexError> {
+            if atoms == 0 || atoms > self.atoms() {
+                return Err(InvalidIndexError::new(atoms, self.atoms() + 1));
+            }
+            let normalization = <T as From<f32>>::from(atoms as f32);
+            Ok((1..=atoms)
+                .map(|length| {
+                    (self.beta
+                        * (self.potentials[atoms]
+                            - self.cycle_energy(atoms, length)
+                            - self.potentials[atoms - length]))
+                        .exp()
+                        / normalization
+                })
+                .collect())
+        }
+
+        /// Draws a permutation from the distribution weighted by the exchange potential.
+        ///
+        /// The last image of atom `i` is linked to the first image of atom `permutation[i]`.
+        pub fn sample_permutation<R>(&self, rng: &mut R) -> Vec<usize>
+        where
+            R: Rng + ?Sized,
+            StandardUniform: Distribution<T>,
+        {
+            let mut permutation: Vec<usize> = (0..self.atoms()).collect();
+            let mut end = self.atoms();
+            while end > 0 {
+                let threshold: T = StandardUniform.sample(rng);
+                let mut cumulative = T::zero();
+                let mut length = end;
+                for (candidate, probability) in self
+                    .cycle_length_probabilities(end)
+                    .expect("`end` is a valid number of atoms")
+                    .into_iter()
+                    .enumerate()
+                {
+                    cumulative = cumulative + probability;
+                    if threshold < cumulative {
+                        length = candidate + 1;
+                        break;
+                    }
+                }
+                let start = end - length;
+                permutation[start..end].rotate_left(1);
+                end = start;
+            }
+            permutation
+        }
+
+        /// Moves `atom` in `image` to `position` and updates the cached recursion.
+        ///
+        /// Returns the change in the potential. A rejected move is undone by
+        /// calling this method again with the old position.
+        pub fn update_after_move(
+            &mut self,
+            image: BoundaryImage,
+            atom: usize,
+            position: V,
+        ) -> Result<T, InvalidIndexError> {
+            let atoms = self.atoms();
+            if atom >= atoms {
+                return Err(InvalidIndexError::new(atom, atoms));
+            }
+            let old_potential = self.potential();
+            match image {
+                BoundaryImage::First => {
+                    self.first_image[atom] = position;
+                    for last in 0..atoms {
+                        self.update_boundary_energy(last, atom);
+                    }
+                    self.update_chain_energies(atom);
+                }
+                BoundaryImage::Last => {
+                    self.last_image[atom] = position;
+                    for first in 0..atoms {
+                        self.update_boundary_energy(atom, first);
+                    }
+                    self.update_chain_energies(atom + 1);
+                }
+            }
+            // The potentials of fewer than `atom + 1` atoms do not involve the moved atom.
+            self.update_potentials(atom + 1);
+            Ok(self.potential() - old_potential)
+        }
+
+        /// Replaces both boundary images, e.g. after the images have been resampled,
+        /// and recalculates the recursion.
+        ///
+        /// # Panics
+        ///
+        /// Panics if either image holds a different number of atoms than the potential.
+        pub fn set_boundary_images(&mut self, first_image: &[V], last_image: &[V]) {
+            assert_eq!(
+                first_image.len(),
+                self.atoms(),
+                "the first image must contain every atom"
+            );
+            assert_eq!(
+                last_image.len(),
+                self.atoms(),
+                "the last image must contain every atom"
+            );
+            self.first_image.clone_from_slice(first_image);
+            self.last_image.clone_from_slice(last_image);
+            self.update_all();
+        }
+
+        /// Recalculates every cached spring energy and the whole recursion.
+        fn update_all(&mut self) {
+            let atoms = self.atoms();
+            for last in 0..atoms {
+                for first in 0..atoms {
+                    self.update_boundary_energy(last, first);
+                }
+            }
+            self.update_chain_energies(0);
+            self.update_potentials(1);
+        }
+
+        fn update_boundary_energy(&mut self, last: usize, first: usize) {
+            let atoms = self.atoms();
+            self.boundary_energies[last * atoms + first] = self.potential_prefactor
+                * (self.first_image[first].clone() - self.last_image[last].clone())
+                    .magnitude_squared();
+        }
+
+        /// Recalculates `chain_energies[l]` for all `l > from`.
+        fn update_chain_energies(&mut self, from: usize) {
+            let atoms = self.atoms();
+            for l in from.max(1)..atoms {
+                self.chain_energies[l] =
+                    self.chain_energies[l - 1] + self.boundary_energies[(l - 1) * atoms + l];
+            }
+        }
+
+        /// Returns the energy of the cycle formed by the atoms `end - length..end`.
+        fn cycle_energy(&self, end: usize, length: usize) -> T {
+            let atoms = self.atoms();
+            let start = end - length;
+            self.chain_energies[end - 1] - self.chain_energies[start]
+                + self.boundary_energies[(end - 1) * atoms + start]
+        }
+
+        /// Recalculates `potentials[n]` and `signs[n]` for all `n >= from`.
+        fn update_potentials(&mut self, from: usize) {
+            for n in from.max(1)..=self.atoms() {
+                let exponents = (1..=n).map(|length| {
+                    -self.beta * (self.cycle_energy(n, length) + self.potentials[n - length])
+                });
+                let max = exponents.clone().fold(T::neg_infinity(), T::max);
+                let sum =
+                    exponents.fold(T::zero(), |accum, exponent| accum + (exponent - max).exp());
+                self.potentials[n] =
+                    -(max + (sum / <T as From<f32>>::from(n as f32)).ln()) / self.beta;
+
+                // `W_F^(n) = Σ_k (-1)^(k - 1) e^(-β E_n^(k)) W_F^(n - k) / n`, divided by
+                // `W_B^(n)`, weighs the signs of the shorter chains by the probabilities
+                // of the cycles, which keeps it within [-1, 1] instead of underflowing.
+                let mut sign = T::zero();
+                for length in 1..=n {
+                    let probability = (self.beta
+                        * (self.potentials[n]
+                            - self.cycle_energy(n, length)
+                            - self.potentials[n - length]))
+                        .exp()
+                        / <T as From<f32>>::from(n as f32);
+                    let term = probability * self.signs[n - length];
+                    sign = if length % 2 == 1 {
+                        sign + term
+                    } else {
+                        sign - term
+                    };
+                }
+                self.signs[n] = sign;
+            }
+        }
+    }
+
+    /// Every cached spring energy and the recursion are recalculated,
+    /// since the springs stiffen and the weights of the permutations change with the temperature.
+    impl<const N: usize, T, V> TemperatureDependent<T> for BosonicExchangePotential<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        fn set_temperature(&mut self, temperature: &T) {
+            let temperature = *temperature;
+            assert!(temperature > T::zero(), "the temperature must be positive");
+            self.potential_prefactor = self.mass_prefactor * temperature * temperature;
+            self.beta = (self.boltzmann_constant * temperature).recip();
+            self.update_all();
+        }
+    }
+
+    /// The springs stiffen in proportion to the number of images.
+    /// The boundary images of the resampled positions are to be set
+    /// with [`BosonicExchangePotential::set_boundary_images`].
+    impl<const N: usize, T, V> BeadCountDependent for BosonicExchangePotential<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        fn set_beads(&mut self, beads: usize) {
+            assert!(beads > 0, "a ring polymer must have beads");
+            let factor =
+                <T as From<f32>>::from(beads as f32) / <T as From<f32>>::from(self.beads as f32);
+            self.mass_prefactor = self.mass_prefactor * factor;
+            self.potential_prefactor = self.potential_prefactor * factor;
+            self.beads = beads;
+            self.update_all();
+        }
+    }
+}
+
+pub use bosonic::{BosonicExchangePotential, BoundaryImage};
@@ -0,0 +1,431 @@
+mod rules {
+    /// A pattern matching the name of an atom type in a rule.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum TypePattern {
+        /// Matches every type; written as `*` or `X`.
+        Any,
+        /// Matches the type with this name only.
+        Exact(String),
+    }
+
+    impl TypePattern {
+        pub fn matches(&self, name: &str) -> bool {
+            match self {
+                Self::Any => true,
+                Self::Exact(exact) => exact == name,
+            }
+        }
+    }
+
+    impl From<&str> for TypePattern {
+        fn from(value: &str) -> Self {
+            match value {
+                "*" | "X" => Self::Any,
+                name => Self::Exact(name.to_owned()),
+            }
+        }
+    }
+
+    /// A table assigning parameters to runs of `K` atom types, e.g. the types
+    /// of the atoms of a bond for `K = 2` or of a dihedral for `K = 4`.
+    ///
+    /// A rule matches the types in either order, since a run of atoms is the same term
+    /// when read backwards. When several rules match, the one with the fewest wildcards
+    /// wins and ties go to the rule added first, so generic rules can be refined by specific ones.
+    #[derive(Clone, Debug)]
+    pub struct RuleTable<const K: usize, P> {
+        rules: Vec<([TypePattern; K], P)>,
+    }
+
+    impl<const K: usize, P> Default for RuleTable<K, P> {
+        fn default() -> Self {
+            Self { rules: Vec::new() }
+        }
+    }
+
+    impl<const K: usize, P> RuleTable<K, P> {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Adds a rule assigning `parameters` to the runs of types matching `patterns`.
+        pub fn add(&mut self, patterns: [&str; K], parameters: P) -> &mut Self {
+            self.rules
+                .push((patterns.map(TypePattern::from), parameters));
+            self
+        }
+
+        pub fn len(&self) -> usize {
+            self.rules.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.rules.is_empty()
+        }
+
+        /// Returns the parameters of the most specific rule matching `types`, if any.
+        pub fn lookup(&self, types: [&str; K]) -> Option<&P> {
+            let mut best: Option<(usize, &P)> = None;
+            for (patterns, parameters) in &self.rules {
+                let forward = patterns
+                    .iter()
+                    .zip(types)
+                    .all(|(pattern, name)| pattern.matches(name));
+                let backward = patterns
+                    .iter()
+                    .zip(types.iter().rev())
+                    .all(|(pattern, name)| pattern.matches(name));
+                if !(forward || backward) {
+                    continue;
+                }
+                let wildcards = patterns
+                    .iter()
+                    .filter(|pattern| **pattern == TypePattern::Any)
+                    .count();
+                if best.is_none_or(|(fewest, _)| wildcards < fewest) {
+                    best = Some((wildcards, parameters));
+                }
+            }
+            best.map(|(_, parameters)| parameters)
+        }
+    }
+}
+
+pub use rules::{RuleTable, TypePattern};
+
+mod terms {
+    use num::Float;
+
+    /// The harmonic potential of a bond angle, `k (θ - θ_eq)² / 2`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Bend<T> {
+        pub angle: T,
+        pub force_constant: T,
+    }
+
+    impl<T: Float + From<f32>> Bend<T> {
+        /// Returns the energy at the angle `angle` and its derivative with respect to it.
+        pub fn energy(&self, angle: T) -> (T, T) {
+            let deviation = angle - self.angle;
+            (
+                <T as From<f32>>::from(0.5) * self.force_constant * deviation * deviation,
+                self.force_constant * deviation,
+            )
+        }
+    }
+
+    /// The periodic potential of a dihedral angle, `k (1 + cos(n φ - δ))`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Torsion<T> {
+        pub force_constant: T,
+        pub multiplicity: u8,
+        pub phase: T,
+    }
+
+    impl<T: Float + From<f32>> Torsion<T> {
+        /// Returns the energy at the dihedral angle `dihedral` and its derivative with respect to it.
+        pub fn energy(&self, dihedral: T) -> (T, T) {
+            let multiplicity = <T as From<f32>>::from(self.multiplicity as f32);
+            let argument = multiplicity * dihedral - self.phase;
+            (
+                self.force_constant * (T::one() + argument.cos()),
+                -self.force_constant * multiplicity * argument.sin(),
+            )
+        }
+    }
+
+    /// The nonbonded parameters of an atom type.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct AtomTypeParameters<T> {
+        pub charge: T,
+        pub lennard_jones: crate::potential::pair::PairParameters<T>,
+    }
+}
+
+pub use terms::{AtomTypeParameters, Bend, Torsion};
+
+mod topology {
+    use lib::core::error::InvalidIndexError;
+
+    use crate::util::{ArrayVec, Overflow};
+
+    /// The number of bonds of an atom stored inline, which covers every
+    /// common valence, beyond which the bonded neighbors spill to the heap.
+    const VALENCE: usize = 6;
+
+    /// The bonded terms of a system as runs of atom indices.
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct BondedTopology {
+        pub bonds: Vec<[usize; 2]>,
+        pub angles: Vec<[usize; 3]>,
+        pub dihedrals: Vec<[usize; 4]>,
+    }
+
+    impl BondedTopology {
+        /// Constructs the topology of `atoms` atoms connected by `bonds`, with every angle
+        /// and proper dihedral spanned by the bonds, as imported topologies often list bonds only.
+        pub fn from_bonds(atoms: usize, bonds: &[[usize; 2]]) -> Result<Self, InvalidIndexError> {
+            let mut neighbors =
+                vec![ArrayVec::<usize, VALENCE>::with_overflow(Overflow::Spill); atoms];
+            for &[first, second] in bonds {
+                for atom in [first, second] {
+                    if atom >= atoms {
+                        return Err(InvalidIndexError::new(atom, atoms));
+                    }
+                }
+                neighbors[first].push(second);
+                neighbors[second].push(first);
+            }
+            let mut angles = Vec::new();
+            for (center, bonded) in neighbors.iter().enumerate() {
+                for (index, &first) in bonded.iter().enumerate() {
+                    for &last in &bonded[index + 1..] {
+                        angles.push([first, center, last]);
+                    }
+                }
+            }
+            let mut dihedrals = Vec::new();
+            for &[second, third] in bonds {
+                for &first in neighbors[second].iter().filter(|&&atom| atom != third) {
+                    for &fourth in neighbors[third]
+                        .iter()
+                        .filter(|&&atom| atom != second && atom != first)
+                    {
+                        dihedrals.push([first, second, third, fourth]);
+                    }
+                }
+            }
+            Ok(Self {
+                bonds: bonds.to_vec(),
+                angles,
+                dihedrals,
+            })
+        }
+    }
+}
+
+pub use topology::BondedTopology;
+
+mod assignment {
+    use std::{
+        collections::HashMap,
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+    };
+
+    use lib::core::error::InvalidIndexError;
+    use num::Float;
+
+    use super::{AtomTypeParameters, Bend, BondedTopology, RuleTable, Torsion};
+    use crate::potential::{
+        pair::{Exclusions, InteractionMatrix, MixingRule, PairParameters},
+        water::Stretch,
+    };
+
+    /// An error in assigning the parameters of a force field to a topology.
+    #[derive(Clone, Debug)]
+    pub enum ForceFieldError {
+        /// A bonded term refers to an atom outside of the topology.
+        InvalidIndex(InvalidIndexError),
+        /// The type of an atom is not defined by the force field.
+        UnknownAtomType { atom: usize, name: String },
+        /// No rule matches the types of the atoms of a bonded term.
+        MissingTerm {
+            /// The kind of the term, e.g. `"bond"`.
+            term: &'static str,
+            atoms: Vec<usize>,
+            types: Vec<String>,
+        },
+    }
+
+    impl From<InvalidIndexError> for ForceFieldError {
+        fn from(value: InvalidIndexError) -> Self {
+            Self::InvalidIndex(value)
+        }
+    }
+
+    impl Display for ForceFieldError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::InvalidIndex(err) => write!(f, "invalid topology: {}", err),
+                Self::UnknownAtomType { atom, name } => {
+                    write!(f, "atom #{} has the unknown type `{}`", atom, name)
+                }
+                Self::MissingTerm { term, atoms, types } => write!(
+                    f,
+                    "no {} parameters for atoms {:?} of types {}",
+                    term,
+                    atoms,
+                    types.join("-")
+                ),
+            }
+        }
+    }
+
+    impl Error for ForceFieldError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::InvalidIndex(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    /// Lookup tables of parameters by atom type.
+    ///
+    /// The Lennard-Jones parameters of a pair are mixed from those of the two types
+    /// unless a rule of `pairs` overrides them.
+    #[derive(Clone, Debug)]
+    pub struct ForceField<T> {
+        pub atom_types: HashMap<String, AtomTypeParameters<T>>,
+        pub mixing: MixingRule,
+        pub pairs: RuleTable<2, PairParameters<T>>,
+        pub bonds: RuleTable<2, Stretch<T>>,
+        pub angles: RuleTable<3, Bend<T>>,
+        pub dihedrals: RuleTable<4, Torsion<T>>,
+    }
+
+    /// The parameters of every term of a topology.
+    #[derive(Clone, Debug)]
+    pub struct Parameterization<T> {
+        /// The distinct atom types in the order of their first appearance.
+        pub types: Vec<String>,
+        /// The index into `types` of the type of every atom, i.e. its group in `interactions`.
+        pub type_indices: Vec<usize>,
+        pub charges: Vec<T>,
+        pub interactions: InteractionMatrix<T>,
+        pub bonds: Vec<([usize; 2], Stretch<T>)>,
+        pub angles: Vec<([usize; 3], Bend<T>)>,
+        pub dihedrals: Vec<([usize; 4], Torsion<T>)>,
+        /// The pairs separated by one or two bonds, which interact through the bonded terms only.
+        pub exclusions: Exclusions,
+    }
+
+    impl<T: Float + From<f32>> ForceField<T> {
+        /// Constructs a force field without any types or rules.
+        pub fn new(mixing: MixingRule) -> Self {
+            Self {
+                atom_types: HashMap::new(),
+                mixing,
+                pairs: RuleTable::new(),
+                bonds: RuleTable::new(),
+                angles: RuleTable::new(),
+                dihedrals: RuleTable::new(),
+            }
+        }
+
+        /// Defines the nonbonded parameters of the type `name`, replacing any previous definition.
+        pub fn define_atom_type(
+            &mut self,
+            name: impl Into<String>,
+            parameters: AtomTypeParameters<T>,
+        ) -> &mut Self {
+            self.atom_types.insert(name.into(), parameters);
+            self
+        }
+
+        /// Assigns parameters to every atom and bonded term of a topology
+        /// whose atoms have the types `atom_types`.
+        ///
+        /// Every bonded term must be matched by a rule; a missing rule is an error
+        /// rather than a silently absent interaction.
+        pub fn parameterize<S: AsRef<str>>(
+            &self,
+            atom_types: &[S],
+            topology: &BondedTopology,
+        ) -> Result<Parameterization<T>, ForceFieldError> {
+            let atoms = atom_types.len();
+            let mut types: Vec<String> = Vec::new();
+            let mut type_parameters = Vec::new();
+            let mut type_indices = Vec::with_capacity(atoms);
+            let mut charges = Vec::with_capacity(atoms);
+            for (atom, name) in atom_types.iter().enumerate() {
+                let name = name.as_ref();
+                let parameters =
+                    self.atom_types
+                        .get(name)
+                        .ok_or_else(|| ForceFieldError::UnknownAtomType {
+                            atom,
+                            name: name.to_owned(),
+                        })?;
+                let index = match types.iter().position(|known| known == name) {
+                    Some(index) => index,
+                    None => {
+                        types.push(name.to_owned());
+                        type_parameters.push(parameters.lennard_jones);
+                        types.len() - 1
+                    }
+                };
+                type_indices.push(index);
+                charges.push(parameters.charge);
+            }
+
+            let mut interactions = InteractionMatrix::from_groups(&type_parameters, self.mixing);
+            for (first, first_name) in types.iter().enumerate() {
+                for (second, second_name) in types.iter().enumerate().skip(first) {
+                    if let Some(parameters) = self
+                        .pairs
+                        .lookup([first_name.as_str(), second_name.as_str()])
+                    {
+                        interactions.set(first, second, *parameters)?;
+                    }
+                }
+            }
+
+            let bonds = assign(&self.bonds, "bond", atom_types, &topology.bonds)?;
+            let angles = assign(&self.angles, "angle", atom_types, &topology.angles)?;
+            let dihedrals = assign(&self.dihedrals, "dihedral", atom_types, &topology.dihedrals)?;
+
+            let mut exclusions = Exclusions::new(atoms);
+            for &[first, second] in &topology.bonds {
+                exclusions.exclude(first, second)?;
+            }
+            for &[first, _, last] in &topology.angles {
+                exclusions.exclude(first, last)?;
+            }
+
+            Ok(Parameterization {
+                types,
+                type_indices,
+                charges,
+                interactions,
+                bonds,
+                angles,
+                dihedrals,
+                exclusions,
+            })
+        }
+    }
+
+    /// Looks up the parameters of every run of atoms in `terms`.
+    fn assign<const K: usize, P: Clone, S: AsRef<str>>(
+        table: &RuleTable<K, P>,
+        term: &'static str,
+        atom_types: &[S],
+        terms: &[[usize; K]],
+    ) -> Result<Vec<([usize; K], P)>, ForceFieldError> {
+        terms
+            .iter()
+            .map(|&atoms| {
+                let mut types = [""; K];
+                for (name, &atom) in types.iter_mut().zip(&atoms) {
+                    *name = atom_types
+                        .get(atom)
+                        .ok_or(InvalidIndexError::new(atom, atom_types.len()))?
+                        .as_ref();
+                }
+                let parameters =
+                    table
+                        .lookup(types)
+                        .ok_or_else(|| ForceFieldError::MissingTerm {
+                            term,
+                            atoms: atoms.to_vec(),
+                            types: types.map(str::to_owned).to_vec(),
+                        })?;
+                Ok((atoms, parameters.clone()))
+            })
+            .collect()
+    }
+}
+
+pub use assignment::{ForceField, ForceFieldError, Parameterization};
@@ -0,0 +1,103 @@
+mod gaussian {
+    use std::convert::Infallible;
+
+    use lib::{
+        core::Vector,
+        potential::{
+            GroupInTypeInImage,
+            ground_state::{LocalEnergy, TrialWavefunction},
+        },
+    };
+    use num::Float;
+
+    use crate::core::constants::REDUCED_PLANK_CONSTANT;
+
+    /// The ground state `exp(-mω r² / 2ħ)` of a harmonic oscillator of frequency `ω`,
+    /// used as the trial wavefunction of every atom in a group.
+    ///
+    /// The local energy assumes the physical potential is harmonic
+    /// with frequency `potential_frequency`, and is exact when it equals `ω`.
+    pub struct GaussianTrialWavefunction<const N: usize, T> {
+        mass: T,
+        frequency: T,
+        potential_frequency: T,
+    }
+
+    impl<const N: usize, T: Float> GaussianTrialWavefunction<N, T> {
+        pub fn new(mass: T, frequency: T, potential_frequency: T) -> Self {
+            assert!(mass > T::zero(), "the mass must be positive");
+            assert!(frequency > T::zero(), "the frequency must be positive");
+            assert!(
+                potential_frequency >= T::zero(),
+                "the frequency of the potential must be non-negative"
+            );
+            Self {
+                mass,
+                frequency,
+                potential_frequency,
+            }
+        }
+    }
+
+    impl<const N: usize, T: Float + From<f32>> GaussianTrialWavefunction<N, T> {
+        /// Returns `mω / ħ`.
+        fn inverse_width(&self) -> T {
+            self.mass * self.frequency / <T as From<f32>>::from(REDUCED_PLANK_CONSTANT)
+        }
+    }
+
+    impl<const N: usize, T, V> TrialWavefunction<T, V> for GaussianTrialWavefunction<N, T>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        type Error = Infallible;
+
+        fn calculate_log_set_gradients(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            gradients: &mut [V],
+        ) -> Result<T, Self::Error> {
+            let inverse_width = self.inverse_width();
+            let mut squared = T::zero();
+            for (gradient, position) in gradients.iter_mut().zip(positions.read()) {
+                squared = squared + position.clone().magnitude_squared();
+                *gradient = -position.clone() * inverse_width;
+            }
+            Ok(-inverse_width * squared / <T as From<f32>>::from(2.0))
+        }
+
+        fn calculate_log(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+            let squared = positions.read().iter().fold(T::zero(), |sum, position| {
+                sum + position.clone().magnitude_squared()
+            });
+            Ok(-self.inverse_width() * squared / <T as From<f32>>::from(2.0))
+        }
+    }
+
+    impl<const N: usize, T, V> LocalEnergy<T, V> for GaussianTrialWavefunction<N, T>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        fn local_energy(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+            let positions = positions.read();
+            let squared = positions.iter().fold(T::zero(), |sum, position| {
+                sum + position.clone().magnitude_squared()
+            });
+            let half = <T as From<f32>>::from(0.5);
+            let zero_point = half
+                * <T as From<f32>>::from(REDUCED_PLANK_CONSTANT)
+                * self.frequency
+                * <T as From<f32>>::from((N * positions.len()) as f32);
+            Ok(zero_point
+                + half
+                    * self.mass
+                    * (self.potential_frequency * self.potential_frequency
+                        - self.frequency * self.frequency)
+                    * squared)
+        }
+    }
+}
+
+pub use gaussian::GaussianTrialWavefunction;
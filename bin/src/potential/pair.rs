@@ -0,0 +1,887 @@
+mod interaction {
+    use lib::core::error::InvalidIndexError;
+    use num::Float;
+
+    /// Lennard-Jones parameters of a pair of groups.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct PairParameters<T> {
+        pub sigma: T,
+        pub epsilon: T,
+    }
+
+    impl<T: Float + From<f32>> PairParameters<T> {
+        pub fn new(sigma: T, epsilon: T) -> Self {
+            assert!(sigma > T::zero(), "sigma must be positive");
+            assert!(epsilon >= T::zero(), "epsilon must be non-negative");
+            Self { sigma, epsilon }
+        }
+
+        /// Returns the Lennard-Jones energy of a pair at `distance_squared`
+        /// and the magnitude of the force divided by the distance.
+        ///
+        /// The force on the first atom is the returned factor times
+        /// the displacement from the second atom to the first.
+        pub fn lennard_jones(&self, distance_squared: T) -> (T, T) {
+            let sigma_squared = self.sigma * self.sigma / distance_squared;
+            let sigma_6 = sigma_squared * sigma_squared * sigma_squared;
+            let sigma_12 = sigma_6 * sigma_6;
+            let four_epsilon = <T as From<f32>>::from(4.0) * self.epsilon;
+            (
+                four_epsilon * (sigma_12 - sigma_6),
+                four_epsilon
+                    * (<T as From<f32>>::from(12.0) * sigma_12
+                        - <T as From<f32>>::from(6.0) * sigma_6)
+                    / distance_squared,
+            )
+        }
+    }
+
+    /// A rule combining the parameters of two groups into the parameters of their pair.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum MixingRule {
+        /// Arithmetic mean of sigmas and geometric mean of epsilons.
+        LorentzBerthelot,
+        /// Geometric means of both sigmas and epsilons.
+        Geometric,
+    }
+
+    impl MixingRule {
+        pub fn mix<T: Float + From<f32>>(
+            self,
+            first: &PairParameters<T>,
+            second: &PairParameters<T>,
+        ) -> PairParameters<T> {
+            let sigma = match self {
+                Self::LorentzBerthelot => {
+                    (first.sigma + second.sigma) / <T as From<f32>>::from(2.0)
+                }
+                Self::Geometric => (first.sigma * second.sigma).sqrt(),
+            };
+            PairParameters {
+                sigma,
+                epsilon: (first.epsilon * second.epsilon).sqrt(),
+            }
+        }
+    }
+
+    /// Parameters of every pair of groups.
+    ///
+    /// The matrix is symmetric: setting the parameters of a pair sets them for both orders.
+    #[derive(Clone, Debug)]
+    pub struct InteractionMatrix<T> {
+        groups: usize,
+        parameters: Box<[PairParameters<T>]>,
+    }
+
+    impl<T: Float + From<f32>> InteractionMatrix<T> {
+        /// Constructs the matrix from the parameters of each group
+        /// by mixing them pairwise with `rule`.
+        pub fn from_groups(groups: &[PairParameters<T>], rule: MixingRule) -> Self {
+            Self {
+                groups: groups.len(),
+                parameters: groups
+                    .iter()
+                    .flat_map(|first| groups.iter().map(move |second| rule.mix(first, second)))
+                    .collect(),
+            }
+        }
+
+        pub fn groups(&self) -> usize {
+            self.groups
+        }
+
+        pub fn get(
+            &self,
+            first: usize,
+            second: usize,
+        ) -> Result<&PairParameters<T>, InvalidIndexError> {
+            Ok(&self.parameters[self.index(first, second)?])
+        }
+
+        /// Overrides the mixed parameters of a pair of groups.
+        pub fn set(
+            &mut self,
+            first: usize,
+            second: usize,
+            parameters: PairParameters<T>,
+        ) -> Result<(), InvalidIndexError> {
+            let index = self.index(first, second)?;
+            self.parameters[index] = parameters;
+            let index = self.index(second, first)?;
+            self.parameters[index] = parameters;
+            Ok(())
+        }
+
+        fn index(&self, first: usize, second: usize) -> Result<usize, InvalidIndexError> {
+            if first >= self.groups {
+                Err(InvalidIndexError::new(first, self.groups))
+            } else if second >= self.groups {
+                Err(InvalidIndexError::new(second, self.groups))
+            } else {
+                Ok(first * self.groups + second)
+            }
+        }
+    }
+
+    /// Per-atom lists of atoms which do not interact through pairwise potentials,
+    /// e.g. bonded neighbors.
+    #[derive(Clone, Debug)]
+    pub struct Exclusions {
+        excluded: Box<[Vec<usize>]>,
+    }
+
+    impl Exclusions {
+        /// Constructs lists for `atoms` atoms without any exclusions.
+        pub fn new(atoms: usize) -> Self {
+            Self {
+                excluded: vec![Vec::new(); atoms].into_boxed_slice(),
+            }
+        }
+
+        pub fn atoms(&self) -> usize {
+            self.excluded.len()
+        }
+
+        /// Excludes the interaction between `first` and `second`.
+        ///
+        /// Both indices are checked before either list is changed,
+        /// so a failed call leaves the exclusions as they were.
+        pub fn exclude(&mut self, first: usize, second: usize) -> Result<(), InvalidIndexError> {
+            let atoms = self.atoms();
+            for atom in [first, second] {
+                if atom >= atoms {
+                    return Err(InvalidIndexError::new(atom, atoms));
+                }
+            }
+            for (this, other) in [(first, second), (second, first)] {
+                let list = &mut self.excluded[this];
+                if let Err(position) = list.binary_search(&other) {
+                    list.insert(position, other);
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns the sorted list of atoms excluded from interacting with `atom`.
+        pub fn excluded(&self, atom: usize) -> Result<&[usize], InvalidIndexError> {
+            self.excluded
+                .get(atom)
+                .map(Vec::as_slice)
+                .ok_or(InvalidIndexError::new(atom, self.atoms()))
+        }
+
+        pub fn is_excluded(&self, first: usize, second: usize) -> bool {
+            self.excluded
+                .get(first)
+                .is_some_and(|list| list.binary_search(&second).is_ok())
+        }
+    }
+}
+
+pub use interaction::{Exclusions, InteractionMatrix, MixingRule, PairParameters};
+
+mod neighbor_list {
+    use std::{
+        collections::HashMap,
+        convert::Infallible,
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+    };
+
+    use lib::{
+        core::{
+            Vector,
+            error::{Expect, ExpectationError, InvalidIndexError},
+        },
+        potential::physical::BoxDependent,
+    };
+    use num::Float;
+
+    use super::Exclusions;
+    use crate::{
+        potential::electrostatics::{CellGrid, PeriodicBox},
+        util::{ArrayVec, Overflow},
+    };
+
+    /// The number of neighbors of an atom gathered without allocating,
+    /// enough for dense liquids with a typical skin.
+    const NEIGHBOR_SCRATCH: usize = 128;
+
+    /// Counters of the work done by a [`VerletList`].
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct NeighborListStats {
+        /// The number of calls to [`VerletList::update`].
+        pub updates: usize,
+        /// The number of times the list has been rebuilt.
+        pub builds: usize,
+    }
+
+    impl NeighborListStats {
+        /// Returns the fraction of updates which triggered a rebuild.
+        pub fn rebuild_frequency(&self) -> Option<f64> {
+            (self.updates != 0).then(|| self.builds as f64 / self.updates as f64)
+        }
+    }
+
+    /// An error of a potential which finds its pairs with a [`VerletList`].
+    #[derive(Clone, Copy, Debug)]
+    pub enum NeighborListError {
+        /// The positions are not those of the atoms of the potential.
+        InvalidIndex(InvalidIndexError),
+        /// A position could not be sorted into a cell, e.g. because it is not finite.
+        Expectation(ExpectationError),
+    }
+
+    impl From<Infallible> for NeighborListError {
+        fn from(value: Infallible) -> Self {
+            match value {}
+        }
+    }
+
+    impl From<InvalidIndexError> for NeighborListError {
+        fn from(value: InvalidIndexError) -> Self {
+            Self::InvalidIndex(value)
+        }
+    }
+
+    impl From<ExpectationError> for NeighborListError {
+        fn from(value: ExpectationError) -> Self {
+            Self::Expectation(value)
+        }
+    }
+
+    impl Display for NeighborListError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::InvalidIndex(err) => write!(f, "invalid index: {}", err),
+                Self::Expectation(err) => write!(f, "{}", err),
+            }
+        }
+    }
+
+    impl Error for NeighborListError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::InvalidIndex(err) => Some(err),
+                Self::Expectation(err) => Some(err),
+            }
+        }
+    }
+
+    /// A Verlet neighbor list built with cells and rebuilt only when an atom
+    /// has moved by more than half the skin since the last build.
+    ///
+    /// With a box, distances and displacements follow the minimum image convention
+    /// and the cells wrap around the periodic axes, so atoms may leave the box between builds.
+    pub struct VerletList<const N: usize, T, V> {
+        cutoff: T,
+        skin: T,
+        periodic_box: Option<PeriodicBox<T, N>>,
+        cells: CellGrid<N>,
+        /// Positions at the last build.
+        reference: Vec<V>,
+        /// For each atom, the neighbors with a greater index.
+        neighbors: Vec<Vec<usize>>,
+        stats: NeighborListStats,
+    }
+
+    impl<const N: usize, T, V> VerletList<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        pub fn new(cutoff: T, skin: T) -> Self {
+            assert!(cutoff > T::zero(), "the cutoff must be positive");
+            assert!(skin >= T::zero(), "the skin must be non-negative");
+            Self {
+                cutoff,
+                skin,
+                periodic_box: None,
+                cells: CellGrid::new(),
+                reference: Vec::new(),
+                neighbors: Vec::new(),
+                stats: NeighborListStats::default(),
+            }
+        }
+
+        /// Applies the minimum image convention of `periodic_box`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the cutoff and the skin exceed half of a periodic length of the box.
+        pub fn with_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            self.set_box(periodic_box);
+            self
+        }
+
+        /// Applies the minimum image convention of `periodic_box`, as [`VerletList::with_box`].
+        pub fn set_box(&mut self, periodic_box: PeriodicBox<T, N>) {
+            let range = self.cutoff + self.skin;
+            assert!(
+                periodic_box
+                    .lengths()
+                    .iter()
+                    .enumerate()
+                    .all(|(axis, &length)| !periodic_box.is_periodic(axis)
+                        || range <= <T as From<f32>>::from(0.5) * length),
+                "the cutoff and the skin must not exceed half of a periodic length of the box"
+            );
+            self.periodic_box = Some(periodic_box);
+            // The positions of the last build may be in another box.
+            self.reference.clear();
+        }
+
+        pub fn periodic_box(&self) -> Option<&PeriodicBox<T, N>> {
+            self.periodic_box.as_ref()
+        }
+
+        pub fn stats(&self) -> NeighborListStats {
+            self.stats
+        }
+
+        /// Returns the neighbors of `atom` with a greater index,
+        /// so that every pair is listed once.
+        pub fn neighbors(&self, atom: usize) -> Result<&[usize], InvalidIndexError> {
+            self.neighbors
+                .get(atom)
+                .map(Vec::as_slice)
+                .ok_or(InvalidIndexError::new(atom, self.neighbors.len()))
+        }
+
+        /// Iterates over all pairs of neighbors.
+        pub fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+            self.neighbors
+                .iter()
+                .enumerate()
+                .flat_map(|(atom, neighbors)| neighbors.iter().map(move |&other| (atom, other)))
+        }
+
+        /// Returns the displacement from `second` to `first`, the closest periodic image with a box.
+        fn displacement(&self, first: &V, second: &V) -> V {
+            let displacement = first.clone() - second.clone();
+            match &self.periodic_box {
+                Some(periodic_box) => periodic_box.minimum_image(displacement),
+                None => displacement,
+            }
+        }
+
+        /// Rebuilds the list if the number of atoms has changed or
+        /// any atom has moved by more than half the skin since the last build.
+        ///
+        /// Returns whether the list has been rebuilt, or an error if a position
+        /// cannot be sorted into a cell.
+        pub fn update(
+            &mut self,
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+        ) -> Result<bool, ExpectationError> {
+            self.stats.updates += 1;
+            let half_skin = self.skin / <T as From<f32>>::from(2.0);
+            let needs_rebuild = positions.len() != self.reference.len()
+                || positions
+                    .iter()
+                    .zip(&self.reference)
+                    .any(|(position, reference)| {
+                        self.displacement(position, reference).magnitude_squared()
+                            > half_skin * half_skin
+                    });
+            if needs_rebuild {
+                self.rebuild(positions, exclusions)?;
+            }
+            Ok(needs_rebuild)
+        }
+
+        /// Unconditionally rebuilds the list.
+        ///
+        /// Fails if a position cannot be sorted into a cell, e.g. because it is not finite.
+        pub fn rebuild(
+            &mut self,
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+        ) -> Result<(), ExpectationError> {
+            self.stats.builds += 1;
+            self.reference.clear();
+            self.reference.extend_from_slice(positions);
+            // The lists of the atoms are kept, so that a rebuild reuses their storage.
+            self.neighbors.resize_with(positions.len(), Vec::new);
+            self.neighbors.iter_mut().for_each(Vec::clear);
+
+            let range = self.cutoff + self.skin;
+            let excluded = |atom: usize, other: usize| {
+                exclusions.is_some_and(|exclusions| exclusions.is_excluded(atom, other))
+            };
+            if let Some(periodic_box) = &self.periodic_box {
+                self.cells.build(periodic_box, range, positions)?;
+                let neighbors = &mut self.neighbors;
+                self.cells.for_each_candidate(|atom, other| {
+                    let displacement = periodic_box
+                        .minimum_image(positions[other].clone() - positions[atom].clone());
+                    if !excluded(atom, other) && displacement.magnitude_squared() <= range * range {
+                        neighbors[atom].push(other);
+                    }
+                });
+                self.neighbors
+                    .iter_mut()
+                    .for_each(|list| list.sort_unstable());
+                return Ok(());
+            }
+
+            let cell_of = |position: &V| -> Result<[i64; N], ExpectationError> {
+                let mut cell = [0; N];
+                for (index, &coordinate) in cell.iter_mut().zip(position.as_array()) {
+                    *index = (coordinate / range)
+                        .floor()
+                        .to_i64()
+                        .expect_in_hot_path("positions must be finite")?;
+                }
+                Ok(cell)
+            };
+            let mut cells: HashMap<[i64; N], Vec<usize>> = HashMap::new();
+            for (atom, position) in positions.iter().enumerate() {
+                cells.entry(cell_of(position)?).or_default().push(atom);
+            }
+
+            let offsets = 3usize.pow(N as u32);
+            let mut scratch = ArrayVec::<usize, NEIGHBOR_SCRATCH>::with_overflow(Overflow::Spill);
+            for (atom, position) in positions.iter().enumerate() {
+                scratch.clear();
+                let cell = cell_of(position)?;
+                for offset in 0..offsets {
+                    let mut neighbor_cell = cell;
+                    let mut digits = offset;
+                    for coordinate in &mut neighbor_cell {
+                        *coordinate += (digits % 3) as i64 - 1;
+                        digits /= 3;
+                    }
+                    let Some(candidates) = cells.get(&neighbor_cell) else {
+                        continue;
+                    };
+                    for &other in candidates {
+                        if other <= atom || excluded(atom, other) {
+                            continue;
+                        }
+                        if (positions[other].clone() - position.clone()).magnitude_squared()
+                            <= range * range
+                        {
+                            scratch.push(other);
+                        }
+                    }
+                }
+                scratch.sort_unstable();
+                self.neighbors[atom].extend_from_slice(&scratch);
+            }
+            Ok(())
+        }
+    }
+
+    impl<const N: usize, T: Float, V> BoxDependent<T> for VerletList<N, T, V> {
+        /// Scales the box of the minimum image convention, if any,
+        /// which moves the atoms, so the list is rebuilt on the next update.
+        fn scale_box(&mut self, factors: &[T]) {
+            if let Some(periodic_box) = &mut self.periodic_box {
+                periodic_box.scale(factors);
+                self.reference.clear();
+            }
+        }
+    }
+
+    /// A builder of the angles formed by the pairs of a neighbor list,
+    /// as required by three-body potentials.
+    ///
+    /// Keeps the neighbors of every atom between builds to reuse their storage.
+    #[derive(Clone, Debug, Default)]
+    pub struct TripletBuilder {
+        adjacency: Vec<Vec<usize>>,
+    }
+
+    impl TripletBuilder {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Sets `triplets` to every angle formed by two of `pairs` sharing an atom
+        /// out of `atoms` atoms, the shared atom first and the other two in increasing order.
+        pub fn build(
+            &mut self,
+            atoms: usize,
+            pairs: impl IntoIterator<Item = [usize; 2]>,
+            triplets: &mut Vec<[usize; 3]>,
+        ) -> Result<(), InvalidIndexError> {
+            self.adjacency.resize_with(atoms, Vec::new);
+            self.adjacency.iter_mut().for_each(Vec::clear);
+            for [first, second] in pairs {
+                for atom in [first, second] {
+                    if atom >= atoms {
+                        return Err(InvalidIndexError::new(atom, atoms));
+                    }
+                }
+                self.adjacency[first].push(second);
+                self.adjacency[second].push(first);
+            }
+            triplets.clear();
+            for (vertex, neighbors) in self.adjacency.iter_mut().enumerate() {
+                neighbors.sort_unstable();
+                for (index, &first) in neighbors.iter().enumerate() {
+                    triplets.extend(
+                        neighbors[index + 1..]
+                            .iter()
+                            .map(|&second| [vertex, first, second]),
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use neighbor_list::{NeighborListError, NeighborListStats, TripletBuilder, VerletList};
+
+mod attribution {
+    /// The share of a single pair of atoms in a pairwise potential.
+    #[derive(Clone, Debug)]
+    pub struct PairContribution<T, V> {
+        pub first: usize,
+        pub second: usize,
+        /// The displacement from the second atom to the first, after the minimum image convention.
+        pub displacement: V,
+        /// The energy of the pair.
+        pub energy: T,
+        /// The force exerted by the second atom on the first,
+        /// which is opposite to the force exerted by the first atom on the second.
+        pub force: V,
+    }
+
+    /// A pairwise potential which can attribute its energy and forces to the pairs of atoms,
+    /// as required by per-atom observables such as the stress or the heat flux of every atom.
+    pub trait PairwiseAttribution<T, V> {
+        /// Calls `visit` with the contribution of every interacting pair of atoms
+        /// of a group at `positions`, each pair visited once.
+        fn for_each_pair<F>(&self, positions: &[V], visit: F)
+        where
+            F: FnMut(PairContribution<T, V>);
+    }
+}
+
+pub use attribution::{PairContribution, PairwiseAttribution};
+
+mod lennard_jones {
+    use std::convert::Infallible;
+
+    use lib::{
+        core::Vector,
+        potential::{
+            GroupInTypeInImage,
+            physical::{BoxDependent, PhysicalPotential, Reload},
+        },
+    };
+    use num::Float;
+
+    use super::{PairContribution, PairParameters, PairwiseAttribution};
+    use crate::potential::{
+        builder::{ParameterError, fits_box, non_negative, positive, required},
+        electrostatics::PeriodicBox,
+    };
+
+    /// The Lennard-Jones interaction between the atoms of a group in `N` dimensions,
+    /// truncated and shifted to vanish at the cutoff.
+    pub struct LennardJones<const N: usize, T> {
+        parameters: PairParameters<T>,
+        cutoff: T,
+        shift: T,
+        periodic_box: Option<PeriodicBox<T, N>>,
+    }
+
+    impl<const N: usize, T: Float + From<f32>> LennardJones<N, T> {
+        pub fn new(parameters: PairParameters<T>, cutoff: T) -> Self {
+            assert!(cutoff > T::zero(), "the cutoff must be positive");
+            Self {
+                parameters,
+                cutoff,
+                shift: parameters.lennard_jones(cutoff * cutoff).0,
+                periodic_box: None,
+            }
+        }
+
+        /// Applies the minimum image convention of `periodic_box` to the displacements.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the cutoff exceeds half of a periodic length of the box.
+        pub fn with_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            let half = <T as From<f32>>::from(0.5);
+            assert!(
+                periodic_box
+                    .lengths()
+                    .iter()
+                    .enumerate()
+                    .all(|(axis, &length)| !periodic_box.is_periodic(axis)
+                        || self.cutoff <= half * length),
+                "the cutoff must not exceed half of a periodic length of the box"
+            );
+            self.periodic_box = Some(periodic_box);
+            self
+        }
+
+        pub fn parameters(&self) -> &PairParameters<T> {
+            &self.parameters
+        }
+
+        pub fn cutoff(&self) -> T {
+            self.cutoff
+        }
+
+        /// Returns a builder which validates the parameters instead of panicking,
+        /// e.g. for parameters read from an input file.
+        pub fn builder() -> LennardJonesBuilder<N, T> {
+            LennardJonesBuilder {
+                sigma: None,
+                epsilon: None,
+                cutoff: None,
+                periodic_box: None,
+            }
+        }
+
+        /// Returns the energy of the group and adds the forces to `forces`, if given.
+        pub(crate) fn accumulate<V>(&self, positions: &[V], mut forces: Option<&mut [V]>) -> T
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let mut energy = T::zero();
+            self.for_each_pair(positions, |pair| {
+                energy = energy + pair.energy;
+                if let Some(forces) = forces.as_deref_mut() {
+                    forces[pair.first] += pair.force.clone();
+                    forces[pair.second] -= pair.force;
+                }
+            });
+            energy
+        }
+    }
+
+    /// A builder of [`LennardJones`], obtained with [`LennardJones::builder`].
+    ///
+    /// `sigma`, `epsilon` and the cutoff are required and the box is optional.
+    #[derive(Clone, Copy, Debug)]
+    pub struct LennardJonesBuilder<const N: usize, T> {
+        sigma: Option<T>,
+        epsilon: Option<T>,
+        cutoff: Option<T>,
+        periodic_box: Option<PeriodicBox<T, N>>,
+    }
+
+    impl<const N: usize, T: Float + From<f32>> LennardJonesBuilder<N, T> {
+        pub fn sigma(mut self, sigma: T) -> Self {
+            self.sigma = Some(sigma);
+            self
+        }
+
+        pub fn epsilon(mut self, epsilon: T) -> Self {
+            self.epsilon = Some(epsilon);
+            self
+        }
+
+        /// Sets both `sigma` and `epsilon`, e.g. from an [`InteractionMatrix`](super::InteractionMatrix).
+        pub fn parameters(self, parameters: PairParameters<T>) -> Self {
+            self.sigma(parameters.sigma).epsilon(parameters.epsilon)
+        }
+
+        pub fn cutoff(mut self, cutoff: T) -> Self {
+            self.cutoff = Some(cutoff);
+            self
+        }
+
+        /// Applies the minimum image convention of `periodic_box`; see [`LennardJones::with_box`].
+        pub fn periodic_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            self.periodic_box = Some(periodic_box);
+            self
+        }
+
+        /// Constructs the potential, or returns the first parameter which is missing or invalid.
+        pub fn build(self) -> Result<LennardJones<N, T>, ParameterError<T>> {
+            let sigma = positive(required(self.sigma, "sigma")?, "sigma")?;
+            let epsilon = non_negative(required(self.epsilon, "epsilon")?, "epsilon")?;
+            let cutoff = positive(required(self.cutoff, "cutoff")?, "cutoff")?;
+            let potential = LennardJones::new(PairParameters::new(sigma, epsilon), cutoff);
+            match self.periodic_box {
+                Some(periodic_box) => {
+                    fits_box(cutoff, &periodic_box)?;
+                    Ok(potential.with_box(periodic_box))
+                }
+                None => Ok(potential),
+            }
+        }
+    }
+
+    impl<const N: usize, T, V> PairwiseAttribution<T, V> for LennardJones<N, T>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        fn for_each_pair<F>(&self, positions: &[V], mut visit: F)
+        where
+            F: FnMut(PairContribution<T, V>),
+        {
+            let cutoff_squared = self.cutoff * self.cutoff;
+            for (i, first) in positions.iter().enumerate() {
+                for (j, second) in positions.iter().enumerate().skip(i + 1) {
+                    let mut displacement = first.clone() - second.clone();
+                    if let Some(periodic_box) = &self.periodic_box {
+                        displacement = periodic_box.minimum_image(displacement);
+                    }
+                    let distance_squared = displacement.clone().magnitude_squared();
+                    if distance_squared >= cutoff_squared {
+                        continue;
+                    }
+                    let (pair_energy, force_factor) =
+                        self.parameters.lennard_jones(distance_squared);
+                    visit(PairContribution {
+                        first: i,
+                        second: j,
+                        force: displacement.clone() * force_factor,
+                        displacement,
+                        energy: pair_energy - self.shift,
+                    });
+                }
+            }
+        }
+    }
+
+    impl<const N: usize, T: Float> BoxDependent<T> for LennardJones<N, T> {
+        /// Scales the box of the minimum image convention, if any.
+        ///
+        /// The cutoff is kept, so a box shrunk below twice the cutoff
+        /// along a periodic axis misses some interactions.
+        fn scale_box(&mut self, factors: &[T]) {
+            if let Some(periodic_box) = &mut self.periodic_box {
+                periodic_box.scale(factors);
+            }
+        }
+    }
+
+    impl<const N: usize, T: Float + From<f32>> Reload for LennardJones<N, T> {
+        type Parameters = PairParameters<T>;
+
+        /// Replaces sigma and epsilon, keeping the potential shifted to vanish at the cutoff.
+        fn reload(&mut self, parameters: &PairParameters<T>) {
+            self.parameters = *parameters;
+            self.shift = parameters.lennard_jones(self.cutoff * self.cutoff).0;
+        }
+    }
+
+    impl<const N: usize, T, V> PhysicalPotential<T, V> for LennardJones<N, T>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        type Error = Infallible;
+
+        fn calculate_potential_set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            group_forces.fill_with(V::zero);
+            Ok(self.accumulate(positions.read(), Some(group_forces)))
+        }
+
+        fn calculate_potential_add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            Ok(self.accumulate(positions.read(), Some(group_forces)))
+        }
+
+        fn calculate_potential(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+        ) -> Result<T, Self::Error> {
+            Ok(self.accumulate(positions.read(), None))
+        }
+
+        fn set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            group_forces.fill_with(V::zero);
+            self.accumulate(positions.read(), Some(group_forces));
+            Ok(())
+        }
+
+        fn add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            self.accumulate(positions.read(), Some(group_forces));
+            Ok(())
+        }
+    }
+}
+
+pub use lennard_jones::{LennardJones, LennardJonesBuilder};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{potential::electrostatics::PeriodicBox, vector::ArrayVector};
+    use lib::{core::Vector, potential::physical::BoxDependent};
+
+    #[test]
+    fn periodic_list_follows_the_minimum_image() {
+        let mut state = 7u64;
+        let mut uniform = move || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / 2f64.powi(53)
+        };
+        let (cutoff, skin) = (1.5, 0.4);
+        let periodic_box = PeriodicBox::new([8.0, 9.0, 7.0]);
+        let mut list = VerletList::new(cutoff, skin).with_box(periodic_box);
+        // Some atoms start outside the box and all of them drift across its faces.
+        let mut positions: Vec<_> = (0..250)
+            .map(|_| {
+                ArrayVector::from([8.0, 9.0, 7.0].map(|length| length * (2.0 * uniform() - 0.5)))
+            })
+            .collect();
+        for _ in 0..40 {
+            list.update(&positions, None).unwrap();
+            let mut pairs: Vec<_> = list.pairs().collect();
+            pairs.sort_unstable();
+            for first in 0..positions.len() {
+                for second in first + 1..positions.len() {
+                    let distance = periodic_box
+                        .minimum_image(positions[second] - positions[first])
+                        .magnitude_squared()
+                        .sqrt();
+                    if distance <= cutoff {
+                        assert!(pairs.binary_search(&(first, second)).is_ok());
+                    }
+                }
+            }
+            for position in &mut positions {
+                *position += ArrayVector::from([(); 3].map(|_| 0.1 * uniform() - 0.05));
+            }
+        }
+        let stats = list.stats();
+        assert_eq!(stats.updates, 40);
+        assert!(stats.builds > 1 && stats.builds < 40);
+
+        // Scaling the box moves every atom, so the next update rebuilds the list.
+        list.scale_box(&[1.1, 1.1, 1.1]);
+        assert!(list.update(&positions, None).unwrap());
+    }
+
+    #[test]
+    fn failed_exclusion_changes_no_list() {
+        let mut exclusions = Exclusions::new(3);
+        exclusions.exclude(0, 2).unwrap();
+        assert!(exclusions.exclude(1, 3).is_err());
+        assert!(exclusions.exclude(3, 1).is_err());
+        assert_eq!(exclusions.excluded(0).unwrap(), &[2]);
+        assert!(exclusions.excluded(1).unwrap().is_empty());
+        assert_eq!(exclusions.excluded(2).unwrap(), &[0]);
+    }
+}
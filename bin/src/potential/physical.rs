@@ -8,6 +8,9 @@ mod harmonic {
         core::{Additive, Vector, error::EmptyError},
         potential::physical::AtomAdditivePhysicalPotential,
     };
+    use num::Float;
+
+    use crate::potential::builder::{ParameterError, non_negative, required};
 
     pub struct Harmonic<const N: usize, T> {
         potential_prefactor: T,
@@ -28,6 +31,47 @@ mod harmonic {
         }
     }
 
+    impl<const N: usize, T: Float + From<f32>> Harmonic<N, T> {
+        /// Returns a builder which validates the parameters instead of panicking.
+        pub fn builder() -> HarmonicBuilder<N, T> {
+            HarmonicBuilder {
+                spring_constant: None,
+                inner_images: None,
+            }
+        }
+    }
+
+    /// A builder of [`Harmonic`], obtained with [`Harmonic::builder`].
+    ///
+    /// Both the spring constant and the number of inner images are required.
+    #[derive(Clone, Copy, Debug)]
+    pub struct HarmonicBuilder<const N: usize, T> {
+        spring_constant: Option<T>,
+        inner_images: Option<usize>,
+    }
+
+    impl<const N: usize, T: Float + From<f32>> HarmonicBuilder<N, T> {
+        pub fn spring_constant(mut self, spring_constant: T) -> Self {
+            self.spring_constant = Some(spring_constant);
+            self
+        }
+
+        pub fn inner_images(mut self, inner_images: usize) -> Self {
+            self.inner_images = Some(inner_images);
+            self
+        }
+
+        /// Constructs the potential, or returns the first parameter which is missing or invalid.
+        pub fn build(self) -> Result<Additive<Harmonic<N, T>>, ParameterError<T>> {
+            let spring_constant = non_negative(
+                required(self.spring_constant, "spring constant")?,
+                "spring constant",
+            )?;
+            let inner_images = required(self.inner_images, "number of inner images")?;
+            Ok(Harmonic::new(spring_constant, inner_images))
+        }
+    }
+
     impl<const N: usize, T, V> AtomAdditivePhysicalPotential<T, V> for Harmonic<N, T>
     where
         T: Clone + From<f32> + Add<Output = T> + Mul<Output = T>,
@@ -88,4 +132,485 @@ mod harmonic {
     }
 }
 
-pub use harmonic::Harmonic;
+pub use harmonic::{Harmonic, HarmonicBuilder};
+
+mod wall {
+    use std::convert::Infallible;
+
+    use lib::{
+        core::{Additive, Vector, error::AccessError},
+        potential::physical::AtomAdditivePhysicalPotential,
+    };
+    use num::Float;
+
+    use crate::potential::builder::{ParameterError, non_negative, positive, required};
+
+    /// The geometry of a wall.
+    ///
+    /// Every shape defines the signed distance of a point from the wall,
+    /// which is positive on the allowed side.
+    #[derive(Clone, Copy, Debug)]
+    pub enum WallShape<T, V> {
+        /// The plane of points `x` satisfying `normal · x = offset`.
+        /// The allowed side is the one `normal` points into.
+        Planar { normal: V, offset: T },
+        /// A spherical cavity confining the atoms.
+        Spherical { center: V, radius: T },
+        /// A cylindrical pore of infinite length confining the atoms.
+        Cylindrical {
+            axis_point: V,
+            axis_direction: V,
+            radius: T,
+        },
+    }
+
+    impl<T, V> WallShape<T, V>
+    where
+        T: Float,
+    {
+        /// Returns the shape with the normal of a plane and the axis of a cylinder
+        /// scaled to unit length, or `None` if either is zero.
+        fn normalized<const N: usize>(self) -> Option<Self>
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let unit = |vector: V| {
+                let length = vector.clone().magnitude_squared().sqrt();
+                (length > T::zero()).then(|| (vector / length, length))
+            };
+            Some(match self {
+                Self::Planar { normal, offset } => {
+                    let (normal, length) = unit(normal)?;
+                    // The plane stays in place as both sides of its equation are scaled.
+                    Self::Planar {
+                        normal,
+                        offset: offset / length,
+                    }
+                }
+                Self::Spherical { .. } => self,
+                Self::Cylindrical {
+                    axis_point,
+                    axis_direction,
+                    radius,
+                } => Self::Cylindrical {
+                    axis_point,
+                    axis_direction: unit(axis_direction)?.0,
+                    radius,
+                },
+            })
+        }
+
+        /// Returns the signed distance of `position` from the wall along with
+        /// the unit vector pointing into the allowed side.
+        ///
+        /// On the center of a sphere or the axis of a cylinder every direction
+        /// is equally far from the wall, so the returned vector is zero there.
+        fn distance_and_normal<const N: usize>(&self, position: &V) -> (T, V)
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let inward = |radial: V, radial_distance: T| {
+                if radial_distance > T::zero() {
+                    -radial / radial_distance
+                } else {
+                    V::zero()
+                }
+            };
+            match self {
+                Self::Planar { normal, offset } => (
+                    normal.clone().dot(position.clone()) - *offset,
+                    normal.clone(),
+                ),
+                Self::Spherical { center, radius } => {
+                    let radial = position.clone() - center.clone();
+                    let radial_distance = radial.clone().magnitude_squared().sqrt();
+                    (*radius - radial_distance, inward(radial, radial_distance))
+                }
+                Self::Cylindrical {
+                    axis_point,
+                    axis_direction,
+                    radius,
+                } => {
+                    let relative = position.clone() - axis_point.clone();
+                    let radial = relative.clone()
+                        - axis_direction.clone() * axis_direction.clone().dot(relative);
+                    let radial_distance = radial.clone().magnitude_squared().sqrt();
+                    (*radius - radial_distance, inward(radial, radial_distance))
+                }
+            }
+        }
+    }
+
+    /// A soft harmonic wall.
+    ///
+    /// Atoms closer to the wall than `range` feel a potential
+    /// `stiffness * (range - distance)^2 / 2` pushing them away from it.
+    pub struct Wall<const N: usize, T, V> {
+        shape: WallShape<T, V>,
+        stiffness: T,
+        range: T,
+    }
+
+    impl<const N: usize, T, V> Wall<N, T, V>
+    where
+        T: Float,
+    {
+        /// Constructs a wall of `shape`, whose normal or axis is scaled to unit length.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the stiffness or the range is negative
+        /// or the normal of a plane or the axis of a cylinder is zero.
+        pub fn new(shape: WallShape<T, V>, stiffness: T, range: T) -> Additive<Self>
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            assert!(stiffness >= T::zero(), "the stiffness must be non-negative");
+            assert!(range >= T::zero(), "the range must be non-negative");
+            Additive::new(Self {
+                shape: shape
+                    .normalized()
+                    .expect("the normal or the axis of a wall must not be zero"),
+                stiffness,
+                range,
+            })
+        }
+
+        pub fn shape(&self) -> &WallShape<T, V> {
+            &self.shape
+        }
+
+        /// Returns a builder of a wall of `shape` which validates the parameters
+        /// instead of panicking.
+        pub fn builder(shape: WallShape<T, V>) -> WallBuilder<N, T, V> {
+            WallBuilder {
+                shape,
+                stiffness: None,
+                range: None,
+            }
+        }
+    }
+
+    /// A builder of [`Wall`], obtained with [`Wall::builder`].
+    ///
+    /// Both the stiffness and the range are required.
+    #[derive(Clone, Copy, Debug)]
+    pub struct WallBuilder<const N: usize, T, V> {
+        shape: WallShape<T, V>,
+        stiffness: Option<T>,
+        range: Option<T>,
+    }
+
+    impl<const N: usize, T: Float, V> WallBuilder<N, T, V> {
+        pub fn stiffness(mut self, stiffness: T) -> Self {
+            self.stiffness = Some(stiffness);
+            self
+        }
+
+        pub fn range(mut self, range: T) -> Self {
+            self.range = Some(range);
+            self
+        }
+
+        /// Constructs the wall, or returns the first parameter which is missing or invalid.
+        pub fn build(self) -> Result<Additive<Wall<N, T, V>>, ParameterError<T>>
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            match &self.shape {
+                WallShape::Planar { normal, .. } => {
+                    positive(
+                        normal.clone().magnitude_squared(),
+                        "magnitude of the normal",
+                    )?;
+                }
+                WallShape::Spherical { radius, .. } => {
+                    positive(*radius, "radius")?;
+                }
+                WallShape::Cylindrical {
+                    axis_direction,
+                    radius,
+                    ..
+                } => {
+                    positive(
+                        axis_direction.clone().magnitude_squared(),
+                        "magnitude of the axis",
+                    )?;
+                    positive(*radius, "radius")?;
+                }
+            }
+            let stiffness = non_negative(required(self.stiffness, "stiffness")?, "stiffness")?;
+            let range = non_negative(required(self.range, "range")?, "range")?;
+            Ok(Wall::new(self.shape, stiffness, range))
+        }
+    }
+
+    impl<const N: usize, T, V> AtomAdditivePhysicalPotential<T, V> for Wall<N, T, V>
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+    {
+        type ErrorAtom = Infallible;
+        type ErrorSystem = AccessError;
+
+        fn calculate_potential_set_force(
+            &mut self,
+            _atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<T, Self::ErrorAtom> {
+            let (distance, normal) = self.shape.distance_and_normal(position);
+            let penetration = (self.range - distance).max(T::zero());
+            *force = normal * (self.stiffness * penetration);
+            Ok(self.stiffness * penetration * penetration / (T::one() + T::one()))
+        }
+
+        fn calculate_potential_add_force(
+            &mut self,
+            _atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<T, Self::ErrorAtom> {
+            let (distance, normal) = self.shape.distance_and_normal(position);
+            let penetration = (self.range - distance).max(T::zero());
+            force.mul_add_assign(&normal, self.stiffness * penetration);
+            Ok(self.stiffness * penetration * penetration / (T::one() + T::one()))
+        }
+
+        fn calculate_potential(
+            &mut self,
+            _atom_index: usize,
+            position: &V,
+        ) -> Result<T, Self::ErrorAtom> {
+            let (distance, _) = self.shape.distance_and_normal(position);
+            let penetration = (self.range - distance).max(T::zero());
+            Ok(self.stiffness * penetration * penetration / (T::one() + T::one()))
+        }
+
+        fn set_force(
+            &mut self,
+            _atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<(), Self::ErrorAtom> {
+            let (distance, normal) = self.shape.distance_and_normal(position);
+            *force = normal * (self.stiffness * (self.range - distance).max(T::zero()));
+            Ok(())
+        }
+
+        fn add_force(
+            &mut self,
+            _atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<(), Self::ErrorAtom> {
+            let (distance, normal) = self.shape.distance_and_normal(position);
+            force.mul_add_assign(
+                &normal,
+                self.stiffness * (self.range - distance).max(T::zero()),
+            );
+            Ok(())
+        }
+    }
+}
+
+pub use wall::{Wall, WallBuilder, WallShape};
+
+mod com_tether {
+    use std::convert::Infallible;
+
+    use lib::{
+        core::Vector,
+        potential::{GroupInTypeInImage, physical::PhysicalPotential},
+    };
+    use num::Float;
+
+    use crate::potential::builder::{ParameterError, non_negative, required};
+
+    /// A harmonic restraint tethering the center of mass of a group to an anchor.
+    ///
+    /// Since all atoms in a group are of the same type, the center of mass
+    /// coincides with the mean position. The restraint is an artificial bias,
+    /// so its energy is excluded from the reported physical potential energy
+    /// unless requested otherwise with [`ComTether::reporting_energy`].
+    pub struct ComTether<const N: usize, T, V> {
+        anchor: V,
+        spring_constant: T,
+        report_energy: bool,
+    }
+
+    impl<const N: usize, T, V> ComTether<N, T, V>
+    where
+        T: Float,
+    {
+        pub fn new(anchor: V, spring_constant: T) -> Self {
+            assert!(
+                spring_constant >= T::zero(),
+                "spring constant must be non-negative"
+            );
+            Self {
+                anchor,
+                spring_constant,
+                report_energy: false,
+            }
+        }
+
+        /// Includes the energy of the restraint in the reported physical potential energy.
+        pub fn reporting_energy(mut self) -> Self {
+            self.report_energy = true;
+            self
+        }
+
+        pub fn anchor(&self) -> &V {
+            &self.anchor
+        }
+
+        pub fn set_anchor(&mut self, anchor: V) {
+            self.anchor = anchor;
+        }
+
+        /// Returns a builder of a tether to `anchor` which validates the parameters
+        /// instead of panicking.
+        pub fn builder(anchor: V) -> ComTetherBuilder<N, T, V> {
+            ComTetherBuilder {
+                anchor,
+                spring_constant: None,
+                report_energy: false,
+            }
+        }
+    }
+
+    /// A builder of [`ComTether`], obtained with [`ComTether::builder`].
+    ///
+    /// The spring constant is required.
+    #[derive(Clone, Copy, Debug)]
+    pub struct ComTetherBuilder<const N: usize, T, V> {
+        anchor: V,
+        spring_constant: Option<T>,
+        report_energy: bool,
+    }
+
+    impl<const N: usize, T: Float, V> ComTetherBuilder<N, T, V> {
+        pub fn spring_constant(mut self, spring_constant: T) -> Self {
+            self.spring_constant = Some(spring_constant);
+            self
+        }
+
+        /// Includes the energy of the restraint in the reported physical potential energy.
+        pub fn reporting_energy(mut self) -> Self {
+            self.report_energy = true;
+            self
+        }
+
+        /// Constructs the tether, or returns the first parameter which is missing or invalid.
+        pub fn build(self) -> Result<ComTether<N, T, V>, ParameterError<T>> {
+            let spring_constant = non_negative(
+                required(self.spring_constant, "spring constant")?,
+                "spring constant",
+            )?;
+            let tether = ComTether::new(self.anchor, spring_constant);
+            Ok(if self.report_energy {
+                tether.reporting_energy()
+            } else {
+                tether
+            })
+        }
+    }
+
+    impl<const N: usize, T, V> ComTether<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        /// Returns the displacement of the center of mass from the anchor
+        /// and the number of atoms in the group.
+        fn displacement(&self, group_positions: &[V]) -> (V, T) {
+            let atoms = <T as From<f32>>::from(group_positions.len() as f32);
+            let mut iter = group_positions.iter().cloned();
+            let center_of_mass = match iter.next() {
+                Some(first) => iter.fold(first, |accum, position| accum + position) / atoms,
+                None => self.anchor.clone(),
+            };
+            (center_of_mass - self.anchor.clone(), atoms)
+        }
+
+        fn reported(&self, displacement: V) -> T {
+            if self.report_energy {
+                self.spring_constant * displacement.magnitude_squared()
+                    / <T as From<f32>>::from(2.0)
+            } else {
+                T::zero()
+            }
+        }
+    }
+
+    impl<const N: usize, T, V> PhysicalPotential<T, V> for ComTether<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        type Error = Infallible;
+
+        fn calculate_potential_set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            let (displacement, atoms) = self.displacement(positions.read());
+            let force = -displacement.clone() * (self.spring_constant / atoms);
+            for atom_force in group_forces {
+                *atom_force = force.clone();
+            }
+            Ok(self.reported(displacement))
+        }
+
+        fn calculate_potential_add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            let (displacement, atoms) = self.displacement(positions.read());
+            let force = -displacement.clone() * (self.spring_constant / atoms);
+            for atom_force in group_forces {
+                *atom_force += force.clone();
+            }
+            Ok(self.reported(displacement))
+        }
+
+        fn calculate_potential(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+        ) -> Result<T, Self::Error> {
+            let (displacement, _) = self.displacement(positions.read());
+            Ok(self.reported(displacement))
+        }
+
+        fn set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            let (displacement, atoms) = self.displacement(positions.read());
+            let force = -displacement * (self.spring_constant / atoms);
+            for atom_force in group_forces {
+                *atom_force = force.clone();
+            }
+            Ok(())
+        }
+
+        fn add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            let (displacement, atoms) = self.displacement(positions.read());
+            let force = -displacement * (self.spring_constant / atoms);
+            for atom_force in group_forces {
+                *atom_force += force.clone();
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use com_tether::{ComTether, ComTetherBuilder};
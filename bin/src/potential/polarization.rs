@@ -0,0 +1,375 @@
+mod thole {
+    use crate::potential::{electrostatics::PeriodicBox, pair::Exclusions};
+    use lib::core::{Vector, error::NotConvergedError, tolerance::TolerancePolicy};
+    use num::Float;
+
+    /// The damped radial factors of a pair at distance `r`:
+    /// `f3 = λ3 / r³`, `f5 = λ5 / r⁵` and their derivatives divided by `r`.
+    struct Damping<T> {
+        f3: T,
+        f5: T,
+        df3: T,
+        df5: T,
+    }
+
+    impl<T: Float + From<f32>> Damping<T> {
+        fn new(distance_squared: T, thole: T, polarizability_product: T) -> Self {
+            let r = distance_squared.sqrt();
+            let r3 = r * distance_squared;
+            let r5 = r3 * distance_squared;
+            let (s, e) = if polarizability_product > T::zero() {
+                let s = thole * r3 / polarizability_product.sqrt();
+                (s, (-s).exp())
+            } else {
+                (T::zero(), T::zero())
+            };
+            let three = <T as From<f32>>::from(3.0);
+            let lambda3 = T::one() - e;
+            let lambda5 = T::one() - (T::one() + s) * e;
+            Self {
+                f3: lambda3 / r3,
+                f5: lambda5 / r5,
+                df3: three * (s * e - lambda3) / (r3 * distance_squared),
+                df5: (three * s * s * e - <T as From<f32>>::from(5.0) * lambda5)
+                    / (r5 * distance_squared),
+            }
+        }
+    }
+
+    /// Induced point dipoles with Thole damping.
+    ///
+    /// The dipole of atom `i` responds to the field of the permanent charges and
+    /// of the other dipoles as `μ_i = α_i E_i`. Interactions between atoms closer than
+    /// their polarizabilities' extent are smeared out with exponential Thole damping,
+    /// which prevents the polarization catastrophe at short distances.
+    ///
+    /// The dipoles can either be converged on every call with [`InducedDipoles::solve`]
+    /// or propagated as auxiliary degrees of freedom with [`ExtendedDipoles`].
+    #[derive(Clone, Debug)]
+    pub struct InducedDipoles<T, V> {
+        polarizabilities: Vec<T>,
+        thole: T,
+        periodic_box: Option<PeriodicBox<T>>,
+        dipoles: Vec<V>,
+        tolerance: TolerancePolicy<T>,
+        mixing: T,
+    }
+
+    impl<T, V> InducedDipoles<T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<3, Element = T> + Clone,
+    {
+        /// The default Thole damping parameter.
+        pub const DEFAULT_THOLE: f32 = 0.39;
+
+        pub fn new(
+            polarizabilities: Vec<T>,
+            thole: T,
+            periodic_box: Option<PeriodicBox<T>>,
+        ) -> Self {
+            assert!(
+                polarizabilities
+                    .iter()
+                    .all(|&polarizability| polarizability >= T::zero()),
+                "polarizabilities must be non-negative"
+            );
+            assert!(thole > T::zero(), "the damping parameter must be positive");
+            let dipoles = polarizabilities.iter().map(|_| V::zero()).collect();
+            Self {
+                polarizabilities,
+                thole,
+                periodic_box,
+                dipoles,
+                tolerance: TolerancePolicy::absolute(<T as From<f32>>::from(1e-6), 100),
+                mixing: <T as From<f32>>::from(0.7),
+            }
+        }
+
+        /// Sets the convergence criteria of [`InducedDipoles::solve`], where the residual
+        /// is the largest change of a dipole in an iteration and the scale is the largest dipole,
+        /// and the fraction of the new dipoles mixed into the old ones.
+        pub fn with_convergence(mut self, tolerance: TolerancePolicy<T>, mixing: T) -> Self {
+            assert!(
+                tolerance.absolute >= T::zero() && tolerance.relative >= T::zero(),
+                "tolerances must be non-negative"
+            );
+            assert!(
+                mixing > T::zero() && mixing <= T::one(),
+                "mixing must be within (0, 1]"
+            );
+            self.tolerance = tolerance;
+            self.mixing = mixing;
+            self
+        }
+
+        pub fn atoms(&self) -> usize {
+            self.polarizabilities.len()
+        }
+
+        pub fn polarizabilities(&self) -> &[T] {
+            &self.polarizabilities
+        }
+
+        pub fn dipoles(&self) -> &[V] {
+            &self.dipoles
+        }
+
+        pub fn dipoles_mut(&mut self) -> &mut [V] {
+            &mut self.dipoles
+        }
+
+        fn displacement(&self, positions: &[V], i: usize, j: usize) -> V {
+            let displacement = positions[i].clone() - positions[j].clone();
+            match &self.periodic_box {
+                Some(periodic_box) => periodic_box.minimum_image(displacement),
+                None => displacement,
+            }
+        }
+
+        fn pairs<'a>(
+            &'a self,
+            positions: &'a [V],
+            exclusions: Option<&'a Exclusions>,
+        ) -> impl Iterator<Item = (usize, usize, V, Damping<T>)> + 'a {
+            (0..positions.len())
+                .flat_map(move |i| (i + 1..positions.len()).map(move |j| (i, j)))
+                .filter(move |&(i, j)| {
+                    !exclusions.is_some_and(|exclusions| exclusions.is_excluded(i, j))
+                })
+                .map(move |(i, j)| {
+                    let displacement = self.displacement(positions, i, j);
+                    let damping = Damping::new(
+                        displacement.clone().magnitude_squared(),
+                        self.thole,
+                        self.polarizabilities[i] * self.polarizabilities[j],
+                    );
+                    (i, j, displacement, damping)
+                })
+        }
+
+        /// Sets `fields` to the damped fields of the permanent charges at every atom.
+        pub fn permanent_fields(
+            &self,
+            charges: &[T],
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+            fields: &mut [V],
+        ) {
+            assert_eq!(charges.len(), self.atoms());
+            assert_eq!(positions.len(), self.atoms());
+            assert_eq!(fields.len(), self.atoms());
+            fields.fill_with(V::zero);
+            for (i, j, displacement, damping) in self.pairs(positions, exclusions) {
+                fields[i].mul_add_assign(&displacement, charges[j] * damping.f3);
+                fields[j].mul_add_assign(&displacement, -(charges[i] * damping.f3));
+            }
+        }
+
+        /// Sets `fields` to the total fields at every atom:
+        /// `permanent_fields` plus the damped fields of the current dipoles.
+        pub fn total_fields(
+            &self,
+            permanent_fields: &[V],
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+            fields: &mut [V],
+        ) {
+            fields.clone_from_slice(permanent_fields);
+            let three = <T as From<f32>>::from(3.0);
+            for (i, j, displacement, damping) in self.pairs(positions, exclusions) {
+                let (dipole_i, dipole_j) = (self.dipoles[i].clone(), self.dipoles[j].clone());
+                fields[i] += displacement.clone()
+                    * (three * damping.f5 * dipole_j.clone().dot(displacement.clone()))
+                    - dipole_j * damping.f3;
+                fields[j] += displacement.clone()
+                    * (three * damping.f5 * dipole_i.clone().dot(displacement))
+                    - dipole_i * damping.f3;
+            }
+        }
+
+        /// Iterates the dipoles to self-consistency with the fields of the permanent charges,
+        /// starting from the current dipoles, and returns the number of iterations.
+        pub fn solve(
+            &mut self,
+            charges: &[T],
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+        ) -> Result<usize, NotConvergedError<T>> {
+            let zero = || V::zero();
+            let mut permanent: Vec<V> = positions.iter().map(|_| zero()).collect();
+            let mut fields: Vec<V> = positions.iter().map(|_| zero()).collect();
+            self.permanent_fields(charges, positions, exclusions, &mut permanent);
+            let mut residual = T::infinity();
+            for iteration in 1..=self.tolerance.max_iterations {
+                self.total_fields(&permanent, positions, exclusions, &mut fields);
+                residual = T::zero();
+                let mut scale = T::zero();
+                for ((dipole, field), &polarizability) in self
+                    .dipoles
+                    .iter_mut()
+                    .zip(&fields)
+                    .zip(&self.polarizabilities)
+                {
+                    let change = (field.clone() * polarizability - dipole.clone()) * self.mixing;
+                    residual = residual.max(change.clone().magnitude_squared().sqrt());
+                    *dipole += change;
+                    scale = scale.max(dipole.clone().magnitude_squared().sqrt());
+                }
+                if self.tolerance.is_converged(residual, scale) {
+                    return Ok(iteration);
+                }
+            }
+            Err(self.tolerance.not_converged(residual))
+        }
+
+        /// Adds the forces on the atoms at the current dipoles to `forces`
+        /// and returns the polarization energy.
+        ///
+        /// The energy is the full polarization functional of the dipoles,
+        /// so the forces are its exact derivatives at the current dipoles.
+        /// At self-consistency it equals `-Σ μ_i · E⁰_i / 2`.
+        pub fn calculate_potential_add_forces(
+            &self,
+            charges: &[T],
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+            forces: &mut [V],
+        ) -> T {
+            assert_eq!(forces.len(), self.atoms());
+            let (two, three) = (<T as From<f32>>::from(2.0), <T as From<f32>>::from(3.0));
+            let mut energy = self
+                .dipoles
+                .iter()
+                .zip(&self.polarizabilities)
+                .filter(|&(_, &polarizability)| polarizability > T::zero())
+                .fold(T::zero(), |energy, (dipole, &polarizability)| {
+                    energy + dipole.clone().magnitude_squared() / (two * polarizability)
+                });
+            for (i, j, displacement, damping) in self.pairs(positions, exclusions) {
+                let (dipole_i, dipole_j) = (self.dipoles[i].clone(), self.dipoles[j].clone());
+                let projection_i = dipole_i.clone().dot(displacement.clone());
+                let projection_j = dipole_j.clone().dot(displacement.clone());
+                let dipoles_dot = dipole_i.clone().dot(dipole_j.clone());
+                let charge_term = charges[i] * projection_j - charges[j] * projection_i;
+                energy = energy + charge_term * damping.f3
+                    - (three * damping.f5 * projection_i * projection_j - damping.f3 * dipoles_dot);
+                // The gradient of the pair energy with respect to the displacement.
+                let gradient = (dipole_j.clone() * charges[i] - dipole_i.clone() * charges[j])
+                    * damping.f3
+                    + displacement.clone() * (charge_term * damping.df3)
+                    - (dipole_i * projection_j + dipole_j * projection_i) * (three * damping.f5)
+                    - displacement
+                        * (three * projection_i * projection_j * damping.df5
+                            - dipoles_dot * damping.df3);
+                forces[i] -= gradient.clone();
+                forces[j] += gradient;
+            }
+            energy
+        }
+
+        /// Sets `dipole_forces` to the negative gradient of the polarization energy
+        /// with respect to every dipole, which vanishes at self-consistency.
+        pub fn dipole_forces(
+            &self,
+            charges: &[T],
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+            dipole_forces: &mut [V],
+        ) {
+            let mut permanent: Vec<V> = positions.iter().map(|_| V::zero()).collect();
+            self.permanent_fields(charges, positions, exclusions, &mut permanent);
+            self.total_fields(&permanent, positions, exclusions, dipole_forces);
+            for ((force, dipole), &polarizability) in dipole_forces
+                .iter_mut()
+                .zip(&self.dipoles)
+                .zip(&self.polarizabilities)
+            {
+                if polarizability > T::zero() {
+                    *force -= dipole.clone() / polarizability;
+                } else {
+                    *force = V::zero();
+                }
+            }
+        }
+    }
+
+    /// Induced dipoles propagated as auxiliary degrees of freedom of an extended Lagrangian.
+    ///
+    /// Every dipole carries a fictitious mass and velocity and is integrated
+    /// alongside the atoms with velocity Verlet, instead of being converged on every step.
+    #[derive(Clone, Debug)]
+    pub struct ExtendedDipoles<T, V> {
+        dipoles: InducedDipoles<T, V>,
+        velocities: Vec<V>,
+        forces: Vec<V>,
+        mass: T,
+    }
+
+    impl<T, V> ExtendedDipoles<T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<3, Element = T> + Clone,
+    {
+        /// Wraps `dipoles`, which should be converged with [`InducedDipoles::solve`] beforehand,
+        /// and assigns them a fictitious mass of `mass`.
+        pub fn new(dipoles: InducedDipoles<T, V>, mass: T) -> Self {
+            assert!(mass > T::zero(), "mass must be positive");
+            let zeros = || (0..dipoles.atoms()).map(|_| V::zero()).collect::<Vec<_>>();
+            Self {
+                velocities: zeros(),
+                forces: zeros(),
+                dipoles,
+                mass,
+            }
+        }
+
+        pub fn dipoles(&self) -> &InducedDipoles<T, V> {
+            &self.dipoles
+        }
+
+        pub fn velocities(&self) -> &[V] {
+            &self.velocities
+        }
+
+        pub fn velocities_mut(&mut self) -> &mut [V] {
+            &mut self.velocities
+        }
+
+        /// Recalculates the forces on the dipoles at `positions`.
+        pub fn update_forces(
+            &mut self,
+            charges: &[T],
+            positions: &[V],
+            exclusions: Option<&Exclusions>,
+        ) {
+            self.dipoles
+                .dipole_forces(charges, positions, exclusions, &mut self.forces);
+        }
+
+        /// Advances the velocities of the dipoles by `step_size` with the last calculated forces.
+        pub fn kick(&mut self, step_size: T) {
+            let factor = step_size / self.mass;
+            for (velocity, force) in self.velocities.iter_mut().zip(&self.forces) {
+                velocity.mul_add_assign(force, factor);
+            }
+        }
+
+        /// Advances the dipoles by `step_size` with their current velocities.
+        pub fn drift(&mut self, step_size: T) {
+            for (dipole, velocity) in self.dipoles.dipoles.iter_mut().zip(&self.velocities) {
+                dipole.mul_add_assign(velocity, step_size);
+            }
+        }
+
+        /// Returns the fictitious kinetic energy of the dipoles.
+        pub fn kinetic_energy(&self) -> T {
+            self.velocities.iter().fold(T::zero(), |sum, velocity| {
+                sum + velocity.clone().magnitude_squared()
+            }) * self.mass
+                / <T as From<f32>>::from(2.0)
+        }
+    }
+}
+
+pub use thole::{ExtendedDipoles, InducedDipoles};
@@ -0,0 +1,366 @@
+mod references {
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        fs, io,
+        path::Path,
+    };
+
+    use lib::core::Vector;
+    use num::{Float, NumCast};
+
+    use crate::steering::ParseError;
+
+    /// Parses reference coordinates, one atom per line, skipping blank lines and `#` comments.
+    ///
+    /// Each line holds the `N` coordinates of an atom, optionally preceded by a label,
+    /// so XYZ files are accepted; their header of the number of atoms and a comment line
+    /// is recognized and checked against the number of atoms read.
+    pub fn parse_references<const N: usize, T, V>(text: &str) -> Result<Vec<V>, ParseError>
+    where
+        T: Float,
+        V: Vector<N, Element = T>,
+    {
+        let mut lines = text.lines().enumerate().peekable();
+        let mut expected = None;
+        if let Some((_, first)) = lines.peek()
+            && let Ok(count) = first.trim().parse::<usize>()
+        {
+            expected = Some(count);
+            lines.next();
+            lines.next();
+        }
+        let mut references = Vec::new();
+        for (index, line) in lines {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| ParseError {
+                line: Some(index + 1),
+                message,
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let coordinates = match fields.len() {
+                length if length == N => &fields[..],
+                length if length == N + 1 => &fields[1..],
+                length => {
+                    return Err(error(format!(
+                        "expected {} coordinates, found {} fields",
+                        N, length
+                    )));
+                }
+            };
+            let mut position = [T::zero(); N];
+            for (coordinate, field) in position.iter_mut().zip(coordinates) {
+                let value: f64 = field
+                    .parse()
+                    .map_err(|err| error(format!("invalid coordinate `{}`: {}", field, err)))?;
+                *coordinate = <T as NumCast>::from(value)
+                    .ok_or_else(|| error(format!("{} is not representable", value)))?;
+            }
+            references.push(V::from(position));
+        }
+        match expected {
+            Some(count) if count != references.len() => Err(ParseError {
+                line: None,
+                message: format!(
+                    "the header declares {} atoms, found {}",
+                    count,
+                    references.len()
+                ),
+            }),
+            _ => Ok(references),
+        }
+    }
+
+    /// An error in loading reference coordinates from a file.
+    #[derive(Debug)]
+    pub enum LoadError {
+        Io(io::Error),
+        Parse(ParseError),
+    }
+
+    impl Display for LoadError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Io(err) => write!(f, "failed to read the references: {}", err),
+                Self::Parse(err) => write!(f, "failed to parse the references: {}", err),
+            }
+        }
+    }
+
+    impl Error for LoadError {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::Io(err) => Some(err),
+                Self::Parse(err) => Some(err),
+            }
+        }
+    }
+
+    /// Reads reference coordinates from the file at `path`; see [`parse_references`].
+    pub fn load_references<const N: usize, T, V>(
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<V>, LoadError>
+    where
+        T: Float,
+        V: Vector<N, Element = T>,
+    {
+        let text = fs::read_to_string(path).map_err(LoadError::Io)?;
+        parse_references(&text).map_err(LoadError::Parse)
+    }
+}
+
+pub use references::{LoadError, load_references, parse_references};
+
+mod position_restraint {
+    use lib::{
+        core::{
+            Vector,
+            error::{AccessError, InvalidIndexError},
+        },
+        potential::physical::{AtomAdditivePhysicalPotential, Ramp, Schedule, TimeDependent},
+    };
+    use num::Float;
+
+    /// Whether the forces of a restraint count towards the pressure of the system.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum PressureContribution {
+        Included,
+        /// The virial of the restraint is tallied, so that pressure estimators
+        /// can subtract it from the virial of the physical forces.
+        Excluded,
+    }
+
+    /// Per-atom harmonic tethers `k_i |r_i - r_i^ref|² / 2` to reference positions.
+    ///
+    /// The references may move from their initial positions to targets along a schedule
+    /// of the progress from 0 to 1, as in targeted MD; they follow the time set
+    /// through [`TimeDependent`]. Like other biases, the energy of the restraint
+    /// is excluded from the reported physical potential energy unless
+    /// requested otherwise with [`PositionRestraint::reporting_energy`].
+    ///
+    /// Implements [`AtomAdditivePhysicalPotential`], so it acts on a group when wrapped
+    /// with [`AdditivePhysicalPotential`](lib::potential::physical::AdditivePhysicalPotential).
+    pub struct PositionRestraint<const N: usize, T, V, S = Ramp<T>> {
+        force_constants: Box<[T]>,
+        initial: Box<[V]>,
+        motion: Option<(Box<[V]>, S)>,
+        references: Box<[V]>,
+        report_energy: bool,
+        pressure: PressureContribution,
+        excluded_virial: T,
+    }
+
+    impl<const N: usize, T, V> PositionRestraint<N, T, V>
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+    {
+        /// Tethers every atom of a group to its reference in `references`
+        /// with the same force constant.
+        pub fn new(references: Vec<V>, force_constant: T) -> Self {
+            let force_constants = vec![force_constant; references.len()];
+            Self::with_force_constants(references, force_constants)
+        }
+
+        /// Tethers every atom of a group to its reference in `references`
+        /// with its own force constant; a zero force constant leaves the atom free.
+        pub fn with_force_constants(references: Vec<V>, force_constants: Vec<T>) -> Self {
+            assert_eq!(
+                references.len(),
+                force_constants.len(),
+                "every reference must have a force constant"
+            );
+            assert!(
+                force_constants
+                    .iter()
+                    .all(|force_constant| *force_constant >= T::zero()),
+                "force constants must be non-negative"
+            );
+            Self {
+                force_constants: force_constants.into_boxed_slice(),
+                initial: references.clone().into_boxed_slice(),
+                motion: None,
+                references: references.into_boxed_slice(),
+                report_energy: false,
+                pressure: PressureContribution::Excluded,
+                excluded_virial: T::zero(),
+            }
+        }
+    }
+
+    impl<const N: usize, T, V, S> PositionRestraint<N, T, V, S>
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+    {
+        /// Moves the references towards `targets` with the progress given by `schedule`,
+        /// which should go from 0 at the start of the pull to 1 at its end.
+        pub fn moving_to<U: Schedule<T>>(
+            self,
+            targets: Vec<V>,
+            schedule: U,
+        ) -> PositionRestraint<N, T, V, U> {
+            assert_eq!(
+                targets.len(),
+                self.initial.len(),
+                "every reference must have a target"
+            );
+            PositionRestraint {
+                force_constants: self.force_constants,
+                initial: self.initial,
+                motion: Some((targets.into_boxed_slice(), schedule)),
+                references: self.references,
+                report_energy: self.report_energy,
+                pressure: self.pressure,
+                excluded_virial: self.excluded_virial,
+            }
+        }
+
+        /// Includes the energy of the restraint in the reported physical potential energy.
+        pub fn reporting_energy(mut self) -> Self {
+            self.report_energy = true;
+            self
+        }
+
+        /// Sets whether the forces of the restraint count towards the pressure,
+        /// which they do not by default.
+        pub fn with_pressure_contribution(mut self, pressure: PressureContribution) -> Self {
+            self.pressure = pressure;
+            self
+        }
+
+        /// Returns the current positions of the references.
+        pub fn references(&self) -> &[V] {
+            &self.references
+        }
+
+        pub fn pressure_contribution(&self) -> PressureContribution {
+            self.pressure
+        }
+
+        /// Returns the virial `Σ r_i · F_i` of the restraint forces evaluated since
+        /// the previous call if the restraint is excluded from the pressure, and zero otherwise.
+        pub fn take_excluded_virial(&mut self) -> T {
+            std::mem::replace(&mut self.excluded_virial, T::zero())
+        }
+
+        /// Returns the energy of the atom at `atom_index` and the force acting on it,
+        /// tallying the virial of the force if required.
+        fn evaluate(
+            &mut self,
+            atom_index: usize,
+            position: &V,
+        ) -> Result<(T, V), InvalidIndexError> {
+            let reference = self
+                .references
+                .get(atom_index)
+                .ok_or(InvalidIndexError::new(atom_index, self.references.len()))?;
+            let force_constant = self.force_constants[atom_index];
+            let displacement = position.clone() - reference.clone();
+            let force = -displacement.clone() * force_constant;
+            if self.pressure == PressureContribution::Excluded {
+                self.excluded_virial = self.excluded_virial + position.clone().dot(force.clone());
+            }
+            let energy = if self.report_energy {
+                force_constant * displacement.magnitude_squared() / (T::one() + T::one())
+            } else {
+                T::zero()
+            };
+            Ok((energy, force))
+        }
+    }
+
+    impl<const N: usize, T, V, S> TimeDependent<T> for PositionRestraint<N, T, V, S>
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+        S: Schedule<T>,
+    {
+        fn set_time(&mut self, _step: usize, time: T) {
+            let Some((targets, schedule)) = &self.motion else {
+                return;
+            };
+            let progress = schedule.factor(&time);
+            for (reference, (initial, target)) in self
+                .references
+                .iter_mut()
+                .zip(self.initial.iter().zip(targets))
+            {
+                *reference = initial.clone() + (target.clone() - initial.clone()) * progress;
+            }
+        }
+    }
+
+    impl<const N: usize, T, V, S> AtomAdditivePhysicalPotential<T, V> for PositionRestraint<N, T, V, S>
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+    {
+        type ErrorAtom = InvalidIndexError;
+        type ErrorSystem = AccessError;
+
+        fn calculate_potential_set_force(
+            &mut self,
+            atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<T, Self::ErrorAtom> {
+            let (energy, atom_force) = self.evaluate(atom_index, position)?;
+            *force = atom_force;
+            Ok(energy)
+        }
+
+        fn calculate_potential_add_force(
+            &mut self,
+            atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<T, Self::ErrorAtom> {
+            let (energy, atom_force) = self.evaluate(atom_index, position)?;
+            *force += atom_force;
+            Ok(energy)
+        }
+
+        fn calculate_potential(
+            &mut self,
+            atom_index: usize,
+            position: &V,
+        ) -> Result<T, Self::ErrorAtom> {
+            if !self.report_energy {
+                return Ok(T::zero());
+            }
+            let reference = self
+                .references
+                .get(atom_index)
+                .ok_or(InvalidIndexError::new(atom_index, self.references.len()))?;
+            Ok(self.force_constants[atom_index]
+                * (position.clone() - reference.clone()).magnitude_squared()
+                / (T::one() + T::one()))
+        }
+
+        fn set_force(
+            &mut self,
+            atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<(), Self::ErrorAtom> {
+            *force = self.evaluate(atom_index, position)?.1;
+            Ok(())
+        }
+
+        fn add_force(
+            &mut self,
+            atom_index: usize,
+            position: &V,
+            force: &mut V,
+        ) -> Result<(), Self::ErrorAtom> {
+            *force += self.evaluate(atom_index, position)?.1;
+            Ok(())
+        }
+    }
+}
+
+pub use position_restraint::{PositionRestraint, PressureContribution};
@@ -0,0 +1,303 @@
+mod stillinger_weber {
+    use std::{convert::Infallible, marker::PhantomData};
+
+    use lib::{
+        core::Vector,
+        potential::physical::{BoxDependent, ThreeBodyPhysicalPotential},
+    };
+    use num::Float;
+
+    use crate::potential::{
+        builder::{ParameterError, fits_box, non_negative, positive, required},
+        electrostatics::PeriodicBox,
+        pair::{NeighborListError, NeighborListStats, TripletBuilder, VerletList},
+    };
+
+    /// The parameters of the Stillinger-Weber potential.
+    ///
+    /// The two-body term is `A ε (B (σ/r)^p - (σ/r)^q) exp(σ / (r - aσ))` and the three-body term
+    /// of the angle `θ` between two bonds of lengths `r1` and `r2` is
+    /// `λ ε (cos θ - cos θ0)^2 exp(γσ / (r1 - aσ)) exp(γσ / (r2 - aσ))`,
+    /// both vanishing smoothly at the cutoff `aσ`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct StillingerWeberParameters<T> {
+        pub epsilon: T,
+        pub sigma: T,
+        pub a: T,
+        pub lambda: T,
+        pub gamma: T,
+        pub cos_theta0: T,
+        pub big_a: T,
+        pub big_b: T,
+        pub p: T,
+        pub q: T,
+    }
+
+    impl<T: Float + From<f32>> StillingerWeberParameters<T> {
+        /// The original parametrization for silicon in eV and Å.
+        pub fn silicon() -> Self {
+            let from = <T as From<f32>>::from;
+            Self {
+                epsilon: from(2.1683),
+                sigma: from(2.0951),
+                a: from(1.8),
+                lambda: from(21.0),
+                gamma: from(1.2),
+                cos_theta0: -T::one() / from(3.0),
+                big_a: from(7.049_556),
+                big_b: from(0.602_224_6),
+                p: from(4.0),
+                q: T::zero(),
+            }
+        }
+
+        pub fn cutoff(&self) -> T {
+            self.a * self.sigma
+        }
+    }
+
+    /// The Stillinger-Weber potential between the atoms of a group in `N` dimensions.
+    ///
+    /// Implements [`ThreeBodyPhysicalPotential`], so it is used wrapped with
+    /// [`ThreeBodyPotential`](lib::potential::physical::ThreeBodyPotential).
+    /// The pairs are found with a Verlet list, under the minimum image convention
+    /// of the box if one is given.
+    pub struct StillingerWeber<const N: usize, T, V> {
+        parameters: StillingerWeberParameters<T>,
+        neighbors: VerletList<N, T, V>,
+        triplet_builder: TripletBuilder,
+    }
+
+    /// A builder of [`StillingerWeber`], obtained with [`StillingerWeber::builder`].
+    ///
+    /// The parameters and the skin of the Verlet list are required and the box is optional.
+    #[derive(Clone, Copy, Debug)]
+    pub struct StillingerWeberBuilder<const N: usize, T, V> {
+        parameters: Option<StillingerWeberParameters<T>>,
+        skin: Option<T>,
+        periodic_box: Option<PeriodicBox<T, N>>,
+        phantom: PhantomData<V>,
+    }
+
+    impl<const N: usize, T, V> StillingerWeberBuilder<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        pub fn parameters(mut self, parameters: StillingerWeberParameters<T>) -> Self {
+            self.parameters = Some(parameters);
+            self
+        }
+
+        pub fn skin(mut self, skin: T) -> Self {
+            self.skin = Some(skin);
+            self
+        }
+
+        /// Applies the minimum image convention of `periodic_box`; see [`StillingerWeber::with_box`].
+        pub fn periodic_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            self.periodic_box = Some(periodic_box);
+            self
+        }
+
+        /// Constructs the potential, or returns the first parameter which is missing or invalid.
+        pub fn build(self) -> Result<StillingerWeber<N, T, V>, ParameterError<T>> {
+            let parameters = required(self.parameters, "Stillinger-Weber parameters")?;
+            positive(parameters.epsilon, "epsilon")?;
+            positive(parameters.sigma, "sigma")?;
+            positive(parameters.a, "reduced cutoff a")?;
+            non_negative(parameters.lambda, "lambda")?;
+            non_negative(parameters.gamma, "gamma")?;
+            let skin = non_negative(required(self.skin, "skin")?, "skin")?;
+            let potential = StillingerWeber::new(parameters, skin);
+            match self.periodic_box {
+                Some(periodic_box) => {
+                    fits_box(parameters.cutoff() + skin, &periodic_box)?;
+                    Ok(potential.with_box(periodic_box))
+                }
+                None => Ok(potential),
+            }
+        }
+    }
+
+    impl<const N: usize, T, V> StillingerWeber<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        /// Constructs the potential with a Verlet list of skin `skin`.
+        pub fn new(parameters: StillingerWeberParameters<T>, skin: T) -> Self {
+            Self {
+                parameters,
+                neighbors: VerletList::new(parameters.cutoff(), skin),
+                triplet_builder: TripletBuilder::new(),
+            }
+        }
+
+        /// Applies the minimum image convention of `periodic_box` to the displacements.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the cutoff and the skin exceed half of a periodic length of the box.
+        pub fn with_box(mut self, periodic_box: PeriodicBox<T, N>) -> Self {
+            self.neighbors.set_box(periodic_box);
+            self
+        }
+
+        pub fn parameters(&self) -> &StillingerWeberParameters<T> {
+            &self.parameters
+        }
+
+        /// Returns the counters of the updates and the rebuilds of the Verlet list,
+        /// to be recorded in a [`ProfileReport`](crate::profile::ProfileReport).
+        pub fn neighbor_list_stats(&self) -> NeighborListStats {
+            self.neighbors.stats()
+        }
+
+        /// Returns a builder which validates the parameters instead of panicking.
+        pub fn builder() -> StillingerWeberBuilder<N, T, V> {
+            StillingerWeberBuilder {
+                parameters: None,
+                skin: None,
+                periodic_box: None,
+                phantom: PhantomData,
+            }
+        }
+
+        fn displacement(&self, from: &V, to: &V) -> V {
+            let displacement = to.clone() - from.clone();
+            match self.neighbors.periodic_box() {
+                Some(periodic_box) => periodic_box.minimum_image(displacement),
+                None => displacement,
+            }
+        }
+
+        /// Returns `exp(scale σ / (r - aσ))` and its derivative with respect to `r`,
+        /// or `None` beyond the cutoff.
+        fn cutoff_factor(&self, distance: T, scale: T) -> Option<(T, T)> {
+            let StillingerWeberParameters { sigma, .. } = self.parameters;
+            let gap = distance - self.parameters.cutoff();
+            if gap >= T::zero() {
+                return None;
+            }
+            let factor = (scale * sigma / gap).exp();
+            Some((factor, -factor * scale * sigma / (gap * gap)))
+        }
+    }
+
+    impl<const N: usize, T, V> ThreeBodyPhysicalPotential<T, V> for StillingerWeber<N, T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        type ErrorTerm = Infallible;
+        type ErrorSystem = NeighborListError;
+
+        fn list_pairs(
+            &mut self,
+            positions: &[V],
+            pairs: &mut Vec<[usize; 2]>,
+        ) -> Result<(), Self::ErrorSystem> {
+            self.neighbors.update(positions, None)?;
+            pairs.clear();
+            pairs.extend(
+                self.neighbors
+                    .pairs()
+                    .map(|(first, second)| [first, second]),
+            );
+            Ok(())
+        }
+
+        fn list_triplets(
+            &mut self,
+            positions: &[V],
+            pairs: &[[usize; 2]],
+            triplets: &mut Vec<[usize; 3]>,
+        ) -> Result<(), Self::ErrorSystem> {
+            Ok(self
+                .triplet_builder
+                .build(positions.len(), pairs.iter().copied(), triplets)?)
+        }
+
+        fn two_body(&mut self, positions: [&V; 2]) -> Result<(T, [V; 2]), Self::ErrorTerm> {
+            let StillingerWeberParameters {
+                epsilon,
+                sigma,
+                big_a,
+                big_b,
+                p,
+                q,
+                ..
+            } = self.parameters;
+            let displacement = self.displacement(positions[1], positions[0]);
+            let distance = displacement.clone().magnitude_squared().sqrt();
+            let Some((factor, factor_derivative)) = self.cutoff_factor(distance, T::one()) else {
+                return Ok((T::zero(), [V::zero(), V::zero()]));
+            };
+            let ratio = sigma / distance;
+            let radial = big_a * epsilon * (big_b * ratio.powf(p) - ratio.powf(q));
+            let radial_derivative =
+                big_a * epsilon * (q * ratio.powf(q) - p * big_b * ratio.powf(p)) / distance;
+            let derivative = radial_derivative * factor + radial * factor_derivative;
+            let force = displacement * (-derivative / distance);
+            Ok((radial * factor, [force.clone(), -force]))
+        }
+
+        fn three_body(&mut self, positions: [&V; 3]) -> Result<(T, [V; 3]), Self::ErrorTerm> {
+            let StillingerWeberParameters {
+                epsilon,
+                lambda,
+                gamma,
+                cos_theta0,
+                ..
+            } = self.parameters;
+            let zero = Ok((T::zero(), [V::zero(), V::zero(), V::zero()]));
+            let first = self.displacement(positions[0], positions[1]);
+            let second = self.displacement(positions[0], positions[2]);
+            let first_distance = first.clone().magnitude_squared().sqrt();
+            let second_distance = second.clone().magnitude_squared().sqrt();
+            let (Some((first_factor, first_derivative)), Some((second_factor, second_derivative))) = (
+                self.cutoff_factor(first_distance, gamma),
+                self.cutoff_factor(second_distance, gamma),
+            ) else {
+                return zero;
+            };
+            let cosine = first.clone().dot(second.clone()) / (first_distance * second_distance);
+            let deviation = cosine - cos_theta0;
+            let angular = lambda * epsilon * deviation * deviation;
+            let energy = angular * first_factor * second_factor;
+            let cosine_derivative = <T as From<f32>>::from(2.0)
+                * lambda
+                * epsilon
+                * deviation
+                * first_factor
+                * second_factor;
+            // The gradients of the energy with respect to the two bond vectors.
+            let first_gradient = first.clone()
+                * (angular * first_derivative * second_factor / first_distance
+                    - cosine_derivative * cosine / (first_distance * first_distance))
+                + second.clone() * (cosine_derivative / (first_distance * second_distance));
+            let second_gradient = second
+                * (angular * first_factor * second_derivative / second_distance
+                    - cosine_derivative * cosine / (second_distance * second_distance))
+                + first * (cosine_derivative / (first_distance * second_distance));
+            Ok((
+                energy,
+                [
+                    first_gradient.clone() + second_gradient.clone(),
+                    -first_gradient,
+                    -second_gradient,
+                ],
+            ))
+        }
+    }
+
+    impl<const N: usize, T: Float, V> BoxDependent<T> for StillingerWeber<N, T, V> {
+        /// Scales the box of the minimum image convention, if any.
+        fn scale_box(&mut self, factors: &[T]) {
+            self.neighbors.scale_box(factors);
+        }
+    }
+}
+
+pub use stillinger_weber::{StillingerWeber, StillingerWeberBuilder, StillingerWeberParameters};
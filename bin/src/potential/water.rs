@@ -0,0 +1,442 @@
+mod model {
+    use num::Float;
+
+    use crate::potential::pair::PairParameters;
+
+    /// The potential of an O-H bond.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Stretch<T> {
+        /// `k (r - r_eq)² / 2`.
+        Harmonic { length: T, force_constant: T },
+        /// The quartic expansion of a Morse potential,
+        /// `D (α² Δr² - α³ Δr³ + 7 α⁴ Δr⁴ / 12)` with `Δr = r - r_eq`,
+        /// which keeps the anharmonicity of the bond without letting it dissociate.
+        QuarticMorse { length: T, depth: T, alpha: T },
+    }
+
+    impl<T: Float + From<f32>> Stretch<T> {
+        /// Returns the energy of a bond of length `length` and its derivative with respect to it.
+        pub fn energy(&self, length: T) -> (T, T) {
+            match *self {
+                Self::Harmonic {
+                    length: equilibrium,
+                    force_constant,
+                } => {
+                    let extension = length - equilibrium;
+                    (
+                        <T as From<f32>>::from(0.5) * force_constant * extension * extension,
+                        force_constant * extension,
+                    )
+                }
+                Self::QuarticMorse {
+                    length: equilibrium,
+                    depth,
+                    alpha,
+                } => {
+                    let x = alpha * (length - equilibrium);
+                    let seven_twelfths = <T as From<f32>>::from(7.0 / 12.0);
+                    (
+                        depth * x * x * (T::one() - x + seven_twelfths * x * x),
+                        depth
+                            * alpha
+                            * x
+                            * (<T as From<f32>>::from(2.0) - <T as From<f32>>::from(3.0) * x
+                                + <T as From<f32>>::from(4.0) * seven_twelfths * x * x),
+                    )
+                }
+            }
+        }
+    }
+
+    /// The parameters of a flexible water model with the atoms of every molecule
+    /// ordered as oxygen, hydrogen, hydrogen.
+    ///
+    /// The negative charge sits on the oxygen in a three-site model and on a massless
+    /// site M on the bisector of the H-O-H angle in a four-site model.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct WaterModel<T> {
+        /// The charge of a hydrogen; the negative site carries minus twice this charge.
+        pub hydrogen_charge: T,
+        /// The weight `γ` of the oxygen in the position of the M site,
+        /// `γ r_O + (1 - γ) (r_H1 + r_H2) / 2`, or `None` for a three-site model.
+        pub m_site: Option<T>,
+        pub stretch: Stretch<T>,
+        /// The equilibrium H-O-H angle in radians.
+        pub bend_angle: T,
+        /// The force constant `k_θ` of the harmonic bend `k_θ (θ - θ_eq)² / 2`.
+        pub bend_force_constant: T,
+        /// The Lennard-Jones interaction between the oxygens of different molecules.
+        pub oxygen_lennard_jones: PairParameters<T>,
+    }
+
+    impl<T: Float + From<f32>> WaterModel<T> {
+        /// The q-SPC/Fw model of Paesani et al. (2006) in Å, kcal/mol and elementary charges.
+        pub fn q_spc_fw() -> Self {
+            Self {
+                hydrogen_charge: <T as From<f32>>::from(0.42),
+                m_site: None,
+                stretch: Stretch::Harmonic {
+                    length: <T as From<f32>>::from(1.0),
+                    force_constant: <T as From<f32>>::from(1059.162),
+                },
+                bend_angle: <T as From<f32>>::from(112.0f32.to_radians()),
+                bend_force_constant: <T as From<f32>>::from(75.90),
+                oxygen_lennard_jones: PairParameters::new(
+                    <T as From<f32>>::from(3.1655),
+                    <T as From<f32>>::from(0.1554),
+                ),
+            }
+        }
+
+        /// The q-TIP4P/F model of Habershon et al. (2009) in Å, kcal/mol and elementary charges.
+        pub fn q_tip4p_f() -> Self {
+            Self {
+                hydrogen_charge: <T as From<f32>>::from(0.5564),
+                m_site: Some(<T as From<f32>>::from(0.73612)),
+                stretch: Stretch::QuarticMorse {
+                    length: <T as From<f32>>::from(0.9419),
+                    depth: <T as From<f32>>::from(116.09),
+                    alpha: <T as From<f32>>::from(2.287),
+                },
+                bend_angle: <T as From<f32>>::from(107.4f32.to_radians()),
+                bend_force_constant: <T as From<f32>>::from(87.85),
+                oxygen_lennard_jones: PairParameters::new(
+                    <T as From<f32>>::from(3.1589),
+                    <T as From<f32>>::from(0.1852),
+                ),
+            }
+        }
+    }
+}
+
+pub use model::{Stretch, WaterModel};
+
+mod composite {
+    use std::ops::Add;
+
+    use lib::{
+        core::{Vector, error::ExpectationError},
+        potential::{GroupInTypeInImage, physical::PhysicalPotential},
+    };
+    use num::Float;
+
+    use super::WaterModel;
+    use crate::{
+        core::constants::COULOMB_CONSTANT,
+        potential::{
+            electrostatics::{Electrostatics, PeriodicBox},
+            pair::LennardJones,
+        },
+    };
+
+    /// The contributions to the energy of a configuration of water.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct WaterEnergy<T> {
+        pub stretch: T,
+        pub bend: T,
+        pub lennard_jones: T,
+        pub electrostatic: T,
+    }
+
+    impl<T: Add<Output = T>> WaterEnergy<T> {
+        pub fn total(self) -> T {
+            self.stretch + self.bend + self.lennard_jones + self.electrostatic
+        }
+    }
+
+    /// The complete potential of a group of flexible water molecules,
+    /// with the atoms of every molecule ordered as oxygen, hydrogen, hydrogen.
+    ///
+    /// The intramolecular part consists of the two O-H stretches and the H-O-H bend.
+    /// The molecules interact through the truncated and shifted Lennard-Jones potential
+    /// between their oxygens and the Coulomb potential between their charged sites,
+    /// which is summed directly for a cluster and by a periodic solver in a box.
+    /// The M site of a four-site model is placed from the atoms of its molecule in every call
+    /// and the force on it is distributed back onto them.
+    ///
+    /// The parameters of [`WaterModel::q_spc_fw`] and [`WaterModel::q_tip4p_f`]
+    /// require the Coulomb constant in kcal Å / (mol e²).
+    pub struct Water<T, V> {
+        model: WaterModel<T>,
+        lennard_jones: LennardJones<3, T>,
+        electrostatics: Option<Electrostatics<T>>,
+        periodic_box: Option<PeriodicBox<T>>,
+        charges: Vec<T>,
+        sites: Vec<V>,
+        site_forces: Vec<V>,
+        oxygens: Vec<V>,
+        oxygen_forces: Vec<V>,
+    }
+
+    impl<T, V> Water<T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<3, Element = T> + Clone,
+    {
+        /// Constructs the potential of a cluster with the Lennard-Jones interaction
+        /// truncated at `cutoff` and the Coulomb interaction summed over every pair of molecules.
+        pub fn cluster(model: WaterModel<T>, cutoff: T) -> Self {
+            Self {
+                lennard_jones: LennardJones::new(model.oxygen_lennard_jones, cutoff),
+                model,
+                electrostatics: None,
+                periodic_box: None,
+                charges: Vec::new(),
+                sites: Vec::new(),
+                site_forces: Vec::new(),
+                oxygens: Vec::new(),
+                oxygen_forces: Vec::new(),
+            }
+        }
+
+        /// Constructs the potential of bulk water in the box of `electrostatics`,
+        /// which sums the Coulomb interaction.
+        ///
+        /// The intramolecular pairs are removed from the sum of the solver, which requires
+        /// its real-space cutoff to exceed the size of a molecule.
+        pub fn periodic(
+            model: WaterModel<T>,
+            cutoff: T,
+            electrostatics: Electrostatics<T>,
+        ) -> Self {
+            let periodic_box = *electrostatics.periodic_box();
+            Self {
+                lennard_jones: LennardJones::new(model.oxygen_lennard_jones, cutoff)
+                    .with_box(periodic_box),
+                electrostatics: Some(electrostatics),
+                periodic_box: Some(periodic_box),
+                ..Self::cluster(model, cutoff)
+            }
+        }
+
+        pub fn model(&self) -> &WaterModel<T> {
+            &self.model
+        }
+
+        fn displacement(&self, from: &V, to: &V) -> V {
+            let displacement = to.clone() - from.clone();
+            match &self.periodic_box {
+                Some(periodic_box) => periodic_box.minimum_image(displacement),
+                None => displacement,
+            }
+        }
+
+        /// Returns the energy of the configuration split into its contributions
+        /// and adds the forces to `forces`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the number of atoms is not a multiple of three.
+        pub fn calculate(
+            &mut self,
+            positions: &[V],
+            forces: &mut [V],
+        ) -> Result<WaterEnergy<T>, ExpectationError> {
+            assert!(
+                positions.len().is_multiple_of(3),
+                "every molecule must consist of an oxygen and two hydrogens"
+            );
+            assert_eq!(forces.len(), positions.len());
+            let mut energy = WaterEnergy {
+                stretch: T::zero(),
+                bend: T::zero(),
+                lennard_jones: T::zero(),
+                electrostatic: T::zero(),
+            };
+            for (molecule, molecule_forces) in
+                positions.chunks_exact(3).zip(forces.chunks_exact_mut(3))
+            {
+                let (stretch, bend) = self.intramolecular(molecule, molecule_forces);
+                energy.stretch = energy.stretch + stretch;
+                energy.bend = energy.bend + bend;
+            }
+            energy.lennard_jones = self.oxygen_lennard_jones(positions, forces);
+            energy.electrostatic = self.coulomb(positions, forces)?;
+            Ok(energy)
+        }
+
+        /// Adds the forces of the stretches and the bend of a molecule
+        /// and returns their energies.
+        fn intramolecular(&self, molecule: &[V], forces: &mut [V]) -> (T, T) {
+            let mut stretch = T::zero();
+            let mut bonds: [(V, T); 2] = std::array::from_fn(|_| (V::zero(), T::zero()));
+            for (hydrogen, bond) in bonds.iter_mut().enumerate() {
+                let displacement = self.displacement(&molecule[0], &molecule[hydrogen + 1]);
+                let length = displacement.clone().magnitude_squared().sqrt();
+                let (energy, derivative) = self.model.stretch.energy(length);
+                stretch = stretch + energy;
+                let force = displacement.clone() * (derivative / length);
+                forces[0] += force.clone();
+                forces[hydrogen + 1] -= force;
+                *bond = (displacement / length, length);
+            }
+            let [(first, first_length), (second, second_length)] = bonds;
+            let cosine = first
+                .clone()
+                .dot(second.clone())
+                .max(-T::one())
+                .min(T::one());
+            let sine = (T::one() - cosine * cosine)
+                .sqrt()
+                .max(<T as From<f32>>::from(1e-8));
+            let deviation = cosine.acos() - self.model.bend_angle;
+            let bend = <T as From<f32>>::from(0.5)
+                * self.model.bend_force_constant
+                * deviation
+                * deviation;
+            // dθ/dr_H = -(û_other - cos θ û_own) / (|r_own| sin θ)
+            let factor = self.model.bend_force_constant * deviation / sine;
+            let first_force = (second.clone() - first.clone() * cosine) * (factor / first_length);
+            let second_force = (first - second * cosine) * (factor / second_length);
+            forces[0] -= first_force.clone() + second_force.clone();
+            forces[1] += first_force;
+            forces[2] += second_force;
+            (stretch, bend)
+        }
+
+        fn oxygen_lennard_jones(&mut self, positions: &[V], forces: &mut [V]) -> T {
+            self.oxygens.clear();
+            self.oxygens.extend(positions.iter().step_by(3).cloned());
+            self.oxygen_forces.clear();
+            self.oxygen_forces.resize(self.oxygens.len(), V::zero());
+            let energy = self
+                .lennard_jones
+                .accumulate(&self.oxygens, Some(&mut self.oxygen_forces));
+            for (force, oxygen_force) in forces.iter_mut().step_by(3).zip(&self.oxygen_forces) {
+                *force += oxygen_force.clone();
+            }
+            energy
+        }
+
+        /// Places the charged sites, i.e. the oxygen or the M site followed by the hydrogens.
+        fn place_sites(&mut self, positions: &[V]) {
+            self.sites.clear();
+            self.charges.clear();
+            for molecule in positions.chunks_exact(3) {
+                let negative = match self.model.m_site {
+                    Some(gamma) => {
+                        let bisector = self.displacement(&molecule[0], &molecule[1])
+                            + self.displacement(&molecule[0], &molecule[2]);
+                        molecule[0].clone()
+                            + bisector * (<T as From<f32>>::from(0.5) * (T::one() - gamma))
+                    }
+                    None => molecule[0].clone(),
+                };
+                self.sites
+                    .extend([negative, molecule[1].clone(), molecule[2].clone()]);
+                let charge = self.model.hydrogen_charge;
+                self.charges
+                    .extend([<T as From<f32>>::from(-2.0) * charge, charge, charge]);
+            }
+        }
+
+        /// Adds the forces of the Coulomb interaction between molecules and returns its energy.
+        fn coulomb(&mut self, positions: &[V], forces: &mut [V]) -> Result<T, ExpectationError> {
+            self.place_sites(positions);
+            self.site_forces.clear();
+            self.site_forces.resize(self.sites.len(), V::zero());
+            let coulomb_constant = <T as From<f32>>::from(COULOMB_CONSTANT);
+            let mut energy = match &mut self.electrostatics {
+                Some(electrostatics) => electrostatics.calculate_potential_set_forces(
+                    &self.charges,
+                    &self.sites,
+                    &mut self.site_forces,
+                )?,
+                None => T::zero(),
+            };
+            // Removes the intramolecular pairs from the periodic sum or,
+            // for a cluster, sums the intermolecular pairs directly.
+            let intramolecular = self.electrostatics.is_some();
+            for i in 0..self.sites.len() {
+                for j in i + 1..self.sites.len() {
+                    if (i / 3 == j / 3) != intramolecular {
+                        continue;
+                    }
+                    let displacement = self.displacement(&self.sites[j], &self.sites[i]);
+                    let distance_squared = displacement.clone().magnitude_squared();
+                    let distance = distance_squared.sqrt();
+                    let mut pair_energy =
+                        coulomb_constant * self.charges[i] * self.charges[j] / distance;
+                    if intramolecular {
+                        pair_energy = -pair_energy;
+                    }
+                    energy = energy + pair_energy;
+                    let force = displacement * (pair_energy / distance_squared);
+                    self.site_forces[i] += force.clone();
+                    self.site_forces[j] -= force;
+                }
+            }
+            for (molecule_forces, site_forces) in forces
+                .chunks_exact_mut(3)
+                .zip(self.site_forces.chunks_exact(3))
+            {
+                match self.model.m_site {
+                    Some(gamma) => {
+                        let hydrogen_share = <T as From<f32>>::from(0.5) * (T::one() - gamma);
+                        molecule_forces[0].mul_add_assign(&site_forces[0], gamma);
+                        molecule_forces[1].mul_add_assign(&site_forces[0], hydrogen_share);
+                        molecule_forces[2].mul_add_assign(&site_forces[0], hydrogen_share);
+                    }
+                    None => molecule_forces[0] += site_forces[0].clone(),
+                }
+                molecule_forces[1] += site_forces[1].clone();
+                molecule_forces[2] += site_forces[2].clone();
+            }
+            Ok(energy)
+        }
+    }
+
+    impl<T, V> PhysicalPotential<T, V> for Water<T, V>
+    where
+        T: Float + From<f32>,
+        V: Vector<3, Element = T> + Clone,
+    {
+        type Error = ExpectationError;
+
+        fn calculate_potential_set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            group_forces.fill_with(V::zero);
+            Ok(self.calculate(positions.read(), group_forces)?.total())
+        }
+
+        fn calculate_potential_add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<T, Self::Error> {
+            Ok(self.calculate(positions.read(), group_forces)?.total())
+        }
+
+        fn calculate_potential(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+        ) -> Result<T, Self::Error> {
+            let mut scratch = vec![V::zero(); positions.read().len()];
+            Ok(self.calculate(positions.read(), &mut scratch)?.total())
+        }
+
+        fn set_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            group_forces.fill_with(V::zero);
+            self.calculate(positions.read(), group_forces)?;
+            Ok(())
+        }
+
+        fn add_forces(
+            &mut self,
+            positions: &GroupInTypeInImage<V>,
+            group_forces: &mut [V],
+        ) -> Result<(), Self::Error> {
+            self.calculate(positions.read(), group_forces)?;
+            Ok(())
+        }
+    }
+}
+
+pub use composite::{Water, WaterEnergy};
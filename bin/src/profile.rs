@@ -0,0 +1,59 @@
+mod report {
+    use std::fmt::{Display, Formatter, Result as FmtResult};
+
+    use crate::potential::pair::NeighborListStats;
+
+    /// The work done by every subsystem of a run which keeps counters of its own,
+    /// e.g. how often the neighbor lists of the potentials were rebuilt.
+    #[derive(Clone, Debug, Default)]
+    pub struct ProfileReport {
+        neighbor_lists: Vec<(String, NeighborListStats)>,
+    }
+
+    impl ProfileReport {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Adds the counters of a neighbor list to those of `subsystem`,
+        /// so the lists of every replica can be recorded under one name.
+        pub fn record_neighbor_list(&mut self, subsystem: &str, stats: NeighborListStats) {
+            match self
+                .neighbor_lists
+                .iter_mut()
+                .find(|(name, _)| name == subsystem)
+            {
+                Some((_, total)) => {
+                    total.updates += stats.updates;
+                    total.builds += stats.builds;
+                }
+                None => self.neighbor_lists.push((subsystem.to_owned(), stats)),
+            }
+        }
+
+        pub fn neighbor_lists(&self) -> &[(String, NeighborListStats)] {
+            &self.neighbor_lists
+        }
+    }
+
+    impl Display for ProfileReport {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            for (subsystem, stats) in &self.neighbor_lists {
+                write!(
+                    f,
+                    "{:<32}{:>10} updates, {} builds",
+                    subsystem, stats.updates, stats.builds
+                )?;
+                match stats.rebuild_frequency() {
+                    Some(frequency) => {
+                        writeln!(f, ", {:.1}% of the updates rebuilt", 100.0 * frequency)?
+                    }
+                    None => writeln!(f)?,
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use report::ProfileReport;
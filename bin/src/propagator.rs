@@ -0,0 +1,596 @@
+pub mod free_ring_polymer;
+
+mod geodesic {
+    use lib::core::{
+        FrozenAtoms, Vector,
+        manifold::{Flat, Manifold},
+    };
+    use num::Float;
+
+    /// A velocity Verlet integrator for atoms confined to a [`Manifold`].
+    ///
+    /// The drift follows the geodesic along the momentum with the exponential map
+    /// and carries the momentum along by parallel transport, which is the exact free
+    /// motion on the manifold. The kicks keep only the tangential part of the forces.
+    /// With the default [`Flat`] manifold every step reduces to the usual velocity Verlet.
+    ///
+    /// Frozen atoms, set by [`GeodesicVerlet::with_frozen`], are neither kicked nor moved.
+    #[derive(Clone, Debug)]
+    pub struct GeodesicVerlet<T, M = Flat> {
+        manifold: M,
+        step_size: T,
+        frozen: FrozenAtoms,
+    }
+
+    impl<T: Float> GeodesicVerlet<T> {
+        /// Constructs an integrator in flat space.
+        pub fn flat(step_size: T) -> Self {
+            Self::new(Flat, step_size)
+        }
+    }
+
+    impl<T: Float, M> GeodesicVerlet<T, M> {
+        pub fn new(manifold: M, step_size: T) -> Self {
+            assert!(step_size > T::zero(), "the step size must be positive");
+            Self {
+                manifold,
+                step_size,
+                frozen: FrozenAtoms::none(0),
+            }
+        }
+
+        /// Holds the atoms of the group frozen in `frozen` in place.
+        pub fn with_frozen(mut self, frozen: FrozenAtoms) -> Self {
+            self.frozen = frozen;
+            self
+        }
+
+        pub fn manifold(&self) -> &M {
+            &self.manifold
+        }
+
+        pub fn step_size(&self) -> T {
+            self.step_size
+        }
+
+        pub fn frozen(&self) -> &FrozenAtoms {
+            &self.frozen
+        }
+
+        /// Adds the tangential part of the forces to the momenta over half a step.
+        pub fn kick<const N: usize, V>(&self, positions: &[V], momenta: &mut [V], forces: &[V])
+        where
+            T: From<f32>,
+            V: Vector<N, Element = T> + Clone,
+            M: Manifold<N, V>,
+        {
+            let half_step = self.step_size * <T as From<f32>>::from(0.5);
+            for (index, ((position, momentum), force)) in
+                positions.iter().zip(momenta).zip(forces).enumerate()
+            {
+                if self.frozen.is_frozen(index) {
+                    continue;
+                }
+                *momentum = self
+                    .manifold
+                    .project_tangent(position, momentum.clone() + force.clone() * half_step);
+            }
+        }
+
+        /// Moves the atoms of mass `mass` along their geodesics for a full step.
+        pub fn drift<const N: usize, V>(&self, mass: T, positions: &mut [V], momenta: &mut [V])
+        where
+            V: Vector<N, Element = T> + Clone,
+            M: Manifold<N, V>,
+        {
+            let scale = self.step_size / mass;
+            for (index, (position, momentum)) in positions.iter_mut().zip(momenta).enumerate() {
+                if self.frozen.is_frozen(index) {
+                    continue;
+                }
+                let start = position.clone();
+                *position = self.manifold.exp_map(&start, momentum.clone() * scale);
+                *momentum = self.manifold.transport(&start, position, momentum.clone());
+            }
+        }
+
+        /// Performs a full step, recomputing `forces` from the new positions with `calculate`,
+        /// which returns the potential energy.
+        ///
+        /// Returns the potential energy at the new positions.
+        pub fn step<const N: usize, V, F, E>(
+            &self,
+            mass: T,
+            positions: &mut [V],
+            momenta: &mut [V],
+            forces: &mut [V],
+            mut calculate: F,
+        ) -> Result<T, E>
+        where
+            T: From<f32>,
+            V: Vector<N, Element = T> + Clone,
+            M: Manifold<N, V>,
+            F: FnMut(&[V], &mut [V]) -> Result<T, E>,
+        {
+            self.kick(positions, momenta, forces);
+            self.drift(mass, positions, momenta);
+            let energy = calculate(positions, forces)?;
+            self.kick(positions, momenta, forces);
+            Ok(energy)
+        }
+    }
+}
+
+pub use geodesic::GeodesicVerlet;
+
+mod ring_polymer {
+    use std::num::NonZero;
+
+    use lib::core::Vector;
+    use num::{Float, NumCast};
+
+    use super::free_ring_polymer::{FreeRingPolymerCache, FreeRingPolymerScheme};
+    use crate::steering::{ParseError, parse_settings};
+
+    /// The settings of the ring polymer integrators, read from lines of `name = value`:
+    /// the `time_step`, the `inner_steps` of [`RingPolymerRespa`], 1 if omitted,
+    /// and the `free_ring_polymer` scheme, `exact` or `cayley`, exact if omitted.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct RingPolymerConfig<T> {
+        pub time_step: T,
+        pub inner_steps: NonZero<usize>,
+        pub scheme: FreeRingPolymerScheme,
+    }
+
+    impl<T: Float> RingPolymerConfig<T> {
+        pub fn parse(text: &str) -> Result<Self, ParseError> {
+            let (mut time_step, mut inner_steps, mut scheme) = (None, NonZero::<usize>::MIN, None);
+            for (line, name, value) in parse_settings(text)? {
+                let error = |message: String| ParseError {
+                    line: Some(line),
+                    message,
+                };
+                match name {
+                    "time_step" => {
+                        time_step = value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|&time_step| time_step > 0.0)
+                            .and_then(<T as NumCast>::from);
+                        if time_step.is_none() {
+                            return Err(error(format!(
+                                "the time step must be a positive number, found `{}`",
+                                value
+                            )));
+                        }
+                    }
+                    "inner_steps" => {
+                        inner_steps = value.parse().map_err(|_| {
+                            error(format!(
+                                "the number of inner steps must be a positive integer, found `{}`",
+                                value
+                            ))
+                        })?;
+                    }
+                    "free_ring_polymer" => {
+                        scheme = Some(value.parse().map_err(|err: ParseError| ParseError {
+                            line: Some(line),
+                            ..err
+                        })?);
+                    }
+                    name => return Err(error(format!("unknown setting `{}`", name))),
+                }
+            }
+            Ok(Self {
+                time_step: time_step.ok_or_else(|| ParseError {
+                    line: None,
+                    message: "missing setting `time_step`".to_owned(),
+                })?,
+                inner_steps,
+                scheme: scheme.unwrap_or_default(),
+            })
+        }
+    }
+
+    /// Adds `forces` to `momenta` over `time`.
+    fn kick<const N: usize, T, V>(time: T, momenta: &mut [V], forces: &[V])
+    where
+        T: Float,
+        V: Vector<N, Element = T> + Clone,
+    {
+        for (momentum, force) in momenta.iter_mut().zip(forces) {
+            momentum.mul_add_assign(force, time);
+        }
+    }
+
+    /// A velocity Verlet integrator of ring polymers in their normal modes.
+    ///
+    /// The physical forces kick the modes over half steps, and in between the free
+    /// ring polymer is evolved over the whole step by the evolution of the group
+    /// in its [`FreeRingPolymerCache`], so the springs do not limit the time step.
+    #[derive(Clone, Debug)]
+    pub struct RingPolymerVerlet<T> {
+        time_step: T,
+        cache: FreeRingPolymerCache<T>,
+    }
+
+    impl<T: Float + From<f32>> RingPolymerVerlet<T> {
+        pub fn new(time_step: T) -> Self {
+            assert!(time_step > T::zero(), "the time step must be positive");
+            Self {
+                time_step,
+                cache: FreeRingPolymerCache::new(),
+            }
+        }
+
+        /// Constructs an integrator with the time step and the scheme of `config`.
+        pub fn from_config(config: &RingPolymerConfig<T>) -> Self {
+            Self::new(config.time_step).with_scheme(config.scheme)
+        }
+
+        /// Evolves the free ring polymer by `scheme`.
+        pub fn with_scheme(mut self, scheme: FreeRingPolymerScheme) -> Self {
+            self.cache.set_scheme(scheme);
+            self
+        }
+
+        pub fn time_step(&self) -> T {
+            self.time_step
+        }
+
+        pub fn cache(&self) -> &FreeRingPolymerCache<T> {
+            &self.cache
+        }
+
+        pub fn cache_mut(&mut self) -> &mut FreeRingPolymerCache<T> {
+            &mut self.cache
+        }
+
+        /// Performs a full step of `group`, whose modes of mass `mass` at `temperature` have
+        /// the spring constants written by `eigenvalues`, and recomputes the physical forces
+        /// on the modes from their new positions with `calculate`,
+        /// which returns the physical potential energy.
+        ///
+        /// Returns the physical potential energy at the new positions.
+        #[allow(clippy::too_many_arguments)]
+        pub fn step<const N: usize, V, F, G, E>(
+            &mut self,
+            group: usize,
+            temperature: T,
+            mass: T,
+            positions: &mut [V],
+            momenta: &mut [V],
+            forces: &mut [V],
+            eigenvalues: G,
+            mut calculate: F,
+        ) -> Result<T, E>
+        where
+            V: Vector<N, Element = T> + Clone,
+            F: FnMut(&[V], &mut [V]) -> Result<T, E>,
+            G: FnOnce(&mut [T]) -> Result<(), E>,
+        {
+            let half_step = self.time_step * <T as From<f32>>::from(0.5);
+            kick(half_step, momenta, forces);
+            self.cache
+                .get_or_try_insert(
+                    group,
+                    self.time_step,
+                    temperature,
+                    mass,
+                    positions.len(),
+                    eigenvalues,
+                )?
+                .propagate(positions, momenta);
+            let energy = calculate(positions, forces)?;
+            kick(half_step, momenta, forces);
+            Ok(energy)
+        }
+    }
+
+    /// A reversible multiple time step (RESPA) integrator of ring polymers
+    /// in their normal modes.
+    ///
+    /// The slow forces kick the modes over half of the outer step, and in between
+    /// `inner_steps` velocity Verlet steps are taken under the fast forces, in which
+    /// the free ring polymer is evolved over the inner time step by the evolution of
+    /// the group in its [`FreeRingPolymerCache`]. The evolution over the inner time
+    /// step is cached apart from that over the outer one.
+    ///
+    /// With ring polymer contraction, the fast forces are the reference forces
+    /// of [`ContractedForces`](super::ContractedForces) and the slow forces its corrections.
+    #[derive(Clone, Debug)]
+    pub struct RingPolymerRespa<T> {
+        time_step: T,
+        inner_steps: NonZero<usize>,
+        cache: FreeRingPolymerCache<T>,
+    }
+
+    impl<T: Float + From<f32>> RingPolymerRespa<T> {
+        /// Constructs an integrator whose outer step of `time_step` is split
+        /// into `inner_steps` steps of the fast forces.
+        pub fn new(time_step: T, inner_steps: NonZero<usize>) -> Self {
+            assert!(time_step > T::zero(), "the time step must be positive");
+            Self {
+                time_step,
+                inner_steps,
+                cache: FreeRingPolymerCache::new(),
+            }
+        }
+
+        /// Constructs an integrator with the time step, the inner steps
+        /// and the scheme of `config`.
+        pub fn from_config(config: &RingPolymerConfig<T>) -> Self {
+            Self::new(config.time_step, config.inner_steps).with_scheme(config.scheme)
+        }
+
+        /// Evolves the free ring polymer by `scheme`.
+        pub fn with_scheme(mut self, scheme: FreeRingPolymerScheme) -> Self {
+            self.cache.set_scheme(scheme);
+            self
+        }
+
+        pub fn time_step(&self) -> T {
+            self.time_step
+        }
+
+        pub fn inner_steps(&self) -> NonZero<usize> {
+            self.inner_steps
+        }
+
+        pub fn inner_time_step(&self) -> T {
+            self.time_step / <T as From<f32>>::from(self.inner_steps.get() as f32)
+        }
+
+        pub fn cache(&self) -> &FreeRingPolymerCache<T> {
+            &self.cache
+        }
+
+        pub fn cache_mut(&mut self) -> &mut FreeRingPolymerCache<T> {
+            &mut self.cache
+        }
+
+        /// Performs an outer step of `group` as [`RingPolymerVerlet::step`], recomputing
+        /// the fast forces with `calculate_fast` after every inner step and the slow forces
+        /// with `calculate_slow` at the end of the outer step.
+        ///
+        /// Returns the fast and the slow potential energies at the new positions.
+        #[allow(clippy::too_many_arguments)]
+        pub fn step<const N: usize, V, F, S, G, E>(
+            &mut self,
+            group: usize,
+            temperature: T,
+            mass: T,
+            positions: &mut [V],
+            momenta: &mut [V],
+            fast_forces: &mut [V],
+            slow_forces: &mut [V],
+            eigenvalues: G,
+            mut calculate_fast: F,
+            mut calculate_slow: S,
+        ) -> Result<(T, T), E>
+        where
+            V: Vector<N, Element = T> + Clone,
+            F: FnMut(&[V], &mut [V]) -> Result<T, E>,
+            S: FnMut(&[V], &mut [V]) -> Result<T, E>,
+            G: FnOnce(&mut [T]) -> Result<(), E>,
+        {
+            let half = <T as From<f32>>::from(0.5);
+            let inner_time_step = self.inner_time_step();
+            kick(half * self.time_step, momenta, slow_forces);
+            let evolution = self.cache.get_or_try_insert(
+                group,
+                inner_time_step,
+                temperature,
+                mass,
+                positions.len(),
+                eigenvalues,
+            )?;
+            let mut fast_energy = T::zero();
+            for _ in 0..self.inner_steps.get() {
+                kick(half * inner_time_step, momenta, fast_forces);
+                evolution.propagate(positions, momenta);
+                fast_energy = calculate_fast(positions, fast_forces)?;
+                kick(half * inner_time_step, momenta, fast_forces);
+            }
+            let slow_energy = calculate_slow(positions, slow_forces)?;
+            kick(half * self.time_step, momenta, slow_forces);
+            Ok((fast_energy, slow_energy))
+        }
+    }
+}
+
+pub use ring_polymer::{RingPolymerConfig, RingPolymerRespa, RingPolymerVerlet};
+
+mod contraction {
+    use std::ops::{AddAssign, Mul};
+
+    use lib::potential::physical::Contraction;
+    use num::Float;
+
+    /// The force assembly of ring polymer contraction for a group in every image at once.
+    ///
+    /// A cheap reference potential is evaluated in every image of the full ring polymer,
+    /// while the difference between an expensive potential and the reference is evaluated
+    /// only in the images of the contracted ring polymer, whose positions are interpolated
+    /// by [`Contraction::contract`] and whose forces are spread back over the full ring polymer
+    /// by [`Contraction::expand_add`]. In [`RingPolymerRespa`](super::RingPolymerRespa)
+    /// the reference forces are the fast forces and the contracted differences the slow ones,
+    /// so the expensive potential is evaluated in few images and once per outer step.
+    ///
+    /// The positions and the forces of the contracted ring polymer are kept between steps.
+    #[derive(Clone, Debug)]
+    pub struct ContractedForces<T, V> {
+        contraction: Contraction<T>,
+        positions: Vec<Vec<V>>,
+        forces: Vec<Vec<V>>,
+    }
+
+    impl<T, V> ContractedForces<T, V>
+    where
+        T: Float + From<f32>,
+        V: Clone + Default + AddAssign + Mul<T, Output = V>,
+    {
+        pub fn new(contraction: Contraction<T>) -> Self {
+            Self {
+                positions: vec![Vec::new(); contraction.contracted_images()],
+                forces: vec![Vec::new(); contraction.contracted_images()],
+                contraction,
+            }
+        }
+
+        pub fn contraction(&self) -> &Contraction<T> {
+            &self.contraction
+        }
+
+        /// Returns the ratio of the number of images to the number of contracted images.
+        pub fn scale(&self) -> T {
+            <T as From<f32>>::from(self.contraction.images() as f32)
+                / <T as From<f32>>::from(self.contraction.contracted_images() as f32)
+        }
+
+        /// Sets the forces of the group in every image of the full ring polymer to the
+        /// reference forces, computed from its positions in the image by `reference`,
+        /// which returns the reference potential energy of the group in the image.
+        ///
+        /// Returns the reference potential energy summed over the images.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the numbers of images do not match the contraction.
+        pub fn reference<F, E>(
+            &self,
+            positions: &[&[V]],
+            forces: &mut [&mut [V]],
+            mut reference: F,
+        ) -> Result<T, E>
+        where
+            F: FnMut(usize, &[V], &mut [V]) -> Result<T, E>,
+        {
+            assert_eq!(positions.len(), self.contraction.images());
+            assert_eq!(forces.len(), self.contraction.images());
+            let mut energy = T::zero();
+            for (image, (positions, forces)) in positions.iter().zip(forces.iter_mut()).enumerate()
+            {
+                energy = energy + reference(image, positions, forces)?;
+            }
+            Ok(energy)
+        }
+
+        /// Contracts the positions of the group and adds to its forces in every image of the
+        /// full ring polymer the forces of the contracted images set by `difference`, which
+        /// sets the difference between the expensive and the reference forces and returns the
+        /// difference between the potential energies multiplied by [`scale`](Self::scale),
+        /// as [`ContractedPotentialPair::calculate_difference_set_forces`] does.
+        ///
+        /// Returns the correction to the physical potential energy of the full ring polymer.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the numbers of images do not match the contraction
+        /// or the group is not of the same size in every image.
+        ///
+        /// [`ContractedPotentialPair::calculate_difference_set_forces`]:
+        /// lib::potential::physical::ContractedPotentialPair::calculate_difference_set_forces
+        pub fn correction<F, E>(
+            &mut self,
+            positions: &[&[V]],
+            forces: &mut [&mut [V]],
+            mut difference: F,
+        ) -> Result<T, E>
+        where
+            F: FnMut(usize, &[V], &mut [V]) -> Result<T, E>,
+        {
+            let atoms = positions.first().map_or(0, |positions| positions.len());
+            for buffer in self.positions.iter_mut().chain(self.forces.iter_mut()) {
+                buffer.resize_with(atoms, V::default);
+            }
+            let mut contracted_positions: Vec<_> =
+                self.positions.iter_mut().map(Vec::as_mut_slice).collect();
+            self.contraction
+                .contract(positions, &mut contracted_positions);
+            let mut correction = T::zero();
+            for (contracted_image, (positions, forces)) in self
+                .positions
+                .iter()
+                .zip(self.forces.iter_mut())
+                .enumerate()
+            {
+                correction = correction + difference(contracted_image, positions, forces)?;
+            }
+            let contracted_forces: Vec<_> = self.forces.iter().map(Vec::as_slice).collect();
+            self.contraction
+                .expand_add(&contracted_forces, forces, self.scale());
+            Ok(correction)
+        }
+    }
+}
+
+pub use contraction::ContractedForces;
+
+mod fire {
+    use lib::core::Vector;
+    use num::Float;
+
+    use crate::minimize::{Fire, FireParameters, FireSums};
+
+    /// A pseudo-propagator relaxing the system with the FIRE algorithm instead of integrating
+    /// its dynamics, so that a configuration can be relaxed by the same loop,
+    /// force evaluation and storage as a simulation.
+    ///
+    /// The momenta serve as the velocities of atoms of unit mass and no thermostat is applied,
+    /// so the heat returned by [`FirePropagator::propagate`] is always zero.
+    #[derive(Clone, Copy, Debug)]
+    pub struct FirePropagator<T> {
+        fire: Fire<T>,
+    }
+
+    impl<T: Float + From<f32>> FirePropagator<T> {
+        pub fn new(parameters: FireParameters<T>) -> Self {
+            Self {
+                fire: Fire::new(parameters),
+            }
+        }
+
+        pub fn fire(&self) -> &Fire<T> {
+            &self.fire
+        }
+
+        /// Performs a FIRE step of a group under the sum of `physical_forces` and `exchange_forces`
+        /// and recomputes the physical forces at the new positions with `calculate`,
+        /// which returns the physical potential energy of the group.
+        ///
+        /// `sums` are the [`FireSums`] over the whole system with the current momenta and forces,
+        /// or `None` to use those of this group alone, as when it is the only one.
+        ///
+        /// Returns the physical and exchange potential energies and the heat,
+        /// in the order of [`Propagator::propagate`](lib::propagator::Propagator::propagate),
+        /// where the exchange energy is left to the caller and reported as zero.
+        pub fn propagate<const N: usize, V, F, E>(
+            &mut self,
+            sums: Option<FireSums<T>>,
+            positions: &mut [V],
+            momenta: &mut [V],
+            physical_forces: &mut [V],
+            exchange_forces: &[V],
+            mut calculate: F,
+        ) -> Result<(T, T, T), E>
+        where
+            V: Vector<N, Element = T> + Clone,
+            F: FnMut(&[V], &mut [V]) -> Result<T, E>,
+        {
+            let forces: Vec<V> = physical_forces
+                .iter()
+                .zip(exchange_forces)
+                .map(|(physical, exchange)| physical.clone() + exchange.clone())
+                .collect();
+            let sums = sums.unwrap_or_else(|| FireSums::of(momenta, &forces));
+            let downhill = self.fire.adapt(&sums);
+            self.fire.mix(downhill, &sums, momenta, &forces);
+            self.fire.integrate(positions, momenta, &forces);
+            let physical_energy = calculate(positions, physical_forces)?;
+            Ok((physical_energy, T::zero(), T::zero()))
+        }
+    }
+}
+
+pub use fire::FirePropagator;
@@ -0,0 +1,601 @@
+//! The exact evolution of the free ring polymer, the harmonic springs between the images,
+//! in the normal-mode representation.
+//!
+//! Every mode is a harmonic oscillator whose spring constant is its eigenvalue in the transform,
+//! so over a time step its position and momentum evolve by a rotation in phase space.
+//! Propagating the springs exactly rather than by Verlet steps removes the stiffest
+//! frequencies from the choice of the time step. The evolutions are cached per group
+//! and used by [`RingPolymerVerlet`] and [`RingPolymerRespa`].
+//!
+//! [`RingPolymerVerlet`]: crate::propagator::RingPolymerVerlet
+//! [`RingPolymerRespa`]: crate::propagator::RingPolymerRespa
+//!
+//! The exact rotation of the stiffest modes by arbitrary angles can resonate with a thermostat
+//! or the physical forces at large time steps, so the Cayley approximation of the rotation,
+//! which is symplectic, stable for any stiffness and tends to a half turn for stiff modes,
+//! may be selected instead with a [`FreeRingPolymerScheme`].
+
+mod evolution {
+    use std::str::FromStr;
+
+    use lib::core::Vector;
+    use num::Float;
+
+    use crate::steering::ParseError;
+
+    /// The way the evolution of a mode is computed.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum FreeRingPolymerScheme {
+        /// The exact rotation in phase space.
+        #[default]
+        Exact,
+        /// The Cayley transform of the generator of the rotation,
+        /// `(1 - A dt / 2)^-1 (1 + A dt / 2)`.
+        Cayley,
+    }
+
+    impl FromStr for FreeRingPolymerScheme {
+        type Err = ParseError;
+
+        /// Parses `exact` or `cayley`, as the value of a switch in a configuration file.
+        fn from_str(name: &str) -> Result<Self, Self::Err> {
+            match name.trim() {
+                "exact" => Ok(Self::Exact),
+                "cayley" => Ok(Self::Cayley),
+                name => Err(ParseError {
+                    line: None,
+                    message: format!(
+                        "unknown free ring polymer scheme `{}`, expected `exact` or `cayley`",
+                        name
+                    ),
+                }),
+            }
+        }
+    }
+
+    /// The exact evolution of a single harmonic mode over a time step,
+    /// `(q, p) -> (c q + s / (m ω) p, -m ω s q + c p)` with `c = cos ω dt` and `s = sin ω dt`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct ModeEvolution<T> {
+        matrix: [[T; 2]; 2],
+    }
+
+    impl<T: Float> ModeEvolution<T> {
+        /// Constructs the evolution of a mode with spring constant `eigenvalue`
+        /// and mass `mass` over `time_step`.
+        ///
+        /// A mode with a zero eigenvalue, such as the centroid, moves freely.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `eigenvalue` is negative or `mass` is not positive.
+        pub fn new(eigenvalue: T, mass: T, time_step: T) -> Self {
+            Self::with_scheme(eigenvalue, mass, time_step, FreeRingPolymerScheme::Exact)
+        }
+
+        /// Constructs the evolution of a mode as [`ModeEvolution::new`], computed by `scheme`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `eigenvalue` is negative or `mass` is not positive.
+        pub fn with_scheme(
+            eigenvalue: T,
+            mass: T,
+            time_step: T,
+            scheme: FreeRingPolymerScheme,
+        ) -> Self {
+            assert!(
+                eigenvalue >= T::zero(),
+                "the eigenvalue must be non-negative"
+            );
+            assert!(mass > T::zero(), "the mass must be positive");
+            if eigenvalue == T::zero() {
+                return Self {
+                    matrix: [[T::one(), time_step / mass], [T::zero(), T::one()]],
+                };
+            }
+            if scheme == FreeRingPolymerScheme::Cayley {
+                let quarter =
+                    (eigenvalue / mass) * time_step * time_step / (T::one() + T::one()).powi(2);
+                let denominator = T::one() + quarter;
+                let diagonal = (T::one() - quarter) / denominator;
+                return Self {
+                    matrix: [
+                        [diagonal, time_step / (mass * denominator)],
+                        [-eigenvalue * time_step / denominator, diagonal],
+                    ],
+                };
+            }
+            let frequency = (eigenvalue / mass).sqrt();
+            let (sin, cos) = (frequency * time_step).sin_cos();
+            let impedance = mass * frequency;
+            Self {
+                matrix: [[cos, sin / impedance], [-impedance * sin, cos]],
+            }
+        }
+
+        /// Returns the matrix mapping the position and the momentum of the mode
+        /// at the beginning of the step to those at its end.
+        pub fn matrix(&self) -> [[T; 2]; 2] {
+            self.matrix
+        }
+
+        /// Evolves the position and the momentum of the mode by a step.
+        pub fn apply<const N: usize, V>(&self, position: &mut V, momentum: &mut V)
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let [[a, b], [c, d]] = self.matrix;
+            let new_position = position.clone() * a + momentum.clone() * b;
+            *momentum = position.clone() * c + momentum.clone() * d;
+            *position = new_position;
+        }
+    }
+}
+
+pub use evolution::{FreeRingPolymerScheme, ModeEvolution};
+
+mod cache {
+    use std::collections::{HashMap, hash_map::Entry};
+
+    use lib::{
+        core::{Vector, resolution::BeadCountDependent},
+        numeric::Real,
+    };
+    use num::Float;
+
+    use super::{FreeRingPolymerScheme, ModeEvolution};
+    use crate::potential::exchange::RingPolymerParameters;
+
+    /// The evolution of every mode of a group over a time step at a temperature.
+    #[derive(Clone, Debug)]
+    pub struct FreeRingPolymer<T> {
+        modes: Box<[ModeEvolution<T>]>,
+    }
+
+    impl<T: Float> FreeRingPolymer<T> {
+        /// Constructs the evolution of modes with spring constants `eigenvalues`
+        /// and mass `mass` over `time_step`, computed by `scheme`.
+        pub fn new(
+            eigenvalues: &[T],
+            mass: T,
+            time_step: T,
+            scheme: FreeRingPolymerScheme,
+        ) -> Self {
+            Self {
+                modes: eigenvalues
+                    .iter()
+                    .map(|&eigenvalue| {
+                        ModeEvolution::with_scheme(eigenvalue, mass, time_step, scheme)
+                    })
+                    .collect(),
+            }
+        }
+
+        /// Constructs the evolution of the modes of a closed ring polymer
+        /// of an atom of mass `mass` with `parameters` over `time_step`, computed by `scheme`.
+        pub fn from_parameters(
+            parameters: &RingPolymerParameters<T>,
+            mass: T,
+            time_step: T,
+            scheme: FreeRingPolymerScheme,
+        ) -> Self
+        where
+            T: Real,
+        {
+            let mut eigenvalues = vec![T::zero(); parameters.beads()];
+            parameters.normal_mode_eigenvalues(mass, &mut eigenvalues);
+            Self::new(&eigenvalues, mass, time_step, scheme)
+        }
+
+        pub fn modes(&self) -> &[ModeEvolution<T>] {
+            &self.modes
+        }
+
+        /// Evolves the positions and the momenta of the modes of the group by a step.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the number of positions or momenta differs from the number of modes.
+        pub fn propagate<const N: usize, V>(&self, positions: &mut [V], momenta: &mut [V])
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            assert_eq!(
+                positions.len(),
+                self.modes.len(),
+                "every mode must have a position"
+            );
+            assert_eq!(
+                momenta.len(),
+                self.modes.len(),
+                "every mode must have a momentum"
+            );
+            for ((mode, position), momentum) in self.modes.iter().zip(positions).zip(momenta) {
+                mode.apply(position, momentum);
+            }
+        }
+    }
+
+    /// The evolutions of the groups, kept between steps and recomputed only
+    /// when a group is propagated over a time step or at a temperature it has not been before,
+    /// e.g. by the inner steps of a multiple time step scheme or during annealing.
+    #[derive(Clone, Debug, Default)]
+    pub struct FreeRingPolymerCache<T> {
+        scheme: FreeRingPolymerScheme,
+        entries: HashMap<(usize, u64, u64), FreeRingPolymer<T>>,
+    }
+
+    impl<T: Float> FreeRingPolymerCache<T> {
+        pub fn new() -> Self {
+            Self::with_scheme(FreeRingPolymerScheme::Exact)
+        }
+
+        pub fn with_scheme(scheme: FreeRingPolymerScheme) -> Self {
+            Self {
+                scheme,
+                entries: HashMap::new(),
+            }
+        }
+
+        pub fn scheme(&self) -> FreeRingPolymerScheme {
+            self.scheme
+        }
+
+        /// Switches to `scheme`, discarding the evolutions computed by the previous one.
+        pub fn set_scheme(&mut self, scheme: FreeRingPolymerScheme) {
+            if scheme != self.scheme {
+                self.scheme = scheme;
+                self.entries.clear();
+            }
+        }
+
+        /// Returns the evolution of `group` over `time_step` at `temperature`,
+        /// computing it from the eigenvalues written by `eigenvalues`, e.g. the `eigenvalues`
+        /// method of the transform of the group, if it is not cached.
+        pub fn get_or_try_insert<E>(
+            &mut self,
+            group: usize,
+            time_step: T,
+            temperature: T,
+            mass: T,
+            modes: usize,
+            eigenvalues: impl FnOnce(&mut [T]) -> Result<(), E>,
+        ) -> Result<&FreeRingPolymer<T>, E> {
+            let entry = self
+                .entries
+                .entry((group, bits(time_step), bits(temperature)));
+            Ok(match entry {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let mut values = vec![T::zero(); modes];
+                    eigenvalues(&mut values)?;
+                    entry.insert(FreeRingPolymer::new(&values, mass, time_step, self.scheme))
+                }
+            })
+        }
+
+        /// Returns the number of cached evolutions.
+        pub fn len(&self) -> usize {
+            self.entries.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.entries.is_empty()
+        }
+
+        /// Discards the evolutions of every group, e.g. after the masses have changed.
+        pub fn clear(&mut self) {
+            self.entries.clear();
+        }
+    }
+
+    /// The modes of every group change with the number of images.
+    impl<T> BeadCountDependent for FreeRingPolymerCache<T> {
+        fn set_beads(&mut self, _beads: usize) {
+            self.entries.clear();
+        }
+    }
+
+    /// Returns a key which is equal for equal values.
+    fn bits<T: Float>(value: T) -> u64 {
+        value.to_f64().unwrap_or(f64::NAN).to_bits()
+    }
+}
+
+pub use cache::{FreeRingPolymer, FreeRingPolymerCache};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib::core::Vector;
+    use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+    /// The coordinate of a mode.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Position([f64; 1]);
+
+    impl From<[f64; 1]> for Position {
+        fn from(array: [f64; 1]) -> Self {
+            Self(array)
+        }
+    }
+
+    impl Add for Position {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self {
+            Self([self.0[0] + rhs.0[0]])
+        }
+    }
+
+    impl AddAssign for Position {
+        fn add_assign(&mut self, rhs: Self) {
+            self.0[0] += rhs.0[0];
+        }
+    }
+
+    impl Sub for Position {
+        type Output = Self;
+
+        fn sub(self, rhs: Self) -> Self {
+            Self([self.0[0] - rhs.0[0]])
+        }
+    }
+
+    impl SubAssign for Position {
+        fn sub_assign(&mut self, rhs: Self) {
+            self.0[0] -= rhs.0[0];
+        }
+    }
+
+    impl Mul<f64> for Position {
+        type Output = Self;
+
+        fn mul(self, rhs: f64) -> Self {
+            Self([self.0[0] * rhs])
+        }
+    }
+
+    impl MulAssign<f64> for Position {
+        fn mul_assign(&mut self, rhs: f64) {
+            self.0[0] *= rhs;
+        }
+    }
+
+    impl Div<f64> for Position {
+        type Output = Self;
+
+        fn div(self, rhs: f64) -> Self {
+            Self([self.0[0] / rhs])
+        }
+    }
+
+    impl DivAssign<f64> for Position {
+        fn div_assign(&mut self, rhs: f64) {
+            self.0[0] /= rhs;
+        }
+    }
+
+    impl Neg for Position {
+        type Output = Self;
+
+        fn neg(self) -> Self {
+            Self([-self.0[0]])
+        }
+    }
+
+    impl Vector<1> for Position {
+        type Element = f64;
+
+        fn zero() -> Self {
+            Self([0.0])
+        }
+
+        fn splat(value: f64) -> Self {
+            Self([value])
+        }
+
+        fn from_array(array: [f64; 1]) -> Self {
+            Self(array)
+        }
+
+        fn as_array(&self) -> &[f64; 1] {
+            &self.0
+        }
+
+        fn as_mut_array(&mut self) -> &mut [f64; 1] {
+            &mut self.0
+        }
+
+        fn magnitude_squared(self) -> f64 {
+            self.0[0] * self.0[0]
+        }
+
+        fn dot(self, rhs: Self) -> f64 {
+            self.0[0] * rhs.0[0]
+        }
+
+        fn mul_add_assign(&mut self, a: &Self, b: f64) {
+            self.0[0] += a.0[0] * b;
+        }
+    }
+
+    type Matrix = [[f64; 2]; 2];
+
+    fn product(a: Matrix, b: Matrix) -> Matrix {
+        std::array::from_fn(|i| std::array::from_fn(|j| a[i][0] * b[0][j] + a[i][1] * b[1][j]))
+    }
+
+    /// Returns `e^A` by the Taylor series of `A / 2^s`, squared `s` times.
+    fn exponential(a: Matrix) -> Matrix {
+        let norm = a.iter().flatten().map(|x| x.abs()).sum::<f64>();
+        let squarings = norm.log2().ceil().max(0.0) as i32 + 4;
+        let scale = 2f64.powi(-squarings);
+        let scaled = a.map(|row| row.map(|x| x * scale));
+        let mut sum = [[1.0, 0.0], [0.0, 1.0]];
+        let mut term = sum;
+        for order in 1..30 {
+            term = product(term, scaled).map(|row| row.map(|x| x / order as f64));
+            sum = std::array::from_fn(|i| std::array::from_fn(|j| sum[i][j] + term[i][j]));
+        }
+        for _ in 0..squarings {
+            sum = product(sum, sum);
+        }
+        sum
+    }
+
+    #[test]
+    fn exact_evolution_is_the_matrix_exponential() {
+        for (eigenvalue, mass, time_step) in [
+            (0.0, 1.0, 0.1),
+            (1.0, 1.0, 0.1),
+            (4.0, 0.5, 0.3),
+            (2500.0, 2.0, 0.05),
+            (1e4, 1.0, 1.0),
+        ] {
+            // `d(q, p)/dt = A (q, p)` with `A = [[0, 1 / m], [-k, 0]]`.
+            let generator = [[0.0, time_step / mass], [-eigenvalue * time_step, 0.0]];
+            let expected = exponential(generator);
+            let matrix = ModeEvolution::new(eigenvalue, mass, time_step).matrix();
+            for (row, expected_row) in matrix.iter().zip(expected) {
+                for (element, expected) in row.iter().zip(expected_row) {
+                    assert!(
+                        (element - expected).abs() < 1e-9 * (1.0 + expected.abs()),
+                        "k = {}, m = {}, dt = {}: {:?} instead of {:?}",
+                        eigenvalue,
+                        mass,
+                        time_step,
+                        matrix,
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cache_reuses_the_evolution_of_a_group() {
+        let mut cache = FreeRingPolymerCache::<f64>::new();
+        let mut computed = 0;
+        for (group, time_step) in [(0, 0.1), (0, 0.1), (1, 0.1), (0, 0.05)] {
+            cache
+                .get_or_try_insert(group, time_step, 1.0, 1.0, 4, |eigenvalues| {
+                    computed += 1;
+                    eigenvalues.copy_from_slice(&[0.0, 2.0, 4.0, 2.0]);
+                    Ok::<_, ()>(())
+                })
+                .unwrap();
+        }
+        assert_eq!(computed, 3);
+        assert_eq!(cache.len(), 3);
+    }
+
+    /// Returns the energy `p² / 2m + k q² / 2` of a mode.
+    fn energy(eigenvalue: f64, mass: f64, [position, momentum]: [f64; 2]) -> f64 {
+        0.5 * momentum * momentum / mass + 0.5 * eigenvalue * position * position
+    }
+
+    /// Returns the largest relative deviation of the energy of a mode from its initial value
+    /// over `steps` applications of `matrix`.
+    fn energy_drift(matrix: Matrix, eigenvalue: f64, mass: f64, steps: usize) -> f64 {
+        let mut state = [1.0, 0.3];
+        let initial = energy(eigenvalue, mass, state);
+        let mut drift = 0.0f64;
+        for _ in 0..steps {
+            state = std::array::from_fn(|i| matrix[i][0] * state[0] + matrix[i][1] * state[1]);
+            drift = drift.max((energy(eigenvalue, mass, state) / initial - 1.0).abs());
+        }
+        drift
+    }
+
+    #[test]
+    fn stiff_modes_stay_bounded_under_both_schemes() {
+        let (mass, time_step): (f64, f64) = (1.0, 0.1);
+        // `ω dt` from 2.5 to 100, where a Verlet step of the springs is unstable.
+        for eigenvalue in [625.0, 1e4, 1e6] {
+            let frequency_step = (eigenvalue / mass).sqrt() * time_step;
+            assert!(frequency_step > 2.0);
+            let verlet = {
+                let half = 0.5 * frequency_step * frequency_step;
+                [
+                    [1.0 - half, time_step / mass],
+                    [-eigenvalue * time_step * (1.0 - 0.5 * half), 1.0 - half],
+                ]
+            };
+            assert!(energy_drift(verlet, eigenvalue, mass, 100) > 1e6);
+            for scheme in [FreeRingPolymerScheme::Exact, FreeRingPolymerScheme::Cayley] {
+                let matrix =
+                    ModeEvolution::with_scheme(eigenvalue, mass, time_step, scheme).matrix();
+                let drift = energy_drift(matrix, eigenvalue, mass, 100_000);
+                assert!(
+                    drift < 1e-9,
+                    "{:?} at ω dt = {}: the energy drifted by {}",
+                    scheme,
+                    frequency_step,
+                    drift
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn configured_cayley_scheme_is_stable_with_physical_forces() {
+        use crate::propagator::{RingPolymerConfig, RingPolymerVerlet};
+
+        let config = RingPolymerConfig::<f64>::parse(
+            "time_step = 0.1\nfree_ring_polymer = cayley # stable for any stiffness\n",
+        )
+        .unwrap();
+        assert_eq!(config.scheme, FreeRingPolymerScheme::Cayley);
+        let mut integrator = RingPolymerVerlet::from_config(&config);
+        assert_eq!(integrator.cache().scheme(), FreeRingPolymerScheme::Cayley);
+
+        // The centroid and a mode with `ω dt = 10` in a weak physical trap.
+        let eigenvalues = [0.0, 1e4];
+        let trap = 1.0;
+        let physical = |positions: &[Position], forces: &mut [Position]| {
+            let mut energy = 0.0;
+            for (position, force) in positions.iter().zip(forces) {
+                *force = *position * -trap;
+                energy += 0.5 * trap * position.dot(*position);
+            }
+            Ok::<_, ()>(energy)
+        };
+        let total = |positions: &[Position], momenta: &[Position], physical: f64| {
+            positions
+                .iter()
+                .zip(momenta)
+                .zip(eigenvalues)
+                .map(|((position, momentum), eigenvalue)| {
+                    0.5 * momentum.dot(*momentum) + 0.5 * eigenvalue * position.dot(*position)
+                })
+                .sum::<f64>()
+                + physical
+        };
+        let mut positions = [Position([1.0]), Position([0.01])];
+        let mut momenta = [Position([0.0]), Position([1.0])];
+        let mut forces = [Position::zero(); 2];
+        let mut potential = physical(&positions, &mut forces).unwrap();
+        let initial = total(&positions, &momenta, potential);
+        for _ in 0..100_000 {
+            potential = integrator
+                .step(
+                    0,
+                    1.0,
+                    1.0,
+                    &mut positions,
+                    &mut momenta,
+                    &mut forces,
+                    |values: &mut [f64]| {
+                        values.copy_from_slice(&eigenvalues);
+                        Ok(())
+                    },
+                    physical,
+                )
+                .unwrap();
+            let drift = (total(&positions, &momenta, potential) / initial - 1.0).abs();
+            assert!(drift < 0.1, "the energy drifted by {}", drift);
+        }
+    }
+}
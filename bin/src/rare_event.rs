@@ -0,0 +1,794 @@
+mod interfaces {
+    use num::Float;
+
+    use crate::colvar::CollectiveVariable;
+
+    /// Interfaces `λ_0 < λ_1 < ... < λ_n` of a collective variable `λ` between two stable states,
+    /// where the initial state `A` is `λ < λ_0` and the final state `B` is `λ ≥ λ_n`.
+    #[derive(Clone, Debug)]
+    pub struct Interfaces<T, C> {
+        variable: C,
+        boundaries: Box<[T]>,
+    }
+
+    impl<T: Float, C> Interfaces<T, C> {
+        /// Places the interfaces at the values `boundaries` of `variable`.
+        pub fn new(variable: C, boundaries: Vec<T>) -> Self {
+            assert!(boundaries.len() >= 2, "the states need an interface each");
+            assert!(
+                boundaries.windows(2).all(|pair| pair[0] < pair[1]),
+                "the interfaces must be strictly increasing"
+            );
+            Self {
+                variable,
+                boundaries: boundaries.into_boxed_slice(),
+            }
+        }
+
+        pub fn variable(&self) -> &C {
+            &self.variable
+        }
+
+        /// Returns the values of the variable at the interfaces.
+        pub fn boundaries(&self) -> &[T] {
+            &self.boundaries
+        }
+
+        /// Returns the number of interfaces, including those of the states.
+        pub fn len(&self) -> usize {
+            self.boundaries.len()
+        }
+
+        /// Returns whether there are no interfaces, which never holds.
+        pub fn is_empty(&self) -> bool {
+            self.boundaries.is_empty()
+        }
+
+        /// Returns the value of the variable for the atoms at `positions`.
+        pub fn value<V>(&self, positions: &[V]) -> T
+        where
+            C: CollectiveVariable<T, V>,
+        {
+            self.variable.value(positions)
+        }
+
+        /// Returns whether `value` lies in the initial state.
+        pub fn in_initial(&self, value: T) -> bool {
+            value < self.boundaries[0]
+        }
+
+        /// Returns whether `value` lies in the final state.
+        pub fn in_final(&self, value: T) -> bool {
+            value >= self.boundaries[self.boundaries.len() - 1]
+        }
+    }
+}
+
+pub use interfaces::Interfaces;
+
+mod system {
+    use std::io::{self, BufRead, Write};
+
+    /// A state of a simulation which can be saved to and restored from a checkpoint.
+    pub trait StateCheckpoint: Sized {
+        fn write_checkpoint<W: Write>(&self, writer: W) -> io::Result<()>;
+
+        fn read_checkpoint<R: BufRead>(reader: R) -> io::Result<Self>;
+    }
+
+    /// A simulation driven by a rare-event sampling scheme, which only
+    /// advances, inspects and branches states it is given.
+    pub trait PathSystem<V> {
+        /// The full state of the simulation, including the momenta and the thermostat.
+        type State: Clone + StateCheckpoint;
+        /// The type associated with an error returned by the implementor.
+        type Error;
+
+        /// Advances `state` by the interval between two evaluations of the collective variable.
+        fn advance(&mut self, state: &mut Self::State) -> Result<(), Self::Error>;
+
+        /// Returns the positions of the atoms in `state`.
+        fn positions<'a>(&self, state: &'a Self::State) -> &'a [V];
+
+        /// Prepares a copy of a stored state for a new trajectory, e.g. by redrawing the momenta
+        /// or reseeding the thermostat with `seed`, so that branches from the same state diverge.
+        fn branch(&mut self, state: &mut Self::State, seed: u64) -> Result<(), Self::Error>;
+    }
+
+    /// A simulation whose dynamics can be run backwards in time.
+    pub trait TimeReversible<V>: PathSystem<V> {
+        /// Reverses the momenta of `state`, so that advancing it retraces its past.
+        fn reverse(&mut self, state: &mut Self::State);
+    }
+}
+
+pub use system::{PathSystem, StateCheckpoint, TimeReversible};
+
+mod forward_flux {
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        fs,
+        io::{self, BufReader, Write},
+        path::{Path, PathBuf},
+    };
+
+    use num::Float;
+    use rand::{
+        Rng,
+        distr::{Distribution, StandardUniform},
+    };
+
+    use super::{Interfaces, PathSystem, StateCheckpoint};
+    use crate::colvar::CollectiveVariable;
+
+    /// An error which stopped forward flux sampling.
+    #[derive(Debug)]
+    pub enum ForwardFluxError<E> {
+        /// Reading or writing a checkpoint failed.
+        Io(io::Error),
+        /// The simulation failed.
+        System(E),
+        /// The initial state given to the run does not lie in the initial state of the interfaces.
+        NotInitial,
+        /// A trial trajectory has neither reached the next interface
+        /// nor returned to the initial state within the allowed number of advances.
+        Stuck {
+            /// The index of the interface the trajectory started from.
+            interface: usize,
+        },
+    }
+
+    impl<E> From<io::Error> for ForwardFluxError<E> {
+        fn from(value: io::Error) -> Self {
+            Self::Io(value)
+        }
+    }
+
+    impl<E: Display> Display for ForwardFluxError<E> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Io(err) => write!(f, "forward flux checkpoint I/O failed: {}", err),
+                Self::System(err) => write!(f, "forward flux simulation failed: {}", err),
+                Self::NotInitial => write!(f, "the simulation does not start in the initial state"),
+                Self::Stuck { interface } => write!(
+                    f,
+                    "a trial from interface #{} neither advanced nor returned",
+                    interface
+                ),
+            }
+        }
+    }
+
+    impl<E: Error + 'static> Error for ForwardFluxError<E> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::Io(err) => Some(err),
+                Self::System(err) => Some(err),
+                _ => None,
+            }
+        }
+    }
+
+    /// The outcome of a forward flux sampling run.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct ForwardFluxResults<T> {
+        /// The number of times the first interface was crossed out of the initial state.
+        pub crossings: usize,
+        /// The time simulated to count the crossings.
+        pub flux_time: T,
+        /// The successes and the trials of the stage from every interface to the next,
+        /// ending at the first stage without successes.
+        pub stages: Vec<(usize, usize)>,
+    }
+
+    impl<T: Float + From<f32>> ForwardFluxResults<T> {
+        /// Returns the flux `Φ_A,0` of trajectories leaving the initial state through the first interface.
+        pub fn flux(&self) -> T {
+            <T as From<f32>>::from(self.crossings as f32) / self.flux_time
+        }
+
+        /// Returns the probability `P(λ_{i+1} | λ_i)` of reaching the next interface from
+        /// the interface at `interface` before returning to the initial state,
+        /// or `None` if the stage was not run.
+        pub fn conditional_probability(&self, interface: usize) -> Option<T> {
+            self.stages.get(interface).map(|&(successes, trials)| {
+                <T as From<f32>>::from(successes as f32) / <T as From<f32>>::from(trials as f32)
+            })
+        }
+
+        /// Returns the committor estimate `P(λ_n | λ_i)` of the interface at `interface`,
+        /// the probability of reaching the final state before the initial one.
+        pub fn committor(&self, interface: usize, interfaces: usize) -> T {
+            (interface..interfaces - 1)
+                .map(|stage| self.conditional_probability(stage).unwrap_or(T::zero()))
+                .fold(T::one(), |product, probability| product * probability)
+        }
+
+        /// Returns the rate constant `k_AB = Φ_A,0 P(λ_n | λ_0)`.
+        pub fn rate(&self, interfaces: usize) -> T {
+            self.flux() * self.committor(0, interfaces)
+        }
+    }
+
+    /// Forward flux sampling of the transitions between the states bounded by a set of interfaces.
+    ///
+    /// A simulation in the initial state collects the configurations at which it crosses
+    /// the first interface; then, from every interface, trial trajectories branched from
+    /// configurations picked at random are run until they reach the next interface,
+    /// where their configurations are collected, or fall back into the initial state.
+    /// The rate constant is the flux through the first interface times the product
+    /// of the fractions of successful trials.
+    ///
+    /// The configurations and the counts of every finished stage are checkpointed in
+    /// `interface-<index>` under the checkpoint directory, so an interrupted run
+    /// resumes from the last interface it reached.
+    pub struct ForwardFlux<T, C, R> {
+        interfaces: Interfaces<T, C>,
+        checkpoint_dir: PathBuf,
+        time_per_advance: T,
+        flux_advances: usize,
+        trials: usize,
+        max_advances: usize,
+        rng: R,
+    }
+
+    impl<T, C, R> ForwardFlux<T, C, R>
+    where
+        T: Float + From<f32>,
+        R: Rng,
+    {
+        /// Constructs a driver counting crossings over `flux_advances` advances of
+        /// `time_per_advance` each and running `trials` trials from every interface,
+        /// each of which may advance at most `max_advances` times.
+        pub fn new<P: Into<PathBuf>>(
+            interfaces: Interfaces<T, C>,
+            checkpoint_dir: P,
+            time_per_advance: T,
+            flux_advances: usize,
+            trials: usize,
+            max_advances: usize,
+            rng: R,
+        ) -> Self {
+            assert!(
+                time_per_advance > T::zero(),
+                "the time per advance must be positive"
+            );
+            assert!(flux_advances > 0, "the flux must be measured");
+            assert!(trials > 0, "every interface needs trials");
+            Self {
+                interfaces,
+                checkpoint_dir: checkpoint_dir.into(),
+                time_per_advance,
+                flux_advances,
+                trials,
+                max_advances,
+                rng,
+            }
+        }
+
+        pub fn interfaces(&self) -> &Interfaces<T, C> {
+            &self.interfaces
+        }
+
+        fn stage_dir(&self, interface: usize) -> PathBuf {
+            self.checkpoint_dir.join(format!("interface-{}", interface))
+        }
+
+        fn seed(&mut self) -> u64 {
+            StandardUniform.sample(&mut self.rng)
+        }
+
+        /// Runs forward flux sampling starting from `initial`, a state in the initial state.
+        pub fn run<V, S>(
+            &mut self,
+            system: &mut S,
+            initial: S::State,
+        ) -> Result<ForwardFluxResults<T>, ForwardFluxError<S::Error>>
+        where
+            S: PathSystem<V>,
+            C: CollectiveVariable<T, V>,
+        {
+            if !self
+                .interfaces
+                .in_initial(self.interfaces.value(system.positions(&initial)))
+            {
+                return Err(ForwardFluxError::NotInitial);
+            }
+            let flux_time =
+                self.time_per_advance * <T as From<f32>>::from(self.flux_advances as f32);
+            let mut configurations = match load_stage(&self.stage_dir(0))? {
+                Some((configurations, _)) => configurations,
+                None => {
+                    let configurations = self.collect_crossings(system, &initial)?;
+                    let counts = [("crossings", configurations.len())];
+                    store_stage(&self.stage_dir(0), &configurations, &counts)?;
+                    configurations
+                }
+            };
+            let crossings = configurations.len();
+            let mut stages = Vec::new();
+            for interface in 0..self.interfaces.len() - 1 {
+                if configurations.is_empty() {
+                    break;
+                }
+                let dir = self.stage_dir(interface + 1);
+                let (next, trials) = match load_stage(&dir)? {
+                    Some(stage) => stage,
+                    None => {
+                        let next = self.run_stage(system, interface, &configurations)?;
+                        let counts = [("successes", next.len()), ("trials", self.trials)];
+                        store_stage(&dir, &next, &counts)?;
+                        (next, self.trials)
+                    }
+                };
+                stages.push((next.len(), trials));
+                configurations = next;
+            }
+            Ok(ForwardFluxResults {
+                crossings,
+                flux_time,
+                stages,
+            })
+        }
+
+        /// Collects the configurations at which a simulation in the initial state
+        /// crosses the first interface.
+        ///
+        /// A simulation reaching the final state is restarted from a branch of `initial`.
+        fn collect_crossings<V, S>(
+            &mut self,
+            system: &mut S,
+            initial: &S::State,
+        ) -> Result<Vec<S::State>, ForwardFluxError<S::Error>>
+        where
+            S: PathSystem<V>,
+            C: CollectiveVariable<T, V>,
+        {
+            let mut configurations = Vec::new();
+            let mut state = initial.clone();
+            let mut armed = true;
+            for _ in 0..self.flux_advances {
+                system
+                    .advance(&mut state)
+                    .map_err(ForwardFluxError::System)?;
+                let value = self.interfaces.value(system.positions(&state));
+                if self.interfaces.in_final(value) {
+                    state = initial.clone();
+                    let seed = self.seed();
+                    system
+                        .branch(&mut state, seed)
+                        .map_err(ForwardFluxError::System)?;
+                    armed = true;
+                } else if self.interfaces.in_initial(value) {
+                    armed = true;
+                } else if armed {
+                    configurations.push(state.clone());
+                    armed = false;
+                }
+            }
+            Ok(configurations)
+        }
+
+        /// Runs the trials from the interface at `interface` and returns the configurations
+        /// at which they have reached the next one.
+        fn run_stage<V, S>(
+            &mut self,
+            system: &mut S,
+            interface: usize,
+            configurations: &[S::State],
+        ) -> Result<Vec<S::State>, ForwardFluxError<S::Error>>
+        where
+            S: PathSystem<V>,
+            C: CollectiveVariable<T, V>,
+        {
+            let target = self.interfaces.boundaries()[interface + 1];
+            let mut successes = Vec::new();
+            for _ in 0..self.trials {
+                let uniform: f64 = StandardUniform.sample(&mut self.rng);
+                let pick = ((uniform * configurations.len() as f64) as usize)
+                    .min(configurations.len() - 1);
+                let mut state = configurations[pick].clone();
+                let seed = self.seed();
+                system
+                    .branch(&mut state, seed)
+                    .map_err(ForwardFluxError::System)?;
+                let mut finished = false;
+                for _ in 0..self.max_advances {
+                    system
+                        .advance(&mut state)
+                        .map_err(ForwardFluxError::System)?;
+                    let value = self.interfaces.value(system.positions(&state));
+                    if value >= target {
+                        successes.push(state);
+                        finished = true;
+                        break;
+                    }
+                    if self.interfaces.in_initial(value) {
+                        finished = true;
+                        break;
+                    }
+                }
+                if !finished {
+                    return Err(ForwardFluxError::Stuck { interface });
+                }
+            }
+            Ok(successes)
+        }
+    }
+
+    /// Writes the configurations and the counts of a stage, the counts last,
+    /// so that a stage is only considered finished once everything has been written.
+    fn store_stage<S: StateCheckpoint>(
+        dir: &Path,
+        configurations: &[S],
+        counts: &[(&str, usize)],
+    ) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        for (index, configuration) in configurations.iter().enumerate() {
+            let mut file = fs::File::create(dir.join(format!("configuration-{}.chk", index)))?;
+            configuration.write_checkpoint(&mut file)?;
+            file.sync_all()?;
+        }
+        let path = dir.join("stage.tsv");
+        let partial = path.with_extension("tsv.partial");
+        let mut file = fs::File::create(&partial)?;
+        for (name, count) in counts {
+            writeln!(file, "{}\t{}", name, count)?;
+        }
+        file.sync_all()?;
+        fs::rename(partial, path)
+    }
+
+    /// Reads the configurations of a finished stage and its number of trials,
+    /// or returns `None` if the stage has not finished.
+    fn load_stage<S: StateCheckpoint>(dir: &Path) -> io::Result<Option<(Vec<S>, usize)>> {
+        let contents = match fs::read_to_string(dir.join("stage.tsv")) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let mut counts = Vec::new();
+        for line in contents.lines() {
+            let count = line
+                .split_once('\t')
+                .and_then(|(name, count)| Some((name.to_owned(), count.parse::<usize>().ok()?)))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("malformed stage line {:?}", line),
+                    )
+                })?;
+            counts.push(count);
+        }
+        let count = |name: &str| {
+            counts
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, count)| *count)
+        };
+        let (Some(configurations), trials) = (
+            count("crossings").or_else(|| count("successes")),
+            count("trials").unwrap_or(0),
+        ) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the stage does not record its configurations",
+            ));
+        };
+        let configurations = (0..configurations)
+            .map(|index| {
+                let file = fs::File::open(dir.join(format!("configuration-{}.chk", index)))?;
+                S::read_checkpoint(BufReader::new(file))
+            })
+            .collect::<io::Result<_>>()?;
+        Ok(Some((configurations, trials)))
+    }
+}
+
+pub use forward_flux::{ForwardFlux, ForwardFluxError, ForwardFluxResults};
+
+mod transition_path {
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        io::{self, Write},
+    };
+
+    use num::Float;
+    use rand::{
+        Rng,
+        distr::{Distribution, StandardUniform},
+    };
+
+    use super::{Interfaces, TimeReversible};
+    use crate::colvar::CollectiveVariable;
+
+    /// The state a frame of a path lies in.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Basin {
+        Initial,
+        Final,
+        /// Between the states.
+        Neither,
+    }
+
+    impl Display for Basin {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Initial => write!(f, "A"),
+                Self::Final => write!(f, "B"),
+                Self::Neither => write!(f, "-"),
+            }
+        }
+    }
+
+    /// An error which stopped transition path sampling.
+    #[derive(Debug)]
+    pub enum TransitionPathError<E> {
+        /// The simulation failed.
+        System(E),
+        /// The initial path does not connect the states.
+        NotReactive,
+    }
+
+    impl<E: Display> Display for TransitionPathError<E> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::System(err) => write!(f, "transition path simulation failed: {}", err),
+                Self::NotReactive => write!(f, "the initial path does not connect the states"),
+            }
+        }
+    }
+
+    impl<E: Error + 'static> Error for TransitionPathError<E> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::System(err) => Some(err),
+                Self::NotReactive => None,
+            }
+        }
+    }
+
+    /// The outcome of a shooting move, the raw data of committor analyses
+    /// such as the likelihood maximization of reaction coordinates.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct ShootingRecord<T> {
+        /// The index of the move.
+        pub cycle: usize,
+        /// The value of the collective variable at the shooting point.
+        pub shooting_value: T,
+        /// Where the trajectory integrated forward in time ended.
+        pub forward: Basin,
+        /// Where the trajectory integrated backward in time ended.
+        pub backward: Basin,
+        pub accepted: bool,
+    }
+
+    impl<T: Display> Display for ShootingRecord<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            write!(
+                f,
+                "{}\t{}\t{}\t{}\t{}",
+                self.cycle,
+                self.shooting_value,
+                self.backward,
+                self.forward,
+                u8::from(self.accepted)
+            )
+        }
+    }
+
+    /// Aimless shooting transition path sampling of the paths between two states.
+    ///
+    /// Every move picks a shooting point at random among the frame at the middle of
+    /// the current path and the frames `offset` advances before and after it, redraws
+    /// its momenta with [`PathSystem::branch`](super::PathSystem::branch) and integrates
+    /// it forward and backward in time by `half_length` advances each. The new path is
+    /// accepted if it connects the initial and the final state in either direction,
+    /// which samples the transition path ensemble when the momenta are drawn from
+    /// the distribution of the thermostat.
+    pub struct AimlessShooting<T, C, R, S> {
+        interfaces: Interfaces<T, C>,
+        half_length: usize,
+        offset: usize,
+        rng: R,
+        path: Vec<S>,
+        shooting_index: usize,
+        records: Vec<ShootingRecord<T>>,
+        accepted: usize,
+    }
+
+    impl<T, C, R, S> AimlessShooting<T, C, R, S>
+    where
+        T: Float + From<f32>,
+        R: Rng,
+        S: Clone,
+    {
+        /// Constructs the sampler of the paths between the first and the last interface
+        /// of `interfaces` starting from `initial_path`, a reactive path of `2 half_length + 1` frames.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the path does not have `2 half_length + 1` frames
+        /// or the offset reaches the ends of the path.
+        pub fn new<V, P>(
+            interfaces: Interfaces<T, C>,
+            system: &P,
+            initial_path: Vec<S>,
+            half_length: usize,
+            offset: usize,
+            rng: R,
+        ) -> Result<Self, TransitionPathError<P::Error>>
+        where
+            P: TimeReversible<V, State = S>,
+            C: CollectiveVariable<T, V>,
+        {
+            assert_eq!(
+                initial_path.len(),
+                2 * half_length + 1,
+                "the path must have 2 half_length + 1 frames"
+            );
+            assert!(offset < half_length, "the offset must stay within the path");
+            let sampler = Self {
+                interfaces,
+                half_length,
+                offset,
+                rng,
+                path: initial_path,
+                shooting_index: half_length,
+                records: Vec::new(),
+                accepted: 0,
+            };
+            let first = sampler.basin(system, &sampler.path[0]);
+            let last = sampler.basin(system, &sampler.path[2 * half_length]);
+            if !Self::is_reactive(first, last) {
+                return Err(TransitionPathError::NotReactive);
+            }
+            Ok(sampler)
+        }
+
+        fn basin<V, P>(&self, system: &P, state: &S) -> Basin
+        where
+            P: TimeReversible<V, State = S>,
+            C: CollectiveVariable<T, V>,
+        {
+            let value = self.interfaces.value(system.positions(state));
+            if self.interfaces.in_initial(value) {
+                Basin::Initial
+            } else if self.interfaces.in_final(value) {
+                Basin::Final
+            } else {
+                Basin::Neither
+            }
+        }
+
+        fn is_reactive(first: Basin, last: Basin) -> bool {
+            matches!(
+                (first, last),
+                (Basin::Initial, Basin::Final) | (Basin::Final, Basin::Initial)
+            )
+        }
+
+        /// Performs a shooting move and returns its record.
+        pub fn shoot<V, P>(
+            &mut self,
+            system: &mut P,
+        ) -> Result<ShootingRecord<T>, TransitionPathError<P::Error>>
+        where
+            P: TimeReversible<V, State = S>,
+            C: CollectiveVariable<T, V>,
+        {
+            let uniform: f64 = StandardUniform.sample(&mut self.rng);
+            let shooting_index = match (uniform * 3.0) as usize {
+                0 => self.shooting_index - self.offset,
+                1 => self.shooting_index,
+                _ => self.shooting_index + self.offset,
+            };
+            let mut shooting_point = self.path[shooting_index].clone();
+            let seed = StandardUniform.sample(&mut self.rng);
+            system
+                .branch(&mut shooting_point, seed)
+                .map_err(TransitionPathError::System)?;
+            let shooting_value = self.interfaces.value(system.positions(&shooting_point));
+
+            let mut forward = Vec::with_capacity(self.half_length);
+            let mut state = shooting_point.clone();
+            for _ in 0..self.half_length {
+                system
+                    .advance(&mut state)
+                    .map_err(TransitionPathError::System)?;
+                forward.push(state.clone());
+            }
+            let mut backward = Vec::with_capacity(self.half_length);
+            let mut state = shooting_point.clone();
+            system.reverse(&mut state);
+            for _ in 0..self.half_length {
+                system
+                    .advance(&mut state)
+                    .map_err(TransitionPathError::System)?;
+                // The frames are stored with the momenta pointing forward in time.
+                let mut frame = state.clone();
+                system.reverse(&mut frame);
+                backward.push(frame);
+            }
+
+            let forward_basin = self.basin(system, forward.last().unwrap_or(&shooting_point));
+            let backward_basin = self.basin(system, backward.last().unwrap_or(&shooting_point));
+            let accepted = Self::is_reactive(backward_basin, forward_basin);
+            if accepted {
+                self.accepted += 1;
+                backward.reverse();
+                backward.push(shooting_point);
+                backward.extend(forward);
+                self.path = backward;
+                self.shooting_index = self.half_length;
+            }
+            let record = ShootingRecord {
+                cycle: self.records.len(),
+                shooting_value,
+                forward: forward_basin,
+                backward: backward_basin,
+                accepted,
+            };
+            self.records.push(record);
+            Ok(record)
+        }
+
+        /// Returns the frames of the current path in the order of time.
+        pub fn path(&self) -> &[S] {
+            &self.path
+        }
+
+        /// Returns the value of the collective variable along the current path.
+        pub fn path_values<V, P>(&self, system: &P) -> Vec<T>
+        where
+            P: TimeReversible<V, State = S>,
+            C: CollectiveVariable<T, V>,
+        {
+            self.path
+                .iter()
+                .map(|state| self.interfaces.value(system.positions(state)))
+                .collect()
+        }
+
+        pub fn records(&self) -> &[ShootingRecord<T>] {
+            &self.records
+        }
+
+        /// Returns the number of moves attempted and accepted so far.
+        pub fn statistics(&self) -> (usize, usize) {
+            (self.records.len(), self.accepted)
+        }
+
+        /// Writes the records of every move as a tab-separated table with a header line.
+        pub fn write_records<W: Write>(&self, mut writer: W) -> io::Result<()>
+        where
+            T: Display,
+        {
+            writeln!(writer, "cycle\tshooting_value\tbackward\tforward\taccepted")?;
+            for record in &self.records {
+                writeln!(writer, "{}", record)?;
+            }
+            Ok(())
+        }
+
+        /// Writes the value of the collective variable along the current path,
+        /// one frame per line, so that the path ensemble can be followed move by move.
+        pub fn write_path<V, P, W>(&self, system: &P, mut writer: W) -> io::Result<()>
+        where
+            P: TimeReversible<V, State = S>,
+            C: CollectiveVariable<T, V>,
+            T: Display,
+            W: Write,
+        {
+            writeln!(writer, "# cycle {}", self.records.len())?;
+            for (frame, value) in self.path_values(system).into_iter().enumerate() {
+                writeln!(writer, "{}\t{}", frame, value)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use transition_path::{AimlessShooting, Basin, ShootingRecord, TransitionPathError};
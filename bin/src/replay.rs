@@ -0,0 +1,388 @@
+mod event {
+    use std::{
+        fmt::{Display, Formatter, Result as FmtResult},
+        io,
+        str::FromStr,
+    };
+
+    /// A nondeterministic input of a step.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum Event {
+        /// A `u32` drawn from a random number generator.
+        U32(u32),
+        /// A `u64` drawn from a random number generator.
+        U64(u64),
+        /// Bytes drawn from a random number generator.
+        Bytes(Vec<u8>),
+        /// The order in which the contributions to a reduction were summed.
+        Order(Vec<usize>),
+    }
+
+    impl Event {
+        pub fn kind(&self) -> &'static str {
+            match self {
+                Self::U32(_) => "u32",
+                Self::U64(_) => "u64",
+                Self::Bytes(_) => "bytes",
+                Self::Order(_) => "order",
+            }
+        }
+    }
+
+    /// An event of a stream during a step, written as a line of the log:
+    /// `<step> <stream> <kind> <payload>`.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Entry {
+        pub step: usize,
+        pub stream: usize,
+        pub event: Event,
+    }
+
+    impl Display for Entry {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            write!(f, "{} {} {}", self.step, self.stream, self.event.kind())?;
+            match &self.event {
+                Event::U32(value) => write!(f, " {}", value),
+                Event::U64(value) => write!(f, " {}", value),
+                Event::Bytes(bytes) => {
+                    write!(f, " ")?;
+                    bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+                }
+                Event::Order(order) => order.iter().try_for_each(|index| write!(f, " {}", index)),
+            }
+        }
+    }
+
+    fn invalid(line: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid replay log entry '{}'", line),
+        )
+    }
+
+    impl FromStr for Entry {
+        type Err = io::Error;
+
+        fn from_str(line: &str) -> Result<Self, Self::Err> {
+            let mut fields = line.split_whitespace();
+            let mut next = || fields.next().ok_or_else(|| invalid(line));
+            let step = next()?.parse().map_err(|_| invalid(line))?;
+            let stream = next()?.parse().map_err(|_| invalid(line))?;
+            let event = match next()? {
+                "u32" => Event::U32(next()?.parse().map_err(|_| invalid(line))?),
+                "u64" => Event::U64(next()?.parse().map_err(|_| invalid(line))?),
+                "bytes" => {
+                    let hex = next().unwrap_or("");
+                    if hex.len() % 2 != 0 {
+                        return Err(invalid(line));
+                    }
+                    Event::Bytes(
+                        (0..hex.len())
+                            .step_by(2)
+                            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                            .collect::<Result<_, _>>()
+                            .map_err(|_| invalid(line))?,
+                    )
+                }
+                "order" => Event::Order(
+                    fields
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid(line))?,
+                ),
+                _ => return Err(invalid(line)),
+            };
+            Ok(Self {
+                step,
+                stream,
+                event,
+            })
+        }
+    }
+}
+
+pub use event::{Entry, Event};
+
+mod log {
+    use super::{Entry, Event};
+    use std::{
+        collections::{HashMap, VecDeque},
+        io::{self, BufRead, Write},
+        ops::Range,
+        sync::{Arc, Mutex, MutexGuard, PoisonError},
+    };
+
+    enum Mode {
+        Record {
+            writer: Box<dyn Write + Send>,
+            error: Option<io::Error>,
+        },
+        Replay {
+            streams: HashMap<usize, VecDeque<Entry>>,
+        },
+    }
+
+    struct State {
+        mode: Mode,
+        steps: Range<usize>,
+        step: usize,
+    }
+
+    /// A log of the random numbers drawn and the reduction orders chosen during a range
+    /// of steps, which is either being recorded or replayed.
+    ///
+    /// Replaying the range from a checkpoint taken at its first step reproduces the numerics
+    /// of the recorded run exactly, even with threads racing to a reduction, so a rare
+    /// blow-up can be studied in a debugger. Outside the range the log is inert.
+    ///
+    /// Every source of nondeterminism is a [`ReplayStream`] with an identifier
+    /// which must be the same in the recorded and the replayed run, e.g. the index
+    /// of the replica owning it.
+    #[derive(Clone)]
+    pub struct ReplayLog {
+        state: Arc<Mutex<State>>,
+    }
+
+    impl ReplayLog {
+        /// Constructs a log recording the events of `steps` into `writer`.
+        pub fn record<W: Write + Send + 'static>(writer: W, steps: Range<usize>) -> Self {
+            Self::with_mode(
+                Mode::Record {
+                    writer: Box::new(writer),
+                    error: None,
+                },
+                steps,
+            )
+        }
+
+        /// Constructs a log replaying the events of `steps` recorded in `reader`.
+        pub fn replay<R: BufRead>(reader: R, steps: Range<usize>) -> io::Result<Self> {
+            let mut streams = HashMap::<_, VecDeque<_>>::new();
+            for line in reader.lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let entry = line.parse::<Entry>()?;
+                if steps.contains(&entry.step) {
+                    streams.entry(entry.stream).or_default().push_back(entry);
+                }
+            }
+            Ok(Self::with_mode(Mode::Replay { streams }, steps))
+        }
+
+        fn with_mode(mode: Mode, steps: Range<usize>) -> Self {
+            Self {
+                state: Arc::new(Mutex::new(State {
+                    mode,
+                    step: steps.start,
+                    steps,
+                })),
+            }
+        }
+
+        // No method panics while holding the lock except on a divergence,
+        // after which the state is not used for anything but the report.
+        fn lock(&self) -> MutexGuard<'_, State> {
+            self.state.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        pub fn is_replaying(&self) -> bool {
+            matches!(self.lock().mode, Mode::Replay { .. })
+        }
+
+        /// Marks the start of `step`, to be called by the driver while no stream is in use.
+        pub fn begin_step(&self, step: usize) {
+            self.lock().step = step;
+        }
+
+        /// Returns the stream with the identifier `id`.
+        pub fn stream(&self, id: usize) -> ReplayStream {
+            ReplayStream {
+                log: self.clone(),
+                id,
+            }
+        }
+
+        /// Flushes the recorded events, returning the first error met while writing them.
+        ///
+        /// When replaying, fails if some recorded events of the range were not consumed,
+        /// which means the replayed run diverged from the recorded one.
+        pub fn finish(&self) -> io::Result<()> {
+            let mut state = self.lock();
+            match &mut state.mode {
+                Mode::Record { writer, error } => match error.take() {
+                    Some(err) => Err(err),
+                    None => writer.flush(),
+                },
+                Mode::Replay { streams } => match streams
+                    .values()
+                    .filter_map(VecDeque::front)
+                    .min_by_key(|entry| entry.step)
+                {
+                    Some(entry) => Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("the event '{}' of the replay log was never consumed", entry),
+                    )),
+                    None => Ok(()),
+                },
+            }
+        }
+    }
+
+    /// A source of nondeterminism whose events are recorded in or replayed from a [`ReplayLog`].
+    #[derive(Clone)]
+    pub struct ReplayStream {
+        log: ReplayLog,
+        id: usize,
+    }
+
+    impl ReplayStream {
+        pub fn id(&self) -> usize {
+            self.id
+        }
+
+        /// Passes an event of the given kind through the log: when recording inside the range,
+        /// `event` is generated and written; when replaying inside the range, the next
+        /// recorded event of the stream is returned instead and `event` is not called.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the replayed run diverged from the recorded one,
+        /// i.e. the next recorded event is of another kind or step, or there is none.
+        pub(crate) fn pass<F: FnOnce() -> Event>(&self, kind: &str, event: F) -> Event {
+            let mut state = self.log.lock();
+            let step = state.step;
+            if !state.steps.contains(&step) {
+                drop(state);
+                return event();
+            }
+            match &mut state.mode {
+                Mode::Record { writer, error } => {
+                    let entry = Entry {
+                        step,
+                        stream: self.id,
+                        event: event(),
+                    };
+                    if error.is_none()
+                        && let Err(err) = writeln!(writer, "{}", entry)
+                    {
+                        *error = Some(err);
+                    }
+                    entry.event
+                }
+                Mode::Replay { streams } => {
+                    match streams.get_mut(&self.id).and_then(VecDeque::pop_front) {
+                        Some(entry) if entry.step == step && entry.event.kind() == kind => {
+                            entry.event
+                        }
+                        Some(entry) => panic!(
+                            "the replay of stream #{} diverged at step {}: expected '{}', found a {} event",
+                            self.id, step, entry, kind
+                        ),
+                        None => panic!(
+                            "the replay of stream #{} diverged at step {}: no event left for a {} event",
+                            self.id, step, kind
+                        ),
+                    }
+                }
+            }
+        }
+
+        /// Records the order in which the contributions to a reduction arrived, or,
+        /// when replaying, overwrites `order` with the recorded one, so that summing
+        /// in `order` reproduces the rounding of the recorded run.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the replayed run diverged from the recorded one.
+        pub fn reduction_order(&self, order: &mut [usize]) {
+            if let Event::Order(recorded) = self.pass("order", || Event::Order(order.to_vec())) {
+                assert_eq!(
+                    recorded.len(),
+                    order.len(),
+                    "the replay of stream #{} diverged: the reduction has a different size",
+                    self.id
+                );
+                order.copy_from_slice(&recorded);
+            }
+        }
+    }
+}
+
+pub use log::{ReplayLog, ReplayStream};
+
+mod rng {
+    use super::{Event, ReplayStream};
+    use rand::{Rng, TryRng};
+    use std::convert::Infallible;
+
+    /// A random number generator whose draws pass through a [`ReplayStream`].
+    ///
+    /// While replaying, the draws come from the log and the inner generator is not advanced.
+    pub struct ReplayRng<R> {
+        inner: R,
+        stream: ReplayStream,
+    }
+
+    impl<R> ReplayRng<R> {
+        pub fn new(inner: R, stream: ReplayStream) -> Self {
+            Self { inner, stream }
+        }
+
+        pub fn stream(&self) -> &ReplayStream {
+            &self.stream
+        }
+
+        pub fn into_inner(self) -> R {
+            self.inner
+        }
+    }
+
+    impl<R: Rng> TryRng for ReplayRng<R> {
+        type Error = Infallible;
+
+        fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+            match self
+                .stream
+                .pass("u32", || Event::U32(self.inner.next_u32()))
+            {
+                Event::U32(value) => Ok(value),
+                _ => unreachable!("the stream checks the kind of the event"),
+            }
+        }
+
+        fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+            match self
+                .stream
+                .pass("u64", || Event::U64(self.inner.next_u64()))
+            {
+                Event::U64(value) => Ok(value),
+                _ => unreachable!("the stream checks the kind of the event"),
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+            let event = self.stream.pass("bytes", || {
+                let mut bytes = vec![0; dst.len()];
+                self.inner.fill_bytes(&mut bytes);
+                Event::Bytes(bytes)
+            });
+            match event {
+                Event::Bytes(bytes) => {
+                    assert_eq!(
+                        bytes.len(),
+                        dst.len(),
+                        "the replay of stream #{} diverged: the draw has a different size",
+                        self.stream.id()
+                    );
+                    dst.copy_from_slice(&bytes);
+                    Ok(())
+                }
+                _ => unreachable!("the stream checks the kind of the event"),
+            }
+        }
+    }
+}
+
+pub use rng::ReplayRng;
@@ -0,0 +1,315 @@
+//! Experimental support for changing the number of beads during a run.
+//!
+//! The ring polymer of every atom is resampled onto the new beads by Fourier interpolation:
+//! the positions are expanded in the normal modes of the closed ring, the modes which
+//! the new number of beads cannot represent are dropped, and the expansion is evaluated
+//! at the new beads. The centroid of every atom is preserved, and upsampling followed by
+//! downsampling to the original number of beads restores the original positions.
+
+mod fourier {
+    use lib::core::Vector;
+    use num::Float;
+
+    /// Resamples the beads of a single closed ring polymer onto `resampled.len()` beads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either `ring` or `resampled` is empty.
+    pub fn resample_ring<const N: usize, T, V>(ring: &[V], resampled: &mut [V])
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        assert!(!ring.is_empty(), "the ring polymer must have beads");
+        assert!(
+            !resampled.is_empty(),
+            "the ring polymer must be resampled onto beads"
+        );
+        let from = ring.len();
+        let to = resampled.len();
+        let cast = |value: usize| <T as From<f32>>::from(value as f32);
+        // Taken in the precision of `T`, so that resampling onto the same beads is exact.
+        let two_pi = <T as From<f32>>::from(8.0) * T::one().atan();
+        let mut centroid = V::zero();
+        for bead in ring {
+            centroid += bead.clone();
+        }
+        let centroid = centroid / cast(from);
+        // The cosine and sine amplitudes of every harmonic both rings can represent.
+        let harmonics = from.min(to) / 2;
+        let mut amplitudes = Vec::with_capacity(harmonics);
+        for harmonic in 1..=harmonics {
+            let (mut cosine, mut sine) = (V::zero(), V::zero());
+            for (index, bead) in ring.iter().enumerate() {
+                let angle = two_pi * cast(harmonic * index % from) / cast(from);
+                let (sin, cos) = angle.sin_cos();
+                cosine.mul_add_assign(bead, cos);
+                sine.mul_add_assign(bead, sin);
+            }
+            // The Nyquist harmonic of an even ring has no sine and appears once in the sum.
+            let weight = if 2 * harmonic == from {
+                T::one() / cast(from)
+            } else {
+                <T as From<f32>>::from(2.0) / cast(from)
+            };
+            amplitudes.push((cosine * weight, sine * weight));
+        }
+        for (index, bead) in resampled.iter_mut().enumerate() {
+            *bead = centroid.clone();
+            for (harmonic, (cosine, sine)) in amplitudes.iter().enumerate() {
+                let angle = two_pi * cast((harmonic + 1) * index % to) / cast(to);
+                let (sin, cos) = angle.sin_cos();
+                bead.mul_add_assign(cosine, cos);
+                bead.mul_add_assign(sine, sin);
+            }
+        }
+    }
+
+    /// Resamples every atom of `images`, where `images[image][atom]` is the vector of `atom`
+    /// in `image`, onto `beads` images.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `images` is empty, the images hold different numbers of atoms
+    /// or `beads` is zero.
+    pub fn resample_images<const N: usize, T, V>(images: &[Vec<V>], beads: usize) -> Vec<Vec<V>>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        assert!(!images.is_empty(), "there must be images to resample");
+        assert!(beads > 0, "the images must be resampled onto beads");
+        let atoms = images[0].len();
+        assert!(
+            images.iter().all(|image| image.len() == atoms),
+            "every image must hold the same number of atoms"
+        );
+        let mut resampled = vec![vec![V::zero(); atoms]; beads];
+        let mut ring = Vec::with_capacity(images.len());
+        let mut resampled_ring = vec![V::zero(); beads];
+        for atom in 0..atoms {
+            ring.clear();
+            ring.extend(images.iter().map(|image| image[atom].clone()));
+            resample_ring(&ring, &mut resampled_ring);
+            for (image, bead) in resampled.iter_mut().zip(&resampled_ring) {
+                image[atom] = bead.clone();
+            }
+        }
+        resampled
+    }
+}
+
+pub use fourier::{resample_images, resample_ring};
+
+mod driver {
+    use lib::{
+        core::{Vector, resolution::BeadCountDependent},
+        events::BeadsChanged,
+    };
+    use num::Float;
+
+    use super::resample_images;
+
+    /// The number of beads of a run, changed on request between two steps.
+    ///
+    /// A change requested during a step, e.g. by an adaptive controller subscribed to its events,
+    /// is held back until [`BeadResolution::commit`] is called by the driver.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct BeadResolution {
+        beads: usize,
+        pending: Option<usize>,
+    }
+
+    impl BeadResolution {
+        /// Constructs the resolution of a run started with `beads` beads.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `beads` is zero.
+        pub fn new(beads: usize) -> Self {
+            assert!(beads > 0, "a ring polymer must have beads");
+            Self {
+                beads,
+                pending: None,
+            }
+        }
+
+        /// Returns the current number of beads.
+        pub fn beads(&self) -> usize {
+            self.beads
+        }
+
+        /// Returns the number of beads to switch to at the next commit, if any.
+        pub fn pending(&self) -> Option<usize> {
+            self.pending
+        }
+
+        /// Requests a switch to `beads` beads, replacing any earlier request.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `beads` is zero.
+        pub fn request(&mut self, beads: usize) {
+            assert!(beads > 0, "a ring polymer must have beads");
+            self.pending = (beads != self.beads).then_some(beads);
+        }
+
+        /// Applies the pending change before `step`: resamples `positions` and `momenta`,
+        /// given as `images[image][atom]`, onto the new beads and informs every component.
+        ///
+        /// The resampled momenta keep the shape of the ring polymer but not its temperature,
+        /// so they are best redrawn by the thermostat before the next step.
+        /// Returns the event to publish, or `None` if no change was pending.
+        pub fn commit<const N: usize, T, V>(
+            &mut self,
+            step: usize,
+            positions: &mut Vec<Vec<V>>,
+            momenta: &mut Vec<Vec<V>>,
+            components: &mut [&mut dyn BeadCountDependent],
+        ) -> Option<BeadsChanged>
+        where
+            T: Float + From<f32>,
+            V: Vector<N, Element = T> + Clone,
+        {
+            let beads = self.pending.take()?;
+            *positions = resample_images(positions, beads);
+            *momenta = resample_images(momenta, beads);
+            for component in components.iter_mut() {
+                component.set_beads(beads);
+            }
+            let from = self.beads;
+            self.beads = beads;
+            Some(BeadsChanged {
+                step,
+                from,
+                to: beads,
+            })
+        }
+    }
+}
+
+pub use driver::BeadResolution;
+
+mod checkpoint {
+    use std::{
+        fmt::Display,
+        io::{self, BufRead, Write},
+        str::FromStr,
+    };
+
+    use lib::core::Vector;
+    use num::Float;
+
+    use super::resample_images;
+    use crate::steering::ParseError;
+
+    /// Writes `images`, given as `images[image][atom]`, to `writer` as a tab-separated table
+    /// preceded by the number of beads, one atom of an image per line.
+    pub fn write_images<const N: usize, T, V, W>(
+        images: &[Vec<V>],
+        writer: &mut W,
+    ) -> io::Result<()>
+    where
+        T: Display,
+        V: Vector<N, Element = T>,
+        W: Write,
+    {
+        writeln!(writer, "beads\t{}", images.len())?;
+        writeln!(writer, "# image\tatom\tcoordinates")?;
+        for (index, image) in images.iter().enumerate() {
+            for (atom, vector) in image.iter().enumerate() {
+                write!(writer, "{}\t{}", index, atom)?;
+                for element in vector.as_array() {
+                    write!(writer, "\t{}", element)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the images written by [`write_images`], resampling them onto `beads` beads
+    /// if the checkpoint was taken with a different number of beads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `beads` is zero.
+    pub fn read_images<const N: usize, T, V, R>(
+        reader: R,
+        beads: usize,
+    ) -> Result<Vec<Vec<V>>, ParseError>
+    where
+        T: Float + From<f32> + FromStr,
+        V: Vector<N, Element = T> + Clone,
+        R: BufRead,
+    {
+        let error = |line: usize, message: &str| ParseError {
+            line: Some(line),
+            message: message.to_owned(),
+        };
+        let mut stored = None;
+        let mut images: Vec<Vec<V>> = Vec::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line.map_err(|err| ParseError {
+                line: None,
+                message: err.to_string(),
+            })?;
+            let line = line.trim();
+            let line_number = number + 1;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some(count) = stored else {
+                let count = line
+                    .strip_prefix("beads\t")
+                    .and_then(|count| count.parse::<usize>().ok())
+                    .filter(|&count| count > 0)
+                    .ok_or_else(|| error(line_number, "expected the number of beads"))?;
+                stored = Some(count);
+                images.resize_with(count, Vec::new);
+                continue;
+            };
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != N + 2 {
+                return Err(error(
+                    line_number,
+                    "expected an image, an atom and coordinates",
+                ));
+            }
+            let image = fields[0]
+                .parse::<usize>()
+                .ok()
+                .filter(|&image| image < count)
+                .ok_or_else(|| error(line_number, "invalid image"))?;
+            if fields[1].parse::<usize>().ok() != Some(images[image].len()) {
+                return Err(error(line_number, "atoms must be listed in order"));
+            }
+            let mut vector = V::zero();
+            for (element, field) in vector.as_mut_array().iter_mut().zip(&fields[2..]) {
+                *element = field
+                    .parse()
+                    .map_err(|_| error(line_number, "invalid coordinate"))?;
+            }
+            images[image].push(vector);
+        }
+        if stored.is_none() {
+            return Err(ParseError {
+                line: None,
+                message: "the checkpoint holds no images".to_owned(),
+            });
+        }
+        let atoms = images[0].len();
+        if images.iter().any(|image| image.len() != atoms) {
+            return Err(ParseError {
+                line: None,
+                message: "the images hold different numbers of atoms".to_owned(),
+            });
+        }
+        if images.len() == beads {
+            return Ok(images);
+        }
+        Ok(resample_images(&images, beads))
+    }
+}
+
+pub use checkpoint::{read_images, write_images};
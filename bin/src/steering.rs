@@ -0,0 +1,239 @@
+mod parameters {
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+    };
+
+    use num::{Float, NumCast};
+
+    use crate::potential::pair::PairParameters;
+
+    /// An error in a file of parameters.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct ParseError {
+        /// The line at fault, if the error is not about the file as a whole.
+        pub line: Option<usize>,
+        pub message: String,
+    }
+
+    impl ParseError {
+        fn file(message: String) -> Self {
+            Self {
+                line: None,
+                message,
+            }
+        }
+    }
+
+    impl Display for ParseError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self.line {
+                Some(line) => write!(f, "line {}: {}", line, self.message),
+                None => write!(f, "{}", self.message),
+            }
+        }
+    }
+
+    impl Error for ParseError {}
+
+    /// Splits lines of `name = value` into the line number, the name and the unparsed value,
+    /// skipping blank lines and `#` comments.
+    pub fn parse_settings(text: &str) -> Result<Vec<(usize, &str, &str)>, ParseError> {
+        let mut settings = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line.split_once('=').ok_or_else(|| ParseError {
+                line: Some(index + 1),
+                message: format!("expected `name = value`, found `{}`", line),
+            })?;
+            settings.push((index + 1, name.trim(), value.trim()));
+        }
+        Ok(settings)
+    }
+
+    /// Parses lines of `name = value` into pairs, skipping blank lines and `#` comments.
+    pub fn parse_assignments(text: &str) -> Result<Vec<(&str, f64)>, ParseError> {
+        parse_settings(text)?
+            .into_iter()
+            .map(|(line, name, value)| {
+                let value = value.parse().map_err(|err| ParseError {
+                    line: Some(line),
+                    message: format!("invalid value of `{}`: {}", name, err),
+                })?;
+                Ok((name, value))
+            })
+            .collect()
+    }
+
+    /// Parses Lennard-Jones parameters given as `sigma = ...` and `epsilon = ...`.
+    pub fn parse_pair_parameters<T: Float + From<f32>>(
+        text: &str,
+    ) -> Result<PairParameters<T>, ParseError> {
+        let (mut sigma, mut epsilon) = (None, None);
+        for (name, value) in parse_assignments(text)? {
+            match name {
+                "sigma" => sigma = Some(value),
+                "epsilon" => epsilon = Some(value),
+                name => {
+                    return Err(ParseError::file(format!("unknown parameter `{}`", name)));
+                }
+            }
+        }
+        let missing = |name: &str| ParseError::file(format!("missing parameter `{}`", name));
+        let sigma = sigma.ok_or_else(|| missing("sigma"))?;
+        let epsilon = epsilon.ok_or_else(|| missing("epsilon"))?;
+        if sigma <= 0.0 || epsilon < 0.0 {
+            return Err(ParseError::file(
+                "sigma must be positive and epsilon non-negative".to_owned(),
+            ));
+        }
+        let convert = |value| {
+            <T as NumCast>::from(value)
+                .ok_or_else(|| ParseError::file(format!("{} is not representable", value)))
+        };
+        Ok(PairParameters::new(convert(sigma)?, convert(epsilon)?))
+    }
+}
+
+pub use parameters::{ParseError, parse_assignments, parse_pair_parameters, parse_settings};
+
+mod file_watch {
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        fs, io,
+        path::PathBuf,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, SystemTime},
+    };
+
+    use lib::potential::physical::ParameterStore;
+
+    /// An error of a [`FileWatch`].
+    #[derive(Debug)]
+    pub enum WatchError<E> {
+        /// The file could not be read.
+        Io(io::Error),
+        /// The contents of the file could not be parsed; the active parameters are kept.
+        Parse(E),
+    }
+
+    impl<E: Display> Display for WatchError<E> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Io(err) => write!(f, "failed to read the parameters: {}", err),
+                Self::Parse(err) => write!(f, "failed to parse the parameters: {}", err),
+            }
+        }
+    }
+
+    impl<E: Error + 'static> Error for WatchError<E> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::Io(err) => Some(err),
+                Self::Parse(err) => Some(err),
+            }
+        }
+    }
+
+    /// Stages the parameters read from a file into a [`ParameterStore`]
+    /// whenever the file is modified, so a run can be steered by editing the file.
+    ///
+    /// The parameters only take effect when the driver commits the store between two steps.
+    pub struct FileWatch<P, F> {
+        path: PathBuf,
+        modified: Option<SystemTime>,
+        store: ParameterStore<P>,
+        parse: F,
+    }
+
+    impl<P, E, F> FileWatch<P, F>
+    where
+        F: FnMut(&str) -> Result<P, E>,
+    {
+        /// Constructs a watch of the file at `path`.
+        ///
+        /// The file is read at the first poll, even if it exists already.
+        pub fn new(path: impl Into<PathBuf>, store: ParameterStore<P>, parse: F) -> Self {
+            Self {
+                path: path.into(),
+                modified: None,
+                store,
+                parse,
+            }
+        }
+
+        /// Reads the file if it has been modified since the last poll and stages its parameters.
+        ///
+        /// Returns whether new parameters were staged. A missing file is not an error,
+        /// so the file can be created in the middle of a run.
+        pub fn poll(&mut self) -> Result<bool, WatchError<E>> {
+            let modified = match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+                Ok(modified) => modified,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(false),
+                Err(err) => return Err(WatchError::Io(err)),
+            };
+            if self.modified == Some(modified) {
+                return Ok(false);
+            }
+            // Records the time first, so a malformed file is reported once rather than at every poll.
+            self.modified = Some(modified);
+            let text = fs::read_to_string(&self.path).map_err(WatchError::Io)?;
+            let parameters = (self.parse)(&text).map_err(WatchError::Parse)?;
+            self.store.stage(parameters);
+            Ok(true)
+        }
+    }
+
+    impl<P, E, F> FileWatch<P, F>
+    where
+        P: Send + Sync + 'static,
+        E: Send + 'static,
+        F: FnMut(&str) -> Result<P, E> + Send + 'static,
+    {
+        /// Polls the file every `interval` on a background thread until the handle is stopped.
+        pub fn spawn(mut self, interval: Duration) -> WatchHandle<E> {
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread = thread::spawn({
+                let stop = Arc::clone(&stop);
+                move || {
+                    let mut errors = Vec::new();
+                    while !stop.load(Ordering::Relaxed) {
+                        if let Err(err) = self.poll() {
+                            errors.push(err);
+                        }
+                        thread::park_timeout(interval);
+                    }
+                    errors
+                }
+            });
+            WatchHandle { stop, thread }
+        }
+    }
+
+    /// A handle to a [`FileWatch`] polling on a background thread.
+    pub struct WatchHandle<E> {
+        stop: Arc<AtomicBool>,
+        thread: JoinHandle<Vec<WatchError<E>>>,
+    }
+
+    impl<E> WatchHandle<E> {
+        /// Stops the watch and returns the errors it encountered.
+        pub fn stop(self) -> Vec<WatchError<E>> {
+            self.stop.store(true, Ordering::Relaxed);
+            self.thread.thread().unpark();
+            self.thread
+                .join()
+                .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+        }
+    }
+}
+
+pub use file_watch::{FileWatch, WatchError, WatchHandle};
@@ -0,0 +1,417 @@
+mod point {
+    /// A named set of parameter values simulated as a single point of a sweep,
+    /// e.g. a temperature and a number of beads.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct SweepPoint {
+        pub(crate) index: usize,
+        pub(crate) values: Vec<(String, f64)>,
+    }
+
+    impl SweepPoint {
+        /// Returns the index of the point in its sweep.
+        pub fn index(&self) -> usize {
+            self.index
+        }
+
+        pub fn values(&self) -> &[(String, f64)] {
+            &self.values
+        }
+
+        /// Returns the value of the parameter `name`, or `None` if the point does not set it.
+        pub fn get(&self, name: &str) -> Option<f64> {
+            self.values
+                .iter()
+                .find_map(|(parameter, value)| (parameter == name).then_some(*value))
+        }
+    }
+
+    /// The points of a parameter sweep.
+    #[derive(Clone, Debug, Default)]
+    pub struct Sweep {
+        points: Vec<SweepPoint>,
+    }
+
+    impl Sweep {
+        /// Constructs the sweep over every combination of the values of `axes`.
+        ///
+        /// The last axis varies the fastest.
+        pub fn grid(axes: &[(&str, &[f64])]) -> Self {
+            let len = axes.iter().map(|(_, values)| values.len()).product();
+            let points = (0..len)
+                .map(|index| {
+                    let mut remainder = index;
+                    let mut values: Vec<(String, f64)> = axes
+                        .iter()
+                        .rev()
+                        .map(|&(name, values)| {
+                            let value = values[remainder % values.len()];
+                            remainder /= values.len();
+                            (name.to_owned(), value)
+                        })
+                        .collect();
+                    values.reverse();
+                    SweepPoint { index, values }
+                })
+                .collect();
+            Self { points }
+        }
+
+        /// Constructs the sweep over an explicit list of parameter sets.
+        pub fn list<I>(sets: I) -> Self
+        where
+            I: IntoIterator<Item = Vec<(String, f64)>>,
+        {
+            Self {
+                points: sets
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, values)| SweepPoint { index, values })
+                    .collect(),
+            }
+        }
+
+        pub fn points(&self) -> &[SweepPoint] {
+            &self.points
+        }
+
+        pub fn len(&self) -> usize {
+            self.points.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.points.is_empty()
+        }
+    }
+}
+
+pub use point::{Sweep, SweepPoint};
+
+mod runner {
+    use super::{Sweep, SweepPoint};
+    use std::{
+        error::Error,
+        fmt::{Display, Formatter, Result as FmtResult},
+        fs,
+        io::{self, Write},
+        num::NonZero,
+        path::{Path, PathBuf},
+        sync::{
+            Mutex,
+            atomic::{AtomicUsize, Ordering},
+        },
+        thread,
+    };
+
+    /// The named results of a single point.
+    pub type PointResults = Vec<(String, f64)>;
+
+    /// The line opening the parameters of the point in a checkpoint.
+    const PARAMETERS: &str = "# parameters";
+    /// The line opening the results of the point in a checkpoint.
+    const RESULTS: &str = "# results";
+
+    /// An error which stopped a sweep.
+    #[derive(Debug)]
+    pub enum SweepError<E> {
+        /// Reading or writing a checkpoint or the results table failed.
+        Io(io::Error),
+        /// The simulation of a point failed.
+        Point {
+            /// The index of the point.
+            index: usize,
+            source: E,
+        },
+        /// The checkpoint of a point holds the results of different parameters,
+        /// as when the sweep has changed since it was written.
+        StaleCheckpoint {
+            /// The index of the point.
+            index: usize,
+            path: PathBuf,
+        },
+    }
+
+    impl<E> From<io::Error> for SweepError<E> {
+        fn from(value: io::Error) -> Self {
+            Self::Io(value)
+        }
+    }
+
+    impl<E: Display> Display for SweepError<E> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::Io(err) => write!(f, "sweep checkpoint I/O failed: {}", err),
+                Self::Point { index, source } => {
+                    write!(f, "point #{} of the sweep failed: {}", index, source)
+                }
+                Self::StaleCheckpoint { index, path } => write!(
+                    f,
+                    "the checkpoint {} was written for other parameters than those of point #{}",
+                    path.display(),
+                    index
+                ),
+            }
+        }
+    }
+
+    impl<E: Error + 'static> Error for SweepError<E> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::Io(err) => Some(err),
+                Self::Point { source, .. } => Some(source),
+                Self::StaleCheckpoint { .. } => None,
+            }
+        }
+    }
+
+    /// How the points of a sweep are distributed.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Execution {
+        /// One point after another on the calling thread.
+        Sequential,
+        /// On a pool of worker threads, each taking the next unfinished point.
+        Threads(NonZero<usize>),
+    }
+
+    /// Runs the points of a sweep, checkpointing the results of every finished point.
+    ///
+    /// The results of a point are written to `point-<index>.tsv` in the checkpoint directory
+    /// as soon as it finishes, and points with an existing checkpoint are not run again,
+    /// so an interrupted sweep resumes where it stopped.
+    ///
+    /// A checkpoint holds the parameters of its point along with the results.
+    /// A checkpoint of other parameters is rejected with [`SweepError::StaleCheckpoint`]
+    /// rather than taken for the results of the point.
+    #[derive(Clone, Debug)]
+    pub struct SweepRunner {
+        checkpoint_dir: PathBuf,
+        execution: Execution,
+    }
+
+    impl SweepRunner {
+        pub fn new<P: Into<PathBuf>>(checkpoint_dir: P, execution: Execution) -> Self {
+            Self {
+                checkpoint_dir: checkpoint_dir.into(),
+                execution,
+            }
+        }
+
+        fn checkpoint_path(&self, index: usize) -> PathBuf {
+            self.checkpoint_dir.join(format!("point-{}.tsv", index))
+        }
+
+        /// Reads the parameters and the results stored in the checkpoint at `path`,
+        /// or returns `None` if there is none.
+        fn load(path: &Path) -> io::Result<Option<(PointResults, PointResults)>> {
+            let contents = match fs::read_to_string(path) {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err),
+            };
+            let malformed = |line: &str| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("malformed checkpoint line {:?}", line),
+                )
+            };
+            let mut parameters = Vec::new();
+            let mut results = Vec::new();
+            let mut section = None;
+            for line in contents.lines() {
+                match line {
+                    PARAMETERS => section = Some(&mut parameters),
+                    RESULTS => section = Some(&mut results),
+                    line => {
+                        let entry = line
+                            .split_once('\t')
+                            .and_then(|(name, value)| Some((name.to_owned(), value.parse().ok()?)))
+                            .ok_or_else(|| malformed(line))?;
+                        section.as_mut().ok_or_else(|| malformed(line))?.push(entry);
+                    }
+                }
+            }
+            Ok(Some((parameters, results)))
+        }
+
+        fn store(&self, point: &SweepPoint, results: &PointResults) -> io::Result<()> {
+            let path = self.checkpoint_path(point.index);
+            let partial = path.with_extension("tsv.partial");
+            let mut file = fs::File::create(&partial)?;
+            for (header, entries) in [(PARAMETERS, point.values()), (RESULTS, results)] {
+                writeln!(file, "{}", header)?;
+                for (name, value) in entries {
+                    writeln!(file, "{}\t{}", name, value)?;
+                }
+            }
+            file.sync_all()?;
+            // Renaming keeps an interrupted write from leaving a truncated checkpoint behind.
+            fs::rename(partial, path)
+        }
+
+        fn run_point<F, E>(
+            &self,
+            point: &SweepPoint,
+            simulate: &F,
+        ) -> Result<PointResults, SweepError<E>>
+        where
+            F: Fn(&SweepPoint) -> Result<PointResults, E>,
+        {
+            let path = self.checkpoint_path(point.index);
+            if let Some((parameters, results)) = Self::load(&path)? {
+                // The values are written in full precision, so they compare exactly.
+                if parameters != point.values {
+                    return Err(SweepError::StaleCheckpoint {
+                        index: point.index,
+                        path,
+                    });
+                }
+                return Ok(results);
+            }
+            let results = simulate(point).map_err(|source| SweepError::Point {
+                index: point.index,
+                source,
+            })?;
+            self.store(point, &results)?;
+            Ok(results)
+        }
+
+        /// Runs `simulate` on every point of `sweep` without a checkpoint
+        /// and collects the results of all points.
+        ///
+        /// With a pool of threads, the workers stop taking new points once a point fails
+        /// and the error of the point with the lowest index is returned.
+        pub fn run<F, E>(&self, sweep: &Sweep, simulate: F) -> Result<SweepResults, SweepError<E>>
+        where
+            F: Fn(&SweepPoint) -> Result<PointResults, E> + Sync,
+            E: Send,
+        {
+            fs::create_dir_all(&self.checkpoint_dir)?;
+            let mut results: Vec<Option<PointResults>> = vec![None; sweep.len()];
+            match self.execution {
+                Execution::Sequential => {
+                    for (point, slot) in sweep.points().iter().zip(&mut results) {
+                        *slot = Some(self.run_point(point, &simulate)?);
+                    }
+                }
+                Execution::Threads(threads) => {
+                    let next = AtomicUsize::new(0);
+                    let slots = Mutex::new((&mut results, None::<SweepError<E>>));
+                    thread::scope(|scope| {
+                        for _ in 0..threads.get().min(sweep.len()) {
+                            scope.spawn(|| {
+                                while let Some(point) =
+                                    sweep.points().get(next.fetch_add(1, Ordering::Relaxed))
+                                {
+                                    let result = self.run_point(point, &simulate);
+                                    let mut slots =
+                                        slots.lock().unwrap_or_else(|err| err.into_inner());
+                                    match result {
+                                        Ok(point_results) => {
+                                            slots.0[point.index] = Some(point_results)
+                                        }
+                                        Err(err) => {
+                                            next.store(sweep.len(), Ordering::Relaxed);
+                                            let index = match &err {
+                                                SweepError::Point { index, .. }
+                                                | SweepError::StaleCheckpoint { index, .. } => {
+                                                    *index
+                                                }
+                                                SweepError::Io(_) => point.index,
+                                            };
+                                            let replace = match &slots.1 {
+                                                Some(
+                                                    SweepError::Point { index: other, .. }
+                                                    | SweepError::StaleCheckpoint {
+                                                        index: other,
+                                                        ..
+                                                    },
+                                                ) => index < *other,
+                                                Some(SweepError::Io(_)) => false,
+                                                None => true,
+                                            };
+                                            if replace {
+                                                slots.1 = Some(err);
+                                            }
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                    });
+                    if let Some(err) = slots.into_inner().unwrap_or_else(|err| err.into_inner()).1 {
+                        return Err(err);
+                    }
+                }
+            }
+            Ok(SweepResults {
+                rows: sweep
+                    .points()
+                    .iter()
+                    .cloned()
+                    .zip(results)
+                    .map(|(point, results)| (point, results.expect("every point has finished")))
+                    .collect(),
+            })
+        }
+    }
+
+    /// The consolidated results of a sweep, one row per point.
+    #[derive(Clone, Debug, Default)]
+    pub struct SweepResults {
+        rows: Vec<(SweepPoint, PointResults)>,
+    }
+
+    impl SweepResults {
+        pub fn rows(&self) -> &[(SweepPoint, PointResults)] {
+            &self.rows
+        }
+
+        /// Writes the results as a tab-separated table with a header line.
+        ///
+        /// The columns are the union of the parameters and results of all points
+        /// in the order they first appear in; missing entries are left empty.
+        pub fn write_table<W: Write>(&self, mut writer: W) -> io::Result<()> {
+            let mut parameters: Vec<&str> = Vec::new();
+            let mut quantities: Vec<&str> = Vec::new();
+            for (point, results) in &self.rows {
+                for (columns, entries) in [
+                    (&mut parameters, point.values()),
+                    (&mut quantities, results),
+                ] {
+                    for (name, _) in entries {
+                        if !columns.contains(&name.as_str()) {
+                            columns.push(name);
+                        }
+                    }
+                }
+            }
+            writeln!(
+                writer,
+                "point\t{}",
+                parameters
+                    .iter()
+                    .chain(&quantities)
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join("\t")
+            )?;
+            for (point, results) in &self.rows {
+                write!(writer, "{}", point.index())?;
+                for (columns, entries) in [
+                    (&parameters, point.values()),
+                    (&quantities, results.as_slice()),
+                ] {
+                    for column in columns.iter() {
+                        match entries.iter().find(|(name, _)| name == column) {
+                            Some((_, value)) => write!(writer, "\t{}", value)?,
+                            None => write!(writer, "\t")?,
+                        }
+                    }
+                }
+                writeln!(writer)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub use runner::{Execution, PointResults, SweepError, SweepResults, SweepRunner};
@@ -0,0 +1,450 @@
+mod configuration {
+    use std::{
+        error::Error,
+        fmt::{Debug, Display, Formatter, Result as FmtResult},
+    };
+
+    use lib::core::{
+        Vector,
+        error::InvalidIndexError,
+        validation::{Severity, Validator},
+    };
+    use num::Float;
+
+    use crate::potential::{electrostatics::PeriodicBox, pair::VerletList};
+
+    /// The thresholds beyond which an initial configuration is considered broken.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct ConfigurationLimits<T> {
+        /// The distance under which two atoms overlap.
+        pub min_distance: T,
+        /// The length over which a bond is broken.
+        pub max_bond_length: T,
+    }
+
+    /// Two atoms closer than the minimal distance.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Overlap<T> {
+        pub first: usize,
+        pub second: usize,
+        pub distance: T,
+    }
+
+    /// A bond longer than the maximal bond length.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct StretchedBond<T> {
+        pub bond: usize,
+        pub atoms: [usize; 2],
+        pub length: T,
+    }
+
+    /// An error in an initial configuration which would blow up the first step.
+    #[derive(Clone, Debug)]
+    pub enum ConfigurationError<T> {
+        /// A bond refers to an atom which is not in the configuration.
+        InvalidIndex(InvalidIndexError),
+        /// The configuration holds atoms which are not finite, overlap or are bonded too far apart.
+        Clashes {
+            /// The atoms with a coordinate which is NaN or infinite.
+            non_finite: Vec<usize>,
+            overlaps: Vec<Overlap<T>>,
+            stretched_bonds: Vec<StretchedBond<T>>,
+        },
+    }
+
+    impl<T> From<InvalidIndexError> for ConfigurationError<T> {
+        fn from(value: InvalidIndexError) -> Self {
+            Self::InvalidIndex(value)
+        }
+    }
+
+    impl<T: Display> ConfigurationError<T> {
+        /// Adds a diagnostic for every problem of the configuration to `validator`.
+        pub fn report_to(&self, validator: &mut Validator) {
+            let Self::Clashes {
+                non_finite,
+                overlaps,
+                stretched_bonds,
+            } = self
+            else {
+                validator.report(
+                    Severity::Error,
+                    "bonds",
+                    self.to_string(),
+                    "bond only atoms of the configuration",
+                );
+                return;
+            };
+            for atom in non_finite {
+                validator.report(
+                    Severity::Error,
+                    &format!("atom #{}", atom),
+                    "the position is not finite".to_owned(),
+                    "check the file the configuration is read from",
+                );
+            }
+            for overlap in overlaps {
+                validator.report(
+                    Severity::Error,
+                    &format!("atoms #{} and #{}", overlap.first, overlap.second),
+                    format!("the atoms overlap at a distance of {}", overlap.distance),
+                    "remove one of the atoms or relax the configuration first",
+                );
+            }
+            for bond in stretched_bonds {
+                validator.report(
+                    Severity::Error,
+                    &format!("bond #{}", bond.bond),
+                    format!(
+                        "atoms #{} and #{} are {} apart",
+                        bond.atoms[0], bond.atoms[1], bond.length
+                    ),
+                    "unwrap the molecules or check the order of the atoms",
+                );
+            }
+        }
+    }
+
+    impl<T: Display> Display for ConfigurationError<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self {
+                Self::InvalidIndex(err) => write!(f, "invalid bond: {}", err),
+                Self::Clashes {
+                    non_finite,
+                    overlaps,
+                    stretched_bonds,
+                } => {
+                    write!(f, "the configuration is broken")?;
+                    if !non_finite.is_empty() {
+                        write!(f, "\n  atoms with non-finite positions: {:?}", non_finite)?;
+                    }
+                    for overlap in overlaps {
+                        write!(
+                            f,
+                            "\n  atoms #{} and #{} overlap at a distance of {}",
+                            overlap.first, overlap.second, overlap.distance
+                        )?;
+                    }
+                    for bond in stretched_bonds {
+                        write!(
+                            f,
+                            "\n  bond #{} between atoms #{} and #{} has a length of {}",
+                            bond.bond, bond.atoms[0], bond.atoms[1], bond.length
+                        )?;
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    impl<T: Debug + Display> Error for ConfigurationError<T> {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            match self {
+                Self::InvalidIndex(err) => Some(err),
+                Self::Clashes { .. } => None,
+            }
+        }
+    }
+
+    /// Checks an initial configuration for atoms which are not finite,
+    /// pairs of atoms closer than `limits.min_distance` and bonds
+    /// between the pairs of atoms in `bonds` longer than `limits.max_bond_length`.
+    ///
+    /// Overlaps are found with a [`VerletList`] of range `limits.min_distance`,
+    /// so the check is cheap enough to run on every setup. Distances are measured between
+    /// the closest periodic images if `periodic_box` is given.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limits.min_distance` exceeds half of a periodic length of `periodic_box`.
+    pub fn validate_configuration<const N: usize, T, V>(
+        positions: &[V],
+        bonds: &[[usize; 2]],
+        periodic_box: Option<&PeriodicBox<T, N>>,
+        limits: &ConfigurationLimits<T>,
+    ) -> Result<(), ConfigurationError<T>>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        assert!(
+            limits.min_distance > T::zero(),
+            "the minimal distance must be positive"
+        );
+        assert!(
+            limits.max_bond_length > limits.min_distance,
+            "the maximal bond length must exceed the minimal distance"
+        );
+        let distance = |first: &V, second: &V| {
+            let displacement = second.clone() - first.clone();
+            match periodic_box {
+                Some(periodic_box) => periodic_box.minimum_image(displacement),
+                None => displacement,
+            }
+            .magnitude_squared()
+            .sqrt()
+        };
+
+        let non_finite: Vec<usize> = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, position)| {
+                !position
+                    .as_array()
+                    .iter()
+                    .all(|coordinate| coordinate.is_finite())
+            })
+            .map(|(atom, _)| atom)
+            .collect();
+
+        let mut stretched_bonds = Vec::new();
+        for (bond, &[first, second]) in bonds.iter().enumerate() {
+            for atom in [first, second] {
+                if atom >= positions.len() {
+                    return Err(InvalidIndexError::new(atom, positions.len()).into());
+                }
+            }
+            let length = distance(&positions[first], &positions[second]);
+            if length > limits.max_bond_length {
+                stretched_bonds.push(StretchedBond {
+                    bond,
+                    atoms: [first, second],
+                    length,
+                });
+            }
+        }
+
+        // The atoms which are not finite cannot be sorted into cells and are already reported.
+        let finite: Vec<usize> = (0..positions.len())
+            .filter(|atom| non_finite.binary_search(atom).is_err())
+            .collect();
+        let finite_positions: Vec<V> = finite.iter().map(|&atom| positions[atom].clone()).collect();
+        let mut list = VerletList::new(limits.min_distance, T::zero());
+        if let Some(periodic_box) = periodic_box {
+            list.set_box(*periodic_box);
+        }
+        list.rebuild(&finite_positions, None)
+            .expect("finite positions must be sorted into cells");
+        let overlaps: Vec<Overlap<T>> = list
+            .pairs()
+            .filter_map(|(first, second)| {
+                let distance = distance(&finite_positions[first], &finite_positions[second]);
+                (distance < limits.min_distance).then_some(Overlap {
+                    first: finite[first],
+                    second: finite[second],
+                    distance,
+                })
+            })
+            .collect();
+
+        if non_finite.is_empty() && overlaps.is_empty() && stretched_bonds.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigurationError::Clashes {
+                non_finite,
+                overlaps,
+                stretched_bonds,
+            })
+        }
+    }
+}
+
+pub use configuration::{
+    ConfigurationError, ConfigurationLimits, Overlap, StretchedBond, validate_configuration,
+};
+
+mod advisor {
+    use std::fmt::{Display, Formatter, Result as FmtResult};
+
+    use num::Float;
+
+    use crate::{core::constants::REDUCED_PLANK_CONSTANT, estimator::trotter::TrotterThresholds};
+
+    /// How stiff the potential an atom type feels is.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Stiffness<T> {
+        /// The highest angular frequency of the vibrations of the atoms.
+        Frequency(T),
+        /// The largest curvature of the potential energy surface along the coordinates
+        /// of an atom, e.g. the largest eigenvalue of its block of the Hessian.
+        Curvature(T),
+    }
+
+    /// An atom type as seen by the [`StepAdvisor`].
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Species<T> {
+        pub label: String,
+        pub mass: T,
+        /// Whether the atoms are represented by ring polymers.
+        pub quantum: bool,
+        pub stiffness: Stiffness<T>,
+    }
+
+    impl<T: Float> Species<T> {
+        /// Returns the highest angular frequency of the vibrations of the atoms.
+        pub fn frequency(&self) -> T {
+            match self.stiffness {
+                Stiffness::Frequency(frequency) => frequency,
+                Stiffness::Curvature(curvature) => (curvature / self.mass).sqrt(),
+            }
+        }
+    }
+
+    /// What limits the suggested time step.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum StepLimit {
+        /// The vibrations of the species at the index in the composition.
+        Vibrations(usize),
+        /// The springs between the beads of the species at the index in the composition.
+        Springs(usize),
+    }
+
+    /// The parameters suggested for a system by a [`StepAdvisor`].
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Advice<T> {
+        /// The suggested time step.
+        pub time_step: T,
+        /// The time step beyond which velocity Verlet is unstable.
+        pub stability_limit: T,
+        pub limit: Option<StepLimit>,
+        /// The smallest number of beads resolving the quantum fluctuations of every species.
+        pub beads: usize,
+        /// The highest angular frequency of the system, including the springs.
+        pub max_frequency: T,
+    }
+
+    impl<T: Display> Display for Advice<T> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            writeln!(f, "suggested time step: {}", self.time_step)?;
+            write!(f, "  stable up to {}", self.stability_limit)?;
+            match self.limit {
+                Some(StepLimit::Vibrations(species)) => {
+                    writeln!(f, ", limited by the vibrations of species #{}", species)?
+                }
+                Some(StepLimit::Springs(species)) => {
+                    writeln!(f, ", limited by the ring polymer of species #{}", species)?
+                }
+                None => writeln!(f)?,
+            }
+            write!(f, "minimum number of beads: {}", self.beads)
+        }
+    }
+
+    /// Suggests a time step and a number of beads from the composition of a system
+    /// before it is run.
+    ///
+    /// The number of beads of a quantum species must be at least a multiple of `βħω`
+    /// for its stiffest vibration `ω`. Velocity Verlet is stable only for time steps
+    /// below `2 / ω_max`, where `ω_max` is the highest frequency of the system, which for
+    /// `P` beads includes the free ring polymer frequencies up to `2 P k_B T / ħ`
+    /// unless the springs are integrated exactly in the normal-mode representation.
+    #[derive(Clone, Copy, Debug)]
+    pub struct StepAdvisor<T> {
+        /// The number of steps the suggested time step resolves the fastest period in.
+        pub steps_per_period: T,
+        /// The smallest number of beads per unit of `βħω`.
+        pub beads_per_quantumness: T,
+        /// Whether the propagator integrates the springs exactly.
+        pub exact_springs: bool,
+    }
+
+    impl<T: Float + From<f32>> Default for StepAdvisor<T> {
+        fn default() -> Self {
+            Self {
+                steps_per_period: <T as From<f32>>::from(20.0),
+                beads_per_quantumness: TrotterThresholds::default().beads_per_quantumness,
+                exact_springs: false,
+            }
+        }
+    }
+
+    impl<T: Float + From<f32>> StepAdvisor<T> {
+        /// Advises on a system of `species` at the thermal energy `thermal_energy`,
+        /// sampled with the suggested number of beads.
+        pub fn advise(&self, species: &[Species<T>], thermal_energy: T) -> Advice<T> {
+            let beads = self.minimum_beads(species, thermal_energy);
+            self.advise_with_beads(species, thermal_energy, beads)
+        }
+
+        /// Advises on a system of `species` at the thermal energy `thermal_energy`
+        /// sampled with `beads` beads, which is kept in the advice even if it is too small.
+        pub fn advise_with_beads(
+            &self,
+            species: &[Species<T>],
+            thermal_energy: T,
+            beads: usize,
+        ) -> Advice<T> {
+            assert!(
+                thermal_energy > T::zero(),
+                "the thermal energy must be positive"
+            );
+            assert!(beads > 0, "the number of beads must be positive");
+            let hbar = <T as From<f32>>::from(REDUCED_PLANK_CONSTANT);
+            let pi = <T as From<f32>>::from(std::f32::consts::PI);
+            let two = <T as From<f32>>::from(2.0);
+            let count = <T as From<f32>>::from(beads as f32);
+            // The highest free ring polymer frequency, `2 ω_P sin(π ⌊P/2⌋ / P)`.
+            let spring_frequency = two * count * thermal_energy / hbar
+                * (pi * <T as From<f32>>::from((beads / 2) as f32) / count).sin();
+
+            let mut max_frequency = T::zero();
+            let mut limit = None;
+            for (index, species) in species.iter().enumerate() {
+                let vibrations = species.frequency();
+                let (frequency, cause) = if species.quantum && !self.exact_springs && beads > 1 {
+                    let frequency =
+                        (vibrations * vibrations + spring_frequency * spring_frequency).sqrt();
+                    let cause = if spring_frequency > vibrations {
+                        StepLimit::Springs(index)
+                    } else {
+                        StepLimit::Vibrations(index)
+                    };
+                    (frequency, cause)
+                } else {
+                    (vibrations, StepLimit::Vibrations(index))
+                };
+                if frequency > max_frequency {
+                    max_frequency = frequency;
+                    limit = Some(cause);
+                }
+            }
+
+            let (time_step, stability_limit) = if max_frequency > T::zero() {
+                (
+                    two * pi / (max_frequency * self.steps_per_period),
+                    two / max_frequency,
+                )
+            } else {
+                (T::infinity(), T::infinity())
+            };
+            Advice {
+                time_step,
+                stability_limit,
+                limit,
+                beads,
+                max_frequency,
+            }
+        }
+
+        /// Returns the smallest number of beads resolving the quantum fluctuations
+        /// of every species at the thermal energy `thermal_energy`.
+        pub fn minimum_beads(&self, species: &[Species<T>], thermal_energy: T) -> usize {
+            let hbar = <T as From<f32>>::from(REDUCED_PLANK_CONSTANT);
+            species
+                .iter()
+                .filter(|species| species.quantum)
+                .map(|species| {
+                    (self.beads_per_quantumness * hbar * species.frequency() / thermal_energy)
+                        .ceil()
+                        .to_usize()
+                        .unwrap_or(usize::MAX)
+                })
+                .max()
+                .unwrap_or(1)
+                .max(1)
+        }
+    }
+}
+
+pub use advisor::{Advice, Species, StepAdvisor, StepLimit, Stiffness};
@@ -2,8 +2,8 @@ mod langevin {
     use std::{array, convert::Infallible, ops::Mul};
 
     use lib::{
-        core::{Decoupled, Vector, error::EmptyError},
-        thermostat::AtomDecoupledThermostat,
+        core::{Decoupled, Vector, error::EmptyError, warnings::WarningSink},
+        thermostat::{AtomDecoupledThermostat, TemperatureDependent},
     };
     use num::Float;
     use rand::Rng;
@@ -37,6 +37,32 @@ mod langevin {
         }
     }
 
+    impl<const N: usize, T: Float, R> Langevin<N, T, R> {
+        /// Warns through `sink` if the friction `gamma` relaxes the momenta within fewer than
+        /// ten steps of `time_step`, which overdamps the dynamics rather than thermalizing it.
+        pub fn check_friction(gamma: T, time_step: T, sink: &WarningSink) {
+            sink.check_time_constant(
+                "Langevin thermostat",
+                gamma.recip().to_f64().unwrap_or(f64::NAN),
+                time_step.to_f64().unwrap_or(f64::NAN),
+                10.0,
+            );
+        }
+    }
+
+    impl<const N: usize, T, R> TemperatureDependent<T> for Langevin<N, T, R>
+    where
+        T: Clone + From<f32> + PartialOrd + Mul<Output = T>,
+    {
+        fn set_temperature(&mut self, temperature: &T) {
+            assert!(
+                temperature.clone() > 0.0.into(),
+                "the temperature must be positive"
+            );
+            self.beta_recip = T::from(BOLTZMANN_CONSTANT) * temperature.clone();
+        }
+    }
+
     impl<const N: usize, T, V, R> AtomDecoupledThermostat<T, V> for Langevin<N, T, R>
     where
         T: Clone + From<f32> + Float,
@@ -71,3 +97,275 @@ mod langevin {
 }
 
 pub use langevin::Langevin;
+
+mod mode_momenta {
+    use std::{array, ops::Mul};
+
+    use lib::{
+        core::Vector,
+        potential::exchange::quadratic::Transform,
+        thermostat::{TemperatureDependent, resample_mode_momenta},
+    };
+    use num::Float;
+    use rand::Rng;
+    use rand_distr::{Distribution, StandardNormal};
+
+    use crate::core::constants::BOLTZMANN_CONSTANT;
+
+    /// Draws normal-mode momenta from the Maxwell-Boltzmann distribution,
+    /// which redraws the velocities of a ring polymer when a run is restarted
+    /// at another temperature.
+    pub struct ModeMomentaSampler<T, R> {
+        mass: T,
+        beta_recip: T,
+        mode_frequency: Option<T>,
+        rng: R,
+    }
+
+    impl<T, R> ModeMomentaSampler<T, R>
+    where
+        T: Clone + From<f32> + PartialOrd + Mul<Output = T>,
+    {
+        /// Creates a sampler in which every mode has the physical mass.
+        pub fn new(mass: T, temperature: T, rng: R) -> Self {
+            assert!(mass.clone() > 0.0.into(), "the mass must be positive");
+            assert!(
+                temperature.clone() > 0.0.into(),
+                "the temperature must be positive"
+            );
+            Self {
+                mass,
+                beta_recip: T::from(BOLTZMANN_CONSTANT) * temperature,
+                mode_frequency: None,
+                rng,
+            }
+        }
+
+        /// Scales the mass of every non-centroid mode such that it oscillates
+        /// at `mode_frequency`, treating its eigenvalue as its spring constant.
+        pub fn with_mode_frequency(mut self, mode_frequency: T) -> Self {
+            assert!(
+                mode_frequency.clone() > 0.0.into(),
+                "the mode frequency must be positive"
+            );
+            self.mode_frequency = Some(mode_frequency);
+            self
+        }
+    }
+
+    /// The eigenvalues passed to [`ModeMomentaSampler::sample`] change along with the temperature,
+    /// so they must be taken from an invalidated cache after the temperature is set.
+    impl<T, R> TemperatureDependent<T> for ModeMomentaSampler<T, R>
+    where
+        T: Clone + From<f32> + PartialOrd + Mul<Output = T>,
+    {
+        fn set_temperature(&mut self, temperature: &T) {
+            assert!(
+                temperature.clone() > 0.0.into(),
+                "the temperature must be positive"
+            );
+            self.beta_recip = T::from(BOLTZMANN_CONSTANT) * temperature.clone();
+        }
+    }
+
+    impl<T, R> ModeMomentaSampler<T, R>
+    where
+        T: Float + From<f32>,
+        R: Rng,
+    {
+        /// Draws the momentum of a mode with the given eigenvalue.
+        pub fn sample<const N: usize, V>(&mut self, eigenvalue: T) -> V
+        where
+            V: Vector<N, Element = T>,
+        {
+            let mass = match self.mode_frequency {
+                Some(frequency) if eigenvalue > T::zero() => eigenvalue / (frequency * frequency),
+                _ => self.mass,
+            };
+            V::from(array::from_fn(|_| {
+                <T as From<_>>::from(StandardNormal.sample(&mut self.rng))
+            })) * (mass * self.beta_recip).sqrt()
+        }
+
+        /// Redraws the momenta of the modes of a group from the eigenvalues of `transform`,
+        /// which are written into `eigenvalues`.
+        ///
+        /// The Cartesian momenta follow from the inverse transformation once every group
+        /// in every image has been resampled.
+        pub fn resample<const N: usize, V, Q>(
+            &mut self,
+            transform: &Q,
+            eigenvalues: &mut [T],
+            group_mode_momenta: &mut [V],
+        ) -> Result<(), Q::Error>
+        where
+            V: Vector<N, Element = T>,
+            Q: Transform<T, V> + ?Sized,
+        {
+            resample_mode_momenta(
+                transform,
+                eigenvalues,
+                group_mode_momenta,
+                |_, eigenvalue| self.sample(eigenvalue),
+            )
+        }
+    }
+}
+
+pub use mode_momenta::ModeMomentaSampler;
+
+mod velocity_rescaling {
+    use std::{convert::Infallible, ops::Mul};
+
+    use lib::{
+        core::{GroupInTypeInImageInSystem, Vector},
+        thermostat::{DegreesOfFreedomDependent, TemperatureDependent, Thermostat},
+    };
+    use num::{Float, NumCast};
+    use rand::Rng;
+    use rand_distr::{ChiSquared, Distribution, StandardNormal};
+
+    use crate::core::constants::BOLTZMANN_CONSTANT;
+
+    /// The stochastic velocity rescaling thermostat of Bussi, Donadio and Parrinello,
+    /// which rescales the momenta of a whole group at once so that its kinetic energy
+    /// relaxes over `time_constant` to the canonical distribution of its degrees of freedom.
+    ///
+    /// The number of degrees of freedom is updated through [`DegreesOfFreedomDependent`],
+    /// e.g. by [`lib::core::Frozen::update_degrees_of_freedom`], which leaves out frozen atoms.
+    pub struct VelocityRescaling<const N: usize, T, R> {
+        mass: T,
+        beta_recip: T,
+        /// `exp(-Δt / τ)`, the fraction of the kinetic energy kept over a step.
+        persistence: T,
+        degrees_of_freedom: usize,
+        rng: R,
+    }
+
+    impl<const N: usize, T, R> VelocityRescaling<N, T, R>
+    where
+        T: Float + From<f32>,
+    {
+        /// Constructs a thermostat of a group of `degrees_of_freedom` degrees of freedom
+        /// of atoms of mass `mass`, applied every `time_step`.
+        pub fn new(
+            mass: T,
+            temperature: T,
+            time_step: T,
+            time_constant: T,
+            degrees_of_freedom: usize,
+            rng: R,
+        ) -> Self {
+            assert!(mass > T::zero(), "the mass must be positive");
+            assert!(temperature > T::zero(), "the temperature must be positive");
+            assert!(
+                time_constant > T::zero(),
+                "the time constant must be positive"
+            );
+            Self {
+                mass,
+                beta_recip: <T as From<f32>>::from(BOLTZMANN_CONSTANT) * temperature,
+                persistence: (-time_step / time_constant).exp(),
+                degrees_of_freedom,
+                rng,
+            }
+        }
+
+        pub fn degrees_of_freedom(&self) -> usize {
+            self.degrees_of_freedom
+        }
+    }
+
+    impl<const N: usize, T, R> TemperatureDependent<T> for VelocityRescaling<N, T, R>
+    where
+        T: Clone + From<f32> + PartialOrd + Mul<Output = T>,
+    {
+        fn set_temperature(&mut self, temperature: &T) {
+            assert!(
+                temperature.clone() > 0.0.into(),
+                "the temperature must be positive"
+            );
+            self.beta_recip = T::from(BOLTZMANN_CONSTANT) * temperature.clone();
+        }
+    }
+
+    impl<const N: usize, T, R> DegreesOfFreedomDependent for VelocityRescaling<N, T, R> {
+        fn set_degrees_of_freedom(&mut self, degrees_of_freedom: usize) {
+            self.degrees_of_freedom = degrees_of_freedom;
+        }
+    }
+
+    impl<const N: usize, T, R> VelocityRescaling<N, T, R>
+    where
+        T: Float + From<f32>,
+        R: Rng,
+    {
+        /// Rescales `group_momenta` by the factor drawn for their kinetic energy.
+        ///
+        /// Returns the change in the kinetic energy.
+        pub fn rescale<V>(&mut self, group_momenta: &mut [V]) -> T
+        where
+            V: Vector<N, Element = T> + Clone,
+        {
+            let half = <T as From<f32>>::from(0.5);
+            let kinetic_energy = group_momenta
+                .iter()
+                .map(|momentum| momentum.clone().magnitude_squared())
+                .fold(T::zero(), |accum, squared| accum + squared)
+                * half
+                / self.mass;
+            if self.degrees_of_freedom == 0 || kinetic_energy <= T::zero() {
+                return T::zero();
+            }
+            // The mean kinetic energy per degree of freedom, `½ k_B T`, over the current one.
+            let ratio = half * self.beta_recip / kinetic_energy;
+            let first = <T as From<f32>>::from(StandardNormal.sample(&mut self.rng));
+            let rest = match self.degrees_of_freedom {
+                1 => T::zero(),
+                count => ChiSquared::new((count - 1) as f64)
+                    .ok()
+                    .and_then(|distribution| {
+                        <T as NumCast>::from(distribution.sample(&mut self.rng))
+                    })
+                    .expect("the remaining degrees of freedom must draw a finite sample"),
+            };
+            let persistence = self.persistence;
+            let relaxation = T::one() - persistence;
+            let factor_squared = persistence
+                + relaxation * ratio * (first * first + rest)
+                + (first + first) * (persistence * relaxation * ratio).sqrt();
+            // The sign keeps the dynamics time reversible, as in the original scheme.
+            let sign = if first + (persistence / (relaxation * ratio)).sqrt() < T::zero() {
+                -T::one()
+            } else {
+                T::one()
+            };
+            let factor = sign * factor_squared.sqrt();
+            for momentum in group_momenta.iter_mut() {
+                *momentum = momentum.clone() * factor;
+            }
+            (factor_squared - T::one()) * kinetic_energy
+        }
+    }
+
+    impl<const N: usize, T, V, R> Thermostat<T, V> for VelocityRescaling<N, T, R>
+    where
+        T: Float + From<f32>,
+        V: Vector<N, Element = T> + Clone,
+        R: Rng,
+    {
+        type Error = Infallible;
+
+        fn thermalize(
+            &mut self,
+            _positions: &GroupInTypeInImageInSystem<V>,
+            _physical_forces: &GroupInTypeInImageInSystem<V>,
+            _exchange_forces: &GroupInTypeInImageInSystem<V>,
+            group_momenta: &mut [V],
+        ) -> Result<T, Self::Error> {
+            Ok(self.rescale(group_momenta))
+        }
+    }
+}
+
+pub use velocity_rescaling::VelocityRescaling;
@@ -0,0 +1,220 @@
+mod array_vec {
+    use std::{
+        fmt::{Debug, Formatter, Result as FmtResult},
+        mem::{self, MaybeUninit},
+        ops::{Deref, DerefMut},
+        ptr, slice,
+    };
+
+    /// What an [`ArrayVec`] does when an element is pushed beyond its capacity.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum Overflow {
+        /// Panic, for lists whose size is bounded by construction.
+        #[default]
+        Panic,
+        /// Move the elements to the heap and keep growing there.
+        Spill,
+    }
+
+    enum Storage<T, const CAP: usize> {
+        Inline {
+            items: [MaybeUninit<T>; CAP],
+            len: usize,
+        },
+        Spilled(Vec<T>),
+    }
+
+    /// A list of at most `CAP` elements stored inline, e.g. the neighbors of a single atom
+    /// gathered in a hot loop, which then never touches the allocator.
+    pub struct ArrayVec<T, const CAP: usize> {
+        storage: Storage<T, CAP>,
+        overflow: Overflow,
+    }
+
+    impl<T, const CAP: usize> ArrayVec<T, CAP> {
+        /// Constructs an empty list which panics when it overflows.
+        pub const fn new() -> Self {
+            Self::with_overflow(Overflow::Panic)
+        }
+
+        /// Constructs an empty list with the given overflow policy.
+        pub const fn with_overflow(overflow: Overflow) -> Self {
+            Self {
+                storage: Storage::Inline {
+                    items: [const { MaybeUninit::uninit() }; CAP],
+                    len: 0,
+                },
+                overflow,
+            }
+        }
+
+        pub fn overflow(&self) -> Overflow {
+            self.overflow
+        }
+
+        /// Returns whether the elements have been moved to the heap.
+        pub fn is_spilled(&self) -> bool {
+            matches!(self.storage, Storage::Spilled(_))
+        }
+
+        /// Returns the number of elements the list holds without growing.
+        pub fn capacity(&self) -> usize {
+            match &self.storage {
+                Storage::Inline { .. } => CAP,
+                Storage::Spilled(items) => items.capacity(),
+            }
+        }
+
+        /// Appends `value`, or returns it if the list is full and does not spill.
+        pub fn try_push(&mut self, value: T) -> Result<(), T> {
+            match &mut self.storage {
+                Storage::Inline { items, len } => {
+                    if let Some(item) = items.get_mut(*len) {
+                        item.write(value);
+                        *len += 1;
+                        return Ok(());
+                    }
+                    if self.overflow == Overflow::Panic {
+                        return Err(value);
+                    }
+                    let mut spilled = Vec::with_capacity(2 * CAP.max(1));
+                    let len = mem::take(len);
+                    // SAFETY: The first `len` items are initialized and, with the length reset,
+                    //         are never read again from the inline storage.
+                    spilled.extend(
+                        items[..len]
+                            .iter()
+                            .map(|item| unsafe { item.assume_init_read() }),
+                    );
+                    spilled.push(value);
+                    self.storage = Storage::Spilled(spilled);
+                    Ok(())
+                }
+                Storage::Spilled(items) => {
+                    items.push(value);
+                    Ok(())
+                }
+            }
+        }
+
+        /// Appends `value`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the list already holds `CAP` elements and does not spill.
+        pub fn push(&mut self, value: T) {
+            if self.try_push(value).is_err() {
+                panic!("the capacity of {} elements is exceeded", CAP);
+            }
+        }
+
+        /// Removes the last element and returns it, if any.
+        pub fn pop(&mut self) -> Option<T> {
+            match &mut self.storage {
+                Storage::Inline { items, len } => {
+                    *len = len.checked_sub(1)?;
+                    // SAFETY: The item was initialized and is now past the length.
+                    Some(unsafe { items[*len].assume_init_read() })
+                }
+                Storage::Spilled(items) => items.pop(),
+            }
+        }
+
+        /// Removes every element, keeping the heap storage of a spilled list for reuse.
+        pub fn clear(&mut self) {
+            match &mut self.storage {
+                Storage::Inline { items, len } => {
+                    let initialized = ptr::slice_from_raw_parts_mut(
+                        items.as_mut_ptr().cast::<T>(),
+                        mem::take(len),
+                    );
+                    // SAFETY: The items were initialized and are now past the length.
+                    unsafe { ptr::drop_in_place(initialized) };
+                }
+                Storage::Spilled(items) => items.clear(),
+            }
+        }
+
+        pub fn as_slice(&self) -> &[T] {
+            match &self.storage {
+                // SAFETY: The first `len` items are initialized.
+                Storage::Inline { items, len } => unsafe {
+                    slice::from_raw_parts(items.as_ptr().cast::<T>(), *len)
+                },
+                Storage::Spilled(items) => items,
+            }
+        }
+
+        pub fn as_mut_slice(&mut self) -> &mut [T] {
+            match &mut self.storage {
+                // SAFETY: The first `len` items are initialized.
+                Storage::Inline { items, len } => unsafe {
+                    slice::from_raw_parts_mut(items.as_mut_ptr().cast::<T>(), *len)
+                },
+                Storage::Spilled(items) => items,
+            }
+        }
+    }
+
+    impl<T, const CAP: usize> Drop for ArrayVec<T, CAP> {
+        fn drop(&mut self) {
+            self.clear();
+        }
+    }
+
+    impl<T, const CAP: usize> Default for ArrayVec<T, CAP> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T: Clone, const CAP: usize> Clone for ArrayVec<T, CAP> {
+        fn clone(&self) -> Self {
+            let mut clone = Self::with_overflow(self.overflow);
+            if let Storage::Spilled(items) = &self.storage {
+                clone.storage = Storage::Spilled(Vec::with_capacity(items.capacity()));
+            }
+            clone.extend(self.iter().cloned());
+            clone
+        }
+    }
+
+    impl<T: Debug, const CAP: usize> Debug for ArrayVec<T, CAP> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            f.debug_list().entries(self.iter()).finish()
+        }
+    }
+
+    impl<T, const CAP: usize> Deref for ArrayVec<T, CAP> {
+        type Target = [T];
+
+        fn deref(&self) -> &[T] {
+            self.as_slice()
+        }
+    }
+
+    impl<T, const CAP: usize> DerefMut for ArrayVec<T, CAP> {
+        fn deref_mut(&mut self) -> &mut [T] {
+            self.as_mut_slice()
+        }
+    }
+
+    impl<T, const CAP: usize> Extend<T> for ArrayVec<T, CAP> {
+        fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+            for value in iter {
+                self.push(value);
+            }
+        }
+    }
+
+    impl<'a, T, const CAP: usize> IntoIterator for &'a ArrayVec<T, CAP> {
+        type Item = &'a T;
+        type IntoIter = slice::Iter<'a, T>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            self.iter()
+        }
+    }
+}
+
+pub use array_vec::{ArrayVec, Overflow};
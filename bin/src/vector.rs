@@ -1,9 +1,10 @@
+#[cfg(not(feature = "stable"))]
 mod simd_vector {
     use lib::core::Vector;
     use std::{
         iter::Sum,
         ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
-        simd::{Simd, SimdElement},
+        simd::{Simd, SimdElement, StdFloat},
     };
 
     pub struct SimdVector<const N: usize, T: SimdElement>(Simd<T, N>);
@@ -117,6 +118,7 @@ mod simd_vector {
     impl<const N: usize, T> Vector<N> for SimdVector<N, T>
     where
         T: SimdElement
+            + Default
             + Add<Output = T>
             + Sub<Output = T>
             + Mul<Output = T>
@@ -126,10 +128,23 @@ mod simd_vector {
             + Sub<Output = Simd<T, N>>
             + Mul<Output = Simd<T, N>>
             + Div<Output = Simd<T, N>>
-            + Neg<Output = Simd<T, N>>,
+            + Neg<Output = Simd<T, N>>
+            + StdFloat,
     {
         type Element = T;
 
+        fn zero() -> Self {
+            Self(Simd::splat(T::default()))
+        }
+
+        fn splat(value: Self::Element) -> Self {
+            Self(Simd::splat(value))
+        }
+
+        fn from_array(array: [Self::Element; N]) -> Self {
+            Self(Simd::from_array(array))
+        }
+
         fn as_array(&self) -> &[Self::Element; N] {
             self.0.as_array()
         }
@@ -145,17 +160,23 @@ mod simd_vector {
         fn dot(self, rhs: Self) -> Self::Element {
             (self.0 * rhs.0).to_array().into_iter().sum()
         }
+
+        fn mul_add_assign(&mut self, a: &Self, b: Self::Element) {
+            self.0 = a.0.mul_add(Simd::splat(b), self.0);
+        }
     }
 }
 
 mod array_vector {
     use lib::core::Vector;
     use std::{
+        array,
         iter::Sum,
         mem::{self, MaybeUninit},
         ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
     };
 
+    #[derive(Clone, Copy, Debug, PartialEq)]
     pub struct ArrayVector<const N: usize, T>([T; N]);
 
     impl<const N: usize, T> From<[T; N]> for ArrayVector<N, T> {
@@ -304,6 +325,7 @@ mod array_vector {
     impl<const N: usize, T> Vector<N> for ArrayVector<N, T>
     where
         T: Clone
+            + Default
             + Add<Output = T>
             + AddAssign
             + Sub<Output = T>
@@ -317,6 +339,18 @@ mod array_vector {
     {
         type Element = T;
 
+        fn zero() -> Self {
+            Self(array::from_fn(|_| T::default()))
+        }
+
+        fn splat(value: Self::Element) -> Self {
+            Self(array::from_fn(|_| value.clone()))
+        }
+
+        fn from_array(array: [Self::Element; N]) -> Self {
+            Self(array)
+        }
+
         fn as_array(&self) -> &[Self::Element; N] {
             &self.0
         }
@@ -336,8 +370,251 @@ mod array_vector {
                 .map(|(lhs, rhs)| lhs * rhs)
                 .sum()
         }
+
+        fn mul_add_assign(&mut self, a: &Self, b: Self::Element) {
+            for (elem_self, elem_a) in self.0.iter_mut().zip(&a.0) {
+                *elem_self += elem_a.clone() * b.clone();
+            }
+        }
     }
 }
 
 pub use array_vector::ArrayVector;
+#[cfg(not(feature = "stable"))]
 pub use simd_vector::SimdVector;
+
+/// Bulk kernels over the flattened elements of many vectors, dispatched at runtime
+/// to the widest instruction set the CPU supports.
+///
+/// The backend is detected once, on the first call, so prebuilt binaries
+/// do not have to be compiled for a specific `target-cpu`.
+pub mod batch {
+    use num::Float;
+    use std::{
+        fmt::{Display, Formatter, Result as FmtResult},
+        sync::OnceLock,
+    };
+
+    /// The instruction set the kernels run on.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Backend {
+        Scalar,
+        Avx2,
+        Avx512,
+        Neon,
+    }
+
+    impl Display for Backend {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            let name = match self {
+                Self::Scalar => "scalar",
+                Self::Avx2 => "AVX2",
+                Self::Avx512 => "AVX-512",
+                Self::Neon => "NEON",
+            };
+            write!(f, "{}", name)
+        }
+    }
+
+    fn detect() -> Backend {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx512f") {
+                return Backend::Avx512;
+            }
+            if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+                return Backend::Avx2;
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                return Backend::Neon;
+            }
+        }
+        Backend::Scalar
+    }
+
+    /// Returns the backend selected for this process, detecting it on the first call.
+    pub fn backend() -> Backend {
+        static BACKEND: OnceLock<Backend> = OnceLock::new();
+        *BACKEND.get_or_init(detect)
+    }
+
+    mod scalar {
+        use num::Float;
+
+        /// The number of independent accumulators of a reduction,
+        /// which lets the compiler keep them in separate vector lanes.
+        const LANES: usize = 8;
+
+        /// Returns `a * b + c`, fused into a single rounding if `FUSED`.
+        ///
+        /// Only the kernels built for targets with FMA instructions fuse,
+        /// since elsewhere `mul_add` is a slow call into the math library.
+        #[inline(always)]
+        fn mul_add<const FUSED: bool, T: Float>(a: T, b: T, c: T) -> T {
+            if FUSED { a.mul_add(b, c) } else { a * b + c }
+        }
+
+        #[inline(always)]
+        pub fn axpy<T: Float>(alpha: T, x: &[T], y: &mut [T]) {
+            axpy_with::<false, _>(alpha, x, y)
+        }
+
+        #[inline(always)]
+        pub fn fused_axpy<T: Float>(alpha: T, x: &[T], y: &mut [T]) {
+            axpy_with::<true, _>(alpha, x, y)
+        }
+
+        #[inline(always)]
+        fn axpy_with<const FUSED: bool, T: Float>(alpha: T, x: &[T], y: &mut [T]) {
+            for (y, &x) in y.iter_mut().zip(x) {
+                *y = mul_add::<FUSED, _>(alpha, x, *y);
+            }
+        }
+
+        #[inline(always)]
+        pub fn scale<T: Float>(alpha: T, x: &mut [T]) {
+            for x in x {
+                *x = *x * alpha;
+            }
+        }
+
+        #[inline(always)]
+        pub fn dot<T: Float>(x: &[T], y: &[T]) -> T {
+            dot_with::<false, _>(x, y)
+        }
+
+        #[inline(always)]
+        pub fn fused_dot<T: Float>(x: &[T], y: &[T]) -> T {
+            dot_with::<true, _>(x, y)
+        }
+
+        #[inline(always)]
+        fn dot_with<const FUSED: bool, T: Float>(x: &[T], y: &[T]) -> T {
+            let len = x.len().min(y.len());
+            let (x, y) = (&x[..len], &y[..len]);
+            let mut sums = [T::zero(); LANES];
+            let mut x_chunks = x.chunks_exact(LANES);
+            let mut y_chunks = y.chunks_exact(LANES);
+            for (x, y) in (&mut x_chunks).zip(&mut y_chunks) {
+                for ((sum, &x), &y) in sums.iter_mut().zip(x).zip(y) {
+                    *sum = mul_add::<FUSED, _>(x, y, *sum);
+                }
+            }
+            let tail = x_chunks
+                .remainder()
+                .iter()
+                .zip(y_chunks.remainder())
+                .fold(T::zero(), |sum, (&x, &y)| mul_add::<FUSED, _>(x, y, sum));
+            sums.into_iter().fold(tail, |total, sum| total + sum)
+        }
+    }
+
+    macro_rules! target_kernels {
+        ($name:ident, $arch:literal, $features:literal) => {
+            #[cfg(target_arch = $arch)]
+            mod $name {
+                use super::scalar;
+                use num::Float;
+
+                #[target_feature(enable = $features)]
+                pub fn axpy<T: Float>(alpha: T, x: &[T], y: &mut [T]) {
+                    scalar::fused_axpy(alpha, x, y)
+                }
+
+                #[target_feature(enable = $features)]
+                pub fn scale<T: Float>(alpha: T, x: &mut [T]) {
+                    scalar::scale(alpha, x)
+                }
+
+                #[target_feature(enable = $features)]
+                pub fn dot<T: Float>(x: &[T], y: &[T]) -> T {
+                    scalar::fused_dot(x, y)
+                }
+            }
+        };
+    }
+
+    target_kernels!(avx2, "x86_64", "avx2,fma");
+    target_kernels!(avx512, "x86_64", "avx512f");
+    target_kernels!(neon, "aarch64", "neon");
+
+    macro_rules! dispatch {
+        ($kernel:ident($($arg:expr),*)) => {
+            match backend() {
+                // SAFETY: The backend is only selected if the CPU supports its features.
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx512 => unsafe { avx512::$kernel($($arg),*) },
+                // SAFETY: See above.
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx2 => unsafe { avx2::$kernel($($arg),*) },
+                // SAFETY: See above.
+                #[cfg(target_arch = "aarch64")]
+                Backend::Neon => unsafe { neon::$kernel($($arg),*) },
+                _ => scalar::$kernel($($arg),*),
+            }
+        };
+    }
+
+    /// Adds `alpha * x` to `y` element by element.
+    pub fn axpy<T: Float>(alpha: T, x: &[T], y: &mut [T]) {
+        assert_eq!(x.len(), y.len(), "the slices must be of the same length");
+        dispatch!(axpy(alpha, x, y))
+    }
+
+    /// Multiplies every element of `x` by `alpha`.
+    pub fn scale<T: Float>(alpha: T, x: &mut [T]) {
+        dispatch!(scale(alpha, x))
+    }
+
+    /// Returns the sum of the products of the elements of `x` and `y`.
+    pub fn dot<T: Float>(x: &[T], y: &[T]) -> T {
+        assert_eq!(x.len(), y.len(), "the slices must be of the same length");
+        dispatch!(dot(x, y))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn dispatched_kernels_agree_with_the_scalar_ones() {
+            let mut state = 11u64;
+            let mut uniform = move || {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                (state >> 11) as f64 / 2f64.powi(53) - 0.5
+            };
+            // A length which is not a multiple of the lanes exercises the remainder too.
+            let x: Vec<f64> = (0..1003).map(|_| uniform()).collect();
+            let y: Vec<f64> = (0..1003).map(|_| uniform()).collect();
+            let alpha = 0.75;
+            let magnitude: f64 = x.iter().zip(&y).map(|(x, y)| (x * y).abs()).sum();
+
+            let dispatched = dot(&x, &y);
+            let expected = scalar::dot(&x, &y);
+            assert!(
+                (dispatched - expected).abs() <= 1e-14 * magnitude,
+                "the {} dot product is {} instead of {}",
+                backend(),
+                dispatched,
+                expected
+            );
+
+            let (mut dispatched, mut expected) = (y.clone(), y.clone());
+            axpy(alpha, &x, &mut dispatched);
+            scalar::axpy(alpha, &x, &mut expected);
+            for (dispatched, expected) in dispatched.iter().zip(&expected) {
+                assert!((dispatched - expected).abs() <= f64::EPSILON * expected.abs().max(1.0));
+            }
+
+            let (mut dispatched, mut expected) = (x.clone(), x.clone());
+            scale(alpha, &mut dispatched);
+            scalar::scale(alpha, &mut expected);
+            assert_eq!(dispatched, expected);
+        }
+    }
+}
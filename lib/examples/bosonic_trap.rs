@@ -0,0 +1,255 @@
+//! Two identical particles in a harmonic trap, as bosons and as fermions.
+//!
+//! Every replica samples the path integral of two non-interacting particles in the trap
+//! `½ m ω² x²` with the weights of bosons: the `P` beads of the particles form either
+//! two rings of their own or, after an exchange, a single ring of `2P` beads, and the
+//! forces on the beads are those of both topologies, weighted by their Boltzmann factors.
+//! The same configurations give the energy of fermions by weighting every one with the
+//! sign of the exchange, `(e₁ - e₂) / (e₁ + e₂)`, through [`SignWeightedAverage`].
+//! Both are compared to the exact energies `-∂ ln Z / ∂β` of the partition functions
+//! `(Z₁(β)² ± Z₁(2β)) / 2`, where `Z₁` is that of a single oscillator.
+//!
+//! Run with `cargo run --release --example bosonic_trap`. The example exits with an error
+//! if an estimate disagrees with the exact energy.
+
+mod common;
+
+use common::{Error, Langevin, Rng, check, mean_and_error, normal};
+use lib::{
+    driver::{Driver, DriverBuilder, ReplicaTask},
+    estimator::quantum::SignWeightedAverage,
+    potential::exchange::RingPolymerParameters,
+    thermostat::AtomDecoupledThermostat,
+};
+use rand::SeedableRng;
+use std::{array, convert::Infallible};
+
+const MASS: f64 = 1.0;
+const FREQUENCY: f64 = 1.0;
+const TEMPERATURE: f64 = 1.0;
+const BEADS: usize = 12;
+const TIME_STEP: f64 = 0.01;
+const FRICTION: f64 = 1.0;
+const STEPS: usize = 400_000;
+const EQUILIBRATION: usize = 40_000;
+const REPLICAS: usize = 8;
+
+/// The beads of both particles, the first particle first, sampled at the temperature
+/// of the simulation with the physical potential of every bead scaled by `1 / P`.
+struct Pair {
+    spring_constant: f64,
+    bead_mass: f64,
+    positions: Vec<f64>,
+    momenta: Vec<f64>,
+    forces: Vec<f64>,
+    exchanged_forces: Vec<f64>,
+    /// The probabilities of the two rings and of the exchanged ring in the last configuration.
+    weights: [f64; 2],
+    /// The spring energies of the two rings and of the exchanged ring in the last configuration.
+    spring_energies: [f64; 2],
+    thermostat: Langevin,
+    bosons: SignWeightedAverage<f64, f64>,
+    fermions: SignWeightedAverage<f64, f64>,
+}
+
+impl Pair {
+    fn new(spring_constant: f64, seed: u64) -> Self {
+        let mut rng = Rng::seed_from_u64(seed);
+        let positions = (0..2 * BEADS)
+            .map(|bead| if bead < BEADS { -0.5 } else { 0.5 } + 0.1 * normal(&mut rng))
+            .collect();
+        let bead_mass = MASS / BEADS as f64;
+        let mut pair = Self {
+            spring_constant,
+            bead_mass,
+            positions,
+            momenta: vec![0.0; 2 * BEADS],
+            forces: vec![0.0; 2 * BEADS],
+            exchanged_forces: vec![0.0; 2 * BEADS],
+            weights: [1.0, 0.0],
+            spring_energies: [0.0; 2],
+            thermostat: Langevin::new(
+                TIME_STEP,
+                TEMPERATURE,
+                vec![FRICTION; 2 * BEADS],
+                vec![bead_mass; 2 * BEADS],
+                rng,
+            ),
+            bosons: SignWeightedAverage::new(),
+            fermions: SignWeightedAverage::new(),
+        };
+        pair.update_forces();
+        pair
+    }
+
+    /// Returns the bead following `bead` in its ring, after an exchange if `exchanged`.
+    fn next(bead: usize, exchanged: bool) -> usize {
+        match (bead + 1) % BEADS {
+            0 if exchanged => (bead + 1) % (2 * BEADS),
+            0 => bead + 1 - BEADS,
+            _ => bead + 1,
+        }
+    }
+
+    /// Sets `forces` to the forces of the springs and returns their energy.
+    fn springs(&self, exchanged: bool, forces: &mut [f64]) -> f64 {
+        forces.fill(0.0);
+        let mut energy = 0.0;
+        for bead in 0..2 * BEADS {
+            let next = Self::next(bead, exchanged);
+            let extension = self.positions[next] - self.positions[bead];
+            energy += 0.5 * self.spring_constant * extension * extension;
+            forces[bead] += self.spring_constant * extension;
+            forces[next] -= self.spring_constant * extension;
+        }
+        energy
+    }
+
+    fn update_forces(&mut self) {
+        let mut forces = std::mem::take(&mut self.forces);
+        let mut exchanged_forces = std::mem::take(&mut self.exchanged_forces);
+        let energies = [
+            self.springs(false, &mut forces),
+            self.springs(true, &mut exchanged_forces),
+        ];
+        // The Boltzmann factors, relative to the larger one so neither underflows.
+        let lowest = energies[0].min(energies[1]);
+        let factors = energies.map(|energy| (-(energy - lowest) / TEMPERATURE).exp());
+        let total = factors[0] + factors[1];
+        self.weights = factors.map(|factor| factor / total);
+        self.spring_energies = energies;
+        for ((force, exchanged), &position) in forces
+            .iter_mut()
+            .zip(&exchanged_forces)
+            .zip(&self.positions)
+        {
+            *force = self.weights[0] * *force + self.weights[1] * exchanged
+                - MASS * FREQUENCY * FREQUENCY * position / BEADS as f64;
+        }
+        self.forces = forces;
+        self.exchanged_forces = exchanged_forces;
+    }
+
+    fn kick(&mut self, time: f64) {
+        for (momentum, force) in self.momenta.iter_mut().zip(&self.forces) {
+            *momentum += time * force;
+        }
+    }
+
+    fn drift(&mut self, time: f64) {
+        for (position, momentum) in self.positions.iter_mut().zip(&self.momenta) {
+            *position += time * momentum / self.bead_mass;
+        }
+    }
+
+    fn thermalize(&mut self) {
+        for (bead, momentum) in self.momenta.iter_mut().enumerate() {
+            let Ok(_heat) = AtomDecoupledThermostat::thermalize(
+                &mut self.thermostat,
+                bead,
+                array::from_ref(&self.positions[bead]),
+                array::from_ref(&self.forces[bead]),
+                &[0.0],
+                array::from_mut(momentum),
+            );
+        }
+    }
+
+    /// Adds the primitive estimators of the energy of bosons and of fermions.
+    fn sample(&mut self) {
+        let trap: f64 = self
+            .positions
+            .iter()
+            .map(|position| 0.5 * MASS * FREQUENCY * FREQUENCY * position * position)
+            .sum::<f64>()
+            / BEADS as f64;
+        let free = (2 * BEADS) as f64 * TEMPERATURE / 2.0 + trap;
+        let [separate, exchanged] = self.spring_energies;
+        let [separate_weight, exchanged_weight] = self.weights;
+        self.bosons.add(
+            free - separate_weight * separate - exchanged_weight * exchanged,
+            1.0,
+        );
+        let sign = separate_weight - exchanged_weight;
+        self.fermions.add(
+            free - (separate_weight * separate - exchanged_weight * exchanged) / sign,
+            sign,
+        );
+    }
+}
+
+impl ReplicaTask for Pair {
+    type Error = Infallible;
+
+    fn phases(&self) -> usize {
+        1
+    }
+
+    fn run_phase(&mut self, step: usize, _phase: usize) -> Result<(), Self::Error> {
+        self.kick(0.5 * TIME_STEP);
+        self.drift(0.5 * TIME_STEP);
+        self.thermalize();
+        self.drift(0.5 * TIME_STEP);
+        self.update_forces();
+        self.kick(0.5 * TIME_STEP);
+        if step >= EQUILIBRATION {
+            self.sample();
+        }
+        Ok(())
+    }
+}
+
+/// Returns the exact energy of two particles in the trap, from the partition function
+/// `(Z₁(β)² + exchange Z₁(2β)) / 2`, where `exchange` is 1 for bosons and -1 for fermions.
+fn exact_energy(exchange: f64) -> f64 {
+    let single = |beta: f64| 1.0 / (2.0 * (0.5 * beta * FREQUENCY).sinh());
+    let log_partition =
+        |beta: f64| (0.5 * (single(beta).powi(2) + exchange * single(2.0 * beta))).ln();
+    let beta = 1.0 / TEMPERATURE;
+    let step = 1e-5;
+    -(log_partition(beta + step) - log_partition(beta - step)) / (2.0 * step)
+}
+
+fn main() -> Result<(), Error> {
+    let spring_constant = RingPolymerParameters::new(TEMPERATURE, BEADS).spring_constant(MASS);
+    let mut pairs: Vec<_> = (0..REPLICAS)
+        .map(|replica| Pair::new(spring_constant, replica as u64))
+        .collect();
+    DriverBuilder::new()
+        .build()
+        .execute(&mut pairs, STEPS, |_| Ok(()))?;
+
+    let average = |statistics: fn(&Pair) -> &SignWeightedAverage<f64, f64>| {
+        let averages: Vec<_> = pairs
+            .iter()
+            .map(|pair| statistics(pair).average().unwrap_or(f64::NAN))
+            .collect();
+        mean_and_error(&averages)
+    };
+    let bosons = average(|pair| &pair.bosons);
+    let fermions = average(|pair| &pair.fermions);
+    let signs: Vec<_> = pairs
+        .iter()
+        .map(|pair| pair.fermions.average_sign().unwrap_or(f64::NAN))
+        .collect();
+    let sign = mean_and_error(&signs);
+    let (exact_bosons, exact_fermions) = (exact_energy(1.0), exact_energy(-1.0));
+
+    println!(
+        "# {:>9} {:>10} {:>10} {:>10}",
+        "", "energy", "error", "exact"
+    );
+    println!(
+        "  {:>9} {:>10.5} {:>10.5} {:>10.5}",
+        "bosons", bosons.0, bosons.1, exact_bosons
+    );
+    println!(
+        "  {:>9} {:>10.5} {:>10.5} {:>10.5}",
+        "fermions", fermions.0, fermions.1, exact_fermions
+    );
+    println!("# the average sign is {:.4} ± {:.4}", sign.0, sign.1);
+
+    check("bosons", bosons, exact_bosons, 5.0, 0.02)?;
+    check("fermions", fermions, exact_fermions, 5.0, 0.02)?;
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Helpers shared by the examples: a Langevin thermostat, a table written
+//! to standard output and the statistics of independent replicas.
+
+#![allow(dead_code)]
+
+use lib::{
+    core::error::EmptyError,
+    output::{Provenance, ValuesOutput},
+    thermostat::{AtomDecoupledThermostat, TemperatureDependent},
+};
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, StandardNormal};
+use std::{
+    convert::Infallible,
+    error::Error as StdError,
+    io::{self, Write},
+};
+
+/// The error of an example.
+pub type Error = Box<dyn StdError>;
+
+/// The generator of the random numbers of an example, seeded per replica.
+pub type Rng = StdRng;
+
+/// Returns a number drawn from the standard normal distribution.
+pub fn normal(rng: &mut Rng) -> f64 {
+    StandardNormal.sample(rng)
+}
+
+/// A Langevin thermostat with a friction and a mass of its own for every atom,
+/// so that it thermalizes the normal modes of a ring polymer (PILE) as well as atoms.
+///
+/// The momenta are damped by `exp(-γ Δt)` and refilled with noise
+/// at the temperature of the thermostat.
+pub struct Langevin {
+    time_step: f64,
+    temperature: f64,
+    frictions: Vec<f64>,
+    masses: Vec<f64>,
+    rng: Rng,
+}
+
+impl Langevin {
+    /// Constructs a thermostat of the atoms of masses `masses`, applied for `time_step`
+    /// with the frictions `frictions`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not a friction for every mass.
+    pub fn new(
+        time_step: f64,
+        temperature: f64,
+        frictions: Vec<f64>,
+        masses: Vec<f64>,
+        rng: Rng,
+    ) -> Self {
+        assert_eq!(
+            frictions.len(),
+            masses.len(),
+            "every atom must have a friction and a mass"
+        );
+        Self {
+            time_step,
+            temperature,
+            frictions,
+            masses,
+            rng,
+        }
+    }
+}
+
+impl TemperatureDependent<f64> for Langevin {
+    fn set_temperature(&mut self, temperature: &f64) {
+        self.temperature = *temperature;
+    }
+}
+
+impl<const N: usize> AtomDecoupledThermostat<f64, [f64; N]> for Langevin {
+    type ErrorAtom = Infallible;
+    type ErrorSystem = EmptyError;
+
+    fn thermalize(
+        &mut self,
+        atom_index: usize,
+        _position: &[f64; N],
+        _physical_force: &[f64; N],
+        _exchange_force: &[f64; N],
+        momentum: &mut [f64; N],
+    ) -> Result<f64, Self::ErrorAtom> {
+        let mass = self.masses[atom_index];
+        let damping = (-self.frictions[atom_index] * self.time_step).exp();
+        let noise = ((1.0 - damping * damping) * mass * self.temperature).sqrt();
+        let mut heat = 0.0;
+        for component in momentum {
+            let old = *component;
+            *component = damping * old + noise * normal(&mut self.rng);
+            heat += 0.5 * (*component * *component - old * old) / mass;
+        }
+        Ok(heat)
+    }
+}
+
+/// A whitespace-separated table of values, one line per step, preceded by the provenance
+/// of the run and the names of the columns as comments.
+pub struct Table<W> {
+    writer: W,
+    header: Option<String>,
+}
+
+impl<W: Write> Table<W> {
+    /// Constructs a table of the given `columns`.
+    pub fn new<S: AsRef<str>>(writer: W, columns: &[S]) -> Self {
+        let mut header = format!("# {:>8}", "step");
+        for column in columns {
+            header.push_str("  ");
+            header.push_str(column.as_ref());
+        }
+        Self {
+            writer,
+            header: Some(header),
+        }
+    }
+}
+
+impl<W: Write> ValuesOutput<f64> for Table<W> {
+    type Error = io::Error;
+
+    fn write_provenance(&mut self, provenance: &Provenance) -> Result<(), Self::Error> {
+        provenance.write_header(&mut self.writer, "# ")
+    }
+
+    fn write_step(&mut self, step: usize) -> Result<(), Self::Error> {
+        if let Some(header) = self.header.take() {
+            writeln!(self.writer, "{}", header)?;
+        }
+        write!(self.writer, "  {:>8}", step)
+    }
+
+    fn write_value(&mut self, value: f64) -> Result<(), Self::Error> {
+        write!(self.writer, " {:>14.6}", value)
+    }
+
+    fn new_line(&mut self) -> Result<(), Self::Error> {
+        writeln!(self.writer)
+    }
+}
+
+/// The mean of the averages of independent replicas and its standard error.
+pub fn mean_and_error(averages: &[f64]) -> (f64, f64) {
+    let count = averages.len() as f64;
+    let mean = averages.iter().sum::<f64>() / count;
+    if averages.len() < 2 {
+        return (mean, f64::INFINITY);
+    }
+    let variance = averages
+        .iter()
+        .map(|average| (average - mean) * (average - mean))
+        .sum::<f64>()
+        / (count - 1.0);
+    (mean, (variance / count).sqrt())
+}
+
+/// Checks that `estimate` agrees with `exact` within `sigmas` standard errors
+/// and the relative `tolerance` of the discretization.
+pub fn check(
+    name: &str,
+    (estimate, error): (f64, f64),
+    exact: f64,
+    sigmas: f64,
+    tolerance: f64,
+) -> Result<(), Error> {
+    let allowed = sigmas * error + tolerance * exact.abs();
+    if (estimate - exact).abs() > allowed {
+        return Err(format!(
+            "{}: the estimate {:.5} ± {:.5} disagrees with {:.5}",
+            name, estimate, error, exact
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,291 @@
+//! The convergence of the energy of a quantum harmonic oscillator with the number of beads.
+//!
+//! Every replica is an independent ring polymer of a single particle in the potential
+//! `½ m ω² x²`, propagated in the normal modes of its springs given by
+//! [`NormalModesTransform`]: the free ring polymer is rotated exactly, the physical
+//! forces are applied as kicks and every mode is thermalized by its own Langevin
+//! thermostat (PILE). The centroid-virial energy, around the centroid kept by a
+//! [`CentroidCache`], is compared, for every number of beads `P`, to the exact energy
+//! of the discretized path integral, which tends to `½ ħω coth(βħω / 2)` as `P` grows.
+//!
+//! Run with `cargo run --release --example harmonic_oscillator`. The example exits
+//! with an error if any estimate disagrees with the exact energy.
+
+mod common;
+
+use common::{Error, Langevin, Rng, check, mean_and_error};
+use lib::{
+    core::centroid::CentroidCache,
+    driver::{Driver, DriverBuilder, ReplicaTask},
+    potential::exchange::{RingPolymerParameters, quadratic::NormalModesTransform},
+    thermostat::AtomDecoupledThermostat,
+};
+use rand::SeedableRng;
+use std::{array, convert::Infallible, f64::consts::PI, sync::Arc};
+
+const MASS: f64 = 1.0;
+const FREQUENCY: f64 = 1.0;
+const TEMPERATURE: f64 = 0.25;
+const TIME_STEP: f64 = 0.05;
+const CENTROID_FRICTION: f64 = 1.0;
+const STEPS: usize = 100_000;
+const EQUILIBRATION: usize = 10_000;
+const REPLICAS: usize = 8;
+const BEADS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+
+/// The coefficients `C_jl` of the normal modes of a ring polymer, the centroid first,
+/// and their frequencies, both from [`NormalModesTransform`].
+struct NormalModes {
+    beads: usize,
+    /// `C_jl` of bead `j` in mode `l` at `j P + l`.
+    coefficients: Vec<f64>,
+    frequencies: Vec<f64>,
+}
+
+impl NormalModes {
+    /// Diagonalizes the springs of a particle of mass `MASS` in a ring with `parameters`,
+    /// which join beads of mass `bead_mass`.
+    fn new(parameters: &RingPolymerParameters<f64>, bead_mass: f64) -> Self {
+        let beads = parameters.beads();
+        let coefficients = (0..beads * beads)
+            .map(|index| {
+                NormalModesTransform::<1, f64>::coefficient(index / beads, index % beads, beads)
+            })
+            .collect();
+        let frequencies = (0..beads)
+            .map(|mode| {
+                (NormalModesTransform::<1, f64>::new(MASS, parameters, mode, 0).eigenvalue()
+                    / bead_mass)
+                    .sqrt()
+            })
+            .collect();
+        Self {
+            beads,
+            coefficients,
+            frequencies,
+        }
+    }
+
+    fn to_modes(&self, cartesian: &[f64], modes: &mut [f64]) {
+        for (mode, value) in modes.iter_mut().enumerate() {
+            *value = (0..self.beads)
+                .map(|bead| self.coefficients[bead * self.beads + mode] * cartesian[bead])
+                .sum();
+        }
+    }
+
+    fn to_cartesian(&self, modes: &[f64], cartesian: &mut [f64]) {
+        for (bead, value) in cartesian.iter_mut().enumerate() {
+            *value = self.coefficients[bead * self.beads..(bead + 1) * self.beads]
+                .iter()
+                .zip(modes)
+                .map(|(coefficient, mode)| coefficient * mode)
+                .sum();
+        }
+    }
+}
+
+/// A ring polymer sampled at the temperature of the simulation, with the physical potential
+/// of every bead scaled by `1 / P` and the mass of every bead by `1 / P`, so the centroid
+/// moves with the physical frequency.
+struct Walker {
+    modes: Arc<NormalModes>,
+    bead_mass: f64,
+    positions: Vec<f64>,
+    momenta: Vec<f64>,
+    mode_positions: Vec<f64>,
+    mode_momenta: Vec<f64>,
+    thermostat: Langevin,
+    centroid: CentroidCache<f64>,
+    energy_sum: f64,
+    samples: usize,
+}
+
+impl Walker {
+    fn new(modes: Arc<NormalModes>, bead_mass: f64, seed: u64) -> Self {
+        let beads = modes.beads;
+        // PILE: every mode is critically damped, the centroid with a friction of its own.
+        let frictions = modes
+            .frequencies
+            .iter()
+            .map(|&frequency| {
+                if frequency == 0.0 {
+                    CENTROID_FRICTION
+                } else {
+                    2.0 * frequency
+                }
+            })
+            .collect();
+        let thermostat = Langevin::new(
+            TIME_STEP,
+            TEMPERATURE,
+            frictions,
+            vec![bead_mass; beads],
+            Rng::seed_from_u64(seed),
+        );
+        Self {
+            modes,
+            bead_mass,
+            positions: vec![0.0; beads],
+            momenta: vec![0.0; beads],
+            mode_positions: vec![0.0; beads],
+            mode_momenta: vec![0.0; beads],
+            thermostat,
+            centroid: CentroidCache::new(),
+            energy_sum: 0.0,
+            samples: 0,
+        }
+    }
+
+    fn kick(&mut self, time: f64) {
+        for (momentum, &position) in self.momenta.iter_mut().zip(&self.positions) {
+            *momentum += time * -MASS * FREQUENCY * FREQUENCY * position / self.modes.beads as f64;
+        }
+    }
+
+    fn rotate(&mut self, time: f64) {
+        for ((position, momentum), &frequency) in self
+            .mode_positions
+            .iter_mut()
+            .zip(self.mode_momenta.iter_mut())
+            .zip(&self.modes.frequencies)
+        {
+            if frequency == 0.0 {
+                *position += time * *momentum / self.bead_mass;
+                continue;
+            }
+            let (sin, cos) = (frequency * time).sin_cos();
+            let (q, p) = (*position, *momentum);
+            *position = q * cos + p * sin / (self.bead_mass * frequency);
+            *momentum = p * cos - q * sin * self.bead_mass * frequency;
+        }
+    }
+
+    fn thermalize(&mut self) {
+        for (mode, (position, momentum)) in self
+            .mode_positions
+            .iter()
+            .zip(self.mode_momenta.iter_mut())
+            .enumerate()
+        {
+            let Ok(_heat) = AtomDecoupledThermostat::thermalize(
+                &mut self.thermostat,
+                mode,
+                array::from_ref(position),
+                &[0.0],
+                &[0.0],
+                array::from_mut(momentum),
+            );
+        }
+    }
+
+    /// The centroid-virial estimator of the energy of the beads at `step`.
+    fn energy(&mut self, step: usize) -> f64 {
+        self.centroid
+            .refresh::<f64, _>(self.positions.chunks(1).map(|bead| (step, bead)));
+        let centroid = self.centroid.centroids()[0];
+        let beads = self.positions.len() as f64;
+        let per_bead: f64 = self
+            .positions
+            .iter()
+            .map(|&position| {
+                let stiffness = MASS * FREQUENCY * FREQUENCY;
+                0.5 * stiffness * position * position
+                    + 0.5 * (position - centroid) * stiffness * position
+            })
+            .sum();
+        0.5 * TEMPERATURE + per_bead / beads
+    }
+
+    fn average(&self) -> f64 {
+        self.energy_sum / self.samples as f64
+    }
+}
+
+impl ReplicaTask for Walker {
+    type Error = Infallible;
+
+    fn phases(&self) -> usize {
+        1
+    }
+
+    fn run_phase(&mut self, step: usize, _phase: usize) -> Result<(), Self::Error> {
+        self.kick(0.5 * TIME_STEP);
+        self.modes
+            .to_modes(&self.positions, &mut self.mode_positions);
+        self.modes.to_modes(&self.momenta, &mut self.mode_momenta);
+        self.rotate(0.5 * TIME_STEP);
+        self.thermalize();
+        self.rotate(0.5 * TIME_STEP);
+        self.modes
+            .to_cartesian(&self.mode_positions, &mut self.positions);
+        self.modes
+            .to_cartesian(&self.mode_momenta, &mut self.momenta);
+        self.kick(0.5 * TIME_STEP);
+        if step >= EQUILIBRATION {
+            self.energy_sum += self.energy(step);
+            self.samples += 1;
+        }
+        Ok(())
+    }
+}
+
+/// The energy of the path integral discretized into `beads` beads, from the spectrum
+/// `4 sin²(πk / P)` of the springs.
+fn discretized_energy(beads: usize) -> f64 {
+    let beta = 1.0 / TEMPERATURE;
+    let p = beads as f64;
+    let frequency_squared = FREQUENCY * FREQUENCY;
+    let modes: f64 = (0..beads)
+        .map(|mode| {
+            let spectrum = 4.0 * (PI * mode as f64 / p).sin().powi(2);
+            let springs = MASS * p * spectrum / beta;
+            let trap = MASS * frequency_squared * beta / p;
+            0.5 * (trap / beta - springs / beta) / (springs + trap)
+        })
+        .sum();
+    0.5 * p / beta + modes
+}
+
+fn main() -> Result<(), Error> {
+    let driver = DriverBuilder::new().build();
+    let exact = 0.5 * FREQUENCY / (0.5 * FREQUENCY / TEMPERATURE).tanh();
+    println!("# the exact energy of the oscillator is {:.5}", exact);
+    println!(
+        "# {:>5} {:>12} {:>10} {:>12}",
+        "beads", "energy", "error", "discretized"
+    );
+    for beads in BEADS {
+        let bead_mass = MASS / beads as f64;
+        let modes = Arc::new(NormalModes::new(
+            &RingPolymerParameters::new(TEMPERATURE, beads),
+            bead_mass,
+        ));
+        let mut walkers: Vec<_> = (0..REPLICAS)
+            .map(|replica| {
+                Walker::new(
+                    Arc::clone(&modes),
+                    bead_mass,
+                    (beads * REPLICAS + replica) as u64,
+                )
+            })
+            .collect();
+        driver.execute(&mut walkers, STEPS, |_| Ok(()))?;
+
+        let averages: Vec<_> = walkers.iter().map(Walker::average).collect();
+        let (energy, error) = mean_and_error(&averages);
+        let discretized = discretized_energy(beads);
+        println!(
+            "  {:>5} {:>12.5} {:>10.5} {:>12.5}",
+            beads, energy, error, discretized
+        );
+        check(
+            &format!("{} beads", beads),
+            (energy, error),
+            discretized,
+            5.0,
+            0.01,
+        )?;
+    }
+    Ok(())
+}
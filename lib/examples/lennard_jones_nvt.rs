@@ -0,0 +1,359 @@
+//! A Lennard-Jones liquid in the canonical ensemble.
+//!
+//! Every replica is an independent box of 108 atoms at the reduced density 0.8442
+//! and temperature 0.722, near the triple point, started from a face-centered cubic
+//! lattice, melted at a higher temperature and propagated by BAOAB Langevin dynamics. After every 500 steps the
+//! potential energy per atom, the kinetic temperature and the virial pressure,
+//! averaged over the replicas, are converted to the units of argon and written to
+//! standard output, preceded by the provenance of the run.
+//!
+//! Run with `cargo run --release --example lennard_jones_nvt`, adding `-- --single-threaded`
+//! to run the replicas round-robin on a single thread, which gives the same output.
+//! The example exits with an error if the thermostat misses the temperature
+//! or the lattice fails to melt.
+
+mod common;
+
+use common::{Error, Langevin, Rng, Table, check, mean_and_error, normal};
+use lib::{
+    driver::{Driver, DriverBuilder, ReplicaTask},
+    output::{
+        BaseUnit, Converted, Dimension, ObservableRegistry, Provenance, Resumable, UnitSystem,
+        ValuesOutput,
+    },
+    thermostat::{AtomDecoupledThermostat, TemperatureDependent},
+};
+use rand::SeedableRng;
+use std::{
+    io,
+    num::NonZero,
+    sync::{Arc, Mutex},
+};
+
+const CELLS: usize = 3;
+const ATOMS: usize = 4 * CELLS * CELLS * CELLS;
+const DENSITY: f64 = 0.8442;
+const TEMPERATURE: f64 = 0.722;
+const CUTOFF: f64 = 2.5;
+const TIME_STEP: f64 = 0.005;
+const FRICTION: f64 = 1.0;
+const MELTING_TEMPERATURE: f64 = 2.0;
+const MELTING: usize = 1_000;
+const STEPS: usize = 20_000;
+const EQUILIBRATION: usize = 4_000;
+const STRIDE: usize = 500;
+const REPLICAS: usize = 4;
+
+/// The instantaneous observables of a replica.
+#[derive(Clone, Copy, Debug, Default)]
+struct Sample {
+    potential_energy: f64,
+    temperature: f64,
+    pressure: f64,
+}
+
+/// A periodic box of atoms interacting by the Lennard-Jones potential,
+/// truncated and shifted to zero at the cutoff.
+struct Liquid {
+    length: f64,
+    positions: Vec<[f64; 3]>,
+    momenta: Vec<[f64; 3]>,
+    forces: Vec<[f64; 3]>,
+    /// The distance every atom has travelled from its lattice site, across the periodic images.
+    displacements: Vec<[f64; 3]>,
+    potential_energy: f64,
+    virial: f64,
+    thermostat: Langevin,
+    index: usize,
+    samples: Arc<Mutex<Vec<Sample>>>,
+    sums: Sample,
+    averaged: usize,
+}
+
+impl Liquid {
+    fn new(index: usize, seed: u64, samples: Arc<Mutex<Vec<Sample>>>) -> Self {
+        let length = (ATOMS as f64 / DENSITY).cbrt();
+        let lattice = length / CELLS as f64;
+        let basis = [
+            [0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.0],
+            [0.5, 0.0, 0.5],
+            [0.0, 0.5, 0.5],
+        ];
+        let mut positions = Vec::with_capacity(ATOMS);
+        for cell in 0..CELLS * CELLS * CELLS {
+            let origin = [cell / (CELLS * CELLS), cell / CELLS % CELLS, cell % CELLS];
+            for offset in basis {
+                positions
+                    .push([0, 1, 2].map(|axis| (origin[axis] as f64 + offset[axis]) * lattice));
+            }
+        }
+        let mut rng = Rng::seed_from_u64(seed);
+        let momenta = (0..ATOMS)
+            .map(|_| [(); 3].map(|_| TEMPERATURE.sqrt() * normal(&mut rng)))
+            .collect();
+        let mut liquid = Self {
+            length,
+            positions,
+            momenta,
+            forces: vec![[0.0; 3]; ATOMS],
+            displacements: vec![[0.0; 3]; ATOMS],
+            potential_energy: 0.0,
+            virial: 0.0,
+            thermostat: Langevin::new(
+                TIME_STEP,
+                MELTING_TEMPERATURE,
+                vec![FRICTION; ATOMS],
+                vec![1.0; ATOMS],
+                rng,
+            ),
+            index,
+            samples,
+            sums: Sample::default(),
+            averaged: 0,
+        };
+        liquid.update_forces();
+        liquid
+    }
+
+    fn update_forces(&mut self) {
+        let cutoff_squared = CUTOFF * CUTOFF;
+        let shift = 4.0 * (CUTOFF.powi(-12) - CUTOFF.powi(-6));
+        self.forces.fill([0.0; 3]);
+        self.potential_energy = 0.0;
+        self.virial = 0.0;
+        for first in 0..ATOMS {
+            for second in first + 1..ATOMS {
+                let separation: [f64; 3] = std::array::from_fn(|axis| {
+                    let distance = self.positions[first][axis] - self.positions[second][axis];
+                    distance - self.length * (distance / self.length).round()
+                });
+                let distance_squared: f64 = separation.iter().map(|x| x * x).sum();
+                if distance_squared >= cutoff_squared {
+                    continue;
+                }
+                let inverse_sixth = distance_squared.recip().powi(3);
+                self.potential_energy += 4.0 * inverse_sixth * (inverse_sixth - 1.0) - shift;
+                // The pair virial `r · F = 24 (2 r^-12 - r^-6)`.
+                let pair_virial = 24.0 * inverse_sixth * (2.0 * inverse_sixth - 1.0);
+                self.virial += pair_virial;
+                for (axis, component) in separation.iter().enumerate() {
+                    let force = pair_virial / distance_squared * component;
+                    self.forces[first][axis] += force;
+                    self.forces[second][axis] -= force;
+                }
+            }
+        }
+    }
+
+    fn kick(&mut self, time: f64) {
+        for (momentum, force) in self.momenta.iter_mut().zip(&self.forces) {
+            for axis in 0..3 {
+                momentum[axis] += time * force[axis];
+            }
+        }
+    }
+
+    fn drift(&mut self, time: f64) {
+        for ((position, displacement), momentum) in self
+            .positions
+            .iter_mut()
+            .zip(self.displacements.iter_mut())
+            .zip(&self.momenta)
+        {
+            for axis in 0..3 {
+                position[axis] = (position[axis] + time * momentum[axis]).rem_euclid(self.length);
+                displacement[axis] += time * momentum[axis];
+            }
+        }
+    }
+
+    fn thermalize(&mut self) {
+        for (atom, momentum) in self.momenta.iter_mut().enumerate() {
+            let Ok(_heat) = AtomDecoupledThermostat::thermalize(
+                &mut self.thermostat,
+                atom,
+                &self.positions[atom],
+                &self.forces[atom],
+                &[0.0; 3],
+                momentum,
+            );
+        }
+    }
+
+    fn mean_squared_displacement(&self) -> f64 {
+        self.displacements
+            .iter()
+            .flatten()
+            .map(|component| component * component)
+            .sum::<f64>()
+            / ATOMS as f64
+    }
+
+    fn sample(&self) -> Sample {
+        let volume = self.length.powi(3);
+        let kinetic_energy: f64 = self
+            .momenta
+            .iter()
+            .flatten()
+            .map(|momentum| 0.5 * momentum * momentum)
+            .sum();
+        let temperature = 2.0 * kinetic_energy / (3 * ATOMS) as f64;
+        Sample {
+            potential_energy: self.potential_energy / ATOMS as f64,
+            temperature,
+            pressure: (ATOMS as f64 * temperature + self.virial / 3.0) / volume,
+        }
+    }
+}
+
+impl ReplicaTask for Liquid {
+    type Error = io::Error;
+
+    fn phases(&self) -> usize {
+        1
+    }
+
+    fn run_phase(&mut self, step: usize, _phase: usize) -> Result<(), Self::Error> {
+        self.kick(0.5 * TIME_STEP);
+        self.drift(0.5 * TIME_STEP);
+        if step == MELTING {
+            self.thermostat.set_temperature(&TEMPERATURE);
+        }
+        self.thermalize();
+        self.drift(0.5 * TIME_STEP);
+        self.update_forces();
+        self.kick(0.5 * TIME_STEP);
+
+        let sample = self.sample();
+        if step >= EQUILIBRATION {
+            self.sums.potential_energy += sample.potential_energy;
+            self.sums.temperature += sample.temperature;
+            self.sums.pressure += sample.pressure;
+            self.averaged += 1;
+        }
+        if step.is_multiple_of(STRIDE) {
+            let mut samples = self
+                .samples
+                .lock()
+                .map_err(|_| io::Error::other("poisoned"))?;
+            samples[self.index] = sample;
+        }
+        Ok(())
+    }
+}
+
+fn write_sample<O: ValuesOutput<f64>>(
+    output: &mut O,
+    step: usize,
+    sample: &Sample,
+) -> Result<(), O::Error> {
+    output.write_step(step)?;
+    output.write_value(sample.potential_energy)?;
+    output.write_value(sample.temperature)?;
+    output.write_value(sample.pressure)?;
+    output.new_line()
+}
+
+/// The units of argon: σ = 3.405 Å, ε / k_B = 119.8 K and m = 39.948 u,
+/// so the unit of time is `σ √(m / ε)` = 2.156 ps.
+fn argon() -> UnitSystem<f64> {
+    UnitSystem {
+        length: BaseUnit::new(3.405, "Å"),
+        mass: BaseUnit::new(39.948, "u"),
+        time: BaseUnit::new(2.156, "ps"),
+        temperature: BaseUnit::new(119.8, "K"),
+        ..UnitSystem::internal()
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let single_threaded = std::env::args().any(|arg| arg == "--single-threaded");
+    let driver = DriverBuilder::new()
+        .single_threaded(single_threaded)
+        .build();
+
+    let mut registry = ObservableRegistry::new();
+    registry.register("potential energy per atom", Dimension::ENERGY);
+    registry.register("temperature", Dimension::TEMPERATURE);
+    registry.register("pressure", Dimension::PRESSURE);
+    let units = argon();
+    let table = Table::new(io::stdout().lock(), &registry.header(&units));
+    let mut output = Resumable::new(Converted::new(table, &registry, &units))
+        .with_stride(NonZero::new(STRIDE).expect("the stride is positive"));
+
+    let mut provenance = Provenance::new(env!("CARGO_PKG_VERSION"))
+        .with_parallelism(if single_threaded { 1 } else { REPLICAS }, REPLICAS);
+    let samples = Arc::new(Mutex::new(vec![Sample::default(); REPLICAS]));
+    let mut replicas: Vec<_> = (0..REPLICAS)
+        .map(|replica| {
+            let seed = 0x5eed + replica as u64;
+            provenance.add_seed(&format!("thermostat.{}", replica), seed);
+            Liquid::new(replica, seed, Arc::clone(&samples))
+        })
+        .collect();
+    output
+        .write_provenance(&provenance)
+        .map_err(io::Error::other)?;
+
+    driver.execute(&mut replicas, STEPS, |step| {
+        if !output.samples(step) {
+            return Ok(());
+        }
+        let samples = samples.lock().map_err(|_| io::Error::other("poisoned"))?;
+        let count = samples.len() as f64;
+        let mean = Sample {
+            potential_energy: samples
+                .iter()
+                .map(|sample| sample.potential_energy)
+                .sum::<f64>()
+                / count,
+            temperature: samples.iter().map(|sample| sample.temperature).sum::<f64>() / count,
+            pressure: samples.iter().map(|sample| sample.pressure).sum::<f64>() / count,
+        };
+        write_sample(&mut output, step, &mean).map_err(io::Error::other)
+    })?;
+
+    let averages = |observable: fn(&Sample) -> f64| -> (f64, f64) {
+        let averages: Vec<_> = replicas
+            .iter()
+            .map(|replica| observable(&replica.sums) / replica.averaged as f64)
+            .collect();
+        mean_and_error(&averages)
+    };
+    let (potential_energy, potential_energy_error) = averages(|sample| sample.potential_energy);
+    let (temperature, temperature_error) = averages(|sample| sample.temperature);
+    let (pressure, pressure_error) = averages(|sample| sample.pressure);
+    println!("# averages in reduced units:");
+    println!(
+        "#   potential energy per atom {:.4} ± {:.4}",
+        potential_energy, potential_energy_error
+    );
+    println!(
+        "#   temperature {:.4} ± {:.4}",
+        temperature, temperature_error
+    );
+    println!("#   pressure {:.4} ± {:.4}", pressure, pressure_error);
+
+    check(
+        "temperature",
+        (temperature, temperature_error),
+        TEMPERATURE,
+        5.0,
+        0.01,
+    )?;
+    // An atom of a liquid wanders off its site by many lattice constants over the run,
+    // while an atom of a crystal only rattles around it.
+    let lattice = (ATOMS as f64 / DENSITY).cbrt() / CELLS as f64;
+    for (replica, liquid) in replicas.iter().enumerate() {
+        let displacement = liquid.mean_squared_displacement();
+        if displacement < lattice * lattice {
+            return Err(format!(
+                "the lattice of replica #{} has not melted, its mean squared displacement is {:.4}",
+                replica, displacement
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
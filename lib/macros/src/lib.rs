@@ -1,9 +1,25 @@
-#![feature(proc_macro_value)]
+#![cfg_attr(not(feature = "stable"), feature(proc_macro_value))]
 
 extern crate proc_macro;
 
 use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
 
+/// Returns the value of a string literal.
+#[cfg(not(feature = "stable"))]
+fn str_value(literal: &Literal) -> Option<String> {
+    literal.str_value().ok()
+}
+
+/// Returns the value of a string literal without escape sequences.
+#[cfg(feature = "stable")]
+fn str_value(literal: &Literal) -> Option<String> {
+    let repr = literal.to_string();
+    repr.strip_prefix('"')
+        .and_then(|repr| repr.strip_suffix('"'))
+        .filter(|value| !value.contains('\\'))
+        .map(str::to_owned)
+}
+
 #[proc_macro_attribute]
 pub fn heavy_computation(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut ret = TokenStream::new();
@@ -47,7 +63,7 @@ pub fn efficient_alternatives(args: TokenStream, item: TokenStream) -> TokenStre
     let mut iterator = args.clone().into_iter();
     while let Some(token_tree) = iterator.next() {
         match &token_tree {
-            TokenTree::Literal(literal) if let Ok(argument) = literal.str_value() => {
+            TokenTree::Literal(literal) if let Some(argument) = str_value(literal) => {
                 message.reserve(argument.len() + 2);
                 message.push('`');
                 message.push_str(argument.as_str());
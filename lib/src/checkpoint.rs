@@ -0,0 +1,734 @@
+//! A portable binary container for checkpoints.
+//!
+//! A checkpoint is a list of typed sections, each holding the state of a part of the simulation
+//! (the arrays of the atoms, the state of the thermostats, the random number generators,
+//! the extended variables) in a payload with a version of its own. The container records the
+//! byte order it was written in, so it can be read on a machine of the other byte order,
+//! and ends with a checksum of its contents.
+//!
+//! Whenever the layout of a payload changes, its version is bumped and a migration from the
+//! previous version is registered in [`Migrations`], so checkpoints written by older versions
+//! of the crate are upgraded section by section when they are loaded.
+//!
+//! The layout of the container is, with every number in the recorded byte order:
+//!
+//! | field | type |
+//! |-------|------|
+//! | magic | `b"RPCK"` |
+//! | byte order | `u8`, 1 for little and 2 for big endian |
+//! | format version | `u32` |
+//! | crate version | string |
+//! | number of sections | `u32` |
+//! | sections | kind `u16`, version `u32`, name string, payload length `u64`, payload |
+//! | checksum | `u64` FNV-1a of everything before it |
+//!
+//! where a string is its length as `u16` followed by its UTF-8 bytes.
+
+use crate::{
+    core::extended::{CheckpointError, ExtendedVariables},
+    output::digest,
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, Read, Write},
+    str::FromStr,
+};
+
+/// The version of the layout of the container, as opposed to the layouts of the payloads.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MAGIC: [u8; 4] = *b"RPCK";
+
+/// The byte order of the numbers in a checkpoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// The least significant byte first.
+    Little,
+    /// The most significant byte first.
+    Big,
+}
+
+impl ByteOrder {
+    /// Returns the byte order of this machine.
+    pub const fn native() -> Self {
+        if cfg!(target_endian = "big") {
+            Self::Big
+        } else {
+            Self::Little
+        }
+    }
+
+    fn tag(self) -> u8 {
+        match self {
+            Self::Little => 1,
+            Self::Big => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(Self::Little),
+            2 => Some(Self::Big),
+            _ => None,
+        }
+    }
+}
+
+/// The part of the simulation whose state a section holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SectionKind {
+    /// The positions, momenta and forces of the atoms.
+    Core,
+    /// The state of a thermostat.
+    Thermostat,
+    /// The state of a random number generator.
+    Rng,
+    /// The extended variables.
+    Extended,
+    /// A section defined outside of the crate, identified by a code of at least 256,
+    /// e.g. constructed by [`SectionKind::custom`].
+    ///
+    /// The codes below 256 are reserved for the kinds of the crate, so a checkpoint
+    /// holding a custom section with such a code is not written.
+    Custom(u16),
+}
+
+impl SectionKind {
+    /// The first code available to the sections defined outside of the crate.
+    pub const FIRST_CUSTOM_CODE: u16 = 256;
+
+    /// Returns the kind of a section defined outside of the crate identified by `code`,
+    /// or `None` if `code` is reserved for the crate.
+    pub const fn custom(code: u16) -> Option<Self> {
+        if code >= Self::FIRST_CUSTOM_CODE {
+            Some(Self::Custom(code))
+        } else {
+            None
+        }
+    }
+
+    fn code(self) -> u16 {
+        match self {
+            Self::Core => 0,
+            Self::Thermostat => 1,
+            Self::Rng => 2,
+            Self::Extended => 3,
+            Self::Custom(code) => code,
+        }
+    }
+
+    fn from_code(code: u16) -> Option<Self> {
+        match code {
+            0 => Some(Self::Core),
+            1 => Some(Self::Thermostat),
+            2 => Some(Self::Rng),
+            3 => Some(Self::Extended),
+            code => Self::custom(code),
+        }
+    }
+}
+
+/// A typed, versioned part of a checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Section {
+    /// What the section holds.
+    pub kind: SectionKind,
+    /// The version of the layout of the payload.
+    pub version: u32,
+    /// The name distinguishing sections of the same kind, e.g. "thermostat.0".
+    pub name: String,
+    /// The payload, encoded in the byte order of the checkpoint.
+    pub payload: Vec<u8>,
+}
+
+impl Section {
+    /// Returns a reader of the payload, decoding numbers in `order`.
+    pub fn reader(&self, order: ByteOrder) -> PayloadReader<'_> {
+        PayloadReader {
+            bytes: &self.payload,
+            order,
+        }
+    }
+}
+
+/// An encoder of the numbers, bytes and arrays of a payload.
+#[derive(Clone, Debug)]
+pub struct PayloadWriter {
+    bytes: Vec<u8>,
+    order: ByteOrder,
+}
+
+impl PayloadWriter {
+    /// Constructs an empty payload encoded in `order`,
+    /// which must be that of the checkpoint the payload is added to.
+    pub fn new(order: ByteOrder) -> Self {
+        Self {
+            bytes: Vec::new(),
+            order,
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) -> &mut Self {
+        self.bytes.extend_from_slice(&match self.order {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        });
+        self
+    }
+
+    /// Appends a `u32`.
+    pub fn write_u32(&mut self, value: u32) -> &mut Self {
+        self.bytes.extend_from_slice(&match self.order {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        });
+        self
+    }
+
+    /// Appends a `u64`.
+    pub fn write_u64(&mut self, value: u64) -> &mut Self {
+        self.bytes.extend_from_slice(&match self.order {
+            ByteOrder::Little => value.to_le_bytes(),
+            ByteOrder::Big => value.to_be_bytes(),
+        });
+        self
+    }
+
+    /// Appends an `f64`.
+    pub fn write_f64(&mut self, value: f64) -> &mut Self {
+        self.write_u64(value.to_bits())
+    }
+
+    fn write_string(&mut self, string: &str) -> io::Result<()> {
+        let length = u16::try_from(string.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a name in a checkpoint must be shorter than 64 KiB",
+            )
+        })?;
+        self.write_u16(length);
+        self.bytes.extend_from_slice(string.as_bytes());
+        Ok(())
+    }
+
+    /// Appends raw bytes preceded by their length.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.write_u64(bytes.len() as u64);
+        self.bytes.extend_from_slice(bytes);
+        self
+    }
+
+    /// Appends an array of `f64` preceded by its shape, e.g. `[images, atoms, N]` for positions.
+    ///
+    /// Fails if the shape has more than `u32::MAX` dimensions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values differs from the product of the shape.
+    pub fn write_array(&mut self, shape: &[usize], values: &[f64]) -> io::Result<&mut Self> {
+        assert_eq!(
+            shape.iter().product::<usize>(),
+            values.len(),
+            "the shape must cover every value"
+        );
+        let rank = u32::try_from(shape.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "an array in a checkpoint must have fewer than 2^32 dimensions",
+            )
+        })?;
+        self.write_u32(rank);
+        for &extent in shape {
+            self.write_u64(extent as u64);
+        }
+        for &value in values {
+            self.write_f64(value);
+        }
+        Ok(self)
+    }
+
+    /// Finishes the payload into a section.
+    pub fn finish(self, kind: SectionKind, version: u32, name: &str) -> Section {
+        Section {
+            kind,
+            version,
+            name: name.to_owned(),
+            payload: self.bytes,
+        }
+    }
+}
+
+/// A decoder of the payload of a section, in the order it was written in.
+#[derive(Clone, Debug)]
+pub struct PayloadReader<'a> {
+    bytes: &'a [u8],
+    order: ByteOrder,
+}
+
+impl<'a> PayloadReader<'a> {
+    fn take<const L: usize>(&mut self) -> Result<[u8; L], ContainerError> {
+        let (head, tail) = self
+            .bytes
+            .split_first_chunk::<L>()
+            .ok_or(ContainerError::Truncated)?;
+        self.bytes = tail;
+        Ok(*head)
+    }
+
+    fn read_u16(&mut self) -> Result<u16, ContainerError> {
+        let bytes = self.take()?;
+        Ok(match self.order {
+            ByteOrder::Little => u16::from_le_bytes(bytes),
+            ByteOrder::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    fn read_string(&mut self) -> Result<String, ContainerError> {
+        let length = self.read_u16()?;
+        let (head, tail) = self
+            .bytes
+            .split_at_checked(length.into())
+            .ok_or(ContainerError::Truncated)?;
+        self.bytes = tail;
+        String::from_utf8(head.to_vec()).map_err(|_| ContainerError::Utf8)
+    }
+
+    /// Reads a `u32`.
+    pub fn read_u32(&mut self) -> Result<u32, ContainerError> {
+        let bytes = self.take()?;
+        Ok(match self.order {
+            ByteOrder::Little => u32::from_le_bytes(bytes),
+            ByteOrder::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads a `u64`.
+    pub fn read_u64(&mut self) -> Result<u64, ContainerError> {
+        let bytes = self.take()?;
+        Ok(match self.order {
+            ByteOrder::Little => u64::from_le_bytes(bytes),
+            ByteOrder::Big => u64::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads an `f64`.
+    pub fn read_f64(&mut self) -> Result<f64, ContainerError> {
+        self.read_u64().map(f64::from_bits)
+    }
+
+    /// Reads bytes written by [`PayloadWriter::write_bytes`].
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], ContainerError> {
+        let length = usize::try_from(self.read_u64()?).map_err(|_| ContainerError::Truncated)?;
+        let (head, tail) = self
+            .bytes
+            .split_at_checked(length)
+            .ok_or(ContainerError::Truncated)?;
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    /// Reads an array written by [`PayloadWriter::write_array`], returning its shape and values.
+    pub fn read_array(&mut self) -> Result<(Vec<usize>, Vec<f64>), ContainerError> {
+        let rank = self.read_u32()?;
+        let shape = (0..rank)
+            .map(|_| usize::try_from(self.read_u64()?).map_err(|_| ContainerError::Truncated))
+            .collect::<Result<Vec<_>, _>>()?;
+        let len = shape
+            .iter()
+            .try_fold(1usize, |len, &extent| len.checked_mul(extent))
+            .filter(|&len| len <= self.bytes.len() / 8)
+            .ok_or(ContainerError::Truncated)?;
+        let values = (0..len)
+            .map(|_| self.read_f64())
+            .collect::<Result<_, _>>()?;
+        Ok((shape, values))
+    }
+
+    /// Returns whether the whole payload has been read.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+/// The sections of a checkpoint together with the byte order and version they were written by.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    order: ByteOrder,
+    crate_version: String,
+    sections: Vec<Section>,
+}
+
+impl Checkpoint {
+    /// Constructs an empty checkpoint written by the given version of the crate,
+    /// e.g. `env!("CARGO_PKG_VERSION")`, in the native byte order.
+    pub fn new(crate_version: &str) -> Self {
+        Self {
+            order: ByteOrder::native(),
+            crate_version: crate_version.to_owned(),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Returns the byte order of the payloads.
+    pub fn byte_order(&self) -> ByteOrder {
+        self.order
+    }
+
+    /// Returns the version of the crate which wrote the checkpoint.
+    pub fn crate_version(&self) -> &str {
+        &self.crate_version
+    }
+
+    /// Returns the sections in the order they were added in.
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    /// Returns the section of `kind` named `name`, if any.
+    pub fn section(&self, kind: SectionKind, name: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.kind == kind && section.name == name)
+    }
+
+    /// Returns an encoder of a payload in the byte order of the checkpoint.
+    pub fn writer(&self) -> PayloadWriter {
+        PayloadWriter::new(self.order)
+    }
+
+    /// Adds `section`, whose payload must have been encoded by [`Checkpoint::writer`],
+    /// replacing the section of the same kind and name, if any.
+    pub fn add(&mut self, section: Section) {
+        match self
+            .sections
+            .iter_mut()
+            .find(|existing| existing.kind == section.kind && existing.name == section.name)
+        {
+            Some(existing) => *existing = section,
+            None => self.sections.push(section),
+        }
+    }
+
+    /// Adds the extended variables as a section named `name`, holding the table
+    /// written by [`ExtendedVariables::write_checkpoint`].
+    pub fn add_extended<T: Display>(
+        &mut self,
+        name: &str,
+        variables: &ExtendedVariables<T>,
+    ) -> Result<(), CheckpointError> {
+        let mut table = Vec::new();
+        variables.write_checkpoint(&mut table)?;
+        let mut payload = self.writer();
+        payload.write_bytes(&table);
+        self.add(payload.finish(SectionKind::Extended, 1, name));
+        Ok(())
+    }
+
+    /// Restores the extended variables from the section named `name`.
+    pub fn restore_extended<T: FromStr>(
+        &self,
+        name: &str,
+        variables: &ExtendedVariables<T>,
+    ) -> Result<(), ContainerError> {
+        let section = self.section(SectionKind::Extended, name).ok_or_else(|| {
+            ContainerError::MissingSection {
+                kind: SectionKind::Extended,
+                name: name.to_owned(),
+            }
+        })?;
+        let table = section.reader(self.order).read_bytes()?;
+        variables
+            .read_checkpoint(table)
+            .map_err(|err| ContainerError::Payload(err.to_string()))
+    }
+
+    /// Writes the container to `writer`.
+    ///
+    /// Fails if there are more than `u32::MAX` sections or a custom section
+    /// has a code reserved for the crate.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut container = PayloadWriter::new(self.order);
+        container.bytes.extend_from_slice(&MAGIC);
+        container.bytes.push(self.order.tag());
+        container.write_u32(FORMAT_VERSION);
+        container.write_string(&self.crate_version)?;
+        let count = u32::try_from(self.sections.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a checkpoint must have fewer than 2^32 sections",
+            )
+        })?;
+        container.write_u32(count);
+        for section in &self.sections {
+            if let SectionKind::Custom(code) = section.kind
+                && code < SectionKind::FIRST_CUSTOM_CODE
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "the custom section {:?} has the code {}, which is reserved",
+                        section.name, code
+                    ),
+                ));
+            }
+            container
+                .write_u16(section.kind.code())
+                .write_u32(section.version);
+            container.write_string(&section.name)?;
+            container.write_bytes(&section.payload);
+        }
+        let checksum = digest(&container.bytes);
+        container.write_u64(checksum);
+        writer.write_all(&container.bytes)
+    }
+
+    /// Reads a container written by [`Checkpoint::write`] in either byte order.
+    ///
+    /// The sections keep the versions they were written with; see [`Migrations::upgrade`].
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, ContainerError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let (contents, checksum) = bytes
+            .split_last_chunk::<8>()
+            .ok_or(ContainerError::Truncated)?;
+        let (magic, rest) = contents
+            .split_first_chunk::<4>()
+            .ok_or(ContainerError::Truncated)?;
+        if *magic != MAGIC {
+            return Err(ContainerError::NotACheckpoint);
+        }
+        let (&tag, rest) = rest.split_first().ok_or(ContainerError::Truncated)?;
+        let order = ByteOrder::from_tag(tag).ok_or(ContainerError::ByteOrder(tag))?;
+        let checksum = PayloadReader {
+            bytes: checksum,
+            order,
+        }
+        .read_u64()?;
+        if checksum != digest(contents) {
+            return Err(ContainerError::Checksum);
+        }
+        let mut reader = PayloadReader { bytes: rest, order };
+        let version = reader.read_u32()?;
+        if version > FORMAT_VERSION {
+            return Err(ContainerError::FormatVersion {
+                found: version,
+                supported: FORMAT_VERSION,
+            });
+        }
+        let crate_version = reader.read_string()?;
+        let count = reader.read_u32()?;
+        let mut sections: Vec<Section> = Vec::new();
+        for _ in 0..count {
+            let code = reader.read_u16()?;
+            let kind = SectionKind::from_code(code).ok_or(ContainerError::SectionKind(code))?;
+            let version = reader.read_u32()?;
+            let name = reader.read_string()?;
+            let payload = reader.read_bytes()?.to_vec();
+            if sections
+                .iter()
+                .any(|section| section.kind == kind && section.name == name)
+            {
+                return Err(ContainerError::DuplicateSection { kind, name });
+            }
+            sections.push(Section {
+                kind,
+                version,
+                name,
+                payload,
+            });
+        }
+        if !reader.is_empty() {
+            return Err(ContainerError::TrailingBytes);
+        }
+        Ok(Self {
+            order,
+            crate_version,
+            sections,
+        })
+    }
+}
+
+type Migration = Box<dyn Fn(&Section, ByteOrder) -> Result<Vec<u8>, ContainerError> + Send + Sync>;
+
+/// The current versions of the payloads of every kind of section and the migrations
+/// which upgrade each older version to the next one.
+#[derive(Default)]
+pub struct Migrations {
+    current: HashMap<SectionKind, u32>,
+    steps: HashMap<(SectionKind, u32), Migration>,
+}
+
+impl Migrations {
+    /// Constructs a registry in which every kind of section is at version 1.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current version of the payloads of `kind`.
+    pub fn current(&self, kind: SectionKind) -> u32 {
+        self.current.get(&kind).copied().unwrap_or(1)
+    }
+
+    /// Registers the migration of the payloads of `kind` from version `from` to `from + 1`,
+    /// which becomes the current version unless a later one has been registered.
+    ///
+    /// The migration receives the old section with the byte order of its checkpoint
+    /// and returns the new payload in the same byte order, e.g. encoded by
+    /// a [`PayloadWriter`] constructed with it.
+    pub fn register<F>(&mut self, kind: SectionKind, from: u32, migration: F)
+    where
+        F: Fn(&Section, ByteOrder) -> Result<Vec<u8>, ContainerError> + Send + Sync + 'static,
+    {
+        let current = self.current.entry(kind).or_insert(1);
+        *current = (*current).max(from + 1);
+        self.steps.insert((kind, from), Box::new(migration));
+    }
+
+    /// Upgrades every section of `checkpoint` to the current version of its kind.
+    ///
+    /// Fails if a section was written by a newer version of the crate
+    /// or no migration upgrades it from its version.
+    pub fn upgrade(&self, checkpoint: &mut Checkpoint) -> Result<(), ContainerError> {
+        let order = checkpoint.order;
+        for section in &mut checkpoint.sections {
+            let current = self.current(section.kind);
+            if section.version > current {
+                return Err(ContainerError::SectionVersion {
+                    kind: section.kind,
+                    found: section.version,
+                    supported: current,
+                });
+            }
+            while section.version < current {
+                let migration = self.steps.get(&(section.kind, section.version)).ok_or(
+                    ContainerError::MissingMigration {
+                        kind: section.kind,
+                        from: section.version,
+                    },
+                )?;
+                section.payload = migration(section, order)?;
+                section.version += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error raised while reading, validating or upgrading a checkpoint.
+#[derive(Debug)]
+pub enum ContainerError {
+    /// Reading failed.
+    Io(io::Error),
+    /// The file does not start with the magic bytes of a checkpoint.
+    NotACheckpoint,
+    /// The byte order tag is unknown.
+    ByteOrder(u8),
+    /// The checkpoint was written with a newer layout of the container.
+    FormatVersion {
+        /// The version of the layout in the file.
+        found: u32,
+        /// The newest layout this crate reads.
+        supported: u32,
+    },
+    /// The checkpoint ends in the middle of a field.
+    Truncated,
+    /// A section has a code reserved for a kind this crate does not know,
+    /// as when it was written by a newer version of the crate.
+    SectionKind(u16),
+    /// The checkpoint goes on after its last section.
+    TrailingBytes,
+    /// The checksum does not match the contents.
+    Checksum,
+    /// A string is not valid UTF-8.
+    Utf8,
+    /// Two sections share a kind and a name.
+    DuplicateSection {
+        /// The kind of the sections.
+        kind: SectionKind,
+        /// The name of the sections.
+        name: String,
+    },
+    /// A section needed to restore the simulation is absent.
+    MissingSection {
+        /// The kind of the section.
+        kind: SectionKind,
+        /// The name of the section.
+        name: String,
+    },
+    /// A section was written by a newer version of the crate.
+    SectionVersion {
+        /// The kind of the section.
+        kind: SectionKind,
+        /// The version of its payload.
+        found: u32,
+        /// The current version of the payloads of its kind.
+        supported: u32,
+    },
+    /// No migration upgrades a section from its version.
+    MissingMigration {
+        /// The kind of the section.
+        kind: SectionKind,
+        /// The version of its payload.
+        from: u32,
+    },
+    /// The payload of a section could not be decoded.
+    Payload(String),
+}
+
+impl From<io::Error> for ContainerError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Display for ContainerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Io(err) => write!(f, "failed to read the checkpoint: {}", err),
+            Self::NotACheckpoint => write!(f, "the file is not a checkpoint"),
+            Self::ByteOrder(tag) => write!(f, "unsupported byte order {}", tag),
+            Self::FormatVersion { found, supported } => write!(
+                f,
+                "the checkpoint has format version {}, newer than the supported {}",
+                found, supported
+            ),
+            Self::Truncated => write!(f, "the checkpoint is truncated"),
+            Self::SectionKind(code) => write!(f, "unknown kind of section {}", code),
+            Self::TrailingBytes => write!(f, "the checkpoint has trailing bytes"),
+            Self::Checksum => write!(f, "the checksum of the checkpoint does not match"),
+            Self::Utf8 => write!(f, "a name in the checkpoint is not valid UTF-8"),
+            Self::DuplicateSection { kind, name } => {
+                write!(f, "the {:?} section {:?} appears twice", kind, name)
+            }
+            Self::MissingSection { kind, name } => {
+                write!(f, "the {:?} section {:?} is missing", kind, name)
+            }
+            Self::SectionVersion {
+                kind,
+                found,
+                supported,
+            } => write!(
+                f,
+                "the {:?} section has version {}, newer than the supported {}",
+                kind, found, supported
+            ),
+            Self::MissingMigration { kind, from } => write!(
+                f,
+                "no migration upgrades the {:?} section from version {}",
+                kind, from
+            ),
+            Self::Payload(message) => write!(f, "malformed section: {}", message),
+        }
+    }
+}
+
+impl Error for ContainerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
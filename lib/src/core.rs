@@ -6,7 +6,11 @@ use std::ops::{
 };
 
 mod map_in_whole {
-    use std::{ops::Deref, ptr, range::Range, slice};
+    #[cfg(feature = "stable")]
+    use std::ops::Range;
+    #[cfg(not(feature = "stable"))]
+    use std::range::Range;
+    use std::{ops::Deref, ptr, slice};
 
     #[derive(Clone, Copy, Debug)]
     pub struct MapInWhole<T, U> {
@@ -546,8 +550,18 @@ mod atoms;
 
 pub use atoms::{AtomTypeInfo, GroupSizes, GroupSizesIter, GroupsIter};
 
+mod frozen;
+
+pub use frozen::{Frozen, FrozenAtoms};
+
+mod virtual_sites;
+
+pub use virtual_sites::{VirtualSite, VirtualSiteError, VirtualSites, WithVirtualSites};
+
 pub mod error;
 
+pub mod extended;
+
 pub mod marker {
     //! Marker traits for allowing default implementations.
 
@@ -562,17 +576,34 @@ pub mod marker {
 
 pub mod stat;
 
+pub mod centroid;
+
+pub mod failure;
+
+pub mod manifold;
+
+pub mod partition;
+
+pub mod registry;
+
+pub mod resolution;
+
+pub mod snapshot;
+
+pub mod tolerance;
+
+pub mod topology;
+
+pub mod validation;
+
+pub mod warnings;
+
 pub mod sync_ops;
 
 pub mod factory;
 
 #[cfg(feature = "monte_carlo")]
-pub mod monte_carlo {
-    pub enum ChangedGroup {
-        This,
-        Other(usize),
-    }
-}
+pub mod monte_carlo;
 
 /// A macro that allows pattern-matching items of [zipped iterators](zip_iterators).
 #[macro_export]
@@ -618,6 +649,15 @@ pub trait Vector<const N: usize>:
     /// The type of the element of the vector.
     type Element;
 
+    /// Constructs the zero vector.
+    fn zero() -> Self;
+
+    /// Constructs a vector with every element equal to `value`.
+    fn splat(value: Self::Element) -> Self;
+
+    /// Constructs a vector from its elements.
+    fn from_array(array: [Self::Element; N]) -> Self;
+
     /// Converts to a reference to an array.
     fn as_array(&self) -> &[Self::Element; N];
 
@@ -629,6 +669,9 @@ pub trait Vector<const N: usize>:
 
     /// Calculates the dot product of `self` with `rhs`.
     fn dot(self, rhs: Self) -> Self::Element;
+
+    /// Adds `a * b` to `self`, with a fused multiply-add where the implementor supports one.
+    fn mul_add_assign(&mut self, a: &Self, b: Self::Element);
 }
 
 /// Exchange potential expansion scheme.
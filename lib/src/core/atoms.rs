@@ -1,5 +1,6 @@
 use std::{iter::FusedIterator, num::NonZeroUsize, slice::Iter};
 
+use crate::core::partition::Treatment;
 use crate::core::stat::Stat;
 
 /// Information about atoms of the same type.
@@ -15,6 +16,39 @@ pub struct AtomTypeInfo<T> {
     pub mass: T,
     /// Whether the atoms are distinguishable.
     pub statistic: Stat<(), ()>,
+    /// Whether the atoms are quantized or represented by a single replica.
+    treatment: Treatment,
+}
+
+impl<T> AtomTypeInfo<T> {
+    /// Constructs the information about a quantized atom type.
+    pub fn new(
+        id: usize,
+        label: String,
+        groups: GroupSizes,
+        mass: T,
+        statistic: Stat<(), ()>,
+    ) -> Self {
+        Self {
+            id,
+            label,
+            groups,
+            mass,
+            statistic,
+            treatment: Treatment::Quantum,
+        }
+    }
+
+    /// Sets whether the atoms are quantized or represented by a single replica.
+    pub fn with_treatment(mut self, treatment: Treatment) -> Self {
+        self.treatment = treatment;
+        self
+    }
+
+    /// Returns whether the atoms are quantized or represented by a single replica.
+    pub fn treatment(&self) -> Treatment {
+        self.treatment
+    }
 }
 
 /// A struct containig information about the sizes of
@@ -26,12 +60,19 @@ pub struct GroupSizes {
 }
 
 impl GroupSizes {
+    /// Constructs the sizes of `total` atoms split into `groups` groups.
+    ///
+    /// Returns `None` if there are more groups than atoms.
+    pub fn new(total: NonZeroUsize, groups: NonZeroUsize) -> Option<Self> {
+        (groups <= total).then_some(Self { total, groups })
+    }
+
     /// Returns an iterator over the sizes of the groups.
     pub fn iter(&self) -> GroupSizesIter {
-        debug_assert!(self.total > self.groups);
+        debug_assert!(self.total >= self.groups);
 
         let small_group_size = usize::from(self.total) / self.groups;
-        let large_groups = usize::from(self.total) % small_group_size;
+        let large_groups = usize::from(self.total) % self.groups;
         GroupSizesIter {
             small_groups: usize::from(self.groups) - large_groups,
             large_groups,
@@ -144,6 +185,15 @@ impl<'a, T> GroupsIter<'a, T> {
     }
 }
 
+impl<T> Clone for GroupsIter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            atom_types_iter: self.atom_types_iter.clone(),
+            opt_group_sizes_iter: self.opt_group_sizes_iter,
+        }
+    }
+}
+
 impl<'a, T> Iterator for GroupsIter<'a, T> {
     type Item = (&'a AtomType<T>, usize);
 
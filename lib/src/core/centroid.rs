@@ -0,0 +1,108 @@
+//! A cache of the centroids of the ring polymers shared by estimators and propagators.
+
+use arc_rw_lock::MappedRwLock;
+use std::ops::{AddAssign, Div};
+
+/// The centroids of the atoms of a group, recomputed only when its positions change.
+///
+/// Staleness is detected through the versions of the position locks, which change
+/// every time a write guard is dropped, so reading the centroids after the positions
+/// have been read but not written costs nothing.
+#[derive(Clone, Debug)]
+pub struct CentroidCache<V> {
+    pub(crate) centroids: Vec<V>,
+    pub(crate) versions: Vec<usize>,
+}
+
+impl<V> CentroidCache<V> {
+    /// Constructs an empty cache.
+    pub fn new() -> Self {
+        Self {
+            centroids: Vec::new(),
+            versions: Vec::new(),
+        }
+    }
+
+    /// Returns the centroids as of the last update.
+    pub fn centroids(&self) -> &[V] {
+        &self.centroids
+    }
+
+    /// Forgets the cached centroids, so that the next update recomputes them.
+    pub fn invalidate(&mut self) {
+        self.versions.clear();
+    }
+}
+
+impl<V> Default for CentroidCache<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Clone + AddAssign> CentroidCache<V> {
+    /// Brings the centroids up to date with the positions of the group in every image,
+    /// given as pairs of the version of the positions and the positions themselves.
+    ///
+    /// Returns whether the centroids have been recomputed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the images hold groups of different sizes.
+    pub fn refresh<'a, T, I>(&mut self, images: I) -> bool
+    where
+        T: From<f32>,
+        V: Div<T, Output = V> + 'a,
+        I: IntoIterator<Item = (usize, &'a [V])>,
+        I::IntoIter: Clone,
+    {
+        let images = images.into_iter();
+        if self.versions.len() == images.clone().count()
+            && self
+                .versions
+                .iter()
+                .zip(images.clone())
+                .all(|(cached, (version, _))| *cached == version)
+        {
+            return false;
+        }
+        self.versions.clear();
+        self.centroids.clear();
+        for (version, positions) in images {
+            self.versions.push(version);
+            if self.versions.len() == 1 {
+                self.centroids.extend_from_slice(positions);
+                continue;
+            }
+            assert_eq!(
+                positions.len(),
+                self.centroids.len(),
+                "the images hold groups of different sizes"
+            );
+            for (centroid, position) in self.centroids.iter_mut().zip(positions) {
+                *centroid += position.clone();
+            }
+        }
+        let images = self.versions.len() as f32;
+        for centroid in &mut self.centroids {
+            *centroid = centroid.clone() / T::from(images);
+        }
+        true
+    }
+
+    /// Brings the centroids up to date with the position locks of the group in every image.
+    ///
+    /// Returns whether the centroids have been recomputed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the images hold groups of different sizes.
+    pub fn update<'a, T, U>(&mut self, images: &[&'a MappedRwLock<[V], U>]) -> bool
+    where
+        T: From<f32>,
+        V: Div<T, Output = V> + 'a,
+        U: ?Sized + 'a,
+    {
+        self.refresh(images.iter().map(|lock| (lock.version(), lock.read())))
+    }
+}
@@ -2,7 +2,7 @@
 use std::{
     convert::Infallible,
     error::Error,
-    fmt::{Display, Formatter, Result as FmtResult},
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
     ops::Range,
 };
 
@@ -93,6 +93,123 @@ impl Display for EmptyError {
 
 impl Error for EmptyError {}
 
+/// An error representing a lock poisoned by a thread which panicked while holding it.
+#[derive(Clone, Copy, Debug)]
+pub struct PoisonedError;
+
+impl From<Infallible> for PoisonedError {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl Display for PoisonedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "a thread panicked while holding the lock")
+    }
+}
+
+impl Error for PoisonedError {}
+
+/// An error representing a thread stopped because another thread of the simulation failed.
+#[derive(Clone, Copy, Debug)]
+pub struct AbortedError;
+
+impl From<Infallible> for AbortedError {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl Display for AbortedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "another thread of the simulation failed")
+    }
+}
+
+impl Error for AbortedError {}
+
+/// An error representing an expectation of a hot loop which does not hold,
+/// e.g. a position which is no longer finite.
+///
+/// Such an error is only ever returned with the `fallible_hot_path` feature enabled.
+/// Otherwise, the violated expectation panics like [`Option::expect`].
+#[derive(Clone, Copy, Debug)]
+pub struct ExpectationError {
+    message: &'static str,
+}
+
+impl ExpectationError {
+    /// Constructs a new `ExpectationError`.
+    pub fn new(message: &'static str) -> Self {
+        Self { message }
+    }
+
+    /// Returns the expectation which does not hold.
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+}
+
+impl From<Infallible> for ExpectationError {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl Display for ExpectationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "an expectation does not hold: {}", self.message)
+    }
+}
+
+impl Error for ExpectationError {}
+
+/// An error representing an iterative solver which has not converged.
+#[derive(Clone, Copy, Debug)]
+pub struct NotConvergedError<T> {
+    iterations: usize,
+    residual: T,
+}
+
+impl<T> NotConvergedError<T> {
+    /// Constructs a new `NotConvergedError`.
+    pub fn new(iterations: usize, residual: T) -> Self {
+        Self {
+            iterations,
+            residual,
+        }
+    }
+
+    /// Returns the number of iterations performed.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Returns the residual after the last iteration.
+    pub fn residual(&self) -> &T {
+        &self.residual
+    }
+}
+
+impl<T> From<Infallible> for NotConvergedError<T> {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl<T: Display> Display for NotConvergedError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "not converged after {} iterations (residual {})",
+            self.iterations, self.residual
+        )
+    }
+}
+
+impl<T: Debug + Display> Error for NotConvergedError<T> {}
+
 /// An error that represents invalid access.
 #[derive(Clone, Debug)]
 pub enum AccessError {
@@ -172,6 +289,13 @@ pub enum CommError {
         /// The index of the group the thread is assigmed to.
         group: usize,
     },
+    /// The error arose in the thread of a replica run by a [`Driver`].
+    ///
+    /// [`Driver`]: crate::driver::Driver
+    Replica {
+        /// The index of the replica.
+        replica: usize,
+    },
 }
 
 impl From<Infallible> for CommError {
@@ -199,8 +323,114 @@ impl Display for CommError {
                 "something happened in a thread dedicated to group #{} in the last image",
                 group
             ),
+            Self::Replica { replica } => write!(
+                f,
+                "something happened in the thread of replica #{}",
+                replica
+            ),
         }
     }
 }
 
 impl Error for CommError {}
+
+impl CommError {
+    /// Returns a short description of the thread, e.g. "group #2 in image #5".
+    pub(crate) fn thread_name(&self) -> String {
+        match *self {
+            Self::Main => "the main thread".to_string(),
+            Self::Leading { group } => format!("group #{} in the first image", group),
+            Self::Inner { image, group } => format!("group #{} in image #{}", group, image),
+            Self::Trailing { group } => format!("group #{} in the last image", group),
+            Self::Replica { replica } => format!("replica #{}", replica),
+        }
+    }
+}
+
+/// An error annotated with the step and the thread it arose in.
+#[derive(Clone, Debug)]
+pub struct ContextError<E> {
+    /// The step during which the error arose.
+    pub step: usize,
+    /// The thread in which the error arose.
+    pub location: CommError,
+    /// The underlying error.
+    pub source: E,
+}
+
+impl<E: Display> Display for ContextError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "at step {} in {}: {}",
+            self.step,
+            self.location.thread_name(),
+            self.source
+        )
+    }
+}
+
+impl<E: Error + 'static> Error for ContextError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// An extension trait for annotating errors with the step and the thread they arose in.
+pub trait ResultExt<T, E> {
+    /// Annotates the error, if any, with `step` and `location`.
+    fn with_context(self, step: usize, location: CommError) -> Result<T, ContextError<E>>;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+    #[inline(always)]
+    fn with_context(self, step: usize, location: CommError) -> Result<T, ContextError<E>> {
+        self.map_err(|source| ContextError {
+            step,
+            location,
+            source,
+        })
+    }
+}
+
+/// An extension trait for expectations checked in hot loops.
+///
+/// Unlike [`Option::expect`], a violated expectation is returned as an [`ExpectationError`]
+/// when the `fallible_hot_path` feature is enabled, so that the driver can save
+/// a checkpoint and exit with a report instead of aborting with a backtrace.
+pub trait Expect<T> {
+    /// Returns the contained value.
+    ///
+    /// # Panics
+    ///
+    /// Panics with `message` if there is no value and the `fallible_hot_path` feature is disabled.
+    fn expect_in_hot_path(self, message: &'static str) -> Result<T, ExpectationError>;
+}
+
+impl<T> Expect<T> for Option<T> {
+    #[inline(always)]
+    #[track_caller]
+    fn expect_in_hot_path(self, message: &'static str) -> Result<T, ExpectationError> {
+        match self {
+            Some(value) => Ok(value),
+            #[cfg(feature = "fallible_hot_path")]
+            None => Err(ExpectationError::new(message)),
+            #[cfg(not(feature = "fallible_hot_path"))]
+            None => panic!("{}", message),
+        }
+    }
+}
+
+impl<T, E: Debug> Expect<T> for Result<T, E> {
+    #[inline(always)]
+    #[track_caller]
+    fn expect_in_hot_path(self, message: &'static str) -> Result<T, ExpectationError> {
+        match self {
+            Ok(value) => Ok(value),
+            #[cfg(feature = "fallible_hot_path")]
+            Err(_) => Err(ExpectationError::new(message)),
+            #[cfg(not(feature = "fallible_hot_path"))]
+            Err(err) => panic!("{}: {:?}", message, err),
+        }
+    }
+}
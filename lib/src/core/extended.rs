@@ -0,0 +1,319 @@
+//! Auxiliary coordinates propagated alongside the atoms.
+//!
+//! Barostats, Nosé–Hoover chains and adaptive biases carry dynamical variables of their own:
+//! the volume of the box, the thermostat momenta or the position of a bias centre.
+//! Rather than hiding them inside each component, they are registered in an
+//! [`ExtendedVariables`] registry, which stores them in a single slice behind a lock,
+//! so that they can be read by estimators, summed into the conserved energy and
+//! written to and restored from checkpoints together with the atoms.
+
+use super::error::PoisonedError;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, BufRead, Write},
+    ops::{Add, Div, Mul, Range},
+    str::FromStr,
+    sync::RwLock,
+};
+
+/// A single extended variable, treated as a particle moving in one dimension.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ExtendedCoordinate<T> {
+    /// The position of the variable.
+    pub position: T,
+    /// The momentum conjugate to the position.
+    pub momentum: T,
+    /// The fictitious mass of the variable.
+    pub mass: T,
+}
+
+impl<T> ExtendedCoordinate<T> {
+    /// Constructs a variable at rest at `position`.
+    pub fn at_rest(position: T, mass: T) -> Self
+    where
+        T: From<f32>,
+    {
+        Self {
+            position,
+            momentum: T::from(0.0),
+            mass,
+        }
+    }
+}
+
+/// A handle to the contiguous block of variables registered by a single component.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendedBlock {
+    start: usize,
+    len: usize,
+}
+
+impl ExtendedBlock {
+    /// Returns the range the block occupies in the storage of the registry.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.len
+    }
+
+    /// Returns the number of variables in the block.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the block holds no variables.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A registry of the extended variables of a simulation.
+///
+/// Variables are registered in named blocks before the simulation starts,
+/// after which the registry is meant to be shared between threads,
+/// each propagator updating its own block through [`ExtendedVariables::update`].
+#[derive(Debug, Default)]
+pub struct ExtendedVariables<T> {
+    names: Vec<(String, ExtendedBlock)>,
+    storage: RwLock<Vec<ExtendedCoordinate<T>>>,
+}
+
+impl<T> ExtendedVariables<T> {
+    /// Constructs an empty registry.
+    pub fn new() -> Self {
+        Self {
+            names: Vec::new(),
+            storage: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Registers the block of variables `coordinates` under `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a block named `name` is already registered.
+    pub fn register<I>(&mut self, name: &str, coordinates: I) -> ExtendedBlock
+    where
+        I: IntoIterator<Item = ExtendedCoordinate<T>>,
+    {
+        assert!(
+            self.block(name).is_none(),
+            "extended variables named '{name}' are already registered"
+        );
+        let storage = self
+            .storage
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let start = storage.len();
+        storage.extend(coordinates);
+        let block = ExtendedBlock {
+            start,
+            len: storage.len() - start,
+        };
+        self.names.push((name.to_owned(), block));
+        block
+    }
+
+    /// Returns the block registered under `name`, if any.
+    pub fn block(&self, name: &str) -> Option<ExtendedBlock> {
+        self.names
+            .iter()
+            .find(|(registered, _)| registered == name)
+            .map(|&(_, block)| block)
+    }
+
+    /// Returns the names and the blocks of every registered component in the order of registration.
+    pub fn blocks(&self) -> impl Iterator<Item = (&str, ExtendedBlock)> {
+        self.names
+            .iter()
+            .map(|(name, block)| (name.as_str(), *block))
+    }
+
+    /// Returns the total number of variables.
+    pub fn len(&self) -> usize {
+        self.names.last().map_or(0, |(_, block)| block.range().end)
+    }
+
+    /// Returns whether no variable is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calls `f` with the variables of `block` while holding the write lock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` was not registered in this registry.
+    pub fn update<F, R>(&self, block: ExtendedBlock, f: F) -> Result<R, PoisonedError>
+    where
+        F: FnOnce(&mut [ExtendedCoordinate<T>]) -> R,
+    {
+        let mut storage = self.storage.write().map_err(|_| PoisonedError)?;
+        Ok(f(&mut storage[block.range()]))
+    }
+
+    /// Returns a copy of the variables of `block`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block` was not registered in this registry.
+    pub fn values(&self, block: ExtendedBlock) -> Result<Vec<ExtendedCoordinate<T>>, PoisonedError>
+    where
+        T: Clone,
+    {
+        let storage = self.storage.read().map_err(|_| PoisonedError)?;
+        Ok(storage[block.range()].to_vec())
+    }
+
+    /// Returns the kinetic energy `Σ p² / 2m` of every variable,
+    /// which enters the conserved energy of the extended system.
+    pub fn kinetic_energy(&self) -> Result<T, PoisonedError>
+    where
+        T: Clone + From<f32> + Add<Output = T> + Mul<Output = T> + Div<Output = T>,
+    {
+        let storage = self.storage.read().map_err(|_| PoisonedError)?;
+        Ok(storage.iter().fold(T::from(0.0), |energy, coordinate| {
+            energy
+                + coordinate.momentum.clone() * coordinate.momentum.clone()
+                    / (T::from(2.0) * coordinate.mass.clone())
+        }))
+    }
+
+    /// Writes every variable to `writer` as a tab-separated table,
+    /// one variable per line, preceded by the name of its block.
+    pub fn write_checkpoint<W: Write>(&self, writer: &mut W) -> Result<(), CheckpointError>
+    where
+        T: Display,
+    {
+        let storage = self.storage.read().map_err(|_| PoisonedError)?;
+        writeln!(writer, "# block\tposition\tmomentum\tmass")?;
+        for (name, block) in &self.names {
+            for coordinate in &storage[block.range()] {
+                writeln!(
+                    writer,
+                    "{}\t{}\t{}\t{}",
+                    name, coordinate.position, coordinate.momentum, coordinate.mass
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores every variable from a table written by [`ExtendedVariables::write_checkpoint`].
+    ///
+    /// The table must list the same blocks, with the same number of variables,
+    /// as the registry. Nothing is restored if it does not.
+    pub fn read_checkpoint<R: BufRead>(&self, reader: R) -> Result<(), CheckpointError>
+    where
+        T: FromStr,
+    {
+        let mut rows = Vec::with_capacity(self.len());
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_number = number + 1;
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [name, position, momentum, mass] = fields[..] else {
+                return Err(CheckpointError::Parse { line: line_number });
+            };
+            let parse = |field: &str| {
+                field
+                    .parse::<T>()
+                    .map_err(|_| CheckpointError::Parse { line: line_number })
+            };
+            let coordinate = ExtendedCoordinate {
+                position: parse(position)?,
+                momentum: parse(momentum)?,
+                mass: parse(mass)?,
+            };
+            let expected = self
+                .names
+                .iter()
+                .find(|(_, block)| block.range().contains(&rows.len()))
+                .map(|(name, _)| name.as_str());
+            if expected != Some(name) {
+                return Err(CheckpointError::Mismatch { line: line_number });
+            }
+            rows.push(coordinate);
+        }
+        if rows.len() != self.len() {
+            return Err(CheckpointError::Count {
+                expected: self.len(),
+                found: rows.len(),
+            });
+        }
+        let mut storage = self.storage.write().map_err(|_| PoisonedError)?;
+        *storage = rows;
+        Ok(())
+    }
+}
+
+/// An error raised while writing or reading the extended variables of a checkpoint.
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// Reading or writing failed.
+    Io(io::Error),
+    /// A thread panicked while holding the lock to the variables.
+    Poisoned(PoisonedError),
+    /// The line at the given number could not be parsed.
+    Parse {
+        /// The number of the line, starting at 1.
+        line: usize,
+    },
+    /// The variable at the given line does not belong to the block registered at its place.
+    Mismatch {
+        /// The number of the line, starting at 1.
+        line: usize,
+    },
+    /// The table holds a different number of variables than the registry.
+    Count {
+        /// The number of registered variables.
+        expected: usize,
+        /// The number of variables in the table.
+        found: usize,
+    },
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<PoisonedError> for CheckpointError {
+    fn from(value: PoisonedError) -> Self {
+        Self::Poisoned(value)
+    }
+}
+
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Io(err) => write!(f, "failed to access the checkpoint: {}", err),
+            Self::Poisoned(err) => write!(f, "extended variables are inaccessible: {}", err),
+            Self::Parse { line } => write!(f, "malformed extended variable at line {}", line),
+            Self::Mismatch { line } => write!(
+                f,
+                "the extended variable at line {} belongs to an unexpected block",
+                line
+            ),
+            Self::Count { expected, found } => write!(
+                f,
+                "expected {} extended variables in the checkpoint, found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Poisoned(err) => Some(err),
+            Self::Parse { .. } | Self::Mismatch { .. } | Self::Count { .. } => None,
+        }
+    }
+}
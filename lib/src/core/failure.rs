@@ -0,0 +1,223 @@
+//! Containment of failures in the threads of a simulation.
+//!
+//! The threads of a step meet at barriers, so a thread which panics or returns an error
+//! would leave the others waiting forever or reading poisoned locks. A [`FailureMonitor`]
+//! replaces the barriers: the first failure is recorded, every waiting thread is released
+//! with an [`AbortedError`] and the remaining threads stop at their next meeting point.
+//!
+//! With the `fallible_hot_path` feature, the expectations of the hot loops are reported
+//! as errors rather than panics, so that a batch job which fails ends with a checkpoint
+//! and a short error file (see [`FailureMonitor::finish_or_exit`]) even when panics abort.
+
+use super::error::{AbortedError, CommError};
+use std::{
+    any::Any,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    process,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+};
+
+/// How a thread of the simulation failed.
+#[derive(Clone, Debug)]
+pub enum FailureCause {
+    /// The thread panicked with the given message.
+    Panic(String),
+    /// The thread returned an error with the given message.
+    Error(String),
+}
+
+/// A structured report of the first failure in a simulation.
+#[derive(Clone, Debug)]
+pub struct FailureReport {
+    /// The step during which the failure arose.
+    pub step: usize,
+    /// The thread in which the failure arose.
+    pub location: CommError,
+    /// The component which failed, e.g. "physical potential".
+    pub component: &'static str,
+    /// How the thread failed.
+    pub cause: FailureCause,
+}
+
+impl Display for FailureReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (kind, message) = match &self.cause {
+            FailureCause::Panic(message) => ("panicked", message),
+            FailureCause::Error(message) => ("failed", message),
+        };
+        write!(
+            f,
+            "the {} {} at step {} in {}: {}",
+            self.component,
+            kind,
+            self.step,
+            self.location.thread_name(),
+            message
+        )
+    }
+}
+
+impl Error for FailureReport {}
+
+struct State {
+    arrived: usize,
+    generation: usize,
+    report: Option<FailureReport>,
+}
+
+/// A barrier for the threads of a simulation which is released as soon as any of them fails.
+pub struct FailureMonitor {
+    threads: usize,
+    state: Mutex<State>,
+    condvar: Condvar,
+}
+
+impl FailureMonitor {
+    /// Constructs a monitor of `threads` threads.
+    pub fn new(threads: usize) -> Self {
+        Self {
+            threads,
+            state: Mutex::new(State {
+                arrived: 0,
+                generation: 0,
+                report: None,
+            }),
+            condvar: Condvar::new(),
+        }
+    }
+
+    // The state is only ever modified without panicking, so a poisoned lock holds valid data.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Blocks until all threads have called this method, like [`Barrier::wait`].
+    ///
+    /// Returns whether this thread is the leader of the generation, or an error
+    /// once any thread has failed, in which case this thread must stop.
+    ///
+    /// [`Barrier::wait`]: std::sync::Barrier::wait
+    pub fn wait(&self) -> Result<bool, AbortedError> {
+        let mut state = self.lock();
+        if state.report.is_some() {
+            return Err(AbortedError);
+        }
+        state.arrived += 1;
+        if state.arrived == self.threads {
+            state.arrived = 0;
+            state.generation += 1;
+            self.condvar.notify_all();
+            return Ok(true);
+        }
+        let generation = state.generation;
+        let state = self
+            .condvar
+            .wait_while(state, |state| {
+                state.generation == generation && state.report.is_none()
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        if state.generation == generation {
+            Err(AbortedError)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Records a failure and releases every waiting thread.
+    ///
+    /// Only the first failure is kept, since later ones are usually its consequences.
+    pub fn fail(&self, report: FailureReport) {
+        let mut state = self.lock();
+        if state.report.is_none() {
+            state.report = Some(report);
+        }
+        self.condvar.notify_all();
+    }
+
+    /// Returns whether any thread has failed.
+    pub fn has_failed(&self) -> bool {
+        self.lock().report.is_some()
+    }
+
+    /// Runs the part of a step performed by `component` in the thread at `location`,
+    /// turning a panic or an error into a recorded failure.
+    ///
+    /// Returns `None` if `f` failed, in which case the thread must stop.
+    pub fn run<R, E, F>(
+        &self,
+        step: usize,
+        location: CommError,
+        component: &'static str,
+        f: F,
+    ) -> Option<R>
+    where
+        E: Display,
+        F: FnOnce() -> Result<R, E>,
+    {
+        let cause = match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(Ok(value)) => return Some(value),
+            Ok(Err(err)) => FailureCause::Error(err.to_string()),
+            Err(payload) => FailureCause::Panic(panic_message(payload.as_ref())),
+        };
+        self.fail(FailureReport {
+            step,
+            location,
+            component,
+            cause,
+        });
+        None
+    }
+
+    /// Consumes the monitor after all threads have stopped.
+    ///
+    /// If a thread has failed, `checkpoint` is called with the report to save
+    /// the last consistent state and the report is returned.
+    pub fn finish<C>(self, checkpoint: C) -> Result<(), FailureReport>
+    where
+        C: FnOnce(&FailureReport),
+    {
+        let state = self
+            .state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        match state.report {
+            Some(report) => {
+                checkpoint(&report);
+                Err(report)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Consumes the monitor after all threads have stopped and exits the process on failure.
+    ///
+    /// If a thread has failed, `checkpoint` is called with the report, the report is written
+    /// to `error_file` and the process exits with a non-zero status and no backtrace.
+    pub fn finish_or_exit<C>(self, checkpoint: C, error_file: &Path)
+    where
+        C: FnOnce(&FailureReport),
+    {
+        if let Err(report) = self.finish(checkpoint) {
+            if let Err(err) = fs::write(error_file, format!("{}\n", report)) {
+                eprintln!("failed to write {}: {}", error_file.display(), err);
+            }
+            eprintln!("{}", report);
+            process::exit(1);
+        }
+    }
+}
+
+/// Returns the message of a panic with the payload `payload`.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        (*message).to_owned()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "a panic with a non-string payload".to_owned()
+    }
+}
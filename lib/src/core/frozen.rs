@@ -0,0 +1,133 @@
+use crate::core::{AtomGroup, error::InvalidIndexError};
+
+/// A mask of the atoms in a group whose positions are held fixed.
+#[derive(Clone, Debug)]
+pub struct FrozenAtoms {
+    mask: Box<[bool]>,
+    frozen: usize,
+}
+
+impl FrozenAtoms {
+    /// Constructs a mask of a group of `group_size` atoms where none are frozen.
+    pub fn none(group_size: usize) -> Self {
+        Self {
+            mask: vec![false; group_size].into_boxed_slice(),
+            frozen: 0,
+        }
+    }
+
+    /// Constructs a mask of a group of `group_size` atoms where all are frozen.
+    pub fn all(group_size: usize) -> Self {
+        Self {
+            mask: vec![true; group_size].into_boxed_slice(),
+            frozen: group_size,
+        }
+    }
+
+    /// Constructs a mask of a group of `group_size` atoms where
+    /// the atoms at `indices` are frozen.
+    pub fn from_indices<I>(group_size: usize, indices: I) -> Result<Self, InvalidIndexError>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut this = Self::none(group_size);
+        for index in indices {
+            let is_frozen = this
+                .mask
+                .get_mut(index)
+                .ok_or(InvalidIndexError::new(index, group_size))?;
+            if !*is_frozen {
+                *is_frozen = true;
+                this.frozen += 1;
+            }
+        }
+        Ok(this)
+    }
+
+    /// Returns whether the atom at `index` is frozen.
+    ///
+    /// Atoms outside of the group are never frozen.
+    pub fn is_frozen(&self, index: usize) -> bool {
+        self.mask.get(index).copied().unwrap_or(false)
+    }
+
+    /// Returns the number of atoms in the group.
+    pub fn len(&self) -> usize {
+        self.mask.len()
+    }
+
+    /// Returns whether the group is empty.
+    pub fn is_empty(&self) -> bool {
+        self.mask.is_empty()
+    }
+
+    /// Returns the number of frozen atoms in the group.
+    pub fn frozen(&self) -> usize {
+        self.frozen
+    }
+
+    /// Returns the number of degrees of freedom of the atoms in the group
+    /// which are not frozen, where each atom has `dimension` degrees of freedom.
+    pub fn degrees_of_freedom(&self, dimension: usize) -> usize {
+        (self.mask.len() - self.frozen) * dimension
+    }
+}
+
+/// A wrapper that holds a set of atoms fixed in place.
+///
+/// Wrapped potentials are evaluated as usual, so frozen atoms are still
+/// seen by all other atoms, but the forces acting on frozen atoms are
+/// zeroed. Wrapped thermostats skip frozen atoms and zero their momenta,
+/// and thermostats of whole groups are told the degrees of freedom
+/// of the atoms which are not frozen.
+/// Consequently, frozen atoms are never displaced by the propagator.
+pub struct Frozen<T: ?Sized> {
+    pub(crate) atoms: FrozenAtoms,
+    pub(crate) inner: T,
+}
+
+impl<T> Frozen<T> {
+    /// Wraps the provided value with `Frozen`.
+    pub fn new(atoms: FrozenAtoms, inner: T) -> Self {
+        Self { atoms, inner }
+    }
+}
+
+impl<T: ?Sized> Frozen<T> {
+    /// Returns the mask of frozen atoms.
+    pub fn atoms(&self) -> &FrozenAtoms {
+        &self.atoms
+    }
+
+    /// Zeroes the vectors of all frozen atoms in `group_vectors`.
+    pub(crate) fn zero_frozen<V: Default>(&self, group_vectors: &mut [V]) {
+        if self.atoms.frozen == 0 {
+            return;
+        }
+        Self::zero_masked(&mut self.atoms.mask.iter(), group_vectors);
+    }
+
+    /// Zeroes the vectors of all frozen atoms in `group_vectors`,
+    /// which are split across several locks in the order of the atoms of the group.
+    pub(crate) fn zero_frozen_in_locks<V: Default>(&self, group_vectors: &mut [AtomGroup<V>]) {
+        if self.atoms.frozen == 0 {
+            return;
+        }
+        let mut mask = self.atoms.mask.iter();
+        for vectors in group_vectors {
+            Self::zero_masked(&mut mask, &mut vectors.write());
+        }
+    }
+
+    /// Zeroes the vectors in `vectors` whose entries of `mask` are set,
+    /// consuming one entry of `mask` per vector.
+    fn zero_masked<'a, V: Default>(mask: &mut impl Iterator<Item = &'a bool>, vectors: &mut [V]) {
+        for (vector, _) in vectors
+            .iter_mut()
+            .zip(mask)
+            .filter(|(_, is_frozen)| **is_frozen)
+        {
+            *vector = V::default();
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Configuration spaces other than flat space, e.g. particles confined to a sphere.
+
+use super::Vector;
+
+/// A Riemannian manifold embedded in `N`-dimensional space on which atoms move.
+///
+/// Positions are points of the manifold and momenta and forces are vectors
+/// tangent to it, all expressed in the coordinates of the embedding space.
+pub trait Manifold<const N: usize, V: Vector<N>> {
+    /// Returns the square of the geodesic distance between `from` and `to`.
+    fn distance_squared(&self, from: &V, to: &V) -> V::Element;
+
+    /// Returns the tangent vector at `from` pointing towards `to`
+    /// whose magnitude is the geodesic distance between them,
+    /// i.e. the generalization of `to - from`.
+    fn log_map(&self, from: &V, to: &V) -> V;
+
+    /// Returns the point reached by following the geodesic from `point`
+    /// along `tangent` for a length equal to its magnitude,
+    /// i.e. the generalization of `point + tangent`.
+    fn exp_map(&self, point: &V, tangent: V) -> V;
+
+    /// Returns the component of `vector` tangent to the manifold at `point`.
+    fn project_tangent(&self, point: &V, vector: V) -> V;
+
+    /// Carries `tangent` from the tangent space at `from` to the tangent space at `to`
+    /// along the geodesic joining them.
+    fn transport(&self, from: &V, to: &V, tangent: V) -> V;
+}
+
+/// Flat Euclidean space, on which every operation of [`Manifold`] reduces to vector arithmetic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flat;
+
+impl<const N: usize, V> Manifold<N, V> for Flat
+where
+    V: Vector<N> + Clone,
+{
+    #[inline(always)]
+    fn distance_squared(&self, from: &V, to: &V) -> V::Element {
+        (to.clone() - from.clone()).magnitude_squared()
+    }
+
+    #[inline(always)]
+    fn log_map(&self, from: &V, to: &V) -> V {
+        to.clone() - from.clone()
+    }
+
+    #[inline(always)]
+    fn exp_map(&self, point: &V, tangent: V) -> V {
+        point.clone() + tangent
+    }
+
+    #[inline(always)]
+    fn project_tangent(&self, _point: &V, vector: V) -> V {
+        vector
+    }
+
+    #[inline(always)]
+    fn transport(&self, _from: &V, _to: &V, tangent: V) -> V {
+        tangent
+    }
+}
@@ -0,0 +1,14 @@
+//! Types shared by Monte-Carlo moves.
+
+/// An enum for tracking which group holds the atom moved in a Monte-Carlo step.
+#[derive(Clone, Copy, Debug)]
+pub enum ChangedGroup {
+    /// The group whose contribution is being evaluated.
+    This,
+    /// Another group, with the given index.
+    Other(usize),
+}
+
+mod volume;
+
+pub use volume::{AxisCoupling, VolumeMove, VolumeProposal, VolumeScaling};
@@ -0,0 +1,393 @@
+//! Volume-changing moves for sampling the isothermal-isobaric (NPT) ensemble.
+
+use crate::core::Vector;
+use std::ops::{Add, Div, Mul, Sub};
+
+/// How a volume move deforms the simulation box.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolumeScaling {
+    /// Every dimension of the box is scaled by the same factor.
+    Isotropic,
+    /// A single dimension of the box, chosen at random in every move, is scaled.
+    Anisotropic,
+    /// Either the coupled dimensions of the box together, by the same factor,
+    /// or a single independent one is scaled, chosen at random in every move,
+    /// e.g. the two dimensions in the plane of a slab and the one normal to it.
+    SemiIsotropic,
+}
+
+/// How a dimension of the box takes part in volume moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AxisCoupling {
+    /// The dimension is scaled together with the other coupled dimensions.
+    #[default]
+    Coupled,
+    /// The dimension is scaled on its own in [`VolumeScaling::SemiIsotropic`] moves.
+    Independent,
+    /// The dimension is never scaled, e.g. the one normal to a wall.
+    Fixed,
+}
+
+/// A proposed change of the dimensions of the simulation box.
+#[derive(Clone, Copy, Debug)]
+pub struct VolumeProposal<T, const N: usize> {
+    /// The factors scaling each dimension of the box.
+    pub factors: [T; N],
+    /// The logarithm of the ratio of the new volume to the old one.
+    pub log_volume_ratio: T,
+}
+
+impl<T, const N: usize> VolumeProposal<T, N>
+where
+    T: Clone + From<f32> + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    /// Returns the proposal restoring the box changed by `self`, used when the move is rejected.
+    pub fn inverse(&self) -> Self {
+        Self {
+            factors: self.factors.clone().map(|factor| T::from(1.0) / factor),
+            log_volume_ratio: T::from(0.0) - self.log_volume_ratio.clone(),
+        }
+    }
+
+    /// Scales the lengths of the box.
+    pub fn scale_box(&self, lengths: &mut [T; N]) {
+        for (length, factor) in lengths.iter_mut().zip(&self.factors) {
+            *length = length.clone() * factor.clone();
+        }
+    }
+
+    /// Scales the centroids of the atoms of a group whose positions in every image
+    /// are given by `images`, keeping the displacements of the images from the centroids.
+    ///
+    /// Scaling the centroids rather than every image leaves the springs between
+    /// the images unchanged, so only the physical potential energy has to be recalculated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the images hold groups of different sizes.
+    pub fn scale_centroids<V>(&self, images: &mut [&mut [V]])
+    where
+        V: Vector<N, Element = T> + Clone,
+    {
+        let Some(atoms) = images.first().map(|image| image.len()) else {
+            return;
+        };
+        assert!(
+            images.iter().all(|image| image.len() == atoms),
+            "every image must hold the same number of atoms"
+        );
+        let image_count = T::from(images.len() as f32);
+        for atom in 0..atoms {
+            let mut centroid = images[0][atom].clone();
+            for image in &images[1..] {
+                centroid += image[atom].clone();
+            }
+            let centroid = centroid / image_count.clone();
+            let mut shift = centroid.clone();
+            for ((component, coordinate), factor) in shift
+                .as_mut_array()
+                .iter_mut()
+                .zip(centroid.as_array())
+                .zip(&self.factors)
+            {
+                *component = coordinate.clone() * (factor.clone() - T::from(1.0));
+            }
+            for image in images.iter_mut() {
+                image[atom] += shift.clone();
+            }
+        }
+    }
+}
+
+/// A Monte-Carlo move changing the volume of the simulation box at constant pressure.
+///
+/// The logarithm of the volume is displaced uniformly by at most `max_log_volume_change`,
+/// all centroids are scaled with the box and the whole system is re-evaluated,
+/// after which the move is accepted with the probability
+/// `min(1, exp(-β (ΔU + P ΔV) + (M + 1) ln(V' / V)))` for `M` molecules.
+///
+/// The dimensions of the box take part in the moves according to their [`AxisCoupling`]
+/// and the groups marked as excluded, e.g. the frozen atoms of a wall, are not scaled with the box.
+#[derive(Clone, Debug)]
+pub struct VolumeMove<T> {
+    pressure: T,
+    beta: T,
+    max_log_volume_change: T,
+    scaling: VolumeScaling,
+    axes: Vec<AxisCoupling>,
+    excluded_groups: Vec<bool>,
+    attempted: usize,
+    accepted: usize,
+}
+
+impl<T> VolumeMove<T> {
+    /// Constructs a move at the pressure `pressure` and inverse temperature `beta`.
+    pub fn new(pressure: T, beta: T, max_log_volume_change: T, scaling: VolumeScaling) -> Self
+    where
+        T: PartialOrd + From<f32>,
+    {
+        assert!(
+            beta > T::from(0.0),
+            "the inverse temperature must be positive"
+        );
+        assert!(
+            max_log_volume_change > T::from(0.0),
+            "the maximal change of the volume must be positive"
+        );
+        Self {
+            pressure,
+            beta,
+            max_log_volume_change,
+            scaling,
+            axes: Vec::new(),
+            excluded_groups: Vec::new(),
+            attempted: 0,
+            accepted: 0,
+        }
+    }
+
+    /// Sets how every dimension of the box takes part in the moves.
+    /// Dimensions beyond `axes` are coupled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every dimension is fixed.
+    pub fn with_axes(self, axes: Vec<AxisCoupling>) -> Self {
+        assert!(
+            axes.is_empty() || axes.iter().any(|&axis| axis != AxisCoupling::Fixed),
+            "at least one dimension of the box must be scaled"
+        );
+        Self { axes, ..self }
+    }
+
+    /// Marks the groups whose centroids are not scaled with the box,
+    /// where `excluded_groups[i]` is `true` for an excluded group with index `i`.
+    pub fn with_excluded_groups(self, excluded_groups: Vec<bool>) -> Self {
+        Self {
+            excluded_groups,
+            ..self
+        }
+    }
+
+    /// Returns how the dimension `axis` of the box takes part in the moves.
+    pub fn axis(&self, axis: usize) -> AxisCoupling {
+        self.axes.get(axis).copied().unwrap_or_default()
+    }
+
+    /// Returns whether the centroids of the group with index `group` are kept in place.
+    pub fn is_excluded(&self, group: usize) -> bool {
+        self.excluded_groups.get(group).copied().unwrap_or(false)
+    }
+
+    /// Scales the centroids of the group with index `group` like [`VolumeProposal::scale_centroids`],
+    /// unless the group is excluded.
+    pub fn scale_group_centroids<const N: usize, V>(
+        &self,
+        proposal: &VolumeProposal<T, N>,
+        group: usize,
+        images: &mut [&mut [V]],
+    ) where
+        T: Clone
+            + From<f32>
+            + Add<Output = T>
+            + Sub<Output = T>
+            + Mul<Output = T>
+            + Div<Output = T>,
+        V: Vector<N, Element = T> + Clone,
+    {
+        if !self.is_excluded(group) {
+            proposal.scale_centroids(images);
+        }
+    }
+
+    /// Returns the target pressure.
+    pub fn pressure(&self) -> &T {
+        &self.pressure
+    }
+
+    /// Sets the target pressure.
+    pub fn set_pressure(&mut self, pressure: T) {
+        self.pressure = pressure;
+    }
+
+    /// Sets the inverse temperature `β = 1 / (k_B T)`.
+    pub fn set_beta(&mut self, beta: T) {
+        self.beta = beta;
+    }
+
+    /// Returns the way the box is deformed.
+    pub fn scaling(&self) -> VolumeScaling {
+        self.scaling
+    }
+
+    /// Returns the number of moves attempted and accepted so far.
+    pub fn statistics(&self) -> (usize, usize) {
+        (self.attempted, self.accepted)
+    }
+}
+
+macro_rules! impl_volume_move {
+    ($($float:ty),*) => {
+        $(
+            impl VolumeMove<$float> {
+                /// Proposes a change of a box with `N` dimensions from `uniform`,
+                /// a random number uniformly distributed in `[0, 1)`, and `axis_uniform`,
+                /// another one choosing the scaled axis in anisotropic moves.
+                ///
+                /// # Panics
+                ///
+                /// Panics if none of the `N` dimensions can be scaled by the moves,
+                /// i.e. all of them are fixed or, in [`VolumeScaling::SemiIsotropic`] moves,
+                /// none of them is coupled or independent.
+                pub fn propose<const N: usize>(
+                    &self,
+                    uniform: $float,
+                    axis_uniform: $float,
+                ) -> VolumeProposal<$float, N> {
+                    let log_volume_ratio = (2.0 * uniform - 1.0) * self.max_log_volume_change;
+                    let scaled: [bool; N] = std::array::from_fn(|axis| {
+                        match self.scaling {
+                            VolumeScaling::Isotropic | VolumeScaling::Anisotropic => {
+                                self.axis(axis) != AxisCoupling::Fixed
+                            }
+                            VolumeScaling::SemiIsotropic => {
+                                self.axis(axis) == AxisCoupling::Coupled
+                            }
+                        }
+                    });
+                    // The coupled dimensions, if any, count as a single candidate
+                    // in semi-isotropic moves.
+                    let coupled = scaled.iter().position(|&coupled| coupled);
+                    let candidates: Vec<usize> = match self.scaling {
+                        VolumeScaling::Isotropic | VolumeScaling::Anisotropic => {
+                            (0..N).filter(|&axis| scaled[axis]).collect()
+                        }
+                        VolumeScaling::SemiIsotropic => coupled
+                            .into_iter()
+                            .chain((0..N).filter(|&axis| {
+                                self.axis(axis) == AxisCoupling::Independent
+                            }))
+                            .collect(),
+                    };
+                    assert!(
+                        !candidates.is_empty(),
+                        "none of the {} dimensions of the box is scaled",
+                        N
+                    );
+                    let index = (axis_uniform * candidates.len() as $float) as usize;
+                    let axis = candidates[index.min(candidates.len() - 1)];
+                    let mut block = [false; N];
+                    match self.scaling {
+                        VolumeScaling::Isotropic => block = scaled,
+                        VolumeScaling::Anisotropic => block[axis] = true,
+                        VolumeScaling::SemiIsotropic => {
+                            if Some(axis) == coupled {
+                                block = scaled;
+                            } else {
+                                block[axis] = true;
+                            }
+                        }
+                    }
+                    let count = block.iter().filter(|&&scaled| scaled).count();
+                    let factor = (log_volume_ratio / count as $float).exp();
+                    let factors = block.map(|scaled| if scaled { factor } else { 1.0 });
+                    VolumeProposal {
+                        factors,
+                        log_volume_ratio,
+                    }
+                }
+
+                /// Returns the logarithm of the acceptance probability of `proposal`
+                /// from a box of volume `old_volume` holding `molecules` molecules
+                /// given the change in the potential energy of the system.
+                pub fn log_acceptance<const N: usize>(
+                    &self,
+                    proposal: &VolumeProposal<$float, N>,
+                    old_volume: $float,
+                    potential_energy_diff: $float,
+                    molecules: usize,
+                ) -> $float {
+                    let volume_diff = old_volume * proposal.log_volume_ratio.exp_m1();
+                    -self.beta * (potential_energy_diff + self.pressure * volume_diff)
+                        + (molecules + 1) as $float * proposal.log_volume_ratio
+                }
+
+                /// Decides whether `proposal` is accepted using `uniform`, a random number
+                /// uniformly distributed in `[0, 1)`, and records the outcome.
+                ///
+                /// On rejection, the caller restores the box and the centroids
+                /// with [`VolumeProposal::inverse`] along with the potential energy.
+                pub fn accept<const N: usize>(
+                    &mut self,
+                    proposal: &VolumeProposal<$float, N>,
+                    old_volume: $float,
+                    potential_energy_diff: $float,
+                    molecules: usize,
+                    uniform: $float,
+                ) -> bool {
+                    self.attempted += 1;
+                    let log_acceptance =
+                        self.log_acceptance(proposal, old_volume, potential_energy_diff, molecules);
+                    let accepted = log_acceptance >= 0.0 || uniform < log_acceptance.exp();
+                    if accepted {
+                        self.accepted += 1;
+                    }
+                    accepted
+                }
+            }
+        )*
+    };
+}
+
+impl_volume_move!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_scaling_changes_the_volume_it_reports() {
+        let axes = vec![
+            AxisCoupling::Coupled,
+            AxisCoupling::Independent,
+            AxisCoupling::Fixed,
+        ];
+        for scaling in [
+            VolumeScaling::Isotropic,
+            VolumeScaling::Anisotropic,
+            VolumeScaling::SemiIsotropic,
+        ] {
+            let volume_move = VolumeMove::new(1.0_f64, 1.0, 0.1, scaling).with_axes(axes.clone());
+            for axis_uniform in [0.0, 0.5, 0.999] {
+                let proposal = volume_move.propose::<3>(0.9, axis_uniform);
+                let log_volume_ratio: f64 = proposal.factors.iter().map(|factor| factor.ln()).sum();
+                assert!((log_volume_ratio - proposal.log_volume_ratio).abs() < 1e-12);
+                assert_eq!(proposal.factors[2], 1.0);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "none of the 2 dimensions of the box is scaled")]
+    fn fixed_dimensions_within_the_box_are_rejected() {
+        VolumeMove::new(1.0_f64, 1.0, 0.1, VolumeScaling::Anisotropic)
+            .with_axes(vec![
+                AxisCoupling::Fixed,
+                AxisCoupling::Fixed,
+                AxisCoupling::Coupled,
+            ])
+            .propose::<2>(0.9, 0.5);
+    }
+
+    #[test]
+    #[should_panic(expected = "none of the 2 dimensions of the box is scaled")]
+    fn semi_isotropic_moves_need_a_coupled_or_independent_dimension() {
+        VolumeMove::new(1.0_f64, 1.0, 0.1, VolumeScaling::SemiIsotropic)
+            .with_axes(vec![
+                AxisCoupling::Fixed,
+                AxisCoupling::Fixed,
+                AxisCoupling::Independent,
+            ])
+            .propose::<2>(0.9, 0.5);
+    }
+}
@@ -0,0 +1,150 @@
+//! Types for describing which groups are treated classically and which are quantized.
+//!
+//! These types only record the treatment of every group. [`Treated`] switches off
+//! the exchange potential of classical groups and [`Partition::contributes`] tells
+//! a per-image estimator whether to count a group, but the positions of every group
+//! are still stored and propagated in every image. Whoever owns the images is responsible
+//! for keeping the replicas of a classical group identical, e.g. by propagating them
+//! under the same forces from the same initial positions.
+
+use super::{atoms::AtomTypeInfo, error::InvalidIndexError};
+
+/// How the atoms of a group are treated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Treatment {
+    /// The atoms are represented by a ring polymer with a replica in every image.
+    #[default]
+    Quantum,
+    /// The atoms are represented by a single replica.
+    Classical,
+}
+
+impl Treatment {
+    /// Returns the number of replicas of every atom in a simulation with `images` images.
+    pub const fn replicas(self, images: usize) -> usize {
+        match self {
+            Self::Quantum => images,
+            Self::Classical => 1,
+        }
+    }
+
+    /// Returns whether the atoms are quantized.
+    pub const fn is_quantum(self) -> bool {
+        matches!(self, Self::Quantum)
+    }
+}
+
+/// The treatment of every group in the system.
+///
+/// Neither the driver nor the estimators consult the partition by themselves,
+/// so it is up to the caller to wrap the exchange potentials in [`Treated`]
+/// and to filter per-image contributions with [`Partition::contributes`].
+#[derive(Clone, Debug)]
+pub struct Partition {
+    treatments: Box<[Treatment]>,
+}
+
+impl Partition {
+    /// Constructs a partition of `groups` groups which are all quantized.
+    pub fn quantum(groups: usize) -> Self {
+        Self {
+            treatments: vec![Treatment::Quantum; groups].into_boxed_slice(),
+        }
+    }
+
+    /// Constructs a partition where every group takes the treatment of its atom type.
+    pub fn from_atom_types<T>(atom_types: &[AtomTypeInfo<T>]) -> Self {
+        Self {
+            treatments: atom_types
+                .iter()
+                .flat_map(|atom_type| {
+                    std::iter::repeat_n(atom_type.treatment(), atom_type.groups.groups())
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the number of groups.
+    pub fn groups(&self) -> usize {
+        self.treatments.len()
+    }
+
+    /// Returns the treatment of `group`.
+    pub fn treatment(&self, group: usize) -> Result<Treatment, InvalidIndexError> {
+        self.treatments
+            .get(group)
+            .copied()
+            .ok_or(InvalidIndexError::new(group, self.groups()))
+    }
+
+    /// Sets the treatment of `group`.
+    pub fn set_treatment(
+        &mut self,
+        group: usize,
+        treatment: Treatment,
+    ) -> Result<(), InvalidIndexError> {
+        let groups = self.groups();
+        *self
+            .treatments
+            .get_mut(group)
+            .ok_or(InvalidIndexError::new(group, groups))? = treatment;
+        Ok(())
+    }
+
+    /// Returns the number of replicas of every atom in `group`
+    /// in a simulation with `images` images.
+    pub fn replicas(&self, group: usize, images: usize) -> Result<usize, InvalidIndexError> {
+        self.treatment(group)
+            .map(|treatment| treatment.replicas(images))
+    }
+
+    /// Returns whether `group` contributes to an observable summed over the images at `image`.
+    ///
+    /// Quantized groups contribute in every image, while classical groups
+    /// contribute only in the first one, so that their single replica is counted once.
+    pub fn contributes(&self, group: usize, image: usize) -> Result<bool, InvalidIndexError> {
+        self.treatment(group)
+            .map(|treatment| treatment.is_quantum() || image == 0)
+    }
+
+    /// Returns an iterator over the indices of the quantized groups.
+    pub fn quantum_groups(&self) -> impl Iterator<Item = usize> + '_ {
+        self.groups_with(Treatment::Quantum)
+    }
+
+    /// Returns an iterator over the indices of the classical groups.
+    pub fn classical_groups(&self) -> impl Iterator<Item = usize> + '_ {
+        self.groups_with(Treatment::Classical)
+    }
+
+    fn groups_with(&self, treatment: Treatment) -> impl Iterator<Item = usize> + '_ {
+        self.treatments
+            .iter()
+            .enumerate()
+            .filter_map(move |(group, &other)| (other == treatment).then_some(group))
+    }
+}
+
+/// A wrapper which switches off the wrapped exchange potential for classical groups.
+///
+/// Quantized groups are forwarded to the wrapped potential, while for classical
+/// groups the exchange potential energy is zero and the forces are left untouched
+/// or set to zero, so that a single replica is not coupled to anything.
+pub struct Treated<T: ?Sized> {
+    pub(crate) treatment: Treatment,
+    pub(crate) inner: T,
+}
+
+impl<T> Treated<T> {
+    /// Wraps the provided value with `Treated`.
+    pub fn new(treatment: Treatment, inner: T) -> Self {
+        Self { treatment, inner }
+    }
+}
+
+impl<T: ?Sized> Treated<T> {
+    /// Returns the treatment of the group.
+    pub fn treatment(&self) -> Treatment {
+        self.treatment
+    }
+}
@@ -0,0 +1,335 @@
+//! Names and stable identifiers of the groups of a simulation.
+//!
+//! Internally a group is addressed by its index, which follows the order the groups
+//! are laid out in and changes whenever they are reordered, e.g. by an edited configuration.
+//! A [`GroupRegistry`] gives every group a name, used by configuration files, selections,
+//! outputs and error messages, and a [`GroupId`] which is stored in checkpoints
+//! and kept by a resumed run, so per-group state is matched to the right group
+//! even if the groups are listed in a different order.
+
+use super::atoms::AtomTypeInfo;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, BufRead, Write},
+};
+
+/// The identifier of a group, which never changes for the lifetime of a simulation,
+/// including across restarts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupId(usize);
+
+impl GroupId {
+    /// Returns the identifier as a number.
+    pub fn get(self) -> usize {
+        self.0
+    }
+}
+
+impl Display for GroupId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "#{}", self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    id: GroupId,
+    name: String,
+    metadata: Vec<(String, String)>,
+}
+
+/// The names, identifiers and metadata of the groups, in the order of their indices.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GroupRegistry {
+    entries: Vec<Entry>,
+    next_id: usize,
+}
+
+impl GroupRegistry {
+    /// Constructs a registry without groups.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a registry naming the groups of `atom_types` after the label of their type,
+    /// followed by the position of the group within the type if the type has several groups,
+    /// e.g. "O" or "H.0" and "H.1".
+    pub fn from_atom_types<T>(atom_types: &[AtomTypeInfo<T>]) -> Result<Self, RegistryError> {
+        let mut registry = Self::new();
+        for atom_type in atom_types {
+            let groups = atom_type.groups.groups();
+            for group in 0..groups {
+                if groups == 1 {
+                    registry.register(&atom_type.label)?;
+                } else {
+                    registry.register(&format!("{}.{}", atom_type.label, group))?;
+                }
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Appends a group named `name`, returning its identifier.
+    ///
+    /// Names are single words, so that they can be written to tab-separated tables,
+    /// and must not start with '#', which marks an identifier in a selection.
+    pub fn register(&mut self, name: &str) -> Result<GroupId, RegistryError> {
+        if name.is_empty() || name.starts_with('#') || name.contains(char::is_whitespace) {
+            return Err(RegistryError::InvalidName(name.to_owned()));
+        }
+        if self.index_of_name(name).is_some() {
+            return Err(RegistryError::DuplicateName(name.to_owned()));
+        }
+        let id = GroupId(self.next_id);
+        self.next_id += 1;
+        self.entries.push(Entry {
+            id,
+            name: name.to_owned(),
+            metadata: Vec::new(),
+        });
+        Ok(id)
+    }
+
+    /// Returns the number of groups.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether there are no groups.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn index_of_name(&self, name: &str) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.name == name)
+    }
+
+    /// Returns the index of the group identified by `id`, if it is registered.
+    pub fn index(&self, id: GroupId) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.id == id)
+    }
+
+    /// Returns the identifier of the group at `index`.
+    pub fn id(&self, index: usize) -> Option<GroupId> {
+        self.entries.get(index).map(|entry| entry.id)
+    }
+
+    /// Returns the name of the group at `index`.
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(|entry| entry.name.as_str())
+    }
+
+    /// Returns the identifier of the group named `name`.
+    pub fn id_of(&self, name: &str) -> Option<GroupId> {
+        self.index_of_name(name).map(|index| self.entries[index].id)
+    }
+
+    /// Returns the index of the group selected by `selection`,
+    /// which is either the name of the group or its identifier, e.g. "#3".
+    pub fn resolve(&self, selection: &str) -> Result<usize, RegistryError> {
+        let index = match selection.strip_prefix('#') {
+            Some(id) => id.parse().ok().and_then(|id| self.index(GroupId(id))),
+            None => self.index_of_name(selection),
+        };
+        index.ok_or_else(|| RegistryError::UnknownGroup(selection.to_owned()))
+    }
+
+    /// Returns a description of the group at `index` for error messages and logs,
+    /// e.g. "group water (#3)", falling back to the bare index if it is not registered.
+    pub fn describe(&self, index: usize) -> String {
+        match self.entries.get(index) {
+            Some(entry) => format!("group {} ({})", entry.name, entry.id),
+            None => format!("group {}", index),
+        }
+    }
+
+    /// Sets the metadata of the group at `index` under `key`, e.g. the residue or the force field,
+    /// replacing any value set before.
+    ///
+    /// Keys and values are single words.
+    pub fn set_metadata(
+        &mut self,
+        index: usize,
+        key: &str,
+        value: &str,
+    ) -> Result<(), RegistryError> {
+        let entry = self
+            .entries
+            .get_mut(index)
+            .ok_or_else(|| RegistryError::UnknownGroup(index.to_string()))?;
+        for word in [key, value] {
+            if word.is_empty() || word.contains(char::is_whitespace) || word.contains('=') {
+                return Err(RegistryError::InvalidName(word.to_owned()));
+            }
+        }
+        match entry
+            .metadata
+            .iter_mut()
+            .find(|(existing, _)| existing == key)
+        {
+            Some((_, existing)) => *existing = value.to_owned(),
+            None => entry.metadata.push((key.to_owned(), value.to_owned())),
+        }
+        Ok(())
+    }
+
+    /// Returns the metadata of the group at `index` under `key`, if any.
+    pub fn metadata(&self, index: usize, key: &str) -> Option<&str> {
+        self.entries
+            .get(index)?
+            .metadata
+            .iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns an iterator over the identifiers and names of the groups, in the order of their indices.
+    pub fn iter(&self) -> impl Iterator<Item = (GroupId, &str)> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.id, entry.name.as_str()))
+    }
+
+    /// Gives the groups of `self`, e.g. freshly read from the configuration of a resumed run,
+    /// the identifiers they have in `stored`, the registry of the checkpoint, matching them
+    /// by name. Groups absent from `stored` receive identifiers no stored group has had.
+    ///
+    /// Returns, for every index of `stored`, the index of the same group in `self`,
+    /// or `None` if the group has been removed, so that per-group state can be permuted.
+    pub fn adopt(&mut self, stored: &GroupRegistry) -> Vec<Option<usize>> {
+        self.next_id = self.next_id.max(stored.next_id);
+        let mut next_id = self.next_id;
+        let mut mapping = vec![None; stored.len()];
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            match stored.index_of_name(&entry.name) {
+                Some(stored_index) => {
+                    entry.id = stored.entries[stored_index].id;
+                    mapping[stored_index] = Some(index);
+                }
+                None => {
+                    entry.id = GroupId(next_id);
+                    next_id += 1;
+                }
+            }
+        }
+        self.next_id = next_id;
+        mapping
+    }
+
+    /// Writes the registry to `writer` as a tab-separated table, one group per line
+    /// in the order of the indices, with the metadata as `key=value` fields.
+    pub fn write_checkpoint<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "next\t{}", self.next_id)?;
+        writeln!(writer, "# id\tname\tmetadata")?;
+        for entry in &self.entries {
+            write!(writer, "{}\t{}", entry.id.0, entry.name)?;
+            for (key, value) in &entry.metadata {
+                write!(writer, "\t{}={}", key, value)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a registry written by [`GroupRegistry::write_checkpoint`].
+    pub fn read_checkpoint<R: BufRead>(reader: R) -> Result<Self, RegistryError> {
+        let mut registry = Self::new();
+        let mut next_id = None;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_number = number + 1;
+            let parse_error = || RegistryError::Parse { line: line_number };
+            if next_id.is_none() {
+                next_id = Some(
+                    line.strip_prefix("next\t")
+                        .and_then(|next| next.parse().ok())
+                        .ok_or_else(parse_error)?,
+                );
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let id = fields
+                .next()
+                .and_then(|id| id.parse().ok())
+                .ok_or_else(parse_error)?;
+            let name = fields.next().ok_or_else(parse_error)?;
+            if registry.index(GroupId(id)).is_some() {
+                return Err(RegistryError::DuplicateId { line: line_number });
+            }
+            registry.register(name)?;
+            let index = registry.len() - 1;
+            registry.entries[index].id = GroupId(id);
+            for field in fields {
+                let (key, value) = field.split_once('=').ok_or_else(parse_error)?;
+                registry.set_metadata(index, key, value)?;
+            }
+        }
+        let next_id = next_id.ok_or(RegistryError::Parse { line: 1 })?;
+        if registry.entries.iter().any(|entry| entry.id.0 >= next_id) {
+            return Err(RegistryError::Parse { line: 1 });
+        }
+        registry.next_id = next_id;
+        Ok(registry)
+    }
+}
+
+/// An error raised while registering, selecting or reading groups.
+#[derive(Debug)]
+pub enum RegistryError {
+    /// Reading failed.
+    Io(io::Error),
+    /// A name, metadata key or value is empty, contains whitespace
+    /// or otherwise cannot be written to a table.
+    InvalidName(String),
+    /// A group of the same name is already registered.
+    DuplicateName(String),
+    /// No group is selected by the given name or identifier.
+    UnknownGroup(String),
+    /// The line at the given number could not be parsed.
+    Parse {
+        /// The number of the line, starting at 1.
+        line: usize,
+    },
+    /// The identifier at the given line has already been listed.
+    DuplicateId {
+        /// The number of the line, starting at 1.
+        line: usize,
+    },
+}
+
+impl From<io::Error> for RegistryError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Io(err) => write!(f, "failed to read the group registry: {}", err),
+            Self::InvalidName(name) => {
+                write!(f, "{:?} is not a valid group name or metadata", name)
+            }
+            Self::DuplicateName(name) => write!(f, "the group {:?} is registered twice", name),
+            Self::UnknownGroup(selection) => write!(f, "no group {:?}", selection),
+            Self::Parse { line } => write!(f, "malformed group at line {}", line),
+            Self::DuplicateId { line } => {
+                write!(f, "the group id at line {} is listed twice", line)
+            }
+        }
+    }
+}
+
+impl Error for RegistryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Changing the number of images of a running simulation.
+//!
+//! The support is experimental: the driver resamples the positions and the momenta
+//! of every atom onto the new images between two steps and informs every component
+//! whose state depends on the number of images, after which the run continues
+//! as if it had been started with the new number of images.
+
+use super::Decoupled;
+
+/// A trait for components whose parameters or cached values depend on the number of images,
+/// e.g. the springs of exchange potentials and the eigenvalues of normal-mode transformations.
+pub trait BeadCountDependent {
+    /// Informs `self` that the number of images has changed to `beads`.
+    ///
+    /// Called by the driver between two steps, after the positions have been
+    /// resampled onto the new images and before any of them is read.
+    fn set_beads(&mut self, beads: usize);
+}
+
+impl<D: BeadCountDependent + ?Sized> BeadCountDependent for Decoupled<D> {
+    #[inline(always)]
+    fn set_beads(&mut self, beads: usize) {
+        self.0.set_beads(beads);
+    }
+}
@@ -0,0 +1,119 @@
+//! Immutable views of the state of a running simulation
+//! for threads outside of it, such as dashboards or language bindings.
+
+use super::{Image, error::PoisonedError};
+use std::sync::{Arc, Mutex};
+
+/// The energies of the system at a step.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Energies<T> {
+    /// The kinetic energy.
+    pub kinetic: T,
+    /// The physical potential energy.
+    pub physical_potential: T,
+    /// The exchange potential energy.
+    pub exchange_potential: T,
+}
+
+/// A copy of the state of the simulation at a step.
+///
+/// The positions are shared between clones, so cloning is cheap.
+#[derive(Debug)]
+pub struct Snapshot<T, V> {
+    step: usize,
+    energies: Energies<T>,
+    positions: Arc<[Box<[V]>]>,
+}
+
+impl<T: Clone, V> Clone for Snapshot<T, V> {
+    fn clone(&self) -> Self {
+        Self {
+            step: self.step,
+            energies: self.energies.clone(),
+            positions: Arc::clone(&self.positions),
+        }
+    }
+}
+
+impl<T, V: Clone> Snapshot<T, V> {
+    /// Copies the positions of every image while holding read locks to the whole image,
+    /// so that every image is copied in a consistent state.
+    ///
+    /// Blocks until the threads writing to an image have released it.
+    pub fn capture(
+        step: usize,
+        energies: Energies<T>,
+        images: &[Image<V>],
+    ) -> Result<Self, PoisonedError> {
+        let positions = images
+            .iter()
+            .map(|image| {
+                image
+                    .read()
+                    .map(|positions| Box::from(&*positions))
+                    .map_err(|_| PoisonedError)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            step,
+            energies,
+            positions,
+        })
+    }
+}
+
+impl<T, V> Snapshot<T, V> {
+    /// Returns the step the snapshot was taken at.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the energies of the system.
+    pub fn energies(&self) -> &Energies<T> {
+        &self.energies
+    }
+
+    /// Returns the number of images.
+    pub fn images(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns the positions of all atoms in `image`, or `None` if there is no such image.
+    pub fn positions(&self, image: usize) -> Option<&[V]> {
+        self.positions.get(image).map(AsRef::as_ref)
+    }
+}
+
+/// A slot holding the latest snapshot, shared between the simulation and its observers.
+#[derive(Debug)]
+pub struct SnapshotCell<T, V> {
+    latest: Mutex<Option<Snapshot<T, V>>>,
+}
+
+impl<T: Clone, V> SnapshotCell<T, V> {
+    /// Constructs an empty slot.
+    pub const fn new() -> Self {
+        Self {
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// Replaces the latest snapshot with `snapshot`.
+    pub fn publish(&self, snapshot: Snapshot<T, V>) {
+        *self.latest.lock().unwrap_or_else(|err| err.into_inner()) = Some(snapshot);
+    }
+
+    /// Returns the latest snapshot, or `None` if none has been published yet.
+    pub fn latest(&self) -> Option<Snapshot<T, V>> {
+        self.latest
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+impl<T: Clone, V> Default for SnapshotCell<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,46 +3,60 @@
 
 use std::ops::{Deref, DerefMut};
 
-/// An enum differentiating between distinguishable and bosonic statistics.
+/// An enum differentiating between distinguishable, bosonic and fermionic statistics.
+///
+/// Fermions are sampled with the weights of bosons, so that the ensemble stays positive,
+/// and observables are reweighted by the sign of the exchange. Unless given otherwise,
+/// the fermionic arm holds the same type as the bosonic one.
 #[derive(Clone, Copy, Debug)]
 #[non_exhaustive]
-pub enum Stat<D, B> {
+pub enum Stat<D, B, F = B> {
     /// Distinguishable statistics.
     Distinguishable(D),
     /// Bosonic statistics.
     Bosonic(B),
+    /// Fermionic statistics.
+    Fermionic(F),
 }
 
-impl<D, B> Stat<D, B> {
-    /// Converts from `Stat<D, B>` to
-    /// `Stat<&D::Target, &B::Target>`.
+impl<D, B, F> Stat<D, B, F> {
+    /// Converts from `Stat<D, B, F>` to
+    /// `Stat<&D::Target, &B::Target, &F::Target>`.
     ///
     /// Leaves the original `Stat` in-place,
     /// creating a new one containing references to the inner types' `Deref::Target` types.
-    pub fn as_deref(&self) -> Stat<&<D as Deref>::Target, &<B as Deref>::Target>
+    pub fn as_deref(
+        &self,
+    ) -> Stat<&<D as Deref>::Target, &<B as Deref>::Target, &<F as Deref>::Target>
     where
         D: Deref,
         B: Deref,
+        F: Deref,
     {
         match self {
             Self::Distinguishable(dist) => Stat::Distinguishable(dist),
             Self::Bosonic(boson) => Stat::Bosonic(boson),
+            Self::Fermionic(fermion) => Stat::Fermionic(fermion),
         }
     }
 
-    /// Converts from `Stat<D, B>` to
-    /// `Stat<&mut D::Target, &mut B::Target>`.
+    /// Converts from `Stat<D, B, F>` to
+    /// `Stat<&mut D::Target, &mut B::Target, &mut F::Target>`.
     ///
     /// Leaves the original `Stat` in-place,
     /// creating a new one containing mutable references to the inner types' `Deref::Target` types.
-    pub fn as_deref_mut(&mut self) -> Stat<&mut <D as Deref>::Target, &mut <B as Deref>::Target>
+    pub fn as_deref_mut(
+        &mut self,
+    ) -> Stat<&mut <D as Deref>::Target, &mut <B as Deref>::Target, &mut <F as Deref>::Target>
     where
         D: DerefMut,
         B: DerefMut,
+        F: DerefMut,
     {
         match self {
             Self::Distinguishable(dist) => Stat::Distinguishable(dist),
             Self::Bosonic(boson) => Stat::Bosonic(boson),
+            Self::Fermionic(fermion) => Stat::Fermionic(fermion),
         }
     }
 }
@@ -52,3 +66,6 @@ pub trait Distinguishable {}
 
 /// A trait for marking exchange potentials of bosons.
 pub trait Bosonic {}
+
+/// A trait for marking exchange potentials of fermions.
+pub trait Fermionic {}
@@ -1,43 +1,318 @@
 //! Traits for parallelized calculations.
 
-/// A trait for objects which add up values and send the sum to a `SyncAddReciever`.
-pub trait SyncAddSender<T> {
+use std::{
+    ops::{Add, Mul},
+    task::Poll,
+};
+
+pub mod local;
+
+/// A trait for associative binary operations used to reduce values across parallel workers.
+pub trait ReduceOp<T> {
+    /// Returns the identity element of the operation.
+    fn identity() -> T;
+
+    /// Combines two values.
+    fn combine(lhs: T, rhs: T) -> T;
+
+    /// Reduces `values`, starting from the identity element.
+    fn reduce<I: IntoIterator<Item = T>>(values: I) -> T {
+        values.into_iter().fold(Self::identity(), Self::combine)
+    }
+}
+
+/// Summation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Sum;
+
+/// Multiplication.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Product;
+
+/// Minimum.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Min;
+
+/// Maximum.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Max;
+
+/// Logical conjunction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct And;
+
+/// Logical disjunction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Or;
+
+impl<T: Default + Add<Output = T>> ReduceOp<T> for Sum {
+    #[inline(always)]
+    fn identity() -> T {
+        T::default()
+    }
+
+    #[inline(always)]
+    fn combine(lhs: T, rhs: T) -> T {
+        lhs + rhs
+    }
+}
+
+impl<T: From<f32> + Mul<Output = T>> ReduceOp<T> for Product {
+    #[inline(always)]
+    fn identity() -> T {
+        T::from(1.0)
+    }
+
+    #[inline(always)]
+    fn combine(lhs: T, rhs: T) -> T {
+        lhs * rhs
+    }
+}
+
+macro_rules! impl_min_max {
+    ($($ty:ty: $min:expr, $max:expr);* $(;)?) => {
+        $(
+            impl ReduceOp<$ty> for Min {
+                #[inline(always)]
+                fn identity() -> $ty {
+                    $max
+                }
+
+                #[inline(always)]
+                fn combine(lhs: $ty, rhs: $ty) -> $ty {
+                    lhs.min(rhs)
+                }
+            }
+
+            impl ReduceOp<$ty> for Max {
+                #[inline(always)]
+                fn identity() -> $ty {
+                    $min
+                }
+
+                #[inline(always)]
+                fn combine(lhs: $ty, rhs: $ty) -> $ty {
+                    lhs.max(rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_min_max!(
+    f32: f32::NEG_INFINITY, f32::INFINITY;
+    f64: f64::NEG_INFINITY, f64::INFINITY;
+    i32: i32::MIN, i32::MAX;
+    i64: i64::MIN, i64::MAX;
+    u32: u32::MIN, u32::MAX;
+    u64: u64::MIN, u64::MAX;
+    usize: usize::MIN, usize::MAX;
+);
+
+impl ReduceOp<bool> for And {
+    #[inline(always)]
+    fn identity() -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn combine(lhs: bool, rhs: bool) -> bool {
+        lhs && rhs
+    }
+}
+
+impl ReduceOp<bool> for Or {
+    #[inline(always)]
+    fn identity() -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn combine(lhs: bool, rhs: bool) -> bool {
+        lhs || rhs
+    }
+}
+
+/// A trait for objects which reduce values with `Op` and send the result to a `SyncReduceReceiver`.
+pub trait SyncReduceSender<T, Op> {
     /// The type associated with an error returned by the implementor.
     type Error;
 
-    /// Sends `value` to the adder.
+    /// Sends `value` to the reducer.
     fn send(&mut self, value: T) -> Result<(), Self::Error>;
 
-    /// Sends an empty message to the adder.
+    /// Sends an empty message to the reducer.
     fn send_empty(&mut self) -> Result<(), Self::Error>;
+
+    /// Sends every value in `values` to the reducer, one reduction per value.
+    ///
+    /// Equivalent to calling [`send`] for each value in order, but implementors
+    /// may override it to synchronize once for the whole batch.
+    ///
+    /// [`send`]: SyncReduceSender::send
+    fn send_all(&mut self, values: &[T]) -> Result<(), Self::Error>
+    where
+        T: Clone,
+    {
+        for value in values {
+            self.send(value.clone())?;
+        }
+        Ok(())
+    }
 }
 
-/// A trait for objects which recieve the sum calculated by `SyncAddSender`s.
-pub trait SyncAddReciever<T> {
+/// A trait for objects which receive the result of a reduction calculated by `SyncReduceSender`s.
+pub trait SyncReduceReceiver<T, Op> {
     /// The type associated with an error returned by the implementor.
     type Error;
 
-    /// Recieves the sum of all non-empty messages.
-    fn recieve_sum(&mut self) -> Result<Option<T>, Self::Error>;
+    /// Receives the reduction of all non-empty messages.
+    fn receive(&mut self) -> Result<Option<T>, Self::Error>;
+
+    /// Receives the results of the next `n` reductions.
+    ///
+    /// Equivalent to calling [`receive`] `n` times, but implementors
+    /// may override it to synchronize once for the whole batch.
+    ///
+    /// [`receive`]: SyncReduceReceiver::receive
+    fn receive_all(&mut self, n: usize) -> Result<Vec<Option<T>>, Self::Error> {
+        (0..n).map(|_| self.receive()).collect()
+    }
 }
 
-/// A trait for objects which multiply values and send the product to a `SyncAddReciever`.
-pub trait SyncMulSender<T> {
+/// A handle to a reduction which may not have completed yet.
+pub trait PendingReduction<T> {
     /// The type associated with an error returned by the implementor.
     type Error;
 
-    /// Sends `value` to the multiplier.
-    fn send(&mut self, value: T) -> Result<(), Self::Error>;
+    /// Checks whether the reduction has completed without blocking.
+    ///
+    /// Once `Poll::Ready` is returned, the handle is exhausted and
+    /// must not be polled again.
+    fn poll(&mut self) -> Result<Poll<Option<T>>, Self::Error>;
 
-    /// Sends an empty message to the multiplier.
-    fn send_empty(&mut self) -> Result<(), Self::Error>;
+    /// Blocks until the reduction completes.
+    fn wait(self) -> Result<Option<T>, Self::Error>;
+}
+
+/// A trait for `SyncReduceReceiver`s that can hand out the result of a reduction
+/// before it is complete.
+///
+/// This allows the receiving thread to overlap communication with its own work.
+pub trait DeferredSyncReduceReceiver<T, Op>: SyncReduceReceiver<T, Op> {
+    /// The handle to an incomplete reduction.
+    type Pending<'a>: PendingReduction<T, Error = Self::Error>
+    where
+        Self: 'a;
+
+    /// Starts receiving the reduction of all non-empty messages without blocking.
+    fn receive_deferred(&mut self) -> Self::Pending<'_>;
+}
+
+/// A trait for objects which add up values and send the sum to a `SyncAddReciever`.
+///
+/// Implemented for every `SyncReduceSender<T, Sum>`.
+pub trait SyncAddSender<T>: SyncReduceSender<T, Sum> {}
+
+impl<T, S> SyncAddSender<T> for S where S: SyncReduceSender<T, Sum> + ?Sized {}
+
+/// A trait for objects which receive the sum calculated by `SyncAddSender`s.
+///
+/// Implemented for every `SyncReduceReceiver<T, Sum>`.
+pub trait SyncAddReciever<T>: SyncReduceReceiver<T, Sum> {
+    /// Receives the sum of all non-empty messages.
+    #[inline(always)]
+    fn receive_sum(&mut self) -> Result<Option<T>, Self::Error> {
+        self.receive()
+    }
+
+    /// Receives the sums of the next `n` batched values.
+    #[inline(always)]
+    fn receive_sums(&mut self, n: usize) -> Result<Vec<Option<T>>, Self::Error> {
+        self.receive_all(n)
+    }
+
+    /// Recieves the sum of all non-empty messages.
+    #[deprecated(note = "use `receive_sum` instead")]
+    #[inline(always)]
+    fn recieve_sum(&mut self) -> Result<Option<T>, Self::Error> {
+        self.receive()
+    }
+
+    /// Starts receiving the sum of all non-empty messages without blocking.
+    #[inline(always)]
+    fn receive_sum_async(&mut self) -> <Self as DeferredSyncReduceReceiver<T, Sum>>::Pending<'_>
+    where
+        Self: DeferredSyncReduceReceiver<T, Sum>,
+    {
+        self.receive_deferred()
+    }
+}
+
+impl<T, R> SyncAddReciever<T> for R where R: SyncReduceReceiver<T, Sum> + ?Sized {}
+
+/// A trait for objects which multiply values and send the product to a `SyncMulReciever`.
+///
+/// Implemented for every `SyncReduceSender<T, Product>`.
+pub trait SyncMulSender<T>: SyncReduceSender<T, Product> {}
+
+impl<T, S> SyncMulSender<T> for S where S: SyncReduceSender<T, Product> + ?Sized {}
+
+/// A trait for objects which receive the product calculated by `SyncMulSender`s.
+///
+/// Implemented for every `SyncReduceReceiver<T, Product>`.
+pub trait SyncMulReciever<T>: SyncReduceReceiver<T, Product> {
+    /// Receives the product of all non-empty messages.
+    #[inline(always)]
+    fn receive_prod(&mut self) -> Result<Option<T>, Self::Error> {
+        self.receive()
+    }
+
+    /// Receives the products of the next `n` batched values.
+    #[inline(always)]
+    fn receive_prods(&mut self, n: usize) -> Result<Vec<Option<T>>, Self::Error> {
+        self.receive_all(n)
+    }
+
+    /// Recieves the product of all non-empty messages.
+    #[deprecated(note = "use `receive_prod` instead")]
+    #[inline(always)]
+    fn recieve_prod(&mut self) -> Result<Option<T>, Self::Error> {
+        self.receive()
+    }
+
+    /// Starts receiving the product of all non-empty messages without blocking.
+    #[inline(always)]
+    fn receive_prod_async(
+        &mut self,
+    ) -> <Self as DeferredSyncReduceReceiver<T, Product>>::Pending<'_>
+    where
+        Self: DeferredSyncReduceReceiver<T, Product>,
+    {
+        self.receive_deferred()
+    }
 }
 
-/// A trait for objects which recieve the product calculated by `SyncAddSender`s.
-pub trait SyncMulReciever<T> {
+impl<T, R> SyncMulReciever<T> for R where R: SyncReduceReceiver<T, Product> + ?Sized {}
+
+/// A trait for objects which send every value to all `SyncBroadcastReceiver`s,
+/// e.g. parameters updated on one thread and used by the threads of every image.
+pub trait SyncBroadcastSender<T> {
     /// The type associated with an error returned by the implementor.
     type Error;
 
-    /// Recieves the product of all non-empty messages.
-    fn recieve_prod(&mut self) -> Result<Option<T>, Self::Error>;
+    /// Sends `value` to every receiver.
+    fn broadcast(&mut self, value: T) -> Result<(), Self::Error>;
+}
+
+/// A trait for objects which receive the values sent by a `SyncBroadcastSender`.
+pub trait SyncBroadcastReceiver<T> {
+    /// The type associated with an error returned by the implementor.
+    type Error;
+
+    /// Blocks until the next value is sent and receives it.
+    fn receive_broadcast(&mut self) -> Result<T, Self::Error>;
+
+    /// Receives the next value if it has already been sent.
+    fn try_receive_broadcast(&mut self) -> Result<Option<T>, Self::Error>;
 }
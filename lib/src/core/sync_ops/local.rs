@@ -0,0 +1,275 @@
+//! In-process reductions and broadcasts between threads built on channels.
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    task::Poll,
+};
+
+use super::{
+    DeferredSyncReduceReceiver, PendingReduction, ReduceOp, SyncBroadcastReceiver,
+    SyncBroadcastSender, SyncReduceReceiver, SyncReduceSender,
+};
+
+/// An error representing a disconnected participant of a reduction or a broadcast.
+#[derive(Clone, Copy, Debug)]
+pub struct DisconnectedError;
+
+impl Display for DisconnectedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "a participant of the communication has disconnected")
+    }
+}
+
+impl Error for DisconnectedError {}
+
+/// Creates `senders` senders and a single receiver which reduce values with `Op`.
+///
+/// Every round of the reduction consists of exactly one value from every sender.
+/// Values sent in a batch are delivered in a single message.
+pub fn reduction<T, Op>(senders: usize) -> (Vec<LocalSender<T, Op>>, LocalReceiver<T, Op>) {
+    let (senders, channels) = (0..senders)
+        .map(|_| {
+            let (sender, receiver) = mpsc::channel();
+            (
+                LocalSender {
+                    channel: sender,
+                    phantom: PhantomData,
+                },
+                Channel {
+                    receiver,
+                    buffer: VecDeque::new(),
+                },
+            )
+        })
+        .unzip();
+    (
+        senders,
+        LocalReceiver {
+            channels,
+            phantom: PhantomData,
+        },
+    )
+}
+
+#[inline(always)]
+fn accumulate<T, Op: ReduceOp<T>>(accum: Option<T>, message: Option<T>) -> Option<T> {
+    match (accum, message) {
+        (Some(accum), Some(value)) => Some(Op::combine(accum, value)),
+        (accum, None) => accum,
+        (None, value) => value,
+    }
+}
+
+/// The sending end of an in-process reduction.
+#[derive(Debug)]
+pub struct LocalSender<T, Op> {
+    channel: Sender<Vec<Option<T>>>,
+    phantom: PhantomData<fn() -> Op>,
+}
+
+impl<T, Op> SyncReduceSender<T, Op> for LocalSender<T, Op> {
+    type Error = DisconnectedError;
+
+    fn send(&mut self, value: T) -> Result<(), Self::Error> {
+        self.channel
+            .send(vec![Some(value)])
+            .map_err(|_| DisconnectedError)
+    }
+
+    fn send_empty(&mut self) -> Result<(), Self::Error> {
+        self.channel.send(vec![None]).map_err(|_| DisconnectedError)
+    }
+
+    fn send_all(&mut self, values: &[T]) -> Result<(), Self::Error>
+    where
+        T: Clone,
+    {
+        self.channel
+            .send(values.iter().cloned().map(Some).collect())
+            .map_err(|_| DisconnectedError)
+    }
+}
+
+/// The receiving end of a single sender, with values of a batch not yet consumed.
+#[derive(Debug)]
+struct Channel<T> {
+    receiver: Receiver<Vec<Option<T>>>,
+    buffer: VecDeque<Option<T>>,
+}
+
+impl<T> Channel<T> {
+    fn next(&mut self) -> Result<Option<T>, DisconnectedError> {
+        loop {
+            if let Some(value) = self.buffer.pop_front() {
+                return Ok(value);
+            }
+            let batch = self.receiver.recv().map_err(|_| DisconnectedError)?;
+            self.buffer.extend(batch);
+        }
+    }
+
+    fn try_next(&mut self) -> Result<Poll<Option<T>>, DisconnectedError> {
+        loop {
+            if let Some(value) = self.buffer.pop_front() {
+                return Ok(Poll::Ready(value));
+            }
+            match self.receiver.try_recv() {
+                Ok(batch) => self.buffer.extend(batch),
+                Err(TryRecvError::Empty) => return Ok(Poll::Pending),
+                Err(TryRecvError::Disconnected) => return Err(DisconnectedError),
+            }
+        }
+    }
+}
+
+/// The receiving end of an in-process reduction.
+#[derive(Debug)]
+pub struct LocalReceiver<T, Op> {
+    channels: Vec<Channel<T>>,
+    phantom: PhantomData<fn() -> Op>,
+}
+
+impl<T, Op: ReduceOp<T>> SyncReduceReceiver<T, Op> for LocalReceiver<T, Op> {
+    type Error = DisconnectedError;
+
+    fn receive(&mut self) -> Result<Option<T>, Self::Error> {
+        let mut accum = None;
+        for channel in &mut self.channels {
+            accum = accumulate::<T, Op>(accum, channel.next()?);
+        }
+        Ok(accum)
+    }
+
+    fn receive_all(&mut self, n: usize) -> Result<Vec<Option<T>>, Self::Error> {
+        let mut accums: Vec<Option<T>> = (0..n).map(|_| None).collect();
+        for channel in &mut self.channels {
+            for accum in &mut accums {
+                *accum = accumulate::<T, Op>(accum.take(), channel.next()?);
+            }
+        }
+        Ok(accums)
+    }
+}
+
+impl<T, Op: ReduceOp<T>> DeferredSyncReduceReceiver<T, Op> for LocalReceiver<T, Op> {
+    type Pending<'a>
+        = LocalPending<'a, T, Op>
+    where
+        Self: 'a;
+
+    fn receive_deferred(&mut self) -> Self::Pending<'_> {
+        LocalPending {
+            receiver: self,
+            next: 0,
+            accum: None,
+        }
+    }
+}
+
+/// A handle to an in-process reduction which may not have completed yet.
+///
+/// Dropping the handle before the reduction completes blocks until
+/// the remaining values of the round arrive and discards them,
+/// so that the next round starts in sync.
+#[derive(Debug)]
+pub struct LocalPending<'a, T, Op> {
+    receiver: &'a mut LocalReceiver<T, Op>,
+    next: usize,
+    accum: Option<T>,
+}
+
+impl<'a, T, Op: ReduceOp<T>> PendingReduction<T> for LocalPending<'a, T, Op> {
+    type Error = DisconnectedError;
+
+    fn poll(&mut self) -> Result<Poll<Option<T>>, Self::Error> {
+        while let Some(channel) = self.receiver.channels.get_mut(self.next) {
+            match channel.try_next()? {
+                Poll::Ready(value) => {
+                    self.next += 1;
+                    self.accum = accumulate::<T, Op>(self.accum.take(), value);
+                }
+                Poll::Pending => return Ok(Poll::Pending),
+            }
+        }
+        Ok(Poll::Ready(self.accum.take()))
+    }
+
+    fn wait(mut self) -> Result<Option<T>, Self::Error> {
+        while let Some(channel) = self.receiver.channels.get_mut(self.next) {
+            let value = channel.next()?;
+            self.next += 1;
+            self.accum = accumulate::<T, Op>(self.accum.take(), value);
+        }
+        Ok(self.accum.take())
+    }
+}
+
+impl<'a, T, Op> Drop for LocalPending<'a, T, Op> {
+    fn drop(&mut self) {
+        while let Some(channel) = self.receiver.channels.get_mut(self.next) {
+            if channel.next().is_err() {
+                break;
+            }
+            self.next += 1;
+        }
+    }
+}
+
+/// Creates a sender and `receivers` receivers of values broadcast between threads.
+///
+/// Every receiver gets a clone of every value, in the order they were sent.
+pub fn broadcast<T>(receivers: usize) -> (LocalBroadcastSender<T>, Vec<LocalBroadcastReceiver<T>>) {
+    let (channels, receivers) = (0..receivers)
+        .map(|_| {
+            let (sender, receiver) = mpsc::channel();
+            (sender, LocalBroadcastReceiver { channel: receiver })
+        })
+        .unzip();
+    (LocalBroadcastSender { channels }, receivers)
+}
+
+/// The sending end of an in-process broadcast.
+#[derive(Debug)]
+pub struct LocalBroadcastSender<T> {
+    channels: Vec<Sender<T>>,
+}
+
+impl<T: Clone> SyncBroadcastSender<T> for LocalBroadcastSender<T> {
+    type Error = DisconnectedError;
+
+    fn broadcast(&mut self, value: T) -> Result<(), Self::Error> {
+        let Some((last, channels)) = self.channels.split_last() else {
+            return Ok(());
+        };
+        for channel in channels {
+            channel.send(value.clone()).map_err(|_| DisconnectedError)?;
+        }
+        last.send(value).map_err(|_| DisconnectedError)
+    }
+}
+
+/// The receiving end of an in-process broadcast.
+#[derive(Debug)]
+pub struct LocalBroadcastReceiver<T> {
+    channel: Receiver<T>,
+}
+
+impl<T> SyncBroadcastReceiver<T> for LocalBroadcastReceiver<T> {
+    type Error = DisconnectedError;
+
+    fn receive_broadcast(&mut self) -> Result<T, Self::Error> {
+        self.channel.recv().map_err(|_| DisconnectedError)
+    }
+
+    fn try_receive_broadcast(&mut self) -> Result<Option<T>, Self::Error> {
+        match self.channel.try_recv() {
+            Ok(value) => Ok(Some(value)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(DisconnectedError),
+        }
+    }
+}
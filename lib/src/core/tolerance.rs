@@ -0,0 +1,59 @@
+//! Convergence criteria shared by iterative solvers.
+
+use super::error::NotConvergedError;
+use std::ops::{Add, Mul};
+
+/// When an iterative solver, such as a self-consistent field or a constraint solver,
+/// is considered converged and when it gives up.
+///
+/// An iteration has converged once its residual is at most
+/// `absolute + relative * scale`, where `scale` is the magnitude of the solution.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TolerancePolicy<T> {
+    /// The largest tolerated residual regardless of the scale of the solution.
+    pub absolute: T,
+    /// The largest tolerated residual per unit of the scale of the solution.
+    pub relative: T,
+    /// The number of iterations after which the solver gives up.
+    pub max_iterations: usize,
+}
+
+impl<T: From<f32>> TolerancePolicy<T> {
+    /// Constructs a policy with only an absolute tolerance.
+    pub fn absolute(absolute: T, max_iterations: usize) -> Self {
+        Self {
+            absolute,
+            relative: T::from(0.0),
+            max_iterations,
+        }
+    }
+
+    /// Constructs a policy with only a relative tolerance.
+    pub fn relative(relative: T, max_iterations: usize) -> Self {
+        Self {
+            absolute: T::from(0.0),
+            relative,
+            max_iterations,
+        }
+    }
+}
+
+impl<T> TolerancePolicy<T>
+where
+    T: Clone + PartialOrd + Add<Output = T> + Mul<Output = T>,
+{
+    /// Returns the largest tolerated residual of a solution of magnitude `scale`.
+    pub fn tolerance(&self, scale: T) -> T {
+        self.absolute.clone() + self.relative.clone() * scale
+    }
+
+    /// Returns whether `residual` is tolerated for a solution of magnitude `scale`.
+    pub fn is_converged(&self, residual: T, scale: T) -> bool {
+        residual <= self.tolerance(scale)
+    }
+
+    /// Returns the error of a solver which has exhausted its iterations with `residual`.
+    pub fn not_converged(&self, residual: T) -> NotConvergedError<T> {
+        NotConvergedError::new(self.max_iterations, residual)
+    }
+}
@@ -0,0 +1,37 @@
+//! Types and traits meant to distinguish between ring polymers
+//! and open chains of images.
+
+/// A trait for markers of the way the images of an atom are connected.
+pub trait Topology {
+    /// Whether the last image is coupled to the first one.
+    const IS_RING: bool;
+
+    /// Returns whether the image at `image` out of `images` is coupled to its predecessor.
+    #[inline(always)]
+    fn is_coupled_to_prev(image: usize, images: usize) -> bool {
+        image < images && (Self::IS_RING || image > 0)
+    }
+
+    /// Returns whether the image at `image` out of `images` is coupled to its successor.
+    #[inline(always)]
+    fn is_coupled_to_next(image: usize, images: usize) -> bool {
+        image < images && (Self::IS_RING || image + 1 < images)
+    }
+}
+
+/// The images of every atom form a closed ring.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RingTopology {}
+
+impl Topology for RingTopology {
+    const IS_RING: bool = true;
+}
+
+/// The images of every atom form an open chain,
+/// so the last image is not coupled to the first one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpenTopology {}
+
+impl Topology for OpenTopology {
+    const IS_RING: bool = false;
+}
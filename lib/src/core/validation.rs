@@ -0,0 +1,400 @@
+//! Validation of the assembly of a simulation before it runs.
+//!
+//! Each check adds diagnostics to a [`Validator`] instead of stopping at the first problem,
+//! so a misconfigured simulation reports everything that must be fixed at once,
+//! with a hint on how to fix it, instead of panicking mid-run.
+
+use super::{atoms::AtomTypeInfo, partition::Treatment, stat::Stat};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::Range,
+};
+
+/// How serious a diagnostic is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The simulation can run, but probably not as intended.
+    Warning,
+    /// The simulation cannot run.
+    Error,
+}
+
+/// A problem found in the assembly of a simulation.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    /// How serious the problem is.
+    pub severity: Severity,
+    /// The part of the assembly at fault, e.g. "group #3".
+    pub subject: String,
+    /// What is wrong.
+    pub message: String,
+    /// How to fix it.
+    pub hint: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}: {}: {} (hint: {})",
+            severity, self.subject, self.message, self.hint
+        )
+    }
+}
+
+/// An error listing every diagnostic of an assembly which cannot run.
+#[derive(Clone, Debug)]
+pub struct ValidationError {
+    /// The diagnostics, errors first.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let errors = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .count();
+        write!(f, "the simulation is misconfigured ({} errors)", errors)?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ValidationError {}
+
+/// The statistics a component of the simulation can handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SupportedStats {
+    /// Whether groups of distinguishable atoms are supported.
+    pub distinguishable: bool,
+    /// Whether groups of bosons are supported.
+    pub bosonic: bool,
+    /// Whether groups of fermions are supported.
+    pub fermionic: bool,
+}
+
+impl SupportedStats {
+    /// Every statistics is supported.
+    pub const ALL: Self = Self {
+        distinguishable: true,
+        bosonic: true,
+        fermionic: true,
+    };
+    /// Only distinguishable atoms are supported.
+    pub const DISTINGUISHABLE: Self = Self {
+        distinguishable: true,
+        bosonic: false,
+        fermionic: false,
+    };
+
+    /// Returns whether `statistic` is supported.
+    pub fn supports<D, B, F>(&self, statistic: &Stat<D, B, F>) -> bool {
+        match statistic {
+            Stat::Distinguishable(_) => self.distinguishable,
+            Stat::Bosonic(_) => self.bosonic,
+            Stat::Fermionic(_) => self.fermionic,
+        }
+    }
+}
+
+fn stat_name<D, B, F>(statistic: &Stat<D, B, F>) -> &'static str {
+    match statistic {
+        Stat::Distinguishable(_) => "distinguishable",
+        Stat::Bosonic(_) => "bosonic",
+        Stat::Fermionic(_) => "fermionic",
+    }
+}
+
+/// A collector of the diagnostics of the checks run on an assembly.
+#[derive(Clone, Debug, Default)]
+pub struct Validator {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator {
+    /// Constructs a validator without diagnostics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a diagnostic.
+    pub fn report(&mut self, severity: Severity, subject: &str, message: String, hint: &str) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            subject: subject.to_owned(),
+            message,
+            hint: hint.to_owned(),
+        });
+    }
+
+    /// Returns the diagnostics collected so far.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns whether an error has been found.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// Checks that the number of images can hold a ring polymer.
+    pub fn check_images<T>(&mut self, images: usize, atom_types: &[AtomTypeInfo<T>]) {
+        if images == 0 {
+            self.report(
+                Severity::Error,
+                "images",
+                "the simulation has no images".to_owned(),
+                "use at least one image",
+            );
+        } else if images == 1
+            && atom_types
+                .iter()
+                .any(|atom_type| atom_type.treatment().is_quantum())
+        {
+            self.report(
+                Severity::Warning,
+                "images",
+                "quantum groups with a single image are classical".to_owned(),
+                "increase the number of images or mark the types as classical",
+            );
+        }
+    }
+
+    /// Checks that the spans of the groups in the slice of `atoms` atoms
+    /// do not overlap and cover the whole slice.
+    pub fn check_group_spans(&mut self, atoms: usize, spans: &[Range<usize>]) {
+        let mut order: Vec<_> = (0..spans.len()).collect();
+        order.sort_by_key(|&group| (spans[group].start, spans[group].end));
+        let mut covered = 0;
+        for group in order {
+            let span = &spans[group];
+            let subject = format!("group #{}", group);
+            if span.is_empty() {
+                self.report(
+                    Severity::Warning,
+                    &subject,
+                    format!("the span {:?} holds no atoms", span),
+                    "remove the group or give it atoms",
+                );
+                continue;
+            }
+            if span.end > atoms {
+                self.report(
+                    Severity::Error,
+                    &subject,
+                    format!("the span {:?} exceeds the {} atoms", span, atoms),
+                    "shrink the span or add the missing atoms",
+                );
+            }
+            if span.start < covered {
+                self.report(
+                    Severity::Error,
+                    &subject,
+                    format!(
+                        "the span {:?} overlaps the atoms up to #{}",
+                        span,
+                        covered - 1
+                    ),
+                    "make every atom belong to exactly one group",
+                );
+            } else if span.start > covered {
+                self.report(
+                    Severity::Error,
+                    &subject,
+                    format!(
+                        "the atoms {:?} before the span {:?} belong to no group",
+                        covered..span.start,
+                        span
+                    ),
+                    "extend a group or add one over the uncovered atoms",
+                );
+            }
+            covered = covered.max(span.end);
+        }
+        if covered < atoms {
+            self.report(
+                Severity::Error,
+                "groups",
+                format!("the atoms {:?} belong to no group", covered..atoms),
+                "extend a group or add one over the uncovered atoms",
+            );
+        }
+    }
+
+    /// Checks that the spans agree with the groups the atom types are split into.
+    pub fn check_group_sizes<T>(&mut self, atom_types: &[AtomTypeInfo<T>], spans: &[Range<usize>]) {
+        let groups: usize = atom_types
+            .iter()
+            .map(|atom_type| atom_type.groups.groups())
+            .sum();
+        if groups != spans.len() {
+            self.report(
+                Severity::Error,
+                "groups",
+                format!(
+                    "the atom types are split into {} groups but {} spans are given",
+                    groups,
+                    spans.len()
+                ),
+                "give one span per group of every atom type, in order",
+            );
+            return;
+        }
+        let mut spans = spans.iter();
+        for atom_type in atom_types {
+            let atoms: usize = spans
+                .by_ref()
+                .take(atom_type.groups.groups())
+                .map(|span| span.len())
+                .sum();
+            if atoms != atom_type.groups.total() {
+                self.report(
+                    Severity::Error,
+                    &format!("atom type '{}'", atom_type.label),
+                    format!(
+                        "the type has {} atoms but its groups span {}",
+                        atom_type.groups.total(),
+                        atoms
+                    ),
+                    "resize the spans of the groups of this type",
+                );
+            }
+        }
+    }
+
+    /// Checks that every one of `groups` groups is acted on by a potential,
+    /// given the groups each potential of kind `kind` is mapped to.
+    pub fn check_potential_mapping(&mut self, kind: &str, groups: usize, mapped: &[usize]) {
+        let mut counts = vec![0usize; groups];
+        for &group in mapped {
+            match counts.get_mut(group) {
+                Some(count) => *count += 1,
+                None => self.report(
+                    Severity::Error,
+                    kind,
+                    format!(
+                        "a potential is mapped to group #{} of only {}",
+                        group, groups
+                    ),
+                    "map the potential to an existing group",
+                ),
+            }
+        }
+        for (group, count) in counts.into_iter().enumerate() {
+            match count {
+                0 => self.report(
+                    Severity::Error,
+                    &format!("group #{}", group),
+                    format!("no {} is mapped to the group", kind),
+                    "map a potential to the group, e.g. a zero potential if it is free",
+                ),
+                1 => {}
+                count => self.report(
+                    Severity::Warning,
+                    &format!("group #{}", group),
+                    format!("{} {}s are mapped to the group", count, kind),
+                    "combine them into a single potential unless they are meant to add up",
+                ),
+            }
+        }
+    }
+
+    /// Checks that the exchange potential of every group matches the statistics of its atoms,
+    /// given `exchange[group]`, the statistics the exchange potential of the group is built for.
+    pub fn check_exchange<T, D, B, F>(
+        &mut self,
+        atom_types: &[AtomTypeInfo<T>],
+        exchange: &[Stat<D, B, F>],
+    ) {
+        let mut exchange = exchange.iter().enumerate();
+        for atom_type in atom_types {
+            let subject = format!("atom type '{}'", atom_type.label);
+            if atom_type.treatment() == Treatment::Classical
+                && !matches!(atom_type.statistic, Stat::Distinguishable(_))
+            {
+                self.report(
+                    Severity::Warning,
+                    &subject,
+                    format!(
+                        "the {} atoms are classical, so they never exchange",
+                        stat_name(&atom_type.statistic)
+                    ),
+                    "quantize the type or make it distinguishable",
+                );
+            }
+            for _ in 0..atom_type.groups.groups() {
+                let Some((group, potential)) = exchange.next() else {
+                    self.report(
+                        Severity::Error,
+                        &subject,
+                        "some groups have no exchange potential".to_owned(),
+                        "give one exchange potential per group",
+                    );
+                    return;
+                };
+                if stat_name(potential) != stat_name(&atom_type.statistic) {
+                    self.report(
+                        Severity::Error,
+                        &format!("group #{}", group),
+                        format!(
+                            "the atoms are {} but the exchange potential is {}",
+                            stat_name(&atom_type.statistic),
+                            stat_name(potential)
+                        ),
+                        "use an exchange potential of the statistics of the atom type",
+                    );
+                }
+            }
+        }
+    }
+
+    /// Checks that the component `name`, e.g. the thermostat or the propagator,
+    /// supports the statistics of every atom type.
+    pub fn check_component<T>(
+        &mut self,
+        name: &str,
+        supported: SupportedStats,
+        atom_types: &[AtomTypeInfo<T>],
+    ) {
+        for atom_type in atom_types {
+            if !supported.supports(&atom_type.statistic) {
+                self.report(
+                    Severity::Error,
+                    name,
+                    format!(
+                        "{} atoms of type '{}' are not supported",
+                        stat_name(&atom_type.statistic),
+                        atom_type.label
+                    ),
+                    "choose a component supporting these statistics",
+                );
+            }
+        }
+    }
+
+    /// Finishes the validation.
+    ///
+    /// Returns the warnings if the simulation can run, or all the diagnostics otherwise.
+    pub fn finish(mut self) -> Result<Vec<Diagnostic>, ValidationError> {
+        self.diagnostics
+            .sort_by_key(|diagnostic| std::cmp::Reverse(diagnostic.severity));
+        if self.has_errors() {
+            Err(ValidationError {
+                diagnostics: self.diagnostics,
+            })
+        } else {
+            Ok(self.diagnostics)
+        }
+    }
+}
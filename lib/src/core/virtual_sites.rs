@@ -0,0 +1,251 @@
+//! Massless interaction sites whose positions derive from parent atoms,
+//! such as the M site of four-site water models.
+//!
+//! A virtual site is an atom of a group that carries interactions but no mass.
+//! Its position is reconstructed from its parents whenever they move and the force
+//! acting on it is projected back onto them before the group is propagated,
+//! so only the parents are ever displaced by the propagator.
+
+use super::error::InvalidIndexError;
+use std::{
+    convert::Infallible,
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{AddAssign, Mul},
+};
+
+/// An error in the definition of a virtual site.
+#[derive(Clone, Copy, Debug)]
+pub enum VirtualSiteError {
+    /// The site or one of its parents is not in the group.
+    InvalidIndex(InvalidIndexError),
+    /// The site has no parents.
+    NoParents {
+        /// The index of the site.
+        site: usize,
+    },
+    /// The site is its own parent or another virtual site is one of its parents.
+    VirtualParent {
+        /// The index of the site.
+        site: usize,
+        /// The index of the parent.
+        parent: usize,
+    },
+    /// The site has already been defined or is the parent of another site.
+    AlreadyUsed {
+        /// The index of the site.
+        site: usize,
+    },
+}
+
+impl From<Infallible> for VirtualSiteError {
+    fn from(value: Infallible) -> Self {
+        match value {}
+    }
+}
+
+impl From<InvalidIndexError> for VirtualSiteError {
+    fn from(value: InvalidIndexError) -> Self {
+        Self::InvalidIndex(value)
+    }
+}
+
+impl Display for VirtualSiteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::InvalidIndex(err) => write!(f, "invalid virtual site: {}", err),
+            Self::NoParents { site } => write!(f, "virtual site #{} has no parents", site),
+            Self::VirtualParent { site, parent } => write!(
+                f,
+                "virtual site #{} has atom #{} as a parent, which is virtual",
+                site, parent
+            ),
+            Self::AlreadyUsed { site } => write!(
+                f,
+                "atom #{} is already a virtual site or the parent of one",
+                site
+            ),
+        }
+    }
+}
+
+impl Error for VirtualSiteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidIndex(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A virtual site placed at a weighted sum of the positions of its parents.
+///
+/// The weights usually add up to one, which makes the site move rigidly with its parents,
+/// e.g. the M site of TIP4P is `γ r_O + (1 - γ) / 2 (r_H1 + r_H2)`.
+#[derive(Clone, Debug)]
+pub struct VirtualSite<T> {
+    /// The index of the site in the group.
+    pub site: usize,
+    /// The indices of the parents in the group along with their weights.
+    pub parents: Box<[(usize, T)]>,
+}
+
+/// The virtual sites of a group.
+#[derive(Clone, Debug)]
+pub struct VirtualSites<T> {
+    sites: Vec<VirtualSite<T>>,
+    /// Whether each atom of the group is a virtual site.
+    mask: Box<[bool]>,
+    /// Whether each atom of the group is the parent of a virtual site.
+    parents: Box<[bool]>,
+}
+
+impl<T> VirtualSites<T> {
+    /// Constructs the definitions of a group of `group_size` atoms without virtual sites.
+    pub fn new(group_size: usize) -> Self {
+        Self {
+            sites: Vec::new(),
+            mask: vec![false; group_size].into_boxed_slice(),
+            parents: vec![false; group_size].into_boxed_slice(),
+        }
+    }
+
+    /// Defines the atom at `site` as a virtual site placed at the weighted sum of `parents`.
+    ///
+    /// Sites cannot be nested, so neither the site nor its parents
+    /// may be virtual sites, and the site may not be the parent of another one.
+    pub fn define(
+        &mut self,
+        site: usize,
+        parents: impl Into<Box<[(usize, T)]>>,
+    ) -> Result<(), VirtualSiteError> {
+        let parents = parents.into();
+        let group_size = self.mask.len();
+        if site >= group_size {
+            return Err(InvalidIndexError::new(site, group_size).into());
+        }
+        if self.mask[site] || self.parents[site] {
+            return Err(VirtualSiteError::AlreadyUsed { site });
+        }
+        if parents.is_empty() {
+            return Err(VirtualSiteError::NoParents { site });
+        }
+        for &(parent, _) in parents.iter() {
+            if parent >= group_size {
+                return Err(InvalidIndexError::new(parent, group_size).into());
+            }
+            if parent == site || self.mask[parent] {
+                return Err(VirtualSiteError::VirtualParent { site, parent });
+            }
+        }
+        self.mask[site] = true;
+        for &(parent, _) in parents.iter() {
+            self.parents[parent] = true;
+        }
+        self.sites.push(VirtualSite { site, parents });
+        Ok(())
+    }
+
+    /// Returns the definitions of the virtual sites.
+    pub fn sites(&self) -> &[VirtualSite<T>] {
+        &self.sites
+    }
+
+    /// Returns whether the atom at `index` is a virtual site.
+    ///
+    /// Atoms outside of the group are never virtual.
+    pub fn is_virtual(&self, index: usize) -> bool {
+        self.mask.get(index).copied().unwrap_or(false)
+    }
+
+    /// Returns the number of atoms in the group.
+    pub fn len(&self) -> usize {
+        self.mask.len()
+    }
+
+    /// Returns whether the group is empty.
+    pub fn is_empty(&self) -> bool {
+        self.mask.is_empty()
+    }
+
+    /// Returns the number of degrees of freedom of the atoms in the group
+    /// which are not virtual, where each atom has `dimension` degrees of freedom.
+    pub fn degrees_of_freedom(&self, dimension: usize) -> usize {
+        (self.mask.len() - self.sites.len()) * dimension
+    }
+}
+
+impl<T: Clone> VirtualSites<T> {
+    /// Places every virtual site of the group at the weighted sum of its parents.
+    ///
+    /// Called whenever the positions of the group have been updated,
+    /// before the potentials are evaluated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group_positions` does not hold the whole group.
+    pub fn reconstruct<V>(&self, group_positions: &mut [V])
+    where
+        V: Clone + AddAssign + Mul<T, Output = V>,
+    {
+        assert_eq!(group_positions.len(), self.mask.len());
+        for VirtualSite { site, parents } in &self.sites {
+            let mut parents = parents.iter();
+            let Some((first, weight)) = parents.next() else {
+                continue;
+            };
+            let mut position = group_positions[*first].clone() * weight.clone();
+            for (parent, weight) in parents {
+                position += group_positions[*parent].clone() * weight.clone();
+            }
+            group_positions[*site] = position;
+        }
+    }
+
+    /// Moves the force acting on every virtual site onto its parents,
+    /// each receiving the force times its weight, and zeroes the force on the site.
+    ///
+    /// The virial and the energy are unchanged, since the sites
+    /// are linear functions of their parents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `group_forces` does not hold the whole group.
+    pub fn project_forces<V>(&self, group_forces: &mut [V])
+    where
+        V: Clone + Default + AddAssign + Mul<T, Output = V>,
+    {
+        assert_eq!(group_forces.len(), self.mask.len());
+        for VirtualSite { site, parents } in &self.sites {
+            let force = std::mem::take(&mut group_forces[*site]);
+            for (parent, weight) in parents.iter() {
+                group_forces[*parent] += force.clone() * weight.clone();
+            }
+        }
+    }
+}
+
+/// A wrapper for physical potentials acting on a group with virtual sites.
+///
+/// The wrapped potential sees the virtual sites as regular atoms, and the forces
+/// acting on them are projected onto their parents before being returned.
+/// The positions of the sites must have been reconstructed with
+/// [`VirtualSites::reconstruct`] after the last update of the positions.
+pub struct WithVirtualSites<T, P: ?Sized> {
+    pub(crate) sites: VirtualSites<T>,
+    pub(crate) inner: P,
+}
+
+impl<T, P> WithVirtualSites<T, P> {
+    /// Wraps `inner` to act on a group with the virtual sites `sites`.
+    pub fn new(sites: VirtualSites<T>, inner: P) -> Self {
+        Self { sites, inner }
+    }
+}
+
+impl<T, P: ?Sized> WithVirtualSites<T, P> {
+    /// Returns the virtual sites of the group.
+    pub fn sites(&self) -> &VirtualSites<T> {
+        &self.sites
+    }
+}
@@ -0,0 +1,280 @@
+//! A channel for conditions which do not stop a simulation but may spoil its results.
+//!
+//! Subsystems report such conditions to a shared [`WarningSink`] instead of printing them,
+//! so that they can be retrieved programmatically while the simulation runs
+//! and listed in a single summary once it ends. The sink keeps only the latest warnings,
+//! counting the older ones it has dropped, so a condition raised at every step
+//! cannot exhaust the memory.
+
+use std::{
+    collections::VecDeque,
+    fmt::{Display, Formatter, Result as FmtResult},
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
+};
+
+/// How much a warning may affect the results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WarningLevel {
+    /// Worth knowing, but harmless, e.g. a fallback to a slower algorithm.
+    Info,
+    /// Likely to degrade the results, e.g. a poorly resolved thermostat.
+    Warning,
+    /// Likely to invalidate the results, e.g. interactions beyond the minimum image.
+    Severe,
+}
+
+impl WarningLevel {
+    const ALL: [Self; 3] = [Self::Info, Self::Warning, Self::Severe];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Severe => "severe",
+        }
+    }
+}
+
+/// A single non-fatal condition reported by a subsystem.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    /// How much the condition may affect the results.
+    pub level: WarningLevel,
+    /// The step at which the condition arose, or `None` during the setup.
+    pub step: Option<usize>,
+    /// The subsystem which reported the condition, e.g. "thermostat".
+    pub source: String,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}: {}", self.level.name(), self.source)?;
+        if let Some(step) = self.step {
+            write!(f, " at step {}", step)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+struct Buffer {
+    warnings: VecDeque<Warning>,
+    counts: [usize; 3],
+    dropped: usize,
+}
+
+/// A bounded, thread-safe buffer of the warnings of a simulation.
+pub struct WarningSink {
+    capacity: usize,
+    buffer: Mutex<Buffer>,
+}
+
+impl WarningSink {
+    /// The capacity of the sink returned by [`WarningSink::global`].
+    pub const DEFAULT_CAPACITY: usize = 256;
+
+    /// Constructs a sink keeping the latest `capacity` warnings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "the sink must hold at least a warning");
+        Self {
+            capacity,
+            buffer: Mutex::new(Buffer {
+                warnings: VecDeque::with_capacity(capacity),
+                counts: [0; 3],
+                dropped: 0,
+            }),
+        }
+    }
+
+    /// Returns the sink shared by the whole process, for subsystems which are not handed one.
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<WarningSink> = OnceLock::new();
+        GLOBAL.get_or_init(|| Self::new(Self::DEFAULT_CAPACITY))
+    }
+
+    // Warnings are pushed without panicking, so a poisoned lock holds valid data.
+    fn lock(&self) -> MutexGuard<'_, Buffer> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Reports a condition found by `source` during the setup.
+    pub fn warn(&self, level: WarningLevel, source: &str, message: String) {
+        self.push(Warning {
+            level,
+            step: None,
+            source: source.to_owned(),
+            message,
+        });
+    }
+
+    /// Reports a condition found by `source` at `step`.
+    pub fn warn_at(&self, step: usize, level: WarningLevel, source: &str, message: String) {
+        self.push(Warning {
+            level,
+            step: Some(step),
+            source: source.to_owned(),
+            message,
+        });
+    }
+
+    /// Adds `warning`, dropping the oldest warning if the sink is full.
+    pub fn push(&self, warning: Warning) {
+        let mut buffer = self.lock();
+        buffer.counts[warning.level as usize] += 1;
+        if buffer.warnings.len() == self.capacity {
+            buffer.warnings.pop_front();
+            buffer.dropped += 1;
+        }
+        buffer.warnings.push_back(warning);
+    }
+
+    /// Returns a copy of the warnings held, oldest first.
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.lock().warnings.iter().cloned().collect()
+    }
+
+    /// Removes and returns the warnings held, oldest first.
+    ///
+    /// The counts of the summary are kept.
+    pub fn drain(&self) -> Vec<Warning> {
+        self.lock().warnings.drain(..).collect()
+    }
+
+    /// Returns the number of warnings of `level` reported so far, including those dropped.
+    pub fn count(&self, level: WarningLevel) -> usize {
+        self.lock().counts[level as usize]
+    }
+
+    /// Returns the highest level reported so far, if any warning was.
+    pub fn highest_level(&self) -> Option<WarningLevel> {
+        let buffer = self.lock();
+        WarningLevel::ALL
+            .into_iter()
+            .rev()
+            .find(|&level| buffer.counts[level as usize] > 0)
+    }
+
+    /// Returns the summary of the warnings, to be printed at the end of a run.
+    pub fn summary(&self) -> WarningSummary {
+        let buffer = self.lock();
+        WarningSummary {
+            counts: buffer.counts,
+            dropped: buffer.dropped,
+            warnings: buffer.warnings.iter().cloned().collect(),
+        }
+    }
+
+    /// Warns if more threads have been requested than the machine can run at once.
+    pub fn check_threads(&self, threads: usize) {
+        let Ok(cores) = std::thread::available_parallelism() else {
+            return;
+        };
+        if threads > cores.get() {
+            self.warn(
+                WarningLevel::Warning,
+                "parallelism",
+                format!(
+                    "{} threads requested with {} cores available, so the threads will \
+                     wait for each other at every barrier",
+                    threads, cores
+                ),
+            );
+        }
+    }
+
+    /// Warns if the time constant of a thermostat spans fewer than `min_steps` steps,
+    /// in which case the thermostat perturbs the dynamics it is meant to thermalize.
+    pub fn check_time_constant(
+        &self,
+        source: &str,
+        time_constant: f64,
+        time_step: f64,
+        min_steps: f64,
+    ) {
+        if time_constant < time_step * min_steps {
+            self.warn(
+                WarningLevel::Warning,
+                source,
+                format!(
+                    "the time constant {} is shorter than {} time steps of {}",
+                    time_constant, min_steps, time_step
+                ),
+            );
+        }
+    }
+
+    /// Warns if `cutoff` exceeds half of `length`, the shortest periodic length of the box,
+    /// in which case pairs beyond the minimum image are missed.
+    pub fn check_cutoff(&self, source: &str, cutoff: f64, length: f64) {
+        if cutoff > 0.5 * length {
+            self.warn(
+                WarningLevel::Severe,
+                source,
+                format!(
+                    "the cutoff {} exceeds half of the periodic length {} of the box",
+                    cutoff, length
+                ),
+            );
+        }
+    }
+}
+
+impl Default for WarningSink {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+/// The warnings of a run, as printed once it ends.
+#[derive(Clone, Debug)]
+pub struct WarningSummary {
+    counts: [usize; 3],
+    dropped: usize,
+    warnings: Vec<Warning>,
+}
+
+impl WarningSummary {
+    /// Returns the total number of warnings reported.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the latest warnings held by the sink.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+}
+
+impl Display for WarningSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.total() == 0 {
+            return write!(f, "no warnings");
+        }
+        write!(f, "{} warnings (", self.total())?;
+        let mut first = true;
+        for level in WarningLevel::ALL.into_iter().rev() {
+            let count = self.counts[level as usize];
+            if count == 0 {
+                continue;
+            }
+            if !first {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} {}", count, level.name())?;
+            first = false;
+        }
+        write!(f, ")")?;
+        if self.dropped > 0 {
+            write!(f, ", the {} oldest not kept", self.dropped)?;
+        }
+        for warning in &self.warnings {
+            write!(f, "\n  {}", warning)?;
+        }
+        Ok(())
+    }
+}
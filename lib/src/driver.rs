@@ -0,0 +1,642 @@
+//! Drivers executing the replicas of a simulation step by step.
+//!
+//! A step of a replica is split into phases, separated by the points at which the replicas
+//! synchronize: every value a replica receives in a phase, e.g. through the reductions of
+//! [`crate::core::sync_ops::local`], must have been sent in an earlier phase.
+//! Under this rule the [`ThreadedDriver`], which runs every replica on a thread of its own
+//! and waits for all of them at the end of every phase, and the [`SingleThreadedDriver`],
+//! which runs the phases of the replicas round-robin on the calling thread,
+//! produce the same results, so a run can be reproduced under a debugger
+//! or on a machine with a single core by switching the driver in [`DriverBuilder`].
+//!
+//! A driver given an [`EventBus`] publishes the start and the end of every step,
+//! the checkpoints written after a step and the first failure of a run on it,
+//! always from the calling thread while the replicas wait.
+
+use crate::{
+    core::{
+        error::{CommError, ContextError, ResultExt},
+        failure::{FailureCause, FailureMonitor, FailureReport, panic_message},
+    },
+    events::{CheckpointWritten, Event, EventBus, RunFailed, StepFinished, StepStarted},
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+/// The work of a single replica, e.g. the groups of an image, split into phases.
+pub trait ReplicaTask {
+    /// The type associated with an error returned by the implementor.
+    type Error;
+
+    /// Returns the number of phases of every step, which must be the same for every replica.
+    fn phases(&self) -> usize;
+
+    /// Runs `phase` of `step`.
+    fn run_phase(&mut self, step: usize, phase: usize) -> Result<(), Self::Error>;
+}
+
+/// An error raised while driving the replicas.
+#[derive(Debug)]
+pub enum DriverError<E> {
+    /// A replica failed, in the step and the thread of the replica recorded in the context.
+    Replica(ContextError<E>),
+    /// The finalization of a step failed, in the step recorded in the context.
+    Finalization(ContextError<E>),
+    /// The checkpoint of a step could not be written, in the step recorded in the context.
+    Checkpoint(ContextError<E>),
+    /// A replica or the finalization of a step panicked.
+    Panic(FailureReport),
+    /// The thread of a replica could not be spawned.
+    Spawn(io::Error),
+    /// The replicas disagree on the number of phases of a step.
+    Phases,
+}
+
+impl<E: Display> Display for DriverError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Replica(err) => write!(f, "a replica failed {}", err),
+            Self::Finalization(err) => write!(f, "the finalization of a step failed {}", err),
+            Self::Checkpoint(err) => write!(f, "writing a checkpoint failed {}", err),
+            Self::Panic(report) => write!(f, "{}", report),
+            Self::Spawn(err) => write!(f, "failed to spawn the thread of a replica: {}", err),
+            Self::Phases => write!(f, "the replicas disagree on the number of phases of a step"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for DriverError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Replica(err) | Self::Finalization(err) | Self::Checkpoint(err) => Some(err),
+            Self::Panic(report) => Some(report),
+            Self::Spawn(err) => Some(err),
+            Self::Phases => None,
+        }
+    }
+}
+
+/// A flag through which a run is stopped early from outside the driver,
+/// e.g. by an analysis script which finds the observables converged.
+///
+/// The driver stops once the step during which the token was cancelled has been finalized,
+/// and reports success.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Constructs a token which has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the run to stop at the end of the current step.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the run has been requested to stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A strategy for executing the replicas of a simulation.
+pub trait Driver {
+    /// Runs `steps` steps of every replica.
+    ///
+    /// `step_finalization` takes the current step and executes custom logic, e.g. writing
+    /// the observables, once every replica has finished the step and before any starts
+    /// the next one. It is only called from the calling thread.
+    ///
+    /// A replica which returns an error or panics stops the run at the end of the phase
+    /// it failed in, and so does a failed finalization. If several replicas fail in the same
+    /// phase, the failure of the one with the lowest index is returned.
+    fn execute<R, F>(
+        &self,
+        replicas: &mut [R],
+        steps: usize,
+        step_finalization: F,
+    ) -> Result<(), DriverError<R::Error>>
+    where
+        R: ReplicaTask + Send,
+        R::Error: Display + Send,
+        F: FnMut(usize) -> Result<(), R::Error>,
+    {
+        self.execute_with_checkpoints(replicas, steps, step_finalization, |_| Ok(None))
+    }
+
+    /// Runs `steps` steps of every replica like [`Driver::execute`], calling `checkpoint`
+    /// with the current step once the step has been finalized.
+    ///
+    /// `checkpoint` decides whether to write a checkpoint of the step and returns the path
+    /// of the checkpoint it has written, if any, which is published as a [`CheckpointWritten`]
+    /// event. It is only called from the calling thread.
+    fn execute_with_checkpoints<R, F, C>(
+        &self,
+        replicas: &mut [R],
+        steps: usize,
+        step_finalization: F,
+        checkpoint: C,
+    ) -> Result<(), DriverError<R::Error>>
+    where
+        R: ReplicaTask + Send,
+        R::Error: Display + Send,
+        F: FnMut(usize) -> Result<(), R::Error>,
+        C: FnMut(usize) -> Result<Option<PathBuf>, R::Error>;
+}
+
+fn phases<R: ReplicaTask>(replicas: &[R]) -> Result<usize, DriverError<R::Error>> {
+    let phases = replicas.first().map_or(0, ReplicaTask::phases);
+    if replicas.iter().any(|replica| replica.phases() != phases) {
+        return Err(DriverError::Phases);
+    }
+    Ok(phases)
+}
+
+/// Publishes `event` on the bus of a driver, if it has one.
+fn publish<V: Event>(events: Option<&Mutex<EventBus>>, event: &V) {
+    if let Some(events) = events {
+        events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .publish(event);
+    }
+}
+
+/// The state of a run shared by the threads of the replicas and the calling thread.
+struct Run<'a, E> {
+    monitor: FailureMonitor,
+    /// The first failure of the replica with the lowest index.
+    failure: Mutex<Option<(usize, DriverError<E>)>>,
+    /// A failure of the calling thread.
+    error: Mutex<Option<DriverError<E>>>,
+    cancellation: Option<&'a CancellationToken>,
+    events: Option<&'a Mutex<EventBus>>,
+    stopped: AtomicBool,
+}
+
+impl<'a, E: Display> Run<'a, E> {
+    fn new(
+        threads: usize,
+        cancellation: Option<&'a CancellationToken>,
+        events: Option<&'a Mutex<EventBus>>,
+    ) -> Self {
+        Self {
+            monitor: FailureMonitor::new(threads),
+            failure: Mutex::new(None),
+            error: Mutex::new(None),
+            cancellation,
+            events,
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    fn publish<V: Event>(&self, event: &V) {
+        publish(self.events, event);
+    }
+
+    /// Announces the first step of a run of `steps` steps.
+    fn start(&self, steps: usize) {
+        if steps > 0 {
+            self.publish(&StepStarted { step: 0 });
+        }
+    }
+
+    fn set_error(&self, error: DriverError<E>) {
+        *self.error.lock().unwrap_or_else(PoisonError::into_inner) = Some(error);
+    }
+
+    /// Records the failure `err` of the calling thread as the error `kind`
+    /// and returns its message for the failure report.
+    fn fail_main(
+        &self,
+        err: ContextError<E>,
+        kind: fn(ContextError<E>) -> DriverError<E>,
+    ) -> String {
+        let message = err.source.to_string();
+        self.set_error(kind(err));
+        message
+    }
+
+    /// Records the failure of the replica `index`, keeping the one of the lowest index.
+    fn fail_replica(&self, index: usize, report: FailureReport, error: DriverError<E>) {
+        let mut failure = self.failure.lock().unwrap_or_else(PoisonError::into_inner);
+        if failure.as_ref().is_none_or(|(other, _)| index < *other) {
+            *failure = Some((index, error));
+        }
+        drop(failure);
+        self.monitor.fail(report);
+    }
+
+    /// Runs `phase` of `step` of the replica `index`.
+    ///
+    /// Returns `false` if the replica has returned an error or panicked,
+    /// in which case the failure has been recorded and the thread must stop.
+    fn run_phase<R>(&self, replica: &mut R, index: usize, step: usize, phase: usize) -> bool
+    where
+        R: ReplicaTask<Error = E>,
+    {
+        let location = CommError::Replica { replica: index };
+        let report = |cause| FailureReport {
+            step,
+            location: location.clone(),
+            component: "replica",
+            cause,
+        };
+        let ran = panic::catch_unwind(AssertUnwindSafe(|| {
+            replica
+                .run_phase(step, phase)
+                .with_context(step, location.clone())
+        }));
+        match ran {
+            Ok(Ok(())) => return true,
+            Ok(Err(err)) => {
+                let report = report(FailureCause::Error(err.source.to_string()));
+                self.fail_replica(index, report, DriverError::Replica(err));
+            }
+            Err(payload) => {
+                let report = report(FailureCause::Panic(panic_message(payload.as_ref())));
+                self.fail_replica(index, report.clone(), DriverError::Panic(report));
+            }
+        }
+        false
+    }
+
+    /// Finalizes `step` of `steps` on the calling thread and writes its checkpoint,
+    /// publishing the end of the step and the start of the next one.
+    ///
+    /// Returns whether the run goes on, which it does not after the last step,
+    /// if the finalization or the checkpoint has failed or if the run has been cancelled.
+    fn finalize<F, C>(
+        &self,
+        step: usize,
+        steps: usize,
+        step_finalization: &mut F,
+        checkpoint: &mut C,
+    ) -> bool
+    where
+        F: FnMut(usize) -> Result<(), E>,
+        C: FnMut(usize) -> Result<Option<PathBuf>, E>,
+    {
+        let finalized = self
+            .monitor
+            .run(step, CommError::Main, "step finalization", || {
+                step_finalization(step)
+                    .with_context(step, CommError::Main)
+                    .map_err(|err| self.fail_main(err, DriverError::Finalization))?;
+                let path = checkpoint(step)
+                    .with_context(step, CommError::Main)
+                    .map_err(|err| self.fail_main(err, DriverError::Checkpoint))?;
+                if let Some(path) = path {
+                    self.publish(&CheckpointWritten { step, path });
+                }
+                self.publish(&StepFinished { step });
+                Ok::<_, String>(())
+            });
+        if finalized.is_none() {
+            return false;
+        }
+        // A cancellation stops the replicas like a failure, without an error.
+        if self
+            .cancellation
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            self.stopped.store(true, Ordering::Relaxed);
+            return false;
+        }
+        if step + 1 == steps {
+            return false;
+        }
+        self.publish(&StepStarted { step: step + 1 });
+        true
+    }
+
+    /// Returns whether the run has been stopped at the end of the last finalized step.
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Returns the error of the run once every thread has stopped.
+    fn finish(self) -> Result<(), DriverError<E>> {
+        let events = self.events;
+        let report = self.monitor.finish(|report| {
+            publish(
+                events,
+                &RunFailed {
+                    report: report.clone(),
+                },
+            )
+        });
+        let failure = self
+            .failure
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        let error = self
+            .error
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        match (failure, error, report) {
+            (Some((_, err)), _, _) | (None, Some(err), _) => Err(err),
+            (None, None, Err(report)) => Err(DriverError::Panic(report)),
+            (None, None, Ok(())) => Ok(()),
+        }
+    }
+}
+
+/// Runs every replica on a thread of its own.
+#[derive(Clone, Debug, Default)]
+pub struct ThreadedDriver {
+    stack_size: Option<usize>,
+    cancellation: Option<CancellationToken>,
+    events: Option<Arc<Mutex<EventBus>>>,
+}
+
+impl ThreadedDriver {
+    /// Constructs a driver spawning threads with the default stack size.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Spawns the threads with stacks of `stack_size` bytes.
+    pub fn with_stack_size(self, stack_size: usize) -> Self {
+        Self {
+            stack_size: Some(stack_size),
+            ..self
+        }
+    }
+
+    /// Stops the run early once `token` has been cancelled.
+    pub fn with_cancellation(self, token: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(token),
+            ..self
+        }
+    }
+
+    /// Publishes the start and the end of every step, the checkpoints
+    /// and the first failure of a run on `events`.
+    pub fn with_events(self, events: Arc<Mutex<EventBus>>) -> Self {
+        Self {
+            events: Some(events),
+            ..self
+        }
+    }
+}
+
+impl Driver for ThreadedDriver {
+    fn execute_with_checkpoints<R, F, C>(
+        &self,
+        replicas: &mut [R],
+        steps: usize,
+        mut step_finalization: F,
+        mut checkpoint: C,
+    ) -> Result<(), DriverError<R::Error>>
+    where
+        R: ReplicaTask + Send,
+        R::Error: Display + Send,
+        F: FnMut(usize) -> Result<(), R::Error>,
+        C: FnMut(usize) -> Result<Option<PathBuf>, R::Error>,
+    {
+        let phases = phases(replicas)?;
+        // The calling thread joins every barrier, so that it finalizes a step
+        // between the last phase of the step and the first phase of the next one.
+        // A failed thread releases the others from the barriers, and they stop
+        // at the end of the phase they are running, so every failure of a run
+        // arises in the same phase.
+        let run = Run::new(
+            replicas.len() + 1,
+            self.cancellation.as_ref(),
+            self.events.as_deref(),
+        );
+        thread::scope(|s| {
+            // The replicas start once all of them have been spawned, so that a failure
+            // to spawn one does not leave the others waiting at a barrier forever.
+            let mut starts = Vec::with_capacity(replicas.len());
+            for (index, replica) in replicas.iter_mut().enumerate() {
+                let mut builder = thread::Builder::new().name(format!("replica-{}", index));
+                if let Some(stack_size) = self.stack_size {
+                    builder = builder.stack_size(stack_size);
+                }
+                let (start, started) = mpsc::channel::<()>();
+                let run = &run;
+                let spawned = builder.spawn_scoped(s, move || {
+                    if started.recv().is_err() {
+                        return;
+                    }
+                    for step in 0..steps {
+                        for phase in 0..phases {
+                            if !run.run_phase(replica, index, step, phase)
+                                || run.monitor.wait().is_err()
+                            {
+                                return;
+                            }
+                        }
+                        // Held until the step has been finalized.
+                        if run.monitor.wait().is_err() || run.is_stopped() {
+                            return;
+                        }
+                    }
+                });
+                if let Err(err) = spawned {
+                    run.set_error(DriverError::Spawn(err));
+                    return;
+                }
+                starts.push(start);
+            }
+            run.start(steps);
+            for start in starts {
+                // The thread only hangs up once it has been started.
+                let _ = start.send(());
+            }
+            for step in 0..steps {
+                for _ in 0..phases {
+                    if run.monitor.wait().is_err() {
+                        return;
+                    }
+                }
+                let goes_on = run.finalize(step, steps, &mut step_finalization, &mut checkpoint);
+                if run.monitor.wait().is_err() || !goes_on {
+                    return;
+                }
+            }
+        });
+        run.finish()
+    }
+}
+
+/// Runs the phases of the replicas round-robin on the calling thread,
+/// in the order of the replicas.
+///
+/// Meant for debugging and for continuous integration, where many threads
+/// are a hindrance, rather than for production runs.
+#[derive(Clone, Debug, Default)]
+pub struct SingleThreadedDriver {
+    cancellation: Option<CancellationToken>,
+    events: Option<Arc<Mutex<EventBus>>>,
+}
+
+impl SingleThreadedDriver {
+    /// Constructs the driver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the run early once `token` has been cancelled.
+    pub fn with_cancellation(self, token: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(token),
+            ..self
+        }
+    }
+
+    /// Publishes the start and the end of every step, the checkpoints
+    /// and the first failure of a run on `events`.
+    pub fn with_events(self, events: Arc<Mutex<EventBus>>) -> Self {
+        Self {
+            events: Some(events),
+            ..self
+        }
+    }
+}
+
+impl Driver for SingleThreadedDriver {
+    fn execute_with_checkpoints<R, F, C>(
+        &self,
+        replicas: &mut [R],
+        steps: usize,
+        mut step_finalization: F,
+        mut checkpoint: C,
+    ) -> Result<(), DriverError<R::Error>>
+    where
+        R: ReplicaTask + Send,
+        R::Error: Display + Send,
+        F: FnMut(usize) -> Result<(), R::Error>,
+        C: FnMut(usize) -> Result<Option<PathBuf>, R::Error>,
+    {
+        let phases = phases(replicas)?;
+        let run = Run::new(1, self.cancellation.as_ref(), self.events.as_deref());
+        run.start(steps);
+        'steps: for step in 0..steps {
+            for phase in 0..phases {
+                for (index, replica) in replicas.iter_mut().enumerate() {
+                    if !run.run_phase(replica, index, step, phase) {
+                        break 'steps;
+                    }
+                }
+            }
+            if !run.finalize(step, steps, &mut step_finalization, &mut checkpoint) {
+                break;
+            }
+        }
+        run.finish()
+    }
+}
+
+/// A driver selected at runtime by a [`DriverBuilder`].
+#[derive(Clone, Debug)]
+pub enum SelectedDriver {
+    /// A thread per replica.
+    Threaded(ThreadedDriver),
+    /// Every replica on the calling thread.
+    SingleThreaded(SingleThreadedDriver),
+}
+
+impl Driver for SelectedDriver {
+    fn execute_with_checkpoints<R, F, C>(
+        &self,
+        replicas: &mut [R],
+        steps: usize,
+        step_finalization: F,
+        checkpoint: C,
+    ) -> Result<(), DriverError<R::Error>>
+    where
+        R: ReplicaTask + Send,
+        R::Error: Display + Send,
+        F: FnMut(usize) -> Result<(), R::Error>,
+        C: FnMut(usize) -> Result<Option<PathBuf>, R::Error>,
+    {
+        match self {
+            Self::Threaded(driver) => {
+                driver.execute_with_checkpoints(replicas, steps, step_finalization, checkpoint)
+            }
+            Self::SingleThreaded(driver) => {
+                driver.execute_with_checkpoints(replicas, steps, step_finalization, checkpoint)
+            }
+        }
+    }
+}
+
+/// A builder of the driver of a run, threaded unless requested otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct DriverBuilder {
+    single_threaded: bool,
+    stack_size: Option<usize>,
+    cancellation: Option<CancellationToken>,
+    events: Option<Arc<Mutex<EventBus>>>,
+}
+
+impl DriverBuilder {
+    /// Constructs a builder of a threaded driver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs every replica on the calling thread if `single_threaded` is `true`.
+    pub fn single_threaded(self, single_threaded: bool) -> Self {
+        Self {
+            single_threaded,
+            ..self
+        }
+    }
+
+    /// Spawns the threads of a threaded driver with stacks of `stack_size` bytes.
+    pub fn stack_size(self, stack_size: usize) -> Self {
+        Self {
+            stack_size: Some(stack_size),
+            ..self
+        }
+    }
+
+    /// Stops the run early once `token` has been cancelled.
+    pub fn cancellation(self, token: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(token),
+            ..self
+        }
+    }
+
+    /// Publishes the events of the run on `events`.
+    pub fn events(self, events: Arc<Mutex<EventBus>>) -> Self {
+        Self {
+            events: Some(events),
+            ..self
+        }
+    }
+
+    /// Builds the driver.
+    pub fn build(self) -> SelectedDriver {
+        if self.single_threaded {
+            return SelectedDriver::SingleThreaded(SingleThreadedDriver {
+                cancellation: self.cancellation,
+                events: self.events,
+            });
+        }
+        SelectedDriver::Threaded(ThreadedDriver {
+            stack_size: self.stack_size,
+            cancellation: self.cancellation,
+            events: self.events,
+        })
+    }
+}
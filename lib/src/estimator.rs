@@ -1,4 +1,6 @@
 //! Traits for calculating the two kinds of quantities.
 
+pub mod bookkeeping;
 pub mod classical;
+pub mod parallel;
 pub mod quantum;
@@ -0,0 +1,114 @@
+//! Observables that keep track of the energy transferred into the system.
+
+use crate::{
+    core::sync_ops::{SyncAddReciever, SyncAddSender},
+    potential::physical::WorkSource,
+    thermostat::HeatSource,
+};
+use std::ops::Add;
+
+/// A running sum of the increments received in every step.
+#[derive(Clone, Copy, Debug)]
+struct Cumulative<T>(Option<T>);
+
+impl<T: Clone + Add<Output = T>> Cumulative<T> {
+    fn send<A>(increment: Option<T>, adder: &mut A) -> Result<(), A::Error>
+    where
+        A: SyncAddSender<T> + ?Sized,
+    {
+        match increment {
+            Some(increment) => adder.send(increment),
+            None => adder.send_empty(),
+        }
+    }
+
+    fn receive<R>(&mut self, receiver: &mut R) -> Result<Option<T>, R::Error>
+    where
+        R: SyncAddReciever<T> + ?Sized,
+    {
+        if let Some(increment) = receiver.receive_sum()? {
+            self.0 = Some(match self.0.take() {
+                Some(accum) => accum + increment,
+                None => increment,
+            });
+        }
+        Ok(self.0.clone())
+    }
+}
+
+/// The cumulative work performed on the system by
+/// time-dependent potentials.
+#[derive(Clone, Copy, Debug)]
+pub struct WorkObservable<T>(Cumulative<T>);
+
+impl<T: Clone + Add<Output = T>> WorkObservable<T> {
+    /// Constructs a new `WorkObservable` with no work performed.
+    pub const fn new() -> Self {
+        Self(Cumulative(None))
+    }
+
+    /// Sends the work performed on this group since the previous step.
+    ///
+    /// Called by every group thread.
+    pub fn send<S, A>(source: &mut S, adder: &mut A) -> Result<(), A::Error>
+    where
+        S: WorkSource<T> + ?Sized,
+        A: SyncAddSender<T> + ?Sized,
+    {
+        Cumulative::send(source.take_work(), adder)
+    }
+
+    /// Receives the work performed on all groups and adds it to the total.
+    ///
+    /// Called by the main thread. Returns the total work performed so far.
+    pub fn receive<R>(&mut self, receiver: &mut R) -> Result<Option<T>, R::Error>
+    where
+        R: SyncAddReciever<T> + ?Sized,
+    {
+        self.0.receive(receiver)
+    }
+}
+
+impl<T: Clone + Add<Output = T>> Default for WorkObservable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The cumulative heat transferred into the system by thermostats.
+#[derive(Clone, Copy, Debug)]
+pub struct HeatObservable<T>(Cumulative<T>);
+
+impl<T: Clone + Add<Output = T>> HeatObservable<T> {
+    /// Constructs a new `HeatObservable` with no heat transferred.
+    pub const fn new() -> Self {
+        Self(Cumulative(None))
+    }
+
+    /// Sends the heat transferred into this group since the previous step.
+    ///
+    /// Called by every group thread.
+    pub fn send<S, A>(source: &mut S, adder: &mut A) -> Result<(), A::Error>
+    where
+        S: HeatSource<T> + ?Sized,
+        A: SyncAddSender<T> + ?Sized,
+    {
+        Cumulative::send(source.take_heat(), adder)
+    }
+
+    /// Receives the heat transferred into all groups and adds it to the total.
+    ///
+    /// Called by the main thread. Returns the total heat transferred so far.
+    pub fn receive<R>(&mut self, receiver: &mut R) -> Result<Option<T>, R::Error>
+    where
+        R: SyncAddReciever<T> + ?Sized,
+    {
+        self.0.receive(receiver)
+    }
+}
+
+impl<T: Clone + Add<Output = T>> Default for HeatObservable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -463,7 +463,7 @@ where
         adder: &mut Adder,
         _multiplier: &mut Multiplier,
     ) -> Result<Self::Output, Self::Error> {
-        Ok(adder.recieve_sum()?.ok_or(EmptyError)?)
+        Ok(adder.receive_sum()?.ok_or(EmptyError)?)
     }
 }
 
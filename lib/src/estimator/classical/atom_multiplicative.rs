@@ -502,7 +502,7 @@ where
         _adder: &mut Adder,
         multiplier: &mut Multiplier,
     ) -> Result<Self::Output, Self::Error> {
-        Ok(multiplier.recieve_prod()?.ok_or(EmptyError)?)
+        Ok(multiplier.receive_prod()?.ok_or(EmptyError)?)
     }
 }
 
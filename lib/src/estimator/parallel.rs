@@ -0,0 +1,184 @@
+//! Evaluation of observables over the groups of a system on several worker threads.
+
+use crate::core::sync_ops::{
+    Sum, SyncAddReciever, SyncReduceSender,
+    local::{self, DisconnectedError},
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::Add,
+    thread,
+};
+
+/// How the contributions of the groups are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Determinism {
+    /// Every worker adds up the contributions of its groups before sending them,
+    /// so that a floating-point result depends on the number of workers.
+    #[default]
+    Relaxed,
+    /// The contribution of every group is sent on its own and the contributions are added
+    /// in the order of the groups, so that the result is the same as that of a serial evaluation
+    /// regardless of the number of workers and of the order they finish in.
+    Strict,
+}
+
+/// An error raised during a parallel evaluation.
+#[derive(Clone, Debug)]
+pub enum ParallelEvaluationError<E> {
+    /// The observable failed for a group.
+    Observable {
+        /// The index of the group.
+        group: usize,
+        /// The underlying error.
+        source: E,
+    },
+    /// A worker stopped before sending all of its contributions.
+    Disconnected(DisconnectedError),
+}
+
+impl<E> From<DisconnectedError> for ParallelEvaluationError<E> {
+    fn from(value: DisconnectedError) -> Self {
+        Self::Disconnected(value)
+    }
+}
+
+impl<E: Display> Display for ParallelEvaluationError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Observable { group, source } => {
+                write!(f, "failed to evaluate group #{}: {}", group, source)
+            }
+            Self::Disconnected(err) => write!(f, "a worker stopped early: {}", err),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for ParallelEvaluationError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Observable { source, .. } => Some(source),
+            Self::Disconnected(err) => Some(err),
+        }
+    }
+}
+
+/// Evaluates an observable which is a sum of the contributions of the groups
+/// by splitting the groups into contiguous chunks, one per worker thread,
+/// and adding up the contributions through a [`SyncAddReciever`].
+#[derive(Clone, Copy, Debug)]
+pub struct ParallelEvaluation {
+    workers: usize,
+    determinism: Determinism,
+}
+
+impl ParallelEvaluation {
+    /// Constructs an evaluation on `workers` worker threads with [`Determinism::Relaxed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `workers` is zero.
+    pub fn new(workers: usize) -> Self {
+        assert!(workers > 0, "at least one worker is required");
+        Self {
+            workers,
+            determinism: Determinism::Relaxed,
+        }
+    }
+
+    /// Sets how the contributions of the groups are combined.
+    pub fn with_determinism(self, determinism: Determinism) -> Self {
+        Self {
+            determinism,
+            ..self
+        }
+    }
+
+    /// Returns the number of worker threads.
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Returns how the contributions of the groups are combined.
+    pub fn determinism(&self) -> Determinism {
+        self.determinism
+    }
+
+    /// Returns the sum of `observable` over `groups`, or `None` if there are no groups.
+    ///
+    /// `observable` is called with the index of every group and the group itself.
+    /// If it fails for several groups, the error of the first of them is returned.
+    pub fn evaluate<G, T, E, F>(
+        &self,
+        groups: &[G],
+        observable: F,
+    ) -> Result<Option<T>, ParallelEvaluationError<E>>
+    where
+        G: Sync,
+        T: Default + Send + Add<Output = T>,
+        E: Send,
+        F: Fn(usize, &G) -> Result<T, E> + Sync,
+    {
+        if groups.is_empty() {
+            return Ok(None);
+        }
+        let workers = self.workers.min(groups.len());
+        let chunk_size = groups.len().div_ceil(workers);
+        let chunks = groups.chunks(chunk_size);
+        let strict = self.determinism == Determinism::Strict;
+        let (senders, mut reciever) =
+            local::reduction::<T, Sum>(if strict { groups.len() } else { chunks.len() });
+        let observable = &observable;
+        thread::scope(|s| {
+            let mut senders = senders.into_iter();
+            let handles: Vec<_> = chunks
+                .enumerate()
+                .map(|(chunk_index, chunk)| {
+                    let mut chunk_senders: Vec<_> = senders
+                        .by_ref()
+                        .take(if strict { chunk.len() } else { 1 })
+                        .collect();
+                    let first_group = chunk_index * chunk_size;
+                    s.spawn(move || -> Result<(), ParallelEvaluationError<E>> {
+                        let mut values = chunk.iter().enumerate().map(|(offset, group)| {
+                            let index = first_group + offset;
+                            observable(index, group).map_err(|source| {
+                                ParallelEvaluationError::Observable {
+                                    group: index,
+                                    source,
+                                }
+                            })
+                        });
+                        if strict {
+                            for (sender, value) in chunk_senders.iter_mut().zip(values) {
+                                sender.send(value?)?;
+                            }
+                        } else {
+                            let mut sum = values.next().expect("chunks are never empty")?;
+                            for value in values {
+                                sum = sum + value?;
+                            }
+                            chunk_senders[0].send(sum)?;
+                        }
+                        Ok(())
+                    })
+                })
+                .collect();
+            let received = reciever.receive_sum();
+            let mut first_error = None;
+            for handle in handles {
+                let result = handle.join().unwrap_or_else(|payload| {
+                    std::panic::resume_unwind(payload);
+                });
+                if let Err(err) = result {
+                    first_error.get_or_insert(err);
+                }
+            }
+            match first_error {
+                Some(err) => Err(err),
+                None => Ok(received?),
+            }
+        })
+    }
+}
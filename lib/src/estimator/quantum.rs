@@ -3,14 +3,18 @@
 use crate::{
     core::{
         AtomGroup, AtomTypeReaderLock, MapInWhole, MapOutsideWhole, Scheme,
-        stat::{Bosonic, Distinguishable},
+        stat::{Bosonic, Distinguishable, Stat},
         sync_ops::{SyncAddReciever, SyncAddSender, SyncMulReciever, SyncMulSender},
     },
     potential::{
-        exchange::{ExchangePotential, quadratic::QuadraticExpansionExchangePotential},
+        exchange::{
+            ExchangePotential, FermionicExchangePotential,
+            quadratic::QuadraticExpansionExchangePotential,
+        },
         physical::PhysicalPotential,
     },
 };
+use std::ops::{Add, Div, Mul};
 
 mod atom_additive;
 pub use atom_additive::{
@@ -22,6 +26,8 @@ pub use atom_multiplicative::{
     AtomMultiplicativeMinimalQuantumEstimatorSender, AtomMultiplicativeQuantumEstimatorReciever,
     AtomMultiplicativeQuantumEstimatorSender,
 };
+mod sign_weighted;
+pub use sign_weighted::SignWeightedAverage;
 
 mod estimator_images {
     use std::ops::Deref;
@@ -92,6 +98,75 @@ where
         adder: &mut Adder,
         multiplier: &mut Multiplier,
     ) -> Result<Self::Output, Self::Error>;
+
+    /// Calculates the observable in a configuration of fermions sampled with the weights
+    /// of bosons and adds it to `average`, weighted by `sign`, the product of the signs
+    /// reported by the [`FermionicExchangePotential`]s of the groups of fermions.
+    ///
+    /// [`FermionicExchangePotential`]: crate::potential::exchange::FermionicExchangePotential
+    fn calculate_sign_weighted(
+        &mut self,
+        adder: &mut Adder,
+        multiplier: &mut Multiplier,
+        sign: T,
+        average: &mut SignWeightedAverage<Self::Output, T>,
+    ) -> Result<(), Self::Error>
+    where
+        Self::Output: Clone + Add<Output = Self::Output> + Mul<T, Output = Self::Output>,
+        Self::Output: Div<T, Output = Self::Output>,
+        T: Clone + PartialEq + From<f32> + Add<Output = T> + Div<Output = T>,
+    {
+        let value = self.calculate(adder, multiplier)?;
+        average.add(value, sign);
+        Ok(())
+    }
+
+    /// Receives the product of the signs sent by the groups with [`send_exchange_sign`]
+    /// and calculates the observable weighted by it into `average`
+    /// with [`calculate_sign_weighted`].
+    ///
+    /// Without groups of fermions every sign is 1.
+    ///
+    /// [`calculate_sign_weighted`]: QuantumEstimatorReciever::calculate_sign_weighted
+    fn calculate_with_exchange_sign<S>(
+        &mut self,
+        adder: &mut Adder,
+        multiplier: &mut Multiplier,
+        signs: &mut S,
+        average: &mut SignWeightedAverage<Self::Output, T>,
+    ) -> Result<(), Self::Error>
+    where
+        S: SyncMulReciever<T> + ?Sized,
+        Self::Error: From<S::Error>,
+        Self::Output: Clone + Add<Output = Self::Output> + Mul<T, Output = Self::Output>,
+        Self::Output: Div<T, Output = Self::Output>,
+        T: Clone + PartialEq + From<f32> + Add<Output = T> + Div<Output = T>,
+    {
+        let sign = signs.receive_prod()?.unwrap_or_else(|| T::from(1.0));
+        self.calculate_sign_weighted(adder, multiplier, sign, average)
+    }
+}
+
+/// Sends the sign of the exchange of a group with `exchange_potential` to be multiplied
+/// with those of the other groups and received by
+/// [`QuantumEstimatorReciever::calculate_with_exchange_sign`].
+///
+/// Groups of fermions send the sign reported by their [`FermionicExchangePotential`],
+/// and the other groups, whose weights are positive, an empty message.
+pub fn send_exchange_sign<T, V, Dist, Boson, Fermion, S>(
+    exchange_potential: Stat<&Dist, &Boson, &Fermion>,
+    signs: &mut S,
+) -> Result<(), S::Error>
+where
+    Dist: ?Sized,
+    Boson: ?Sized,
+    Fermion: FermionicExchangePotential<T, V> + ?Sized,
+    S: SyncMulSender<T> + ?Sized,
+{
+    match exchange_potential {
+        Stat::Fermionic(exchange_potential) => signs.send(exchange_potential.sign()),
+        Stat::Distinguishable(_) | Stat::Bosonic(_) => signs.send_empty(),
+    }
 }
 
 /// A trait for quantum estimators.
@@ -130,7 +205,8 @@ where
     /// Calculates the contribution of this group to the observable
     /// and sends it to a [`QuantumEstimatorReciever`].
     ///
-    /// Assumes this group obeys bosonic statistics.
+    /// Assumes this group obeys bosonic statistics. Groups of fermions, which are sampled
+    /// with the weights of bosons, are passed here too and reweighted by the reciever.
     fn calculate_bosonic(
         &mut self,
         adder: &mut Adder,
@@ -176,7 +252,8 @@ where
     /// Calculates the contribution of this group to the observable
     /// and sends it to a [`QuantumEstimatorReciever`].
     ///
-    /// Assumes this group obeys bosonic statistics.
+    /// Assumes this group obeys bosonic statistics. Groups of fermions, which are sampled
+    /// with the weights of bosons, are passed here too and reweighted by the reciever.
     fn calculate_bosonic(
         &mut self,
         exchange_potential_is_cyclic: bool,
@@ -147,7 +147,7 @@ where
         adder: &mut Adder,
         _multiplier: &mut Multiplier,
     ) -> Result<Self::Output, Self::Error> {
-        Ok(adder.recieve_sum()?.ok_or(EmptyError)?)
+        Ok(adder.receive_sum()?.ok_or(EmptyError)?)
     }
 }
 
@@ -262,6 +262,11 @@ where
         physical_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
         exchange_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
     ) -> Result<(), Self::Error> {
+        if let Scheme::Regular(potential) = &exchange_potential
+            && !potential.is_coupling()
+        {
+            return Ok(adder.send_empty()?);
+        }
         let mut iter = zip_iterators!(
             positions.read(),
             physical_forces.read(),
@@ -316,6 +321,11 @@ where
         physical_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
         exchange_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
     ) -> Result<(), Self::Error> {
+        if let Scheme::Regular(potential) = &exchange_potential
+            && !potential.is_coupling()
+        {
+            return Ok(adder.send_empty()?);
+        }
         let mut iter = zip_iterators!(
             positions.read(),
             physical_forces.read(),
@@ -157,7 +157,7 @@ where
         _adder: &mut Adder,
         multiplier: &mut Multiplier,
     ) -> Result<Self::Output, Self::Error> {
-        Ok(multiplier.recieve_product()?.ok_or(EmptyError)?)
+        Ok(multiplier.receive_prod()?.ok_or(EmptyError)?)
     }
 }
 
@@ -297,6 +297,11 @@ where
         physical_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
         exchange_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
     ) -> Result<(), Self::Error> {
+        if let Scheme::Regular(potential) = &exchange_potential
+            && !potential.is_coupling()
+        {
+            return Ok(multiplier.send_empty()?);
+        }
         let mut iter = zip_iterators!(
             positions.read(),
             physical_forces.read(),
@@ -351,6 +356,11 @@ where
         physical_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
         exchange_forces: &EstimatorImages<GroupInTypeInImageInSystem<V>>,
     ) -> Result<(), Self::Error> {
+        if let Scheme::Regular(potential) = &exchange_potential
+            && !potential.is_coupling()
+        {
+            return Ok(multiplier.send_empty()?);
+        }
         let mut iter = zip_iterators!(
             positions.read(),
             physical_forces.read(),
@@ -0,0 +1,78 @@
+//! Averages of observables of fermions sampled with the weights of bosons.
+
+use std::ops::{Add, Div, Mul};
+
+/// An average `⟨A s⟩ / ⟨s⟩` over configurations sampled with the weights of bosons,
+/// where `s` is the sign of the exchange of the fermions in every configuration.
+///
+/// The average sign `⟨s⟩` decays exponentially with the number of fermions and
+/// the inverse temperature, so it is kept alongside the average to judge
+/// whether the reweighted observable can be trusted at all.
+#[derive(Clone, Debug)]
+pub struct SignWeightedAverage<O, T> {
+    weighted_sum: Option<O>,
+    sign_sum: T,
+    samples: usize,
+}
+
+impl<O, T> SignWeightedAverage<O, T>
+where
+    O: Clone + Add<Output = O> + Mul<T, Output = O> + Div<T, Output = O>,
+    T: Clone + PartialEq + From<f32> + Add<Output = T> + Div<Output = T>,
+{
+    /// Constructs an average of no samples.
+    pub fn new() -> Self {
+        Self {
+            weighted_sum: None,
+            sign_sum: T::from(0.0),
+            samples: 0,
+        }
+    }
+
+    /// Adds `value` of a configuration whose sign is `sign`.
+    pub fn add(&mut self, value: O, sign: T) {
+        let weighted = value * sign.clone();
+        self.weighted_sum = Some(match self.weighted_sum.take() {
+            Some(sum) => sum + weighted,
+            None => weighted,
+        });
+        self.sign_sum = self.sign_sum.clone() + sign;
+        self.samples += 1;
+    }
+
+    /// Returns the number of configurations added.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Returns `⟨s⟩`, or `None` before a configuration is added.
+    pub fn average_sign(&self) -> Option<T> {
+        (self.samples > 0).then(|| self.sign_sum.clone() / T::from(self.samples as f32))
+    }
+
+    /// Returns `⟨A s⟩ / ⟨s⟩`, or `None` before a configuration is added
+    /// or while the signs cancel exactly.
+    pub fn average(&self) -> Option<O> {
+        if self.sign_sum == T::from(0.0) {
+            return None;
+        }
+        self.weighted_sum
+            .clone()
+            .map(|sum| sum / self.sign_sum.clone())
+    }
+
+    /// Discards every configuration added.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<O, T> Default for SignWeightedAverage<O, T>
+where
+    O: Clone + Add<Output = O> + Mul<T, Output = O> + Div<T, Output = O>,
+    T: Clone + PartialEq + From<f32> + Add<Output = T> + Div<Output = T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,197 @@
+//! A typed publish/subscribe bus for the events of a simulation,
+//! through which extensions such as loggers and adaptive controllers
+//! can observe the driver without depending on it or on each other.
+
+use crate::core::failure::FailureReport;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    path::PathBuf,
+};
+
+/// A marker trait for types which can be published on an [`EventBus`].
+pub trait Event: 'static {}
+
+/// A step of the simulation is about to start.
+#[derive(Clone, Copy, Debug)]
+pub struct StepStarted {
+    /// The index of the step.
+    pub step: usize,
+}
+
+impl Event for StepStarted {}
+
+/// A step of the simulation has been finalized.
+#[derive(Clone, Copy, Debug)]
+pub struct StepFinished {
+    /// The index of the step.
+    pub step: usize,
+}
+
+impl Event for StepFinished {}
+
+/// The forces on all atoms have been calculated.
+#[derive(Clone, Copy, Debug)]
+pub struct ForcesComputed<T> {
+    /// The index of the step.
+    pub step: usize,
+    /// The physical potential energy of the system.
+    pub physical_potential_energy: T,
+    /// The exchange potential energy of the system.
+    pub exchange_potential_energy: T,
+}
+
+impl<T: 'static> Event for ForcesComputed<T> {}
+
+/// A Monte Carlo move has been accepted.
+#[derive(Clone, Copy, Debug)]
+pub struct MoveAccepted<T> {
+    /// The index of the step.
+    pub step: usize,
+    /// The index of the moved atom.
+    pub atom: usize,
+    /// The change in the potential energy caused by the move.
+    pub energy_change: T,
+}
+
+impl<T: 'static> Event for MoveAccepted<T> {}
+
+/// A checkpoint has been written.
+#[derive(Clone, Debug)]
+pub struct CheckpointWritten {
+    /// The index of the step.
+    pub step: usize,
+    /// The path of the checkpoint.
+    pub path: PathBuf,
+}
+
+impl Event for CheckpointWritten {}
+
+/// The simulation has stopped after a failure.
+#[derive(Clone, Debug)]
+pub struct RunFailed {
+    /// The first failure of the run.
+    pub report: FailureReport,
+}
+
+impl Event for RunFailed {}
+
+/// The number of images has been changed between two steps.
+#[derive(Clone, Copy, Debug)]
+pub struct BeadsChanged {
+    /// The index of the first step with the new number of images.
+    pub step: usize,
+    /// The previous number of images.
+    pub from: usize,
+    /// The new number of images.
+    pub to: usize,
+}
+
+impl Event for BeadsChanged {}
+
+/// Forces above the cap of an early equilibration have been clamped.
+#[derive(Clone, Copy, Debug)]
+pub struct ForcesCapped<T> {
+    /// The index of the step.
+    pub step: usize,
+    /// The number of clamped forces in the evaluation.
+    pub capped: usize,
+    /// The largest magnitude of a force before it was clamped.
+    pub largest_magnitude: T,
+}
+
+impl<T: 'static> Event for ForcesCapped<T> {}
+
+type Handlers<E> = Vec<(usize, Box<dyn FnMut(&E) + Send>)>;
+
+/// A handle to a subscription to events of type `E`.
+#[derive(Debug)]
+pub struct Subscription<E> {
+    id: usize,
+    phantom: PhantomData<fn(&E)>,
+}
+
+/// A bus delivering published events to the handlers subscribed to their type.
+///
+/// Handlers are called in the order they have subscribed in.
+/// They receive the events by reference and cannot publish events themselves;
+/// extensions which react to events by raising others should queue them
+/// and publish them once the handler returns.
+#[derive(Default)]
+pub struct EventBus {
+    handlers: HashMap<TypeId, Box<dyn Any + Send>>,
+    next_id: usize,
+}
+
+impl EventBus {
+    /// Constructs a bus without any subscribers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes `handler` to events of type `E`.
+    pub fn subscribe<E, F>(&mut self, handler: F) -> Subscription<E>
+    where
+        E: Event,
+        F: FnMut(&E) + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.handlers
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(Handlers::<E>::new()))
+            .downcast_mut::<Handlers<E>>()
+            .expect("handlers are stored under the type id of their event")
+            .push((id, Box::new(handler)));
+        Subscription {
+            id,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Removes the handler of `subscription`.
+    ///
+    /// Returns whether the handler was still subscribed.
+    pub fn unsubscribe<E: Event>(&mut self, subscription: Subscription<E>) -> bool {
+        let Some(handlers) = self.handlers_mut::<E>() else {
+            return false;
+        };
+        let len = handlers.len();
+        handlers.retain(|(id, _)| *id != subscription.id);
+        handlers.len() != len
+    }
+
+    /// Returns whether any handler is subscribed to events of type `E`,
+    /// so that publishers can skip assembling events nobody listens to.
+    pub fn has_subscribers<E: Event>(&self) -> bool {
+        self.handlers
+            .get(&TypeId::of::<E>())
+            .and_then(|handlers| handlers.downcast_ref::<Handlers<E>>())
+            .is_some_and(|handlers| !handlers.is_empty())
+    }
+
+    /// Delivers `event` to every handler subscribed to its type.
+    pub fn publish<E: Event>(&mut self, event: &E) {
+        if let Some(handlers) = self.handlers_mut::<E>() {
+            for (_, handler) in handlers {
+                handler(event);
+            }
+        }
+    }
+
+    fn handlers_mut<E: Event>(&mut self) -> Option<&mut Handlers<E>> {
+        self.handlers
+            .get_mut(&TypeId::of::<E>())
+            .and_then(|handlers| handlers.downcast_mut::<Handlers<E>>())
+    }
+}
+
+impl Debug for EventBus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("EventBus")
+            .field("event_types", &self.handlers.len())
+            .finish_non_exhaustive()
+    }
+}
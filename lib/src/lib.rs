@@ -1,4 +1,3 @@
-#![feature(ptr_metadata, substr_range)]
 #![allow(clippy::too_many_arguments)]
 #![warn(missing_docs)]
 #![allow(clippy::too_many_arguments)]
@@ -47,8 +46,12 @@ use std::{
     thread,
 };
 
+pub mod checkpoint;
 pub mod core;
+pub mod driver;
 pub mod estimator;
+pub mod events;
+pub mod numeric;
 pub mod output;
 pub mod potential;
 pub mod propagator;
@@ -0,0 +1,84 @@
+//! Numerical building blocks shared by the solvers of the crate
+//! and available to the authors of potentials.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+pub mod interp;
+pub mod iterate;
+pub mod linalg;
+
+/// The arithmetic of real numbers needed by the numerical routines,
+/// implemented by `f32` and `f64`.
+pub trait Real:
+    Copy
+    + PartialOrd
+    + From<f32>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    /// The difference between 1 and the next larger representable number.
+    const EPSILON: Self;
+
+    /// Archimedes' constant π.
+    const PI: Self;
+
+    /// Returns the absolute value.
+    fn abs(self) -> Self;
+
+    /// Returns the square root.
+    fn sqrt(self) -> Self;
+
+    /// Returns the sine and the cosine of the angle in radians.
+    fn sin_cos(self) -> (Self, Self);
+
+    /// Returns whether the number is neither infinite nor NaN.
+    fn is_finite(self) -> bool;
+
+    /// Converts to an index, truncating toward zero and saturating at the bounds of `usize`,
+    /// with NaN converted to 0.
+    fn to_usize(self) -> usize;
+
+    /// Converts from `f64`, rounding to the nearest representable number.
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! impl_real {
+    ($($float:ident),*) => {
+        $(
+            impl Real for $float {
+                const EPSILON: Self = <$float>::EPSILON;
+
+                const PI: Self = std::$float::consts::PI;
+
+                fn abs(self) -> Self {
+                    <$float>::abs(self)
+                }
+
+                fn sqrt(self) -> Self {
+                    <$float>::sqrt(self)
+                }
+
+                fn sin_cos(self) -> (Self, Self) {
+                    <$float>::sin_cos(self)
+                }
+
+                fn is_finite(self) -> bool {
+                    <$float>::is_finite(self)
+                }
+
+                fn to_usize(self) -> usize {
+                    self as usize
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value as $float
+                }
+            }
+        )*
+    };
+}
+
+impl_real!(f32, f64);
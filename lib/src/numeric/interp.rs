@@ -0,0 +1,524 @@
+//! Interpolation of tabulated functions and of their derivatives.
+//!
+//! Potentials given as tables, e.g. the embedding functions of EAM or a bias accumulated
+//! on a grid, need both the value and the derivative between the points, and the forces
+//! are only as smooth as the interpolant. The interpolants here return both at once:
+//!
+//! - [`UniformTable`], a cubic Hermite interpolant of evenly spaced values
+//!   with slopes from finite differences,
+//! - [`CubicSpline`], whose second derivative is continuous as well,
+//! - [`MonotoneCubic`], which neither overshoots nor oscillates between monotone data,
+//! - [`BilinearGrid`], a function of two variables on a regular grid.
+//!
+//! Beyond the ends of the knots, the functions of one variable are continued linearly.
+
+use super::Real;
+
+/// Returns the cubic Hermite polynomial through `y0` and `y1` with the slopes `d0` and `d1`
+/// with respect to `t` in [0, 1], and its derivative with respect to `t`.
+fn hermite<T: Real>(t: T, y0: T, y1: T, d0: T, d1: T) -> (T, T) {
+    let one = T::from(1.0);
+    let two = T::from(2.0);
+    let three = T::from(3.0);
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let value = (two * t3 - three * t2 + one) * y0
+        + (t3 - two * t2 + t) * d0
+        + (three * t2 - two * t3) * y1
+        + (t3 - t2) * d1;
+    let derivative = (T::from(6.0) * (t2 - t)) * (y0 - y1)
+        + (three * t2 - T::from(4.0) * t + one) * d0
+        + (three * t2 - two * t) * d1;
+    (value, derivative)
+}
+
+/// Returns the index of the interval of `knots` containing `x`,
+/// the first or the last one if `x` is outside the knots.
+fn interval<T: Real>(knots: &[T], x: T) -> usize {
+    knots
+        .partition_point(|&knot| knot <= x)
+        .clamp(1, knots.len() - 1)
+        - 1
+}
+
+fn assert_knots<T: Real>(knots: &[T], values: &[T]) {
+    assert!(
+        knots.len() >= 2,
+        "an interpolant must have at least two knots"
+    );
+    assert_eq!(knots.len(), values.len(), "every knot must have a value");
+    assert!(
+        knots.windows(2).all(|pair| pair[0] < pair[1]),
+        "the knots must be strictly increasing"
+    );
+}
+
+/// A function tabulated at evenly spaced points and interpolated by cubic Hermite polynomials
+/// with the slopes estimated by finite differences.
+///
+/// Beyond the first and the last point the function is continued linearly.
+#[derive(Clone, Debug)]
+pub struct UniformTable<T> {
+    start: T,
+    step: T,
+    values: Box<[T]>,
+    slopes: Box<[T]>,
+}
+
+impl<T: Real> UniformTable<T> {
+    /// Constructs the table of `values` at `start`, `start + step`, ...
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two values or `step` is not positive.
+    pub fn new(start: T, step: T, values: Vec<T>) -> Self {
+        assert!(values.len() >= 2, "a table must have at least two values");
+        assert!(step > T::from(0.0), "the step of a table must be positive");
+        let half = T::from(0.5);
+        let last = values.len() - 1;
+        let slopes = (0..values.len())
+            .map(|index| match index {
+                0 => values[1] - values[0],
+                index if index == last => values[last] - values[last - 1],
+                index => (values[index + 1] - values[index - 1]) * half,
+            })
+            .collect();
+        Self {
+            start,
+            step,
+            values: values.into_boxed_slice(),
+            slopes,
+        }
+    }
+
+    /// Returns the first point of the table.
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    /// Returns the last point of the table.
+    pub fn end(&self) -> T {
+        self.start + self.step * T::from((self.values.len() - 1) as f32)
+    }
+
+    /// Returns the value of the function at `x` and its derivative.
+    pub fn evaluate(&self, x: T) -> (T, T) {
+        let last = self.values.len() - 1;
+        let position = (x - self.start) / self.step;
+        if position < T::from(0.0) {
+            let slope = self.slopes[0] / self.step;
+            return (self.values[0] + slope * (x - self.start), slope);
+        }
+        if position >= T::from(last as f32) {
+            let slope = self.slopes[last] / self.step;
+            return (self.values[last] + slope * (x - self.end()), slope);
+        }
+        let index = position.to_usize().min(last - 1);
+        let (value, derivative) = hermite(
+            position - T::from(index as f32),
+            self.values[index],
+            self.values[index + 1],
+            self.slopes[index],
+            self.slopes[index + 1],
+        );
+        (value, derivative / self.step)
+    }
+}
+
+/// The condition fixing the two degrees of freedom a cubic spline leaves at its ends.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Boundary<T> {
+    /// The second derivative vanishes at both ends.
+    Natural,
+    /// The first derivative takes the given values at the first and the last knot,
+    /// e.g. 0 for a potential which must join a constant smoothly.
+    Clamped {
+        /// The derivative at the first knot.
+        start: T,
+        /// The derivative at the last knot.
+        end: T,
+    },
+}
+
+/// An interpolating cubic spline, whose value and first and second derivatives
+/// are continuous at the knots.
+///
+/// Beyond the ends of the knots the spline is continued linearly.
+#[derive(Clone, Debug)]
+pub struct CubicSpline<T> {
+    knots: Box<[T]>,
+    values: Box<[T]>,
+    second_derivatives: Box<[T]>,
+}
+
+impl<T: Real> CubicSpline<T> {
+    /// Constructs the spline through `values` at `knots` under `boundary`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two knots, their number differs from that of
+    /// the values or they are not strictly increasing.
+    pub fn new(knots: Vec<T>, values: Vec<T>, boundary: Boundary<T>) -> Self {
+        assert_knots(&knots, &values);
+        let zero = T::from(0.0);
+        let two = T::from(2.0);
+        let six = T::from(6.0);
+        let n = knots.len();
+        let width = |index: usize| knots[index + 1] - knots[index];
+        let secant = |index: usize| (values[index + 1] - values[index]) / width(index);
+        // The tridiagonal system of the continuity of the first derivative, solved by the
+        // Thomas algorithm: `lower[i] M[i-1] + diagonal[i] M[i] + upper[i] M[i+1] = rhs[i]`.
+        let mut diagonal = vec![zero; n];
+        let mut upper = vec![zero; n];
+        let mut rhs = vec![zero; n];
+        let lower = |index: usize| match (index, boundary) {
+            (index, _) if index + 1 < n => width(index - 1),
+            (_, Boundary::Natural) => zero,
+            (_, Boundary::Clamped { .. }) => width(n - 2),
+        };
+        match boundary {
+            Boundary::Natural => diagonal[0] = T::from(1.0),
+            Boundary::Clamped { start, .. } => {
+                diagonal[0] = two * width(0);
+                upper[0] = width(0);
+                rhs[0] = six * (secant(0) - start);
+            }
+        }
+        for index in 1..n - 1 {
+            diagonal[index] = two * (width(index - 1) + width(index));
+            upper[index] = width(index);
+            rhs[index] = six * (secant(index) - secant(index - 1));
+        }
+        match boundary {
+            Boundary::Natural => diagonal[n - 1] = T::from(1.0),
+            Boundary::Clamped { end, .. } => {
+                diagonal[n - 1] = two * width(n - 2);
+                rhs[n - 1] = six * (end - secant(n - 2));
+            }
+        }
+        for index in 1..n {
+            let factor = lower(index) / diagonal[index - 1];
+            diagonal[index] = diagonal[index] - factor * upper[index - 1];
+            rhs[index] = rhs[index] - factor * rhs[index - 1];
+        }
+        let mut second_derivatives = rhs;
+        second_derivatives[n - 1] = second_derivatives[n - 1] / diagonal[n - 1];
+        for index in (0..n - 1).rev() {
+            second_derivatives[index] = (second_derivatives[index]
+                - upper[index] * second_derivatives[index + 1])
+                / diagonal[index];
+        }
+        Self {
+            knots: knots.into_boxed_slice(),
+            values: values.into_boxed_slice(),
+            second_derivatives: second_derivatives.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the knots of the spline.
+    pub fn knots(&self) -> &[T] {
+        &self.knots
+    }
+
+    /// Returns the value of the spline at `x` and its derivative.
+    pub fn evaluate(&self, x: T) -> (T, T) {
+        let last = self.knots.len() - 1;
+        let sixth = T::from(1.0 / 6.0);
+        let segment = |index: usize, x: T| {
+            let width = self.knots[index + 1] - self.knots[index];
+            let (m0, m1) = (
+                self.second_derivatives[index],
+                self.second_derivatives[index + 1],
+            );
+            let a = (self.knots[index + 1] - x) / width;
+            let b = T::from(1.0) - a;
+            let value = a * self.values[index]
+                + b * self.values[index + 1]
+                + ((a * a * a - a) * m0 + (b * b * b - b) * m1) * width * width * sixth;
+            let derivative = (self.values[index + 1] - self.values[index]) / width
+                + ((T::from(3.0) * b * b - T::from(1.0)) * m1
+                    - (T::from(3.0) * a * a - T::from(1.0)) * m0)
+                    * width
+                    * sixth;
+            (value, derivative)
+        };
+        if x < self.knots[0] {
+            let (value, slope) = segment(0, self.knots[0]);
+            return (value + slope * (x - self.knots[0]), slope);
+        }
+        if x > self.knots[last] {
+            let (value, slope) = segment(last - 1, self.knots[last]);
+            return (value + slope * (x - self.knots[last]), slope);
+        }
+        segment(interval(&self.knots, x), x)
+    }
+}
+
+/// A cubic Hermite interpolant which preserves the monotonicity of the data,
+/// by the method of Fritsch and Carlson.
+///
+/// Between two knots the interpolant lies within their values, so it neither overshoots
+/// nor oscillates, at the price of a second derivative which jumps at the knots.
+/// Beyond the ends of the knots it is continued linearly.
+#[derive(Clone, Debug)]
+pub struct MonotoneCubic<T> {
+    knots: Box<[T]>,
+    values: Box<[T]>,
+    slopes: Box<[T]>,
+}
+
+impl<T: Real> MonotoneCubic<T> {
+    /// Constructs the interpolant of `values` at `knots`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer than two knots, their number differs from that of
+    /// the values or they are not strictly increasing.
+    pub fn new(knots: Vec<T>, values: Vec<T>) -> Self {
+        assert_knots(&knots, &values);
+        let zero = T::from(0.0);
+        let n = knots.len();
+        let width = |index: usize| knots[index + 1] - knots[index];
+        let secants: Vec<T> = (0..n - 1)
+            .map(|index| (values[index + 1] - values[index]) / width(index))
+            .collect();
+        let slopes = (0..n)
+            .map(|index| match index {
+                0 => secants[0],
+                index if index == n - 1 => secants[n - 2],
+                index => {
+                    let (left, right) = (secants[index - 1], secants[index]);
+                    if left * right <= zero {
+                        // An extremum of the data, where the interpolant must be flat.
+                        zero
+                    } else {
+                        // The weighted harmonic mean keeps the slope within three times
+                        // either secant, which is sufficient for monotonicity.
+                        let first = T::from(2.0) * width(index) + width(index - 1);
+                        let second = width(index) + T::from(2.0) * width(index - 1);
+                        (first + second) / (first / left + second / right)
+                    }
+                }
+            })
+            .collect();
+        Self {
+            knots: knots.into_boxed_slice(),
+            values: values.into_boxed_slice(),
+            slopes,
+        }
+    }
+
+    /// Returns the knots of the interpolant.
+    pub fn knots(&self) -> &[T] {
+        &self.knots
+    }
+
+    /// Returns the value of the interpolant at `x` and its derivative.
+    pub fn evaluate(&self, x: T) -> (T, T) {
+        let last = self.knots.len() - 1;
+        if x < self.knots[0] {
+            return (
+                self.values[0] + self.slopes[0] * (x - self.knots[0]),
+                self.slopes[0],
+            );
+        }
+        if x > self.knots[last] {
+            return (
+                self.values[last] + self.slopes[last] * (x - self.knots[last]),
+                self.slopes[last],
+            );
+        }
+        let index = interval(&self.knots, x);
+        let width = self.knots[index + 1] - self.knots[index];
+        let (value, derivative) = hermite(
+            (x - self.knots[index]) / width,
+            self.values[index],
+            self.values[index + 1],
+            self.slopes[index] * width,
+            self.slopes[index + 1] * width,
+        );
+        (value, derivative / width)
+    }
+}
+
+/// A function of two variables tabulated on a regular grid and interpolated bilinearly,
+/// e.g. a free energy surface or a bias over two collective variables.
+///
+/// Outside the grid the point is moved to the nearest edge, so the function is constant
+/// along the directions leaving the grid.
+#[derive(Clone, Debug)]
+pub struct BilinearGrid<T> {
+    origin: [T; 2],
+    step: [T; 2],
+    shape: [usize; 2],
+    values: Box<[T]>,
+}
+
+impl<T: Real> BilinearGrid<T> {
+    /// Constructs the grid of `shape` points starting at `origin` and spaced by `step`,
+    /// whose `values` are stored with the second coordinate varying fastest.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grid has fewer than two points along a direction, a step is not positive
+    /// or the number of values differs from the number of points.
+    pub fn new(origin: [T; 2], step: [T; 2], shape: [usize; 2], values: Vec<T>) -> Self {
+        assert!(
+            shape.iter().all(|&points| points >= 2),
+            "a grid must have at least two points along each direction"
+        );
+        assert!(
+            step.iter().all(|&step| step > T::from(0.0)),
+            "the steps of a grid must be positive"
+        );
+        assert_eq!(
+            values.len(),
+            shape[0] * shape[1],
+            "every point of the grid must have a value"
+        );
+        Self {
+            origin,
+            step,
+            shape,
+            values: values.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the number of points along each direction.
+    pub fn shape(&self) -> [usize; 2] {
+        self.shape
+    }
+
+    /// Returns the values, with the second coordinate varying fastest.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the values for modification, e.g. to add a Gaussian to a bias.
+    pub fn values_mut(&mut self) -> &mut [T] {
+        &mut self.values
+    }
+
+    /// Returns the value of the function at `point` and its gradient.
+    pub fn evaluate(&self, point: [T; 2]) -> (T, [T; 2]) {
+        let zero = T::from(0.0);
+        let one = T::from(1.0);
+        let mut indices = [0; 2];
+        let mut fractions = [zero; 2];
+        let mut inside = [true; 2];
+        for axis in 0..2 {
+            let last = T::from((self.shape[axis] - 1) as f32);
+            let mut position = (point[axis] - self.origin[axis]) / self.step[axis];
+            if position < zero {
+                position = zero;
+                inside[axis] = false;
+            } else if position > last {
+                position = last;
+                inside[axis] = false;
+            }
+            indices[axis] = position.to_usize().min(self.shape[axis] - 2);
+            fractions[axis] = position - T::from(indices[axis] as f32);
+        }
+        let at = |first: usize, second: usize| {
+            self.values[(indices[0] + first) * self.shape[1] + indices[1] + second]
+        };
+        let [u, v] = fractions;
+        let value = (one - u) * (one - v) * at(0, 0)
+            + (one - u) * v * at(0, 1)
+            + u * (one - v) * at(1, 0)
+            + u * v * at(1, 1);
+        let gradient = [
+            ((one - v) * (at(1, 0) - at(0, 0)) + v * (at(1, 1) - at(0, 1))) / self.step[0],
+            ((one - u) * (at(0, 1) - at(0, 0)) + u * (at(1, 1) - at(1, 0))) / self.step[1],
+        ];
+        let gradient = [
+            if inside[0] { gradient[0] } else { zero },
+            if inside[1] { gradient[1] } else { zero },
+        ];
+        (value, gradient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "{} instead of {}",
+            actual,
+            expected
+        );
+    }
+
+    /// Samples of `sin` on [0, 2] with the knots at `knots`.
+    fn sine(knots: &[f64]) -> Vec<f64> {
+        knots.iter().map(|knot| knot.sin()).collect()
+    }
+
+    fn knots(count: usize) -> Vec<f64> {
+        (0..count)
+            .map(|index| 2.0 * index as f64 / (count - 1) as f64)
+            .collect()
+    }
+
+    #[test]
+    fn interpolants_reproduce_a_smooth_function() {
+        let knots = knots(41);
+        let table = UniformTable::new(0.0, knots[1], sine(&knots));
+        let spline = CubicSpline::new(
+            knots.clone(),
+            sine(&knots),
+            Boundary::Clamped {
+                start: 1.0,
+                end: 2.0_f64.cos(),
+            },
+        );
+        let monotone = MonotoneCubic::new(knots.clone(), sine(&knots));
+        for index in 0..=200 {
+            let x = 0.01 * index as f64;
+            // The one-sided slopes at the ends of the table are only accurate to first order.
+            for (name, (value, derivative), tolerances) in [
+                ("table", table.evaluate(x), (1e-3, 3e-2)),
+                ("spline", spline.evaluate(x), (1e-5, 1e-4)),
+                ("monotone", monotone.evaluate(x), (1e-3, 3e-2)),
+            ] {
+                assert!(
+                    (value - x.sin()).abs() <= tolerances.0
+                        && (derivative - x.cos()).abs() <= tolerances.1,
+                    "{} at {}: ({}, {}) instead of ({}, {})",
+                    name,
+                    x,
+                    value,
+                    derivative,
+                    x.sin(),
+                    x.cos()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn interpolants_are_continued_linearly_at_both_ends() {
+        let knots = knots(11);
+        let table = UniformTable::new(0.0, knots[1], sine(&knots));
+        let spline = CubicSpline::new(knots.clone(), sine(&knots), Boundary::Natural);
+        let monotone = MonotoneCubic::new(knots.clone(), sine(&knots));
+        let evaluators: [&dyn Fn(f64) -> (f64, f64); 3] =
+            [&|x| table.evaluate(x), &|x| spline.evaluate(x), &|x| {
+                monotone.evaluate(x)
+            }];
+        for evaluate in evaluators {
+            for end in [0.0, 2.0] {
+                let (value, slope) = evaluate(end);
+                for offset in [0.25, 1.0, 3.0] {
+                    let x = if end == 0.0 { -offset } else { end + offset };
+                    let (extrapolated, derivative) = evaluate(x);
+                    assert_close(extrapolated, value + slope * (x - end), 1e-12);
+                    assert_close(derivative, slope, 1e-12);
+                }
+            }
+        }
+    }
+}
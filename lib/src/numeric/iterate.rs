@@ -0,0 +1,290 @@
+//! Fixed-point iteration with damping, Anderson acceleration and a history of the residuals.
+//!
+//! Self-consistent solvers, e.g. of induced dipoles, constraints or free energies, look for
+//! a vector `x` with `F(x) = x`. [`FixedPoint`] iterates such a map until the largest change
+//! of a component is tolerated by a [`TolerancePolicy`], optionally mixing only a fraction of
+//! the new vector into the old one or extrapolating from the previous iterates.
+
+use super::{Real, linalg::Qr};
+use crate::core::{error::NotConvergedError, tolerance::TolerancePolicy};
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+};
+
+/// How the next iterate is obtained from the current one and its image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Acceleration {
+    /// The damped image `x + β (F(x) - x)`.
+    None,
+    /// Anderson mixing, which extrapolates from the damped images of up to `depth`
+    /// previous iterates such that the residual is minimized in the least-squares sense.
+    Anderson {
+        /// The number of previous iterates extrapolated from.
+        depth: usize,
+    },
+}
+
+/// The residuals of the iterations of a solver, oldest first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConvergenceHistory<T> {
+    residuals: Vec<T>,
+}
+
+impl<T: Real> ConvergenceHistory<T> {
+    /// Returns the number of iterations.
+    pub fn iterations(&self) -> usize {
+        self.residuals.len()
+    }
+
+    /// Returns the residual, the largest change of a component, of every iteration.
+    pub fn residuals(&self) -> &[T] {
+        &self.residuals
+    }
+
+    /// Returns the residual of the last iteration.
+    pub fn last_residual(&self) -> Option<T> {
+        self.residuals.last().copied()
+    }
+
+    /// Returns the ratio of the last residual to the one before it,
+    /// which stays close to 1 when the iteration stagnates.
+    pub fn contraction(&self) -> Option<T> {
+        match self.residuals[..] {
+            [.., previous, last] => Some(last / previous),
+            _ => None,
+        }
+    }
+}
+
+/// A solver of fixed-point problems.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedPoint<T> {
+    tolerance: TolerancePolicy<T>,
+    damping: T,
+    acceleration: Acceleration,
+}
+
+impl<T: Real> FixedPoint<T> {
+    /// Constructs a solver which iterates the map without damping or acceleration
+    /// until `tolerance` is met, where the residual is the largest change of a component
+    /// in an iteration and the scale is the largest component.
+    pub fn new(tolerance: TolerancePolicy<T>) -> Self {
+        Self {
+            tolerance,
+            damping: T::from(1.0),
+            acceleration: Acceleration::None,
+        }
+    }
+
+    /// Sets the fraction `β` of the change proposed by the map which is applied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `damping` is not within (0, 1].
+    pub fn with_damping(mut self, damping: T) -> Self {
+        assert!(
+            damping > T::from(0.0) && damping <= T::from(1.0),
+            "the damping must be within (0, 1]"
+        );
+        self.damping = damping;
+        self
+    }
+
+    /// Sets how the next iterate is obtained.
+    ///
+    /// # Panics
+    ///
+    /// Panics if Anderson mixing is requested with a depth of zero.
+    pub fn with_acceleration(mut self, acceleration: Acceleration) -> Self {
+        assert!(
+            acceleration != Acceleration::Anderson { depth: 0 },
+            "Anderson mixing needs at least one previous iterate"
+        );
+        self.acceleration = acceleration;
+        self
+    }
+
+    /// Returns the convergence criteria.
+    pub fn tolerance(&self) -> &TolerancePolicy<T> {
+        &self.tolerance
+    }
+
+    /// Iterates `map`, which sets its second argument to the image of the first,
+    /// starting from `solution`, until the iteration converges.
+    ///
+    /// On success `solution` holds the image of the last iterate.
+    /// If `map` fails, the iteration stops with its error.
+    pub fn solve<F, E>(
+        &self,
+        solution: &mut [T],
+        mut map: F,
+    ) -> Result<ConvergenceHistory<T>, IterationError<T, E>>
+    where
+        F: FnMut(&[T], &mut [T]) -> Result<(), E>,
+    {
+        let zero = T::from(0.0);
+        let mut history = ConvergenceHistory {
+            residuals: Vec::new(),
+        };
+        let mut image = solution.to_vec();
+        let mut residual = vec![zero; solution.len()];
+        let mut anderson = match self.acceleration {
+            Acceleration::None => None,
+            Acceleration::Anderson { depth } => Some(AndersonState::new(depth)),
+        };
+        for _ in 0..self.tolerance.max_iterations {
+            map(solution, &mut image).map_err(IterationError::Map)?;
+            let mut largest_change = zero;
+            let mut scale = zero;
+            for ((change, &new), &old) in residual.iter_mut().zip(&image).zip(&*solution) {
+                *change = new - old;
+                if change.abs() > largest_change || !change.is_finite() {
+                    largest_change = change.abs();
+                }
+                if new.abs() > scale {
+                    scale = new.abs();
+                }
+            }
+            history.residuals.push(largest_change);
+            if !largest_change.is_finite() {
+                return Err(IterationError::Diverged(history));
+            }
+            if self.tolerance.is_converged(largest_change, scale) {
+                solution.copy_from_slice(&image);
+                return Ok(history);
+            }
+            match &mut anderson {
+                Some(anderson) => anderson.update(solution, &residual, self.damping),
+                None => {
+                    for (value, &change) in solution.iter_mut().zip(&residual) {
+                        *value = *value + self.damping * change;
+                    }
+                }
+            }
+        }
+        let residual = history.last_residual().unwrap_or(zero);
+        Err(IterationError::NotConverged(
+            self.tolerance.not_converged(residual),
+            history,
+        ))
+    }
+}
+
+/// The differences between consecutive iterates and between their residuals.
+struct AndersonState<T> {
+    depth: usize,
+    previous: Option<(Vec<T>, Vec<T>)>,
+    iterate_differences: VecDeque<Vec<T>>,
+    residual_differences: VecDeque<Vec<T>>,
+}
+
+impl<T: Real> AndersonState<T> {
+    fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            previous: None,
+            iterate_differences: VecDeque::with_capacity(depth),
+            residual_differences: VecDeque::with_capacity(depth),
+        }
+    }
+
+    /// Replaces `iterate` by the next iterate, given its `residual`.
+    fn update(&mut self, iterate: &mut [T], residual: &[T], damping: T) {
+        if let Some((previous_iterate, previous_residual)) = self.previous.take() {
+            if self.iterate_differences.len() == self.depth {
+                self.iterate_differences.pop_front();
+                self.residual_differences.pop_front();
+            }
+            self.iterate_differences.push_back(
+                iterate
+                    .iter()
+                    .zip(&previous_iterate)
+                    .map(|(&new, &old)| new - old)
+                    .collect(),
+            );
+            self.residual_differences.push_back(
+                residual
+                    .iter()
+                    .zip(&previous_residual)
+                    .map(|(&new, &old)| new - old)
+                    .collect(),
+            );
+        }
+        self.previous = Some((iterate.to_vec(), residual.to_vec()));
+        // A least-squares problem has at most as many unknowns as equations.
+        while self.residual_differences.len() > residual.len() {
+            self.iterate_differences.pop_front();
+            self.residual_differences.pop_front();
+        }
+        // γ minimizes |r - ΔR γ|.
+        let columns = self.residual_differences.len();
+        let matrix: Vec<T> = (0..residual.len() * columns)
+            .map(|index| self.residual_differences[index % columns][index / columns])
+            .collect();
+        let coefficients =
+            match Qr::new(&matrix, residual.len(), columns).solve_least_squares(residual) {
+                Ok(coefficients) => coefficients,
+                Err(_) => {
+                    // The previous iterates are degenerate, so start the extrapolation over.
+                    self.iterate_differences.clear();
+                    self.residual_differences.clear();
+                    Vec::new()
+                }
+            };
+        for (component, value) in iterate.iter_mut().enumerate() {
+            let mut next = *value + damping * residual[component];
+            for ((iterate_difference, residual_difference), &coefficient) in self
+                .iterate_differences
+                .iter()
+                .zip(&self.residual_differences)
+                .zip(&coefficients)
+            {
+                next = next
+                    - (iterate_difference[component] + damping * residual_difference[component])
+                        * coefficient;
+            }
+            *value = next;
+        }
+    }
+}
+
+/// An error raised by [`FixedPoint::solve`].
+#[derive(Clone, Debug)]
+pub enum IterationError<T, E> {
+    /// The map failed.
+    Map(E),
+    /// A component of an iterate became infinite or NaN.
+    Diverged(ConvergenceHistory<T>),
+    /// The iteration has exhausted its iterations.
+    NotConverged(NotConvergedError<T>, ConvergenceHistory<T>),
+}
+
+impl<T: Display, E: Display> Display for IterationError<T, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Map(err) => write!(f, "the iterated map failed: {}", err),
+            Self::Diverged(history) => write!(
+                f,
+                "the iteration diverged after {} iterations",
+                history.residuals.len()
+            ),
+            Self::NotConverged(err, _) => write!(f, "the iteration has {}", err),
+        }
+    }
+}
+
+impl<T, E> Error for IterationError<T, E>
+where
+    T: Debug + Display + 'static,
+    E: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Map(err) => Some(err),
+            Self::Diverged(_) => None,
+            Self::NotConverged(err, _) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,541 @@
+//! Decompositions of small dense matrices, such as Hessians, normal-mode transforms,
+//! the cells of triclinic boxes and least-squares fits.
+//!
+//! Matrices are slices of their elements in row-major order, so that `matrix[i * columns + j]`
+//! is the element in row `i` and column `j`. The algorithms are the textbook ones,
+//! meant for matrices of up to a few hundred rows, without external dependencies.
+
+use super::Real;
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// A matrix is numerically singular, or rank-deficient for a least-squares problem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SingularMatrixError {
+    column: usize,
+}
+
+impl SingularMatrixError {
+    /// Returns the column at which the decomposition found no usable pivot.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for SingularMatrixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "the matrix is singular at column {}", self.column)
+    }
+}
+
+impl Error for SingularMatrixError {}
+
+fn largest_magnitude<T: Real>(values: impl IntoIterator<Item = T>) -> T {
+    values.into_iter().fold(T::from(0.0), |largest, value| {
+        if value.abs() > largest {
+            value.abs()
+        } else {
+            largest
+        }
+    })
+}
+
+/// The LU decomposition `P A = L U` of a square matrix with partial pivoting.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lu<T> {
+    n: usize,
+    /// `L` below the diagonal, with an implicit unit diagonal, and `U` above and on it.
+    factors: Vec<T>,
+    /// The row of `A` at every row of `P A`.
+    permutation: Vec<usize>,
+    odd_permutation: bool,
+}
+
+impl<T: Real> Lu<T> {
+    /// Decomposes the `n`×`n` matrix `matrix`.
+    ///
+    /// Fails if a pivot is negligible compared to the largest element of the matrix.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `matrix` does not hold `n * n` elements.
+    pub fn new(matrix: &[T], n: usize) -> Result<Self, SingularMatrixError> {
+        assert_eq!(matrix.len(), n * n, "the matrix must be square");
+        let mut factors = matrix.to_vec();
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut odd_permutation = false;
+        let threshold = largest_magnitude(matrix.iter().copied()) * T::EPSILON * T::from(n as f32);
+        for column in 0..n {
+            let pivot = (column..n).fold(column, |pivot, row| {
+                if factors[row * n + column].abs() > factors[pivot * n + column].abs() {
+                    row
+                } else {
+                    pivot
+                }
+            });
+            let pivot_value = factors[pivot * n + column];
+            if pivot_value.abs() <= threshold || !pivot_value.is_finite() {
+                return Err(SingularMatrixError { column });
+            }
+            if pivot != column {
+                for k in 0..n {
+                    factors.swap(pivot * n + k, column * n + k);
+                }
+                permutation.swap(pivot, column);
+                odd_permutation = !odd_permutation;
+            }
+            for row in column + 1..n {
+                let factor = factors[row * n + column] / factors[column * n + column];
+                factors[row * n + column] = factor;
+                for k in column + 1..n {
+                    factors[row * n + k] = factors[row * n + k] - factor * factors[column * n + k];
+                }
+            }
+        }
+        Ok(Self {
+            n,
+            factors,
+            permutation,
+            odd_permutation,
+        })
+    }
+
+    /// Returns the number of rows and columns of the matrix.
+    pub fn size(&self) -> usize {
+        self.n
+    }
+
+    /// Replaces `rhs` by the solution `x` of `A x = rhs`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rhs` does not have an element per row.
+    pub fn solve(&self, rhs: &mut [T]) {
+        let n = self.n;
+        assert_eq!(rhs.len(), n, "there must be an element per row");
+        let permuted: Vec<T> = self.permutation.iter().map(|&row| rhs[row]).collect();
+        rhs.copy_from_slice(&permuted);
+        for row in 0..n {
+            for k in 0..row {
+                rhs[row] = rhs[row] - self.factors[row * n + k] * rhs[k];
+            }
+        }
+        for row in (0..n).rev() {
+            for k in row + 1..n {
+                rhs[row] = rhs[row] - self.factors[row * n + k] * rhs[k];
+            }
+            rhs[row] = rhs[row] / self.factors[row * n + row];
+        }
+    }
+
+    /// Returns the determinant of the matrix.
+    pub fn determinant(&self) -> T {
+        let product = (0..self.n).fold(T::from(1.0), |product, index| {
+            product * self.factors[index * self.n + index]
+        });
+        if self.odd_permutation {
+            -product
+        } else {
+            product
+        }
+    }
+
+    /// Returns the inverse of the matrix.
+    pub fn inverse(&self) -> Vec<T> {
+        let n = self.n;
+        let mut inverse = vec![T::from(0.0); n * n];
+        let mut column = vec![T::from(0.0); n];
+        for j in 0..n {
+            column.fill(T::from(0.0));
+            column[j] = T::from(1.0);
+            self.solve(&mut column);
+            for (i, &value) in column.iter().enumerate() {
+                inverse[i * n + j] = value;
+            }
+        }
+        inverse
+    }
+}
+
+/// The QR decomposition `A = Q R` of a matrix with at least as many rows as columns,
+/// by Householder reflections, where `Q` has orthonormal columns and `R` is upper triangular.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Qr<T> {
+    rows: usize,
+    columns: usize,
+    /// The vector of every reflection, over the rows from its column down.
+    reflections: Vec<Vec<T>>,
+    r: Vec<T>,
+}
+
+impl<T: Real> Qr<T> {
+    /// Decomposes the `rows`×`columns` matrix `matrix`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `matrix` does not hold `rows * columns` elements
+    /// or has fewer rows than columns.
+    pub fn new(matrix: &[T], rows: usize, columns: usize) -> Self {
+        assert_eq!(
+            matrix.len(),
+            rows * columns,
+            "the matrix must hold rows * columns elements"
+        );
+        assert!(
+            rows >= columns,
+            "the matrix must have at least as many rows as columns"
+        );
+        let zero = T::from(0.0);
+        let mut a = matrix.to_vec();
+        let mut reflections = Vec::with_capacity(columns);
+        for k in 0..columns {
+            let mut vector: Vec<T> = (k..rows).map(|row| a[row * columns + k]).collect();
+            let norm = vector
+                .iter()
+                .fold(zero, |sum, &value| sum + value * value)
+                .sqrt();
+            // The sign avoids cancellation in the first element of the reflection.
+            let alpha = if vector[0] > zero { -norm } else { norm };
+            vector[0] = vector[0] - alpha;
+            let norm_squared = vector.iter().fold(zero, |sum, &value| sum + value * value);
+            if norm_squared > zero {
+                for j in k..columns {
+                    let projection = (k..rows)
+                        .zip(&vector)
+                        .fold(zero, |sum, (row, &v)| sum + v * a[row * columns + j]);
+                    let factor = T::from(2.0) * projection / norm_squared;
+                    for (row, &v) in (k..rows).zip(&vector) {
+                        a[row * columns + j] = a[row * columns + j] - factor * v;
+                    }
+                }
+            }
+            reflections.push(vector);
+        }
+        let r = (0..columns * columns)
+            .map(|index| {
+                let (i, j) = (index / columns, index % columns);
+                if i <= j { a[i * columns + j] } else { zero }
+            })
+            .collect();
+        Self {
+            rows,
+            columns,
+            reflections,
+            r,
+        }
+    }
+
+    /// Applies the reflection of column `k` to `vector`, a column of length `rows`.
+    fn reflect(&self, k: usize, vector: &mut [T]) {
+        let zero = T::from(0.0);
+        let reflection = &self.reflections[k];
+        let norm_squared = reflection
+            .iter()
+            .fold(zero, |sum, &value| sum + value * value);
+        if norm_squared > zero {
+            let projection = reflection
+                .iter()
+                .zip(&vector[k..])
+                .fold(zero, |sum, (&v, &x)| sum + v * x);
+            let factor = T::from(2.0) * projection / norm_squared;
+            for (x, &v) in vector[k..].iter_mut().zip(reflection) {
+                *x = *x - factor * v;
+            }
+        }
+    }
+
+    /// Returns the `columns`×`columns` upper triangular factor `R`.
+    pub fn r(&self) -> &[T] {
+        &self.r
+    }
+
+    /// Returns the `rows`×`columns` factor `Q`, whose columns are orthonormal.
+    pub fn q(&self) -> Vec<T> {
+        let (rows, columns) = (self.rows, self.columns);
+        let mut q = vec![T::from(0.0); rows * columns];
+        let mut column = vec![T::from(0.0); rows];
+        for j in 0..columns {
+            column.fill(T::from(0.0));
+            column[j] = T::from(1.0);
+            for k in (0..columns).rev() {
+                self.reflect(k, &mut column);
+            }
+            for (i, &value) in column.iter().enumerate() {
+                q[i * columns + j] = value;
+            }
+        }
+        q
+    }
+
+    /// Returns the `x` minimizing `|A x - rhs|`, which solves `A x = rhs` for a square matrix.
+    ///
+    /// Fails if the columns of the matrix are numerically linearly dependent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rhs` does not have an element per row.
+    pub fn solve_least_squares(&self, rhs: &[T]) -> Result<Vec<T>, SingularMatrixError> {
+        let (rows, columns) = (self.rows, self.columns);
+        assert_eq!(rhs.len(), rows, "there must be an element per row");
+        let mut projected = rhs.to_vec();
+        for k in 0..columns {
+            self.reflect(k, &mut projected);
+        }
+        let threshold = largest_magnitude((0..columns).map(|i| self.r[i * columns + i]))
+            * T::EPSILON
+            * T::from(rows as f32);
+        let mut solution = projected[..columns].to_vec();
+        for row in (0..columns).rev() {
+            let diagonal = self.r[row * columns + row];
+            if diagonal.abs() <= threshold || !diagonal.is_finite() {
+                return Err(SingularMatrixError { column: row });
+            }
+            for k in row + 1..columns {
+                solution[row] = solution[row] - self.r[row * columns + k] * solution[k];
+            }
+            solution[row] = solution[row] / diagonal;
+        }
+        Ok(solution)
+    }
+}
+
+/// The eigenvalues and eigenvectors of a real symmetric matrix, by cyclic Jacobi rotations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymmetricEigen<T> {
+    n: usize,
+    values: Vec<T>,
+    vectors: Vec<T>,
+}
+
+impl<T: Real> SymmetricEigen<T> {
+    /// The number of sweeps over the off-diagonal elements after which the rotations stop.
+    pub const MAX_SWEEPS: usize = 100;
+
+    /// Decomposes the symmetric `n`×`n` matrix `matrix`, of which only
+    /// the elements above the diagonal and on it are read.
+    ///
+    /// The rotations stop once the off-diagonal elements are negligible
+    /// compared to the norm of the matrix, or after [`SymmetricEigen::MAX_SWEEPS`] sweeps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `matrix` does not hold `n * n` elements.
+    pub fn new(matrix: &[T], n: usize) -> Self {
+        assert_eq!(matrix.len(), n * n, "the matrix must be square");
+        let zero = T::from(0.0);
+        let mut a: Vec<T> = (0..n * n)
+            .map(|index| {
+                let (i, j) = (index / n, index % n);
+                if i <= j {
+                    matrix[index]
+                } else {
+                    matrix[j * n + i]
+                }
+            })
+            .collect();
+        let mut vectors: Vec<T> = (0..n * n)
+            .map(|index| T::from(if index / n == index % n { 1.0 } else { 0.0 }))
+            .collect();
+        let norm_squared = a.iter().fold(zero, |sum, &value| sum + value * value);
+        let threshold = norm_squared * T::EPSILON * T::EPSILON;
+        for _ in 0..Self::MAX_SWEEPS {
+            let off_diagonal = (0..n)
+                .flat_map(|p| (p + 1..n).map(move |q| (p, q)))
+                .fold(zero, |sum, (p, q)| sum + a[p * n + q] * a[p * n + q]);
+            if off_diagonal <= threshold {
+                break;
+            }
+            for p in 0..n {
+                for q in p + 1..n {
+                    let apq = a[p * n + q];
+                    if apq == zero {
+                        continue;
+                    }
+                    let theta = (a[q * n + q] - a[p * n + p]) / (T::from(2.0) * apq);
+                    let t = if theta == zero {
+                        T::from(1.0)
+                    } else {
+                        let t =
+                            T::from(1.0) / (theta.abs() + (theta * theta + T::from(1.0)).sqrt());
+                        if theta < zero { -t } else { t }
+                    };
+                    let c = T::from(1.0) / (t * t + T::from(1.0)).sqrt();
+                    let s = t * c;
+                    for k in 0..n {
+                        let (akp, akq) = (a[k * n + p], a[k * n + q]);
+                        a[k * n + p] = c * akp - s * akq;
+                        a[k * n + q] = s * akp + c * akq;
+                    }
+                    for k in 0..n {
+                        let (apk, aqk) = (a[p * n + k], a[q * n + k]);
+                        a[p * n + k] = c * apk - s * aqk;
+                        a[q * n + k] = s * apk + c * aqk;
+                    }
+                    for k in 0..n {
+                        let (vkp, vkq) = (vectors[k * n + p], vectors[k * n + q]);
+                        vectors[k * n + p] = c * vkp - s * vkq;
+                        vectors[k * n + q] = s * vkp + c * vkq;
+                    }
+                }
+            }
+        }
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&first, &second| {
+            a[first * n + first]
+                .partial_cmp(&a[second * n + second])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Self {
+            n,
+            values: order.iter().map(|&index| a[index * n + index]).collect(),
+            vectors: (0..n * n)
+                .map(|index| vectors[index / n * n + order[index % n]])
+                .collect(),
+        }
+    }
+
+    /// Returns the eigenvalues in ascending order.
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Returns the `n`×`n` matrix whose columns are the normalized eigenvectors,
+    /// in the order of the eigenvalues.
+    pub fn vectors(&self) -> &[T] {
+        &self.vectors
+    }
+
+    /// Returns the eigenvector of the eigenvalue at `index`.
+    pub fn vector(&self, index: usize) -> Vec<T> {
+        (0..self.n)
+            .map(|row| self.vectors[row * self.n + index])
+            .collect()
+    }
+
+    /// Returns the Moore-Penrose pseudo-inverse of the matrix, leaving out the eigenvalues
+    /// at most `relative_threshold` times the eigenvalue of the largest magnitude.
+    pub fn pseudo_inverse(&self, relative_threshold: T) -> Vec<T> {
+        let n = self.n;
+        let threshold = relative_threshold * largest_magnitude(self.values.iter().copied());
+        let mut pseudo_inverse = vec![T::from(0.0); n * n];
+        for (m, &value) in self.values.iter().enumerate() {
+            if value.abs() <= threshold {
+                continue;
+            }
+            for i in 0..n {
+                for j in 0..n {
+                    pseudo_inverse[i * n + j] = pseudo_inverse[i * n + j]
+                        + self.vectors[i * n + m] * self.vectors[j * n + m] / value;
+                }
+            }
+        }
+        pseudo_inverse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(a: &[f64], b: &[f64], rows: usize, inner: usize, columns: usize) -> Vec<f64> {
+        (0..rows * columns)
+            .map(|index| {
+                let (i, j) = (index / columns, index % columns);
+                (0..inner)
+                    .map(|k| a[i * inner + k] * b[k * columns + j])
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+        for (index, (a, e)) in actual.iter().zip(expected).enumerate() {
+            assert!(
+                (a - e).abs() <= tolerance * (1.0 + e.abs()),
+                "element {}: {} instead of {}",
+                index,
+                a,
+                e
+            );
+        }
+    }
+
+    #[test]
+    fn hilbert_system_is_solved() {
+        // The Hilbert matrix `1 / (i + j + 1)` of order 6 has a condition number of about 1.5e7.
+        let n = 6;
+        let hilbert: Vec<f64> = (0..n * n)
+            .map(|index| 1.0 / (index / n + index % n + 1) as f64)
+            .collect();
+        let expected: Vec<f64> = (0..n).map(|i| 1.0 + i as f64).collect();
+        let rhs = product(&hilbert, &expected, n, n, 1);
+
+        let mut solution = rhs.clone();
+        Lu::new(&hilbert, n).unwrap().solve(&mut solution);
+        assert_close(&solution, &expected, 1e-7);
+
+        let solution = Qr::new(&hilbert, n, n).solve_least_squares(&rhs).unwrap();
+        assert_close(&solution, &expected, 1e-7);
+    }
+
+    #[test]
+    fn rank_deficient_least_squares_is_rejected() {
+        // The third column is the sum of the first two.
+        let (rows, columns) = (4, 3);
+        let matrix = [
+            1.0, 2.0, 3.0, //
+            4.0, 5.0, 9.0, //
+            7.0, 8.0, 15.0, //
+            1.0, 0.0, 1.0,
+        ];
+        let qr = Qr::new(&matrix, rows, columns);
+        assert_close(
+            &product(&qr.q(), qr.r(), rows, columns, columns),
+            &matrix,
+            1e-12,
+        );
+        let q = qr.q();
+        let gram: Vec<f64> = (0..columns * columns)
+            .map(|index| {
+                let (i, j) = (index / columns, index % columns);
+                (0..rows)
+                    .map(|k| q[k * columns + i] * q[k * columns + j])
+                    .sum()
+            })
+            .collect();
+        assert_close(&gram, &[1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0], 1e-12);
+        assert_eq!(
+            qr.solve_least_squares(&[1.0, 2.0, 3.0, 4.0])
+                .unwrap_err()
+                .column(),
+            2
+        );
+    }
+
+    #[test]
+    fn tridiagonal_eigenvalues_are_exact() {
+        // The matrix with 2 on the diagonal and -1 next to it has the eigenvalues
+        // `2 - 2 cos(k π / (n + 1))` for `k` from 1 to `n`.
+        let n: usize = 8;
+        let matrix: Vec<f64> = (0..n * n)
+            .map(|index| match (index / n).abs_diff(index % n) {
+                0 => 2.0,
+                1 => -1.0,
+                _ => 0.0,
+            })
+            .collect();
+        let eigen = SymmetricEigen::new(&matrix, n);
+        let expected: Vec<f64> = (1..=n)
+            .map(|k| 2.0 - 2.0 * (k as f64 * std::f64::consts::PI / (n + 1) as f64).cos())
+            .collect();
+        assert_close(eigen.values(), &expected, 1e-12);
+        for (index, &value) in eigen.values().iter().enumerate() {
+            let vector = eigen.vector(index);
+            let image = product(&matrix, &vector, n, n, 1);
+            let scaled: Vec<f64> = vector.iter().map(|x| value * x).collect();
+            assert_close(&image, &scaled, 1e-12);
+        }
+    }
+}
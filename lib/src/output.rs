@@ -4,6 +4,32 @@ use std::ops::{Deref, DerefMut};
 
 use crate::core::{GroupTypeHandle, Vector};
 
+mod centroid;
+pub use centroid::{
+    CentroidForcesError, CentroidForcesOutput, receive_centroid_forces, send_centroid_forces,
+};
+
+mod provenance;
+pub use provenance::Provenance;
+pub(crate) use provenance::digest;
+
+mod resume;
+pub use resume::{OutputCursor, Resumable, ResumeError, ResumeState};
+
+mod trace;
+pub use trace::{
+    TRACE_VERSION, TraceError, TraceLevel, TraceReader, TraceRecord, TraceSettings, TraceWriter,
+    Tracer,
+};
+
+mod trajectory;
+pub use trajectory::{
+    BeadLabel, TrajectoryFrame, TrajectoryFrameError, TrajectoryLayout, TrajectoryOutput,
+};
+
+mod units;
+pub use units::{BaseUnit, ConversionError, Converted, Dimension, ObservableRegistry, UnitSystem};
+
 /// A trait for streams that write to coordinate files, such as '.xyz' files.
 pub trait VectorsOutput<const N: usize, T, V>
 where
@@ -21,6 +47,14 @@ pub trait ValuesOutput<T> {
     /// The type associated with an error returned by the implementor.
     type Error;
 
+    /// Writes the metadata of the run into the header of the file.
+    ///
+    /// Called once, before the first step. Streams without a header ignore it.
+    fn write_provenance(&mut self, provenance: &Provenance) -> Result<(), Self::Error> {
+        let _ = provenance;
+        Ok(())
+    }
+
     /// Writes the prelude.
     fn write_step(&mut self, step: usize) -> Result<(), Self::Error>;
 
@@ -31,6 +65,15 @@ pub trait ValuesOutput<T> {
     fn new_line(&mut self) -> Result<(), Self::Error>;
 }
 
+/// A trait for streams that write histograms, one line per step.
+pub trait HistogramOutput<T> {
+    /// The type associated with an error returned by the implementor.
+    type Error;
+
+    /// Writes the values of the bins of the histogram at this step.
+    fn write_histogram(&mut self, step: usize, bins: &[T]) -> Result<(), Self::Error>;
+}
+
 /// A struct which contains the estimators and the output stream.
 pub struct ObservablesOutput<T, U> {
     /// The estimators.
@@ -0,0 +1,92 @@
+//! Gathering of centroid forces for centroid molecular dynamics.
+
+use crate::core::{
+    error::EmptyError,
+    sync_ops::{SyncAddReciever, SyncAddSender},
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::Div,
+};
+
+/// A trait for streams that write the time series of the centroid forces.
+pub trait CentroidForcesOutput<T, V> {
+    /// The type associated with an error returned by the implementor.
+    type Error;
+
+    /// Writes the centroid forces of the atoms in a group.
+    fn write(
+        &mut self,
+        step: usize,
+        group_index: usize,
+        centroid_forces: &[V],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Sends the forces of this group in this image to the adder,
+/// one message per atom.
+///
+/// Every image of the group must call this function with the
+/// same number of atoms for [`receive_centroid_forces`] to
+/// gather meaningful values.
+pub fn send_centroid_forces<V, S>(adder: &mut S, group_forces: &[V]) -> Result<(), S::Error>
+where
+    V: Clone,
+    S: SyncAddSender<V> + ?Sized,
+{
+    for force in group_forces {
+        adder.send(force.clone())?;
+    }
+    Ok(())
+}
+
+/// Receives the forces sent by [`send_centroid_forces`] and averages
+/// them over the images, writing the centroid forces of the group into
+/// `centroid_forces`.
+pub fn receive_centroid_forces<T, V, R>(
+    receiver: &mut R,
+    images: T,
+    centroid_forces: &mut [V],
+) -> Result<(), CentroidForcesError<R::Error>>
+where
+    T: Clone,
+    V: Div<T, Output = V>,
+    R: SyncAddReciever<V> + ?Sized,
+{
+    for centroid_force in centroid_forces {
+        let sum = receiver
+            .receive_sum()
+            .map_err(CentroidForcesError::Comm)?
+            .ok_or(CentroidForcesError::Empty(EmptyError))?;
+        *centroid_force = sum / images.clone();
+    }
+    Ok(())
+}
+
+/// An error returned by [`receive_centroid_forces`].
+#[derive(Clone, Debug)]
+pub enum CentroidForcesError<E> {
+    /// The receiver failed.
+    Comm(E),
+    /// No image sent a force for an atom.
+    Empty(EmptyError),
+}
+
+impl<E: Display> Display for CentroidForcesError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Comm(err) => write!(f, "failed to receive centroid forces: {}", err),
+            Self::Empty(_) => write!(f, "no image sent a force"),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for CentroidForcesError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Comm(err) => Some(err),
+            Self::Empty(err) => Some(err),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Metadata describing how the data in an output file was produced.
+
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, Write},
+};
+
+/// The metadata of a run, collected by the driver before the simulation starts
+/// and written into the header of every output file.
+///
+/// Together with the configuration it was digested from, it suffices to repeat the run
+/// or to tell which runs a set of files came from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Provenance {
+    version: String,
+    git_hash: Option<String>,
+    config_digest: Option<u64>,
+    seeds: Vec<(String, u64)>,
+    threads: usize,
+    replicas: usize,
+}
+
+impl Provenance {
+    /// Constructs the metadata of a run of the given version of the crate,
+    /// e.g. `env!("CARGO_PKG_VERSION")`.
+    pub fn new(version: &str) -> Self {
+        Self {
+            version: version.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the commit the binary was built from.
+    pub fn with_git_hash(self, git_hash: &str) -> Self {
+        Self {
+            git_hash: Some(git_hash.to_owned()),
+            ..self
+        }
+    }
+
+    /// Sets the digest of the configuration to that of its raw contents.
+    pub fn with_config(self, config: &[u8]) -> Self {
+        Self {
+            config_digest: Some(digest(config)),
+            ..self
+        }
+    }
+
+    /// Sets the number of threads and of replicas of the simulation.
+    pub fn with_parallelism(self, threads: usize, replicas: usize) -> Self {
+        Self {
+            threads,
+            replicas,
+            ..self
+        }
+    }
+
+    /// Records the seed of the random number generator of `component`, e.g. "thermostat".
+    ///
+    /// # Panics
+    ///
+    /// Panics if `component` contains whitespace or '='.
+    pub fn add_seed(&mut self, component: &str, seed: u64) {
+        assert!(
+            !component.is_empty() && !component.contains(|c: char| c.is_whitespace() || c == '='),
+            "the name of a component must be a single word without '='"
+        );
+        match self.seeds.iter_mut().find(|(name, _)| name == component) {
+            Some((_, recorded)) => *recorded = seed,
+            None => self.seeds.push((component.to_owned(), seed)),
+        }
+    }
+
+    /// Returns the version of the crate.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Returns the commit the binary was built from, if known.
+    pub fn git_hash(&self) -> Option<&str> {
+        self.git_hash.as_deref()
+    }
+
+    /// Returns the digest of the configuration, if any.
+    pub fn config_digest(&self) -> Option<u64> {
+        self.config_digest
+    }
+
+    /// Returns the seeds in the order they were recorded in.
+    pub fn seeds(&self) -> &[(String, u64)] {
+        &self.seeds
+    }
+
+    /// Returns the number of threads.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Returns the number of replicas.
+    pub fn replicas(&self) -> usize {
+        self.replicas
+    }
+
+    /// Returns the metadata as `key=value` pairs with values free of whitespace.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries = vec![("version".to_owned(), self.version.clone())];
+        if let Some(git_hash) = &self.git_hash {
+            entries.push(("git_hash".to_owned(), git_hash.clone()));
+        }
+        if let Some(config_digest) = self.config_digest {
+            entries.push((
+                "config_digest".to_owned(),
+                format!("{:016x}", config_digest),
+            ));
+        }
+        entries.push(("threads".to_owned(), self.threads.to_string()));
+        entries.push(("replicas".to_owned(), self.replicas.to_string()));
+        entries.extend(
+            self.seeds
+                .iter()
+                .map(|(component, seed)| (format!("seed.{}", component), seed.to_string())),
+        );
+        entries
+    }
+
+    /// Writes the metadata to `writer`, one `key=value` pair per line preceded by `prefix`,
+    /// e.g. "# " for the header of a table.
+    pub fn write_header<W: Write + ?Sized>(&self, writer: &mut W, prefix: &str) -> io::Result<()> {
+        for (key, value) in self.entries() {
+            writeln!(writer, "{}{}={}", prefix, key, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let entries = self.entries();
+        let mut entries = entries.iter();
+        if let Some((key, value)) = entries.next() {
+            write!(f, "{}={}", key, value)?;
+        }
+        for (key, value) in entries {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, which, unlike the hasher of the standard library,
+/// is guaranteed to stay the same across releases and platforms.
+pub(crate) fn digest(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
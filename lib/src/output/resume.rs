@@ -0,0 +1,314 @@
+//! Down-sampled output streams which continue where they left off after a restart.
+//!
+//! A checkpoint is taken at some step while the streams may already have written
+//! frames of later steps, or not yet written the frame of the current sampling interval.
+//! Storing the last step written by every stream, together with the length of its file
+//! at that moment, in the checkpoint lets a resumed run truncate the file to that length
+//! and skip every step the stream has already written.
+
+use super::{HistogramOutput, Provenance, TrajectoryFrame, TrajectoryOutput, ValuesOutput};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, BufRead, Write},
+    num::NonZero,
+};
+
+/// The position of a stream at the moment a checkpoint was taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OutputCursor {
+    /// The last step written to the stream, if any.
+    pub step: Option<usize>,
+    /// The number of bytes in the file of the stream once the last step was written.
+    pub offset: u64,
+}
+
+/// The cursors of every stream of a simulation, stored in its checkpoint.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResumeState {
+    cursors: Vec<(String, OutputCursor)>,
+}
+
+impl ResumeState {
+    /// Constructs the state of a run without streams.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the cursor of the stream named `name`, e.g. "trajectory" or "observables.0".
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is empty or contains whitespace.
+    pub fn set(&mut self, name: &str, cursor: OutputCursor) {
+        assert!(
+            !name.is_empty() && !name.contains(char::is_whitespace),
+            "the name of a stream must be a single word"
+        );
+        match self.cursors.iter_mut().find(|(stream, _)| stream == name) {
+            Some((_, recorded)) => *recorded = cursor,
+            None => self.cursors.push((name.to_owned(), cursor)),
+        }
+    }
+
+    /// Returns the cursor of the stream named `name`,
+    /// or `None` if the stream did not exist when the checkpoint was taken.
+    pub fn get(&self, name: &str) -> Option<OutputCursor> {
+        self.cursors
+            .iter()
+            .find(|(stream, _)| stream == name)
+            .map(|(_, cursor)| *cursor)
+    }
+
+    /// Returns the cursors in the order they were recorded in.
+    pub fn cursors(&self) -> &[(String, OutputCursor)] {
+        &self.cursors
+    }
+
+    /// Writes the cursors to `writer` as a tab-separated table, one stream per line,
+    /// with '-' in place of the step of a stream that has written nothing.
+    pub fn write_checkpoint<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "# stream\tstep\toffset")?;
+        for (name, cursor) in &self.cursors {
+            match cursor.step {
+                Some(step) => writeln!(writer, "{}\t{}\t{}", name, step, cursor.offset)?,
+                None => writeln!(writer, "{}\t-\t{}", name, cursor.offset)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the cursors from a table written by [`ResumeState::write_checkpoint`].
+    pub fn read_checkpoint<R: BufRead>(reader: R) -> Result<Self, ResumeError> {
+        let mut state = Self::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line_number = number + 1;
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [name, step, offset] = fields[..] else {
+                return Err(ResumeError::Parse { line: line_number });
+            };
+            let step = match step {
+                "-" => None,
+                step => Some(
+                    step.parse()
+                        .map_err(|_| ResumeError::Parse { line: line_number })?,
+                ),
+            };
+            let offset = offset
+                .parse()
+                .map_err(|_| ResumeError::Parse { line: line_number })?;
+            if name.is_empty() || state.get(name).is_some() {
+                return Err(ResumeError::Duplicate { line: line_number });
+            }
+            state
+                .cursors
+                .push((name.to_owned(), OutputCursor { step, offset }));
+        }
+        Ok(state)
+    }
+}
+
+/// An error raised while reading the cursors of a checkpoint.
+#[derive(Debug)]
+pub enum ResumeError {
+    /// Reading failed.
+    Io(io::Error),
+    /// The line at the given number could not be parsed.
+    Parse {
+        /// The number of the line, starting at 1.
+        line: usize,
+    },
+    /// The stream at the given line is unnamed or has already been listed.
+    Duplicate {
+        /// The number of the line, starting at 1.
+        line: usize,
+    },
+}
+
+impl From<io::Error> for ResumeError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Display for ResumeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Io(err) => write!(f, "failed to read the output cursors: {}", err),
+            Self::Parse { line } => write!(f, "malformed output cursor at line {}", line),
+            Self::Duplicate { line } => {
+                write!(f, "the output cursor at line {} is listed twice", line)
+            }
+        }
+    }
+}
+
+impl Error for ResumeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Parse { .. } | Self::Duplicate { .. } => None,
+        }
+    }
+}
+
+/// A stream which writes every `stride`-th step and skips the steps
+/// it has written before a restart.
+///
+/// Steps are sampled by their absolute number, so the sampling stays aligned
+/// across restarts regardless of the step the checkpoint was taken at.
+/// The header of a resumed stream is already in its file unless the file was empty,
+/// so the provenance is not written again.
+#[derive(Clone, Debug)]
+pub struct Resumable<O> {
+    stream: O,
+    stride: NonZero<usize>,
+    last_step: Option<usize>,
+    resumed: bool,
+    skipping: bool,
+}
+
+impl<O> Resumable<O> {
+    /// Wraps a fresh `stream` to write every step.
+    pub fn new(stream: O) -> Self {
+        Self {
+            stream,
+            stride: NonZero::<usize>::MIN,
+            last_step: None,
+            resumed: false,
+            skipping: false,
+        }
+    }
+
+    /// Wraps `stream`, whose file has been truncated to the offset of `cursor`,
+    /// to continue after the last step of `cursor`.
+    pub fn resume(stream: O, cursor: OutputCursor) -> Self {
+        Self {
+            last_step: cursor.step,
+            resumed: cursor.offset > 0,
+            ..Self::new(stream)
+        }
+    }
+
+    /// Writes only the steps which are multiples of `stride`.
+    pub fn with_stride(self, stride: NonZero<usize>) -> Self {
+        Self { stride, ..self }
+    }
+
+    /// Returns the last step written to the stream, if any.
+    pub fn last_step(&self) -> Option<usize> {
+        self.last_step
+    }
+
+    /// Returns the cursor of the stream, given the number of bytes in its file,
+    /// which must have been flushed.
+    pub fn cursor(&self, offset: u64) -> OutputCursor {
+        OutputCursor {
+            step: self.last_step,
+            offset,
+        }
+    }
+
+    /// Returns whether `step` is to be written.
+    pub fn samples(&self, step: usize) -> bool {
+        step % self.stride == 0 && self.last_step.is_none_or(|last| step > last)
+    }
+
+    /// Returns a reference to the inner stream.
+    pub fn get_ref(&self) -> &O {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the inner stream.
+    pub fn get_mut(&mut self) -> &mut O {
+        &mut self.stream
+    }
+
+    /// Returns the inner stream.
+    pub fn into_inner(self) -> O {
+        self.stream
+    }
+}
+
+impl<O, V> TrajectoryOutput<V> for Resumable<O>
+where
+    O: TrajectoryOutput<V>,
+{
+    type Error = O::Error;
+
+    fn write_provenance(&mut self, provenance: &Provenance) -> Result<(), Self::Error> {
+        if self.resumed {
+            return Ok(());
+        }
+        self.stream.write_provenance(provenance)
+    }
+
+    fn write_frame(&mut self, step: usize, frame: &TrajectoryFrame<V>) -> Result<(), Self::Error> {
+        if !self.samples(step) {
+            return Ok(());
+        }
+        self.stream.write_frame(step, frame)?;
+        self.last_step = Some(step);
+        Ok(())
+    }
+}
+
+impl<O, T> ValuesOutput<T> for Resumable<O>
+where
+    O: ValuesOutput<T>,
+{
+    type Error = O::Error;
+
+    fn write_provenance(&mut self, provenance: &Provenance) -> Result<(), Self::Error> {
+        if self.resumed {
+            return Ok(());
+        }
+        self.stream.write_provenance(provenance)
+    }
+
+    fn write_step(&mut self, step: usize) -> Result<(), Self::Error> {
+        self.skipping = !self.samples(step);
+        if self.skipping {
+            return Ok(());
+        }
+        self.stream.write_step(step)?;
+        self.last_step = Some(step);
+        Ok(())
+    }
+
+    fn write_value(&mut self, value: T) -> Result<(), Self::Error> {
+        if self.skipping {
+            return Ok(());
+        }
+        self.stream.write_value(value)
+    }
+
+    fn new_line(&mut self) -> Result<(), Self::Error> {
+        if self.skipping {
+            self.skipping = false;
+            return Ok(());
+        }
+        self.stream.new_line()
+    }
+}
+
+impl<O, T> HistogramOutput<T> for Resumable<O>
+where
+    O: HistogramOutput<T>,
+{
+    type Error = O::Error;
+
+    fn write_histogram(&mut self, step: usize, bins: &[T]) -> Result<(), Self::Error> {
+        if !self.samples(step) {
+            return Ok(());
+        }
+        self.stream.write_histogram(step, bins)?;
+        self.last_step = Some(step);
+        Ok(())
+    }
+}
@@ -0,0 +1,375 @@
+//! A compact binary trace of per-step numerical quantities, for diagnosing instabilities.
+//!
+//! When tracing is enabled for a range of steps, every replica records, for every group,
+//! its energies and, depending on the [`TraceLevel`], the largest force on its atoms and
+//! the kinetic energy its thermostat added. The records are buffered by a [`Tracer`]
+//! owned by the replica, so the hot loop does no I/O, and appended by the driver
+//! to a [`TraceWriter`] between steps.
+//!
+//! The layout of a trace is, with every number in little endian:
+//!
+//! | field | type |
+//! |-------|------|
+//! | magic | `b"RPTR"` |
+//! | format version | `u32` |
+//! | level | `u8`, 1 to 3 |
+//! | first and past-the-last step | `u64`, `u64` |
+//! | records | step `u64`, replica `u32`, group `u32`, then one `f64` per quantity of the level |
+
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, Read, Write},
+    ops::Range,
+};
+
+/// The version of the layout of a trace.
+pub const TRACE_VERSION: u32 = 1;
+
+const MAGIC: [u8; 4] = *b"RPTR";
+
+/// The quantities recorded in a trace, each level including those of the levels below.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TraceLevel {
+    /// The potential and kinetic energies.
+    #[default]
+    Energies,
+    /// The energies and the magnitude of the largest force.
+    Forces,
+    /// The energies, the largest force and the kinetic energy added by the thermostat.
+    Thermostat,
+}
+
+impl TraceLevel {
+    fn tag(self) -> u8 {
+        match self {
+            Self::Energies => 1,
+            Self::Forces => 2,
+            Self::Thermostat => 3,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(Self::Energies),
+            2 => Some(Self::Forces),
+            3 => Some(Self::Thermostat),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of quantities in a record of this level.
+    pub const fn quantities(self) -> usize {
+        match self {
+            Self::Energies => 2,
+            Self::Forces => 3,
+            Self::Thermostat => 4,
+        }
+    }
+
+    fn record_size(self) -> usize {
+        16 + 8 * self.quantities()
+    }
+}
+
+/// The level of a trace and the steps it covers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceSettings {
+    level: TraceLevel,
+    steps: Range<usize>,
+}
+
+impl TraceSettings {
+    /// Constructs settings tracing the quantities of `level` during `steps`.
+    pub fn new(level: TraceLevel, steps: Range<usize>) -> Self {
+        Self { level, steps }
+    }
+
+    /// Returns the level of the trace.
+    pub fn level(&self) -> TraceLevel {
+        self.level
+    }
+
+    /// Returns the steps covered by the trace.
+    pub fn steps(&self) -> &Range<usize> {
+        &self.steps
+    }
+
+    /// Returns whether `step` is traced.
+    pub fn contains(&self, step: usize) -> bool {
+        self.steps.contains(&step)
+    }
+}
+
+/// The quantities of a group in a replica at a step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceRecord<T> {
+    /// The step.
+    pub step: usize,
+    /// The index of the replica.
+    pub replica: usize,
+    /// The index of the group.
+    pub group: usize,
+    /// The potential energy of the group.
+    pub potential_energy: T,
+    /// The kinetic energy of the group.
+    pub kinetic_energy: T,
+    /// The magnitude of the largest force on an atom of the group,
+    /// from [`TraceLevel::Forces`] up.
+    pub max_force: Option<T>,
+    /// The kinetic energy added to the group by the thermostat during the step,
+    /// at [`TraceLevel::Thermostat`].
+    pub thermostat_increment: Option<T>,
+}
+
+/// The records of a replica, buffered until the driver appends them to the trace.
+#[derive(Clone, Debug)]
+pub struct Tracer<T> {
+    settings: TraceSettings,
+    replica: usize,
+    records: Vec<TraceRecord<T>>,
+}
+
+impl<T> Tracer<T> {
+    /// Constructs a tracer of the replica at index `replica`.
+    pub fn new(settings: TraceSettings, replica: usize) -> Self {
+        Self {
+            settings,
+            replica,
+            records: Vec::new(),
+        }
+    }
+
+    /// Returns the settings of the trace.
+    pub fn settings(&self) -> &TraceSettings {
+        &self.settings
+    }
+
+    /// Returns whether `step` is traced, so that the quantities which are not computed
+    /// otherwise, such as the largest force, are only computed when they are recorded.
+    pub fn is_active(&self, step: usize) -> bool {
+        self.settings.contains(step)
+    }
+
+    /// Records the quantities of `group` at `step`, if the step is traced.
+    ///
+    /// The quantities above the level of the trace are dropped.
+    pub fn record(
+        &mut self,
+        step: usize,
+        group: usize,
+        potential_energy: T,
+        kinetic_energy: T,
+        max_force: Option<T>,
+        thermostat_increment: Option<T>,
+    ) {
+        if !self.is_active(step) {
+            return;
+        }
+        let level = self.settings.level;
+        self.records.push(TraceRecord {
+            step,
+            replica: self.replica,
+            group,
+            potential_energy,
+            kinetic_energy,
+            max_force: max_force.filter(|_| level >= TraceLevel::Forces),
+            thermostat_increment: thermostat_increment.filter(|_| level >= TraceLevel::Thermostat),
+        });
+    }
+
+    /// Returns the buffered records, oldest first, and clears the buffer.
+    pub fn take_records(&mut self) -> Vec<TraceRecord<T>> {
+        std::mem::take(&mut self.records)
+    }
+}
+
+/// Appends records to a trace.
+pub struct TraceWriter<W: Write> {
+    writer: W,
+    level: TraceLevel,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> TraceWriter<W> {
+    /// Writes the header of a trace with `settings` to `writer`.
+    pub fn new(mut writer: W, settings: &TraceSettings) -> io::Result<Self> {
+        let mut header = Vec::with_capacity(25);
+        header.extend_from_slice(&MAGIC);
+        header.extend_from_slice(&TRACE_VERSION.to_le_bytes());
+        header.push(settings.level.tag());
+        header.extend_from_slice(&(settings.steps.start as u64).to_le_bytes());
+        header.extend_from_slice(&(settings.steps.end as u64).to_le_bytes());
+        writer.write_all(&header)?;
+        Ok(Self {
+            writer,
+            level: settings.level,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Appends `records` to the trace.
+    ///
+    /// A quantity of the level which is absent from a record is written as NaN.
+    pub fn write<T: Copy + Into<f64>>(&mut self, records: &[TraceRecord<T>]) -> io::Result<()> {
+        self.buffer.clear();
+        for record in records {
+            self.buffer
+                .extend_from_slice(&(record.step as u64).to_le_bytes());
+            self.buffer
+                .extend_from_slice(&(record.replica as u32).to_le_bytes());
+            self.buffer
+                .extend_from_slice(&(record.group as u32).to_le_bytes());
+            let quantities = [
+                Some(record.potential_energy),
+                Some(record.kinetic_energy),
+                record.max_force,
+                record.thermostat_increment,
+            ];
+            for quantity in &quantities[..self.level.quantities()] {
+                let value = quantity.map_or(f64::NAN, Into::into);
+                self.buffer.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        self.writer.write_all(&self.buffer)
+    }
+
+    /// Flushes the trace and returns the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads the records of a trace written by a [`TraceWriter`], in the order they were written.
+pub struct TraceReader<R: Read> {
+    reader: R,
+    settings: TraceSettings,
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> TraceReader<R> {
+    /// Reads the header of the trace in `reader`.
+    pub fn new(mut reader: R) -> Result<Self, TraceError> {
+        let mut header = [0; 25];
+        read_full(&mut reader, &mut header)?
+            .then_some(())
+            .ok_or(TraceError::Truncated)?;
+        if header[..4] != MAGIC {
+            return Err(TraceError::NotATrace);
+        }
+        let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        if version > TRACE_VERSION {
+            return Err(TraceError::Version {
+                found: version,
+                supported: TRACE_VERSION,
+            });
+        }
+        let level = TraceLevel::from_tag(header[8]).ok_or(TraceError::Level(header[8]))?;
+        let start = u64::from_le_bytes(header[9..17].try_into().unwrap()) as usize;
+        let end = u64::from_le_bytes(header[17..25].try_into().unwrap()) as usize;
+        Ok(Self {
+            reader,
+            settings: TraceSettings::new(level, start..end),
+            buffer: vec![0; level.record_size()],
+        })
+    }
+
+    /// Returns the settings the trace was written with.
+    pub fn settings(&self) -> &TraceSettings {
+        &self.settings
+    }
+}
+
+// Returns `false` if the reader ends before the first byte and fails if it ends after it.
+fn read_full<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<bool, TraceError> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(TraceError::Truncated),
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(true)
+}
+
+impl<R: Read> Iterator for TraceReader<R> {
+    type Item = Result<TraceRecord<f64>, TraceError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match read_full(&mut self.reader, &mut self.buffer) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(err) => return Some(Err(err)),
+        }
+        let bytes = &self.buffer;
+        let quantity = |index: usize| {
+            let offset = 16 + 8 * index;
+            f64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+        };
+        let quantities = self.settings.level.quantities();
+        Some(Ok(TraceRecord {
+            step: u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize,
+            replica: u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize,
+            group: u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize,
+            potential_energy: quantity(0),
+            kinetic_energy: quantity(1),
+            max_force: (quantities > 2).then(|| quantity(2)),
+            thermostat_increment: (quantities > 3).then(|| quantity(3)),
+        }))
+    }
+}
+
+/// An error raised while reading a trace.
+#[derive(Debug)]
+pub enum TraceError {
+    /// Reading failed.
+    Io(io::Error),
+    /// The file does not start with the magic of a trace.
+    NotATrace,
+    /// The trace was written by a newer version of the crate.
+    Version {
+        /// The version of the layout of the trace.
+        found: u32,
+        /// The latest supported version.
+        supported: u32,
+    },
+    /// The level of the trace is unknown.
+    Level(u8),
+    /// The trace ends in the middle of its header or of a record.
+    Truncated,
+}
+
+impl From<io::Error> for TraceError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Io(err) => write!(f, "failed to read the trace: {}", err),
+            Self::NotATrace => write!(f, "the file is not a trace"),
+            Self::Version { found, supported } => write!(
+                f,
+                "the trace has version {}, newer than the supported {}",
+                found, supported
+            ),
+            Self::Level(tag) => write!(f, "unknown trace level {}", tag),
+            Self::Truncated => write!(f, "the trace is truncated"),
+        }
+    }
+}
+
+impl Error for TraceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Selection of the replicas written to trajectory files.
+
+use super::Provenance;
+use crate::core::error::{EmptyError, InvalidIndexError};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{AddAssign, Div},
+};
+
+/// Which replicas of the atoms are written to a trajectory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TrajectoryLayout {
+    /// The centroid of every atom, averaged over the images.
+    #[default]
+    Centroid,
+    /// The replica of every atom in a single image.
+    Replica(usize),
+    /// The replicas of every atom in all images, one image after another.
+    AllBeads,
+}
+
+impl TrajectoryLayout {
+    /// Returns the number of entries in a frame of `atoms` atoms in `images` images.
+    pub const fn entries(&self, atoms: usize, images: usize) -> usize {
+        match self {
+            Self::Centroid | Self::Replica(_) => atoms,
+            Self::AllBeads => atoms * images,
+        }
+    }
+
+    /// Returns whether the positions in `image` are needed to build a frame.
+    pub const fn reads_image(&self, image: usize) -> bool {
+        match *self {
+            Self::Centroid | Self::AllBeads => true,
+            Self::Replica(replica) => replica == image,
+        }
+    }
+}
+
+/// A trait for streams that write trajectory frames, such as '.xyz' files.
+pub trait TrajectoryOutput<V> {
+    /// The type associated with an error returned by the implementor.
+    type Error;
+
+    /// Writes the metadata of the run into the header of the file.
+    ///
+    /// Called once, before the first frame. Streams without a header ignore it.
+    fn write_provenance(&mut self, provenance: &Provenance) -> Result<(), Self::Error> {
+        let _ = provenance;
+        Ok(())
+    }
+
+    /// Writes the frame of this step to the stream.
+    fn write_frame(&mut self, step: usize, frame: &TrajectoryFrame<V>) -> Result<(), Self::Error>;
+}
+
+/// The atom and the image an entry of a frame belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BeadLabel {
+    /// The index of the atom in the system.
+    pub atom: usize,
+    /// The image of the replica, or `None` for a centroid.
+    pub image: Option<usize>,
+}
+
+/// The vectors written to a trajectory in a single step, together with their labels.
+#[derive(Clone, Debug)]
+pub struct TrajectoryFrame<V> {
+    pub(crate) layout: TrajectoryLayout,
+    pub(crate) labels: Vec<BeadLabel>,
+    pub(crate) vectors: Vec<V>,
+}
+
+impl<V> TrajectoryFrame<V> {
+    /// Constructs an empty frame.
+    pub fn new(layout: TrajectoryLayout) -> Self {
+        Self {
+            layout,
+            labels: Vec::new(),
+            vectors: Vec::new(),
+        }
+    }
+
+    /// Returns the layout of the frame.
+    pub fn layout(&self) -> TrajectoryLayout {
+        self.layout
+    }
+
+    /// Returns the labels of the entries.
+    pub fn labels(&self) -> &[BeadLabel] {
+        &self.labels
+    }
+
+    /// Returns the vectors of the entries.
+    pub fn vectors(&self) -> &[V] {
+        &self.vectors
+    }
+
+    /// Returns an iterator over the labelled entries.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&BeadLabel, &V)> {
+        self.labels.iter().zip(&self.vectors)
+    }
+}
+
+impl<V: Clone> TrajectoryFrame<V> {
+    /// Rebuilds the frame from the vectors of all atoms in every image,
+    /// where `images_vectors[image][atom]` is the vector of `atom` in `image`.
+    ///
+    /// With [`TrajectoryLayout::Replica`], the other images may be empty.
+    pub fn gather<T>(&mut self, images_vectors: &[&[V]]) -> Result<(), TrajectoryFrameError>
+    where
+        T: Clone + From<f32>,
+        V: AddAssign + Div<T, Output = V>,
+    {
+        self.labels.clear();
+        self.vectors.clear();
+        match self.layout {
+            TrajectoryLayout::Centroid => {
+                let (first, rest) = images_vectors
+                    .split_first()
+                    .ok_or(TrajectoryFrameError::Empty(EmptyError))?;
+                self.vectors.extend_from_slice(first);
+                for image_vectors in rest {
+                    if image_vectors.len() != self.vectors.len() {
+                        return Err(TrajectoryFrameError::Mismatch);
+                    }
+                    for (centroid, vector) in self.vectors.iter_mut().zip(*image_vectors) {
+                        *centroid += vector.clone();
+                    }
+                }
+                let images = T::from(images_vectors.len() as f32);
+                for centroid in &mut self.vectors {
+                    *centroid = centroid.clone() / images.clone();
+                }
+                self.labels
+                    .extend((0..self.vectors.len()).map(|atom| BeadLabel { atom, image: None }));
+            }
+            TrajectoryLayout::Replica(image) => {
+                let image_vectors =
+                    images_vectors
+                        .get(image)
+                        .ok_or(TrajectoryFrameError::Index(InvalidIndexError::new(
+                            image,
+                            images_vectors.len(),
+                        )))?;
+                self.vectors.extend_from_slice(image_vectors);
+                self.labels
+                    .extend((0..image_vectors.len()).map(|atom| BeadLabel {
+                        atom,
+                        image: Some(image),
+                    }));
+            }
+            TrajectoryLayout::AllBeads => {
+                let atoms = images_vectors.first().map_or(0, |first| first.len());
+                for (image, image_vectors) in images_vectors.iter().enumerate() {
+                    if image_vectors.len() != atoms {
+                        return Err(TrajectoryFrameError::Mismatch);
+                    }
+                    self.vectors.extend_from_slice(image_vectors);
+                    self.labels.extend((0..atoms).map(|atom| BeadLabel {
+                        atom,
+                        image: Some(image),
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error returned by [`TrajectoryFrame::gather`].
+#[derive(Clone, Copy, Debug)]
+pub enum TrajectoryFrameError {
+    /// There are no images to average over.
+    Empty(EmptyError),
+    /// The selected replica is not among the images.
+    Index(InvalidIndexError),
+    /// The images hold different numbers of atoms.
+    Mismatch,
+}
+
+impl Display for TrajectoryFrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Empty(_) => write!(f, "no images to build a frame from"),
+            Self::Index(err) => write!(f, "the selected replica does not exist: {}", err),
+            Self::Mismatch => write!(f, "the images hold different numbers of atoms"),
+        }
+    }
+}
+
+impl Error for TrajectoryFrameError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Empty(err) => Some(err),
+            Self::Index(err) => Some(err),
+            Self::Mismatch => None,
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! Units of the observables and their conversion when written out.
+
+use super::{Provenance, ValuesOutput};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{Div, Mul},
+};
+
+/// The dimension of a quantity as the exponents of the base quantities.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Dimension {
+    /// The exponent of length.
+    pub length: i8,
+    /// The exponent of mass.
+    pub mass: i8,
+    /// The exponent of time.
+    pub time: i8,
+    /// The exponent of temperature.
+    pub temperature: i8,
+    /// The exponent of charge.
+    pub charge: i8,
+}
+
+impl Dimension {
+    /// The dimension of a pure number.
+    pub const DIMENSIONLESS: Self = Self::new(0, 0, 0, 0, 0);
+    /// The dimension of length.
+    pub const LENGTH: Self = Self::new(1, 0, 0, 0, 0);
+    /// The dimension of mass.
+    pub const MASS: Self = Self::new(0, 1, 0, 0, 0);
+    /// The dimension of time.
+    pub const TIME: Self = Self::new(0, 0, 1, 0, 0);
+    /// The dimension of temperature.
+    pub const TEMPERATURE: Self = Self::new(0, 0, 0, 1, 0);
+    /// The dimension of charge.
+    pub const CHARGE: Self = Self::new(0, 0, 0, 0, 1);
+    /// The dimension of energy, mass times length squared per time squared.
+    pub const ENERGY: Self = Self::new(2, 1, -2, 0, 0);
+    /// The dimension of force, energy per length.
+    pub const FORCE: Self = Self::new(1, 1, -2, 0, 0);
+    /// The dimension of momentum, mass times length per time.
+    pub const MOMENTUM: Self = Self::new(1, 1, -1, 0, 0);
+    /// The dimension of pressure, energy per volume.
+    pub const PRESSURE: Self = Self::new(-1, 1, -2, 0, 0);
+
+    /// Constructs a dimension from the exponents of
+    /// length, mass, time, temperature and charge.
+    pub const fn new(length: i8, mass: i8, time: i8, temperature: i8, charge: i8) -> Self {
+        Self {
+            length,
+            mass,
+            time,
+            temperature,
+            charge,
+        }
+    }
+
+    /// Returns this dimension raised to the power `exponent`.
+    pub const fn powi(self, exponent: i8) -> Self {
+        Self::new(
+            self.length * exponent,
+            self.mass * exponent,
+            self.time * exponent,
+            self.temperature * exponent,
+            self.charge * exponent,
+        )
+    }
+
+    fn exponents(self) -> [i8; 5] {
+        [
+            self.length,
+            self.mass,
+            self.time,
+            self.temperature,
+            self.charge,
+        ]
+    }
+}
+
+impl Mul for Dimension {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.length + rhs.length,
+            self.mass + rhs.mass,
+            self.time + rhs.time,
+            self.temperature + rhs.temperature,
+            self.charge + rhs.charge,
+        )
+    }
+}
+
+impl Div for Dimension {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.powi(-1)
+    }
+}
+
+/// A unit of a base quantity.
+#[derive(Clone, Debug)]
+pub struct BaseUnit<T> {
+    /// The value of one internal unit in this unit.
+    pub factor: T,
+    /// The symbol of this unit, e.g. "nm".
+    pub symbol: String,
+}
+
+impl<T> BaseUnit<T> {
+    /// Constructs a unit in which one internal unit is worth `factor`.
+    pub fn new(factor: T, symbol: &str) -> Self {
+        Self {
+            factor,
+            symbol: symbol.to_owned(),
+        }
+    }
+}
+
+/// The units in which the observables are written, given by the units of the base quantities.
+///
+/// The system is configured once for the whole simulation, so that every stream
+/// converting with it writes consistent values.
+#[derive(Clone, Debug)]
+pub struct UnitSystem<T> {
+    /// The unit of length.
+    pub length: BaseUnit<T>,
+    /// The unit of mass.
+    pub mass: BaseUnit<T>,
+    /// The unit of time.
+    pub time: BaseUnit<T>,
+    /// The unit of temperature.
+    pub temperature: BaseUnit<T>,
+    /// The unit of charge.
+    pub charge: BaseUnit<T>,
+    /// The symbols of derived units used in labels instead of the base units,
+    /// e.g. "kJ/mol" for energy in a system of nm, u and ps.
+    pub derived: Vec<(Dimension, String)>,
+}
+
+impl<T: From<f32>> UnitSystem<T> {
+    /// Constructs the system of the internal units, in which no value is converted.
+    pub fn internal() -> Self {
+        let unit = |symbol| BaseUnit::new(T::from(1.0), symbol);
+        Self {
+            length: unit("L"),
+            mass: unit("M"),
+            time: unit("T"),
+            temperature: unit("Θ"),
+            charge: unit("Q"),
+            derived: Vec::new(),
+        }
+    }
+}
+
+impl<T> UnitSystem<T> {
+    fn units(&self) -> [&BaseUnit<T>; 5] {
+        [
+            &self.length,
+            &self.mass,
+            &self.time,
+            &self.temperature,
+            &self.charge,
+        ]
+    }
+
+    /// Returns the factor converting a value of the given dimension
+    /// from internal units to this system.
+    pub fn factor(&self, dimension: Dimension) -> T
+    where
+        T: Clone + From<f32> + Mul<Output = T> + Div<Output = T>,
+    {
+        let mut factor = T::from(1.0);
+        for (unit, exponent) in self.units().into_iter().zip(dimension.exponents()) {
+            for _ in 0..exponent.unsigned_abs() {
+                factor = if exponent > 0 {
+                    factor * unit.factor.clone()
+                } else {
+                    factor / unit.factor.clone()
+                };
+            }
+        }
+        factor
+    }
+
+    /// Returns the label of the unit of the given dimension in this system,
+    /// e.g. "kg m^2 s^-2" unless a derived unit is named for it,
+    /// or an empty string for a dimensionless quantity.
+    pub fn label(&self, dimension: Dimension) -> String {
+        if let Some((_, symbol)) = self
+            .derived
+            .iter()
+            .find(|(derived, _)| *derived == dimension)
+        {
+            return symbol.clone();
+        }
+        let mut label = String::new();
+        for (unit, exponent) in self.units().into_iter().zip(dimension.exponents()) {
+            if exponent == 0 {
+                continue;
+            }
+            if !label.is_empty() {
+                label.push(' ');
+            }
+            label.push_str(&unit.symbol);
+            if exponent != 1 {
+                label.push_str(&format!("^{}", exponent));
+            }
+        }
+        label
+    }
+}
+
+/// The observables written to a stream, in the order of their columns,
+/// each annotated with its dimension.
+#[derive(Clone, Debug, Default)]
+pub struct ObservableRegistry {
+    entries: Vec<(String, Dimension)>,
+}
+
+impl ObservableRegistry {
+    /// Constructs an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an observable in the next column and returns the index of the column.
+    pub fn register(&mut self, name: &str, dimension: Dimension) -> usize {
+        self.entries.push((name.to_owned(), dimension));
+        self.entries.len() - 1
+    }
+
+    /// Returns the names and dimensions of the observables.
+    pub fn entries(&self) -> &[(String, Dimension)] {
+        &self.entries
+    }
+
+    /// Returns the header of every column, i.e. the name of the observable
+    /// followed by its unit in `units` in brackets, if it has any.
+    pub fn header<T>(&self, units: &UnitSystem<T>) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|(name, dimension)| match units.label(*dimension) {
+                label if label.is_empty() => name.clone(),
+                label => format!("{} [{}]", name, label),
+            })
+            .collect()
+    }
+}
+
+/// An error returned by a [`Converted`] stream.
+#[derive(Clone, Debug)]
+pub enum ConversionError<E> {
+    /// The inner stream failed.
+    Stream(E),
+    /// A value was written to a column which has no registered observable.
+    Unregistered {
+        /// The index of the column.
+        column: usize,
+    },
+}
+
+impl<E: Display> Display for ConversionError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Stream(err) => write!(f, "failed to write a converted value: {}", err),
+            Self::Unregistered { column } => {
+                write!(f, "no observable is registered for column #{}", column)
+            }
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for ConversionError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Stream(err) => Some(err),
+            Self::Unregistered { .. } => None,
+        }
+    }
+}
+
+/// A stream which converts every value from internal units to a [`UnitSystem`]
+/// according to the dimension of the observable registered for its column.
+///
+/// The columns restart at every step, so a value written to a column
+/// without a registered observable is an error rather than being written unconverted.
+#[derive(Clone, Debug)]
+pub struct Converted<O, T> {
+    pub(crate) stream: O,
+    pub(crate) factors: Box<[T]>,
+    pub(crate) column: usize,
+}
+
+impl<O, T> Converted<O, T>
+where
+    T: Clone + From<f32> + Mul<Output = T> + Div<Output = T>,
+{
+    /// Wraps `stream` to convert the observables of `registry` to `units`.
+    pub fn new(stream: O, registry: &ObservableRegistry, units: &UnitSystem<T>) -> Self {
+        Self {
+            stream,
+            factors: registry
+                .entries()
+                .iter()
+                .map(|(_, dimension)| units.factor(*dimension))
+                .collect(),
+            column: 0,
+        }
+    }
+}
+
+impl<O, T> Converted<O, T> {
+    /// Returns the inner stream.
+    pub fn into_inner(self) -> O {
+        self.stream
+    }
+}
+
+impl<O, T> ValuesOutput<T> for Converted<O, T>
+where
+    O: ValuesOutput<T>,
+    T: Clone + Mul<Output = T>,
+{
+    type Error = ConversionError<O::Error>;
+
+    fn write_provenance(&mut self, provenance: &Provenance) -> Result<(), Self::Error> {
+        self.stream
+            .write_provenance(provenance)
+            .map_err(ConversionError::Stream)
+    }
+
+    fn write_step(&mut self, step: usize) -> Result<(), Self::Error> {
+        self.column = 0;
+        self.stream
+            .write_step(step)
+            .map_err(ConversionError::Stream)
+    }
+
+    fn write_value(&mut self, value: T) -> Result<(), Self::Error> {
+        let factor = self
+            .factors
+            .get(self.column)
+            .ok_or(ConversionError::Unregistered {
+                column: self.column,
+            })?;
+        self.column += 1;
+        self.stream
+            .write_value(value * factor.clone())
+            .map_err(ConversionError::Stream)
+    }
+
+    fn new_line(&mut self) -> Result<(), Self::Error> {
+        self.column = 0;
+        self.stream.new_line().map_err(ConversionError::Stream)
+    }
+}
@@ -3,8 +3,12 @@
 use crate::core::{AtomGroup, AtomTypeReaderLock, MapInWhole, MapOutsideWhole};
 
 pub mod exchange;
+pub mod ground_state;
+mod groups_positions;
 pub mod physical;
 
+pub use groups_positions::{GroupsPositions, GroupsPositionsIter};
+
 pub type GroupInTypeInImage<'a, V> = MapOutsideWhole<
     &'a AtomGroup<V>,
     MapInWhole<&'a AtomTypeReaderLock<V>, &'a [AtomTypeReaderLock<V>]>,
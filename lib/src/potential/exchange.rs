@@ -3,24 +3,51 @@
 use super::GroupInTypeInImage;
 use macros::{efficient_alternatives, heavy_computation};
 
+mod cache;
+mod frozen;
+mod harmonic;
+mod null;
+mod parameters;
 pub mod quadratic;
+mod treated;
 
 #[cfg(feature = "monte_carlo")]
 mod monte_carlo;
 #[cfg(feature = "monte_carlo")]
 pub use monte_carlo::{MonteCarloExchangePotential, NeighboringImage};
 
-use crate::core::AtomGroup;
+pub use cache::{Cached, EnergyCache};
+pub use harmonic::HarmonicSpringPotential;
+pub use null::NullExchangePotential;
+pub use parameters::RingPolymerParameters;
+
+use crate::core::{AtomGroup, stat::Fermionic, topology::Topology};
 
 /// A trait for exchange potentials.
 pub trait ExchangePotential<T, V> {
     /// The type associated with an error returned by the implementor.
     type Error;
+    /// The way the images of each atom are connected.
+    ///
+    /// For an [`OpenTopology`], the first and the last images do not contribute
+    /// the coupling between them.
+    ///
+    /// [`OpenTopology`]: crate::core::topology::OpenTopology
+    type Topology: Topology;
 
     /// Returns whether this exchange potential is invariant under
     /// a cyclic permutation of the images.
     fn is_cyclic(&self) -> bool;
 
+    /// Returns whether this exchange potential couples the images at all.
+    ///
+    /// Quantum estimators skip the groups whose exchange potential does not,
+    /// as their replicas are independent classical copies.
+    #[inline(always)]
+    fn is_coupling(&self) -> bool {
+        true
+    }
+
     /// Calculates the contribution of this group in this image to the total exchange potential energy
     /// of the type and sets the forces of this group accordingly.
     ///
@@ -80,3 +107,16 @@ pub trait ExchangePotential<T, V> {
         group_forces: &mut [V],
     ) -> Result<(), Self::Error>;
 }
+
+/// A trait for exchange potentials of fermions.
+///
+/// The images are propagated with the weights of bosons, which are positive,
+/// and the antisymmetry of the fermions enters only through the sign reported here,
+/// by which the observables are reweighted, e.g. with a [`SignWeightedAverage`].
+///
+/// [`SignWeightedAverage`]: crate::estimator::quantum::SignWeightedAverage
+pub trait FermionicExchangePotential<T, V>: ExchangePotential<T, V> + Fermionic {
+    /// Returns the ratio of the weight of the fermions to that of the bosons
+    /// in the configuration of the last evaluation, which lies within [-1, 1].
+    fn sign(&self) -> T;
+}
@@ -0,0 +1,218 @@
+use super::ExchangePotential;
+use crate::{
+    core::{AtomGroup, resolution::BeadCountDependent},
+    potential::GroupInTypeInImage,
+    thermostat::TemperatureDependent,
+};
+
+/// A cache of the exchange potential energy of a group, keyed by the versions of
+/// the positions it was calculated from.
+///
+/// The versions change whenever a write guard to the positions is dropped,
+/// so a cached energy stays valid for as long as none of the images have been written to.
+#[derive(Clone, Debug)]
+pub struct EnergyCache<T> {
+    entry: Option<([usize; 3], T)>,
+}
+
+impl<T> EnergyCache<T> {
+    /// Constructs an empty cache.
+    pub const fn new() -> Self {
+        Self { entry: None }
+    }
+
+    /// Returns the key corresponding to the current versions of the positions.
+    pub fn key<V>(
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+    ) -> [usize; 3] {
+        [
+            positions_prev_image.version(),
+            positions_next_image.version(),
+            positions.version(),
+        ]
+    }
+
+    /// Returns the cached energy if it has been calculated from positions with versions `key`.
+    pub fn get(&self, key: [usize; 3]) -> Option<&T> {
+        self.entry
+            .as_ref()
+            .and_then(|(cached_key, energy)| (*cached_key == key).then_some(energy))
+    }
+
+    /// Stores `energy` calculated from positions with versions `key`.
+    pub fn insert(&mut self, key: [usize; 3], energy: T) {
+        self.entry = Some((key, energy));
+    }
+
+    /// Discards the cached energy.
+    pub fn invalidate(&mut self) {
+        self.entry = None;
+    }
+}
+
+impl<T> Default for EnergyCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A wrapper around an exchange potential which does not recalculate
+/// the energy of a group unless the positions have been written to.
+///
+/// Calls which update the forces always reach the inner potential,
+/// but the energy they return is cached for subsequent calls to
+/// [`ExchangePotential::calculate_potential`].
+#[derive(Clone, Debug)]
+pub struct Cached<T, P: ?Sized> {
+    pub(crate) cache: EnergyCache<T>,
+    pub(crate) inner: P,
+}
+
+impl<T, P> Cached<T, P> {
+    /// Wraps `inner` with an empty cache.
+    pub const fn new(inner: P) -> Self {
+        Self {
+            cache: EnergyCache::new(),
+            inner,
+        }
+    }
+
+    /// Returns the cache.
+    pub fn cache(&self) -> &EnergyCache<T> {
+        &self.cache
+    }
+}
+
+/// The springs change with the temperature while the positions stay the same,
+/// so the cached energy is discarded along with the change.
+impl<T, U, P> TemperatureDependent<U> for Cached<T, P>
+where
+    P: TemperatureDependent<U> + ?Sized,
+{
+    fn set_temperature(&mut self, temperature: &U) {
+        self.cache.invalidate();
+        self.inner.set_temperature(temperature);
+    }
+}
+
+/// The springs change with the number of images, so the cached energy is discarded.
+impl<T, P> BeadCountDependent for Cached<T, P>
+where
+    P: BeadCountDependent + ?Sized,
+{
+    fn set_beads(&mut self, beads: usize) {
+        self.cache.invalidate();
+        self.inner.set_beads(beads);
+    }
+}
+
+impl<T, V, P> ExchangePotential<T, V> for Cached<T, P>
+where
+    T: Clone,
+    P: ExchangePotential<T, V> + ?Sized,
+{
+    type Error = P::Error;
+    type Topology = P::Topology;
+
+    #[inline(always)]
+    fn is_cyclic(&self) -> bool {
+        self.inner.is_cyclic()
+    }
+
+    #[inline(always)]
+    fn is_coupling(&self) -> bool {
+        self.inner.is_coupling()
+    }
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let key = EnergyCache::<T>::key(positions_prev_image, positions_next_image, positions);
+        let potential_energy = self.inner.calculate_potential_set_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )?;
+        self.cache.insert(key, potential_energy.clone());
+        Ok(potential_energy)
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let key = EnergyCache::<T>::key(positions_prev_image, positions_next_image, positions);
+        let potential_energy = self.inner.calculate_potential_add_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )?;
+        self.cache.insert(key, potential_energy.clone());
+        Ok(potential_energy)
+    }
+
+    fn calculate_potential(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+    ) -> Result<T, Self::Error> {
+        let key = EnergyCache::<T>::key(positions_prev_image, positions_next_image, positions);
+        if let Some(potential_energy) = self.cache.get(key) {
+            return Ok(potential_energy.clone());
+        }
+        #[allow(deprecated)]
+        let potential_energy = self.inner.calculate_potential(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+        )?;
+        self.cache.insert(key, potential_energy.clone());
+        Ok(potential_energy)
+    }
+
+    #[inline(always)]
+    fn set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [AtomGroup<V>],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.inner.set_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )
+    }
+
+    #[inline(always)]
+    fn add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.inner.add_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )
+    }
+}
@@ -0,0 +1,106 @@
+use super::ExchangePotential;
+use crate::{
+    core::{AtomGroup, Frozen},
+    potential::GroupInTypeInImage,
+};
+
+impl<T, V, P> ExchangePotential<T, V> for Frozen<P>
+where
+    V: Default,
+    P: ExchangePotential<T, V> + ?Sized,
+{
+    type Error = P::Error;
+    type Topology = P::Topology;
+
+    #[inline(always)]
+    fn is_cyclic(&self) -> bool {
+        self.inner.is_cyclic()
+    }
+
+    #[inline(always)]
+    fn is_coupling(&self) -> bool {
+        self.inner.is_coupling()
+    }
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let potential_energy = self.inner.calculate_potential_set_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )?;
+        self.zero_frozen(group_forces);
+        Ok(potential_energy)
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let potential_energy = self.inner.calculate_potential_add_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )?;
+        self.zero_frozen(group_forces);
+        Ok(potential_energy)
+    }
+
+    #[inline(always)]
+    fn calculate_potential(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+    ) -> Result<T, Self::Error> {
+        #[allow(deprecated)]
+        self.inner
+            .calculate_potential(positions_prev_image, positions_next_image, positions)
+    }
+
+    fn set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [AtomGroup<V>],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.inner.set_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )?;
+        self.zero_frozen_in_locks(group_forces);
+        Ok(())
+    }
+
+    fn add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.inner.add_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )?;
+        self.zero_frozen(group_forces);
+        Ok(())
+    }
+}
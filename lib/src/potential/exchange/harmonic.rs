@@ -0,0 +1,453 @@
+use super::{ExchangePotential, RingPolymerParameters};
+use crate::{
+    core::{
+        AtomGroup, Vector,
+        resolution::BeadCountDependent,
+        stat::Distinguishable,
+        topology::{OpenTopology, RingTopology, Topology},
+    },
+    numeric::Real,
+    potential::GroupInTypeInImage,
+    thermostat::TemperatureDependent,
+};
+use std::{convert::Infallible, marker::PhantomData};
+
+/// The springs between consecutive images of distinguishable atoms of the same mass,
+/// the standard exchange potential of path-integral molecular dynamics.
+///
+/// The neighboring images of every atom are coupled by a spring of constant
+/// `k = m P k_B² T² / ħ²` set by the [`RingPolymerParameters`], where `P` is the number of images.
+/// Every image is assigned half of each of its two springs, so the contributions
+/// of all images add up to `Σ ½ k |x_{j+1} - x_j|²`.
+///
+/// Under a [`RingTopology`] the images form a closed ring, in which the last image
+/// is coupled to the first one. Under an [`OpenTopology`] they form an open chain
+/// without that spring, so the potential of an open chain knows the image it is assigned to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HarmonicSpringPotential<const N: usize, T, Top = RingTopology> {
+    mass: T,
+    parameters: RingPolymerParameters<T>,
+    image: usize,
+    spring_constant: T,
+    topology: PhantomData<Top>,
+}
+
+impl<const N: usize, T: Real> HarmonicSpringPotential<N, T> {
+    /// Constructs the springs of atoms of mass `mass` in a ring of `beads` images
+    /// at `temperature`, in the internal units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mass` or `temperature` is not positive or `beads` is zero.
+    pub fn new(mass: T, temperature: T, beads: usize) -> Self {
+        Self::from_parameters(mass, &RingPolymerParameters::new(temperature, beads))
+    }
+
+    /// Constructs the springs of atoms of mass `mass` in a ring with `parameters`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mass` is not positive.
+    pub fn from_parameters(mass: T, parameters: &RingPolymerParameters<T>) -> Self {
+        Self::with_image(mass, parameters, 0)
+    }
+}
+
+impl<const N: usize, T: Real> HarmonicSpringPotential<N, T, OpenTopology> {
+    /// Constructs the springs of atoms of mass `mass` at the image `image`
+    /// of an open chain with `parameters`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mass` is not positive or `image` is not less than the number of beads.
+    pub fn open_chain(mass: T, parameters: &RingPolymerParameters<T>, image: usize) -> Self {
+        Self::with_image(mass, parameters, image)
+    }
+}
+
+impl<const N: usize, T: Real, Top: Topology> HarmonicSpringPotential<N, T, Top> {
+    fn with_image(mass: T, parameters: &RingPolymerParameters<T>, image: usize) -> Self {
+        assert!(mass > T::from(0.0), "the mass must be positive");
+        assert!(
+            image < parameters.beads(),
+            "image #{} is not in a chain of {} images",
+            image,
+            parameters.beads()
+        );
+        Self {
+            mass,
+            parameters: *parameters,
+            image,
+            spring_constant: parameters.spring_constant(mass),
+            topology: PhantomData,
+        }
+    }
+
+    /// Returns the mass of the atoms.
+    pub fn mass(&self) -> T {
+        self.mass
+    }
+
+    /// Returns the parameters of the ring polymer.
+    pub fn parameters(&self) -> &RingPolymerParameters<T> {
+        &self.parameters
+    }
+
+    /// Returns the temperature the springs are set for.
+    pub fn temperature(&self) -> T {
+        self.parameters.temperature()
+    }
+
+    /// Returns the number of images in the ring.
+    pub fn beads(&self) -> usize {
+        self.parameters.beads()
+    }
+
+    /// Returns the image the springs are evaluated at, which only matters
+    /// for the ends of an open chain.
+    pub fn image(&self) -> usize {
+        self.image
+    }
+
+    /// Returns the spring constant `m P k_B² T² / ħ²`.
+    pub fn spring_constant(&self) -> T {
+        self.spring_constant
+    }
+
+    /// Returns the contribution of the atoms at `positions` to the energy of the ring
+    /// and, if `forces` is given, adds the forces of the springs on them.
+    fn accumulate<'a, V, I>(
+        &self,
+        positions_prev_image: &[V],
+        positions_next_image: &[V],
+        positions: &[V],
+        forces: Option<I>,
+    ) -> T
+    where
+        V: Vector<N, Element = T> + Clone + 'a,
+        I: Iterator<Item = &'a mut V>,
+    {
+        let mut forces = forces;
+        // The spring to an uncoupled end of an open chain has no extension.
+        let is_coupled_to_prev = Top::is_coupled_to_prev(self.image, self.beads());
+        let is_coupled_to_next = Top::is_coupled_to_next(self.image, self.beads());
+        let mut squared_extensions = T::from(0.0);
+        for ((position, prev), next) in positions
+            .iter()
+            .zip(positions_prev_image)
+            .zip(positions_next_image)
+        {
+            let to_prev = if is_coupled_to_prev {
+                prev.clone() - position.clone()
+            } else {
+                V::zero()
+            };
+            let to_next = if is_coupled_to_next {
+                next.clone() - position.clone()
+            } else {
+                V::zero()
+            };
+            squared_extensions = squared_extensions
+                + to_prev.clone().magnitude_squared()
+                + to_next.clone().magnitude_squared();
+            if let Some(force) = forces.as_mut().and_then(Iterator::next) {
+                force.mul_add_assign(&(to_prev + to_next), self.spring_constant);
+            }
+        }
+        T::from(0.25) * self.spring_constant * squared_extensions
+    }
+}
+
+impl<const N: usize, T: Real, Top: Topology> TemperatureDependent<T>
+    for HarmonicSpringPotential<N, T, Top>
+{
+    fn set_temperature(&mut self, temperature: &T) {
+        self.parameters = self.parameters.with_temperature(*temperature);
+        self.spring_constant = self.parameters.spring_constant(self.mass);
+    }
+}
+
+/// The springs stiffen in proportion to the number of images.
+///
+/// # Panics
+///
+/// Panics if the image of the springs is not in the new chain.
+impl<const N: usize, T: Real, Top: Topology> BeadCountDependent
+    for HarmonicSpringPotential<N, T, Top>
+{
+    fn set_beads(&mut self, beads: usize) {
+        assert!(
+            self.image < beads,
+            "image #{} is not in a chain of {} images",
+            self.image,
+            beads
+        );
+        self.parameters = self.parameters.with_beads(beads);
+        self.spring_constant = self.parameters.spring_constant(self.mass);
+    }
+}
+
+impl<const N: usize, T, Top> Distinguishable for HarmonicSpringPotential<N, T, Top> {}
+
+impl<const N: usize, T, V, Top> ExchangePotential<T, V> for HarmonicSpringPotential<N, T, Top>
+where
+    T: Real,
+    V: Vector<N, Element = T> + Clone,
+    Top: Topology,
+{
+    type Error = Infallible;
+    type Topology = Top;
+
+    #[inline(always)]
+    fn is_cyclic(&self) -> bool {
+        Top::IS_RING
+    }
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        group_forces.fill_with(V::zero);
+        Ok(self.accumulate(
+            positions_prev_image.read(),
+            positions_next_image.read(),
+            positions.read(),
+            Some(group_forces.iter_mut()),
+        ))
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        Ok(self.accumulate(
+            positions_prev_image.read(),
+            positions_next_image.read(),
+            positions.read(),
+            Some(group_forces.iter_mut()),
+        ))
+    }
+
+    fn calculate_potential(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+    ) -> Result<T, Self::Error> {
+        Ok(self.accumulate(
+            positions_prev_image.read(),
+            positions_next_image.read(),
+            positions.read(),
+            None::<std::slice::IterMut<V>>,
+        ))
+    }
+
+    fn set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [AtomGroup<V>],
+    ) -> Result<(), Self::Error> {
+        let mut guards: Vec<_> = group_forces
+            .iter_mut()
+            .map(|forces| {
+                let mut guard = forces.write();
+                guard.fill_with(V::zero);
+                guard
+            })
+            .collect();
+        // The forces of the group may be split across several locks, in the order of its atoms.
+        self.accumulate(
+            positions_prev_image.read(),
+            positions_next_image.read(),
+            positions.read(),
+            Some(guards.iter_mut().flat_map(|guard| guard.iter_mut())),
+        );
+        Ok(())
+    }
+
+    fn add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.accumulate(
+            positions_prev_image.read(),
+            positions_next_image.read(),
+            positions.read(),
+            Some(group_forces.iter_mut()),
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+    /// A position on a line.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Position([f64; 1]);
+
+    impl From<[f64; 1]> for Position {
+        fn from(array: [f64; 1]) -> Self {
+            Self(array)
+        }
+    }
+
+    impl Add for Position {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self {
+            Self([self.0[0] + rhs.0[0]])
+        }
+    }
+
+    impl AddAssign for Position {
+        fn add_assign(&mut self, rhs: Self) {
+            self.0[0] += rhs.0[0];
+        }
+    }
+
+    impl Sub for Position {
+        type Output = Self;
+
+        fn sub(self, rhs: Self) -> Self {
+            Self([self.0[0] - rhs.0[0]])
+        }
+    }
+
+    impl SubAssign for Position {
+        fn sub_assign(&mut self, rhs: Self) {
+            self.0[0] -= rhs.0[0];
+        }
+    }
+
+    impl Mul<f64> for Position {
+        type Output = Self;
+
+        fn mul(self, rhs: f64) -> Self {
+            Self([self.0[0] * rhs])
+        }
+    }
+
+    impl MulAssign<f64> for Position {
+        fn mul_assign(&mut self, rhs: f64) {
+            self.0[0] *= rhs;
+        }
+    }
+
+    impl Div<f64> for Position {
+        type Output = Self;
+
+        fn div(self, rhs: f64) -> Self {
+            Self([self.0[0] / rhs])
+        }
+    }
+
+    impl DivAssign<f64> for Position {
+        fn div_assign(&mut self, rhs: f64) {
+            self.0[0] /= rhs;
+        }
+    }
+
+    impl Neg for Position {
+        type Output = Self;
+
+        fn neg(self) -> Self {
+            Self([-self.0[0]])
+        }
+    }
+
+    impl Vector<1> for Position {
+        type Element = f64;
+
+        fn zero() -> Self {
+            Self([0.0])
+        }
+
+        fn splat(value: f64) -> Self {
+            Self([value])
+        }
+
+        fn from_array(array: [f64; 1]) -> Self {
+            Self(array)
+        }
+
+        fn as_array(&self) -> &[f64; 1] {
+            &self.0
+        }
+
+        fn as_mut_array(&mut self) -> &mut [f64; 1] {
+            &mut self.0
+        }
+
+        fn magnitude_squared(self) -> f64 {
+            self.0[0] * self.0[0]
+        }
+
+        fn dot(self, rhs: Self) -> f64 {
+            self.0[0] * rhs.0[0]
+        }
+
+        fn mul_add_assign(&mut self, a: &Self, b: f64) {
+            self.0[0] += a.0[0] * b;
+        }
+    }
+
+    const IMAGES: [f64; 4] = [0.0, 1.0, 3.0, -2.0];
+
+    /// Returns the energy of all images and the forces on them.
+    fn evaluate<Top: Topology>(
+        potential_of_image: impl Fn(usize) -> HarmonicSpringPotential<1, f64, Top>,
+    ) -> (f64, Vec<f64>) {
+        let beads = IMAGES.len();
+        let mut energy = 0.0;
+        let mut forces = vec![Position::zero(); beads];
+        for image in 0..beads {
+            let position = |image: usize| [Position([IMAGES[image % beads]])];
+            energy += potential_of_image(image).accumulate(
+                &position(image + beads - 1),
+                &position(image + 1),
+                &position(image),
+                Some(forces[image..=image].iter_mut()),
+            );
+        }
+        (energy, forces.iter().map(|force| force.0[0]).collect())
+    }
+
+    #[test]
+    fn open_chain_has_no_bond_between_its_ends() {
+        let ring = HarmonicSpringPotential::<1, f64>::new(2.0, 0.5, IMAGES.len());
+        let k = ring.spring_constant();
+        let (ring_energy, ring_forces) = evaluate(|_| ring);
+        let (open_energy, open_forces) = evaluate(|image| {
+            HarmonicSpringPotential::<1, f64, OpenTopology>::open_chain(
+                2.0,
+                &RingPolymerParameters::new(0.5, IMAGES.len()),
+                image,
+            )
+        });
+
+        // The bonds 0-1, 1-2 and 2-3 of the chain and the bond 3-0 that closes the ring.
+        let chain = 0.5 * k * (1.0 + 4.0 + 25.0);
+        assert!((open_energy - chain).abs() < 1e-12);
+        assert!((ring_energy - chain - 0.5 * k * 4.0).abs() < 1e-12);
+        let expected = [k * 1.0, k * 1.0, -k * 7.0, k * 5.0];
+        for (force, expected) in open_forces.iter().zip(expected) {
+            assert!((force - expected).abs() < 1e-12);
+        }
+        assert!((ring_forces[0] - k * (1.0 - 2.0)).abs() < 1e-12);
+        assert!((ring_forces[3] - k * (5.0 + 2.0)).abs() < 1e-12);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::AtomGroup;
+use crate::core::{AtomGroup, topology::Topology};
 
 use super::ExchangePotential;
 use macros::{efficient_alternatives, heavy_computation};
@@ -10,15 +10,56 @@ pub enum NeighboringImage {
     This,
     /// This image's predecessor.
     ///
-    /// For the first image, the last one counts as its predecessor.
+    /// For the first image of a ring, the last one counts as its predecessor.
     Prev,
     /// This image's successor.
     ///
-    /// For the last image, the first on counts as its successor.
+    /// For the last image of a ring, the first one counts as its successor.
     Next,
 }
 
+impl NeighboringImage {
+    /// Returns whether a change in this neighboring image of the image at `image`
+    /// out of `images` affects the contribution of the latter.
+    ///
+    /// Used to skip evaluating the difference in exchange potential energy
+    /// across the uncoupled ends of an open chain.
+    #[inline(always)]
+    pub fn is_coupled<Top: Topology + ?Sized>(self, image: usize, images: usize) -> bool {
+        match self {
+            Self::This => image < images,
+            Self::Prev => Top::is_coupled_to_prev(image, images),
+            Self::Next => Top::is_coupled_to_next(image, images),
+        }
+    }
+
+    /// Returns the images whose contributions to the exchange potential energy change
+    /// when an atom in the image at `image` out of `images` is moved, under the topology `Top`,
+    /// together with the relation of the moved image to each of them.
+    ///
+    /// These are the moved image itself, its successor, for which it is the predecessor,
+    /// and its predecessor, for which it is the successor, unless they are not coupled to it,
+    /// as the ends of an open chain are not.
+    pub fn affected_images<Top: Topology + ?Sized>(
+        image: usize,
+        images: usize,
+    ) -> impl Iterator<Item = (usize, Self)> {
+        let next = (image + 1) % images;
+        let prev = (image + images - 1) % images;
+        [(image, Self::This), (next, Self::Prev), (prev, Self::Next)]
+            .into_iter()
+            // The single image of a ring is its own neighbor, at a distance of zero.
+            .filter(move |&(affected, relation)| {
+                matches!(relation, Self::This) || affected != image
+            })
+            .filter(move |&(affected, relation)| relation.is_coupled::<Top>(affected, images))
+    }
+}
+
 /// A trait for exchange potentials that may be used in a Monte-Carlo algorithm.
+///
+/// After a move, the methods are called for every image returned by
+/// [`NeighboringImage::affected_images`] under the topology of the potential.
 pub trait MonteCarloExchangePotential<T, V>: ExchangePotential<T, V> {
     /// The type associated with an error returned by the implementor.
     type Error;
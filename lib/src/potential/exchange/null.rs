@@ -0,0 +1,211 @@
+use super::{ExchangePotential, FermionicExchangePotential};
+use crate::{
+    core::{
+        AtomGroup,
+        stat::{Bosonic, Distinguishable, Fermionic},
+        topology::RingTopology,
+    },
+    potential::GroupInTypeInImage,
+};
+use std::{convert::Infallible, marker::PhantomData};
+
+/// An exchange potential which does not couple the images at all.
+///
+/// A group with this potential keeps a replica in every image, but the replicas evolve
+/// independently of each other, e.g. to model a classical solvent sampled alongside
+/// a quantized solute, as does a classical group under [`Treated`].
+/// The potential energy and the forces are always zero and [`ExchangePotential::is_coupling`]
+/// returns `false`, so that quantum estimators skip the group.
+///
+/// [`Treated`]: crate::core::partition::Treated
+#[derive(Clone, Copy, Debug)]
+pub struct NullExchangePotential<T> {
+    phantom: PhantomData<T>,
+}
+
+impl<T> NullExchangePotential<T> {
+    /// Constructs an exchange potential which does not couple the images.
+    pub const fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> Default for NullExchangePotential<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Distinguishable for NullExchangePotential<T> {}
+
+impl<T> Bosonic for NullExchangePotential<T> {}
+
+impl<T> Fermionic for NullExchangePotential<T> {}
+
+impl<T: Default, V: Default> ExchangePotential<T, V> for NullExchangePotential<T> {
+    type Error = Infallible;
+    type Topology = RingTopology;
+
+    #[inline(always)]
+    fn is_cyclic(&self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn is_coupling(&self) -> bool {
+        false
+    }
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        _positions_prev_image: &GroupInTypeInImage<V>,
+        _positions_next_image: &GroupInTypeInImage<V>,
+        _positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        group_forces.fill_with(V::default);
+        Ok(T::default())
+    }
+
+    #[inline(always)]
+    fn calculate_potential_add_forces(
+        &mut self,
+        _positions_prev_image: &GroupInTypeInImage<V>,
+        _positions_next_image: &GroupInTypeInImage<V>,
+        _positions: &GroupInTypeInImage<V>,
+        _group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        Ok(T::default())
+    }
+
+    #[inline(always)]
+    fn calculate_potential(
+        &mut self,
+        _positions_prev_image: &GroupInTypeInImage<V>,
+        _positions_next_image: &GroupInTypeInImage<V>,
+        _positions: &GroupInTypeInImage<V>,
+    ) -> Result<T, Self::Error> {
+        Ok(T::default())
+    }
+
+    fn set_forces(
+        &mut self,
+        _positions_prev_image: &GroupInTypeInImage<V>,
+        _positions_next_image: &GroupInTypeInImage<V>,
+        _positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [AtomGroup<V>],
+    ) -> Result<(), Self::Error> {
+        for forces in group_forces {
+            forces.fill_with(V::default);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn add_forces(
+        &mut self,
+        _positions_prev_image: &GroupInTypeInImage<V>,
+        _positions_next_image: &GroupInTypeInImage<V>,
+        _positions: &GroupInTypeInImage<V>,
+        _group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Without exchange, every configuration is weighted as the identity permutation.
+impl<T: Default + From<f32>, V: Default> FermionicExchangePotential<T, V>
+    for NullExchangePotential<T>
+{
+    #[inline(always)]
+    fn sign(&self) -> T {
+        T::from(1.0)
+    }
+}
+
+#[cfg(feature = "monte_carlo")]
+mod monte_carlo {
+    use super::NullExchangePotential;
+    use crate::{
+        core::AtomGroup,
+        potential::exchange::{MonteCarloExchangePotential, NeighboringImage},
+    };
+    use std::convert::Infallible;
+
+    /// A change in any image leaves the potential unchanged,
+    /// so every method reports that this group is not affected.
+    impl<T: Default, V: Default> MonteCarloExchangePotential<T, V> for NullExchangePotential<T> {
+        type Error = Infallible;
+
+        #[inline(always)]
+        fn calculate_potential_diff_set_changed_forces(
+            &mut self,
+            _changed_image: NeighboringImage,
+            _changed_atom_index: usize,
+            _old_value: V,
+            _type_positions_last_image: &[AtomGroup<V>],
+            _type_positions_next_image: &[AtomGroup<V>],
+            _type_positions: &[AtomGroup<V>],
+            _group_forces: &mut [V],
+        ) -> Result<Option<T>, Infallible> {
+            Ok(None)
+        }
+
+        #[inline(always)]
+        fn calculate_potential_diff_add_changed_forces(
+            &mut self,
+            _changed_image: NeighboringImage,
+            _changed_atom_index: usize,
+            _old_value: V,
+            _type_positions_last_image: &[AtomGroup<V>],
+            _type_positions_next_image: &[AtomGroup<V>],
+            _type_positions: &[AtomGroup<V>],
+            _group_forces: &mut [V],
+        ) -> Result<Option<T>, Infallible> {
+            Ok(None)
+        }
+
+        #[inline(always)]
+        fn calculate_potential_diff(
+            &mut self,
+            _changed_image: NeighboringImage,
+            _changed_atom_index: usize,
+            _old_value: V,
+            _type_positions_last_image: &[AtomGroup<V>],
+            _type_positions_next_image: &[AtomGroup<V>],
+            _type_positions: &[AtomGroup<V>],
+        ) -> Result<Option<T>, Infallible> {
+            Ok(None)
+        }
+
+        #[inline(always)]
+        fn set_changed_forces(
+            &mut self,
+            _changed_image: NeighboringImage,
+            _changed_atom_index: usize,
+            _old_value: V,
+            _type_positions_last_image: &[AtomGroup<V>],
+            _type_positions_next_image: &[AtomGroup<V>],
+            _type_positions: &[AtomGroup<V>],
+            _group_forces: &mut [V],
+        ) -> Result<(), Infallible> {
+            Ok(())
+        }
+
+        #[inline(always)]
+        fn add_changed_forces(
+            &mut self,
+            _changed_image: NeighboringImage,
+            _changed_atom_index: usize,
+            _old_value: V,
+            _type_positions_last_image: &[AtomGroup<V>],
+            _type_positions_next_image: &[AtomGroup<V>],
+            _type_positions: &[AtomGroup<V>],
+            _group_forces: &mut [V],
+        ) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+}
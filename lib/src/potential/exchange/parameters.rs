@@ -0,0 +1,172 @@
+use crate::numeric::Real;
+use std::ops::{Div, Mul};
+
+/// The physical parameters which set the springs of a ring polymer:
+/// the temperature, the number of beads and the constants `ħ` and `k_B`
+/// in the unit system of the simulation.
+///
+/// The spring constant of an atom of mass `m` is `m P k_B² T² / ħ²`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RingPolymerParameters<T> {
+    temperature: T,
+    beads: usize,
+    reduced_planck_constant: T,
+    boltzmann_constant: T,
+}
+
+impl<T> RingPolymerParameters<T>
+where
+    T: Clone + From<f32> + PartialOrd,
+{
+    /// Constructs the parameters of `beads` beads at `temperature`
+    /// with the constants of the internal units, in which `ħ` and `k_B` are 1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `temperature` is not positive or `beads` is zero.
+    pub fn new(temperature: T, beads: usize) -> Self {
+        assert!(
+            temperature.clone() > 0.0.into(),
+            "the temperature must be positive"
+        );
+        assert!(beads > 0, "a ring polymer must have beads");
+        Self {
+            temperature,
+            beads,
+            reduced_planck_constant: 1.0.into(),
+            boltzmann_constant: 1.0.into(),
+        }
+    }
+
+    /// Sets `ħ` to its value in the unit system of the simulation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reduced_planck_constant` is not positive.
+    pub fn with_reduced_planck_constant(self, reduced_planck_constant: T) -> Self {
+        assert!(
+            reduced_planck_constant.clone() > 0.0.into(),
+            "the reduced Planck constant must be positive"
+        );
+        Self {
+            reduced_planck_constant,
+            ..self
+        }
+    }
+
+    /// Sets `k_B` to its value in the unit system of the simulation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `boltzmann_constant` is not positive.
+    pub fn with_boltzmann_constant(self, boltzmann_constant: T) -> Self {
+        assert!(
+            boltzmann_constant.clone() > 0.0.into(),
+            "the Boltzmann constant must be positive"
+        );
+        Self {
+            boltzmann_constant,
+            ..self
+        }
+    }
+
+    /// Returns the same parameters with `beads` beads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `beads` is zero.
+    pub fn with_beads(self, beads: usize) -> Self {
+        assert!(beads > 0, "a ring polymer must have beads");
+        Self { beads, ..self }
+    }
+
+    /// Returns the same parameters at `temperature`, e.g. for another replica of a ladder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `temperature` is not positive.
+    pub fn with_temperature(self, temperature: T) -> Self {
+        assert!(
+            temperature.clone() > 0.0.into(),
+            "the temperature must be positive"
+        );
+        Self {
+            temperature,
+            ..self
+        }
+    }
+}
+
+impl<T: Clone> RingPolymerParameters<T> {
+    /// Returns the temperature.
+    pub fn temperature(&self) -> T {
+        self.temperature.clone()
+    }
+
+    /// Returns the number of beads.
+    pub fn beads(&self) -> usize {
+        self.beads
+    }
+
+    /// Returns `ħ` in the unit system of the simulation.
+    pub fn reduced_planck_constant(&self) -> T {
+        self.reduced_planck_constant.clone()
+    }
+
+    /// Returns `k_B` in the unit system of the simulation.
+    pub fn boltzmann_constant(&self) -> T {
+        self.boltzmann_constant.clone()
+    }
+}
+
+impl<T> RingPolymerParameters<T>
+where
+    T: Clone + From<f32> + Mul<Output = T> + Div<Output = T>,
+{
+    /// Returns `1 / k_B T`.
+    pub fn beta(&self) -> T {
+        T::from(1.0) / (self.boltzmann_constant.clone() * self.temperature.clone())
+    }
+
+    /// Returns the spring constant of an atom of mass `mass` divided by `T²`,
+    /// which stays fixed when the temperature changes.
+    pub fn spring_prefactor(&self, mass: T) -> T {
+        let boltzmann_constant = self.boltzmann_constant.clone();
+        let reduced_planck_constant = self.reduced_planck_constant.clone();
+        T::from(self.beads as f32) * boltzmann_constant.clone() * boltzmann_constant * mass
+            / (reduced_planck_constant.clone() * reduced_planck_constant)
+    }
+
+    /// Returns the spring constant `m P k_B² T² / ħ²` of an atom of mass `mass`.
+    pub fn spring_constant(&self, mass: T) -> T {
+        self.spring_prefactor(mass) * self.temperature.clone() * self.temperature.clone()
+    }
+
+    /// Returns the frequency `ω_P = P k_B T / ħ` of the springs.
+    pub fn frequency(&self) -> T {
+        T::from(self.beads as f32) * self.boltzmann_constant.clone() * self.temperature.clone()
+            / self.reduced_planck_constant.clone()
+    }
+}
+
+impl<T: Real> RingPolymerParameters<T> {
+    /// Writes the spring constants of the normal modes of a closed ring polymer
+    /// of an atom of mass `mass` into `eigenvalues`, `4 k sin²(π l / P)` for mode `l`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `eigenvalues` does not hold a value per bead.
+    pub fn normal_mode_eigenvalues(&self, mass: T, eigenvalues: &mut [T]) {
+        assert_eq!(
+            eigenvalues.len(),
+            self.beads,
+            "every bead must have an eigenvalue"
+        );
+        let spring_constant = self.spring_constant(mass);
+        let beads = T::from(self.beads as f32);
+        for (mode, eigenvalue) in eigenvalues.iter_mut().enumerate() {
+            let (sine, _) = (T::PI * T::from(mode as f32) / beads).sin_cos();
+            *eigenvalue = T::from(4.0) * spring_constant * sine * sine;
+        }
+    }
+}
@@ -4,6 +4,9 @@ use super::ExchangePotential;
 use crate::{core::AtomTypeReaderLock, stride::Stride};
 use std::iter::FusedIterator;
 
+mod normal_modes;
+pub use normal_modes::NormalModesTransform;
+
 /// A trait for exchange potential that may be expanded to second order.
 pub trait QuadraticExpansionExchangePotential<'a, T, V> {
     /// The transformation that yields the modes such that
@@ -0,0 +1,212 @@
+use super::{Transform, TypeAcrossImages};
+use crate::{
+    core::{Vector, error::PoisonedError, resolution::BeadCountDependent},
+    numeric::Real,
+    potential::exchange::{HarmonicSpringPotential, RingPolymerParameters},
+    thermostat::TemperatureDependent,
+};
+
+/// The real orthogonal transformation between the images of closed ring polymers
+/// and their normal modes, which diagonalizes the springs of [`HarmonicSpringPotential`].
+///
+/// Mode `l` of the images `x_j` of a ring of `P` images is `q_l = Σ_j C_jl x_j`, where
+/// `C_j0 = 1 / √P`, `C_jl = √(2 / P) cos(2π jl / P)` for `0 < l < P / 2`,
+/// `C_jl = (-1)^j / √P` for `l = P / 2` and `C_jl = √(2 / P) sin(2π jl / P)` for `l > P / 2`.
+/// The energy of the springs of constant `k` is then `Σ_l ½ λ_l q_l²`
+/// with `λ_l = 4 k sin²(π l / P)`, and the centroid is the free mode 0.
+///
+/// The transformation of an image yields the mode of the same index for the atoms of
+/// its group and restores the coordinates of these atoms in the image from all modes.
+/// Either direction is a single column or row of `C`, a sum over the `P` images which
+/// a fast Fourier transform of all the modes at once would not speed up,
+/// so only these coefficients are kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NormalModesTransform<const N: usize, T> {
+    springs: HarmonicSpringPotential<N, T>,
+    image: usize,
+    first_atom: usize,
+    /// `C_jl` of every image `j` for the mode `l` of this image.
+    mode_coefficients: Vec<T>,
+    /// `C_jl` of every mode `l` for the image `j` of this transformation.
+    image_coefficients: Vec<T>,
+}
+
+impl<const N: usize, T: Real> NormalModesTransform<N, T> {
+    /// Constructs the transformation of `image` for the springs of atoms of mass `mass`
+    /// in a ring with `parameters`, whose group starts at the atom `first_atom` of its type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mass` is not positive or `image` is not less than the number of beads.
+    pub fn new(
+        mass: T,
+        parameters: &RingPolymerParameters<T>,
+        image: usize,
+        first_atom: usize,
+    ) -> Self {
+        assert!(
+            image < parameters.beads(),
+            "image #{} is not in a ring of {} images",
+            image,
+            parameters.beads()
+        );
+        let mut transform = Self {
+            springs: HarmonicSpringPotential::from_parameters(mass, parameters),
+            image,
+            first_atom,
+            mode_coefficients: Vec::new(),
+            image_coefficients: Vec::new(),
+        };
+        transform.update_coefficients();
+        transform
+    }
+
+    /// Returns the springs diagonalized by the transformation.
+    pub fn springs(&self) -> &HarmonicSpringPotential<N, T> {
+        &self.springs
+    }
+
+    /// Returns the image, which is also the index of the mode, of the transformation.
+    pub fn image(&self) -> usize {
+        self.image
+    }
+
+    /// Returns the first atom of the group in its type.
+    pub fn first_atom(&self) -> usize {
+        self.first_atom
+    }
+
+    /// Returns `C_jl`, the weight of image `j` in mode `l` of a ring of `beads` images.
+    pub fn coefficient(image: usize, mode: usize, beads: usize) -> T {
+        let count = T::from(beads as f32);
+        if mode == 0 || 2 * mode == beads {
+            let sign = if mode != 0 && image % 2 == 1 {
+                T::from(-1.0)
+            } else {
+                T::from(1.0)
+            };
+            return sign / count.sqrt();
+        }
+        // Reduced modulo `P` so the angle, and thereby its rounding, stays below 2π.
+        let angle = T::from(2.0) * T::PI * T::from((image * mode % beads) as f32) / count;
+        let (sin, cos) = angle.sin_cos();
+        let norm = (T::from(2.0) / count).sqrt();
+        if 2 * mode < beads {
+            norm * cos
+        } else {
+            norm * sin
+        }
+    }
+
+    /// Returns `λ_l = 4 k sin²(π l / P)` of the mode `l` of this image.
+    pub fn eigenvalue(&self) -> T {
+        let beads = T::from(self.springs.beads() as f32);
+        let (sine, _) = (T::PI * T::from(self.image as f32) / beads).sin_cos();
+        T::from(4.0) * self.springs.spring_constant() * sine * sine
+    }
+
+    fn update_coefficients(&mut self) {
+        let beads = self.springs.beads();
+        self.mode_coefficients = (0..beads)
+            .map(|image| Self::coefficient(image, self.image, beads))
+            .collect();
+        self.image_coefficients = (0..beads)
+            .map(|mode| Self::coefficient(self.image, mode, beads))
+            .collect();
+    }
+
+    /// Sets `group_vectors` to the sum of the vectors of the group in every image
+    /// weighted by `coefficients`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of images is not the number of coefficients
+    /// or an image does not hold every atom of the group.
+    fn combine<V>(
+        &self,
+        images: TypeAcrossImages<V>,
+        coefficients: &[T],
+        group_vectors: &mut [V],
+    ) -> Result<(), PoisonedError>
+    where
+        V: Vector<N, Element = T> + Clone,
+    {
+        assert_eq!(
+            images.len(),
+            coefficients.len(),
+            "the ring must have a coefficient per image"
+        );
+        group_vectors.fill_with(V::zero);
+        for (image, &coefficient) in images.zip(coefficients) {
+            let groups = image.read().map_err(|_| PoisonedError)?;
+            let mut atoms = groups
+                .iter()
+                .flat_map(|group| group.read().iter())
+                .skip(self.first_atom);
+            for vector in group_vectors.iter_mut() {
+                let atom = atoms
+                    .next()
+                    .expect("every image must hold every atom of the group");
+                vector.mul_add_assign(atom, coefficient);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize, T: Real> TemperatureDependent<T> for NormalModesTransform<N, T> {
+    fn set_temperature(&mut self, temperature: &T) {
+        self.springs.set_temperature(temperature);
+    }
+}
+
+/// The coefficients are recomputed for the new number of images.
+///
+/// # Panics
+///
+/// Panics if the image of the transformation is not in the new ring.
+impl<const N: usize, T: Real> BeadCountDependent for NormalModesTransform<N, T> {
+    fn set_beads(&mut self, beads: usize) {
+        assert!(
+            self.image < beads,
+            "image #{} is not in a ring of {} images",
+            self.image,
+            beads
+        );
+        self.springs.set_beads(beads);
+        self.update_coefficients();
+    }
+}
+
+impl<const N: usize, T, V> Transform<T, V> for NormalModesTransform<N, T>
+where
+    T: Real,
+    V: Vector<N, Element = T> + Clone,
+{
+    type Error = PoisonedError;
+
+    fn transform(
+        &mut self,
+        images_type_coordinates: TypeAcrossImages<V>,
+        group_modes: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.combine(
+            images_type_coordinates,
+            &self.mode_coefficients,
+            group_modes,
+        )
+    }
+
+    fn inverse_transform(
+        &mut self,
+        modes: TypeAcrossImages<V>,
+        group_coordinates: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.combine(modes, &self.image_coefficients, group_coordinates)
+    }
+
+    fn eigenvalues(&self, eigenvalues: &mut [T]) -> Result<(), Self::Error> {
+        eigenvalues.fill(self.eigenvalue());
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+use super::ExchangePotential;
+use crate::{
+    core::{AtomGroup, partition::Treated},
+    potential::GroupInTypeInImage,
+};
+
+impl<T, V, P> ExchangePotential<T, V> for Treated<P>
+where
+    T: Default,
+    V: Default,
+    P: ExchangePotential<T, V> + ?Sized,
+{
+    type Error = P::Error;
+    type Topology = P::Topology;
+
+    #[inline(always)]
+    fn is_cyclic(&self) -> bool {
+        self.inner.is_cyclic()
+    }
+
+    #[inline(always)]
+    fn is_coupling(&self) -> bool {
+        self.inner.is_coupling()
+    }
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        if !self.treatment.is_quantum() {
+            group_forces.fill_with(V::default);
+            return Ok(T::default());
+        }
+        self.inner.calculate_potential_set_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        if !self.treatment.is_quantum() {
+            return Ok(T::default());
+        }
+        self.inner.calculate_potential_add_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )
+    }
+
+    fn calculate_potential(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+    ) -> Result<T, Self::Error> {
+        if !self.treatment.is_quantum() {
+            return Ok(T::default());
+        }
+        #[allow(deprecated)]
+        self.inner
+            .calculate_potential(positions_prev_image, positions_next_image, positions)
+    }
+
+    fn set_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [AtomGroup<V>],
+    ) -> Result<(), Self::Error> {
+        if !self.treatment.is_quantum() {
+            for forces in group_forces {
+                forces.fill_with(V::default);
+            }
+            return Ok(());
+        }
+        #[allow(deprecated)]
+        self.inner.set_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )
+    }
+
+    fn add_forces(
+        &mut self,
+        positions_prev_image: &GroupInTypeInImage<V>,
+        positions_next_image: &GroupInTypeInImage<V>,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        if !self.treatment.is_quantum() {
+            return Ok(());
+        }
+        #[allow(deprecated)]
+        self.inner.add_forces(
+            positions_prev_image,
+            positions_next_image,
+            positions,
+            group_forces,
+        )
+    }
+}
@@ -0,0 +1,184 @@
+//! Building blocks for path-integral ground-state (PIGS) simulations,
+//! where the images of every atom form an open chain whose ends
+//! are weighted by a trial wavefunction.
+//!
+//! The exchange potentials of such a simulation use an
+//! [`OpenTopology`](crate::core::topology::OpenTopology), and a [`TrialPotential`]
+//! is added to the physical potential of the leading and the trailing images only.
+
+use super::{GroupInTypeInImage, physical::PhysicalPotential};
+use std::ops::{AddAssign, Mul, Neg};
+
+/// A trait for trial wavefunctions projected onto the ground state by the chain.
+pub trait TrialWavefunction<T, V> {
+    /// The type associated with an error returned by the implementor.
+    type Error;
+
+    /// Calculates the logarithm of the trial wavefunction of this group
+    /// and sets `gradients` to its gradient with respect to the positions.
+    ///
+    /// Returns the logarithm of the trial wavefunction.
+    fn calculate_log_set_gradients(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        gradients: &mut [V],
+    ) -> Result<T, Self::Error>;
+
+    /// Calculates the logarithm of the trial wavefunction of this group.
+    fn calculate_log(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error>;
+}
+
+/// A trait for trial wavefunctions whose local energy `Hψ / ψ` is known,
+/// which yields the mixed estimator of the ground-state energy at the chain ends.
+pub trait LocalEnergy<T, V>: TrialWavefunction<T, V> {
+    /// Calculates the contribution of this group to the local energy of the trial wavefunction.
+    fn local_energy(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error>;
+}
+
+/// The potential `-kT ln ψ` which weights the end of an open chain by the trial wavefunction `ψ`.
+///
+/// `kT` is the thermal energy the images are sampled at, so that the Boltzmann factor
+/// of this potential is exactly the trial wavefunction.
+pub struct TrialPotential<T, V, W> {
+    pub(crate) wavefunction: W,
+    pub(crate) thermal_energy: T,
+    pub(crate) scratch: Vec<V>,
+}
+
+impl<T, V, W> TrialPotential<T, V, W> {
+    /// Wraps `wavefunction` for images sampled at the thermal energy `thermal_energy`.
+    pub fn new(wavefunction: W, thermal_energy: T) -> Self {
+        Self {
+            wavefunction,
+            thermal_energy,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Returns a reference to the wrapped trial wavefunction.
+    pub fn wavefunction(&self) -> &W {
+        &self.wavefunction
+    }
+
+    /// Returns a mutable reference to the wrapped trial wavefunction.
+    pub fn wavefunction_mut(&mut self) -> &mut W {
+        &mut self.wavefunction
+    }
+}
+
+impl<T, V, W> PhysicalPotential<T, V> for TrialPotential<T, V, W>
+where
+    T: Clone + Mul<Output = T> + Neg<Output = T>,
+    V: Clone + AddAssign + Mul<T, Output = V>,
+    W: TrialWavefunction<T, V>,
+{
+    type Error = W::Error;
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let log = self
+            .wavefunction
+            .calculate_log_set_gradients(positions, group_forces)?;
+        for force in group_forces {
+            *force = force.clone() * self.thermal_energy.clone();
+        }
+        Ok(-(log * self.thermal_energy.clone()))
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        self.scratch.clear();
+        self.scratch.extend_from_slice(group_forces);
+        let log = self
+            .wavefunction
+            .calculate_log_set_gradients(positions, &mut self.scratch)?;
+        for (force, gradient) in group_forces.iter_mut().zip(&self.scratch) {
+            *force += gradient.clone() * self.thermal_energy.clone();
+        }
+        Ok(-(log * self.thermal_energy.clone()))
+    }
+
+    fn calculate_potential(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+        Ok(-(self.wavefunction.calculate_log(positions)? * self.thermal_energy.clone()))
+    }
+
+    fn set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.calculate_potential_set_forces(positions, group_forces)
+            .map(|_| ())
+    }
+
+    fn add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.calculate_potential_add_forces(positions, group_forces)
+            .map(|_| ())
+    }
+}
+
+/// The image of an open chain where ground-state observables are estimated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroundStateImage {
+    /// The middle of the chain, where the distribution is `|ψ₀|²`
+    /// once the chain is long enough, so any diagonal observable is unbiased.
+    Middle(usize),
+    /// The ends of the chain, where the distribution is `ψ₀ ψ`,
+    /// so only observables commuting with the Hamiltonian, such as the
+    /// energy through [`LocalEnergy`], are unbiased.
+    Ends {
+        /// The index of the leading image.
+        leading: usize,
+        /// The index of the trailing image.
+        trailing: usize,
+    },
+}
+
+impl GroundStateImage {
+    /// Returns the middle image of an open chain of `images` images.
+    ///
+    /// For an even number of images, the later of the two central images is returned.
+    pub const fn middle(images: usize) -> Self {
+        Self::Middle(images / 2)
+    }
+
+    /// Returns the ends of an open chain of `images` images.
+    pub const fn ends(images: usize) -> Self {
+        Self::Ends {
+            leading: 0,
+            trailing: images.saturating_sub(1),
+        }
+    }
+
+    /// Returns whether observables estimated here are evaluated at `image`.
+    pub const fn contains(&self, image: usize) -> bool {
+        match *self {
+            Self::Middle(middle) => image == middle,
+            Self::Ends { leading, trailing } => image == leading || image == trailing,
+        }
+    }
+
+    /// Returns the number of images observables are averaged over.
+    pub const fn images(&self) -> usize {
+        match *self {
+            Self::Middle(_) => 1,
+            Self::Ends { leading, trailing } => {
+                if leading == trailing {
+                    1
+                } else {
+                    2
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use crate::core::{AtomTypeInfo, GroupsIter};
+use std::iter::FusedIterator;
+
+/// A view pairing every group of an image with the positions of its atoms.
+///
+/// The positions of the image are stored contiguously, one group after another in the order
+/// of [`GroupsIter`]. The view is `Copy`, so potentials needing several passes over the groups
+/// iterate it again instead of collecting or cloning an iterator, and it can also be
+/// indexed by group.
+#[derive(Debug)]
+pub struct GroupsPositions<'a, T, V> {
+    atom_types: &'a [AtomTypeInfo<T>],
+    positions: &'a [V],
+}
+
+impl<T, V> Clone for GroupsPositions<'_, T, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V> Copy for GroupsPositions<'_, T, V> {}
+
+impl<'a, T, V> GroupsPositions<'a, T, V> {
+    /// Constructs a view of `positions`, which hold the atoms of every group of `atom_types`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of positions differs from the total number of atoms.
+    pub fn new(atom_types: &'a [AtomTypeInfo<T>], positions: &'a [V]) -> Self {
+        assert_eq!(
+            atom_types
+                .iter()
+                .map(|atom_type| atom_type.groups.total())
+                .sum::<usize>(),
+            positions.len(),
+            "there must be a position for every atom"
+        );
+        Self {
+            atom_types,
+            positions,
+        }
+    }
+
+    /// Returns the number of groups.
+    pub fn len(&self) -> usize {
+        self.atom_types
+            .iter()
+            .map(|atom_type| atom_type.groups.groups())
+            .sum()
+    }
+
+    /// Returns whether there are no groups.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the positions of every atom of the image.
+    pub fn positions(&self) -> &'a [V] {
+        self.positions
+    }
+
+    /// Returns the type of the group at `index` and the positions of its atoms,
+    /// or `None` if there is no such group.
+    pub fn get(&self, index: usize) -> Option<(&'a AtomTypeInfo<T>, &'a [V])> {
+        self.iter().nth(index)
+    }
+
+    /// Returns the index of the first atom of the group at `index` among all atoms,
+    /// or `None` if there is no such group.
+    pub fn offset(&self, index: usize) -> Option<usize> {
+        let mut groups = GroupsIter::from_atom_types(self.atom_types);
+        let offset = groups.by_ref().take(index).map(|(_, size)| size).sum();
+        groups.next().map(|_| offset)
+    }
+
+    /// Returns an iterator over the groups, each with the positions of its atoms.
+    pub fn iter(&self) -> GroupsPositionsIter<'a, T, V> {
+        GroupsPositionsIter {
+            groups: GroupsIter::from_atom_types(self.atom_types),
+            positions: self.positions,
+        }
+    }
+}
+
+impl<'a, T, V> IntoIterator for GroupsPositions<'a, T, V> {
+    type Item = (&'a AtomTypeInfo<T>, &'a [V]);
+    type IntoIter = GroupsPositionsIter<'a, T, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, V> IntoIterator for &GroupsPositions<'a, T, V> {
+    type Item = (&'a AtomTypeInfo<T>, &'a [V]);
+    type IntoIter = GroupsPositionsIter<'a, T, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the groups of a [`GroupsPositions`], each with the positions of its atoms.
+pub struct GroupsPositionsIter<'a, T, V> {
+    groups: GroupsIter<'a, T>,
+    positions: &'a [V],
+}
+
+impl<T, V> Clone for GroupsPositionsIter<'_, T, V> {
+    fn clone(&self) -> Self {
+        Self {
+            groups: self.groups.clone(),
+            positions: self.positions,
+        }
+    }
+}
+
+impl<'a, T, V> Iterator for GroupsPositionsIter<'a, T, V> {
+    type Item = (&'a AtomTypeInfo<T>, &'a [V]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (atom_type, size) = self.groups.next()?;
+        let (group, rest) = self.positions.split_at(size);
+        self.positions = rest;
+        Some((atom_type, group))
+    }
+}
+
+impl<T, V> FusedIterator for GroupsPositionsIter<'_, T, V> {}
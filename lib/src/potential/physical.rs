@@ -4,7 +4,23 @@ use super::GroupInTypeInImage;
 use macros::{efficient_alternatives, heavy_computation};
 
 mod atom_additive;
-pub use atom_additive::AtomAdditivePhysicalPotential;
+mod capped;
+mod contracted;
+mod frozen;
+pub use atom_additive::{AdditivePhysicalPotential, AtomAdditivePhysicalPotential};
+pub use capped::{Capped, ForceCap};
+pub use contracted::{ContractedError, ContractedPotentialPair, Contraction};
+mod learned;
+pub use learned::{Corrected, CorrectedError, CorrectionModel, CorrectionTrainer, TrainerError};
+mod reloadable;
+pub use reloadable::{ParameterStore, Reload, Reloadable};
+mod three_body;
+pub use three_body::{ThreeBodyPhysicalPotential, ThreeBodyPotential};
+mod time_dependent;
+pub use time_dependent::{
+    Piecewise, Ramp, Schedule, Sinusoid, TimeDependent, TimeDependentPotential, WorkSource,
+};
+mod virtual_sites;
 
 #[cfg(feature = "monte_carlo")]
 mod monte_carlo;
@@ -66,3 +82,11 @@ pub trait PhysicalPotential<T, V> {
         group_forces: &mut [V],
     ) -> Result<(), Self::Error>;
 }
+
+/// A trait for physical potentials that depend on the dimensions of the simulation box,
+/// e.g. through periodic images or a long-range correction.
+pub trait BoxDependent<T> {
+    /// Informs `self` that every dimension of the box has been scaled by the respective factor
+    /// in `factors`, e.g. by a volume move or a barostat.
+    fn scale_box(&mut self, factors: &[T]);
+}
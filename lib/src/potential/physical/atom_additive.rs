@@ -1,4 +1,4 @@
-use super::PhysicalPotential;
+use super::{PhysicalPotential, TimeDependent};
 use crate::{
     core::error::{EmptyError, InvalidIndexError},
     potential::GroupInTypeInImage,
@@ -27,6 +27,25 @@ impl<P> AdditivePhysicalPotential<P> {
     }
 }
 
+impl<P: ?Sized> AdditivePhysicalPotential<P> {
+    /// Returns a reference to the wrapped potential.
+    pub const fn inner(&self) -> &P {
+        &self.0
+    }
+
+    /// Returns a mutable reference to the wrapped potential.
+    pub const fn inner_mut(&mut self) -> &mut P {
+        &mut self.0
+    }
+}
+
+impl<T, P: TimeDependent<T> + ?Sized> TimeDependent<T> for AdditivePhysicalPotential<P> {
+    #[inline(always)]
+    fn set_time(&mut self, step: usize, time: T) {
+        self.0.set_time(step, time);
+    }
+}
+
 /// A trait for physical potentials that can be expressed as a sum
 /// of potentials that depend only on a single atom.
 ///
@@ -0,0 +1,231 @@
+use super::{PhysicalPotential, TimeDependent};
+use crate::{
+    core::Vector,
+    events::{EventBus, ForcesCapped},
+    potential::GroupInTypeInImage,
+};
+
+/// The magnitude above which forces are clamped and the number of steps during which
+/// they are, counted from the start of the run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ForceCap<T> {
+    threshold: T,
+    steps: usize,
+}
+
+impl<T: PartialOrd + From<f32>> ForceCap<T> {
+    /// Constructs a cap clamping forces to `threshold` during the first `steps` steps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `threshold` is not positive.
+    pub fn new(threshold: T, steps: usize) -> Self {
+        assert!(
+            threshold > T::from(0.0),
+            "the force cap must be a positive magnitude"
+        );
+        Self { threshold, steps }
+    }
+}
+
+impl<T> ForceCap<T> {
+    /// Returns the largest magnitude a force keeps.
+    pub fn threshold(&self) -> &T {
+        &self.threshold
+    }
+
+    /// Returns the number of steps during which forces are capped.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+}
+
+/// A wrapper that clamps the forces of a physical potential to the magnitude of a [`ForceCap`]
+/// during the first steps of a run, so that overlapping atoms of an imperfect initial structure
+/// do not blow the simulation up before it has equilibrated.
+///
+/// The potential energy is left as it is, so it is inconsistent with the capped forces,
+/// and the steps during which forces are capped belong to the equilibration
+/// rather than to the production statistics. Every evaluation which caps a force is queued
+/// as a [`ForcesCapped`] event, to be published by the driver with [`Capped::publish`].
+pub struct Capped<const N: usize, T, V, P> {
+    potential: P,
+    cap: ForceCap<T>,
+    step: usize,
+    events: Vec<ForcesCapped<T>>,
+    scratch: Vec<V>,
+}
+
+impl<const N: usize, T, V, P> Capped<N, T, V, P> {
+    /// Wraps `potential` such that its forces are capped according to `cap`.
+    pub fn new(potential: P, cap: ForceCap<T>) -> Self {
+        Self {
+            potential,
+            cap,
+            step: 0,
+            events: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Returns whether forces are capped in the current step.
+    pub fn is_active(&self) -> bool {
+        self.step < self.cap.steps
+    }
+
+    /// Returns a reference to the wrapped potential.
+    pub fn potential(&self) -> &P {
+        &self.potential
+    }
+
+    /// Returns a mutable reference to the wrapped potential.
+    pub fn potential_mut(&mut self) -> &mut P {
+        &mut self.potential
+    }
+
+    /// Returns the queued capping events, oldest first, and clears the queue.
+    pub fn take_events(&mut self) -> Vec<ForcesCapped<T>> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Publishes the queued capping events on `bus`, to be called by the driver
+    /// between two steps.
+    pub fn publish(&mut self, bus: &mut EventBus)
+    where
+        T: 'static,
+    {
+        for event in self.events.drain(..) {
+            bus.publish(&event);
+        }
+    }
+}
+
+impl<const N: usize, T, V, P> TimeDependent<T> for Capped<N, T, V, P> {
+    fn set_time(&mut self, step: usize, _time: T) {
+        self.step = step;
+    }
+}
+
+macro_rules! impl_capped {
+    ($($float:ty),*) => {
+        $(
+            impl<const N: usize, V, P> Capped<N, $float, V, P>
+            where
+                V: Vector<N, Element = $float> + Clone,
+            {
+                fn cap(&mut self, forces: &mut [V]) {
+                    if !self.is_active() {
+                        return;
+                    }
+                    let threshold_squared = self.cap.threshold * self.cap.threshold;
+                    let mut capped = 0;
+                    let mut largest_magnitude: $float = 0.0;
+                    for force in forces {
+                        let magnitude_squared = force.clone().magnitude_squared();
+                        if magnitude_squared > threshold_squared {
+                            let magnitude = magnitude_squared.sqrt();
+                            *force *= self.cap.threshold / magnitude;
+                            capped += 1;
+                            largest_magnitude = largest_magnitude.max(magnitude);
+                        }
+                    }
+                    if capped > 0 {
+                        self.events.push(ForcesCapped {
+                            step: self.step,
+                            capped,
+                            largest_magnitude,
+                        });
+                    }
+                }
+            }
+
+            impl<const N: usize, V, P> PhysicalPotential<$float, V> for Capped<N, $float, V, P>
+            where
+                V: Vector<N, Element = $float> + Clone,
+                P: PhysicalPotential<$float, V>,
+            {
+                type Error = P::Error;
+
+                fn calculate_potential_set_forces(
+                    &mut self,
+                    positions: &GroupInTypeInImage<V>,
+                    group_forces: &mut [V],
+                ) -> Result<$float, Self::Error> {
+                    let potential_energy = self
+                        .potential
+                        .calculate_potential_set_forces(positions, group_forces)?;
+                    self.cap(group_forces);
+                    Ok(potential_energy)
+                }
+
+                fn calculate_potential_add_forces(
+                    &mut self,
+                    positions: &GroupInTypeInImage<V>,
+                    group_forces: &mut [V],
+                ) -> Result<$float, Self::Error> {
+                    if !self.is_active() {
+                        return self
+                            .potential
+                            .calculate_potential_add_forces(positions, group_forces);
+                    }
+                    // Only the contribution of this potential is capped.
+                    let mut scratch = std::mem::take(&mut self.scratch);
+                    scratch.clear();
+                    scratch.resize(group_forces.len(), V::zero());
+                    let potential_energy = self
+                        .potential
+                        .calculate_potential_set_forces(positions, &mut scratch)?;
+                    self.cap(&mut scratch);
+                    for (force, contribution) in group_forces.iter_mut().zip(&scratch) {
+                        *force += contribution.clone();
+                    }
+                    self.scratch = scratch;
+                    Ok(potential_energy)
+                }
+
+                fn calculate_potential(
+                    &mut self,
+                    positions: &GroupInTypeInImage<V>,
+                ) -> Result<$float, Self::Error> {
+                    #[allow(deprecated)]
+                    self.potential.calculate_potential(positions)
+                }
+
+                fn set_forces(
+                    &mut self,
+                    positions: &GroupInTypeInImage<V>,
+                    group_forces: &mut [V],
+                ) -> Result<(), Self::Error> {
+                    #[allow(deprecated)]
+                    self.potential.set_forces(positions, group_forces)?;
+                    self.cap(group_forces);
+                    Ok(())
+                }
+
+                fn add_forces(
+                    &mut self,
+                    positions: &GroupInTypeInImage<V>,
+                    group_forces: &mut [V],
+                ) -> Result<(), Self::Error> {
+                    if !self.is_active() {
+                        #[allow(deprecated)]
+                        return self.potential.add_forces(positions, group_forces);
+                    }
+                    let mut scratch = std::mem::take(&mut self.scratch);
+                    scratch.clear();
+                    scratch.resize(group_forces.len(), V::zero());
+                    #[allow(deprecated)]
+                    self.potential.set_forces(positions, &mut scratch)?;
+                    self.cap(&mut scratch);
+                    for (force, contribution) in group_forces.iter_mut().zip(&scratch) {
+                        *force += contribution.clone();
+                    }
+                    self.scratch = scratch;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_capped!(f32, f64);
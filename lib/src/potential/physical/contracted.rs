@@ -0,0 +1,297 @@
+use super::PhysicalPotential;
+use crate::{numeric::Real, potential::GroupInTypeInImage};
+use std::{
+    error::Error,
+    f64::consts::TAU,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::{AddAssign, Mul, SubAssign},
+};
+
+/// The linear map from the images of a ring polymer to a ring polymer with
+/// fewer images that keeps only the lowest-frequency free ring polymer modes.
+///
+/// The contracted images are placed at equal imaginary-time intervals
+/// and obtained by Fourier interpolation of the full ring polymer,
+/// so a single contracted image is the centroid.
+#[derive(Clone, Debug)]
+pub struct Contraction<T> {
+    images: usize,
+    contracted_images: usize,
+    coefficients: Box<[T]>,
+}
+
+impl<T: Real> Contraction<T> {
+    /// Constructs the contraction of a ring polymer with `images` images
+    /// to `contracted_images` images.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `contracted_images` is zero or greater than `images`.
+    pub fn new(images: usize, contracted_images: usize) -> Self {
+        assert!(
+            contracted_images > 0 && contracted_images <= images,
+            "the number of contracted images must be between 1 and the number of images"
+        );
+        let coefficients = (0..contracted_images)
+            .flat_map(|contracted_image| {
+                (0..images).map(move |image| {
+                    T::from_f64(Self::coefficient_f64(
+                        images,
+                        contracted_images,
+                        contracted_image,
+                        image,
+                    ))
+                })
+            })
+            .collect();
+        Self {
+            images,
+            contracted_images,
+            coefficients,
+        }
+    }
+
+    fn coefficient_f64(
+        images: usize,
+        contracted_images: usize,
+        contracted_image: usize,
+        image: usize,
+    ) -> f64 {
+        if contracted_images == images {
+            return if contracted_image == image { 1.0 } else { 0.0 };
+        }
+        let contracted_time = contracted_image as f64 / contracted_images as f64;
+        let time = image as f64 / images as f64;
+        let mut coefficient = 1.0;
+        for mode in 1..contracted_images.div_ceil(2) {
+            coefficient += 2.0 * (TAU * mode as f64 * (contracted_time - time)).cos();
+        }
+        if contracted_images.is_multiple_of(2) {
+            // Only the cosine part of the highest mode survives at the contracted images.
+            let mode = (contracted_images / 2) as f64;
+            coefficient += 2.0 * (TAU * mode * contracted_time).cos() * (TAU * mode * time).cos();
+        }
+        coefficient / images as f64
+    }
+}
+
+impl<T> Contraction<T> {
+    /// Returns the number of images of the full ring polymer.
+    pub fn images(&self) -> usize {
+        self.images
+    }
+
+    /// Returns the number of images of the contracted ring polymer.
+    pub fn contracted_images(&self) -> usize {
+        self.contracted_images
+    }
+
+    /// Returns the derivative of the position of `contracted_image`
+    /// with respect to the position of `image`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn coefficient(&self, contracted_image: usize, image: usize) -> &T {
+        assert!(image < self.images, "the image is out of bounds");
+        &self.coefficients[contracted_image * self.images + image]
+    }
+}
+
+impl<T: Clone> Contraction<T> {
+    /// Sets the positions of a group in every contracted image
+    /// from its positions in every image of the full ring polymer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the numbers of images do not match the contraction
+    /// or the groups are of different sizes.
+    pub fn contract<V>(&self, positions: &[&[V]], contracted_positions: &mut [&mut [V]])
+    where
+        V: Clone + Default + AddAssign + Mul<T, Output = V>,
+    {
+        assert_eq!(positions.len(), self.images);
+        assert_eq!(contracted_positions.len(), self.contracted_images);
+        for (coefficients, contracted) in self
+            .coefficients
+            .chunks_exact(self.images)
+            .zip(contracted_positions.iter_mut())
+        {
+            contracted.fill_with(V::default);
+            for (coefficient, image_positions) in coefficients.iter().zip(positions) {
+                assert_eq!(image_positions.len(), contracted.len());
+                for (contracted_position, position) in contracted.iter_mut().zip(*image_positions) {
+                    *contracted_position += position.clone() * coefficient.clone();
+                }
+            }
+        }
+    }
+
+    /// Adds the forces of a group in every image of the full ring polymer
+    /// arising from forces acting on it in the contracted images.
+    ///
+    /// `scale` multiplies every contracted force and is usually the ratio of the numbers
+    /// of images, under which the forces are the gradient of the contracted energies
+    /// summed over the contracted images and multiplied by `scale`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the numbers of images do not match the contraction
+    /// or the groups are of different sizes.
+    pub fn expand_add<V>(&self, contracted_forces: &[&[V]], forces: &mut [&mut [V]], scale: T)
+    where
+        V: Clone + AddAssign + Mul<T, Output = V>,
+        T: Mul<Output = T>,
+    {
+        assert_eq!(contracted_forces.len(), self.contracted_images);
+        assert_eq!(forces.len(), self.images);
+        for (coefficients, contracted) in self
+            .coefficients
+            .chunks_exact(self.images)
+            .zip(contracted_forces)
+        {
+            for (coefficient, image_forces) in coefficients.iter().zip(forces.iter_mut()) {
+                assert_eq!(image_forces.len(), contracted.len());
+                let factor = coefficient.clone() * scale.clone();
+                for (force, contracted_force) in image_forces.iter_mut().zip(*contracted) {
+                    *force += contracted_force.clone() * factor.clone();
+                }
+            }
+        }
+    }
+}
+
+/// An error returned by one of the potentials of a [`ContractedPotentialPair`].
+#[derive(Clone, Copy, Debug)]
+pub enum ContractedError<E, R> {
+    /// The expensive potential failed.
+    Expensive(E),
+    /// The reference potential failed.
+    Reference(R),
+}
+
+impl<E: Display, R: Display> Display for ContractedError<E, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Expensive(err) => write!(f, "the expensive potential failed: {}", err),
+            Self::Reference(err) => write!(f, "the reference potential failed: {}", err),
+        }
+    }
+}
+
+impl<E: Error + 'static, R: Error + 'static> Error for ContractedError<E, R> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Expensive(err) => Some(err),
+            Self::Reference(err) => Some(err),
+        }
+    }
+}
+
+/// An expensive physical potential evaluated on a contracted ring polymer
+/// together with a cheap reference potential evaluated on every image.
+///
+/// The physical potential energy of the full ring polymer is approximated as
+/// the reference potential summed over the full ring polymer plus the difference
+/// between the expensive and the reference potentials summed over the contracted
+/// ring polymer and scaled by the ratio of the numbers of images.
+/// This is accurate whenever the difference varies slowly within the ring polymer,
+/// e.g. when the reference is a cheaper electronic surface with the same short-range part.
+///
+/// In each step, the force assembly
+/// 1. calls [`calculate_reference_set_forces`] for every group in every image,
+/// 2. [`contract`]s the positions of every group,
+/// 3. calls [`calculate_difference_set_forces`] for every group in every contracted image,
+/// 4. and adds the contracted forces to the forces of every image with [`expand_add`].
+///
+/// [`calculate_reference_set_forces`]: Self::calculate_reference_set_forces
+/// [`contract`]: Contraction::contract
+/// [`calculate_difference_set_forces`]: Self::calculate_difference_set_forces
+/// [`expand_add`]: Contraction::expand_add
+pub struct ContractedPotentialPair<T, V, E, R> {
+    pub(crate) expensive: E,
+    pub(crate) reference: R,
+    pub(crate) contraction: Contraction<T>,
+    pub(crate) scratch: Vec<V>,
+}
+
+impl<T, V, E, R> ContractedPotentialPair<T, V, E, R> {
+    /// Constructs a new `ContractedPotentialPair`.
+    pub fn new(expensive: E, reference: R, contraction: Contraction<T>) -> Self {
+        Self {
+            expensive,
+            reference,
+            contraction,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Returns the contraction of the ring polymer.
+    pub fn contraction(&self) -> &Contraction<T> {
+        &self.contraction
+    }
+
+    /// Returns a mutable reference to the expensive potential.
+    pub fn expensive_mut(&mut self) -> &mut E {
+        &mut self.expensive
+    }
+
+    /// Returns a mutable reference to the reference potential.
+    pub fn reference_mut(&mut self) -> &mut R {
+        &mut self.reference
+    }
+}
+
+impl<T, V, E, R> ContractedPotentialPair<T, V, E, R>
+where
+    T: Real + SubAssign,
+    V: Clone + SubAssign,
+    E: PhysicalPotential<T, V>,
+    R: PhysicalPotential<T, V>,
+{
+    /// Returns the ratio of the number of images to the number of contracted images,
+    /// which scales every contracted contribution.
+    pub fn scale(&self) -> T {
+        T::from_f64(self.contraction.images as f64 / self.contraction.contracted_images as f64)
+    }
+
+    /// Calculates the contribution of this group in an image of the full ring polymer
+    /// to the reference potential energy and sets the forces of this group accordingly.
+    pub fn calculate_reference_set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, ContractedError<E::Error, R::Error>> {
+        self.reference
+            .calculate_potential_set_forces(positions, group_forces)
+            .map_err(ContractedError::Reference)
+    }
+
+    /// Calculates the difference between the expensive and the reference potential energies
+    /// of this group in a contracted image and sets the contracted forces of this group
+    /// to the difference between the respective forces.
+    ///
+    /// Returns the difference scaled by [`scale`](Self::scale), which is
+    /// the correction to the physical potential energy of the full ring polymer
+    /// once summed over the contracted images.
+    pub fn calculate_difference_set_forces(
+        &mut self,
+        contracted_positions: &GroupInTypeInImage<V>,
+        contracted_forces: &mut [V],
+    ) -> Result<T, ContractedError<E::Error, R::Error>> {
+        let mut difference = self
+            .expensive
+            .calculate_potential_set_forces(contracted_positions, contracted_forces)
+            .map_err(ContractedError::Expensive)?;
+        self.scratch.clear();
+        self.scratch.extend_from_slice(contracted_forces);
+        difference -= self
+            .reference
+            .calculate_potential_set_forces(contracted_positions, &mut self.scratch)
+            .map_err(ContractedError::Reference)?;
+        for (force, reference_force) in contracted_forces.iter_mut().zip(&self.scratch) {
+            *force -= reference_force.clone();
+        }
+        Ok(difference * self.scale())
+    }
+}
@@ -0,0 +1,62 @@
+use super::PhysicalPotential;
+use crate::{core::Frozen, potential::GroupInTypeInImage};
+
+impl<T, V, P> PhysicalPotential<T, V> for Frozen<P>
+where
+    V: Default,
+    P: PhysicalPotential<T, V> + ?Sized,
+{
+    type Error = P::Error;
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let potential_energy = self
+            .inner
+            .calculate_potential_set_forces(positions, group_forces)?;
+        self.zero_frozen(group_forces);
+        Ok(potential_energy)
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let potential_energy = self
+            .inner
+            .calculate_potential_add_forces(positions, group_forces)?;
+        self.zero_frozen(group_forces);
+        Ok(potential_energy)
+    }
+
+    #[inline(always)]
+    fn calculate_potential(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+        #[allow(deprecated)]
+        self.inner.calculate_potential(positions)
+    }
+
+    fn set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.inner.set_forces(positions, group_forces)?;
+        self.zero_frozen(group_forces);
+        Ok(())
+    }
+
+    fn add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.inner.add_forces(positions, group_forces)?;
+        self.zero_frozen(group_forces);
+        Ok(())
+    }
+}
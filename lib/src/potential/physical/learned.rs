@@ -0,0 +1,295 @@
+use super::PhysicalPotential;
+use crate::{
+    core::sync_ops::{SyncBroadcastReceiver, SyncBroadcastSender},
+    potential::GroupInTypeInImage,
+};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    num::NonZeroUsize,
+    ops::Add,
+};
+
+/// A trait for models that learn a correction to a physical potential on the fly
+/// from sampled configurations, e.g. a Gaussian process fitted to the difference
+/// between an expensive reference and the potential used in the run.
+pub trait CorrectionModel<V> {
+    /// The correction potential produced by a fit, which is sent to every image.
+    type Correction;
+    /// The type associated with an error returned by the implementor.
+    type Error;
+
+    /// Adds a configuration of a group sampled at `step` to the training data.
+    fn observe(&mut self, step: usize, positions: &[V]) -> Result<(), Self::Error>;
+
+    /// Fits the model to the training data and returns the updated correction.
+    fn fit(&mut self) -> Result<Self::Correction, Self::Error>;
+}
+
+/// Returns whether the correction is updated at `step`, i.e. whether
+/// it is a positive multiple of `stride`.
+fn is_update_step(stride: NonZeroUsize, step: usize) -> bool {
+    step > 0 && step.is_multiple_of(stride.get())
+}
+
+/// An error returned by a [`CorrectionTrainer`].
+#[derive(Clone, Copy, Debug)]
+pub enum TrainerError<M, S> {
+    /// The model failed to observe or fit.
+    Model(M),
+    /// The correction could not be sent to the images.
+    Broadcast(S),
+}
+
+impl<M: Display, S: Display> Display for TrainerError<M, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Model(err) => write!(f, "the correction model failed: {}", err),
+            Self::Broadcast(err) => write!(f, "failed to broadcast the correction: {}", err),
+        }
+    }
+}
+
+impl<M: Error + 'static, S: Error + 'static> Error for TrainerError<M, S> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Model(err) => Some(err),
+            Self::Broadcast(err) => Some(err),
+        }
+    }
+}
+
+/// The side of the learned correction which owns the model.
+///
+/// Lives on a single thread, which feeds it the sampled configurations
+/// and calls [`CorrectionTrainer::step`] after every step. Every `stride` steps,
+/// the model is refitted and the correction is broadcast to the [`Corrected`]
+/// potential of every image, which expects it at the same step.
+pub struct CorrectionTrainer<V, M, S> {
+    pub(crate) model: M,
+    pub(crate) sender: S,
+    pub(crate) stride: NonZeroUsize,
+    pub(crate) phantom: PhantomData<fn(&[V])>,
+}
+
+impl<V, M, S> CorrectionTrainer<V, M, S> {
+    /// Constructs a trainer refitting `model` every `stride` steps.
+    pub fn new(model: M, sender: S, stride: NonZeroUsize) -> Self {
+        Self {
+            model,
+            sender,
+            stride,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+}
+
+impl<V, M, S> CorrectionTrainer<V, M, S>
+where
+    M: CorrectionModel<V>,
+    S: SyncBroadcastSender<M::Correction>,
+{
+    /// Adds a configuration of a group sampled at `step` to the training data.
+    pub fn observe(
+        &mut self,
+        step: usize,
+        positions: &[V],
+    ) -> Result<(), TrainerError<M::Error, S::Error>> {
+        self.model
+            .observe(step, positions)
+            .map_err(TrainerError::Model)
+    }
+
+    /// Refits the model and broadcasts the correction if `step` is an update step.
+    ///
+    /// Returns whether the correction has been updated.
+    pub fn step(&mut self, step: usize) -> Result<bool, TrainerError<M::Error, S::Error>> {
+        if !is_update_step(self.stride, step) {
+            return Ok(false);
+        }
+        let correction = self.model.fit().map_err(TrainerError::Model)?;
+        self.sender
+            .broadcast(correction)
+            .map_err(TrainerError::Broadcast)?;
+        Ok(true)
+    }
+}
+
+/// An error returned by a [`Corrected`] potential.
+#[derive(Clone, Copy, Debug)]
+pub enum CorrectedError<P, C> {
+    /// The base potential failed.
+    Potential(P),
+    /// The correction failed.
+    Correction(C),
+}
+
+impl<P: Display, C: Display> Display for CorrectedError<P, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Potential(err) => write!(f, "the base potential failed: {}", err),
+            Self::Correction(err) => write!(f, "the learned correction failed: {}", err),
+        }
+    }
+}
+
+impl<P: Error + 'static, C: Error + 'static> Error for CorrectedError<P, C> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Potential(err) => Some(err),
+            Self::Correction(err) => Some(err),
+        }
+    }
+}
+
+/// A physical potential plus a correction learned on the fly by a [`CorrectionTrainer`].
+///
+/// There is no correction until the first update. The driver calls
+/// [`Corrected::synchronize`] before every step, which blocks at the update steps
+/// until the new correction arrives, so every image switches to it in the same step.
+pub struct Corrected<P, C, R> {
+    pub(crate) potential: P,
+    pub(crate) correction: Option<C>,
+    pub(crate) receiver: R,
+    pub(crate) stride: NonZeroUsize,
+}
+
+impl<P, C, R> Corrected<P, C, R> {
+    /// Wraps `potential` to be corrected by the corrections received every `stride` steps,
+    /// which must be the stride of the trainer.
+    pub fn new(potential: P, receiver: R, stride: NonZeroUsize) -> Self {
+        Self {
+            potential,
+            correction: None,
+            receiver,
+            stride,
+        }
+    }
+
+    /// Returns a reference to the base potential.
+    pub fn potential(&self) -> &P {
+        &self.potential
+    }
+
+    /// Returns the current correction, if any has been received.
+    pub fn correction(&self) -> Option<&C> {
+        self.correction.as_ref()
+    }
+
+    /// Receives the updated correction if `step` is an update step.
+    ///
+    /// Returns whether the correction has been updated.
+    pub fn synchronize(&mut self, step: usize) -> Result<bool, R::Error>
+    where
+        R: SyncBroadcastReceiver<C>,
+    {
+        if !is_update_step(self.stride, step) {
+            return Ok(false);
+        }
+        self.correction = Some(self.receiver.receive_broadcast()?);
+        Ok(true)
+    }
+}
+
+impl<T, V, P, C, R> PhysicalPotential<T, V> for Corrected<P, C, R>
+where
+    T: Add<Output = T>,
+    P: PhysicalPotential<T, V>,
+    C: PhysicalPotential<T, V>,
+{
+    type Error = CorrectedError<P::Error, C::Error>;
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let energy = self
+            .potential
+            .calculate_potential_set_forces(positions, group_forces)
+            .map_err(CorrectedError::Potential)?;
+        match &mut self.correction {
+            Some(correction) => Ok(energy
+                + correction
+                    .calculate_potential_add_forces(positions, group_forces)
+                    .map_err(CorrectedError::Correction)?),
+            None => Ok(energy),
+        }
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let energy = self
+            .potential
+            .calculate_potential_add_forces(positions, group_forces)
+            .map_err(CorrectedError::Potential)?;
+        match &mut self.correction {
+            Some(correction) => Ok(energy
+                + correction
+                    .calculate_potential_add_forces(positions, group_forces)
+                    .map_err(CorrectedError::Correction)?),
+            None => Ok(energy),
+        }
+    }
+
+    fn calculate_potential(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+        #[allow(deprecated)]
+        let energy = self
+            .potential
+            .calculate_potential(positions)
+            .map_err(CorrectedError::Potential)?;
+        match &mut self.correction {
+            #[allow(deprecated)]
+            Some(correction) => Ok(energy
+                + correction
+                    .calculate_potential(positions)
+                    .map_err(CorrectedError::Correction)?),
+            None => Ok(energy),
+        }
+    }
+
+    fn set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.potential
+            .set_forces(positions, group_forces)
+            .map_err(CorrectedError::Potential)?;
+        if let Some(correction) = &mut self.correction {
+            #[allow(deprecated)]
+            correction
+                .add_forces(positions, group_forces)
+                .map_err(CorrectedError::Correction)?;
+        }
+        Ok(())
+    }
+
+    fn add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.potential
+            .add_forces(positions, group_forces)
+            .map_err(CorrectedError::Potential)?;
+        if let Some(correction) = &mut self.correction {
+            #[allow(deprecated)]
+            correction
+                .add_forces(positions, group_forces)
+                .map_err(CorrectedError::Correction)?;
+        }
+        Ok(())
+    }
+}
@@ -76,4 +76,21 @@ pub trait MonteCarloPhysicalPotential<T, V>: PhysicalPotential<T, V> {
         positions: &GroupInTypeInImage<V>,
         group_forces: &mut [V],
     ) -> Result<(), <Self as MonteCarloPhysicalPotential<T, V>>::Error>;
+
+    /// Recalculates the contribution of this group to the total physical potential energy
+    /// of the image from scratch after a change in the positions of every atom,
+    /// e.g. in a volume move, rather than the difference after a single-atom move.
+    ///
+    /// Implementors that keep state between single-atom moves, such as neighbor lists
+    /// or partial sums, should override this method to rebuild it.
+    ///
+    /// Returns the contribution to the total physical potential energy.
+    #[heavy_computation]
+    fn recalculate_potential(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+    ) -> Result<T, <Self as PhysicalPotential<T, V>>::Error> {
+        #[allow(deprecated)]
+        self.calculate_potential(positions)
+    }
 }
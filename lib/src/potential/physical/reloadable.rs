@@ -0,0 +1,207 @@
+use super::PhysicalPotential;
+use crate::potential::GroupInTypeInImage;
+use std::sync::{
+    Arc, Mutex, PoisonError, RwLock,
+    atomic::{AtomicUsize, Ordering},
+};
+
+/// A trait for potentials whose parameters can be replaced during a run.
+pub trait Reload {
+    /// The parameters, e.g. the Lennard-Jones epsilon and sigma or the strength of a bias.
+    type Parameters;
+
+    /// Replaces the parameters of `self`.
+    fn reload(&mut self, parameters: &Self::Parameters);
+}
+
+struct Shared<P> {
+    pending: Mutex<Option<P>>,
+    active: RwLock<P>,
+    version: AtomicUsize,
+}
+
+/// The parameters shared by every copy of a potential in the simulation,
+/// which may be replaced between steps for steering or annealing.
+///
+/// New parameters are staged from any thread, e.g. one watching a file,
+/// and take effect only when the driver commits them between two steps,
+/// so every image and group of a step sees the same parameters.
+pub struct ParameterStore<P> {
+    shared: Arc<Shared<P>>,
+}
+
+impl<P> Clone for ParameterStore<P> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<P> ParameterStore<P> {
+    /// Constructs a store holding `parameters`.
+    pub fn new(parameters: P) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                pending: Mutex::new(None),
+                active: RwLock::new(parameters),
+                version: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Stages `parameters` to replace the active ones at the next commit,
+    /// discarding any parameters staged before.
+    pub fn stage(&self, parameters: P) {
+        // The locks are never held across a panic, so poisoned data is still valid.
+        *self
+            .shared
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(parameters);
+    }
+
+    /// Returns whether parameters are waiting for a commit.
+    pub fn is_pending(&self) -> bool {
+        self.shared
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Makes the staged parameters active, to be called by the driver between two steps
+    /// while no potential is being evaluated.
+    ///
+    /// Returns whether there were staged parameters.
+    pub fn commit(&self) -> bool {
+        let Some(parameters) = self
+            .shared
+            .pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        else {
+            return false;
+        };
+        *self
+            .shared
+            .active
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = parameters;
+        self.shared.version.fetch_add(1, Ordering::Release);
+        true
+    }
+
+    /// Returns the number of commits so far.
+    pub fn version(&self) -> usize {
+        self.shared.version.load(Ordering::Acquire)
+    }
+
+    /// Calls `f` with the active parameters.
+    pub fn with_active<R, F: FnOnce(&P) -> R>(&self, f: F) -> R {
+        f(&self
+            .shared
+            .active
+            .read()
+            .unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// A wrapper that reloads the parameters of a potential from a [`ParameterStore`]
+/// whenever new ones have been committed.
+pub struct Reloadable<P: Reload> {
+    potential: P,
+    store: ParameterStore<P::Parameters>,
+    version: usize,
+}
+
+impl<P: Reload> Reloadable<P> {
+    /// Wraps `potential` and loads the active parameters of `store` into it.
+    pub fn new(mut potential: P, store: ParameterStore<P::Parameters>) -> Self {
+        let version = store.version();
+        store.with_active(|parameters| potential.reload(parameters));
+        Self {
+            potential,
+            store,
+            version,
+        }
+    }
+
+    /// Loads the active parameters if they have changed since the last load.
+    ///
+    /// Called before every evaluation of the potential; returns whether they have changed.
+    pub fn refresh(&mut self) -> bool {
+        let version = self.store.version();
+        if version == self.version {
+            return false;
+        }
+        self.store
+            .with_active(|parameters| self.potential.reload(parameters));
+        self.version = version;
+        true
+    }
+
+    /// Returns the store the parameters are loaded from.
+    pub fn store(&self) -> &ParameterStore<P::Parameters> {
+        &self.store
+    }
+
+    /// Returns a reference to the wrapped potential.
+    pub fn potential(&self) -> &P {
+        &self.potential
+    }
+}
+
+impl<T, V, P> PhysicalPotential<T, V> for Reloadable<P>
+where
+    P: PhysicalPotential<T, V> + Reload,
+{
+    type Error = P::Error;
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        self.refresh();
+        self.potential
+            .calculate_potential_set_forces(positions, group_forces)
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        self.refresh();
+        self.potential
+            .calculate_potential_add_forces(positions, group_forces)
+    }
+
+    fn calculate_potential(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+        self.refresh();
+        #[allow(deprecated)]
+        self.potential.calculate_potential(positions)
+    }
+
+    fn set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.refresh();
+        #[allow(deprecated)]
+        self.potential.set_forces(positions, group_forces)
+    }
+
+    fn add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.refresh();
+        #[allow(deprecated)]
+        self.potential.add_forces(positions, group_forces)
+    }
+}
@@ -0,0 +1,190 @@
+use super::PhysicalPotential;
+use crate::{core::error::InvalidIndexError, potential::GroupInTypeInImage};
+use std::ops::{Add, AddAssign};
+
+/// A trait for physical potentials that can be expressed as a sum of terms
+/// that depend on pairs and triplets of atoms, such as the Stillinger-Weber
+/// or the Axilrod-Teller potentials.
+///
+/// For any type `P` that implements this trait, [`ThreeBodyPotential<P>`]
+/// automatically implements [`PhysicalPotential`].
+pub trait ThreeBodyPhysicalPotential<T, V> {
+    /// The type of error returned by the terms of `Self`.
+    type ErrorTerm;
+    /// The type of error [`ThreeBodyPotential<Self>`] returns.
+    type ErrorSystem: From<Self::ErrorTerm> + From<InvalidIndexError>;
+
+    /// Sets `pairs` to the pairs of atoms of the group at `positions`
+    /// whose two-body term may be nonzero, each pair listed once.
+    fn list_pairs(
+        &mut self,
+        positions: &[V],
+        pairs: &mut Vec<[usize; 2]>,
+    ) -> Result<(), Self::ErrorSystem>;
+
+    /// Sets `triplets` to the triplets of atoms of the group at `positions`
+    /// whose three-body term may be nonzero, given the pairs listed by
+    /// [`list_pairs`](ThreeBodyPhysicalPotential::list_pairs).
+    ///
+    /// The first atom of a triplet is the vertex of the angle formed by the other two,
+    /// and every angle is listed once.
+    fn list_triplets(
+        &mut self,
+        positions: &[V],
+        pairs: &[[usize; 2]],
+        triplets: &mut Vec<[usize; 3]>,
+    ) -> Result<(), Self::ErrorSystem>;
+
+    /// Calculates the two-body term of a pair of atoms.
+    ///
+    /// Returns the energy of the term and the force it exerts on each atom.
+    fn two_body(&mut self, positions: [&V; 2]) -> Result<(T, [V; 2]), Self::ErrorTerm>;
+
+    /// Calculates the three-body term of a triplet of atoms, the first being the vertex.
+    ///
+    /// Returns the energy of the term and the force it exerts on each atom.
+    fn three_body(&mut self, positions: [&V; 3]) -> Result<(T, [V; 3]), Self::ErrorTerm>;
+}
+
+/// A wrapper for implementors of the [`ThreeBodyPhysicalPotential`] trait.
+///
+/// Keeps the lists of pairs and triplets between evaluations to reuse their storage.
+pub struct ThreeBodyPotential<P: ?Sized> {
+    pairs: Vec<[usize; 2]>,
+    triplets: Vec<[usize; 3]>,
+    pub(crate) inner: P,
+}
+
+impl<P> ThreeBodyPotential<P> {
+    /// Wraps the provided value with `ThreeBodyPotential`.
+    pub const fn new(inner: P) -> Self {
+        Self {
+            pairs: Vec::new(),
+            triplets: Vec::new(),
+            inner,
+        }
+    }
+}
+
+impl<P: ?Sized> ThreeBodyPotential<P> {
+    /// Returns a reference to the wrapped potential.
+    pub const fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped potential.
+    pub const fn inner_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+
+    /// Returns the triplets listed at the last evaluation.
+    pub fn triplets(&self) -> &[[usize; 3]] {
+        &self.triplets
+    }
+
+    /// Returns the energy of the group at `positions` and adds the forces to `forces`, if given.
+    fn accumulate<T, V>(
+        &mut self,
+        positions: &[V],
+        mut forces: Option<&mut [V]>,
+    ) -> Result<T, P::ErrorSystem>
+    where
+        T: Add<Output = T> + Default,
+        V: AddAssign,
+        P: ThreeBodyPhysicalPotential<T, V>,
+    {
+        let get = |atom: usize| {
+            positions
+                .get(atom)
+                .ok_or(InvalidIndexError::new(atom, positions.len()))
+        };
+        self.inner.list_pairs(positions, &mut self.pairs)?;
+        self.inner
+            .list_triplets(positions, &self.pairs, &mut self.triplets)?;
+
+        let mut energy = T::default();
+        for &[first, second] in &self.pairs {
+            let (term_energy, term_forces) = self.inner.two_body([get(first)?, get(second)?])?;
+            energy = energy + term_energy;
+            if let Some(forces) = forces.as_deref_mut() {
+                add_term_forces(forces, [first, second], term_forces)?;
+            }
+        }
+        for &[vertex, first, second] in &self.triplets {
+            let (term_energy, term_forces) =
+                self.inner
+                    .three_body([get(vertex)?, get(first)?, get(second)?])?;
+            energy = energy + term_energy;
+            if let Some(forces) = forces.as_deref_mut() {
+                add_term_forces(forces, [vertex, first, second], term_forces)?;
+            }
+        }
+        Ok(energy)
+    }
+}
+
+fn add_term_forces<V: AddAssign, const K: usize>(
+    forces: &mut [V],
+    atoms: [usize; K],
+    term_forces: [V; K],
+) -> Result<(), InvalidIndexError> {
+    let len = forces.len();
+    for (atom, force) in atoms.into_iter().zip(term_forces) {
+        *forces
+            .get_mut(atom)
+            .ok_or(InvalidIndexError::new(atom, len))? += force;
+    }
+    Ok(())
+}
+
+impl<T, V, P> PhysicalPotential<T, V> for ThreeBodyPotential<P>
+where
+    T: Add<Output = T> + Default,
+    V: AddAssign + Default,
+    P: ThreeBodyPhysicalPotential<T, V> + ?Sized,
+{
+    type Error = P::ErrorSystem;
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        group_forces.fill_with(V::default);
+        self.accumulate(positions.read(), Some(group_forces))
+    }
+
+    #[inline(always)]
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        self.accumulate(positions.read(), Some(group_forces))
+    }
+
+    #[inline(always)]
+    fn calculate_potential(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+        self.accumulate(positions.read(), None)
+    }
+
+    fn set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        group_forces.fill_with(V::default);
+        self.accumulate::<T, V>(positions.read(), Some(group_forces))?;
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        self.accumulate::<T, V>(positions.read(), Some(group_forces))?;
+        Ok(())
+    }
+}
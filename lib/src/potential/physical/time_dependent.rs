@@ -0,0 +1,290 @@
+use super::PhysicalPotential;
+use crate::potential::GroupInTypeInImage;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Sub};
+
+/// A trait for objects whose behaviour depends on the simulation time.
+pub trait TimeDependent<T> {
+    /// Informs `self` of the current step and simulation time.
+    ///
+    /// Called by the driver before the potential is evaluated in each step.
+    fn set_time(&mut self, step: usize, time: T);
+}
+
+/// A trait for objects that perform work on the system.
+pub trait WorkSource<T> {
+    /// Returns the work performed since the previous call,
+    /// or `None` if no work has been performed.
+    fn take_work(&mut self) -> Option<T>;
+}
+
+/// A trait for schedules that yield a dimensionless factor
+/// as a function of the simulation time.
+pub trait Schedule<T> {
+    /// Returns the factor at `time`.
+    fn factor(&self, time: &T) -> T;
+}
+
+/// A schedule that changes linearly from `start_factor` to `end_factor`
+/// between `start_time` and `end_time` and stays constant outside of that interval.
+#[derive(Clone, Copy, Debug)]
+pub struct Ramp<T> {
+    /// The time at which the ramp begins.
+    pub start_time: T,
+    /// The time at which the ramp ends.
+    pub end_time: T,
+    /// The factor before the ramp begins.
+    pub start_factor: T,
+    /// The factor after the ramp ends.
+    pub end_factor: T,
+}
+
+impl<T> Schedule<T> for Ramp<T>
+where
+    T: Clone + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    fn factor(&self, time: &T) -> T {
+        if *time <= self.start_time {
+            self.start_factor.clone()
+        } else if *time >= self.end_time {
+            self.end_factor.clone()
+        } else {
+            self.start_factor.clone()
+                + (self.end_factor.clone() - self.start_factor.clone())
+                    * (time.clone() - self.start_time.clone())
+                    / (self.end_time.clone() - self.start_time.clone())
+        }
+    }
+}
+
+/// A schedule that oscillates as
+/// `offset + amplitude * sin(angular_frequency * time + phase)`.
+#[derive(Clone, Copy, Debug)]
+pub struct Sinusoid<T> {
+    /// The mean value of the factor.
+    pub offset: T,
+    /// The amplitude of the oscillation.
+    pub amplitude: T,
+    /// The angular frequency of the oscillation.
+    pub angular_frequency: T,
+    /// The phase at time zero.
+    pub phase: T,
+}
+
+macro_rules! impl_sinusoid {
+    ($($float:ty),*) => {
+        $(
+            impl Schedule<$float> for Sinusoid<$float> {
+                fn factor(&self, time: &$float) -> $float {
+                    self.offset + self.amplitude * (self.angular_frequency * time + self.phase).sin()
+                }
+            }
+        )*
+    };
+}
+
+impl_sinusoid!(f32, f64);
+
+/// A schedule that linearly interpolates between `(time, factor)` knots
+/// and stays constant outside of them.
+#[derive(Clone, Debug)]
+pub struct Piecewise<T> {
+    knots: Box<[(T, T)]>,
+}
+
+impl<T: PartialOrd> Piecewise<T> {
+    /// Constructs a new `Piecewise` schedule.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `knots` is empty or the times of the knots are not strictly increasing.
+    pub fn new(knots: impl Into<Box<[(T, T)]>>) -> Self {
+        let knots = knots.into();
+        assert!(
+            !knots.is_empty(),
+            "a piecewise schedule requires at least one knot"
+        );
+        assert!(
+            knots.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "the times of the knots must be strictly increasing"
+        );
+        Self { knots }
+    }
+}
+
+impl<T> Schedule<T> for Piecewise<T>
+where
+    T: Clone + PartialOrd + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    fn factor(&self, time: &T) -> T {
+        let index = self
+            .knots
+            .partition_point(|(knot_time, _)| knot_time <= time);
+        match (index.checked_sub(1), self.knots.get(index)) {
+            (None, _) => self.knots[0].1.clone(),
+            (Some(before), None) => self.knots[before].1.clone(),
+            (Some(before), Some((end_time, end_factor))) => Ramp {
+                start_time: self.knots[before].0.clone(),
+                end_time: end_time.clone(),
+                start_factor: self.knots[before].1.clone(),
+                end_factor: end_factor.clone(),
+            }
+            .factor(time),
+        }
+    }
+}
+
+/// A wrapper that scales the energy and forces of a physical
+/// potential by a factor that follows a [`Schedule`].
+///
+/// Changing the factor at fixed positions performs work on the system,
+/// which is accumulated and made available through [`WorkSource`].
+pub struct TimeDependentPotential<T, V, P, S> {
+    potential: P,
+    schedule: S,
+    factor: T,
+    unscaled_potential_energy: Option<T>,
+    work: Option<T>,
+    scratch: Vec<V>,
+}
+
+impl<T, V, P, S> TimeDependentPotential<T, V, P, S>
+where
+    S: Schedule<T>,
+{
+    /// Wraps `potential` such that it is scaled according to `schedule`,
+    /// starting at `initial_time`.
+    pub fn new(potential: P, schedule: S, initial_time: T) -> Self {
+        Self {
+            factor: schedule.factor(&initial_time),
+            potential,
+            schedule,
+            unscaled_potential_energy: None,
+            work: None,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Returns the factor currently applied to the potential.
+    pub fn factor(&self) -> &T {
+        &self.factor
+    }
+
+    /// Returns a reference to the wrapped potential.
+    pub fn potential(&self) -> &P {
+        &self.potential
+    }
+
+    /// Returns a mutable reference to the wrapped potential.
+    pub fn potential_mut(&mut self) -> &mut P {
+        &mut self.potential
+    }
+}
+
+impl<T, V: Clone, P, S> TimeDependentPotential<T, V, P, S> {
+    /// Resizes `scratch` to hold the forces of the group, which the wrapped potential
+    /// then overwrites, so elements are only copied when the group grows.
+    fn fit_scratch<'a>(scratch: &'a mut Vec<V>, group_forces: &[V]) -> &'a mut [V] {
+        scratch.truncate(group_forces.len());
+        let kept = scratch.len();
+        scratch.extend_from_slice(&group_forces[kept..]);
+        scratch
+    }
+}
+
+impl<T, V, P, S> TimeDependent<T> for TimeDependentPotential<T, V, P, S>
+where
+    T: Clone + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+    S: Schedule<T>,
+{
+    fn set_time(&mut self, _step: usize, time: T) {
+        let factor = self.schedule.factor(&time);
+        if let Some(unscaled_potential_energy) = self.unscaled_potential_energy.take() {
+            let work = (factor.clone() - self.factor.clone()) * unscaled_potential_energy;
+            self.work = Some(match self.work.take() {
+                Some(accum_work) => accum_work + work,
+                None => work,
+            });
+        }
+        self.factor = factor;
+    }
+}
+
+impl<T, V, P, S> WorkSource<T> for TimeDependentPotential<T, V, P, S> {
+    fn take_work(&mut self) -> Option<T> {
+        self.work.take()
+    }
+}
+
+impl<T, V, P, S> PhysicalPotential<T, V> for TimeDependentPotential<T, V, P, S>
+where
+    T: Clone + Mul<Output = T>,
+    V: Clone + AddAssign + Mul<T, Output = V> + MulAssign<T>,
+    P: PhysicalPotential<T, V>,
+{
+    type Error = P::Error;
+
+    fn calculate_potential_set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let potential_energy = self
+            .potential
+            .calculate_potential_set_forces(positions, group_forces)?;
+        for force in group_forces {
+            *force *= self.factor.clone();
+        }
+        self.unscaled_potential_energy = Some(potential_energy.clone());
+        Ok(potential_energy * self.factor.clone())
+    }
+
+    fn calculate_potential_add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let scratch = Self::fit_scratch(&mut self.scratch, group_forces);
+        let potential_energy = self
+            .potential
+            .calculate_potential_set_forces(positions, scratch)?;
+        for (force, scratch_force) in group_forces.iter_mut().zip(self.scratch.iter()) {
+            *force += scratch_force.clone() * self.factor.clone();
+        }
+        self.unscaled_potential_energy = Some(potential_energy.clone());
+        Ok(potential_energy * self.factor.clone())
+    }
+
+    fn calculate_potential(&mut self, positions: &GroupInTypeInImage<V>) -> Result<T, Self::Error> {
+        #[allow(deprecated)]
+        let potential_energy = self.potential.calculate_potential(positions)?;
+        self.unscaled_potential_energy = Some(potential_energy.clone());
+        Ok(potential_energy * self.factor.clone())
+    }
+
+    fn set_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        #[allow(deprecated)]
+        self.potential.set_forces(positions, group_forces)?;
+        for force in group_forces {
+            *force *= self.factor.clone();
+        }
+        Ok(())
+    }
+
+    fn add_forces(
+        &mut self,
+        positions: &GroupInTypeInImage<V>,
+        group_forces: &mut [V],
+    ) -> Result<(), Self::Error> {
+        let scratch = Self::fit_scratch(&mut self.scratch, group_forces);
+        #[allow(deprecated)]
+        self.potential.set_forces(positions, scratch)?;
+        for (force, scratch_force) in group_forces.iter_mut().zip(self.scratch.iter()) {
+            *force += scratch_force.clone() * self.factor.clone();
+        }
+        Ok(())
+    }
+}
@@ -3,6 +3,8 @@
 use crate::{
     core::{
         AtomGroupRwLock, AtomTypeReaderLock, MapInWhole, MapOutsideWhole,
+        error::PoisonedError,
+        extended::{ExtendedBlock, ExtendedCoordinate, ExtendedVariables},
         stat::{Bosonic, Distinguishable, Stat},
     },
     potential::{exchange::ExchangePotential, physical::PhysicalPotential},
@@ -49,3 +51,46 @@ where
         exchange_forces: &mut GroupRwLockInTypeInImageInSystem<V>,
     ) -> Result<(T, T, T), Self::Error>;
 }
+
+/// A trait for a propagator which also propagates extended variables,
+/// such as the coordinates of a barostat or a thermostat chain.
+///
+/// The variables live in a shared [`ExtendedVariables`] registry rather than in the propagator,
+/// so that they are visible to estimators and included in checkpoints.
+pub trait ExtendedPropagator<T, V, Phys, Dist, Boson, Therm>:
+    Propagator<T, V, Phys, Dist, Boson, Therm>
+where
+    Phys: PhysicalPotential<T, V> + ?Sized,
+    Dist: ExchangePotential<T, V> + Distinguishable + ?Sized,
+    Boson: ExchangePotential<T, V> + Bosonic + ?Sized,
+    Therm: Thermostat<T, V> + ?Sized,
+{
+    /// Returns the block of the registry holding the variables of this propagator.
+    fn extended_block(&self) -> ExtendedBlock;
+
+    /// Propagates the extended variables by a single step.
+    ///
+    /// Returns the contribution of the variables to the conserved energy
+    /// beyond their kinetic energy, e.g. `P V` for a barostat.
+    fn propagate_extended(
+        &mut self,
+        step: usize,
+        coordinates: &mut [ExtendedCoordinate<T>],
+    ) -> Result<T, Self::Error>;
+
+    /// Propagates the variables of [`ExtendedPropagator::extended_block`] in `variables`
+    /// while holding the write lock to them.
+    fn propagate_in(
+        &mut self,
+        step: usize,
+        variables: &ExtendedVariables<T>,
+    ) -> Result<T, Self::Error>
+    where
+        Self::Error: From<PoisonedError>,
+    {
+        let block = self.extended_block();
+        variables.update(block, |coordinates| {
+            self.propagate_extended(step, coordinates)
+        })?
+    }
+}
@@ -83,7 +83,7 @@ impl<'a, T> Strides<'a, T> {
 
     pub fn from_slice(mut s: &'a [T], stride: usize) -> Self {
         let stride = NonZero::new(stride).expect("stride must be non-zero");
-        let start = NonNull::from(&*s).to_raw_parts().0.cast();
+        let start = NonNull::from(&*s).cast();
         let n = s.len() / stride;
         if n > 0 {
             // SAFETY: Checked above that `n * stride <= s.len()`.
@@ -116,8 +116,6 @@ impl<'a, T> Iterator for Strides<'a, T> {
         if self.start < self.end {
             let chunks = unsafe {
                 NonNull::from(self.remainder)
-                    .to_raw_parts()
-                    .0
                     .cast()
                     // SAFETY: - By construction, `end` preceeds `remainder`.
                     //         - By construction, both pointers are derived from the same allocation.
@@ -150,8 +148,6 @@ impl<'a, T> DoubleEndedIterator for Strides<'a, T> {
             }
             let chunks = unsafe {
                 NonNull::from(self.remainder)
-                    .to_raw_parts()
-                    .0
                     .cast()
                     // SAFETY: - By construction, `end` preceeds `remainder`.
                     //         - By construction, both pointers are derived from the same allocation.
@@ -77,7 +77,7 @@ impl<'a, T> StridesMut<'a, T> {
 
     pub fn from_slice(mut s: &'a mut [T], stride: usize) -> Self {
         let stride = NonZero::new(stride).expect("stride must be non-zero");
-        let start = NonNull::from(&*s).to_raw_parts().0.cast();
+        let start = NonNull::from(&*s).cast();
         let n = s.len() / stride;
         if n > 0 {
             // SAFETY: Checked above that `n * stride <= s.len()`.
@@ -110,8 +110,6 @@ impl<'a, T> Iterator for StridesMut<'a, T> {
         if self.start < self.end {
             let chunks = unsafe {
                 NonNull::from(self.remainder)
-                    .to_raw_parts()
-                    .0
                     .cast()
                     // SAFETY: - By construction, `end` preceeds `remainder`.
                     //         - By construction, both pointers are derived from the same allocation.
@@ -144,8 +142,6 @@ impl<'a, T> DoubleEndedIterator for StridesMut<'a, T> {
             }
             let chunks = unsafe {
                 NonNull::from(self.remainder)
-                    .to_raw_parts()
-                    .0
                     .cast()
                     // SAFETY: - By construction, `end` preceeds `remainder`.
                     //         - By construction, both pointers are derived from the same allocation.
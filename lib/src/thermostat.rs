@@ -3,8 +3,17 @@
 use crate::core::GroupInTypeInImageInSystem;
 use macros::heavy_computation;
 
+mod annealing;
+pub use annealing::{Annealing, TemperatureDependent, TemperatureSchedule};
 mod atom_decoupled;
 pub use atom_decoupled::AtomDecoupledThermostat;
+mod frozen;
+mod heat;
+pub use heat::{HeatSource, HeatTracking};
+mod normal_modes;
+pub use normal_modes::{
+    EigenvalueCache, NormalModeThermostatAdapter, NormalModeThermostatError, resample_mode_momenta,
+};
 
 /// A trait for thermostats.
 ///
@@ -28,3 +37,15 @@ pub trait Thermostat<T, V> {
         group_momenta: &mut [V],
     ) -> Result<T, Self::Error>;
 }
+
+/// A trait for thermostats which act on the kinetic energy of a whole group,
+/// and so depend on the number of its degrees of freedom.
+///
+/// The number is the whole group's unless the thermostat is wrapped in [`Frozen`],
+/// which leaves out the frozen atoms.
+///
+/// [`Frozen`]: crate::core::Frozen
+pub trait DegreesOfFreedomDependent {
+    /// Informs `self` that its group has `degrees_of_freedom` degrees of freedom.
+    fn set_degrees_of_freedom(&mut self, degrees_of_freedom: usize);
+}
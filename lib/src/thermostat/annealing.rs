@@ -0,0 +1,122 @@
+//! Control of the target temperature of the simulation over time.
+
+use crate::{
+    core::Decoupled,
+    potential::physical::{Piecewise, Ramp, Schedule},
+};
+
+/// A trait for components whose parameters depend on the temperature,
+/// e.g. thermostats and the springs of exchange potentials.
+pub trait TemperatureDependent<T> {
+    /// Informs `self` that the target temperature has changed to `temperature`.
+    ///
+    /// Called by the driver between two steps, so that every component
+    /// of every replica switches to the new temperature in the same step.
+    fn set_temperature(&mut self, temperature: &T);
+}
+
+impl<T, D: TemperatureDependent<T> + ?Sized> TemperatureDependent<T> for Decoupled<D> {
+    #[inline(always)]
+    fn set_temperature(&mut self, temperature: &T) {
+        self.0.set_temperature(temperature);
+    }
+}
+
+/// A temperature as a function of the simulation time for simulated annealing.
+///
+/// The temperature stays constant before and after the schedule.
+#[derive(Clone, Debug)]
+pub enum TemperatureSchedule<T> {
+    /// Changes linearly between the temperatures at the ends of the ramp.
+    Linear(Ramp<T>),
+    /// Changes geometrically between the temperatures at the ends of the ramp,
+    /// i.e. by the same ratio in every interval of the same length.
+    Exponential(Ramp<T>),
+    /// Interpolates linearly between `(time, temperature)` knots.
+    Piecewise(Piecewise<T>),
+}
+
+macro_rules! impl_temperature_schedule {
+    ($($float:ty),*) => {
+        $(
+            impl Schedule<$float> for TemperatureSchedule<$float> {
+                fn factor(&self, time: &$float) -> $float {
+                    match self {
+                        Self::Linear(ramp) => ramp.factor(time),
+                        Self::Exponential(ramp) => {
+                            if *time <= ramp.start_time {
+                                ramp.start_factor
+                            } else if *time >= ramp.end_time {
+                                ramp.end_factor
+                            } else {
+                                let progress =
+                                    (time - ramp.start_time) / (ramp.end_time - ramp.start_time);
+                                ramp.start_factor
+                                    * (ramp.end_factor / ramp.start_factor).powf(progress)
+                            }
+                        }
+                        Self::Piecewise(piecewise) => piecewise.factor(time),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_temperature_schedule!(f32, f64);
+
+/// A controller that drives the target temperature along a [`Schedule`]
+/// and informs the temperature-dependent components whenever it changes.
+#[derive(Clone, Debug)]
+pub struct Annealing<T, S> {
+    schedule: S,
+    temperature: T,
+}
+
+impl<T, S> Annealing<T, S>
+where
+    T: PartialEq,
+    S: Schedule<T>,
+{
+    /// Constructs a controller following `schedule` from `initial_time`.
+    ///
+    /// The components are expected to be constructed at the initial temperature.
+    pub fn new(schedule: S, initial_time: T) -> Self {
+        Self {
+            temperature: schedule.factor(&initial_time),
+            schedule,
+        }
+    }
+
+    /// Returns the current target temperature.
+    pub fn temperature(&self) -> &T {
+        &self.temperature
+    }
+
+    /// Moves the schedule to `time`.
+    ///
+    /// Returns the new temperature if it has changed.
+    pub fn advance(&mut self, time: T) -> Option<&T> {
+        let temperature = self.schedule.factor(&time);
+        if temperature == self.temperature {
+            return None;
+        }
+        self.temperature = temperature;
+        Some(&self.temperature)
+    }
+
+    /// Moves the schedule to `time` and, if the temperature has changed, sets it on
+    /// every one of `components`, which should hold the thermostat along with every
+    /// exchange potential and cache of eigenvalues that depends on the temperature.
+    ///
+    /// Returns whether the temperature has changed.
+    pub fn update(&mut self, time: T, components: &mut [&mut dyn TemperatureDependent<T>]) -> bool {
+        let Some(temperature) = self.advance(time) else {
+            return false;
+        };
+        for component in components {
+            component.set_temperature(temperature);
+        }
+        true
+    }
+}
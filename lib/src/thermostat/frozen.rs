@@ -0,0 +1,69 @@
+use super::{AtomDecoupledThermostat, DegreesOfFreedomDependent, Thermostat};
+use crate::core::{Frozen, GroupInTypeInImageInSystem};
+use std::ops::Add;
+
+impl<U: DegreesOfFreedomDependent + ?Sized> Frozen<U> {
+    /// Informs the wrapped thermostat of the degrees of freedom of the atoms
+    /// which are not frozen, where each atom has `dimension` degrees of freedom.
+    pub fn update_degrees_of_freedom(&mut self, dimension: usize) {
+        self.inner
+            .set_degrees_of_freedom(self.atoms.degrees_of_freedom(dimension));
+    }
+}
+
+/// The momenta of the frozen atoms are zeroed before and after the wrapped thermostat
+/// thermalizes the group, so they neither enter its kinetic energy nor are changed by it.
+impl<T, V, U> Thermostat<T, V> for Frozen<U>
+where
+    V: Default,
+    U: Thermostat<T, V> + ?Sized,
+{
+    type Error = U::Error;
+
+    fn thermalize(
+        &mut self,
+        positions: &GroupInTypeInImageInSystem<V>,
+        physical_forces: &GroupInTypeInImageInSystem<V>,
+        exchange_forces: &GroupInTypeInImageInSystem<V>,
+        group_momenta: &mut [V],
+    ) -> Result<T, Self::Error> {
+        self.zero_frozen(group_momenta);
+        let heat =
+            self.inner
+                .thermalize(positions, physical_forces, exchange_forces, group_momenta)?;
+        self.zero_frozen(group_momenta);
+        Ok(heat)
+    }
+}
+
+impl<T, V, U> AtomDecoupledThermostat<T, V> for Frozen<U>
+where
+    T: Default + Add<Output = T>,
+    V: Default,
+    U: AtomDecoupledThermostat<T, V> + ?Sized,
+{
+    type ErrorAtom = U::ErrorAtom;
+    type ErrorSystem = U::ErrorSystem;
+
+    #[inline(always)]
+    fn thermalize(
+        &mut self,
+        atom_index: usize,
+        position: &V,
+        physical_force: &V,
+        exchange_force: &V,
+        momentum: &mut V,
+    ) -> Result<T, Self::ErrorAtom> {
+        if self.atoms.is_frozen(atom_index) {
+            *momentum = V::default();
+            return Ok(T::default());
+        }
+        self.inner.thermalize(
+            atom_index,
+            position,
+            physical_force,
+            exchange_force,
+            momentum,
+        )
+    }
+}
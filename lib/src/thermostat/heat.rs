@@ -0,0 +1,71 @@
+use super::{TemperatureDependent, Thermostat};
+use crate::core::GroupInTypeInImageInSystem;
+use std::ops::Add;
+
+/// A trait for objects that exchange heat with the system.
+pub trait HeatSource<T> {
+    /// Returns the heat transferred into the system since the
+    /// previous call, or `None` if no heat has been transferred.
+    fn take_heat(&mut self) -> Option<T>;
+}
+
+/// A wrapper for thermostats that accumulates the heat they transfer
+/// into the system, making it available through [`HeatSource`].
+pub struct HeatTracking<T, U: ?Sized> {
+    heat: Option<T>,
+    thermostat: U,
+}
+
+impl<T, U> HeatTracking<T, U> {
+    /// Wraps the provided thermostat with `HeatTracking`.
+    pub const fn new(thermostat: U) -> Self {
+        Self {
+            heat: None,
+            thermostat,
+        }
+    }
+}
+
+impl<T, U: ?Sized> HeatSource<T> for HeatTracking<T, U> {
+    fn take_heat(&mut self) -> Option<T> {
+        self.heat.take()
+    }
+}
+
+impl<T, V, U> Thermostat<T, V> for HeatTracking<T, U>
+where
+    T: Clone + Add<Output = T>,
+    U: Thermostat<T, V> + ?Sized,
+{
+    type Error = U::Error;
+
+    fn thermalize(
+        &mut self,
+        positions: &GroupInTypeInImageInSystem<V>,
+        physical_forces: &GroupInTypeInImageInSystem<V>,
+        exchange_forces: &GroupInTypeInImageInSystem<V>,
+        group_momenta: &mut [V],
+    ) -> Result<T, Self::Error> {
+        let heat = self.thermostat.thermalize(
+            positions,
+            physical_forces,
+            exchange_forces,
+            group_momenta,
+        )?;
+        self.heat = Some(match self.heat.take() {
+            Some(accum_heat) => accum_heat + heat.clone(),
+            None => heat.clone(),
+        });
+        Ok(heat)
+    }
+}
+
+impl<T, H, U> TemperatureDependent<T> for HeatTracking<H, U>
+where
+    U: TemperatureDependent<T> + ?Sized,
+{
+    #[inline(always)]
+    fn set_temperature(&mut self, temperature: &T) {
+        self.thermostat.set_temperature(temperature);
+    }
+}
@@ -0,0 +1,234 @@
+//! Utilities for thermalizing the system in the normal-mode representation.
+
+use super::{TemperatureDependent, Thermostat};
+use crate::core::{GroupInTypeInImageInSystem, resolution::BeadCountDependent};
+use crate::potential::exchange::quadratic::{Transform, TypeAcrossImages};
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// The eigenvalues of the modes of a group, kept between steps
+/// instead of being requested from the transformation every time.
+///
+/// The eigenvalues are the spring constants of the modes, which scale with
+/// the square of the temperature, so a change of temperature invalidates them.
+#[derive(Clone, Debug)]
+pub struct EigenvalueCache<T> {
+    eigenvalues: Vec<T>,
+    valid: bool,
+}
+
+impl<T> EigenvalueCache<T> {
+    /// Constructs an empty cache.
+    pub const fn new() -> Self {
+        Self {
+            eigenvalues: Vec::new(),
+            valid: false,
+        }
+    }
+
+    /// Returns the eigenvalues of the `modes` modes allocated to the group of `transform`,
+    /// requesting them from `transform` only if the cache is invalid.
+    pub fn get<V, Q>(&mut self, transform: &Q, modes: usize) -> Result<&[T], Q::Error>
+    where
+        T: Clone + Default,
+        Q: Transform<T, V> + ?Sized,
+    {
+        if !self.valid || self.eigenvalues.len() != modes {
+            self.eigenvalues.clear();
+            self.eigenvalues.resize(modes, T::default());
+            transform.eigenvalues(&mut self.eigenvalues)?;
+            self.valid = true;
+        }
+        Ok(&self.eigenvalues)
+    }
+
+    /// Returns whether the cached eigenvalues may be used.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Discards the cached eigenvalues.
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+}
+
+impl<T> Default for EigenvalueCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, U> TemperatureDependent<U> for EigenvalueCache<T> {
+    #[inline(always)]
+    fn set_temperature(&mut self, _temperature: &U) {
+        self.invalidate();
+    }
+}
+
+impl<T> BeadCountDependent for EigenvalueCache<T> {
+    #[inline(always)]
+    fn set_beads(&mut self, _beads: usize) {
+        self.invalidate();
+    }
+}
+
+/// Resamples the momenta of the modes allocated to this group.
+///
+/// The eigenvalues of `transform` are written into `eigenvalues`, after which
+/// `sample` is called for every mode with its index within the group and
+/// its eigenvalue, and is expected to return a momentum drawn from the
+/// Maxwell-Boltzmann distribution of that mode.
+///
+/// The resulting mode momenta are converted back to Cartesian momenta via
+/// [`Transform::inverse_transform`] once every group in every image has
+/// resampled its modes.
+pub fn resample_mode_momenta<T, V, Q, F>(
+    transform: &Q,
+    eigenvalues: &mut [T],
+    group_mode_momenta: &mut [V],
+    mut sample: F,
+) -> Result<(), Q::Error>
+where
+    T: Clone,
+    Q: Transform<T, V> + ?Sized,
+    F: FnMut(usize, T) -> V,
+{
+    transform.eigenvalues(eigenvalues)?;
+    for (index, (eigenvalue, momentum)) in eigenvalues
+        .iter()
+        .zip(group_mode_momenta.iter_mut())
+        .enumerate()
+    {
+        *momentum = sample(index, eigenvalue.clone());
+    }
+    Ok(())
+}
+
+/// An error raised by a [`NormalModeThermostatAdapter`].
+#[derive(Debug)]
+pub enum NormalModeThermostatError<Q, H> {
+    /// The transformation between the momenta and the modes failed.
+    Transform(Q),
+    /// The thermostat failed.
+    Thermostat(H),
+}
+
+impl<Q: Display, H: Display> Display for NormalModeThermostatError<Q, H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Transform(err) => write!(f, "the normal-mode transformation failed: {}", err),
+            Self::Thermostat(err) => write!(f, "the mode thermostat failed: {}", err),
+        }
+    }
+}
+
+impl<Q: Error + 'static, H: Error + 'static> Error for NormalModeThermostatError<Q, H> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Transform(err) => Some(err),
+            Self::Thermostat(err) => Some(err),
+        }
+    }
+}
+
+/// An adapter applying any [`Thermostat`] to the momenta of the normal modes of a group
+/// instead of its Cartesian momenta, which makes the thermostat mode-resolved.
+///
+/// A mode mixes the momenta of the group in every image, so the adapter works in two phases
+/// separated by a barrier: [`NormalModeThermostatAdapter::thermalize_modes`] transforms
+/// the momenta of every image into the modes allocated to the group and thermalizes them,
+/// and once every group of every image has done so,
+/// [`NormalModeThermostatAdapter::restore_momenta`] transforms all modes back
+/// into the Cartesian momenta of the group.
+///
+/// The thermostat receives the mode momenta in place of the Cartesian ones, while the positions
+/// and the forces it is given stay Cartesian. The change in the internal energy it returns
+/// is only the change in the Cartesian kinetic energy if the transformation is orthogonal.
+pub struct NormalModeThermostatAdapter<Q, H> {
+    transform: Q,
+    thermostat: H,
+}
+
+impl<Q, H> NormalModeThermostatAdapter<Q, H> {
+    /// Wraps `thermostat` to thermalize the modes of `transform`.
+    pub fn new(transform: Q, thermostat: H) -> Self {
+        Self {
+            transform,
+            thermostat,
+        }
+    }
+
+    /// Returns a reference to the transformation.
+    pub fn transform(&self) -> &Q {
+        &self.transform
+    }
+
+    /// Returns a reference to the wrapped thermostat.
+    pub fn thermostat(&self) -> &H {
+        &self.thermostat
+    }
+
+    /// Returns a mutable reference to the wrapped thermostat.
+    pub fn thermostat_mut(&mut self) -> &mut H {
+        &mut self.thermostat
+    }
+
+    /// Transforms `momenta`, the momenta of the type of the group in every image,
+    /// into `group_mode_momenta`, the momenta of the modes allocated to the group,
+    /// and thermalizes them.
+    ///
+    /// Returns the change in the internal energy reported by the thermostat.
+    pub fn thermalize_modes<T, V>(
+        &mut self,
+        momenta: TypeAcrossImages<V>,
+        positions: &GroupInTypeInImageInSystem<V>,
+        physical_forces: &GroupInTypeInImageInSystem<V>,
+        exchange_forces: &GroupInTypeInImageInSystem<V>,
+        group_mode_momenta: &mut [V],
+    ) -> Result<T, NormalModeThermostatError<Q::Error, H::Error>>
+    where
+        Q: Transform<T, V>,
+        H: Thermostat<T, V>,
+    {
+        self.transform
+            .transform(momenta, group_mode_momenta)
+            .map_err(NormalModeThermostatError::Transform)?;
+        self.thermostat
+            .thermalize(
+                positions,
+                physical_forces,
+                exchange_forces,
+                group_mode_momenta,
+            )
+            .map_err(NormalModeThermostatError::Thermostat)
+    }
+
+    /// Transforms `mode_momenta`, the thermalized modes of the type in every image,
+    /// back into `group_momenta`, the Cartesian momenta of the group.
+    ///
+    /// Must be called only once every group of every image
+    /// has called [`NormalModeThermostatAdapter::thermalize_modes`].
+    pub fn restore_momenta<T, V>(
+        &mut self,
+        mode_momenta: TypeAcrossImages<V>,
+        group_momenta: &mut [V],
+    ) -> Result<(), Q::Error>
+    where
+        Q: Transform<T, V>,
+    {
+        self.transform
+            .inverse_transform(mode_momenta, group_momenta)
+    }
+}
+
+impl<Q: BeadCountDependent, H: BeadCountDependent> BeadCountDependent
+    for NormalModeThermostatAdapter<Q, H>
+{
+    fn set_beads(&mut self, beads: usize) {
+        self.transform.set_beads(beads);
+        self.thermostat.set_beads(beads);
+    }
+}